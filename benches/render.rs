@@ -0,0 +1,81 @@
+use std::path::PathBuf;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use wiki_tui::{
+    bench::{decode_cached, encode_cached, parse_html, transform_document, BENCH_WIDTHS},
+    renderer::default_renderer::render_document,
+};
+
+fn fixtures() -> Vec<(String, String)> {
+    let directory = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let mut fixtures: Vec<(String, String)> = std::fs::read_dir(&directory)
+        .expect("unable to read the fixtures directory")
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().map(|ext| ext == "html").unwrap_or(false))
+        .map(|path| {
+            let name = path.file_stem().unwrap().to_string_lossy().to_string();
+            let html = std::fs::read_to_string(&path).expect("unable to read the fixture");
+            (name, html)
+        })
+        .collect();
+    fixtures.sort();
+    fixtures
+}
+
+fn bench_parser(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for (name, html) in fixtures() {
+        group.throughput(Throughput::Bytes(html.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(&name), &html, |b, html| {
+            b.iter(|| parse_html(html))
+        });
+    }
+    group.finish();
+}
+
+fn bench_transforms(c: &mut Criterion) {
+    let mut group = c.benchmark_group("transform");
+    for (name, html) in fixtures() {
+        let document = parse_html(&html);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(&name),
+            &document,
+            |b, document| b.iter(|| transform_document(document.clone())),
+        );
+    }
+    group.finish();
+}
+
+fn bench_cache(c: &mut Criterion) {
+    let mut group = c.benchmark_group("load cached");
+    for (name, html) in fixtures() {
+        let bytes = encode_cached(transform_document(parse_html(&html)));
+        group.throughput(Throughput::Bytes(bytes.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(&name), &bytes, |b, bytes| {
+            b.iter(|| decode_cached(bytes))
+        });
+    }
+    group.finish();
+}
+
+fn bench_renderer(c: &mut Criterion) {
+    let mut group = c.benchmark_group("render");
+    for (name, html) in fixtures() {
+        let document = transform_document(parse_html(&html));
+        for width in BENCH_WIDTHS {
+            group.bench_with_input(BenchmarkId::new(&name, width), &document, |b, document| {
+                b.iter(|| render_document(document, width))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_parser,
+    bench_transforms,
+    bench_cache,
+    bench_renderer
+);
+criterion_main!(benches);
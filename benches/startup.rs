@@ -0,0 +1,32 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use wiki_tui::{app::AppComponent, config::Config, i18n::tr};
+
+/// A config setting a value in most tables, like a config file of a regular user would
+const CONFIG: &str = r#"
+[api]
+language = "de"
+
+[behavior]
+navigation = "select"
+
+[parser]
+transforms = ["merge_whitespace", "strip_empty_sections"]
+
+[ui]
+show_contents = false
+"#;
+
+fn bench_config(c: &mut Criterion) {
+    c.bench_function("startup/config", |b| {
+        b.iter(|| toml::from_str::<Config>(CONFIG).expect("the config is valid"))
+    });
+}
+
+fn bench_components(c: &mut Criterion) {
+    // the strings are loaded once per process, like the config
+    tr("status.help");
+    c.bench_function("startup/components", |b| b.iter(AppComponent::default));
+}
+
+criterion_group!(benches, bench_config, bench_components);
+criterion_main!(benches);
@@ -1,13 +1,60 @@
-use std::fmt::Debug;
+use std::{collections::HashMap, fmt::Debug, path::PathBuf};
 
 use tokio::sync::mpsc;
-use wiki_api::{page::Page, search::Search};
+use wiki_api::{
+    assessment::Quality,
+    backend::Summary,
+    error::ApiError,
+    feed::OnThisDay,
+    geosearch::{Coordinates, GeoSearchResult},
+    languages::Language,
+    media::MediaInfo,
+    page::{Page, Section},
+    pageviews::PageViews,
+    portals::PortalSection,
+    search::Search,
+};
+
+use crate::{
+    citation::CitationFormat,
+    components::{context_menu::ContextMenu, page::Renderer},
+    config::ThemePreset,
+    export::DumpFormat,
+    flashcards::FlashcardTarget,
+    renderer::epub::EpubSource,
+    yank_ring::Yank,
+};
+
+/// What happens to the running background tasks when quitting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitMode {
+    /// The interface is closed right away, but the tasks are finished before exiting
+    WaitForTasks,
+    CancelTasks,
+}
 
-use crate::components::page::Renderer;
+/// A request to the wiki that failed, so it can be sent again
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FailedRequest {
+    /// Loading the page with the title
+    Page(String),
+    /// Searching for the query
+    Search(String),
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Action {
+    /// Quits, unless background tasks or an annotation would be lost. Then it's asked first
     Quit,
+    /// Quits without asking
+    Exit(ExitMode),
+    /// Closes the dialog asking whether to quit, without quitting
+    CancelQuit,
+    /// Explains why the request failed with an error of the API and what can be done about it
+    ShowApiError(FailedRequest, ApiError),
+    CloseApiError,
+    /// Closes the dialog of the first run, switching to the entered language when there is one
+    FinishSetup(Option<Language>),
     Resume,
     Suspend,
     RenderTick,
@@ -19,8 +66,24 @@ pub enum Action {
 
     SwitchContextSearch,
     SwitchContextPage,
+    SwitchContextFeed,
+    SwitchContextNearby,
+    SwitchContextLinkGraph,
+    SwitchContextNotes,
+    SwitchContextHistory,
+    SwitchContextDisambiguation,
+    SwitchContextFetchQueue,
+    SwitchContextLanguages,
+    SwitchContextPortals,
+    SwitchContextBookmarks,
+    SwitchContextCache,
+    SwitchContextReadingQueue,
     SwitchPreviousContext,
 
+    // Pane sizes
+    ResizeLogger(i16),
+    ResizeContents(i16),
+
     // Scrolling
     ScrollUp(u16),
     ScrollDown(u16),
@@ -37,6 +100,33 @@ pub enum Action {
     EnterInsert,
     EnterNormal,
     EnterProcessing,
+    /// Closes the list of invalid settings shown at startup
+    DismissConfigProblems,
+    /// Shows or hides the links and passages yanked in this session
+    ToggleYankRing,
+    /// Shows or hides the searchable list of the available commands
+    ToggleCommandPalette,
+    /// Shows a menu of the actions available for the selected entry
+    OpenContextMenu(ContextMenu),
+    CloseContextMenu,
+    /// Shows a menu of the built-in themes
+    ShowThemeMenu,
+    /// Shows pages in the theme from now on and saves it in the config file
+    SetTheme(ThemePreset),
+    /// Reverts the latest bookmark removal, annotation deletion, reading queue removal or cache
+    /// purge
+    Undo,
+    /// Shows or hides the list of the saved workspaces
+    ToggleWorkspaces,
+    /// Saves the open pages as a workspace with the name, replacing the one with the same name
+    SaveWorkspace(String),
+    /// Opens the pages of the workspace with the name instead of the open pages
+    LoadWorkspace(String),
+    /// Deletes the workspace with the name, which can be undone
+    DeleteWorkspace(String),
+
+    /// Searches for the text of the clipboard, or opens the article when it's a link to one
+    SearchClipboard,
 
     // Search Bar
     EnterSearchBar,
@@ -46,18 +136,87 @@ pub enum Action {
 
     // Page loading
     LoadPage(String),
+    /// Loads the page below the shown one, like a background tab, without switching to it
+    LoadPageInBackground(String),
+    /// Loads the page into an overlay on top of the shown page, see [`PageViewerAction::DisplayPeek`]
+    LoadPeek(String),
+    /// Loads only the section of the page, shown on its own
+    LoadSection(String, Section),
+    /// Loads the redirect page with the title itself, without following it
+    LoadRedirect(String),
+    /// Checks whether the shown page was edited since it was loaded
+    CheckPageUpdate,
+    /// Loads the latest revision of the page into the pages showing it, see
+    /// [`PageViewerAction::ReloadPage`]
+    ReloadPage(String),
+    ToggleOfflineMode,
+    /// Adds the page to the fetch queue
+    QueueFetch(String),
+    /// Compiles the articles into a single EPUB in the export directory
+    ExportEpub(EpubSource),
+    /// Exports the latest requests to the wiki as an HTTP Archive
+    ExportHar,
+    /// Exports the annotations of all pages as flashcards
+    ExportFlashcards(FlashcardTarget),
+    /// Saves a bug report of the session, it's also handed to the `text_yanked` hook to copy it
+    /// when `true`
+    BugReport(bool),
+    /// Fetches the pending pages of the fetch queue, even when offline
+    FetchQueued,
+    /// Searches and loads pages in the language from now on
+    SwitchLanguage(Language),
+    /// Opens the url in the web browser
+    OpenInBrowser(String),
+    /// Bookmarks the page without reading it
+    AddBookmark(String, Language),
+    /// Queues the page to be read after the other queued pages
+    QueueReading(String, Language),
+    /// Opens the next page of the reading queue and removes it from the queue
+    ReadNext,
+    /// Runs the hook of the yank and remembers it in the yank ring
+    Yank(Yank),
 
     Search(SearchAction),
     Page(PageAction),
     PageViewer(PageViewerAction),
+    Feed(FeedAction),
+    Nearby(NearbyAction),
+    LinkGraph(LinkGraphAction),
+    Notes(NotesAction),
+    History(HistoryAction),
+    Disambiguation(DisambiguationAction),
+    FetchQueue(FetchQueueAction),
+    Languages(LanguagesAction),
+    Portals(PortalsAction),
+    YankRing(YankRingAction),
+    Bookmarks(BookmarksAction),
+    ReadingQueue(ReadingQueueAction),
+    Cache(CacheAction),
+    Workspaces(WorkspacesAction),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SearchAction {
     StartSearch(String),
     FinshSearch(Search),
+    FinishQualities(HashMap<String, Quality>),
+    FinishPageViews(HashMap<String, PageViews>),
+    FailSearch(String),
+    AcceptSuggestion,
+    EditQuery,
+    SubmitQuery,
+    CancelQueryEdit,
     ClearSearchResults,
     OpenSearchResult,
+    QueueSearchResult,
+    /// Shows the actions available for the selected result
+    ShowResultMenu,
+    /// Collapses or expands the group of the selected result
+    ToggleGroup,
+    /// Fetches the summary of the result with the title
+    PreviewResult(String),
+    FinishPreview(Summary),
+    ClosePreview,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -72,12 +231,340 @@ pub enum PageAction {
 
     SelectPrevLink,
     SelectNextLink,
+    JumpToReference(usize),
+    QueueSelectedLink,
+    /// Peeks at the page the selected link points to, without leaving the page
+    PeekSelectedLink,
+    /// Loads the newer revision of the page that was announced
+    ReloadLatestRevision,
+    DismissUpdateNotice,
+    YankSelectedLink,
+
+    ScrollLeft,
+    ScrollRight,
+
+    /// Narrows the text of the page
+    ZoomIn,
+    /// Widens the text of the page again
+    ZoomOut,
+    ResetZoom,
+
+    ToggleSection,
+    ToggleAllSections,
+    /// Collapses or expands the first collapsible box shown, like a navbox
+    ToggleCollapsible,
+    ToggleTaxobox,
+
+    ToggleContents,
+    FilterContents,
+    JumpToContentsMatch,
+    CloseContentsFilter,
+    /// Opens the section that matches the contents filter best on its own
+    OpenContentsMatchSection,
+
+    /// Starts searching for text in the page, or edits the query of the search
+    FindInPage,
+    /// Scrolls to the first match after the top of the page and stops editing the query
+    SubmitFind,
+    CloseFind,
+    /// Switches between finding matches in the whole page and in the section at the top of the
+    /// page
+    ToggleFindScope,
+    NextMatch,
+    PrevMatch,
+
+    ToggleSpeech,
+    StopSpeech,
+    SpeakNextSection,
+    /// The sentence a speaker session is reading
+    SpeechProgress(u64, usize),
+    SpeechFinished(u64),
+
+    ToggleHighlightMode,
+    SelectNextPassage,
+    SelectPrevPassage,
+    ExtendPassage,
+    EditNote,
+    SaveAnnotation,
+    CancelNote,
+
+    ExportMarkdown,
+    /// Exports the page as plain text, keeping the targets of its links
+    ExportText,
+    /// Exports the page as a standalone HTML file
+    ExportHtml,
+    /// Exports the page as an EPUB with a single chapter
+    ExportEpub,
+    /// Exports the page in the format to the path, sent through the remote socket
+    ExportTo(DumpFormat, PathBuf),
+    /// Shows a menu to cite the page in one of the citation formats
+    ShowCiteMenu,
+    /// Yanks a citation of the page, which the `text_yanked` hook can copy to the clipboard
+    Cite(CitationFormat),
+    /// Adds a citation of the page to the configured bibliography
+    AddToBibliography,
+    /// Bookmarks the page, or removes its bookmark
+    ToggleBookmark,
+    /// Marks the text at the top of the viewport as the place where the reader stopped
+    SetScrollMark,
+    /// Scrolls back to the place marked as where the reader stopped
+    JumpToScrollMark,
+    /// Hides the notice of the title the page was redirected from
+    DismissRedirectNotice,
+    /// Opens the redirect the page was reached through, without following it
+    OpenRedirectSource,
+    /// Yanks the selected passages while highlighting
+    YankPassage,
+
+    /// Selects the cells of the table in view one by one, or stops selecting them
+    ToggleTableMode,
+    SelectCellLeft,
+    SelectCellRight,
+    SelectCellAbove,
+    SelectCellBelow,
+
+    ExternalLink(ExternalLinkAction),
+    Media(MediaAction),
+    ToggleRenderStats,
+
+    /// Opens a menu with actions for the page the selected link points to
+    ShowLinkMenu,
+    /// Fetches the summary of the linked page and shows it over the page
+    PreviewLink(String),
+    FinishPreview(Summary),
+    ClosePreview,
+    /// Shows the leading sentences of the section at the top of the page and of each of its
+    /// subsections
+    OutlineSection,
+    CloseOutline,
+    /// Scrolls the selected link to a position in the viewport, like `zt`, `zz` and `zb` in vim
+    AlignSelection(ViewportPosition),
+
+    /// Switches between scrolling the page and reading it page by page
+    TogglePagination,
+    /// Turns to the next page, or scrolls down a screen when the page isn't paginated
+    NextPage,
+    /// Turns to the previous page, or scrolls up a screen when the page isn't paginated
+    PrevPage,
+}
+
+/// A line of the viewport a line of the page can be scrolled to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViewportPosition {
+    Top,
+    Center,
+    Bottom,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExternalLinkAction {
+    /// Shows the dialog for the url and checks whether it can still be reached
+    Show(String),
+    Close,
+
+    SelectPrevOption,
+    SelectNextOption,
+    OpenSelected,
+
+    OpenLink,
+    /// Looks up the closest snapshot of the link in the Wayback Machine and opens it
+    OpenSnapshot,
+
+    /// Result of checking whether the url can be reached
+    FinishCheck(String, Result<(), String>),
+    /// Url of the closest snapshot of the link, `None` when it was never archived
+    FinishSnapshotLookup(String, Result<Option<String>, String>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MediaAction {
+    /// Shows the dialog for the file description page with the title and fetches its description
+    Show(String),
+    Close,
+
+    /// Downloads the original file into the media directory
+    Download,
+    /// Opens the file description page in the web browser
+    OpenDescription,
+
+    /// Description of the file with the title
+    FinishLoading(String, Result<Box<MediaInfo>, String>),
+    /// Where the file with the title was downloaded to
+    FinishDownload(String, Result<PathBuf, String>),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PageViewerAction {
     DisplayPage(Page),
+    /// Shows the page fetched for one of its sections, together with the heading of the section
+    DisplaySection(Page, String),
     PopPage,
+    /// Shows the page again, fetching it only when it's not loaded anymore
+    ReopenPage(String),
+    /// Tells that the page can't be shown because it isn't cached and the app is offline
+    ShowUnavailable(String),
+    /// Adds the page below the shown one, it's shown once the pages above it are closed
+    DisplayPageInBackground(Page),
+    /// Shows the page in an overlay on top of the shown page. Peeks can be nested a few levels
+    DisplayPeek(Page),
+    /// Tells the pages with the title that there is a newer revision of them
+    UpdateAvailable(String, usize),
+    /// Tells the pages with the title how often they were viewed lately
+    FinishPageViews(String, PageViews),
+    /// Gives the pages with the title the titles of their linked pages in the secondary language
+    FinishLanguageHints(String, HashMap<String, String>),
+    /// Replaces the pages showing the same page with its latest revision, they stay scrolled to
+    /// where they were
+    ReloadPage(Page),
+    /// Closes the topmost peek, going back to the page below it
+    PopPeek,
+    /// Opens the topmost peek as a regular page and closes every peek
+    PromotePeek,
+    /// Replaces the open pages with the pages of a workspace, each with the position it's
+    /// scrolled to, together with the index of the shown page
+    DisplayWorkspace(Vec<(Page, Option<(usize, usize)>)>, usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FeedAction {
+    Load,
+    FinishLoading(OnThisDay),
+    AbortLoading,
+
+    OpenSelected,
+
+    SelectPrevPage,
+    SelectNextPage,
+
+    PrevDay,
+    NextDay,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NearbyAction {
+    /// Searches around the given coordinates, or the configured home location
+    Search(Option<Coordinates>),
+    FinishSearch(Coordinates, Vec<GeoSearchResult>),
+    AbortSearch,
+
+    OpenSelected,
+    ToggleSortOrder,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkGraphAction {
+    Open(String),
+
+    Expand,
+    ExpandLevel,
+    Collapse,
+    FinishLinks(String, Vec<String>),
+
+    OpenSelected,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotesAction {
+    OpenSelected,
+    DeleteSelected,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum YankRingAction {
+    /// Yanks the selected entry again, running its hook
+    YankSelected,
+    /// Opens the linked page, or the page the passage is on
+    OpenSelected,
+    DeleteSelected,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DisambiguationAction {
+    /// Lists the pages of the disambiguation page to choose from
+    Show(Page),
+    OpenSelected,
+    /// Displays the disambiguation page itself
+    ShowPage,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FetchQueueAction {
+    OpenSelected,
+    RemoveSelected,
+    ClearFetched,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BookmarksAction {
+    OpenSelected,
+    RemoveSelected,
+    /// Opens what changed on the selected page since it was last read in the browser
+    ShowSelectedChanges,
+    /// Checks whether the bookmarked pages changed since they were last read
+    CheckUpdates,
+    /// The latest revisions of the bookmarked pages in the language, by their title
+    FinishCheck(Language, HashMap<String, usize>),
+    AbortCheck,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReadingQueueAction {
+    /// Opens the selected page and removes it from the queue
+    OpenSelected,
+    RemoveSelected,
+    /// Moves the selected page by the offset, negative offsets move it towards the front
+    MoveSelected(isize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CacheAction {
+    OpenSelected,
+    RemoveSelected,
+    /// Removes the pages that weren't opened for longer than `cache.purge_age`
+    PurgeOld,
+    /// Removes outdated and stray files from the page cache
+    Vacuum,
+    Refresh,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkspacesAction {
+    OpenSelected,
+    DeleteSelected,
+    /// Asks for the name to save the open pages under
+    EnterName,
+    SubmitName,
+    CancelName,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PortalsAction {
+    Load(String),
+    FinishLoading(String, Vec<PortalSection>),
+    AbortLoading,
+    OpenSelected,
+    OpenPortalPage,
+    SelectPrevArticle,
+    SelectNextArticle,
+    ShowPortals,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LanguagesAction {
+    SwitchToSelected,
+    Search,
+    FinishSearch,
+    CancelSearch,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HistoryAction {
+    OpenSelected,
+    ToggleSortOrder,
+
+    Search,
+    FinishSearch,
+    CancelSearch,
 }
 
 pub enum ActionResult {
@@ -124,10 +611,19 @@ impl ActionPacket {
         self
     }
 
+    pub fn append(mut self, mut packet: ActionPacket) -> Self {
+        self.actions.append(&mut packet.actions);
+        self
+    }
+
     pub fn add_action(&mut self, action: Action) {
         self.actions.push(action);
     }
 
+    pub fn contains(&self, action: &Action) -> bool {
+        self.actions.contains(action)
+    }
+
     pub fn send(self, action_tx: &mpsc::UnboundedSender<Action>) {
         for action in self.actions {
             action_tx.send(action).unwrap();
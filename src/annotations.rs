@@ -0,0 +1,143 @@
+use std::{
+    path::PathBuf,
+    sync::{Mutex, MutexGuard, OnceLock},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+use wiki_api::languages::Language;
+
+use crate::{
+    config::data_dir,
+    speech::{Sentence, WordPosition},
+};
+
+const ANNOTATIONS_FILE: &str = "annotations.json";
+
+static ANNOTATIONS: OnceLock<Mutex<AnnotationStore>> = OnceLock::new();
+
+/// A highlighted passage of a page with an optional note
+///
+/// The passage is anchored by its text, so it can be found again after the page was re-rendered
+/// with a different width or even edited
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Annotation {
+    /// Title of the page the passage belongs to
+    pub page: String,
+    /// Text of the highlighted passage
+    pub text: String,
+    /// Note attached to the passage, can be empty
+    pub note: String,
+    /// Unix timestamp of when the annotation was created
+    pub created: i64,
+    /// Language of the page, missing for annotations made before it was stored
+    #[serde(default)]
+    pub language: Option<Language>,
+}
+
+/// All annotations, stored in the data directory
+#[derive(Default)]
+pub struct AnnotationStore {
+    annotations: Vec<Annotation>,
+}
+
+impl AnnotationStore {
+    fn path() -> Result<PathBuf> {
+        Ok(data_dir()?.join(ANNOTATIONS_FILE))
+    }
+
+    fn load() -> Result<Self> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        info!("loading the annotations from '{}'", path.display());
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("unable to read the annotations '{}'", path.display()))?;
+        let annotations = serde_json::from_str(&contents)
+            .with_context(|| format!("unable to parse the annotations '{}'", path.display()))?;
+        Ok(Self { annotations })
+    }
+
+    fn save(&self) {
+        let result = Self::path().and_then(|path| {
+            std::fs::create_dir_all(data_dir()?)?;
+            std::fs::write(&path, serde_json::to_string_pretty(&self.annotations)?)
+                .with_context(|| format!("unable to write the annotations '{}'", path.display()))
+        });
+
+        if let Err(error) = result {
+            error!("unable to save the annotations: {:?}", error);
+        }
+    }
+
+    pub fn all(&self) -> &[Annotation] {
+        &self.annotations
+    }
+
+    pub fn for_page<'a>(&'a self, page: &'a str) -> impl Iterator<Item = &'a Annotation> {
+        self.annotations
+            .iter()
+            .filter(move |annotation| annotation.page == page)
+    }
+
+    pub fn add(&mut self, annotation: Annotation) {
+        self.annotations.push(annotation);
+        self.save();
+    }
+
+    /// Removes the annotation at the index, returns it when there was one
+    pub fn remove(&mut self, index: usize) -> Option<Annotation> {
+        if index >= self.annotations.len() {
+            return None;
+        }
+        let annotation = self.annotations.remove(index);
+        self.save();
+        Some(annotation)
+    }
+
+    /// Puts a removed annotation back at its index, or at the end when there are fewer
+    /// annotations now
+    pub fn restore(&mut self, index: usize, annotation: Annotation) {
+        let index = index.min(self.annotations.len());
+        self.annotations.insert(index, annotation);
+        self.save();
+    }
+}
+
+/// Returns the stored annotations, loading them on first use
+pub fn annotations() -> MutexGuard<'static, AnnotationStore> {
+    ANNOTATIONS
+        .get_or_init(|| {
+            Mutex::new(AnnotationStore::load().unwrap_or_else(|error| {
+                error!("unable to load the annotations: {:?}", error);
+                AnnotationStore::default()
+            }))
+        })
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Finds the consecutive sentences that make up the text and returns the positions of their
+/// first and last word
+pub fn find_passage(sentences: &[Sentence], text: &str) -> Option<(WordPosition, WordPosition)> {
+    for (first, sentence) in sentences.iter().enumerate() {
+        let mut passage = String::new();
+        for next in &sentences[first..] {
+            if !passage.is_empty() {
+                passage.push(' ');
+            }
+            passage.push_str(&next.text);
+
+            if passage == text {
+                return Some((sentence.start, next.end));
+            }
+            if !text.starts_with(&passage) {
+                break;
+            }
+        }
+    }
+    None
+}
@@ -1,44 +1,187 @@
+use std::time::Duration;
+
 use anyhow::Result;
 use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::prelude::{Constraint, Direction, Layout, Rect};
-use tracing::warn;
+use tracing::{error, info, warn};
 use wiki_api::{languages::Language, Endpoint};
 
 use tokio::sync::mpsc;
 
 use crate::{
-    action::{Action, ActionPacket, ActionResult},
+    action::{Action, ActionPacket, ActionResult, BookmarksAction, ExitMode, FeedAction},
+    bookmarks::bookmarks,
+    browser::open_url,
+    bug_report::save_bug_report,
+    clipboard::search_clipboard,
     components::{
+        api_error::ApiErrorComponent,
+        bookmarks::BookmarksComponent,
+        cache::CacheComponent,
+        command_palette::CommandPaletteComponent,
+        config_problems::ConfigProblemsComponent,
+        context_menu::{ContextMenu, ContextMenuComponent},
+        disambiguation::DisambiguationComponent,
+        feed::FeedComponent,
+        fetch_queue::FetchQueueComponent,
         help::{HelpComponent, Keymap},
+        history::HistoryComponent,
+        languages::LanguagesComponent,
+        link_graph::LinkGraphComponent,
         logger::LoggerComponent,
+        nearby::NearbyComponent,
+        notes::NotesComponent,
         page_viewer::PageViewer,
+        portals::PortalsComponent,
+        quit::QuitComponent,
+        reading_queue::ReadingQueueComponent,
         search::SearchComponent,
         search_bar::{SearchBarComponent, SEARCH_BAR_HEIGTH},
+        setup::SetupComponent,
         status::{StatusComponent, STATUS_HEIGHT},
+        workspaces::WorkspacesComponent,
+        yank_ring::YankRingComponent,
         Component,
     },
-    has_modifier, key_event,
+    config::{config, config_problems, persist_value, ThemePreset},
+    event::Event,
+    fetch_queue::{fetch_queue, FetchStatus},
+    flashcards::export_flashcards,
+    has_modifier,
+    hooks::{run_hook, Hook},
+    i18n::tr,
+    key_event,
+    key_sequences::{command_action, KeySequences},
     page_loader::PageLoader,
+    reading_queue::reading_queue,
+    remote::start_remote,
+    request_log::export_har,
+    setup::FirstRun,
+    tasks::tasks,
     terminal::Frame,
-    ui::centered_rect,
+    ui::{centered_rect, render_too_small, set_theme_preset, theme_preset, ResponsiveLayout},
+    undo::{record, undo, Undoable},
+    workspaces::workspaces,
+    yank_ring::yank_ring,
 };
 
 const CONTEXT_SEARCH: u8 = 0;
 const CONTEXT_PAGE: u8 = 1;
+const CONTEXT_FEED: u8 = 2;
+const CONTEXT_NEARBY: u8 = 3;
+const CONTEXT_LINK_GRAPH: u8 = 4;
+const CONTEXT_NOTES: u8 = 5;
+const CONTEXT_HISTORY: u8 = 6;
+const CONTEXT_DISAMBIGUATION: u8 = 7;
+const CONTEXT_FETCH_QUEUE: u8 = 8;
+const CONTEXT_LANGUAGES: u8 = 9;
+const CONTEXT_PORTALS: u8 = 10;
+const CONTEXT_BOOKMARKS: u8 = 11;
+const CONTEXT_CACHE: u8 = 12;
+const CONTEXT_READING_QUEUE: u8 = 13;
+
+const PANE_RESIZE_STEP: i16 = 5;
+const PANE_MIN_WIDTH: u16 = 10;
+const PANE_MAX_WIDTH: u16 = 90;
+
+/// Changes a pane width (in percent) by `amount`, keeping it in the allowed range
+pub fn resize_pane(width: u16, amount: i16) -> u16 {
+    (width as i16)
+        .saturating_add(amount)
+        .clamp(PANE_MIN_WIDTH as i16, PANE_MAX_WIDTH as i16) as u16
+}
+
+/// Asks for a check of the shown page for newer revisions every `behavior.update_check_interval`
+/// minutes, when the checks are enabled
+fn watch_page_updates(action_tx: mpsc::UnboundedSender<Action>) {
+    let minutes = config().behavior.update_check_interval;
+    if minutes == 0 {
+        return;
+    }
+
+    info!(
+        "checking the shown page for changes every '{}' minutes",
+        minutes
+    );
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(minutes * 60));
+        // the first tick completes immediately, when the page was just loaded
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            if action_tx.send(Action::CheckPageUpdate).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// Returns the menu to switch between the built-in themes, the current one is checked
+fn theme_menu() -> ActionResult {
+    let current = theme_preset();
+    let menu = ThemePreset::ALL
+        .iter()
+        .fold(ContextMenu::new(tr("theme.title")), |menu, preset| {
+            let (key, name) = match preset {
+                ThemePreset::Default => ('d', tr("theme.default")),
+                ThemePreset::HighContrast => ('h', tr("theme.high_contrast")),
+                ThemePreset::Deuteranopia => ('e', tr("theme.deuteranopia")),
+                ThemePreset::Protanopia => ('p', tr("theme.protanopia")),
+            };
+            let label = match *preset == current {
+                true => format!("{} ✓", name),
+                false => name.to_string(),
+            };
+            menu.item(key, label, vec![Action::SetTheme(*preset)])
+        });
+    Action::OpenContextMenu(menu).into()
+}
 
-#[derive(Default)]
 pub struct AppComponent {
     search: SearchComponent,
     page: PageViewer,
+    feed: FeedComponent,
+    nearby: NearbyComponent,
+    link_graph: LinkGraphComponent,
+    notes: NotesComponent,
+    history: HistoryComponent,
+    disambiguation: DisambiguationComponent,
+    fetch_queue: FetchQueueComponent,
+    languages: LanguagesComponent,
+    portals: PortalsComponent,
+    bookmarks: BookmarksComponent,
+    reading_queue: ReadingQueueComponent,
+    cache: CacheComponent,
     logger: LoggerComponent,
     status: StatusComponent,
     search_bar: SearchBarComponent,
     help: HelpComponent,
+    /// Invalid settings of the config file, shown until they are dismissed
+    config_problems: Option<ConfigProblemsComponent>,
+    /// Popup with the links and passages yanked in this session, shown over the context
+    yank_ring: Option<YankRingComponent>,
+    /// Popup with the commands of the current context, shown over the context
+    command_palette: Option<CommandPaletteComponent>,
+    /// Actions for an entry of the current context, shown over the context
+    context_menu: Option<ContextMenuComponent>,
+    /// Popup with the saved workspaces, shown over the context
+    workspaces: Option<WorkspacesComponent>,
+    /// Explains why a request to the wiki failed, shown over the context
+    api_error: Option<ApiErrorComponent>,
+    /// Welcomes the user on the first run, shown over the context
+    setup: Option<SetupComponent>,
+    /// Asks whether to quit while something would be lost, shown over everything
+    quit: Option<QuitComponent>,
 
     page_loader: Option<PageLoader>,
 
     is_logger: bool,
     is_help: bool,
+    is_window_prefix: bool,
+    /// The configured key sequences, like `gg`, recognized in the pressed keys
+    key_sequences: KeySequences,
+
+    logger_width: u16,
 
     context: u8,
     prev_context: u8,
@@ -46,6 +189,55 @@ pub struct AppComponent {
     action_tx: Option<mpsc::UnboundedSender<Action>>,
 }
 
+impl Default for AppComponent {
+    fn default() -> Self {
+        Self {
+            search: SearchComponent::default(),
+            page: PageViewer::default(),
+            feed: FeedComponent::default(),
+            nearby: NearbyComponent::default(),
+            link_graph: LinkGraphComponent::default(),
+            notes: NotesComponent::default(),
+            history: HistoryComponent::default(),
+            disambiguation: DisambiguationComponent::default(),
+            fetch_queue: FetchQueueComponent::default(),
+            languages: LanguagesComponent::default(),
+            portals: PortalsComponent::default(),
+            bookmarks: BookmarksComponent::default(),
+            reading_queue: ReadingQueueComponent::default(),
+            cache: CacheComponent::default(),
+            logger: LoggerComponent::default(),
+            status: StatusComponent::default(),
+            search_bar: SearchBarComponent::default(),
+            help: HelpComponent::default(),
+            config_problems: Some(config_problems())
+                .filter(|problems| !problems.is_empty())
+                .map(|problems| ConfigProblemsComponent::new(problems.to_vec())),
+            yank_ring: None,
+            command_palette: None,
+            context_menu: None,
+            workspaces: None,
+            api_error: None,
+            setup: None,
+            quit: None,
+
+            page_loader: None,
+
+            is_logger: false,
+            is_help: false,
+            is_window_prefix: false,
+            key_sequences: KeySequences::new(&config().keys),
+
+            logger_width: config().ui.logger_width,
+
+            context: CONTEXT_SEARCH,
+            prev_context: CONTEXT_SEARCH,
+
+            action_tx: None,
+        }
+    }
+}
+
 impl AppComponent {
     fn switch_context(&mut self, context: u8) {
         self.prev_context = context;
@@ -55,46 +247,83 @@ impl AppComponent {
     fn toggle_show_help(&mut self) {
         self.is_help = !self.is_help;
 
-        if !self.is_help {
-            return;
+        if self.is_help {
+            let keymap = self.context_keymap();
+            self.help.set_keymap(keymap);
         }
+    }
 
+    fn toggle_command_palette(&mut self) {
+        self.command_palette = match self.command_palette {
+            Some(_) => None,
+            None => Some(CommandPaletteComponent::new(self.context_keymap())),
+        }
+    }
+
+    /// Returns the global keybindings followed by the ones of the current context
+    fn context_keymap(&self) -> Keymap {
         let mut keymap = self.keymap();
         keymap.append(&mut match self.context {
             CONTEXT_SEARCH => self.search.keymap(),
             CONTEXT_PAGE => self.page.keymap(),
-            _ => return warn!("unknown context"),
+            CONTEXT_FEED => self.feed.keymap(),
+            CONTEXT_NEARBY => self.nearby.keymap(),
+            CONTEXT_LINK_GRAPH => self.link_graph.keymap(),
+            CONTEXT_NOTES => self.notes.keymap(),
+            CONTEXT_HISTORY => self.history.keymap(),
+            CONTEXT_DISAMBIGUATION => self.disambiguation.keymap(),
+            CONTEXT_FETCH_QUEUE => self.fetch_queue.keymap(),
+            CONTEXT_LANGUAGES => self.languages.keymap(),
+            CONTEXT_PORTALS => self.portals.keymap(),
+            CONTEXT_BOOKMARKS => self.bookmarks.keymap(),
+            CONTEXT_READING_QUEUE => self.reading_queue.keymap(),
+            CONTEXT_CACHE => self.cache.keymap(),
+            _ => {
+                warn!("unknown context");
+                Vec::new()
+            }
         });
-        self.help.set_keymap(keymap);
+        keymap
     }
-}
 
-impl Component for AppComponent {
-    fn init(&mut self, action_tx: mpsc::UnboundedSender<Action>) -> Result<()> {
-        self.search.init(action_tx.clone())?;
-        self.page.init(action_tx.clone())?;
-        self.search_bar.init(action_tx.clone())?;
-
-        self.page_loader = Some(PageLoader::new(
-            Endpoint::parse("https://en.wikipedia.org/w/api.php").unwrap(),
-            Language::default(),
-            action_tx.clone(),
-        ));
+    /// Handles a key that isn't part of a key sequence
+    fn handle_key(&mut self, key: KeyEvent) -> ActionResult {
+        if self.is_window_prefix {
+            return self.handle_window_key(key);
+        }
 
-        action_tx.send(Action::EnterSearchBar).unwrap();
-        self.action_tx = Some(action_tx);
+        if matches!(key.code, KeyCode::Char('w')) && has_modifier!(key, Modifier::CONTROL) {
+            self.is_window_prefix = true;
+            return ActionResult::consumed();
+        }
 
-        Ok(())
-    }
+        if matches!(key.code, KeyCode::Char('p')) && has_modifier!(key, Modifier::CONTROL) {
+            return Action::ToggleCommandPalette.into();
+        }
 
-    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
-        if self.search_bar.is_focussed {
-            return self.search_bar.handle_key_events(key);
+        // text that is being typed is pasted into the input instead
+        if matches!(key.code, KeyCode::Char('v'))
+            && has_modifier!(key, Modifier::CONTROL)
+            && !self.is_typing()
+        {
+            return Action::SearchClipboard.into();
         }
 
         let result = match self.context {
             CONTEXT_SEARCH => self.search.handle_key_events(key),
             CONTEXT_PAGE => self.page.handle_key_events(key),
+            CONTEXT_FEED => self.feed.handle_key_events(key),
+            CONTEXT_NEARBY => self.nearby.handle_key_events(key),
+            CONTEXT_LINK_GRAPH => self.link_graph.handle_key_events(key),
+            CONTEXT_NOTES => self.notes.handle_key_events(key),
+            CONTEXT_HISTORY => self.history.handle_key_events(key),
+            CONTEXT_DISAMBIGUATION => self.disambiguation.handle_key_events(key),
+            CONTEXT_FETCH_QUEUE => self.fetch_queue.handle_key_events(key),
+            CONTEXT_LANGUAGES => self.languages.handle_key_events(key),
+            CONTEXT_PORTALS => self.portals.handle_key_events(key),
+            CONTEXT_BOOKMARKS => self.bookmarks.handle_key_events(key),
+            CONTEXT_READING_QUEUE => self.reading_queue.handle_key_events(key),
+            CONTEXT_CACHE => self.cache.handle_key_events(key),
             _ => {
                 warn!("unknown context");
                 return ActionResult::Ignored;
@@ -112,6 +341,14 @@ impl Component for AppComponent {
 
             KeyCode::Char('s') => Action::SwitchContextSearch.into(),
             KeyCode::Char('p') => Action::SwitchContextPage.into(),
+            KeyCode::Char('f') => Action::SwitchContextFeed.into(),
+            KeyCode::Char('n') => Action::SwitchContextNotes.into(),
+            KeyCode::Char('H') => Action::SwitchContextHistory.into(),
+            KeyCode::Char('F') => Action::SwitchContextFetchQueue.into(),
+            KeyCode::Char('P') => Action::SwitchContextPortals.into(),
+            KeyCode::Char('B') => Action::SwitchContextBookmarks.into(),
+            KeyCode::Char('C') => Action::SwitchContextCache.into(),
+            KeyCode::Char('J') => Action::ReadNext.into(),
 
             KeyCode::Char('j') => Action::ScrollDown(1).into(),
             KeyCode::Char('k') => Action::ScrollUp(1).into(),
@@ -130,10 +367,202 @@ impl Component for AppComponent {
 
             KeyCode::Char('i') => Action::EnterSearchBar.into(),
 
+            KeyCode::Char('O') => Action::ToggleOfflineMode.into(),
+            KeyCode::Char('Y') => Action::ToggleYankRing.into(),
+            KeyCode::Char('A') => Action::ShowThemeMenu.into(),
+            KeyCode::Char('u') => Action::Undo.into(),
+            KeyCode::Char('w') => Action::ToggleWorkspaces.into(),
+
             _ => ActionResult::Ignored,
         }
     }
 
+    /// Handles the keys that turned out not to be part of a key sequence, in the order they were
+    /// pressed
+    fn handle_single_keys(&mut self, keys: Vec<KeyEvent>) -> ActionPacket {
+        let mut packet = ActionPacket::default();
+        for key in keys {
+            if let ActionResult::Consumed(actions) = self.handle_key(key) {
+                packet = packet.append(actions);
+            }
+        }
+        packet
+    }
+
+    /// Handles the key following the window prefix (`Ctrl+W`)
+    ///
+    /// The logger is resized when it's shown, otherwise the contents sidebar is resized
+    fn handle_window_key(&mut self, key: KeyEvent) -> ActionResult {
+        self.is_window_prefix = false;
+
+        let amount = match key.code {
+            KeyCode::Char('<') => -PANE_RESIZE_STEP,
+            KeyCode::Char('>') => PANE_RESIZE_STEP,
+            _ => return ActionResult::consumed(),
+        };
+
+        if self.is_logger {
+            Action::ResizeLogger(amount).into()
+        } else {
+            Action::ResizeContents(amount).into()
+        }
+    }
+
+    /// Quits right away, unless background tasks are running or an annotation isn't saved yet.
+    /// Then a dialog asks whether to let the tasks finish, cancel them or stay
+    fn quit(&mut self) -> ActionResult {
+        self.page.save_reading_position();
+        let unsaved_annotation = self.page.has_unsaved_annotation();
+        if tasks().is_empty() && !unsaved_annotation {
+            return Action::Exit(ExitMode::CancelTasks).into();
+        }
+
+        info!("asking before quitting, something would be lost");
+        self.quit = Some(QuitComponent::new(unsaved_annotation));
+        ActionResult::consumed()
+    }
+
+    fn render_quit(&mut self, f: &mut Frame<'_>, area: Rect) {
+        if let Some(ref mut quit) = self.quit {
+            quit.render(f, area);
+        }
+    }
+
+    /// Searches and loads pages in the language for the rest of the session
+    fn switch_language(&mut self, language: Language) {
+        self.set_endpoint(language.wikipedia_endpoint());
+        self.search.set_language(language.clone());
+        self.languages.set_current(language.clone());
+        self.page_loader.as_mut().unwrap().set_language(language);
+    }
+
+    /// Lets the components that aren't bound to a page fetch from the wiki at the endpoint
+    fn set_endpoint(&mut self, endpoint: Endpoint) {
+        self.feed.set_endpoint(endpoint.clone());
+        self.nearby.set_endpoint(endpoint.clone());
+        self.link_graph.set_endpoint(endpoint);
+    }
+
+    /// Shows the dialog of the first run, with the language chosen for the new config
+    pub fn show_setup(&mut self, first_run: FirstRun) {
+        self.setup = Some(SetupComponent::new(first_run));
+    }
+
+    fn finish_setup(&mut self, language: Option<Language>) {
+        self.setup = None;
+        if let Some(language) = language {
+            if let Err(error) = persist_value("api", "language", language.code()) {
+                error!("unable to save the language: {:?}", error);
+            }
+            self.switch_language(language);
+        }
+    }
+
+    fn resize_logger(&mut self, amount: i16) {
+        self.logger_width = resize_pane(self.logger_width, amount);
+        if let Err(error) = persist_value("ui", "logger_width", self.logger_width as i64) {
+            error!("unable to save the logger width: {:?}", error);
+        }
+    }
+}
+
+impl Component for AppComponent {
+    fn init(&mut self, action_tx: mpsc::UnboundedSender<Action>) -> Result<()> {
+        self.search.init(action_tx.clone())?;
+        self.page.init(action_tx.clone())?;
+        self.feed.init(action_tx.clone())?;
+        self.nearby.init(action_tx.clone())?;
+        self.link_graph.init(action_tx.clone())?;
+        self.portals.init(action_tx.clone())?;
+        self.bookmarks.init(action_tx.clone())?;
+        self.search_bar.init(action_tx.clone())?;
+
+        let language = config().api.language.clone();
+        self.set_endpoint(language.wikipedia_endpoint());
+        self.languages.set_current(language.clone());
+        self.page_loader = Some(PageLoader::new(language, action_tx.clone()));
+        watch_page_updates(action_tx.clone());
+        start_remote(action_tx.clone());
+
+        action_tx.send(Action::EnterSearchBar).unwrap();
+        self.action_tx = Some(action_tx);
+
+        Ok(())
+    }
+
+    fn is_typing(&self) -> bool {
+        match self.context {
+            CONTEXT_SEARCH => self.search.is_typing(),
+            CONTEXT_PAGE => self.page.is_typing(),
+            CONTEXT_HISTORY => self.history.is_typing(),
+            CONTEXT_LANGUAGES => self.languages.is_typing(),
+            _ => false,
+        }
+    }
+
+    fn handle_events(&mut self, event: Option<Event>) -> ActionResult {
+        match event {
+            Some(Event::Quit) => Action::Quit.into(),
+            Some(Event::RenderTick) => {
+                // the keys of a sequence that wasn't finished in time are handled on their own
+                let keys = self.key_sequences.expire();
+                ActionResult::Consumed(self.handle_single_keys(keys).action(Action::RenderTick))
+            }
+            Some(Event::Key(key_event)) => self.handle_key_events(key_event),
+            Some(Event::Resize(x, y)) => Action::Resize(x, y).into(),
+            None => ActionResult::Ignored,
+        }
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        if let Some(ref mut quit) = self.quit {
+            return quit.handle_key_events(key);
+        }
+
+        if let Some(ref mut config_problems) = self.config_problems {
+            return config_problems.handle_key_events(key);
+        }
+
+        if let Some(ref mut setup) = self.setup {
+            return setup.handle_key_events(key);
+        }
+
+        if let Some(ref mut api_error) = self.api_error {
+            return api_error.handle_key_events(key);
+        }
+
+        if let Some(ref mut yank_ring) = self.yank_ring {
+            return yank_ring.handle_key_events(key);
+        }
+
+        if let Some(ref mut command_palette) = self.command_palette {
+            return command_palette.handle_key_events(key);
+        }
+
+        if let Some(ref mut context_menu) = self.context_menu {
+            return context_menu.handle_key_events(key);
+        }
+
+        if let Some(ref mut workspaces) = self.workspaces {
+            return workspaces.handle_key_events(key);
+        }
+
+        if self.search_bar.is_focussed {
+            return self.search_bar.handle_key_events(key);
+        }
+
+        if self.is_window_prefix || self.is_typing() {
+            return self.handle_key(key);
+        }
+
+        let (single_keys, command) = self.key_sequences.push(key);
+        let mut packet = self.handle_single_keys(single_keys);
+        if let Some(command) = command {
+            packet.add_action(command_action(command));
+        }
+        ActionResult::Consumed(packet)
+    }
+
     fn keymap(&self) -> Keymap {
         vec![
             (
@@ -153,6 +582,38 @@ impl Component for AppComponent {
                 key_event!('p'),
                 ActionPacket::single(Action::SwitchContextPage),
             ),
+            (
+                key_event!('f'),
+                ActionPacket::single(Action::SwitchContextFeed),
+            ),
+            (
+                key_event!('n'),
+                ActionPacket::single(Action::SwitchContextNotes),
+            ),
+            (
+                key_event!('H', Modifier::SHIFT),
+                ActionPacket::single(Action::SwitchContextHistory),
+            ),
+            (
+                key_event!('F', Modifier::SHIFT),
+                ActionPacket::single(Action::SwitchContextFetchQueue),
+            ),
+            (
+                key_event!('P', Modifier::SHIFT),
+                ActionPacket::single(Action::SwitchContextPortals),
+            ),
+            (
+                key_event!('B', Modifier::SHIFT),
+                ActionPacket::single(Action::SwitchContextBookmarks),
+            ),
+            (
+                key_event!('C', Modifier::SHIFT),
+                ActionPacket::single(Action::SwitchContextCache),
+            ),
+            (
+                key_event!('J', Modifier::SHIFT),
+                ActionPacket::single(Action::ReadNext),
+            ),
             (key_event!('j'), ActionPacket::single(Action::ScrollDown(1))),
             (key_event!('k'), ActionPacket::single(Action::ScrollUp(1))),
             (
@@ -163,16 +624,63 @@ impl Component for AppComponent {
                 key_event!('i'),
                 ActionPacket::single(Action::EnterSearchBar),
             ),
+            (
+                key_event!('O', Modifier::SHIFT),
+                ActionPacket::single(Action::ToggleOfflineMode),
+            ),
+            (
+                key_event!('Y', Modifier::SHIFT),
+                ActionPacket::single(Action::ToggleYankRing),
+            ),
+            (
+                key_event!('p', Modifier::CONTROL),
+                ActionPacket::single(Action::ToggleCommandPalette),
+            ),
+            (
+                key_event!('v', Modifier::CONTROL),
+                ActionPacket::single(Action::SearchClipboard),
+            ),
+            (
+                key_event!('A', Modifier::SHIFT),
+                ActionPacket::single(Action::ShowThemeMenu),
+            ),
+            (key_event!('u'), ActionPacket::single(Action::Undo)),
+            (
+                key_event!('w'),
+                ActionPacket::single(Action::ToggleWorkspaces),
+            ),
         ]
     }
 
     fn update(&mut self, action: Action) -> ActionResult {
-        let result = if self.is_help {
+        let result = if let Some(ref mut config_problems) = self.config_problems {
+            config_problems.update(action.clone())
+        } else if let Some(ref mut yank_ring) = self.yank_ring {
+            yank_ring.update(action.clone())
+        } else if let Some(ref mut command_palette) = self.command_palette {
+            command_palette.update(action.clone())
+        } else if let Some(ref mut context_menu) = self.context_menu {
+            context_menu.update(action.clone())
+        } else if let Some(ref mut workspaces) = self.workspaces {
+            workspaces.update(action.clone())
+        } else if self.is_help {
             self.help.update(action.clone())
         } else {
             match self.context {
                 CONTEXT_SEARCH => self.search.update(action.clone()),
                 CONTEXT_PAGE => self.page.update(action.clone()),
+                CONTEXT_FEED => self.feed.update(action.clone()),
+                CONTEXT_NEARBY => self.nearby.update(action.clone()),
+                CONTEXT_LINK_GRAPH => self.link_graph.update(action.clone()),
+                CONTEXT_NOTES => self.notes.update(action.clone()),
+                CONTEXT_HISTORY => self.history.update(action.clone()),
+                CONTEXT_DISAMBIGUATION => self.disambiguation.update(action.clone()),
+                CONTEXT_FETCH_QUEUE => self.fetch_queue.update(action.clone()),
+                CONTEXT_LANGUAGES => self.languages.update(action.clone()),
+                CONTEXT_PORTALS => self.portals.update(action.clone()),
+                CONTEXT_BOOKMARKS => self.bookmarks.update(action.clone()),
+                CONTEXT_READING_QUEUE => self.reading_queue.update(action.clone()),
+                CONTEXT_CACHE => self.cache.update(action.clone()),
                 _ => {
                     warn!("unknown context");
                     return ActionResult::Ignored;
@@ -186,25 +694,190 @@ impl Component for AppComponent {
 
         // global actions
         match action {
+            Action::Quit => return self.quit(),
+            Action::CancelQuit => self.quit = None,
+            Action::ShowApiError(request, error) => {
+                self.api_error = Some(ApiErrorComponent::new(request, error))
+            }
+            Action::CloseApiError => self.api_error = None,
+            Action::FinishSetup(language) => self.finish_setup(language),
             Action::ToggleShowLogger => self.is_logger = !self.is_logger,
             Action::ToggleShowHelp => self.toggle_show_help(),
+            Action::DismissConfigProblems => self.config_problems = None,
+            Action::ToggleYankRing => {
+                self.yank_ring = match self.yank_ring {
+                    Some(_) => None,
+                    None => Some(YankRingComponent::new()),
+                }
+            }
+            Action::ToggleCommandPalette => self.toggle_command_palette(),
+            Action::OpenContextMenu(menu) => {
+                self.context_menu = Some(ContextMenuComponent::new(menu))
+            }
+            Action::CloseContextMenu => self.context_menu = None,
+            Action::ShowThemeMenu => return theme_menu(),
+            Action::SetTheme(preset) => {
+                set_theme_preset(preset);
+                if let Err(error) = persist_value("ui", "theme", preset.key()) {
+                    error!("unable to save the theme: {:?}", error);
+                }
+            }
+            Action::Undo => {
+                match undo() {
+                    Ok(Some(_)) => {}
+                    Ok(None) => info!("there is nothing to undo"),
+                    Err(error) => error!("unable to undo: {:?}", error),
+                }
+                // the restored pages have to show up in the list
+                self.cache.refresh();
+            }
+            Action::ToggleWorkspaces => {
+                self.workspaces = match self.workspaces {
+                    Some(_) => None,
+                    None => Some(WorkspacesComponent::new()),
+                }
+            }
+            Action::SaveWorkspace(name) => match self.page.workspace(name) {
+                Some(workspace) => {
+                    info!("saved the workspace '{}'", workspace.name);
+                    workspaces().store(workspace);
+                }
+                None => info!("there are no open pages to save"),
+            },
+            Action::LoadWorkspace(name) => {
+                let workspace = workspaces().get(&name).cloned();
+                match workspace {
+                    Some(workspace) => self.page_loader.as_mut().unwrap().load_workspace(workspace),
+                    None => warn!("there is no workspace named '{}'", name),
+                }
+            }
+            Action::DeleteWorkspace(name) => match workspaces().remove(&name) {
+                Some(workspace) => record(Undoable::Workspace(workspace)),
+                None => warn!("there is no workspace named '{}'", name),
+            },
 
             Action::SwitchContextSearch => self.switch_context(CONTEXT_SEARCH),
             Action::SwitchContextPage => self.switch_context(CONTEXT_PAGE),
+            Action::SwitchContextFeed => {
+                self.switch_context(CONTEXT_FEED);
+                if !self.feed.is_loaded() {
+                    return Action::Feed(FeedAction::Load).into();
+                }
+            }
+            Action::SwitchContextNearby => self.switch_context(CONTEXT_NEARBY),
+            Action::SwitchContextLinkGraph => self.switch_context(CONTEXT_LINK_GRAPH),
+            Action::SwitchContextNotes => self.switch_context(CONTEXT_NOTES),
+            Action::SwitchContextHistory => self.switch_context(CONTEXT_HISTORY),
+            Action::SwitchContextDisambiguation => self.switch_context(CONTEXT_DISAMBIGUATION),
+            Action::SwitchContextFetchQueue => self.switch_context(CONTEXT_FETCH_QUEUE),
+            Action::SwitchContextLanguages => self.switch_context(CONTEXT_LANGUAGES),
+            Action::SwitchContextPortals => self.switch_context(CONTEXT_PORTALS),
+            Action::SwitchContextBookmarks => {
+                self.switch_context(CONTEXT_BOOKMARKS);
+                if self.bookmarks.needs_check() {
+                    return Action::Bookmarks(BookmarksAction::CheckUpdates).into();
+                }
+            }
+            Action::SwitchContextCache => {
+                self.switch_context(CONTEXT_CACHE);
+                self.cache.refresh();
+            }
+            Action::SwitchContextReadingQueue => self.switch_context(CONTEXT_READING_QUEUE),
             Action::SwitchPreviousContext => self.switch_context(self.prev_context),
 
+            Action::ResizeLogger(amount) => self.resize_logger(amount),
+
             Action::EnterSearchBar => self.search_bar.is_focussed = true,
             Action::ExitSearchBar => self.search_bar.is_focussed = false,
             Action::ClearSearchBar => self.search_bar.clear(),
             Action::SubmitSearchBar => {
                 return ActionPacket::default()
                     .action(Action::ExitSearchBar)
-                    .action(Action::SwitchContextSearch)
-                    .action(self.search_bar.submit())
+                    .append(self.search_bar.submit())
                     .into()
             }
 
-            Action::LoadPage(title) => self.page_loader.as_ref().unwrap().load_page(title),
+            Action::LoadPage(title) => self.page_loader.as_mut().unwrap().load_page(title),
+            Action::LoadPageInBackground(title) => self
+                .page_loader
+                .as_mut()
+                .unwrap()
+                .load_page_in_background(title),
+            Action::LoadPeek(title) => self.page_loader.as_mut().unwrap().load_peek(title),
+            Action::CheckPageUpdate => {
+                if let Some((title, language, revision)) = self.page.current_page_revision() {
+                    self.page_loader
+                        .as_ref()
+                        .unwrap()
+                        .check_for_update(title, language, revision);
+                }
+            }
+            Action::ReloadPage(title) => self.page_loader.as_mut().unwrap().reload_page(title),
+            Action::LoadSection(title, section) => self
+                .page_loader
+                .as_mut()
+                .unwrap()
+                .load_section(title, section),
+            Action::LoadRedirect(title) => self.page_loader.as_mut().unwrap().load_redirect(title),
+            Action::ToggleOfflineMode => self.page_loader.as_mut().unwrap().toggle_offline(),
+            Action::QueueFetch(title) => self.page_loader.as_mut().unwrap().queue_page(title),
+            Action::FetchQueued => self.page_loader.as_ref().unwrap().fetch_queued(),
+            Action::ExportHar => {
+                if let Err(error) = export_har() {
+                    error!("unable to export the requests: {:?}", error);
+                }
+            }
+            Action::ExportFlashcards(target) => export_flashcards(target),
+            Action::SearchClipboard => {
+                if let Some(ref action_tx) = self.action_tx {
+                    let language = self.page_loader.as_ref().unwrap().language().clone();
+                    search_clipboard(action_tx.clone(), language);
+                }
+            }
+            Action::BugReport(copy) => {
+                match save_bug_report(self.page.current_page_report().as_ref()) {
+                    Ok((_, report)) if copy => run_hook(
+                        Hook::TextYanked,
+                        vec![("text", report), ("page", String::new())],
+                    ),
+                    Ok(_) => {}
+                    Err(error) => error!("unable to save the bug report: {:?}", error),
+                }
+            }
+            Action::ExportEpub(source) => self.page_loader.as_ref().unwrap().export_epub(source),
+            Action::SwitchLanguage(language) => self.switch_language(language),
+            Action::OpenInBrowser(url) => {
+                if let Err(error) = open_url(&url) {
+                    error!("unable to open '{}' in the browser: {:?}", url, error);
+                }
+            }
+            Action::AddBookmark(title, language) => {
+                if bookmarks().add(&title, &language) {
+                    info!("bookmarked '{}'", title);
+                }
+            }
+            Action::QueueReading(title, language) => {
+                if reading_queue().push(&title, &language) {
+                    info!("queued '{}' to be read next", title);
+                }
+            }
+            Action::ReadNext => {
+                let next = reading_queue().pop();
+                match next {
+                    Some(page) => {
+                        info!("reading '{}' from the reading queue", page.title);
+                        return ActionPacket::single(Action::SwitchLanguage(page.language))
+                            .action(Action::LoadPage(page.title))
+                            .into();
+                    }
+                    None => info!("the reading queue is empty"),
+                }
+            }
+            Action::Yank(yank) => yank_ring().yank(yank),
+            // checks for changes finish in the background, even when the list isn't shown
+            Action::Bookmarks(_) => return self.bookmarks.update(action),
+            // pages loaded in the background arrive while another view is shown
+            Action::PageViewer(_) => return self.page.update(action),
             _ => return ActionResult::Ignored,
         };
 
@@ -212,22 +885,58 @@ impl Component for AppComponent {
     }
 
     fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let layout = ResponsiveLayout::new(area);
+        if layout.is_too_small() {
+            render_too_small(f, area);
+            return;
+        }
+
+        let status_height = if layout.shows_status() {
+            STATUS_HEIGHT
+        } else {
+            0
+        };
         let (search_bar_area, area, status_area) = {
             let chunks = Layout::default()
                 .direction(Direction::Vertical)
                 .constraints([
                     Constraint::Min(SEARCH_BAR_HEIGTH),
                     Constraint::Percentage(100),
-                    Constraint::Min(STATUS_HEIGHT),
+                    Constraint::Length(status_height),
                 ])
                 .split(area);
             (chunks[0], chunks[1], chunks[2])
         };
 
-        self.status.render(f, status_area);
+        if layout.shows_status() {
+            self.status.set_page(match self.context {
+                CONTEXT_PAGE => self.page.current_page_status(),
+                _ => None,
+            });
+            self.status.set_task_count(
+                fetch_queue()
+                    .all()
+                    .iter()
+                    .filter(|page| page.status != FetchStatus::Fetched)
+                    .count(),
+            );
+            if let Some(ref page_loader) = self.page_loader {
+                self.status.set_offline(page_loader.is_offline());
+                self.status.set_language(page_loader.language().clone());
+            }
+            self.status.render(f, status_area);
+        }
+
+        if let Some(ref mut config_problems) = self.config_problems {
+            config_problems.render(f, area);
+            self.render_quit(f, area);
+            return;
+        }
 
         if self.is_help {
-            self.help.render(f, centered_rect(area, 30, 50));
+            let help_width = if layout.is_narrow() { 100 } else { 30 };
+            self.help.render(f, centered_rect(area, help_width, 50));
+            self.render_quit(f, area);
             return;
         }
 
@@ -236,7 +945,10 @@ impl Component for AppComponent {
         let area = if self.is_logger {
             let chunks = Layout::default()
                 .direction(Direction::Horizontal)
-                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .constraints([
+                    Constraint::Percentage(100 - self.logger_width),
+                    Constraint::Percentage(self.logger_width),
+                ])
                 .split(area);
             self.logger.render(f, chunks[1]);
             chunks[0]
@@ -247,7 +959,48 @@ impl Component for AppComponent {
         match self.context {
             CONTEXT_SEARCH => self.search.render(f, area),
             CONTEXT_PAGE => self.page.render(f, area),
+            CONTEXT_FEED => self.feed.render(f, area),
+            CONTEXT_NEARBY => self.nearby.render(f, area),
+            CONTEXT_LINK_GRAPH => self.link_graph.render(f, area),
+            CONTEXT_NOTES => self.notes.render(f, area),
+            CONTEXT_HISTORY => self.history.render(f, area),
+            CONTEXT_DISAMBIGUATION => self.disambiguation.render(f, area),
+            CONTEXT_FETCH_QUEUE => self.fetch_queue.render(f, area),
+            CONTEXT_LANGUAGES => self.languages.render(f, area),
+            CONTEXT_PORTALS => self.portals.render(f, area),
+            CONTEXT_BOOKMARKS => self.bookmarks.render(f, area),
+            CONTEXT_READING_QUEUE => self.reading_queue.render(f, area),
+            CONTEXT_CACHE => self.cache.render(f, area),
             _ => warn!("unknown context"),
         }
+
+        if let Some(ref mut yank_ring) = self.yank_ring {
+            let width = if layout.is_narrow() { 100 } else { 60 };
+            yank_ring.render(f, centered_rect(area, width, 50));
+        }
+
+        if let Some(ref mut command_palette) = self.command_palette {
+            let width = if layout.is_narrow() { 100 } else { 60 };
+            command_palette.render(f, centered_rect(area, width, 50));
+        }
+
+        if let Some(ref mut workspaces) = self.workspaces {
+            let width = if layout.is_narrow() { 100 } else { 60 };
+            workspaces.render(f, centered_rect(area, width, 50));
+        }
+
+        if let Some(ref mut context_menu) = self.context_menu {
+            context_menu.render(f, area);
+        }
+
+        if let Some(ref mut api_error) = self.api_error {
+            api_error.render(f, area);
+        }
+
+        if let Some(ref mut setup) = self.setup {
+            setup.render(f, area);
+        }
+
+        self.render_quit(f, area);
     }
 }
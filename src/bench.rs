@@ -0,0 +1,116 @@
+use std::{
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use wiki_api::{
+    document::Document,
+    languages::Language,
+    page::Page,
+    parser::parse_document,
+    transform::{apply_transforms, BuiltinTransform, DocumentTransform, NumberReferences},
+};
+
+use crate::{
+    page_cache::{decode_page, encode_page},
+    renderer::default_renderer::render_document,
+};
+
+/// Widths the document is rendered with when benchmarking
+pub const BENCH_WIDTHS: [u16; 3] = [80, 120, 200];
+
+/// How often each stage is run, the reported time is the average of all runs
+const BENCH_RUNS: u32 = 10;
+
+/// Parses the html into a document, without applying any transforms
+pub fn parse_html(html: &str) -> Document {
+    parse_document(html)
+}
+
+/// Applies the default transforms and numbers the references, just like it's done when loading
+/// a page
+pub fn transform_document(document: Document) -> Document {
+    let document = apply_transforms(document, &BuiltinTransform::defaults());
+    NumberReferences.transform(document)
+}
+
+/// Wraps the document into a page and serializes it the way it's stored in the page cache
+pub fn encode_cached(document: Document) -> Vec<u8> {
+    let page = Page {
+        title: String::new(),
+        pageid: 0,
+        content: document,
+        language: Language::default(),
+        language_links: None,
+        sections: None,
+        revision_id: None,
+        is_disambiguation: false,
+        quality: None,
+        parse_time: None,
+        redirected_from: None,
+    };
+    encode_page(&page).expect("pages can be serialized")
+}
+
+/// Loads a page serialized by [`encode_cached`], just like it's done when opening a cached page
+pub fn decode_cached(bytes: &[u8]) -> Option<Page> {
+    decode_page(bytes).expect("the page was serialized by `encode_cached`")
+}
+
+/// Runs `f` a few times and returns the average duration and the result of the last run
+fn time<T>(mut f: impl FnMut() -> T) -> (Duration, T) {
+    let start = Instant::now();
+    let mut result = f();
+    for _ in 1..BENCH_RUNS {
+        result = f();
+    }
+    (start.elapsed() / BENCH_RUNS, result)
+}
+
+/// Parses, transforms and renders the saved html file and prints the time each stage takes
+pub fn bench_render(path: &Path) -> Result<()> {
+    let html = std::fs::read_to_string(path)
+        .with_context(|| format!("unable to read the html file '{}'", path.display()))?;
+    println!(
+        "wiki-tui BENCH: '{}' ({} bytes, {} runs per stage)",
+        path.display(),
+        html.len(),
+        BENCH_RUNS
+    );
+
+    let (parse_time, document) = time(|| parse_html(&html));
+    println!(
+        "\t parse:     {:>10.3?} ({} nodes)",
+        parse_time,
+        document.nodes.len()
+    );
+
+    let (transform_time, document) = time(|| transform_document(document.clone()));
+    println!(
+        "\t transform: {:>10.3?} ({} nodes)",
+        transform_time,
+        document.nodes.len()
+    );
+
+    let bytes = encode_cached(document.clone());
+    let (cache_time, _) = time(|| decode_cached(&bytes));
+    println!(
+        "\t cached:    {:>10.3?} ({} bytes, {:.1}x faster than parse and transform)",
+        cache_time,
+        bytes.len(),
+        (parse_time + transform_time).as_secs_f64() / cache_time.as_secs_f64().max(f64::EPSILON)
+    );
+
+    for width in BENCH_WIDTHS {
+        let (render_time, rendered) = time(|| render_document(&document, width));
+        println!(
+            "\t render {:>3}: {:>9.3?} ({} lines)",
+            width,
+            render_time,
+            rendered.lines.len()
+        );
+    }
+
+    Ok(())
+}
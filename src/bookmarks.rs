@@ -0,0 +1,194 @@
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Mutex, MutexGuard, OnceLock},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+use wiki_api::{languages::Language, page::Page};
+
+use crate::config::data_dir;
+
+const BOOKMARKS_FILE: &str = "bookmarks.json";
+
+static BOOKMARKS: OnceLock<Mutex<Bookmarks>> = OnceLock::new();
+
+/// A bookmarked page, together with the revisions needed to tell whether it changed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub title: String,
+    pub language: Language,
+    /// Unix timestamp of when the page was bookmarked
+    pub added: i64,
+    /// Revision of the page when it was last read
+    pub read_revision: Option<usize>,
+    /// Latest revision of the page found by the last check
+    #[serde(default)]
+    pub latest_revision: Option<usize>,
+}
+
+impl Bookmark {
+    /// Whether the page changed since it was last read
+    pub fn is_updated(&self) -> bool {
+        match (self.read_revision, self.latest_revision) {
+            (Some(read), Some(latest)) => latest != read,
+            _ => false,
+        }
+    }
+}
+
+/// Bookmarked pages, stored in the data directory
+#[derive(Default)]
+pub struct Bookmarks {
+    bookmarks: Vec<Bookmark>,
+}
+
+impl Bookmarks {
+    fn path() -> Result<PathBuf> {
+        Ok(data_dir()?.join(BOOKMARKS_FILE))
+    }
+
+    fn load() -> Result<Self> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        info!("loading the bookmarks from '{}'", path.display());
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("unable to read the bookmarks '{}'", path.display()))?;
+        let bookmarks = serde_json::from_str(&contents)
+            .with_context(|| format!("unable to parse the bookmarks '{}'", path.display()))?;
+        Ok(Self { bookmarks })
+    }
+
+    fn save(&self) {
+        let result = Self::path().and_then(|path| {
+            std::fs::create_dir_all(data_dir()?)?;
+            std::fs::write(&path, serde_json::to_string_pretty(&self.bookmarks)?)
+                .with_context(|| format!("unable to write the bookmarks '{}'", path.display()))
+        });
+
+        if let Err(error) = result {
+            error!("unable to save the bookmarks: {:?}", error);
+        }
+    }
+
+    pub fn all(&self) -> &[Bookmark] {
+        &self.bookmarks
+    }
+
+    fn position(&self, title: &str, language: &Language) -> Option<usize> {
+        self.bookmarks
+            .iter()
+            .position(|bookmark| bookmark.title == title && &bookmark.language == language)
+    }
+
+    /// Bookmarks the page as read, or removes its bookmark. Returns whether the page is
+    /// bookmarked now
+    pub fn toggle(&mut self, page: &Page) -> bool {
+        let is_bookmarked = match self.position(&page.title, &page.language) {
+            Some(position) => {
+                self.bookmarks.remove(position);
+                false
+            }
+            None => {
+                self.bookmarks.push(Bookmark {
+                    title: page.title.clone(),
+                    language: page.language.clone(),
+                    added: chrono::Utc::now().timestamp(),
+                    read_revision: page.revision_id,
+                    latest_revision: page.revision_id,
+                });
+                true
+            }
+        };
+        self.save();
+        is_bookmarked
+    }
+
+    /// Bookmarks the page without a read revision, it's marked as read once it's opened.
+    /// Returns false when the page is already bookmarked
+    pub fn add(&mut self, title: &str, language: &Language) -> bool {
+        if self.position(title, language).is_some() {
+            return false;
+        }
+        self.bookmarks.push(Bookmark {
+            title: title.to_string(),
+            language: language.clone(),
+            added: chrono::Utc::now().timestamp(),
+            read_revision: None,
+            latest_revision: None,
+        });
+        self.save();
+        true
+    }
+
+    /// Remembers the revision of the page as read, when the page is bookmarked
+    pub fn mark_read(&mut self, page: &Page) {
+        let revision = match page.revision_id {
+            Some(revision) => revision,
+            None => return,
+        };
+        if let Some(position) = self.position(&page.title, &page.language) {
+            let bookmark = &mut self.bookmarks[position];
+            bookmark.read_revision = Some(revision);
+            // a revision newer than the one found by the last check was read
+            match bookmark.latest_revision {
+                Some(latest) if latest >= revision => {}
+                _ => bookmark.latest_revision = Some(revision),
+            }
+            self.save();
+        }
+    }
+
+    /// Stores the latest revisions of the bookmarked pages in the language
+    pub fn set_latest_revisions(
+        &mut self,
+        language: &Language,
+        revisions: &HashMap<String, usize>,
+    ) {
+        for bookmark in self
+            .bookmarks
+            .iter_mut()
+            .filter(|bookmark| &bookmark.language == language)
+        {
+            if let Some(revision) = revisions.get(&bookmark.title) {
+                bookmark.latest_revision = Some(*revision);
+            }
+        }
+        self.save();
+    }
+
+    /// Removes the bookmark of the page, returns it when the page was bookmarked
+    pub fn remove(&mut self, title: &str, language: &Language) -> Option<Bookmark> {
+        let position = self.position(title, language)?;
+        let bookmark = self.bookmarks.remove(position);
+        self.save();
+        Some(bookmark)
+    }
+
+    /// Adds a removed bookmark again, unless the page was bookmarked since
+    pub fn restore(&mut self, bookmark: Bookmark) {
+        if self.position(&bookmark.title, &bookmark.language).is_some() {
+            return;
+        }
+        self.bookmarks.push(bookmark);
+        self.save();
+    }
+}
+
+/// Returns the stored bookmarks, loading them on first use
+pub fn bookmarks() -> MutexGuard<'static, Bookmarks> {
+    BOOKMARKS
+        .get_or_init(|| {
+            Mutex::new(Bookmarks::load().unwrap_or_else(|error| {
+                error!("unable to load the bookmarks: {:?}", error);
+                Bookmarks::default()
+            }))
+        })
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
@@ -0,0 +1,45 @@
+use std::process::Stdio;
+
+use anyhow::{Context, Result};
+use tokio::process::Command;
+use tracing::{info, warn};
+
+/// Command opening an url with the default application of the platform
+fn opener() -> Command {
+    if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        // the empty argument is the title of the window, otherwise a quoted url would be used
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        Command::new("xdg-open")
+    }
+}
+
+/// Opens the url in the default browser. Returns an error when the browser could not be started
+///
+/// The browser keeps running in the background, failures after it started are only logged
+pub fn open_url(url: &str) -> Result<()> {
+    let mut child = opener()
+        .arg(url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("failed starting the browser")?;
+    info!("opening '{}' in the browser", url);
+
+    let url = url.to_string();
+    tokio::spawn(async move {
+        match child.wait().await {
+            Ok(status) if !status.success() => {
+                warn!("the browser failed opening '{}' with {}", url, status)
+            }
+            Ok(_) => {}
+            Err(error) => warn!("unable to wait for the browser: {:?}", error),
+        }
+    });
+    Ok(())
+}
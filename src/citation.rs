@@ -0,0 +1,272 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::{Datelike, NaiveDate};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::info;
+use url::Url;
+use wiki_api::page::Page;
+
+use crate::export::article_url;
+
+const AUTHOR: &str = "Wikipedia contributors";
+const PUBLISHER: &str = "Wikipedia, The Free Encyclopedia";
+
+/// How a citation of a page is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CitationFormat {
+    /// A sentence like the one of the "Cite this page" tool of Wikipedia
+    Plain,
+    /// A `@misc` entry for LaTeX documents
+    Bibtex,
+    /// A CSL-JSON item, which reference managers like Zotero import
+    CslJson,
+}
+
+/// Returns the url of the revision of the page, which keeps showing the cited text after the
+/// page was edited. Pages without a known revision are linked to by their title
+pub fn permanent_url(page: &Page) -> String {
+    let language_code = page.language.code();
+    let revision = match page.revision_id {
+        Some(revision) => revision,
+        None => return article_url(language_code, &page.title),
+    };
+    let index = format!("https://{}.wikipedia.org/w/index.php", language_code);
+    Url::parse_with_params(
+        &index,
+        &[
+            ("title", page.title.replace(' ', "_")),
+            ("oldid", revision.to_string()),
+        ],
+    )
+    .map(String::from)
+    .unwrap_or(index)
+}
+
+/// Key of the page in a bibliography, like `wiki:Rust_(programming_language)`
+pub fn citation_key(page: &Page) -> String {
+    let title: String = page
+        .title
+        .chars()
+        .map(|c| match c {
+            ' ' | ',' | '{' | '}' | '"' | '#' | '%' | '~' | '\\' => '_',
+            c => c,
+        })
+        .collect();
+    format!("wiki:{}", title)
+}
+
+/// Escapes the characters with a special meaning in BibTeX values
+fn bibtex_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '&' | '%' | '$' | '#' | '_' | '{' | '}') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn csl_item(page: &Page, accessed: NaiveDate) -> Value {
+    let mut item = json!({
+        "id": citation_key(page),
+        "type": "entry-encyclopedia",
+        "title": page.title,
+        "container-title": PUBLISHER,
+        "author": [{ "literal": AUTHOR }],
+        "language": page.language.code(),
+        "URL": permanent_url(page),
+        "accessed": {
+            "date-parts": [[accessed.year(), accessed.month(), accessed.day()]]
+        },
+    });
+    if let Some(revision) = page.revision_id {
+        item["version"] = json!(revision.to_string());
+    }
+    item
+}
+
+/// Writes a citation of the page, which was read on the `accessed` date
+pub fn cite(page: &Page, format: CitationFormat, accessed: NaiveDate) -> String {
+    let url = permanent_url(page);
+    match format {
+        CitationFormat::Plain => {
+            let revision = page
+                .revision_id
+                .map(|revision| format!(" Revision {}.", revision))
+                .unwrap_or_default();
+            format!(
+                "{}. \"{}.\" {}.{} {} (accessed {}).",
+                AUTHOR,
+                page.title,
+                PUBLISHER,
+                revision,
+                url,
+                accessed.format("%B %-d, %Y")
+            )
+        }
+        CitationFormat::Bibtex => {
+            let fields = [
+                format!("author = \"{{{}}}\"", AUTHOR),
+                format!(
+                    "title = \"{} --- {{Wikipedia}}{{,}} The Free Encyclopedia\"",
+                    bibtex_escape(&page.title)
+                ),
+                format!("year = \"{}\"", accessed.year()),
+                format!("howpublished = \"\\url{{{}}}\"", url),
+                format!(
+                    "note = \"[Online; accessed {}]\"",
+                    accessed.format("%-d-%B-%Y")
+                ),
+            ];
+            format!(
+                "@misc{{{},\n  {}\n}}",
+                citation_key(page),
+                fields.join(",\n  ")
+            )
+        }
+        CitationFormat::CslJson => serde_json::to_string_pretty(&csl_item(page, accessed))
+            .expect("a json value can always be serialized"),
+    }
+}
+
+/// Adds the citation of the page to the bibliography. A `.json` file is a CSL-JSON list of
+/// items, any other file is a BibTeX database. A page that is already in the bibliography is
+/// replaced in a CSL-JSON list and left alone in a BibTeX database
+pub fn add_to_bibliography(path: &Path, page: &Page, accessed: NaiveDate) -> Result<()> {
+    let existing = match path.exists() {
+        true => std::fs::read_to_string(path)
+            .with_context(|| format!("unable to read the bibliography '{}'", path.display()))?,
+        false => String::new(),
+    };
+
+    let contents = if path
+        .extension()
+        .is_some_and(|extension| extension == "json")
+    {
+        let mut items: Vec<Value> = match existing.trim().is_empty() {
+            true => Vec::new(),
+            false => serde_json::from_str(&existing).with_context(|| {
+                format!(
+                    "the bibliography '{}' is not a CSL-JSON list",
+                    path.display()
+                )
+            })?,
+        };
+        let item = csl_item(page, accessed);
+        items.retain(|other| other.get("id") != item.get("id"));
+        items.push(item);
+        serde_json::to_string_pretty(&items)? + "\n"
+    } else {
+        if existing.contains(&format!("{{{},", citation_key(page))) {
+            info!("'{}' is already in the bibliography", page.title);
+            return Ok(());
+        }
+        let separator = match existing.is_empty() || existing.ends_with("\n\n") {
+            true => "",
+            false if existing.ends_with('\n') => "\n",
+            false => "\n\n",
+        };
+        format!(
+            "{}{}{}\n",
+            existing,
+            separator,
+            cite(page, CitationFormat::Bibtex, accessed)
+        )
+    };
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, contents)
+        .with_context(|| format!("unable to write the bibliography '{}'", path.display()))?;
+    info!("added '{}' to '{}'", page.title, path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use wiki_api::{languages::Language, page::Page};
+
+    use super::{add_to_bibliography, cite, CitationFormat};
+    use crate::bench::parse_html;
+
+    fn page() -> Page {
+        Page {
+            title: "Rust (programming language)".to_string(),
+            pageid: 29414838,
+            content: parse_html("<p>Text</p>"),
+            language: Language::English,
+            language_links: None,
+            sections: None,
+            revision_id: Some(1187654321),
+            is_disambiguation: false,
+            quality: None,
+            parse_time: None,
+            redirected_from: None,
+        }
+    }
+
+    fn accessed() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, 7).unwrap()
+    }
+
+    #[test]
+    fn formats() {
+        let url = concat!(
+            "https://en.wikipedia.org/w/index.php",
+            "?title=Rust_%28programming_language%29&oldid=1187654321"
+        );
+
+        assert_eq!(
+            cite(&page(), CitationFormat::Plain, accessed()),
+            format!(
+                "Wikipedia contributors. \"Rust (programming language).\" Wikipedia, The Free \
+                 Encyclopedia. Revision 1187654321. {url} (accessed March 7, 2024)."
+            )
+        );
+        assert_eq!(
+            cite(&page(), CitationFormat::Bibtex, accessed()),
+            format!(
+                "@misc{{wiki:Rust_(programming_language),\n  \
+                 author = \"{{Wikipedia contributors}}\",\n  \
+                 title = \"Rust (programming language) --- {{Wikipedia}}{{,}} The Free \
+                 Encyclopedia\",\n  \
+                 year = \"2024\",\n  \
+                 howpublished = \"\\url{{{url}}}\",\n  \
+                 note = \"[Online; accessed 7-March-2024]\"\n}}"
+            )
+        );
+
+        let item: serde_json::Value =
+            serde_json::from_str(&cite(&page(), CitationFormat::CslJson, accessed())).unwrap();
+        assert_eq!(item["URL"], url);
+        assert_eq!(item["version"], "1187654321");
+        assert_eq!(item["accessed"]["date-parts"][0][2], 7);
+    }
+
+    #[test]
+    fn bibliography() {
+        let directory =
+            std::env::temp_dir().join(format!("wiki-tui-bibliography-{}", std::process::id()));
+        let bibtex = directory.join("references.bib");
+        let csl = directory.join("references.json");
+
+        for _ in 0..2 {
+            add_to_bibliography(&bibtex, &page(), accessed()).unwrap();
+            add_to_bibliography(&csl, &page(), accessed()).unwrap();
+        }
+
+        let bibtex = std::fs::read_to_string(bibtex).unwrap();
+        assert_eq!(bibtex.matches("@misc{").count(), 1);
+        let items: Vec<serde_json::Value> =
+            serde_json::from_str(&std::fs::read_to_string(csl).unwrap()).unwrap();
+        assert_eq!(items.len(), 1);
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -1,6 +1,20 @@
+use std::path::{Path, PathBuf};
+
 use clap::{Args, Parser, Subcommand};
 
-use crate::action::{Action, ActionPacket, SearchAction};
+use crate::{
+    action::{Action, ActionPacket, SearchAction},
+    config::{config, load_config, TextLinks},
+    cookies::initialize_cookies,
+    export::{export_dump, DumpFormat},
+    page_loader::load_page_once,
+    remote::send_command,
+    sources::set_zim_override,
+    startup::{
+        enable_startup_profile, format_startup_profile, is_profiling_startup, startup_phase,
+        startup_phases,
+    },
+};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -10,6 +24,35 @@ struct Cli {
     #[arg(value_name = "QUERY")]
     search_query: Option<String>,
 
+    /// Parse and render a saved html file, printing the time each stage takes
+    #[arg(long, value_name = "FILE", hide = true)]
+    bench_render: Option<PathBuf>,
+
+    /// Print an article as plain text and exit
+    #[arg(long, value_name = "TITLE")]
+    dump: Option<String>,
+
+    /// Format the article is printed in with --dump, defaults to plain text
+    #[arg(long, value_name = "FORMAT", requires = "dump")]
+    dump_format: Option<DumpFormat>,
+
+    /// How links are written with --dump, defaults to `export.text_links` of the config
+    #[arg(long, value_name = "STYLE", requires = "dump")]
+    links: Option<TextLinks>,
+
+    /// Print the time each step of the startup takes once wiki-tui started, and quit
+    #[arg(long)]
+    profile_startup: bool,
+
+    /// Send a command to the running wiki-tui through the remote socket, like `open "Rust"`, and
+    /// print its answer
+    #[arg(long, value_name = "COMMAND")]
+    remote: Option<String>,
+
+    /// Search and read pages from a ZIM archive (Kiwix) instead of the wiki, without network
+    #[arg(long, value_name = "FILE")]
+    zim: Option<PathBuf>,
+
     #[command(subcommand)]
     commands: Option<Commands>,
 }
@@ -29,6 +72,26 @@ struct DebugCommand {
 pub fn match_cli() -> Option<ActionPacket> {
     let cli = Cli::parse();
 
+    if cli.profile_startup {
+        enable_startup_profile();
+    }
+
+    if let Some(path) = cli.zim {
+        command_zim(path);
+    }
+
+    if let Some(ref path) = cli.bench_render {
+        command_bench_render(path);
+    }
+
+    if let Some(ref command) = cli.remote {
+        command_remote(command);
+    }
+
+    if let Some(ref title) = cli.dump {
+        command_dump(title, cli.dump_format.unwrap_or_default(), cli.links);
+    }
+
     let mut packet = ActionPacket::default();
 
     if let Some(search_query) = cli.search_query {
@@ -45,6 +108,81 @@ pub fn match_cli() -> Option<ActionPacket> {
     Some(packet)
 }
 
+fn command_zim(path: PathBuf) {
+    if !path.is_file() {
+        eprintln!(
+            "wiki-tui ZIM: the archive '{}' doesn't exist",
+            path.display()
+        );
+        std::process::exit(libc::EXIT_FAILURE)
+    }
+    set_zim_override(path);
+}
+
+fn command_bench_render(path: &Path) {
+    match crate::bench::bench_render(path) {
+        Ok(()) => std::process::exit(libc::EXIT_SUCCESS),
+        Err(error) => {
+            eprintln!("wiki-tui BENCH: {:?}", error);
+            std::process::exit(libc::EXIT_FAILURE)
+        }
+    }
+}
+
+fn command_dump(title: &str, format: DumpFormat, links: Option<TextLinks>) {
+    let result = startup_phase("config", load_config).and_then(|_| {
+        startup_phase("cookies", initialize_cookies);
+        // the runtime of the app is already running, but nothing else is
+        startup_phase("page", || {
+            tokio::task::block_in_place(|| {
+                tokio::runtime::Handle::current().block_on(load_page_once(title))
+            })
+        })
+    });
+
+    match result {
+        Ok(page) => {
+            let links = links.unwrap_or(config().export.text_links);
+            let footnotes = config().export.footnotes;
+            print!(
+                "{}",
+                export_dump(&page, format, links, footnotes, chrono::Utc::now())
+            );
+            // the article is printed to stdout, the profile mustn't end up in it
+            if is_profiling_startup() {
+                eprint!("{}", format_startup_profile(&startup_phases()));
+            }
+            std::process::exit(libc::EXIT_SUCCESS)
+        }
+        Err(error) => {
+            eprintln!("wiki-tui DUMP: {:?}", error);
+            std::process::exit(libc::EXIT_FAILURE)
+        }
+    }
+}
+
+fn command_remote(command: &str) {
+    let result = load_config().and_then(|_| {
+        tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current().block_on(send_command(command))
+        })
+    });
+
+    match result {
+        Ok(reply) => {
+            println!("{}", reply);
+            match reply.as_str() {
+                "ok" => std::process::exit(libc::EXIT_SUCCESS),
+                _ => std::process::exit(libc::EXIT_FAILURE),
+            }
+        }
+        Err(error) => {
+            eprintln!("wiki-tui REMOTE: {:?}", error);
+            std::process::exit(libc::EXIT_FAILURE)
+        }
+    }
+}
+
 fn command_debug(command: &DebugCommand) {
     println!("wiki-tui DEBUG: Debug Information");
 
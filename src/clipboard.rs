@@ -0,0 +1,238 @@
+use std::{process::Stdio, time::Duration};
+
+use anyhow::{bail, Context, Result};
+use tokio::{process::Command, sync::mpsc::UnboundedSender};
+use tracing::{error, info};
+use url::Url;
+use wiki_api::languages::Language;
+
+use crate::{
+    action::{Action, SearchAction},
+    config::config,
+};
+
+/// How long reading the clipboard may take, the command is killed afterwards
+const PASTE_TIMEOUT: Duration = Duration::from_secs(2);
+/// Titles are at most 255 bytes long, longer text can't be one and is cut
+const MAX_QUERY_CHARS: usize = 255;
+/// Quotes the copied text is often wrapped in
+const QUOTES: [char; 10] = ['"', '\'', '`', '“', '”', '„', '‘', '’', '«', '»'];
+
+/// What the text of the clipboard is looked up as
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Pasted {
+    /// A link to an article, it's opened directly. The language is the one of the Wikipedia
+    /// edition the link points to
+    Title {
+        title: String,
+        language: Option<Language>,
+    },
+    /// Any other text, it's searched for
+    Query(String),
+}
+
+/// Command printing the text of the clipboard, unless `behavior.paste_command` is configured
+fn default_paste_command() -> Vec<String> {
+    let command: &[&str] = if cfg!(target_os = "macos") {
+        &["pbpaste"]
+    } else if cfg!(windows) {
+        &["powershell", "-NoProfile", "-Command", "Get-Clipboard"]
+    } else if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        &["wl-paste", "--no-newline"]
+    } else {
+        &["xclip", "-selection", "clipboard", "-o"]
+    };
+    command.iter().map(|arg| arg.to_string()).collect()
+}
+
+/// Runs the paste command and returns what it printed
+///
+/// # Error
+///
+/// This function returns an error when one of the following things happens:
+/// - the command couldn't be started, like when it isn't installed
+/// - the command didn't finish in time or failed
+pub async fn read_clipboard() -> Result<String> {
+    let mut command = config().behavior.paste_command.clone();
+    if command.is_empty() {
+        command = default_paste_command();
+    }
+
+    let child = Command::new(&command[0])
+        .args(&command[1..])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("failed starting '{}'", command[0]))?;
+    let output = tokio::time::timeout(PASTE_TIMEOUT, child.wait_with_output())
+        .await
+        .with_context(|| format!("'{}' didn't finish in time", command[0]))?
+        .with_context(|| format!("failed running '{}'", command[0]))?;
+    if !output.status.success() {
+        bail!("'{}' failed with {}", command[0], output.status);
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Decodes the escaped characters of an url path, like `%C3%A9` for `é`
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Returns the article a Wikipedia url points to, both `/wiki/<title>` and
+/// `/w/index.php?title=<title>` urls of the desktop and mobile site are understood
+fn wikipedia_title(text: &str) -> Option<Pasted> {
+    let url = Url::parse(text).ok()?;
+    let subdomain = url.host_str()?.strip_suffix(".wikipedia.org")?;
+    let title = match url.path().strip_prefix("/wiki/") {
+        Some(title) => percent_decode(title),
+        None => url
+            .query_pairs()
+            .find(|(key, _)| key == "title")
+            .map(|(_, title)| title.into_owned())?,
+    };
+
+    let title = title.replace('_', " ").trim().to_string();
+    if title.is_empty() {
+        return None;
+    }
+    // the mobile site is `<language>.m.wikipedia.org`
+    let language = subdomain.split('.').next().and_then(Language::find);
+    Some(Pasted::Title { title, language })
+}
+
+/// Cleans up the text of the clipboard: only its first line is used, without the surrounding
+/// whitespace and quotes. A link to a Wikipedia article is turned into its title. Returns `None`
+/// when nothing is left
+pub fn clean_pasted(text: &str) -> Option<Pasted> {
+    let line = text.lines().map(str::trim).find(|line| !line.is_empty())?;
+    if let Some(pasted) = wikipedia_title(line) {
+        return Some(pasted);
+    }
+
+    let query: String = line
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_matches(|c: char| QUOTES.contains(&c) || c.is_whitespace())
+        .chars()
+        .take(MAX_QUERY_CHARS)
+        .collect();
+    if query.is_empty() {
+        return None;
+    }
+    Some(Pasted::Query(query))
+}
+
+/// Returns the actions looking up the text: opening the article it links to, in the language of
+/// the link, or searching for it
+fn lookup_actions(pasted: Pasted, language: &Language) -> Vec<Action> {
+    match pasted {
+        Pasted::Title {
+            title,
+            language: Some(link_language),
+        } if &link_language != language => {
+            vec![
+                Action::SwitchLanguage(link_language),
+                Action::LoadPage(title),
+            ]
+        }
+        Pasted::Title { title, .. } => vec![Action::LoadPage(title)],
+        Pasted::Query(query) => vec![
+            Action::SwitchContextSearch,
+            Action::Search(SearchAction::StartSearch(query)),
+        ],
+    }
+}
+
+/// Reads the clipboard in the background and looks up its text. Pages are searched and opened in
+/// the language, unless the clipboard links to a page in another one
+pub fn search_clipboard(action_tx: UnboundedSender<Action>, language: Language) {
+    tokio::spawn(async move {
+        let text = match read_clipboard().await {
+            Ok(text) => text,
+            Err(error) => return error!("unable to read the clipboard: {:?}", error),
+        };
+        let pasted = match clean_pasted(&text) {
+            Some(pasted) => pasted,
+            None => return info!("the clipboard contains no text to look up"),
+        };
+
+        info!("looking up the clipboard: {:?}", pasted);
+        for action in lookup_actions(pasted, &language) {
+            if action_tx.send(action).is_err() {
+                return;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use wiki_api::languages::Language;
+
+    use super::{clean_pasted, Pasted};
+
+    fn title(title: &str, language: &str) -> Option<Pasted> {
+        Some(Pasted::Title {
+            title: title.to_string(),
+            language: Language::find(language),
+        })
+    }
+
+    #[test]
+    fn clean() {
+        assert_eq!(
+            clean_pasted("  \n “Red  fox”\nsecond line"),
+            Some(Pasted::Query("Red fox".to_string()))
+        );
+        assert_eq!(clean_pasted(" \n\t"), None);
+        assert_eq!(clean_pasted("\"\""), None);
+        assert_eq!(
+            clean_pasted("https://example.com/wiki/Fox"),
+            Some(Pasted::Query("https://example.com/wiki/Fox".to_string()))
+        );
+    }
+
+    #[test]
+    fn wikipedia_urls() {
+        assert_eq!(
+            clean_pasted("https://en.wikipedia.org/wiki/Red_fox#Diet\n"),
+            title("Red fox", "en")
+        );
+        assert_eq!(
+            clean_pasted("https://de.m.wikipedia.org/wiki/Stra%C3%9Fe"),
+            title("Straße", "de")
+        );
+        assert_eq!(
+            clean_pasted("https://fr.wikipedia.org/w/index.php?title=Renard_roux&action=history"),
+            title("Renard roux", "fr")
+        );
+        assert_eq!(
+            clean_pasted("https://en.wikipedia.org/wiki/C++"),
+            title("C++", "en")
+        );
+    }
+}
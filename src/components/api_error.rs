@@ -0,0 +1,144 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::Rect,
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
+};
+use wiki_api::error::ApiError;
+
+use crate::{
+    action::{Action, ActionPacket, ActionResult, FailedRequest, SearchAction},
+    i18n::{tr, tr_args},
+    terminal::Frame,
+    ui::centered_rect,
+};
+
+use super::Component;
+
+const DIALOG_WIDTH: u16 = 64;
+const DIALOG_HEIGHT: u16 = 12;
+
+/// Explains why a request to the wiki failed with an error of the API and what can be done about
+/// it: retrying temporary errors after a while, searching for a page that doesn't exist or
+/// logging in again
+pub struct ApiErrorComponent {
+    request: FailedRequest,
+    error: ApiError,
+}
+
+impl ApiErrorComponent {
+    pub fn new(request: FailedRequest, error: ApiError) -> Self {
+        Self { request, error }
+    }
+
+    /// Returns the actions sending the failed request again
+    fn retry(&self) -> ActionPacket {
+        let packet = ActionPacket::single(Action::CloseApiError);
+        match self.request {
+            FailedRequest::Page(ref title) => packet.action(Action::LoadPage(title.clone())),
+            FailedRequest::Search(ref query) => packet
+                .action(Action::SwitchContextSearch)
+                .action(Action::Search(SearchAction::StartSearch(query.clone()))),
+        }
+    }
+
+    /// Returns the actions searching for the title of the missing page
+    fn search_missing(&self) -> ActionResult {
+        match self.request {
+            FailedRequest::Page(ref title) => ActionPacket::single(Action::CloseApiError)
+                .action(Action::SwitchContextSearch)
+                .action(Action::Search(SearchAction::StartSearch(title.clone())))
+                .into(),
+            FailedRequest::Search(_) => ActionResult::consumed(),
+        }
+    }
+
+    /// Returns what the user can do about the error
+    fn guidance(&self) -> String {
+        match self.error {
+            ApiError::Lagged { .. } => tr("api_error.lagged").to_string(),
+            ApiError::ReadOnly { ref reason } => tr_args(
+                "api_error.read_only",
+                &[("reason", reason.clone().unwrap_or_default())],
+            ),
+            ApiError::MissingTitle => tr("api_error.missing_title").to_string(),
+            ApiError::RateLimited { .. } => tr("api_error.rate_limited").to_string(),
+            ApiError::BadToken => tr("api_error.bad_token").to_string(),
+            ApiError::Other { ref info, .. } => {
+                tr_args("api_error.other", &[("info", info.clone())])
+            }
+        }
+    }
+
+    fn keys(&self) -> &'static str {
+        if self.error.is_temporary() {
+            return tr("api_error.keys_retry");
+        }
+        match (&self.error, &self.request) {
+            (ApiError::MissingTitle, FailedRequest::Page(_)) => tr("api_error.keys_search"),
+            _ => tr("api_error.keys_close"),
+        }
+    }
+
+    fn lines(&self) -> Vec<Line<'static>> {
+        let request = match self.request {
+            FailedRequest::Page(ref title) => {
+                tr_args("api_error.page", &[("title", title.clone())])
+            }
+            FailedRequest::Search(ref query) => {
+                tr_args("api_error.search", &[("query", query.clone())])
+            }
+        };
+
+        let mut lines = vec![
+            Line::from(Span::raw(request).bold()),
+            Line::from(self.error.to_string().red()),
+            Line::default(),
+            Line::from(self.guidance()),
+        ];
+        if let Some(seconds) = self.error.retry_after() {
+            lines.push(Line::from(
+                tr_args("api_error.retry_after", &[("seconds", seconds.to_string())]).yellow(),
+            ));
+        }
+        lines.push(Line::default());
+        lines.push(Line::from(self.keys().dark_gray()));
+        lines
+    }
+}
+
+impl Component for ApiErrorComponent {
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        match key.code {
+            KeyCode::Char('r') if self.error.is_temporary() => self.retry().into(),
+            KeyCode::Char('s') if self.error == ApiError::MissingTitle => self.search_missing(),
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => Action::CloseApiError.into(),
+            // the dialog is modal
+            _ => ActionResult::consumed(),
+        }
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let height = DIALOG_HEIGHT.min(area.height);
+        let area = Rect {
+            y: area.y + (area.height - height) / 2,
+            height,
+            ..centered_rect(area, DIALOG_WIDTH, 100)
+        };
+
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Paragraph::new(self.lines())
+                .wrap(Wrap { trim: false })
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_type(BorderType::Rounded)
+                        .border_style(Style::default().fg(Color::Red))
+                        .title(tr("api_error.title")),
+                ),
+            area,
+        );
+    }
+}
@@ -0,0 +1,279 @@
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Borders, HighlightSpacing, List, ListItem, ListState, Paragraph},
+};
+use tokio::sync::mpsc;
+use tracing::{error, warn};
+use wiki_api::{
+    languages::Language,
+    revisions::{diff_url, fetch_latest_revisions},
+};
+
+use crate::{
+    action::{Action, ActionPacket, ActionResult, BookmarksAction},
+    bookmarks::{bookmarks, Bookmark},
+    browser::open_url,
+    history::history,
+    i18n::{tr, tr_args},
+    key_event,
+    renderer::epub::EpubSource,
+    terminal::Frame,
+    ui::centered_rect,
+    undo::{record, Undoable},
+};
+
+use super::{history::progress_span, Component};
+
+/// How long the revisions of the bookmarked pages are considered up to date. Opening the list
+/// after that checks them again
+const CHECK_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// Lists the bookmarked pages and marks the ones that changed since they were last read
+#[derive(Default)]
+pub struct BookmarksComponent {
+    state: ListState,
+    /// Languages whose pages are currently checked for changes
+    checking: usize,
+    last_check: Option<Instant>,
+
+    action_tx: Option<mpsc::UnboundedSender<Action>>,
+}
+
+impl BookmarksComponent {
+    /// Whether the last check is too long ago, or there wasn't one yet
+    pub fn needs_check(&self) -> bool {
+        self.checking == 0
+            && self
+                .last_check
+                .map(|last_check| last_check.elapsed() >= CHECK_INTERVAL)
+                .unwrap_or(true)
+    }
+
+    fn selected_bookmark(&self) -> Option<Bookmark> {
+        let selected = self.state.selected()?;
+        bookmarks().all().get(selected).cloned()
+    }
+
+    fn select(&mut self, offset: isize) {
+        let len = bookmarks().all().len();
+        if len == 0 {
+            return self.state.select(None);
+        }
+
+        let selected = match self.state.selected() {
+            Some(selected) => (selected as isize + offset).clamp(0, len as isize - 1) as usize,
+            None => 0,
+        };
+        self.state.select(Some(selected));
+    }
+
+    fn open_selected(&self) -> ActionResult {
+        match self.selected_bookmark() {
+            Some(bookmark) => ActionPacket::single(Action::SwitchLanguage(bookmark.language))
+                .action(Action::LoadPage(bookmark.title))
+                .into(),
+            None => ActionResult::Ignored,
+        }
+    }
+
+    fn remove_selected(&mut self) {
+        if let Some(bookmark) = self.selected_bookmark() {
+            let removed = bookmarks().remove(&bookmark.title, &bookmark.language);
+            if let Some(removed) = removed {
+                record(Undoable::Bookmark(removed));
+            }
+            self.select(0);
+        }
+    }
+
+    fn show_selected_changes(&self) {
+        let bookmark = match self.selected_bookmark() {
+            Some(bookmark) if bookmark.is_updated() => bookmark,
+            _ => return,
+        };
+        // updated pages always have a read revision
+        let revision = bookmark.read_revision.unwrap_or_default();
+        let result = diff_url(
+            &bookmark.language.wikipedia_endpoint(),
+            &bookmark.title,
+            revision,
+        )
+        .and_then(|url| open_url(url.as_str()));
+        if let Err(error) = result {
+            error!(
+                "unable to show the changes of '{}': {:?}",
+                bookmark.title, error
+            );
+        }
+    }
+
+    /// Fetches the latest revisions of the bookmarked pages in the background, one request per
+    /// language
+    fn check_updates(&mut self) {
+        let mut titles: BTreeMap<String, (Language, Vec<String>)> = BTreeMap::new();
+        for bookmark in bookmarks().all() {
+            titles
+                .entry(bookmark.language.code().to_string())
+                .or_insert_with(|| (bookmark.language.clone(), Vec::new()))
+                .1
+                .push(bookmark.title.clone());
+        }
+
+        self.last_check = Some(Instant::now());
+        let tx = self.action_tx.clone().unwrap();
+        for (language, titles) in titles.into_values() {
+            self.checking += 1;
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                match fetch_latest_revisions(language.wikipedia_endpoint(), &titles).await {
+                    Ok(revisions) => tx
+                        .send(Action::Bookmarks(BookmarksAction::FinishCheck(
+                            language, revisions,
+                        )))
+                        .unwrap(),
+                    Err(error) => {
+                        warn!("unable to check the bookmarks for changes: {:?}", error);
+                        tx.send(Action::Bookmarks(BookmarksAction::AbortCheck))
+                            .unwrap();
+                    }
+                }
+            });
+        }
+    }
+}
+
+impl Component for BookmarksComponent {
+    fn init(&mut self, action_tx: mpsc::UnboundedSender<Action>) -> anyhow::Result<()> {
+        self.action_tx = Some(action_tx);
+        Ok(())
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        match key.code {
+            KeyCode::Enter => Action::Bookmarks(BookmarksAction::OpenSelected).into(),
+            KeyCode::Char('d') => Action::Bookmarks(BookmarksAction::RemoveSelected).into(),
+            KeyCode::Char('c') => Action::Bookmarks(BookmarksAction::ShowSelectedChanges).into(),
+            KeyCode::Char('r') => Action::Bookmarks(BookmarksAction::CheckUpdates).into(),
+            KeyCode::Char('e') => Action::ExportEpub(EpubSource::Bookmarks).into(),
+            _ => ActionResult::Ignored,
+        }
+    }
+
+    fn keymap(&self) -> super::help::Keymap {
+        vec![
+            (
+                key_event!(Key::Enter),
+                Action::Bookmarks(BookmarksAction::OpenSelected).into(),
+            ),
+            (
+                key_event!('d'),
+                Action::Bookmarks(BookmarksAction::RemoveSelected).into(),
+            ),
+            (
+                key_event!('c'),
+                Action::Bookmarks(BookmarksAction::ShowSelectedChanges).into(),
+            ),
+            (
+                key_event!('r'),
+                Action::Bookmarks(BookmarksAction::CheckUpdates).into(),
+            ),
+            (
+                key_event!('e'),
+                Action::ExportEpub(EpubSource::Bookmarks).into(),
+            ),
+        ]
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        match action {
+            Action::Bookmarks(bookmarks_action) => match bookmarks_action {
+                BookmarksAction::OpenSelected => return self.open_selected(),
+                BookmarksAction::RemoveSelected => self.remove_selected(),
+                BookmarksAction::ShowSelectedChanges => self.show_selected_changes(),
+                BookmarksAction::CheckUpdates => self.check_updates(),
+                BookmarksAction::FinishCheck(language, revisions) => {
+                    self.checking = self.checking.saturating_sub(1);
+                    bookmarks().set_latest_revisions(&language, &revisions);
+                }
+                BookmarksAction::AbortCheck => self.checking = self.checking.saturating_sub(1),
+            },
+            Action::ScrollUp(n) => self.select(-(n as isize)),
+            Action::ScrollDown(n) => self.select(n as isize),
+            Action::UnselectScroll => self.state.select(None),
+            _ => return ActionResult::Ignored,
+        }
+        ActionResult::consumed()
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let [info_area, list_area] = {
+            let rects = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(1), Constraint::Percentage(100)])
+                .split(area);
+            [rects[0], rects[1]]
+        };
+
+        let pages = bookmarks().all().to_vec();
+        let updated = pages.iter().filter(|page| page.is_updated()).count();
+        let mut info = vec![Span::raw(tr_args(
+            "bookmarks.info",
+            &[
+                ("pages", pages.len().to_string()),
+                ("updated", updated.to_string()),
+            ],
+        ))];
+        if self.checking > 0 {
+            info.push(Span::raw(format!(" | {}", tr("bookmarks.checking"))).dark_gray());
+        }
+        f.render_widget(Paragraph::new(Line::from(info)), info_area);
+
+        if pages.is_empty() {
+            f.render_widget(
+                Paragraph::new(tr("bookmarks.empty")).alignment(Alignment::Center),
+                centered_rect(list_area, 100, 50),
+            );
+            return;
+        }
+
+        let items: Vec<ListItem> = pages
+            .iter()
+            .map(|page| {
+                let mut spans = vec![
+                    Span::raw(page.title.clone()),
+                    Span::raw(format!(" ({}) ", page.language.name())).dark_gray(),
+                ];
+                if page.is_updated() {
+                    spans.push(Span::raw(tr("bookmarks.updated")).yellow());
+                }
+                if let Some(percent) = history().progress(&page.title) {
+                    spans.push(progress_span(percent));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(tr("bookmarks.title")),
+            )
+            .highlight_symbol("| ")
+            .highlight_spacing(HighlightSpacing::Always)
+            .highlight_style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::ITALIC),
+            );
+        f.render_stateful_widget(list, list_area, &mut self.state);
+    }
+}
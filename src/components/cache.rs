@@ -0,0 +1,254 @@
+use std::time::{Duration, SystemTime};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Borders, HighlightSpacing, List, ListItem, ListState, Paragraph},
+};
+use tracing::{error, info};
+
+use crate::{
+    action::{Action, ActionPacket, ActionResult, CacheAction},
+    config::config,
+    i18n::{tr, tr_args},
+    key_event,
+    page_cache::{cache_entries, older_than, remove_entries, vacuum, CacheEntry},
+    terminal::Frame,
+    ui::centered_rect,
+    undo::{audit, record, Undoable},
+};
+
+use super::Component;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Formats the size in bytes with the largest fitting binary unit, like `1.5 MiB`
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{} {}", bytes, UNITS[0]),
+        unit => format!("{:.1} {}", size, UNITS[unit]),
+    }
+}
+
+/// Lists the pages in the page cache with their size and when they were last opened, and
+/// manages the disk space the cache takes up
+#[derive(Default)]
+pub struct CacheComponent {
+    state: ListState,
+    entries: Vec<CacheEntry>,
+}
+
+impl CacheComponent {
+    /// Reads the page cache again, pages are stored and removed while the list isn't shown
+    pub fn refresh(&mut self) {
+        match cache_entries() {
+            Ok(entries) => self.entries = entries,
+            Err(error) => error!("unable to read the page cache: {:?}", error),
+        }
+        self.select(0);
+    }
+
+    fn select(&mut self, offset: isize) {
+        let len = self.entries.len();
+        if len == 0 {
+            return self.state.select(None);
+        }
+
+        let selected = match self.state.selected() {
+            Some(selected) => (selected as isize + offset).clamp(0, len as isize - 1) as usize,
+            None => 0,
+        };
+        self.state.select(Some(selected));
+    }
+
+    fn selected_entry(&self) -> Option<&CacheEntry> {
+        self.entries.get(self.state.selected()?)
+    }
+
+    fn open_selected(&self) -> ActionResult {
+        match self.selected_entry() {
+            Some(entry) => ActionPacket::single(Action::SwitchLanguage(entry.language.clone()))
+                .action(Action::LoadPage(entry.title.clone()))
+                .into(),
+            None => ActionResult::Ignored,
+        }
+    }
+
+    fn remove(&mut self, entries: &[CacheEntry]) {
+        if entries.is_empty() {
+            return;
+        }
+        // the pages are read before they are gone, so their removal can be undone
+        let undoable = Undoable::cached_pages(entries);
+        match remove_entries(entries) {
+            Ok(removed) => {
+                info!("removed {} pages from the page cache", removed);
+                match undoable {
+                    Some(undoable) => record(undoable),
+                    None => audit(&format!("removed {} pages from the page cache", removed)),
+                }
+            }
+            Err(error) => error!("unable to remove the cached pages: {:?}", error),
+        }
+        self.refresh();
+    }
+
+    fn remove_selected(&mut self) {
+        if let Some(entry) = self.selected_entry().cloned() {
+            self.remove(&[entry]);
+        }
+    }
+
+    fn purge_old(&mut self) {
+        let age = Duration::from_secs(config().cache.purge_age * SECONDS_PER_DAY);
+        let old = older_than(&self.entries, age, SystemTime::now());
+        self.remove(&old);
+    }
+
+    fn vacuum(&mut self) {
+        match vacuum() {
+            Ok(0) => {}
+            Ok(removed) => audit(&format!(
+                "vacuumed {} outdated files from the page cache",
+                removed
+            )),
+            Err(error) => error!("unable to vacuum the page cache: {:?}", error),
+        }
+        self.refresh();
+    }
+}
+
+impl Component for CacheComponent {
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        match key.code {
+            KeyCode::Enter => Action::Cache(CacheAction::OpenSelected).into(),
+            KeyCode::Char('d') => Action::Cache(CacheAction::RemoveSelected).into(),
+            KeyCode::Char('o') => Action::Cache(CacheAction::PurgeOld).into(),
+            KeyCode::Char('v') => Action::Cache(CacheAction::Vacuum).into(),
+            KeyCode::Char('r') => Action::Cache(CacheAction::Refresh).into(),
+            _ => ActionResult::Ignored,
+        }
+    }
+
+    fn keymap(&self) -> super::help::Keymap {
+        vec![
+            (
+                key_event!(Key::Enter),
+                Action::Cache(CacheAction::OpenSelected).into(),
+            ),
+            (
+                key_event!('d'),
+                Action::Cache(CacheAction::RemoveSelected).into(),
+            ),
+            (key_event!('o'), Action::Cache(CacheAction::PurgeOld).into()),
+            (key_event!('v'), Action::Cache(CacheAction::Vacuum).into()),
+            (key_event!('r'), Action::Cache(CacheAction::Refresh).into()),
+        ]
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        match action {
+            Action::Cache(cache_action) => match cache_action {
+                CacheAction::OpenSelected => return self.open_selected(),
+                CacheAction::RemoveSelected => self.remove_selected(),
+                CacheAction::PurgeOld => self.purge_old(),
+                CacheAction::Vacuum => self.vacuum(),
+                CacheAction::Refresh => self.refresh(),
+            },
+            Action::ScrollUp(n) => self.select(-(n as isize)),
+            Action::ScrollDown(n) => self.select(n as isize),
+            Action::UnselectScroll => self.state.select(None),
+            _ => return ActionResult::Ignored,
+        }
+        ActionResult::consumed()
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let [info_area, list_area] = {
+            let rects = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(1), Constraint::Percentage(100)])
+                .split(area);
+            [rects[0], rects[1]]
+        };
+
+        let size: u64 = self.entries.iter().map(|entry| entry.size).sum();
+        let max_size = match config().cache.max_size {
+            0 => tr("cache.unlimited").to_string(),
+            max_size => format_size(max_size * 1024 * 1024),
+        };
+        f.render_widget(
+            Paragraph::new(tr_args(
+                "cache.info",
+                &[
+                    ("pages", self.entries.len().to_string()),
+                    ("size", format_size(size)),
+                    ("max_size", max_size),
+                    ("days", config().cache.purge_age.to_string()),
+                ],
+            )),
+            info_area,
+        );
+
+        if self.entries.is_empty() {
+            f.render_widget(
+                Paragraph::new(tr("cache.empty")).alignment(Alignment::Center),
+                centered_rect(list_area, 100, 50),
+            );
+            return;
+        }
+
+        let items: Vec<ListItem> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let accessed = chrono::DateTime::from_timestamp(entry.accessed, 0)
+                    .map(|accessed| accessed.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_default();
+                ListItem::new(Line::from(vec![
+                    Span::raw(entry.title.clone()),
+                    Span::raw(format!(" ({}) ", entry.language.name())).dark_gray(),
+                    Span::raw(format_size(entry.size)).yellow(),
+                    Span::raw(format!(" {}", accessed)).dark_gray(),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(tr("cache.title")),
+            )
+            .highlight_symbol("| ")
+            .highlight_spacing(HighlightSpacing::Always)
+            .highlight_style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::ITALIC),
+            );
+        f.render_stateful_widget(list, list_area, &mut self.state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_size;
+
+    #[test]
+    fn sizes() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(512 * 1024 * 1024), "512.0 MiB");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024 * 1024), "3072.0 GiB");
+    }
+}
@@ -0,0 +1,282 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    prelude::{Alignment, Constraint, Direction, Layout, Margin, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{
+        Block, BorderType, Borders, Clear, HighlightSpacing, List, ListItem, ListState, Paragraph,
+    },
+};
+use tui_input::{backend::crossterm::EventHandler, Input};
+
+use crate::{
+    action::{Action, ActionPacket, ActionResult},
+    fuzzy::rank_matches,
+    has_modifier,
+    i18n::tr,
+    terminal::Frame,
+    ui::highlight_matches,
+};
+
+use super::{help::Keymap, Component};
+
+/// A command of the palette, together with the key it's bound to
+struct Command {
+    name: String,
+    key: String,
+    actions: ActionPacket,
+}
+
+/// Popup listing every command available in the current context with its keybinding. Typing
+/// narrows the list, Enter runs the selected command
+pub struct CommandPaletteComponent {
+    commands: Vec<Command>,
+    input: Input,
+    state: ListState,
+}
+
+impl CommandPaletteComponent {
+    pub fn new(keymap: Keymap) -> Self {
+        let commands = keymap
+            .into_iter()
+            .filter(|(_, actions)| !actions.contains(&Action::ToggleCommandPalette))
+            .map(|(key, actions)| Command {
+                name: command_name(&actions),
+                key: key_name(&key),
+                actions,
+            })
+            .collect();
+
+        let mut state = ListState::default();
+        state.select(Some(0));
+        Self {
+            commands,
+            input: Input::default(),
+            state,
+        }
+    }
+
+    /// Returns the indices of the commands matching the query, the best match first, together
+    /// with the positions of the matched characters in their name
+    fn matches(&self) -> Vec<(usize, Vec<usize>)> {
+        rank_matches(self.input.value(), 0..self.commands.len(), |n| {
+            self.commands[*n].name.as_str()
+        })
+        .into_iter()
+        .map(|(n, matched)| (n, matched.positions))
+        .collect()
+    }
+
+    fn select(&mut self, offset: isize) {
+        let len = self.matches().len();
+        if len == 0 {
+            return self.state.select(None);
+        }
+
+        let selected = match self.state.selected() {
+            Some(selected) => (selected as isize + offset).clamp(0, len as isize - 1) as usize,
+            None => 0,
+        };
+        self.state.select(Some(selected));
+    }
+
+    /// Closes the palette and runs the selected command in the context it was opened in
+    fn execute_selected(&mut self) -> ActionResult {
+        let command = self
+            .state
+            .selected()
+            .and_then(|selected| self.matches().get(selected).map(|(n, _)| *n));
+        match command {
+            Some(n) => ActionPacket::single(Action::ToggleCommandPalette)
+                .append(std::mem::take(&mut self.commands[n].actions))
+                .into(),
+            None => ActionResult::consumed(),
+        }
+    }
+}
+
+/// Turns the debug representation of the actions into words, `Page(ToggleBookmark)` becomes
+/// `Page: toggle bookmark`
+fn command_name(actions: &ActionPacket) -> String {
+    let debug = format!("{:?}", actions);
+    let mut name = String::with_capacity(debug.len() + 8);
+    // whether each open parenthesis was turned into a colon
+    let mut parentheses: Vec<bool> = Vec::new();
+    let mut prev: Option<char> = None;
+    let mut chars = debug.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '(' => {
+                // nested actions are named after their group
+                let is_nested = chars.peek().map(|next| next.is_uppercase()) == Some(true);
+                name.push_str(if is_nested { ": " } else { " (" });
+                parentheses.push(is_nested);
+            }
+            ')' => {
+                if !parentheses.pop().unwrap_or(false) {
+                    name.push(')');
+                }
+            }
+            c if c.is_uppercase() && !name.is_empty() => {
+                if prev
+                    .map(|prev| prev.is_lowercase() || prev.is_ascii_digit())
+                    .unwrap_or(false)
+                {
+                    name.push(' ');
+                }
+                name.extend(c.to_lowercase());
+            }
+            c => name.push(c),
+        }
+        prev = Some(c);
+    }
+    name
+}
+
+/// Returns how the key is written in the keymaps of the documentation, like `Ctrl+d`
+fn key_name(key: &KeyEvent) -> String {
+    let mut name = String::new();
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        name.push_str("Ctrl+");
+    }
+    if key.modifiers.contains(KeyModifiers::ALT) {
+        name.push_str("Alt+");
+    }
+    // shifted characters are already uppercase
+    if key.modifiers.contains(KeyModifiers::SHIFT) && !matches!(key.code, KeyCode::Char(_)) {
+        name.push_str("Shift+");
+    }
+
+    match key.code {
+        KeyCode::Char(' ') => name.push_str("Space"),
+        KeyCode::Char(c) => name.push(c),
+        KeyCode::F(n) => name.push_str(&format!("F{}", n)),
+        code => name.push_str(&format!("{:?}", code)),
+    }
+    name
+}
+
+impl Component for CommandPaletteComponent {
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        match key.code {
+            KeyCode::Enter => self.execute_selected(),
+            KeyCode::Esc => Action::ToggleCommandPalette.into(),
+            KeyCode::Char('p') if has_modifier!(key, Modifier::CONTROL) => {
+                Action::ToggleCommandPalette.into()
+            }
+            KeyCode::Down | KeyCode::Tab => Action::ScrollDown(1).into(),
+            KeyCode::Up | KeyCode::BackTab => Action::ScrollUp(1).into(),
+            // the palette is modal, every other key edits the query
+            _ => {
+                self.input.handle_event(&crossterm::event::Event::Key(key));
+                self.state.select(Some(0));
+                ActionResult::consumed()
+            }
+        }
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        match action {
+            Action::ScrollUp(n) => self.select(-(n as isize)),
+            Action::ScrollDown(n) => self.select(n as isize),
+            _ => return ActionResult::Ignored,
+        }
+        ActionResult::consumed()
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(Color::Yellow))
+            .title(tr("command_palette.title"));
+        f.render_widget(Clear, area);
+        f.render_widget(block, area);
+        let area = area.inner(&Margin::new(1, 1));
+
+        let [input_area, list_area] = {
+            let rects = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(1), Constraint::Percentage(100)])
+                .split(area);
+            [rects[0], rects[1]]
+        };
+
+        f.render_widget(
+            Paragraph::new(Line::from(vec![
+                Span::raw("> ").dark_gray(),
+                Span::raw(self.input.value().to_string()),
+            ])),
+            input_area,
+        );
+        f.set_cursor(
+            input_area.x + 2 + self.input.visual_cursor() as u16,
+            input_area.y,
+        );
+
+        let matches = self.matches();
+        if matches.is_empty() {
+            f.render_widget(
+                Paragraph::new(tr("command_palette.empty")).alignment(Alignment::Center),
+                list_area,
+            );
+            return;
+        }
+
+        // the keys are aligned to the right edge, next to the highlight symbol
+        let width = list_area.width.saturating_sub(2) as usize;
+        let items: Vec<ListItem> = matches
+            .iter()
+            .map(|(n, positions)| {
+                let command = &self.commands[*n];
+                let padding = width
+                    .saturating_sub(command.name.chars().count() + command.key.chars().count());
+                let mut spans = highlight_matches(&command.name, positions);
+                spans.push(Span::raw(" ".repeat(padding)));
+                spans.push(Span::raw(command.key.clone()).dark_gray());
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .highlight_symbol("| ")
+            .highlight_spacing(HighlightSpacing::Always)
+            .highlight_style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::ITALIC),
+            );
+        f.render_stateful_widget(list, list_area, &mut self.state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        action::{Action, ActionPacket, PageAction},
+        key_event,
+    };
+
+    use super::{command_name, key_name};
+
+    #[test]
+    fn names() {
+        assert_eq!(
+            command_name(&ActionPacket::single(Action::SwitchContextFetchQueue)),
+            "Switch context fetch queue"
+        );
+        assert_eq!(
+            command_name(&Action::Page(PageAction::ToggleBookmark).into()),
+            "Page: toggle bookmark"
+        );
+        assert_eq!(
+            command_name(&ActionPacket::single(Action::ScrollDown(1))),
+            "Scroll down (1)"
+        );
+
+        assert_eq!(key_name(&key_event!('d', Modifier::CONTROL)), "Ctrl+d");
+        assert_eq!(key_name(&key_event!('B', Modifier::SHIFT)), "B");
+        assert_eq!(key_name(&key_event!(Key::Enter)), "Enter");
+    }
+}
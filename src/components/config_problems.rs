@@ -0,0 +1,111 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::Rect,
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Paragraph, Wrap},
+};
+
+use crate::{
+    action::{Action, ActionResult},
+    config::{config_path, ConfigProblem},
+    i18n::{tr, tr_args},
+    terminal::Frame,
+};
+
+use super::Component;
+
+/// Lists the invalid settings of the config file at startup. The app continues with their
+/// default values once the list is dismissed
+pub struct ConfigProblemsComponent {
+    problems: Vec<ConfigProblem>,
+    scroll: u16,
+}
+
+impl ConfigProblemsComponent {
+    pub fn new(problems: Vec<ConfigProblem>) -> Self {
+        Self {
+            problems,
+            scroll: 0,
+        }
+    }
+
+    fn lines(&self) -> Vec<Line<'static>> {
+        let path = config_path()
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|_| tr("config_problems.whole_file").to_string());
+        let mut lines = vec![
+            Line::from(tr_args(
+                "config_problems.summary",
+                &[("count", self.problems.len().to_string()), ("path", path)],
+            )),
+            Line::default(),
+        ];
+
+        for problem in self.problems.iter() {
+            let location = match problem.location {
+                Some((line, column)) => tr_args(
+                    "config_problems.location",
+                    &[("line", line.to_string()), ("column", column.to_string())],
+                ),
+                None => tr("config_problems.unknown_location").to_string(),
+            };
+            let key = if problem.key.is_empty() {
+                tr("config_problems.whole_file").to_string()
+            } else {
+                problem.key.clone()
+            };
+
+            lines.push(Line::from(vec![
+                Span::raw(key).bold(),
+                Span::raw(format!(" ({})", location)).dark_gray(),
+            ]));
+            lines.push(Line::from(format!("  {}", problem.message).red()));
+            lines.push(Line::default());
+        }
+
+        lines.push(Line::from(tr("config_problems.continue").dark_gray()));
+        lines
+    }
+}
+
+impl Component for ConfigProblemsComponent {
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        match key.code {
+            KeyCode::Enter | KeyCode::Esc | KeyCode::Char('q') => {
+                Action::DismissConfigProblems.into()
+            }
+            KeyCode::Down | KeyCode::Char('j') => Action::ScrollDown(1).into(),
+            KeyCode::Up | KeyCode::Char('k') => Action::ScrollUp(1).into(),
+            // nothing else can be used until the problems are dismissed
+            _ => ActionResult::consumed(),
+        }
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        match action {
+            Action::ScrollDown(amount) => self.scroll = self.scroll.saturating_add(amount),
+            Action::ScrollUp(amount) => self.scroll = self.scroll.saturating_sub(amount),
+            _ => return ActionResult::Ignored,
+        }
+        ActionResult::consumed()
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let lines = self.lines();
+        self.scroll = self.scroll.min(lines.len().saturating_sub(1) as u16);
+        f.render_widget(
+            Paragraph::new(lines)
+                .wrap(Wrap { trim: false })
+                .scroll((self.scroll, 0))
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_type(BorderType::Rounded)
+                        .border_style(Style::default().fg(Color::Red))
+                        .title(tr("config_problems.title")),
+                ),
+            area,
+        );
+    }
+}
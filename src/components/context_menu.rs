@@ -0,0 +1,208 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::Rect,
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, HighlightSpacing, List, ListItem, ListState},
+};
+
+use crate::{
+    action::{Action, ActionPacket, ActionResult},
+    terminal::Frame,
+};
+
+use super::Component;
+
+/// An entry of a context menu and the actions it runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct MenuItem {
+    key: char,
+    label: String,
+    actions: Vec<Action>,
+}
+
+/// Actions available for an entry of a view, like a search result. Views open it with
+/// [`Action::OpenContextMenu`]
+///
+/// Every item can be chosen with its key. `j`, `k` and `q` are used by the menu itself
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContextMenu {
+    title: String,
+    items: Vec<MenuItem>,
+}
+
+impl ContextMenu {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            items: Vec::new(),
+        }
+    }
+
+    pub fn item(mut self, key: char, label: impl Into<String>, actions: Vec<Action>) -> Self {
+        self.items.push(MenuItem {
+            key,
+            label: label.into(),
+            actions,
+        });
+        self
+    }
+}
+
+/// Popup showing a [`ContextMenu`]
+pub struct ContextMenuComponent {
+    menu: ContextMenu,
+    state: ListState,
+}
+
+impl ContextMenuComponent {
+    pub fn new(menu: ContextMenu) -> Self {
+        let mut state = ListState::default();
+        if !menu.items.is_empty() {
+            state.select(Some(0));
+        }
+        Self { menu, state }
+    }
+
+    fn select(&mut self, offset: isize) {
+        let len = self.menu.items.len();
+        if len == 0 {
+            return self.state.select(None);
+        }
+
+        let selected = match self.state.selected() {
+            Some(selected) => (selected as isize + offset).clamp(0, len as isize - 1) as usize,
+            None => 0,
+        };
+        self.state.select(Some(selected));
+    }
+
+    /// Closes the menu and runs the actions of the item in the view it was opened in
+    fn choose(&mut self, n: usize) -> ActionResult {
+        let actions = match self.menu.items.get_mut(n) {
+            Some(item) => std::mem::take(&mut item.actions),
+            None => return ActionResult::consumed(),
+        };
+        actions
+            .into_iter()
+            .fold(
+                ActionPacket::single(Action::CloseContextMenu),
+                ActionPacket::action,
+            )
+            .into()
+    }
+
+    /// Returns the area of the popup, just large enough for the items and centered in the area
+    fn popup_area(&self, area: Rect) -> Rect {
+        let label_width = self
+            .menu
+            .items
+            .iter()
+            .map(|item| item.label.chars().count() + 4)
+            .chain(std::iter::once(self.menu.title.chars().count()))
+            .max()
+            .unwrap_or_default();
+        // borders and the highlight symbol
+        let width = (label_width as u16 + 4).min(area.width);
+        let height = (self.menu.items.len() as u16 + 2).min(area.height);
+        Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        }
+    }
+}
+
+impl Component for ContextMenuComponent {
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        match key.code {
+            KeyCode::Enter => match self.state.selected() {
+                Some(selected) => self.choose(selected),
+                None => ActionResult::consumed(),
+            },
+            KeyCode::Esc | KeyCode::Char('q') => Action::CloseContextMenu.into(),
+            KeyCode::Down | KeyCode::Tab | KeyCode::Char('j') => Action::ScrollDown(1).into(),
+            KeyCode::Up | KeyCode::BackTab | KeyCode::Char('k') => Action::ScrollUp(1).into(),
+            KeyCode::Char(c) => match self.menu.items.iter().position(|item| item.key == c) {
+                Some(n) => self.choose(n),
+                None => ActionResult::consumed(),
+            },
+            // the menu is modal
+            _ => ActionResult::consumed(),
+        }
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        match action {
+            Action::ScrollUp(n) => self.select(-(n as isize)),
+            Action::ScrollDown(n) => self.select(n as isize),
+            _ => return ActionResult::Ignored,
+        }
+        ActionResult::consumed()
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let area = self.popup_area(area);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(Color::Yellow))
+            .title(self.menu.title.clone());
+        f.render_widget(Clear, area);
+
+        let items: Vec<ListItem> = self
+            .menu
+            .items
+            .iter()
+            .map(|item| {
+                ListItem::new(Line::from(vec![
+                    Span::raw(format!("{}  ", item.key)).dark_gray(),
+                    Span::raw(item.label.clone()),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(block)
+            .highlight_symbol("| ")
+            .highlight_spacing(HighlightSpacing::Always)
+            .highlight_style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::ITALIC),
+            );
+        f.render_stateful_widget(list, area, &mut self.state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        action::{Action, ActionResult},
+        components::Component,
+        key_event,
+    };
+
+    use super::{ContextMenu, ContextMenuComponent};
+
+    #[test]
+    fn choose() {
+        let menu = ContextMenu::new("Rust")
+            .item('o', "Open", vec![Action::LoadPage("Rust".to_string())])
+            .item('a', "Queue", vec![Action::QueueFetch("Rust".to_string())]);
+        let mut component = ContextMenuComponent::new(menu);
+
+        match component.handle_key_events(key_event!('a')) {
+            ActionResult::Consumed(packet) => assert_eq!(
+                format!("{:?}", packet),
+                r#"[CloseContextMenu, QueueFetch("Rust")]"#
+            ),
+            ActionResult::Ignored => panic!("the key of the item was ignored"),
+        }
+        match component.handle_key_events(key_event!('x')) {
+            ActionResult::Consumed(packet) => assert_eq!(format!("{:?}", packet), "Nothing"),
+            ActionResult::Ignored => panic!("the menu isn't modal"),
+        }
+    }
+}
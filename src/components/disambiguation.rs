@@ -0,0 +1,178 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, HighlightSpacing, List, ListItem, Paragraph},
+};
+use wiki_api::{
+    disambiguation::{disambiguation_targets, DisambiguationTarget},
+    page::Page,
+};
+
+use crate::{
+    action::{Action, ActionPacket, ActionResult, DisambiguationAction, PageViewerAction},
+    i18n::{tr, tr_args},
+    key_event,
+    terminal::Frame,
+    ui::{centered_rect, ScrollBehaviour, StatefulList},
+};
+
+use super::Component;
+
+/// Lets the user choose between the pages listed on a disambiguation page, instead of showing
+/// the disambiguation page itself
+pub struct DisambiguationComponent {
+    page: Option<Page>,
+    targets: StatefulList<DisambiguationTarget>,
+}
+
+impl Default for DisambiguationComponent {
+    fn default() -> Self {
+        Self {
+            page: None,
+            targets: StatefulList::with_items(Vec::new())
+                .scroll_behavior(ScrollBehaviour::StickToEnds),
+        }
+    }
+}
+
+impl DisambiguationComponent {
+    fn show(&mut self, page: Page) {
+        self.targets = StatefulList::with_items(disambiguation_targets(&page.content))
+            .scroll_behavior(ScrollBehaviour::StickToEnds);
+        self.targets.next();
+        self.page = Some(page);
+    }
+
+    fn open_selected(&self) -> ActionResult {
+        if let Some(target) = self.targets.selected() {
+            return Action::LoadPage(target.title.clone()).into();
+        }
+        ActionResult::Ignored
+    }
+
+    /// Displays the disambiguation page like any other page
+    fn show_page(&self) -> ActionResult {
+        if let Some(ref page) = self.page {
+            return ActionPacket::default()
+                .action(Action::SwitchContextPage)
+                .action(Action::PageViewer(PageViewerAction::DisplayPage(
+                    page.clone(),
+                )))
+                .into();
+        }
+        ActionResult::Ignored
+    }
+}
+
+impl Component for DisambiguationComponent {
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        match key.code {
+            KeyCode::Enter => Action::Disambiguation(DisambiguationAction::OpenSelected).into(),
+            KeyCode::Char('r') => Action::Disambiguation(DisambiguationAction::ShowPage).into(),
+            _ => ActionResult::Ignored,
+        }
+    }
+
+    fn keymap(&self) -> super::help::Keymap {
+        vec![
+            (
+                key_event!(Key::Enter),
+                Action::Disambiguation(DisambiguationAction::OpenSelected).into(),
+            ),
+            (
+                key_event!('r'),
+                Action::Disambiguation(DisambiguationAction::ShowPage).into(),
+            ),
+        ]
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        match action {
+            Action::Disambiguation(disambiguation_action) => match disambiguation_action {
+                DisambiguationAction::Show(page) => self.show(page),
+                DisambiguationAction::OpenSelected => return self.open_selected(),
+                DisambiguationAction::ShowPage => return self.show_page(),
+            },
+            Action::ScrollUp(n) => {
+                for _ in 0..n {
+                    self.targets.previous();
+                }
+            }
+            Action::ScrollDown(n) => {
+                for _ in 0..n {
+                    self.targets.next();
+                }
+            }
+            Action::UnselectScroll => self.targets.unselect(),
+            _ => return ActionResult::Ignored,
+        }
+        ActionResult::consumed()
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let title = match self.page {
+            Some(ref page) => page.title.clone(),
+            None => {
+                f.render_widget(
+                    Paragraph::new(tr("disambiguation.empty")).alignment(Alignment::Center),
+                    centered_rect(area, 100, 50),
+                );
+                return;
+            }
+        };
+
+        let [info_area, list_area] = {
+            let rects = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(1), Constraint::Percentage(100)])
+                .split(area);
+            [rects[0], rects[1]]
+        };
+
+        f.render_widget(
+            Paragraph::new(tr_args(
+                "disambiguation.info",
+                &[
+                    ("title", title.to_string()),
+                    ("count", self.targets.get_items().len().to_string()),
+                ],
+            )),
+            info_area,
+        );
+
+        let width = list_area.width.saturating_sub(4) as usize;
+        let items: Vec<ListItem> = self
+            .targets
+            .get_items()
+            .iter()
+            .map(|target| {
+                let mut text = Text::from(Line::from(Span::raw(target.title.clone()).blue()));
+                text.lines.append(
+                    &mut textwrap::wrap(&target.description, width.max(1))
+                        .iter()
+                        .map(|s| Line::from(Span::raw(s.to_string()).dark_gray()))
+                        .collect(),
+                );
+                ListItem::new(text)
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(tr("disambiguation.title")),
+            )
+            .highlight_symbol("| ")
+            .repeat_highlight_symbol(true)
+            .highlight_spacing(HighlightSpacing::Always)
+            .highlight_style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::ITALIC),
+            );
+        f.render_stateful_widget(list, list_area, self.targets.get_state_mut());
+    }
+}
@@ -0,0 +1,301 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::Rect,
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
+};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+use wiki_api::wayback::{check_link, closest_snapshot};
+
+use crate::{
+    action::{Action, ActionResult, ExternalLinkAction, PageAction},
+    browser::open_url,
+    i18n::{tr, tr_args},
+    terminal::Frame,
+    ui::centered_rect,
+};
+
+use super::Component;
+
+const DIALOG_WIDTH: u16 = 60;
+const DIALOG_HEIGHT: u16 = 8;
+
+/// Whether the link can still be reached
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum LinkStatus {
+    Checking,
+    Reachable,
+    Dead(String),
+    /// The browser could not be started for the link
+    FailedOpening(String),
+}
+
+/// Progress of looking up the link in the Wayback Machine
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SnapshotStatus {
+    NotRequested,
+    Searching,
+    Missing,
+    Failed(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LinkOption {
+    Open,
+    Snapshot,
+}
+
+fn external_link_action(action: ExternalLinkAction) -> Action {
+    Action::Page(PageAction::ExternalLink(action))
+}
+
+/// Dialog asking how an external link should be opened. Offers the archived snapshot of the link
+/// when it can't be reached anymore
+pub struct ExternalLinkComponent {
+    url: String,
+    status: LinkStatus,
+    snapshot: SnapshotStatus,
+    selected: LinkOption,
+
+    action_tx: Option<mpsc::UnboundedSender<Action>>,
+}
+
+impl ExternalLinkComponent {
+    /// Creates the dialog for the url and starts checking whether the link can be reached
+    pub fn new(url: String, action_tx: Option<mpsc::UnboundedSender<Action>>) -> Self {
+        // links to other sites are often protocol-relative
+        let url = match url.strip_prefix("//") {
+            Some(url) => format!("https://{}", url),
+            None => url,
+        };
+
+        let component = Self {
+            url,
+            status: LinkStatus::Checking,
+            snapshot: SnapshotStatus::NotRequested,
+            selected: LinkOption::Open,
+            action_tx,
+        };
+        component.check_link();
+        component
+    }
+
+    fn check_link(&self) {
+        let action_tx = match self.action_tx {
+            Some(ref action_tx) => action_tx.clone(),
+            None => return,
+        };
+
+        let url = self.url.clone();
+        tokio::spawn(async move {
+            let result = check_link(&url).await.map_err(|error| {
+                info!("the link '{}' seems to be dead: {:?}", url, error);
+                format!("{:#}", error)
+            });
+            let _ = action_tx.send(external_link_action(ExternalLinkAction::FinishCheck(
+                url, result,
+            )));
+        });
+    }
+
+    fn finish_check(&mut self, result: Result<(), String>) {
+        match result {
+            Ok(()) => self.status = LinkStatus::Reachable,
+            Err(error) => {
+                self.status = LinkStatus::Dead(error);
+                self.selected = LinkOption::Snapshot;
+            }
+        }
+    }
+
+    fn open_link(&mut self) -> ActionResult {
+        match open_url(&self.url) {
+            Ok(()) => external_link_action(ExternalLinkAction::Close).into(),
+            Err(error) => {
+                warn!("unable to open the link: {:?}", error);
+                self.status = LinkStatus::FailedOpening(format!("{:#}", error));
+                self.selected = LinkOption::Snapshot;
+                ActionResult::consumed()
+            }
+        }
+    }
+
+    fn open_snapshot(&mut self) {
+        if self.snapshot == SnapshotStatus::Searching {
+            return;
+        }
+        let action_tx = match self.action_tx {
+            Some(ref action_tx) => action_tx.clone(),
+            None => return,
+        };
+
+        self.selected = LinkOption::Snapshot;
+        self.snapshot = SnapshotStatus::Searching;
+        let url = self.url.clone();
+        tokio::spawn(async move {
+            let result = closest_snapshot(&url)
+                .await
+                .map(|snapshot| snapshot.map(|snapshot| snapshot.url))
+                .map_err(|error| {
+                    warn!("unable to look up a snapshot of '{}': {:?}", url, error);
+                    format!("{:#}", error)
+                });
+            let _ = action_tx.send(external_link_action(
+                ExternalLinkAction::FinishSnapshotLookup(url, result),
+            ));
+        });
+    }
+
+    fn finish_snapshot_lookup(&mut self, result: Result<Option<String>, String>) -> ActionResult {
+        match result {
+            Ok(Some(snapshot)) => match open_url(&snapshot) {
+                Ok(()) => return external_link_action(ExternalLinkAction::Close).into(),
+                Err(error) => {
+                    warn!("unable to open the snapshot: {:?}", error);
+                    self.snapshot = SnapshotStatus::Failed(format!("{:#}", error));
+                }
+            },
+            Ok(None) => self.snapshot = SnapshotStatus::Missing,
+            Err(error) => self.snapshot = SnapshotStatus::Failed(error),
+        }
+        ActionResult::consumed()
+    }
+
+    fn open_selected(&mut self) -> ActionResult {
+        match self.selected {
+            LinkOption::Open => self.open_link(),
+            LinkOption::Snapshot => {
+                self.open_snapshot();
+                ActionResult::consumed()
+            }
+        }
+    }
+
+    fn switch_option(&mut self) {
+        self.selected = match self.selected {
+            LinkOption::Open => LinkOption::Snapshot,
+            LinkOption::Snapshot => LinkOption::Open,
+        };
+    }
+
+    fn status_line(&self) -> Line<'static> {
+        match self.status {
+            LinkStatus::Checking => Line::from(tr("external_link.checking").yellow()),
+            LinkStatus::Reachable => Line::from(tr("external_link.reachable").green()),
+            LinkStatus::Dead(ref error) => {
+                Line::from(tr_args("external_link.dead", &[("error", error.clone())]).red())
+            }
+            LinkStatus::FailedOpening(ref error) => Line::from(
+                tr_args("external_link.failed_opening", &[("error", error.clone())]).red(),
+            ),
+        }
+    }
+
+    fn option_line(&self, option: LinkOption) -> Line<'static> {
+        let mut spans = match option {
+            LinkOption::Open => vec![Span::raw(tr("external_link.open"))],
+            LinkOption::Snapshot => {
+                let status = match self.snapshot {
+                    SnapshotStatus::NotRequested => Span::raw(""),
+                    SnapshotStatus::Searching => tr("external_link.searching").yellow(),
+                    SnapshotStatus::Missing => tr("external_link.never_archived").red(),
+                    SnapshotStatus::Failed(ref error) => {
+                        tr_args("external_link.snapshot_failed", &[("error", error.clone())]).red()
+                    }
+                };
+                vec![Span::raw(tr("external_link.open_snapshot")), status]
+            }
+        };
+
+        if option != self.selected {
+            spans.insert(0, Span::raw("  "));
+            return Line::from(spans);
+        }
+
+        spans.insert(0, Span::raw("| "));
+        let mut line = Line::from(spans);
+        line.patch_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::ITALIC),
+        );
+        line
+    }
+}
+
+impl Component for ExternalLinkComponent {
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                external_link_action(ExternalLinkAction::SelectPrevOption).into()
+            }
+            KeyCode::Down | KeyCode::Char('j') | KeyCode::Tab => {
+                external_link_action(ExternalLinkAction::SelectNextOption).into()
+            }
+            KeyCode::Enter => external_link_action(ExternalLinkAction::OpenSelected).into(),
+            KeyCode::Char('o') => external_link_action(ExternalLinkAction::OpenLink).into(),
+            KeyCode::Char('w') => external_link_action(ExternalLinkAction::OpenSnapshot).into(),
+            KeyCode::Esc | KeyCode::Char('q') => {
+                external_link_action(ExternalLinkAction::Close).into()
+            }
+            // the dialog is modal
+            _ => ActionResult::consumed(),
+        }
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        let action = match action {
+            Action::Page(PageAction::ExternalLink(action)) => action,
+            _ => return ActionResult::Ignored,
+        };
+
+        match action {
+            ExternalLinkAction::SelectPrevOption | ExternalLinkAction::SelectNextOption => {
+                self.switch_option()
+            }
+            ExternalLinkAction::OpenSelected => return self.open_selected(),
+            ExternalLinkAction::OpenLink => return self.open_link(),
+            ExternalLinkAction::OpenSnapshot => self.open_snapshot(),
+            // results for links of a dialog that was closed in the meantime are dropped
+            ExternalLinkAction::FinishCheck(url, result) if url == self.url => {
+                self.finish_check(result)
+            }
+            ExternalLinkAction::FinishSnapshotLookup(url, result) if url == self.url => {
+                return self.finish_snapshot_lookup(result)
+            }
+            _ => return ActionResult::Ignored,
+        }
+        ActionResult::consumed()
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let height = DIALOG_HEIGHT.min(area.height);
+        let area = Rect {
+            y: area.y + (area.height - height) / 2,
+            height,
+            ..centered_rect(area, DIALOG_WIDTH, 100)
+        };
+
+        let lines = vec![
+            Line::from(self.url.clone().underlined()),
+            self.status_line(),
+            Line::default(),
+            self.option_line(LinkOption::Open),
+            self.option_line(LinkOption::Snapshot),
+        ];
+
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(Color::Yellow))
+                    .title(tr("external_link.title")),
+            ),
+            area,
+        );
+    }
+}
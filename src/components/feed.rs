@@ -0,0 +1,303 @@
+use chrono::{Datelike, Local, NaiveDate};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span, Text},
+    widgets::{Block, BorderType, Borders, HighlightSpacing, List, ListItem, Paragraph},
+};
+use tokio::sync::mpsc;
+use tracing::error;
+use wiki_api::{
+    feed::{Event, OnThisDay, OnThisDayKind},
+    Endpoint,
+};
+
+use crate::{
+    action::{Action, ActionResult, FeedAction},
+    i18n::{tr, tr_args},
+    key_event,
+    terminal::Frame,
+    ui::{centered_rect, ScrollBehaviour, StatefulList},
+};
+
+use super::Component;
+
+const CURRENT_EVENTS_PAGE: &str = "Portal:Current events";
+
+/// Shows the events that happened on a given day in history
+pub struct FeedComponent {
+    date: NaiveDate,
+    events: StatefulList<Event>,
+    selected_page: usize,
+
+    is_loaded: bool,
+    is_processing: bool,
+
+    endpoint: Option<Endpoint>,
+    action_tx: Option<mpsc::UnboundedSender<Action>>,
+}
+
+impl Default for FeedComponent {
+    fn default() -> Self {
+        Self {
+            date: Local::now().date_naive(),
+            events: StatefulList::with_items(Vec::new())
+                .scroll_behavior(ScrollBehaviour::StickToEnds),
+            selected_page: 0,
+
+            is_loaded: false,
+            is_processing: false,
+
+            endpoint: None,
+            action_tx: None,
+        }
+    }
+}
+
+impl FeedComponent {
+    pub fn is_loaded(&self) -> bool {
+        self.is_loaded
+    }
+
+    /// Fetches the events from the wiki at the endpoint, they're loaded again the next time the
+    /// feed is shown
+    pub fn set_endpoint(&mut self, endpoint: Endpoint) {
+        self.endpoint = Some(endpoint);
+        self.is_loaded = false;
+    }
+
+    fn load(&mut self) {
+        let endpoint = match self.endpoint {
+            Some(ref endpoint) => endpoint.clone(),
+            None => return error!("No Endpoint configured"),
+        };
+        let tx = self.action_tx.clone().unwrap();
+        let (month, day) = (self.date.month(), self.date.day());
+
+        self.is_loaded = true;
+        self.is_processing = true;
+        tokio::spawn(async move {
+            match OnThisDay::fetch(endpoint, OnThisDayKind::Events, month, day).await {
+                Ok(feed) => tx.send(Action::Feed(FeedAction::FinishLoading(feed))),
+                Err(error) => {
+                    error!("Unable to load the feed: {:?}", error);
+                    tx.send(Action::Feed(FeedAction::AbortLoading))
+                }
+            }
+            .unwrap();
+        });
+    }
+
+    fn finish_loading(&mut self, feed: OnThisDay) {
+        self.is_processing = false;
+        self.events =
+            StatefulList::with_items(feed.events).scroll_behavior(ScrollBehaviour::StickToEnds);
+        self.events.next();
+        self.selected_page = 0;
+    }
+
+    fn change_day(&mut self, days: i64) {
+        if let Some(date) = self.date.checked_add_signed(chrono::Duration::days(days)) {
+            self.date = date;
+            self.load();
+        }
+    }
+
+    fn select_page(&mut self, offset: isize) {
+        let page_count = self
+            .events
+            .selected()
+            .map(|event| event.pages.len())
+            .unwrap_or_default();
+        if page_count == 0 {
+            return;
+        }
+        self.selected_page =
+            (self.selected_page as isize + offset).rem_euclid(page_count as isize) as usize;
+    }
+
+    fn open_selected(&self) -> ActionResult {
+        if let Some(page) = self
+            .events
+            .selected()
+            .and_then(|event| event.pages.get(self.selected_page))
+        {
+            return Action::LoadPage(page.title().to_string()).into();
+        }
+        ActionResult::Ignored
+    }
+}
+
+impl Component for FeedComponent {
+    fn init(&mut self, action_tx: mpsc::UnboundedSender<Action>) -> anyhow::Result<()> {
+        self.action_tx = Some(action_tx);
+        Ok(())
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        if self.is_processing {
+            return ActionResult::Ignored;
+        }
+
+        match key.code {
+            KeyCode::Enter => Action::Feed(FeedAction::OpenSelected).into(),
+            KeyCode::Left => Action::Feed(FeedAction::SelectPrevPage).into(),
+            KeyCode::Right => Action::Feed(FeedAction::SelectNextPage).into(),
+            KeyCode::Char('[') => Action::Feed(FeedAction::PrevDay).into(),
+            KeyCode::Char(']') => Action::Feed(FeedAction::NextDay).into(),
+            KeyCode::Char('e') => Action::LoadPage(CURRENT_EVENTS_PAGE.to_string()).into(),
+            _ => ActionResult::Ignored,
+        }
+    }
+
+    fn keymap(&self) -> super::help::Keymap {
+        vec![
+            (
+                key_event!(Key::Enter),
+                Action::Feed(FeedAction::OpenSelected).into(),
+            ),
+            (
+                key_event!(Key::Left),
+                Action::Feed(FeedAction::SelectPrevPage).into(),
+            ),
+            (
+                key_event!(Key::Right),
+                Action::Feed(FeedAction::SelectNextPage).into(),
+            ),
+            (key_event!('['), Action::Feed(FeedAction::PrevDay).into()),
+            (key_event!(']'), Action::Feed(FeedAction::NextDay).into()),
+            (
+                key_event!('e'),
+                Action::LoadPage(CURRENT_EVENTS_PAGE.to_string()).into(),
+            ),
+        ]
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        match action {
+            Action::Feed(feed_action) => match feed_action {
+                FeedAction::Load => self.load(),
+                FeedAction::FinishLoading(feed) => self.finish_loading(feed),
+                FeedAction::AbortLoading => self.is_processing = false,
+                FeedAction::OpenSelected => return self.open_selected(),
+                FeedAction::SelectPrevPage => self.select_page(-1),
+                FeedAction::SelectNextPage => self.select_page(1),
+                FeedAction::PrevDay => self.change_day(-1),
+                FeedAction::NextDay => self.change_day(1),
+            },
+            Action::ScrollUp(n) => {
+                for _ in 0..n {
+                    self.events.previous();
+                }
+                self.selected_page = 0;
+            }
+            Action::ScrollDown(n) => {
+                for _ in 0..n {
+                    self.events.next();
+                }
+                self.selected_page = 0;
+            }
+            Action::UnselectScroll => self.events.unselect(),
+            _ => return ActionResult::Ignored,
+        }
+        ActionResult::consumed()
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        if self.is_processing {
+            f.render_widget(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(Color::Yellow)),
+                area,
+            );
+            f.render_widget(
+                Paragraph::new(tr("feed.loading")).alignment(Alignment::Center),
+                centered_rect(area, 100, 50),
+            );
+            return;
+        }
+
+        let [info_area, events_area] = {
+            let rects = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(1), Constraint::Percentage(100)])
+                .split(area);
+            [rects[0], rects[1]]
+        };
+
+        f.render_widget(
+            Paragraph::new(tr_args(
+                "feed.info",
+                &[("date", self.date.format("%B %-d").to_string())],
+            )),
+            info_area,
+        );
+
+        if self.events.get_items().is_empty() {
+            f.render_widget(
+                Paragraph::new(tr("feed.no_events")).alignment(Alignment::Center),
+                centered_rect(events_area, 100, 50),
+            );
+            return;
+        }
+
+        let events_width = events_area.width.saturating_sub(3) as usize;
+        let selected_event = self.events.get_state_mut().selected();
+        let items: Vec<ListItem> = self
+            .events
+            .get_items()
+            .iter()
+            .enumerate()
+            .map(|(i, event)| {
+                let year = match event.year {
+                    Some(year) if year < 0 => format!("{} BC", -year),
+                    Some(year) => year.to_string(),
+                    None => String::new(),
+                };
+                let mut text = Text::from(Span::raw(year).red());
+                text.lines.append(
+                    &mut textwrap::wrap(&event.text, events_width)
+                        .iter()
+                        .map(|s| Line::from(s.to_string()))
+                        .collect(),
+                );
+
+                let pages: Vec<Span> = event
+                    .pages
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(n, page)| {
+                        let span = Span::raw(page.title().to_string());
+                        let span = if selected_event == Some(i) && n == self.selected_page {
+                            span.blue().underlined()
+                        } else {
+                            span.blue()
+                        };
+                        [span, Span::raw(" | ")]
+                    })
+                    .collect();
+                text.lines.push(Line::from(pages));
+                ListItem::new(text)
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(tr("feed.title")),
+            )
+            .highlight_symbol("| ")
+            .repeat_highlight_symbol(true)
+            .highlight_spacing(HighlightSpacing::Always)
+            .highlight_style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::ITALIC),
+            );
+        f.render_stateful_widget(list, events_area, self.events.get_state_mut());
+    }
+}
@@ -0,0 +1,176 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Borders, HighlightSpacing, List, ListItem, ListState, Paragraph},
+};
+
+use crate::{
+    action::{Action, ActionPacket, ActionResult, FetchQueueAction},
+    fetch_queue::{fetch_queue, FetchStatus, QueuedPage},
+    i18n::{tr, tr_args},
+    key_event,
+    terminal::Frame,
+    ui::centered_rect,
+};
+
+use super::Component;
+
+/// Lists the pages that are fetched later, together with the status of their fetch
+#[derive(Default)]
+pub struct FetchQueueComponent {
+    state: ListState,
+}
+
+impl FetchQueueComponent {
+    fn selected_page(&self) -> Option<QueuedPage> {
+        let selected = self.state.selected()?;
+        fetch_queue().all().get(selected).cloned()
+    }
+
+    fn select(&mut self, offset: isize) {
+        let len = fetch_queue().all().len();
+        if len == 0 {
+            return self.state.select(None);
+        }
+
+        let selected = match self.state.selected() {
+            Some(selected) => (selected as isize + offset).clamp(0, len as isize - 1) as usize,
+            None => 0,
+        };
+        self.state.select(Some(selected));
+    }
+
+    fn open_selected(&self) -> ActionResult {
+        match self.selected_page() {
+            Some(page) => ActionPacket::single(Action::SwitchLanguage(page.language))
+                .action(Action::LoadPage(page.title))
+                .into(),
+            None => ActionResult::Ignored,
+        }
+    }
+
+    fn remove_selected(&mut self) {
+        if let Some(page) = self.selected_page() {
+            fetch_queue().remove(&page.title, &page.language);
+            self.select(0);
+        }
+    }
+
+    fn clear_fetched(&mut self) {
+        fetch_queue().clear_fetched();
+        self.state.select(None);
+    }
+}
+
+impl Component for FetchQueueComponent {
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        match key.code {
+            KeyCode::Enter => Action::FetchQueue(FetchQueueAction::OpenSelected).into(),
+            KeyCode::Char('d') => Action::FetchQueue(FetchQueueAction::RemoveSelected).into(),
+            KeyCode::Char('c') => Action::FetchQueue(FetchQueueAction::ClearFetched).into(),
+            KeyCode::Char('r') => Action::FetchQueued.into(),
+            _ => ActionResult::Ignored,
+        }
+    }
+
+    fn keymap(&self) -> super::help::Keymap {
+        vec![
+            (
+                key_event!(Key::Enter),
+                Action::FetchQueue(FetchQueueAction::OpenSelected).into(),
+            ),
+            (
+                key_event!('d'),
+                Action::FetchQueue(FetchQueueAction::RemoveSelected).into(),
+            ),
+            (
+                key_event!('c'),
+                Action::FetchQueue(FetchQueueAction::ClearFetched).into(),
+            ),
+            (key_event!('r'), Action::FetchQueued.into()),
+        ]
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        match action {
+            Action::FetchQueue(fetch_queue_action) => match fetch_queue_action {
+                FetchQueueAction::OpenSelected => return self.open_selected(),
+                FetchQueueAction::RemoveSelected => self.remove_selected(),
+                FetchQueueAction::ClearFetched => self.clear_fetched(),
+            },
+            Action::ScrollUp(n) => self.select(-(n as isize)),
+            Action::ScrollDown(n) => self.select(n as isize),
+            Action::UnselectScroll => self.state.select(None),
+            _ => return ActionResult::Ignored,
+        }
+        ActionResult::consumed()
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let [info_area, list_area] = {
+            let rects = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(1), Constraint::Percentage(100)])
+                .split(area);
+            [rects[0], rects[1]]
+        };
+
+        let pages = fetch_queue().all().to_vec();
+        let pending = pages
+            .iter()
+            .filter(|page| page.status != FetchStatus::Fetched)
+            .count();
+        f.render_widget(
+            Paragraph::new(tr_args(
+                "fetch_queue.info",
+                &[
+                    ("pages", pages.len().to_string()),
+                    ("pending", pending.to_string()),
+                ],
+            )),
+            info_area,
+        );
+
+        if pages.is_empty() {
+            f.render_widget(
+                Paragraph::new(tr("fetch_queue.empty")).alignment(Alignment::Center),
+                centered_rect(list_area, 100, 50),
+            );
+            return;
+        }
+
+        let items: Vec<ListItem> = pages
+            .iter()
+            .map(|page| {
+                let status = match page.status {
+                    FetchStatus::Queued => Span::raw("queued").dark_gray(),
+                    FetchStatus::Fetching => Span::raw("fetching").yellow(),
+                    FetchStatus::Fetched => Span::raw("fetched").green(),
+                    FetchStatus::Failed(ref error) => Span::raw(format!("failed: {}", error)).red(),
+                };
+                ListItem::new(Line::from(vec![
+                    Span::raw(page.title.clone()),
+                    Span::raw(format!(" ({}) ", page.language.name())).dark_gray(),
+                    status,
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(tr("fetch_queue.title")),
+            )
+            .highlight_symbol("| ")
+            .highlight_spacing(HighlightSpacing::Always)
+            .highlight_style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::ITALIC),
+            );
+        f.render_stateful_widget(list, list_area, &mut self.state);
+    }
+}
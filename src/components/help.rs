@@ -7,12 +7,12 @@ use ratatui::{
 
 use crate::{
     action::{Action, ActionPacket, ActionResult},
+    i18n::tr,
     ui::{ScrollBehaviour, StatefulList},
 };
 
 use super::Component;
 
-const INFO_TEXT: &str = "Below are the keybindings for the current context";
 const INFO_LIST_SPACING: u16 = 1;
 
 pub type Keybinding = (KeyEvent, ActionPacket);
@@ -66,7 +66,7 @@ impl Component for HelpComponent {
             (chunks[0], chunks[2])
         };
 
-        let info_widget = Paragraph::new(INFO_TEXT).alignment(Alignment::Center);
+        let info_widget = Paragraph::new(tr("help.info")).alignment(Alignment::Center);
         f.render_widget(info_widget, info_area);
 
         let (actions_area, spacer_area, events_area) = {
@@ -93,14 +93,14 @@ impl Component for HelpComponent {
         }
 
         let actions_widget = List::new(actions_items)
-            .block(Block::default().title("Action(s)"))
+            .block(Block::default().title(tr("help.actions")))
             .highlight_style(
                 Style::default()
                     .bg(Color::DarkGray)
                     .add_modifier(Modifier::ITALIC),
             );
         let event_widget = List::new(event_items)
-            .block(Block::default().title("Keybinding"))
+            .block(Block::default().title(tr("help.keybinding")))
             .highlight_style(
                 Style::default()
                     .bg(Color::DarkGray)
@@ -0,0 +1,265 @@
+use std::cmp::Reverse;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Borders, HighlightSpacing, List, ListItem, ListState, Paragraph},
+};
+use tui_input::{backend::crossterm::EventHandler, Input};
+
+use crate::{
+    action::{Action, ActionPacket, ActionResult, HistoryAction, PageViewerAction},
+    fuzzy::fuzzy_match,
+    history::{history, HistoryEntry},
+    i18n::{tr, tr_args},
+    key_event,
+    terminal::Frame,
+    ui::centered_rect,
+};
+
+use super::Component;
+
+/// Returns how much of a page was read, highlighted while the page isn't read completely
+pub fn progress_span(percent: u16) -> Span<'static> {
+    let span = Span::raw(format!(
+        " {}",
+        tr_args("history.read", &[("percent", percent.to_string())])
+    ));
+    if percent < 100 {
+        span.yellow()
+    } else {
+        span.dark_gray()
+    }
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+enum SortOrder {
+    #[default]
+    Recent,
+    MostVisited,
+}
+
+impl SortOrder {
+    fn toggle(self) -> Self {
+        match self {
+            SortOrder::Recent => SortOrder::MostVisited,
+            SortOrder::MostVisited => SortOrder::Recent,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            SortOrder::Recent => "recent",
+            SortOrder::MostVisited => "most visited",
+        }
+    }
+}
+
+/// Lists the previously visited pages
+#[derive(Default)]
+pub struct HistoryComponent {
+    state: ListState,
+    sort_order: SortOrder,
+    /// Narrows the listed pages while typing
+    search: Option<Input>,
+}
+
+impl HistoryComponent {
+    /// Returns the entries matching the search, in the selected order
+    fn entries(&self) -> Vec<HistoryEntry> {
+        let mut entries: Vec<HistoryEntry> = match self.search {
+            Some(ref input) if !input.value().is_empty() => history()
+                .all()
+                .iter()
+                .filter(|entry| fuzzy_match(input.value(), &entry.title).is_some())
+                .cloned()
+                .collect(),
+            _ => history().all().to_vec(),
+        };
+
+        match self.sort_order {
+            SortOrder::Recent => entries.sort_by_key(|entry| Reverse(entry.last_visited)),
+            SortOrder::MostVisited => entries.sort_by(|a, b| {
+                b.visits
+                    .cmp(&a.visits)
+                    .then(b.last_visited.cmp(&a.last_visited))
+            }),
+        }
+        entries
+    }
+
+    fn select(&mut self, offset: isize) {
+        let len = self.entries().len();
+        if len == 0 {
+            return self.state.select(None);
+        }
+
+        let selected = match self.state.selected() {
+            Some(selected) => (selected as isize + offset).clamp(0, len as isize - 1) as usize,
+            None => 0,
+        };
+        self.state.select(Some(selected));
+    }
+
+    fn open_selected(&self) -> ActionResult {
+        let selected = self.state.selected();
+        if let Some(entry) = selected.and_then(|n| self.entries().get(n).cloned()) {
+            return ActionPacket::default()
+                .action(Action::SwitchContextPage)
+                .action(Action::PageViewer(PageViewerAction::ReopenPage(
+                    entry.title,
+                )))
+                .into();
+        }
+        ActionResult::Ignored
+    }
+
+    fn toggle_sort_order(&mut self) {
+        self.sort_order = self.sort_order.toggle();
+        self.state.select(None);
+    }
+}
+
+impl Component for HistoryComponent {
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        if let Some(ref mut input) = self.search {
+            return match key.code {
+                KeyCode::Enter => Action::History(HistoryAction::FinishSearch).into(),
+                KeyCode::Esc => Action::History(HistoryAction::CancelSearch).into(),
+                _ => {
+                    input.handle_event(&crossterm::event::Event::Key(key));
+                    self.state.select(None);
+                    ActionResult::consumed()
+                }
+            };
+        }
+
+        match key.code {
+            KeyCode::Enter => Action::History(HistoryAction::OpenSelected).into(),
+            KeyCode::Char('o') => Action::History(HistoryAction::ToggleSortOrder).into(),
+            KeyCode::Char('/') => Action::History(HistoryAction::Search).into(),
+            _ => ActionResult::Ignored,
+        }
+    }
+
+    fn keymap(&self) -> super::help::Keymap {
+        vec![
+            (
+                key_event!(Key::Enter),
+                Action::History(HistoryAction::OpenSelected).into(),
+            ),
+            (
+                key_event!('o'),
+                Action::History(HistoryAction::ToggleSortOrder).into(),
+            ),
+            (
+                key_event!('/'),
+                Action::History(HistoryAction::Search).into(),
+            ),
+        ]
+    }
+
+    fn is_typing(&self) -> bool {
+        self.search.is_some()
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        match action {
+            Action::History(history_action) => match history_action {
+                HistoryAction::OpenSelected => return self.open_selected(),
+                HistoryAction::ToggleSortOrder => self.toggle_sort_order(),
+                HistoryAction::Search => self.search = Some(Input::default()),
+                HistoryAction::FinishSearch => {
+                    if self.search.as_ref().map(|input| input.value().is_empty()) == Some(true) {
+                        self.search = None;
+                    }
+                    self.select(0);
+                }
+                HistoryAction::CancelSearch => {
+                    self.search = None;
+                    self.state.select(None);
+                }
+            },
+            Action::ScrollUp(n) => self.select(-(n as isize)),
+            Action::ScrollDown(n) => self.select(n as isize),
+            Action::UnselectScroll => self.state.select(None),
+            _ => return ActionResult::Ignored,
+        }
+        ActionResult::consumed()
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let [info_area, list_area] = {
+            let rects = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(1), Constraint::Percentage(100)])
+                .split(area);
+            [rects[0], rects[1]]
+        };
+
+        let info = match self.search {
+            Some(ref input) => Line::from(vec![
+                Span::raw("Search: ").dark_gray(),
+                Span::raw(input.value().to_string()),
+            ]),
+            None => Line::from(format!(
+                "Pages: {} | Sorted by: {}",
+                history().all().len(),
+                self.sort_order.name()
+            )),
+        };
+        f.render_widget(Paragraph::new(info), info_area);
+
+        let entries = self.entries();
+        if entries.is_empty() {
+            let message = if self.search.is_some() {
+                "No visited page matches the search"
+            } else {
+                "No pages visited yet"
+            };
+            f.render_widget(
+                Paragraph::new(message).alignment(Alignment::Center),
+                centered_rect(list_area, 100, 50),
+            );
+            return;
+        }
+
+        let items: Vec<ListItem> = entries
+            .iter()
+            .map(|entry| {
+                let last_visited = chrono::DateTime::from_timestamp(entry.last_visited, 0)
+                    .map(|last_visited| last_visited.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_default();
+                let visits = match entry.visits {
+                    1 => "1 visit".to_string(),
+                    n => format!("{} visits", n),
+                };
+                let mut spans = vec![
+                    Span::raw(entry.title.clone()),
+                    Span::raw(format!(" ({}, {})", last_visited, visits)).dark_gray(),
+                ];
+                if let Some(position) = entry.position {
+                    spans.push(progress_span(position.percent()));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(tr("history.title")),
+            )
+            .highlight_symbol("| ")
+            .highlight_spacing(HighlightSpacing::Always)
+            .highlight_style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::ITALIC),
+            );
+        f.render_stateful_widget(list, list_area, &mut self.state);
+    }
+}
@@ -0,0 +1,211 @@
+use std::cmp::Reverse;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Borders, HighlightSpacing, List, ListItem, ListState, Paragraph},
+};
+use tui_input::{backend::crossterm::EventHandler, Input};
+use wiki_api::languages::{Language, LANGUAGES};
+
+use crate::{
+    action::{Action, ActionPacket, ActionResult, LanguagesAction},
+    fuzzy::fuzzy_match,
+    i18n::tr,
+    key_event,
+    terminal::Frame,
+    ui::centered_rect,
+};
+
+use super::Component;
+
+/// Lists every Wikipedia edition, to switch the language pages are searched and loaded in
+#[derive(Default)]
+pub struct LanguagesComponent {
+    state: ListState,
+    /// Language that is currently used, highlighted in the list
+    current: Language,
+    /// Narrows the listed languages while typing
+    search: Option<Input>,
+}
+
+impl LanguagesComponent {
+    pub fn set_current(&mut self, language: Language) {
+        self.current = language;
+    }
+
+    /// Returns the languages matching the search, the best match first
+    fn languages(&self) -> Vec<Language> {
+        let query = match self.search {
+            Some(ref input) if !input.value().is_empty() => input.value(),
+            _ => return LANGUAGES.to_vec(),
+        };
+
+        let mut matches: Vec<(i64, &Language)> = LANGUAGES
+            .iter()
+            .filter_map(|language| {
+                [language.code(), language.name(), language.local_name()]
+                    .iter()
+                    .filter_map(|candidate| fuzzy_match(query, candidate))
+                    .map(|matched| matched.score)
+                    .max()
+                    .map(|score| (score, language))
+            })
+            .collect();
+        matches.sort_by_key(|(score, _)| Reverse(*score));
+        matches
+            .into_iter()
+            .map(|(_, language)| language.clone())
+            .collect()
+    }
+
+    fn select(&mut self, offset: isize) {
+        let len = self.languages().len();
+        if len == 0 {
+            return self.state.select(None);
+        }
+
+        let selected = match self.state.selected() {
+            Some(selected) => (selected as isize + offset).clamp(0, len as isize - 1) as usize,
+            None => 0,
+        };
+        self.state.select(Some(selected));
+    }
+
+    fn switch_to_selected(&self) -> ActionResult {
+        let selected = self.state.selected();
+        match selected.and_then(|n| self.languages().get(n).cloned()) {
+            Some(language) => ActionPacket::default()
+                .action(Action::SwitchLanguage(language))
+                .action(Action::SwitchPreviousContext)
+                .into(),
+            None => ActionResult::Ignored,
+        }
+    }
+}
+
+impl Component for LanguagesComponent {
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        if let Some(ref mut input) = self.search {
+            return match key.code {
+                KeyCode::Enter => Action::Languages(LanguagesAction::FinishSearch).into(),
+                KeyCode::Esc => Action::Languages(LanguagesAction::CancelSearch).into(),
+                _ => {
+                    input.handle_event(&crossterm::event::Event::Key(key));
+                    self.state.select(None);
+                    ActionResult::consumed()
+                }
+            };
+        }
+
+        match key.code {
+            KeyCode::Enter => Action::Languages(LanguagesAction::SwitchToSelected).into(),
+            KeyCode::Char('/') => Action::Languages(LanguagesAction::Search).into(),
+            _ => ActionResult::Ignored,
+        }
+    }
+
+    fn keymap(&self) -> super::help::Keymap {
+        vec![
+            (
+                key_event!(Key::Enter),
+                Action::Languages(LanguagesAction::SwitchToSelected).into(),
+            ),
+            (
+                key_event!('/'),
+                Action::Languages(LanguagesAction::Search).into(),
+            ),
+        ]
+    }
+
+    fn is_typing(&self) -> bool {
+        self.search.is_some()
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        match action {
+            Action::Languages(languages_action) => match languages_action {
+                LanguagesAction::SwitchToSelected => return self.switch_to_selected(),
+                LanguagesAction::Search => self.search = Some(Input::default()),
+                LanguagesAction::FinishSearch => {
+                    if self.search.as_ref().map(|input| input.value().is_empty()) == Some(true) {
+                        self.search = None;
+                    }
+                    self.select(0);
+                }
+                LanguagesAction::CancelSearch => {
+                    self.search = None;
+                    self.state.select(None);
+                }
+            },
+            Action::ScrollUp(n) => self.select(-(n as isize)),
+            Action::ScrollDown(n) => self.select(n as isize),
+            Action::UnselectScroll => self.state.select(None),
+            _ => return ActionResult::Ignored,
+        }
+        ActionResult::consumed()
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let [info_area, list_area] = {
+            let rects = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(1), Constraint::Percentage(100)])
+                .split(area);
+            [rects[0], rects[1]]
+        };
+
+        let info = match self.search {
+            Some(ref input) => Line::from(vec![
+                Span::raw("Search: ").dark_gray(),
+                Span::raw(input.value().to_string()),
+            ]),
+            None => Line::from(format!(
+                "Current language: {} | Press [/] to search",
+                self.current.name()
+            )),
+        };
+        f.render_widget(Paragraph::new(info), info_area);
+
+        let languages = self.languages();
+        if languages.is_empty() {
+            f.render_widget(
+                Paragraph::new(tr("languages.no_match")).alignment(Alignment::Center),
+                centered_rect(list_area, 100, 50),
+            );
+            return;
+        }
+
+        let items: Vec<ListItem> = languages
+            .iter()
+            .map(|language| {
+                let mut name = Span::raw(language.name().to_string());
+                if language == &self.current {
+                    name = name.bold();
+                }
+                ListItem::new(Line::from(vec![
+                    name,
+                    Span::raw(format!(" {} ({})", language.local_name(), language.code()))
+                        .dark_gray(),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(tr("languages.title")),
+            )
+            .highlight_symbol("| ")
+            .highlight_spacing(HighlightSpacing::Always)
+            .highlight_style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::ITALIC),
+            );
+        f.render_stateful_widget(list, list_area, &mut self.state);
+    }
+}
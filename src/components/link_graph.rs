@@ -0,0 +1,382 @@
+use std::collections::{HashMap, VecDeque};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Borders, HighlightSpacing, List, ListItem, Paragraph},
+};
+use tokio::sync::mpsc;
+use tracing::{error, warn};
+use wiki_api::{links::fetch_links, Endpoint};
+
+use crate::{
+    action::{Action, ActionResult, LinkGraphAction},
+    i18n::{tr, tr_args},
+    key_event,
+    terminal::Frame,
+    ui::{centered_rect, StatefulList},
+};
+
+use super::Component;
+
+/// Nodes deeper than this (the root having a depth of 0) can't be expanded
+const MAX_DEPTH: usize = 3;
+/// Maximum amount of links fetched for a single page
+const MAX_LINKS: usize = 500;
+/// Maximum amount of nodes expanded at once when expanding a whole level
+const MAX_LEVEL_EXPANSION: usize = 25;
+
+struct GraphNode {
+    title: String,
+    depth: usize,
+    /// `None` as long as the links of the page were not loaded
+    children: Option<Vec<usize>>,
+    is_expanded: bool,
+    is_loading: bool,
+}
+
+/// Explores the articles linked from a page as a lazily expanded tree
+pub struct LinkGraphComponent {
+    nodes: Vec<GraphNode>,
+    /// Indices of the nodes in the order they are shown
+    visible: StatefulList<usize>,
+    /// Already fetched links of a page, shared between all nodes with the same title
+    links: HashMap<String, Vec<String>>,
+
+    endpoint: Option<Endpoint>,
+    action_tx: Option<mpsc::UnboundedSender<Action>>,
+}
+
+impl Default for LinkGraphComponent {
+    fn default() -> Self {
+        Self {
+            nodes: Vec::new(),
+            visible: StatefulList::with_items(Vec::new()),
+            links: HashMap::new(),
+
+            endpoint: None,
+            action_tx: None,
+        }
+    }
+}
+
+impl LinkGraphComponent {
+    /// Fetches the links in the wiki at the endpoint
+    pub fn set_endpoint(&mut self, endpoint: Endpoint) {
+        self.endpoint = Some(endpoint);
+    }
+
+    fn open(&mut self, title: String) {
+        self.visible = StatefulList::with_items(Vec::new());
+        self.nodes = vec![GraphNode {
+            title,
+            depth: 0,
+            children: None,
+            is_expanded: false,
+            is_loading: false,
+        }];
+        self.update_visible();
+        self.visible.next();
+        self.expand(0);
+    }
+
+    fn selected_node(&self) -> Option<usize> {
+        self.visible.selected().copied()
+    }
+
+    /// Expands the node, fetching its links when they are not known yet
+    fn expand(&mut self, index: usize) {
+        let node = &mut self.nodes[index];
+        if node.depth >= MAX_DEPTH || node.is_loading {
+            return;
+        }
+        if node.children.is_some() {
+            node.is_expanded = true;
+            return self.update_visible();
+        }
+
+        if let Some(links) = self.links.get(&node.title).cloned() {
+            self.add_children(index, links);
+            return self.update_visible();
+        }
+
+        let endpoint = match self.endpoint {
+            Some(ref endpoint) => endpoint.clone(),
+            None => return error!("No Endpoint configured"),
+        };
+        let tx = self.action_tx.clone().unwrap();
+        let title = node.title.clone();
+
+        node.is_loading = true;
+        tokio::spawn(async move {
+            match fetch_links(endpoint, &title, MAX_LINKS).await {
+                Ok(links) => tx
+                    .send(Action::LinkGraph(LinkGraphAction::FinishLinks(
+                        title, links,
+                    )))
+                    .unwrap(),
+                Err(error) => {
+                    error!("Unable to fetch the links of '{}': {:?}", title, error);
+                    tx.send(Action::LinkGraph(LinkGraphAction::FinishLinks(
+                        title,
+                        Vec::new(),
+                    )))
+                    .unwrap()
+                }
+            }
+        });
+    }
+
+    /// Expands every collapsed node below the selected one that is not expanded yet, growing
+    /// the tree by one level
+    fn expand_level(&mut self) {
+        let selected = match self.selected_node() {
+            Some(selected) => selected,
+            None => return,
+        };
+
+        let mut collapsed = Vec::new();
+        let mut queue = VecDeque::from([selected]);
+        while let Some(index) = queue.pop_front() {
+            let node = &self.nodes[index];
+            match node.children {
+                Some(ref children) if node.is_expanded => queue.extend(children),
+                _ if node.depth < MAX_DEPTH => collapsed.push(index),
+                _ => {}
+            }
+        }
+
+        if collapsed.len() > MAX_LEVEL_EXPANSION {
+            warn!(
+                "only expanding {} of {} nodes",
+                MAX_LEVEL_EXPANSION,
+                collapsed.len()
+            );
+        }
+        for index in collapsed.into_iter().take(MAX_LEVEL_EXPANSION) {
+            self.expand(index);
+        }
+    }
+
+    fn collapse(&mut self) {
+        let selected = match self.selected_node() {
+            Some(selected) => selected,
+            None => return,
+        };
+
+        if self.nodes[selected].is_expanded {
+            self.nodes[selected].is_expanded = false;
+            self.update_visible();
+        }
+    }
+
+    fn add_children(&mut self, index: usize, links: Vec<String>) {
+        let depth = self.nodes[index].depth + 1;
+        let first = self.nodes.len();
+        self.nodes.extend(links.into_iter().map(|title| GraphNode {
+            title,
+            depth,
+            children: None,
+            is_expanded: false,
+            is_loading: false,
+        }));
+
+        let children = (first..self.nodes.len()).collect();
+        let node = &mut self.nodes[index];
+        node.children = Some(children);
+        node.is_expanded = true;
+        node.is_loading = false;
+    }
+
+    fn finish_links(&mut self, title: String, links: Vec<String>) {
+        let loading: Vec<usize> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| node.is_loading && node.title == title)
+            .map(|(index, _)| index)
+            .collect();
+
+        for index in loading {
+            self.add_children(index, links.clone());
+        }
+        self.links.insert(title, links);
+        self.update_visible();
+    }
+
+    /// Rebuilds the list of shown nodes, keeping the selection
+    fn update_visible(&mut self) {
+        let selected = self.visible.get_state_mut().selected();
+
+        let mut visible = Vec::new();
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let node = match self.nodes.get(index) {
+                Some(node) => node,
+                None => continue,
+            };
+            visible.push(index);
+            if let Some(ref children) = node.children {
+                if node.is_expanded {
+                    stack.extend(children.iter().rev());
+                }
+            }
+        }
+
+        self.visible = StatefulList::with_items(visible);
+        self.visible.get_state_mut().select(selected);
+    }
+
+    fn open_selected(&self) -> ActionResult {
+        if let Some(index) = self.selected_node() {
+            return Action::LoadPage(self.nodes[index].title.clone()).into();
+        }
+        ActionResult::Ignored
+    }
+}
+
+impl Component for LinkGraphComponent {
+    fn init(&mut self, action_tx: mpsc::UnboundedSender<Action>) -> anyhow::Result<()> {
+        self.action_tx = Some(action_tx);
+        Ok(())
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        match key.code {
+            KeyCode::Enter => Action::LinkGraph(LinkGraphAction::OpenSelected).into(),
+            KeyCode::Right => Action::LinkGraph(LinkGraphAction::Expand).into(),
+            KeyCode::Left => Action::LinkGraph(LinkGraphAction::Collapse).into(),
+            KeyCode::Char('x') => Action::LinkGraph(LinkGraphAction::ExpandLevel).into(),
+            _ => ActionResult::Ignored,
+        }
+    }
+
+    fn keymap(&self) -> super::help::Keymap {
+        vec![
+            (
+                key_event!(Key::Enter),
+                Action::LinkGraph(LinkGraphAction::OpenSelected).into(),
+            ),
+            (
+                key_event!(Key::Right),
+                Action::LinkGraph(LinkGraphAction::Expand).into(),
+            ),
+            (
+                key_event!(Key::Left),
+                Action::LinkGraph(LinkGraphAction::Collapse).into(),
+            ),
+            (
+                key_event!('x'),
+                Action::LinkGraph(LinkGraphAction::ExpandLevel).into(),
+            ),
+        ]
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        match action {
+            Action::LinkGraph(graph_action) => match graph_action {
+                LinkGraphAction::Open(title) => self.open(title),
+                LinkGraphAction::Expand => {
+                    if let Some(selected) = self.selected_node() {
+                        self.expand(selected);
+                    }
+                }
+                LinkGraphAction::ExpandLevel => self.expand_level(),
+                LinkGraphAction::Collapse => self.collapse(),
+                LinkGraphAction::FinishLinks(title, links) => self.finish_links(title, links),
+                LinkGraphAction::OpenSelected => return self.open_selected(),
+            },
+            Action::ScrollUp(n) => {
+                for _ in 0..n {
+                    self.visible.previous();
+                }
+            }
+            Action::ScrollDown(n) => {
+                for _ in 0..n {
+                    self.visible.next();
+                }
+            }
+            Action::UnselectScroll => self.visible.unselect(),
+            _ => return ActionResult::Ignored,
+        }
+        ActionResult::consumed()
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let root = match self.nodes.first() {
+            Some(root) => root,
+            None => {
+                f.render_widget(
+                    Paragraph::new(tr("link_graph.empty")).alignment(Alignment::Center),
+                    centered_rect(area, 100, 50),
+                );
+                return;
+            }
+        };
+
+        let [info_area, graph_area] = {
+            let rects = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(1), Constraint::Percentage(100)])
+                .split(area);
+            [rects[0], rects[1]]
+        };
+
+        f.render_widget(
+            Paragraph::new(tr_args(
+                "link_graph.info",
+                &[
+                    ("title", root.title.clone()),
+                    ("pages", self.nodes.len().to_string()),
+                    ("depth", MAX_DEPTH.to_string()),
+                ],
+            )),
+            info_area,
+        );
+
+        let items: Vec<ListItem> = self
+            .visible
+            .get_items()
+            .iter()
+            .map(|&index| {
+                let node = &self.nodes[index];
+                let marker = if node.is_loading {
+                    "…"
+                } else if node.depth >= MAX_DEPTH {
+                    " "
+                } else if node.is_expanded {
+                    "▾"
+                } else {
+                    "▸"
+                };
+
+                let mut spans = vec![
+                    Span::raw("  ".repeat(node.depth)),
+                    Span::raw(format!("{} ", marker)).red(),
+                    Span::raw(node.title.clone()),
+                ];
+                if let Some(ref children) = node.children {
+                    spans.push(Span::raw(format!(" ({})", children.len())).dark_gray());
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(tr("link_graph.title")),
+            )
+            .highlight_symbol("| ")
+            .highlight_spacing(HighlightSpacing::Always)
+            .highlight_style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::ITALIC),
+            );
+        f.render_stateful_widget(list, graph_area, self.visible.get_state_mut());
+    }
+}
@@ -7,7 +7,7 @@ use ratatui::{
 use tokio::sync::mpsc;
 use tui_logger::{TuiLoggerWidget, TuiWidgetState};
 
-use crate::{action::Action, terminal::Frame};
+use crate::{action::Action, i18n::tr, terminal::Frame};
 
 use super::Component;
 
@@ -24,7 +24,7 @@ impl Component for LoggerComponent {
 
     fn render(&mut self, frame: &mut Frame<'_>, size: Rect) {
         let widget = TuiLoggerWidget::default()
-            .block(Block::new().title("Log").borders(Borders::ALL))
+            .block(Block::new().title(tr("logger.title")).borders(Borders::ALL))
             .style_error(Style::default().fg(Color::Red))
             .style_warn(Style::default().fg(Color::Yellow))
             .style_info(Style::default().fg(Color::Cyan))
@@ -0,0 +1,308 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::Rect,
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
+};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+use url::Url;
+use wiki_api::{
+    languages::Language,
+    media::{fetch_media_file, fetch_media_info, file_name, MediaInfo},
+};
+
+use crate::{
+    action::{Action, ActionResult, MediaAction, PageAction},
+    browser::open_url,
+    config::config,
+    export::media_directory,
+    i18n::{tr, tr_args},
+    tasks::{spawn_task, TaskKind},
+    terminal::Frame,
+    ui::centered_rect,
+};
+
+use super::{cache::format_size, Component};
+
+const DIALOG_WIDTH: u16 = 70;
+const DIALOG_HEIGHT: u16 = 16;
+
+fn media_action(action: MediaAction) -> Action {
+    Action::Page(PageAction::Media(action))
+}
+
+/// Progress of fetching the description of the file
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum InfoStatus {
+    Loading,
+    Loaded(Box<MediaInfo>),
+    Failed(String),
+}
+
+/// Progress of downloading the original file
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum DownloadStatus {
+    NotRequested,
+    Downloading,
+    Finished(PathBuf),
+    Failed(String),
+}
+
+/// Writes the downloaded file into the media directory and returns its path
+fn save_media_file(name: &str, contents: &[u8]) -> anyhow::Result<PathBuf> {
+    let directory = media_directory(&config().export)?;
+    std::fs::create_dir_all(&directory).with_context(|| {
+        format!(
+            "unable to create the media directory '{}'",
+            directory.display()
+        )
+    })?;
+
+    // the name comes from the wiki, it must not leave the directory
+    let name = name.replace(['/', '\\'], "_");
+    let path = directory.join(name);
+    std::fs::write(&path, contents)
+        .with_context(|| format!("unable to write '{}'", path.display()))?;
+    Ok(path)
+}
+
+/// Dialog describing a media file: its description, author, license and resolution, from its
+/// file description page. The original file can be downloaded into the media directory
+pub struct MediaComponent {
+    title: String,
+    info: InfoStatus,
+    download: DownloadStatus,
+
+    action_tx: Option<mpsc::UnboundedSender<Action>>,
+}
+
+impl MediaComponent {
+    /// Creates the dialog for the file description page with the title and starts fetching it
+    /// from the wiki in the language
+    pub fn new(
+        title: String,
+        language: &Language,
+        action_tx: Option<mpsc::UnboundedSender<Action>>,
+    ) -> Self {
+        let component = Self {
+            title,
+            info: InfoStatus::Loading,
+            download: DownloadStatus::NotRequested,
+            action_tx,
+        };
+        component.fetch_info(language);
+        component
+    }
+
+    fn fetch_info(&self, language: &Language) {
+        let action_tx = match self.action_tx {
+            Some(ref action_tx) => action_tx.clone(),
+            None => return,
+        };
+
+        let endpoint = language.wikipedia_endpoint();
+        let title = self.title.clone();
+        tokio::spawn(async move {
+            let result = fetch_media_info(endpoint, &title)
+                .await
+                .map(Box::new)
+                .map_err(|error| {
+                    warn!(
+                        "unable to fetch the description of '{}': {:?}",
+                        title, error
+                    );
+                    format!("{:#}", error)
+                });
+            let _ = action_tx.send(media_action(MediaAction::FinishLoading(title, result)));
+        });
+    }
+
+    fn download(&mut self) {
+        let info = match self.info {
+            InfoStatus::Loaded(ref info) => info.clone(),
+            _ => return,
+        };
+        if self.download == DownloadStatus::Downloading {
+            return;
+        }
+        let action_tx = match self.action_tx {
+            Some(ref action_tx) => action_tx.clone(),
+            None => return,
+        };
+
+        self.download = DownloadStatus::Downloading;
+        let title = self.title.clone();
+        let name = info.file_name().to_string();
+        spawn_task(TaskKind::Download, name, async move {
+            let result = async {
+                let url = Url::parse(&info.url)
+                    .with_context(|| format!("the url '{}' is invalid", info.url))?;
+                let contents = fetch_media_file(url).await?;
+                save_media_file(info.file_name(), &contents)
+            }
+            .await
+            .map_err(|error| {
+                warn!("unable to download '{}': {:?}", info.title, error);
+                format!("{:#}", error)
+            });
+            let _ = action_tx.send(media_action(MediaAction::FinishDownload(title, result)));
+        });
+    }
+
+    fn finish_download(&mut self, result: Result<PathBuf, String>) {
+        self.download = match result {
+            Ok(path) => {
+                info!("downloaded '{}' to '{}'", self.title, path.display());
+                DownloadStatus::Finished(path)
+            }
+            Err(error) => DownloadStatus::Failed(error),
+        }
+    }
+
+    fn open_description(&self) {
+        let url = match self.info {
+            InfoStatus::Loaded(ref info) if !info.description_url.is_empty() => {
+                info.description_url.clone()
+            }
+            _ => return,
+        };
+        if let Err(error) = open_url(&url) {
+            warn!("unable to open the file description page: {:?}", error);
+        }
+    }
+
+    /// Returns a line with the label and value, lines of missing values are left out
+    fn field(label: &'static str, value: Option<String>) -> Option<Line<'static>> {
+        Some(Line::from(vec![
+            Span::raw(format!("{}: ", tr(label))).dark_gray(),
+            Span::raw(value?),
+        ]))
+    }
+
+    fn info_lines(info: &MediaInfo) -> Vec<Line<'static>> {
+        let resolution = match (info.width, info.height) {
+            (Some(width), Some(height)) => Some(format!("{} × {} px", width, height)),
+            _ => None,
+        };
+        let license = match (&info.license, &info.license_url) {
+            (Some(license), Some(url)) => Some(format!("{} ({})", license, url)),
+            (license, _) => license.clone(),
+        };
+        let uploaded = info
+            .uploaded
+            .as_deref()
+            .map(|uploaded| uploaded.split('T').next().unwrap_or(uploaded).to_string());
+
+        let mut lines = vec![
+            Line::from(info.description.clone().unwrap_or_default()),
+            Line::default(),
+        ];
+        lines.extend(
+            vec![
+                Self::field("media.author", info.author.clone()),
+                Self::field("media.license", license),
+                Self::field("media.credit", info.credit.clone()),
+                Self::field("media.resolution", resolution),
+                Self::field(
+                    "media.file",
+                    Some(format!("{}, {}", info.mime_type, format_size(info.size))),
+                ),
+                Self::field("media.uploaded", uploaded),
+            ]
+            .into_iter()
+            .flatten(),
+        );
+        lines
+    }
+
+    fn download_line(&self) -> Line<'static> {
+        match self.download {
+            DownloadStatus::NotRequested => Line::default(),
+            DownloadStatus::Downloading => Line::from(tr("media.downloading").yellow()),
+            DownloadStatus::Finished(ref path) => Line::from(
+                tr_args("media.downloaded", &[("path", path.display().to_string())]).green(),
+            ),
+            DownloadStatus::Failed(ref error) => {
+                Line::from(tr_args("media.download_failed", &[("error", error.clone())]).red())
+            }
+        }
+    }
+}
+
+impl Component for MediaComponent {
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        match key.code {
+            KeyCode::Char('d') => media_action(MediaAction::Download).into(),
+            KeyCode::Char('o') => media_action(MediaAction::OpenDescription).into(),
+            KeyCode::Esc | KeyCode::Char('q') => media_action(MediaAction::Close).into(),
+            // the dialog is modal
+            _ => ActionResult::consumed(),
+        }
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        let action = match action {
+            Action::Page(PageAction::Media(action)) => action,
+            _ => return ActionResult::Ignored,
+        };
+
+        match action {
+            MediaAction::Download => self.download(),
+            MediaAction::OpenDescription => self.open_description(),
+            // results for files of a dialog that was closed in the meantime are dropped
+            MediaAction::FinishLoading(title, result) if title == self.title => {
+                self.info = match result {
+                    Ok(info) => InfoStatus::Loaded(info),
+                    Err(error) => InfoStatus::Failed(error),
+                }
+            }
+            MediaAction::FinishDownload(title, result) if title == self.title => {
+                self.finish_download(result)
+            }
+            _ => return ActionResult::Ignored,
+        }
+        ActionResult::consumed()
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let height = DIALOG_HEIGHT.min(area.height);
+        let area = Rect {
+            y: area.y + (area.height - height) / 2,
+            height,
+            ..centered_rect(area, DIALOG_WIDTH, 100)
+        };
+
+        let mut lines = vec![
+            Line::from(file_name(&self.title).to_string().bold()),
+            Line::default(),
+        ];
+        match self.info {
+            InfoStatus::Loading => lines.push(Line::from(tr("media.loading").yellow())),
+            InfoStatus::Loaded(ref info) => {
+                lines.append(&mut Self::info_lines(info));
+                lines.push(Line::default());
+                lines.push(self.download_line());
+            }
+            InfoStatus::Failed(ref error) => lines.push(Line::from(
+                tr_args("media.failed", &[("error", error.clone())]).red(),
+            )),
+        }
+
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(Color::Yellow))
+                    .title(tr("media.title")),
+            ),
+            area,
+        );
+    }
+}
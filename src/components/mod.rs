@@ -11,13 +11,36 @@ use crate::{
 
 use self::help::Keymap;
 
+pub mod api_error;
+pub mod bookmarks;
+pub mod cache;
+pub mod command_palette;
+pub mod config_problems;
+pub mod context_menu;
+pub mod disambiguation;
+pub mod external_link;
+pub mod feed;
+pub mod fetch_queue;
 pub mod help;
+pub mod history;
+pub mod languages;
+pub mod link_graph;
 pub mod logger;
+pub mod media;
+pub mod nearby;
+pub mod notes;
 pub mod page;
 pub mod page_viewer;
+pub mod portals;
+pub mod quit;
+pub mod reading_queue;
+pub mod render_stats;
 pub mod search;
 pub mod search_bar;
+pub mod setup;
 pub mod status;
+pub mod workspaces;
+pub mod yank_ring;
 
 #[macro_export]
 macro_rules! key_event {
@@ -68,6 +91,12 @@ pub trait Component {
         Vec::new()
     }
 
+    /// Whether the component takes text input right now, like a search field. Key sequences
+    /// aren't recognized while typing
+    fn is_typing(&self) -> bool {
+        false
+    }
+
     #[allow(unused_variables)]
     fn update(&mut self, action: Action) -> ActionResult {
         ActionResult::Ignored
@@ -0,0 +1,345 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, HighlightSpacing, List, ListItem, Paragraph},
+};
+use tokio::sync::mpsc;
+use tracing::error;
+use wiki_api::{
+    geosearch::{geosearch, Coordinates, GeoSearchResult},
+    Endpoint,
+};
+
+use crate::{
+    action::{Action, ActionResult, NearbyAction},
+    config::config,
+    i18n::{tr, tr_args},
+    key_event,
+    terminal::Frame,
+    ui::{centered_rect, ScrollBehaviour, StatefulList},
+};
+
+use super::Component;
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+enum SortOrder {
+    #[default]
+    Distance,
+    Title,
+}
+
+impl SortOrder {
+    fn toggle(self) -> Self {
+        match self {
+            SortOrder::Distance => SortOrder::Title,
+            SortOrder::Title => SortOrder::Distance,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            SortOrder::Distance => "distance",
+            SortOrder::Title => "title",
+        }
+    }
+}
+
+/// Lists the pages located near some coordinates
+pub struct NearbyComponent {
+    coordinates: Option<Coordinates>,
+    results: StatefulList<GeoSearchResult>,
+    sort_order: SortOrder,
+    is_processing: bool,
+
+    endpoint: Option<Endpoint>,
+    action_tx: Option<mpsc::UnboundedSender<Action>>,
+}
+
+impl Default for NearbyComponent {
+    fn default() -> Self {
+        Self {
+            coordinates: None,
+            results: StatefulList::with_items(Vec::new())
+                .scroll_behavior(ScrollBehaviour::StickToEnds),
+            sort_order: SortOrder::default(),
+            is_processing: false,
+
+            endpoint: None,
+            action_tx: None,
+        }
+    }
+}
+
+/// Formats a distance in meters into a human readable form
+fn format_distance(meters: f64) -> String {
+    if meters < 1000.0 {
+        format!("{:.0} m", meters)
+    } else {
+        format!("{:.1} km", meters / 1000.0)
+    }
+}
+
+impl NearbyComponent {
+    /// Searches for pages in the wiki at the endpoint
+    pub fn set_endpoint(&mut self, endpoint: Endpoint) {
+        self.endpoint = Some(endpoint);
+    }
+
+    fn start_search(&mut self, coordinates: Option<Coordinates>) {
+        let coordinates = match coordinates {
+            Some(coordinates) => coordinates,
+            None => match config().nearby.home.as_deref().map(str::parse) {
+                Some(Ok(coordinates)) => coordinates,
+                Some(Err(error)) => return error!("invalid home location: {:?}", error),
+                None => return error!("no coordinates given and no home location configured"),
+            },
+        };
+
+        let endpoint = match self.endpoint {
+            Some(ref endpoint) => endpoint.clone(),
+            None => return error!("No Endpoint configured"),
+        };
+        let tx = self.action_tx.clone().unwrap();
+        let (radius, limit) = (config().nearby.radius, config().nearby.limit);
+
+        self.is_processing = true;
+        tokio::spawn(async move {
+            match geosearch(endpoint, coordinates, radius, limit).await {
+                Ok(results) => tx.send(Action::Nearby(NearbyAction::FinishSearch(
+                    coordinates,
+                    results,
+                ))),
+                Err(error) => {
+                    error!("Unable to search for nearby pages: {:?}", error);
+                    tx.send(Action::Nearby(NearbyAction::AbortSearch))
+                }
+            }
+            .unwrap();
+        });
+    }
+
+    fn finish_search(&mut self, coordinates: Coordinates, results: Vec<GeoSearchResult>) {
+        self.is_processing = false;
+        self.coordinates = Some(coordinates);
+        self.results =
+            StatefulList::with_items(results).scroll_behavior(ScrollBehaviour::StickToEnds);
+        self.sort();
+        self.results.next();
+    }
+
+    /// Sorts the results in the sort order, the selection stays on the same page
+    fn sort(&mut self) {
+        let selected = self.results.selected().map(|result| result.pageid);
+        let results = self.results.get_items_mut();
+        match self.sort_order {
+            SortOrder::Distance => {
+                results.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+            }
+            SortOrder::Title => results.sort_by(|a, b| a.title.cmp(&b.title)),
+        }
+
+        if let Some(pageid) = selected {
+            let position = results.iter().position(|result| result.pageid == pageid);
+            self.results.get_state_mut().select(position);
+        }
+    }
+
+    fn toggle_sort_order(&mut self) {
+        self.sort_order = self.sort_order.toggle();
+        self.sort();
+    }
+
+    fn open_selected(&self) -> ActionResult {
+        if let Some(result) = self.results.selected() {
+            return Action::LoadPage(result.title.clone()).into();
+        }
+        ActionResult::Ignored
+    }
+}
+
+impl Component for NearbyComponent {
+    fn init(&mut self, action_tx: mpsc::UnboundedSender<Action>) -> anyhow::Result<()> {
+        self.action_tx = Some(action_tx);
+        Ok(())
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        if self.is_processing {
+            return ActionResult::Ignored;
+        }
+
+        match key.code {
+            KeyCode::Enter => Action::Nearby(NearbyAction::OpenSelected).into(),
+            KeyCode::Char('o') => Action::Nearby(NearbyAction::ToggleSortOrder).into(),
+            _ => ActionResult::Ignored,
+        }
+    }
+
+    fn keymap(&self) -> super::help::Keymap {
+        vec![
+            (
+                key_event!(Key::Enter),
+                Action::Nearby(NearbyAction::OpenSelected).into(),
+            ),
+            (
+                key_event!('o'),
+                Action::Nearby(NearbyAction::ToggleSortOrder).into(),
+            ),
+        ]
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        match action {
+            Action::Nearby(nearby_action) => match nearby_action {
+                NearbyAction::Search(coordinates) => self.start_search(coordinates),
+                NearbyAction::FinishSearch(coordinates, results) => {
+                    self.finish_search(coordinates, results)
+                }
+                NearbyAction::AbortSearch => self.is_processing = false,
+                NearbyAction::OpenSelected => return self.open_selected(),
+                NearbyAction::ToggleSortOrder => self.toggle_sort_order(),
+            },
+            Action::ScrollUp(n) => {
+                for _ in 0..n {
+                    self.results.previous();
+                }
+            }
+            Action::ScrollDown(n) => {
+                for _ in 0..n {
+                    self.results.next();
+                }
+            }
+            Action::UnselectScroll => self.results.unselect(),
+            _ => return ActionResult::Ignored,
+        }
+        ActionResult::consumed()
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        if self.is_processing {
+            f.render_widget(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(Color::Yellow)),
+                area,
+            );
+            f.render_widget(
+                Paragraph::new(tr("nearby.searching")).alignment(Alignment::Center),
+                centered_rect(area, 100, 50),
+            );
+            return;
+        }
+
+        let coordinates = match self.coordinates {
+            Some(coordinates) => coordinates,
+            None => {
+                f.render_widget(
+                    Paragraph::new(tr("nearby.usage")).alignment(Alignment::Center),
+                    centered_rect(area, 100, 50),
+                );
+                return;
+            }
+        };
+
+        let [info_area, results_area] = {
+            let rects = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(1), Constraint::Percentage(100)])
+                .split(area);
+            [rects[0], rects[1]]
+        };
+
+        f.render_widget(
+            Paragraph::new(tr_args(
+                "nearby.info",
+                &[
+                    ("lat", coordinates.lat.to_string()),
+                    ("lon", coordinates.lon.to_string()),
+                    ("results", self.results.get_items().len().to_string()),
+                    ("order", self.sort_order.name().to_string()),
+                ],
+            )),
+            info_area,
+        );
+
+        let items: Vec<ListItem> = self
+            .results
+            .get_items()
+            .iter()
+            .map(|result| {
+                ListItem::new(Line::from(vec![
+                    Span::raw(format!("{:>9} ", format_distance(result.distance))).red(),
+                    Span::raw(result.title.clone()),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(tr("nearby.title")),
+            )
+            .highlight_symbol("| ")
+            .highlight_spacing(HighlightSpacing::Always)
+            .highlight_style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::ITALIC),
+            );
+        f.render_stateful_widget(list, results_area, self.results.get_state_mut());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiki_api::geosearch::{Coordinates, GeoSearchResult};
+
+    use super::NearbyComponent;
+
+    fn result(title: &str, pageid: usize, distance: f64) -> GeoSearchResult {
+        GeoSearchResult {
+            title: title.to_string(),
+            pageid,
+            coordinates: Coordinates { lat: 0.0, lon: 0.0 },
+            distance,
+        }
+    }
+
+    #[test]
+    fn sorting_keeps_selection() {
+        let mut nearby = NearbyComponent::default();
+        nearby.finish_search(
+            Coordinates { lat: 0.0, lon: 0.0 },
+            vec![
+                result("Alexanderplatz", 1, 300.0),
+                result("Brandenburg Gate", 2, 200.0),
+                result("Charlottenburg", 3, 100.0),
+            ],
+        );
+        // the closest page is selected first
+        assert_eq!(
+            nearby.results.selected().map(|result| result.pageid),
+            Some(3)
+        );
+
+        nearby.toggle_sort_order();
+        let titles: Vec<&str> = nearby
+            .results
+            .get_items()
+            .iter()
+            .map(|result| result.title.as_str())
+            .collect();
+        assert_eq!(
+            titles,
+            ["Alexanderplatz", "Brandenburg Gate", "Charlottenburg"]
+        );
+        assert_eq!(
+            nearby.results.selected().map(|result| result.pageid),
+            Some(3)
+        );
+    }
+}
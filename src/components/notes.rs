@@ -0,0 +1,163 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::{Alignment, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, HighlightSpacing, List, ListItem, ListState, Paragraph},
+};
+
+use crate::{
+    action::{Action, ActionResult, NotesAction},
+    annotations::annotations,
+    flashcards::FlashcardTarget,
+    i18n::tr,
+    key_event,
+    terminal::Frame,
+    ui::centered_rect,
+    undo::{record, Undoable},
+};
+
+use super::Component;
+
+/// Lists the annotations of all pages
+#[derive(Default)]
+pub struct NotesComponent {
+    state: ListState,
+}
+
+impl NotesComponent {
+    fn select(&mut self, offset: isize) {
+        let len = annotations().all().len();
+        if len == 0 {
+            return self.state.select(None);
+        }
+
+        let selected = match self.state.selected() {
+            Some(selected) => (selected as isize + offset).clamp(0, len as isize - 1) as usize,
+            None => 0,
+        };
+        self.state.select(Some(selected));
+    }
+
+    fn open_selected(&self) -> ActionResult {
+        let selected = self.state.selected();
+        if let Some(annotation) = selected.and_then(|n| annotations().all().get(n).cloned()) {
+            return Action::LoadPage(annotation.page).into();
+        }
+        ActionResult::Ignored
+    }
+
+    fn delete_selected(&mut self) {
+        if let Some(selected) = self.state.selected() {
+            let removed = annotations().remove(selected);
+            if let Some(removed) = removed {
+                record(Undoable::Annotation(selected, removed));
+            }
+            self.select(0);
+        }
+    }
+}
+
+impl Component for NotesComponent {
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        match key.code {
+            KeyCode::Enter => Action::Notes(NotesAction::OpenSelected).into(),
+            KeyCode::Char('d') => Action::Notes(NotesAction::DeleteSelected).into(),
+            KeyCode::Char('e') => Action::ExportFlashcards(FlashcardTarget::Tsv).into(),
+            KeyCode::Char('E') => Action::ExportFlashcards(FlashcardTarget::AnkiConnect).into(),
+            _ => ActionResult::Ignored,
+        }
+    }
+
+    fn keymap(&self) -> super::help::Keymap {
+        vec![
+            (
+                key_event!(Key::Enter),
+                Action::Notes(NotesAction::OpenSelected).into(),
+            ),
+            (
+                key_event!('d'),
+                Action::Notes(NotesAction::DeleteSelected).into(),
+            ),
+            (
+                key_event!('e'),
+                Action::ExportFlashcards(FlashcardTarget::Tsv).into(),
+            ),
+            (
+                key_event!('E', Modifier::SHIFT),
+                Action::ExportFlashcards(FlashcardTarget::AnkiConnect).into(),
+            ),
+        ]
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        match action {
+            Action::Notes(notes_action) => match notes_action {
+                NotesAction::OpenSelected => return self.open_selected(),
+                NotesAction::DeleteSelected => self.delete_selected(),
+            },
+            Action::ScrollUp(n) => self.select(-(n as isize)),
+            Action::ScrollDown(n) => self.select(n as isize),
+            Action::UnselectScroll => self.state.select(None),
+            _ => return ActionResult::Ignored,
+        }
+        ActionResult::consumed()
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let annotations = annotations();
+        if annotations.all().is_empty() {
+            f.render_widget(
+                Paragraph::new(tr("notes.empty")).alignment(Alignment::Center),
+                centered_rect(area, 100, 50),
+            );
+            return;
+        }
+
+        let width = area.width.saturating_sub(3) as usize;
+        let items: Vec<ListItem> = annotations
+            .all()
+            .iter()
+            .map(|annotation| {
+                let created = chrono::DateTime::from_timestamp(annotation.created, 0)
+                    .map(|created| created.format(" (%Y-%m-%d)").to_string())
+                    .unwrap_or_default();
+                let mut text = Text::from(Line::from(vec![
+                    Span::raw(annotation.page.clone()).red(),
+                    Span::raw(created).dark_gray(),
+                ]));
+                text.lines.append(
+                    &mut textwrap::wrap(&format!("\"{}\"", annotation.text), width)
+                        .iter()
+                        .map(|s| Line::from(s.to_string()))
+                        .collect(),
+                );
+                if !annotation.note.is_empty() {
+                    text.lines.append(
+                        &mut textwrap::wrap(&annotation.note, width)
+                            .iter()
+                            .map(|s| Line::from(Span::raw(s.to_string()).italic()))
+                            .collect(),
+                    );
+                }
+                ListItem::new(text)
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(tr("notes.title")),
+            )
+            .highlight_symbol("| ")
+            .repeat_highlight_symbol(true)
+            .highlight_spacing(HighlightSpacing::Always)
+            .highlight_style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::ITALIC),
+            );
+        f.render_stateful_widget(list, area, &mut self.state);
+    }
+}
@@ -1,22 +1,78 @@
-use std::collections::HashMap;
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    ops::Range,
+    time::Instant,
+};
 
+use chrono::Local;
 use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{
+    prelude::{Alignment, Constraint, Direction, Layout},
     prelude::{Margin, Rect},
-    style::{Modifier, Style, Stylize},
+    style::{Color, Modifier, Style, Stylize},
     text::{Line, Span},
-    widgets::{Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
+};
+use tokio::sync::mpsc;
+use tracing::{debug, error, info, warn};
+use tui_input::{backend::crossterm::EventHandler, Input};
+use wiki_api::{
+    backend::Summary,
+    document::{Data, Document, Node},
+    media::{is_file_title, media_link_title},
+    outline::{outline_section, SectionOutline},
+    page::{Page, Section},
+    pageviews::PageViews,
+    taxobox,
 };
-use tracing::{debug, info};
-use wiki_api::{document::Data, page::Page};
 
 use crate::{
-    action::{Action, ActionResult, PageAction},
-    components::Component,
-    has_modifier, key_event,
-    renderer::{default_renderer::render_document, RenderedDocument},
+    action::{
+        Action, ActionPacket, ActionResult, ExternalLinkAction, LinkGraphAction, MediaAction,
+        PageAction, ViewportPosition,
+    },
+    annotations::{annotations, find_passage, Annotation},
+    bookmarks::bookmarks,
+    bug_report::PageReport,
+    citation::{add_to_bibliography, cite, CitationFormat},
+    components::{
+        context_menu::ContextMenu,
+        external_link::ExternalLinkComponent,
+        media::MediaComponent,
+        render_stats::{self, RenderStats},
+        Component,
+    },
+    config::{config, Columns, Navigation, RendererConfig, ThemePreset},
+    event::RESIZE_DEBOUNCE,
+    export::{article_url, save_dump, save_markdown, save_text},
+    fuzzy::rank_matches,
+    has_modifier,
+    history::ReadingPosition,
+    hooks::{article_placeholders, run_hook, Hook},
+    i18n::{tr, tr_args},
+    key_event,
+    renderer::{
+        cache::RenderCache,
+        default_renderer::{
+            is_link, is_top_level_section, link_nodes, render_document_sections, RenderedSection,
+            SectionRenders,
+        },
+        epub::save_epub,
+        html::save_html,
+        is_long_table, table_rows, RenderedDocument, RenderedTable, Word,
+    },
+    scroll_marks::scroll_marks,
+    sources::content_source,
+    speech::{split_sentences, Sentence, Speaker, WordPosition},
+    tasks::{spawn_task, TaskKind},
     terminal::Frame,
-    ui::padded_rect,
+    ui::{
+        centered_rect, highlight_matches, padded_rect, render_summary_popup, theme_preset,
+        ResponsiveLayout, SectionScrollbar,
+    },
+    undo::{record, Undoable},
+    yank_ring::{yank_ring, Yank, YankKind},
 };
 
 #[cfg(debug_assertions)]
@@ -25,7 +81,30 @@ use crate::renderer::test_renderer::{render_nodes_raw, render_tree_data, render_
 const SCROLLBAR: bool = true;
 const LINK_SELECT: bool = true;
 
-#[derive(Default, Debug, Clone, PartialEq, Eq)]
+/// How many columns the page is scrolled horizontally at once
+const HORIZONTAL_SCROLL_STEP: u16 = 4;
+
+/// With `renderer.columns = "auto"`, pages at least this wide are shown in two columns
+const COLUMNS_AUTO_MIN_WIDTH: u16 = 160;
+/// Pages are only split into columns when every column is at least this wide
+const COLUMN_MIN_WIDTH: u16 = 40;
+/// Width of the space between two columns, with a line in its middle
+const COLUMN_GAP: u16 = 3;
+
+/// Zoom of a page that isn't zoomed in, its text takes up the whole width
+const MAX_ZOOM: u16 = 100;
+/// Zooming in stops once the text is narrowed to this percentage of the width
+const MIN_ZOOM: u16 = 50;
+const ZOOM_STEP: u16 = 10;
+
+const NOTE_INPUT_HEIGHT: u16 = 3;
+const NOTE_INPUT_WIDTH: u16 = 80;
+
+/// The text of the selected table cell or abbreviation takes up at most this fraction of the page
+/// height
+const INFO_LINE_MAX_HEIGHT_FRACTION: u16 = 3;
+
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
 #[repr(u8)]
 pub enum Renderer {
     #[default]
@@ -57,290 +136,3123 @@ impl Renderer {
     }
 }
 
+/// Everything a rendered page depends on
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct RenderKey {
+    width: u16,
+    /// Zoom the page is shown at, in percent
+    zoom: u16,
+    renderer: Renderer,
+    /// Collapsing a section only renders that section again, see [`SectionKey`]. The render from
+    /// before stays cached, so expanding the section again doesn't render anything
+    collapsed: BTreeSet<usize>,
+    taxoboxes: bool,
+    theme: ThemePreset,
+    /// How many links have a language hint, they are only ever added
+    language_hints: usize,
+}
+
+/// Everything the render of a top-level section depends on
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct SectionKey {
+    section: usize,
+    width: u16,
+    taxoboxes: bool,
+    theme: ThemePreset,
+    /// The collapsed sections and boxes inside of the section
+    collapsed: BTreeSet<usize>,
+    /// How many links of the section have a language hint
+    language_hints: usize,
+}
+
+/// The cached renders of the top-level sections, used while the page is rendered
+struct PageSections<'a> {
+    cache: &'a mut RenderCache<SectionKey, RenderedSection>,
+    document: &'a Document,
+    width: u16,
+    taxoboxes: bool,
+    collapsed: &'a BTreeSet<usize>,
+    language_hints: &'a BTreeMap<usize, String>,
+}
+
+impl SectionRenders for PageSections<'_> {
+    fn get_or_render(
+        &mut self,
+        section: Node<'_>,
+        render: &mut dyn FnMut() -> RenderedSection,
+    ) -> RenderedSection {
+        let document = self.document;
+        let is_inside = |index: &usize| {
+            let mut node = document.nth(*index);
+            while let Some(current) = node {
+                if current.index() == section.index() {
+                    return true;
+                }
+                node = current.parent();
+            }
+            false
+        };
+        let key = SectionKey {
+            section: section.index(),
+            width: self.width,
+            taxoboxes: self.taxoboxes,
+            theme: theme_preset(),
+            collapsed: self.collapsed.iter().copied().filter(is_inside).collect(),
+            language_hints: self
+                .language_hints
+                .keys()
+                .filter(|index| is_inside(index))
+                .count(),
+        };
+        self.cache.get_or_render(key, render).clone()
+    }
+}
+
+/// Cell of a table selected in table mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TableCell {
+    /// Index of the table node
+    table: usize,
+    row: usize,
+    column: usize,
+}
+
+/// Text searched for in the page
+struct PageFind {
+    input: Input,
+    /// Whether the query is still being typed
+    is_typing: bool,
+    /// Section the matches are limited to, together with its subsections. `None` finds the
+    /// matches in the whole page
+    section: Option<usize>,
+    /// First and last word of every match, in the order of the page
+    matches: Vec<(WordPosition, WordPosition)>,
+    /// The match scrolled to last
+    current: Option<usize>,
+    /// The render the matches were found in, `None` after the query or the scope changed
+    key: Option<RenderKey>,
+}
+
+/// Returns the first and last word of every occurrence of the query in the lines, ignoring the
+/// case. Occurrences continuing on the next line aren't found
+fn find_matches(lines: &[Vec<Word>], query: &str) -> Vec<(WordPosition, WordPosition)> {
+    let fold = |c: char| c.to_lowercase().next().unwrap_or(c);
+    let query: Vec<char> = query.chars().map(fold).collect();
+    if query.iter().all(|c| c.is_whitespace()) {
+        return Vec::new();
+    }
+
+    let mut matches = Vec::new();
+    for (n, line) in lines.iter().enumerate() {
+        // the characters of the line, each with the word it belongs to
+        let chars: Vec<(char, usize)> = line
+            .iter()
+            .enumerate()
+            .flat_map(|(i, word)| {
+                let whitespace = vec![' '; word.whitespace_width as usize];
+                word.content
+                    .chars()
+                    .chain(whitespace)
+                    .map(move |c| (fold(c), i))
+            })
+            .collect();
+
+        let mut start = 0;
+        while start + query.len() <= chars.len() {
+            let window = &chars[start..start + query.len()];
+            if window.iter().map(|(c, _)| c).eq(query.iter()) {
+                matches.push(((n, window[0].1), (n, window[window.len() - 1].1)));
+                start += query.len();
+            } else {
+                start += 1;
+            }
+        }
+    }
+    matches
+}
+
+/// Draws the spans at the bottom of the area, wrapped over as many lines as they need
+fn render_info_line(f: &mut Frame, area: Rect, spans: Vec<Span>) {
+    let length: usize = spans.iter().map(|span| span.content.chars().count()).sum();
+    let height = (length as u16 / area.width.max(1) + 1)
+        .min(area.height / INFO_LINE_MAX_HEIGHT_FRACTION)
+        .max(1);
+    let area = Rect {
+        y: area.bottom().saturating_sub(height),
+        height: height.min(area.height),
+        ..area
+    };
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(Line::from(spans)).wrap(Wrap { trim: true }),
+        area,
+    );
+}
+
+fn render_page(
+    page: &Page,
+    renderer: &Renderer,
+    sections: &mut PageSections<'_>,
+) -> RenderedDocument {
+    match renderer {
+        Renderer::Default => {
+            let options = RendererConfig {
+                taxoboxes: sections.taxoboxes,
+                ..config().renderer.clone()
+            };
+            let (width, collapsed, language_hints) =
+                (sections.width, sections.collapsed, sections.language_hints);
+            render_document_sections(
+                &page.content,
+                width,
+                &options,
+                collapsed,
+                language_hints,
+                sections,
+            )
+        }
+        #[cfg(debug_assertions)]
+        Renderer::TestRendererTreeData => render_tree_data(&page.content),
+        #[cfg(debug_assertions)]
+        Renderer::TestRendererTreeRaw => render_tree_raw(&page.content),
+        #[cfg(debug_assertions)]
+        Renderer::TestRendererNodeRaw => render_nodes_raw(&page.content),
+    }
+}
+
+/// Returns the position of the text at the top line that doesn't depend on the width the page
+/// is rendered with: the node of its first word and how many characters of the node come before
+/// that word. A long paragraph is a single node, the characters keep the position inside of it
+fn text_anchor(lines: &[Vec<Word>], top: usize) -> Option<(usize, usize)> {
+    let node = lines
+        .iter()
+        .skip(top)
+        .flatten()
+        .map(|word| word.index)
+        .find(|index| *index != usize::MAX)?;
+    let offset = lines
+        .iter()
+        .take(top)
+        .flatten()
+        .filter(|word| word.index == node)
+        .map(|word| word.content.chars().count())
+        .sum();
+    Some((node, offset))
+}
+
+/// Returns the line showing the text of the anchor, see [`text_anchor`]
+fn anchor_line(lines: &[Vec<Word>], (node, offset): (usize, usize)) -> Option<usize> {
+    let mut before = 0;
+    let mut last = None;
+    for (n, line) in lines.iter().enumerate() {
+        for word in line.iter().filter(|word| word.index == node) {
+            before += word.content.chars().count();
+            if before > offset {
+                return Some(n);
+            }
+            last = Some(n);
+        }
+    }
+    last
+}
+
+/// Returns the title of the page the link points to, without the suffix of red links
+pub fn link_title(data: &Data) -> Option<String> {
+    match data {
+        Data::WikiLink {
+            title: Some(title), ..
+        } => Some(title.clone()),
+        Data::WikiLink { href, .. } => Some(href.trim_start_matches("./").replace('_', " ")),
+        Data::RedLink { title: Some(title) } => {
+            Some(title.trim_end_matches(" (page does not exist)").to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Returns the indices of the sections that are not nested in another section, except the lead
+fn top_level_sections(page: &Page) -> BTreeSet<usize> {
+    page.content
+        .nth(0)
+        .map(|root| {
+            root.descendants()
+                .filter(|node| {
+                    matches!(node.data(), Data::Section { .. }) && is_top_level_section(*node)
+                })
+                .map(|node| node.index())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Returns how many renders of top-level sections are kept, enough for every section of each
+/// cached render of the page
+fn section_cache_size(page: &Page) -> usize {
+    config().ui.render_cache_size * top_level_sections(page).len().max(1)
+}
+
+/// Returns the width of the text of the page when it is narrowed by the zoom
+fn zoomed_width(width: u16, zoom: u16) -> u16 {
+    (width as u32 * zoom as u32 / MAX_ZOOM as u32) as u16
+}
+
+/// Returns the collapsible boxes the wiki shows collapsed at first, the footer of related pages
+/// and the tables that are cut off because they're too long
+fn collapsed_boxes(page: &Page) -> BTreeSet<usize> {
+    let max_rows = config().renderer.table_rows;
+    page.content
+        .nodes
+        .iter()
+        .filter(|node| match node.data {
+            Data::Collapsible {
+                collapsed: true, ..
+            }
+            | Data::RelatedPages => true,
+            Data::Table => page
+                .content
+                .nth(node.index)
+                .is_some_and(|table| is_long_table(table, max_rows) && !is_in_table(table)),
+            _ => false,
+        })
+        .map(|node| node.index)
+        .collect()
+}
+
+/// Returns whether the node is inside of a table. Tables in tables are rendered as text and
+/// never cut off
+fn is_in_table(node: Node<'_>) -> bool {
+    let mut parent = node.parent();
+    while let Some(current) = parent {
+        if matches!(current.data(), Data::Table) {
+            return true;
+        }
+        parent = current.parent();
+    }
+    false
+}
+
+/// Returns what is collapsed when the page is opened: the collapsed boxes and, when enabled, the
+/// top-level sections
+fn initially_collapsed(page: &Page) -> BTreeSet<usize> {
+    let mut collapsed = collapsed_boxes(page);
+    if config().ui.collapse_sections {
+        collapsed.extend(top_level_sections(page));
+    }
+    collapsed
+}
+
 pub struct PageComponent {
     page: Page,
     renderer: Renderer,
-    render_cache: HashMap<u16, RenderedDocument>,
+    render_cache: RenderCache<RenderKey>,
+    /// Renders of the top-level sections the renders of the page are put together from
+    section_cache: RenderCache<SectionKey, RenderedSection>,
     viewport: Rect,
+    /// Zoom the page is shown at, in percent. Zooming in narrows the text
+    zoom: u16,
+    /// Width of the page before it's narrowed by the zoom, when it was last shown
+    page_width: u16,
+    /// Indices of the sections of which only the header is shown
+    collapsed: BTreeSet<usize>,
+    /// Whether the taxobox is shown at the top of the page
+    is_taxobox: bool,
     selected: (usize, usize),
+
+    is_contents: bool,
+    /// Width of the contents sidebar, in percent
+    pub contents_width: u16,
+    /// Narrows the sections listed in the contents sidebar while typing
+    contents_filter: Option<Input>,
+    /// Text searched for in the page, its matches are highlighted while it's `Some`
+    find: Option<PageFind>,
+    /// Whether the page was last rendered too narrow for the contents sidebar and scrollbar
+    is_narrow: bool,
+    /// Width the page is being resized to, and since when it's available
+    pending_width: Option<(u16, Instant)>,
+    /// Position the page was kept at by the last width change and the line it was shown at, see
+    /// [`text_anchor`]. Until the page is scrolled, the next width change keeps the same
+    /// position instead of the start of that line, so the position doesn't drift
+    width_anchor: Option<((usize, usize), u16)>,
+
+    speaker: Option<Speaker>,
+    sentences: Vec<Sentence>,
+    spoken_sentence: Option<usize>,
+    speech_end: usize,
+
+    /// Sentences of the rendered page, the passages that can be highlighted
+    passages: Vec<Sentence>,
+    /// Selected passages while highlighting
+    highlight_selection: Option<Range<usize>>,
+    /// Word positions of the annotated passages in the rendered page
+    highlights: Vec<(WordPosition, WordPosition)>,
+    /// The render the passages and highlights were taken from
+    highlights_key: Option<RenderKey>,
+    note_input: Option<Input>,
+    /// Dialog for opening the selected external link
+    external_link: Option<ExternalLinkComponent>,
+    /// Dialog describing the selected media file
+    media: Option<MediaComponent>,
+    /// Selected cell while moving through a table
+    table_cell: Option<TableCell>,
+
+    render_stats: RenderStats,
+    /// Whether the render statistics are shown on top of the page
+    is_render_stats: bool,
+
+    /// Heading of the section when only that section of the page was loaded
+    section: Option<String>,
+    /// Whether the title the page was redirected from is shown above the page
+    is_redirect_notice: bool,
+    /// Revision of the page that is newer than the shown one, a notice offering to reload the
+    /// page is shown while it's `Some`
+    newer_revision: Option<usize>,
+    /// Whether the page is read page by page instead of scrolled. A reading page is as high as
+    /// the viewport
+    is_paginated: bool,
+    /// How many columns the page is shown in. The viewport spans the lines of every column
+    columns: Columns,
+    /// Summary of a linked page shown over the page, `Some` while the preview is open. It's
+    /// `Some(None)` while the summary is loading
+    preview: Option<Option<Summary>>,
+    /// Leading sentences of a section and its subsections shown over the page, `Some` while the
+    /// outline is open
+    outline: Option<Vec<SectionOutline>>,
+    /// Place the reader marked as where they stopped, see [`text_anchor`]
+    scroll_mark: Option<(usize, usize)>,
+    /// How often the page was viewed lately, `None` until the views are fetched
+    page_views: Option<PageViews>,
+    /// Titles of the linked pages in `api.secondary_language`, by their title in the language of
+    /// the page
+    language_titles: HashMap<String, String>,
+    /// The titles of `language_titles` by the index of the link node they are shown after
+    language_hints: BTreeMap<usize, String>,
+
+    action_tx: Option<mpsc::UnboundedSender<Action>>,
 }
 
 impl PageComponent {
     pub fn new(page: Page) -> Self {
+        let collapsed = initially_collapsed(&page);
+
+        let is_redirect_notice = page.redirected_from.is_some();
+        let scroll_mark = scroll_marks().get(&page);
+        let section_cache = RenderCache::new(section_cache_size(&page));
         Self {
             page,
             renderer: Renderer::default(),
-            render_cache: HashMap::new(),
+            render_cache: RenderCache::new(config().ui.render_cache_size),
+            section_cache,
             viewport: Rect::default(),
+            zoom: MAX_ZOOM,
+            page_width: 0,
+            collapsed,
+            is_taxobox: config().renderer.taxoboxes,
             selected: (0, 0),
+
+            is_contents: config().ui.show_contents,
+            contents_width: config().ui.contents_width,
+            contents_filter: None,
+            find: None,
+            is_narrow: false,
+            pending_width: None,
+            width_anchor: None,
+
+            speaker: None,
+            sentences: Vec::new(),
+            spoken_sentence: None,
+            speech_end: 0,
+
+            passages: Vec::new(),
+            highlight_selection: None,
+            highlights: Vec::new(),
+            highlights_key: None,
+            note_input: None,
+            external_link: None,
+            media: None,
+            table_cell: None,
+
+            render_stats: RenderStats::default(),
+            is_render_stats: false,
+
+            section: None,
+            is_redirect_notice,
+            newer_revision: None,
+            is_paginated: config().ui.paginate,
+            columns: config().renderer.columns,
+            preview: None,
+            outline: None,
+            scroll_mark,
+            page_views: None,
+            language_titles: HashMap::new(),
+            language_hints: BTreeMap::new(),
+
+            action_tx: None,
         }
     }
 
-    fn render_page(&self, width: u16) -> RenderedDocument {
-        match self.renderer {
-            Renderer::Default => render_document(&self.page.content, width),
-            #[cfg(debug_assertions)]
-            Renderer::TestRendererTreeData => render_tree_data(&self.page.content),
-            #[cfg(debug_assertions)]
-            Renderer::TestRendererTreeRaw => render_tree_raw(&self.page.content),
-            #[cfg(debug_assertions)]
-            Renderer::TestRendererNodeRaw => render_nodes_raw(&self.page.content),
-        }
+    pub fn page(&self) -> &Page {
+        &self.page
     }
 
-    fn switch_renderer(&mut self, renderer: Renderer) {
-        self.renderer = renderer;
-        self.flush_cache();
+    /// Marks the page as a single section of the article with the heading
+    pub fn with_section(mut self, heading: String) -> Self {
+        self.section = Some(heading);
+        self
     }
 
-    fn flush_cache(&mut self) {
-        debug!("flushing '{}' cached renders", self.render_cache.len());
-        self.render_cache.clear();
-        if LINK_SELECT {
-            self.selected = (0, 0);
-        }
+    pub fn section(&self) -> Option<&str> {
+        self.section.as_deref()
     }
 
-    fn scroll_down(&mut self, amount: u16) {
-        self.viewport.y += amount;
+    pub fn page_views(&self) -> Option<&PageViews> {
+        self.page_views.as_ref()
     }
 
-    fn scroll_up(&mut self, amount: u16) {
-        self.viewport.y = self.viewport.y.saturating_sub(amount);
+    pub fn set_page_views(&mut self, page_views: PageViews) {
+        self.page_views = Some(page_views);
     }
 
-    fn select_first(&mut self) {
-        if self.page.content.nth(0).is_none() {
-            return;
-        }
+    /// Shows the titles of the linked pages in the secondary language after the links
+    pub fn set_language_hints(&mut self, titles: HashMap<String, String>) {
+        self.language_titles = titles;
+        self.update_language_hints();
+    }
 
-        let selectable_node = self
+    /// Finds the links the titles in the secondary language belong to. Titles that are the same
+    /// in both languages, like most names, aren't repeated
+    fn update_language_hints(&mut self) {
+        let titles = &self.language_titles;
+        self.language_hints = self
             .page
             .content
-            .nth(0)
-            .unwrap()
-            .descendants()
-            .find(|node| matches!(node.data(), &Data::WikiLink { .. }));
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| matches!(node.data, Data::WikiLink { .. }))
+            .filter_map(|(index, node)| {
+                let title = link_title(&node.data)?;
+                let hint = titles.get(&title)?;
+                (!hint.eq_ignore_ascii_case(&title)).then(|| (index, hint.clone()))
+            })
+            .collect();
+    }
 
-        if let Some(selectable_node) = selectable_node {
-            let first_index = selectable_node.index();
-            let last_index = selectable_node
-                .last_child()
-                .map(|child| child.index())
-                .unwrap_or(first_index);
-            self.selected = (first_index, last_index);
+    /// Shows the notice that there is a newer revision of the page, unless the shown one is as new
+    pub fn set_newer_revision(&mut self, revision: usize) {
+        if self.page.revision_id < Some(revision) {
+            self.newer_revision = Some(revision);
         }
     }
 
-    fn select_prev(&mut self) {
-        if self.page.content.nth(0).is_none() {
-            return;
-        }
+    /// Replaces the page with another revision of it. The page stays scrolled to where it was
+    /// and the selected link stays selected when the new revision still contains it
+    pub fn reload(&mut self, page: Page) {
+        let selected_title = self.selected_link_title();
+        info!(
+            "reloading '{}' at the revision '{:?}'",
+            page.title, page.revision_id
+        );
 
-        let selectable_node = self
-            .page
-            .content
-            .nth(0)
-            .unwrap()
-            .descendants()
-            .filter(|node| {
-                matches!(node.data(), &Data::WikiLink { .. }) && node.index() < self.selected.0
+        self.collapsed = initially_collapsed(&page);
+        self.page = page;
+        self.render_cache = RenderCache::new(config().ui.render_cache_size);
+        self.section_cache = RenderCache::new(section_cache_size(&self.page));
+        self.update_language_hints();
+        self.newer_revision = None;
+        self.highlights_key = None;
+        self.find = None;
+        self.reset_render_state();
+
+        let link = selected_title.and_then(|title| {
+            self.page.content.nth(0)?.descendants().find(|node| {
+                is_link(node.data()) && link_title(node.data()).as_ref() == Some(&title)
             })
-            .last();
+        });
+        self.selected = match link {
+            Some(link) => link_nodes(link),
+            None => (0, 0),
+        };
+    }
 
-        if let Some(selectable_node) = selectable_node {
-            let first_index = selectable_node.index();
-            let last_index = selectable_node
-                .last_child()
-                .map(|child| child.index())
-                .unwrap_or(first_index);
-            self.selected = (first_index, last_index);
+    /// Returns what a bug report contains about the page and how it's rendered
+    pub fn report(&self) -> PageReport {
+        PageReport {
+            title: self.page.title.clone(),
+            language: self.page.language.name().to_string(),
+            revision: self.page.revision_id,
+            section: self.section.clone(),
+            renderer: format!("{:?}", self.renderer),
+            width: self.viewport.width,
+            collapsed_sections: self.collapsed.len(),
+            taxoboxes: self.is_taxobox,
+            paginated: self.is_paginated,
         }
     }
 
-    fn select_next(&mut self) {
-        if self.page.content.nth(0).is_none() {
-            return;
+    /// Whether the contents sidebar is enabled, available and fits next to the page
+    fn shows_contents(&self) -> bool {
+        self.is_contents && self.page.sections.is_some() && !self.is_narrow
+    }
+
+    fn render_key(&self, width: u16) -> RenderKey {
+        RenderKey {
+            width,
+            zoom: self.zoom,
+            renderer: self.renderer.clone(),
+            collapsed: self.collapsed.clone(),
+            taxoboxes: self.is_taxobox,
+            theme: theme_preset(),
+            language_hints: self.language_hints.len(),
         }
+    }
 
-        let selectable_node = self
-            .page
-            .content
-            .nth(0)
-            .unwrap()
-            .descendants()
-            .find(|node| {
-                matches!(node.data(), &Data::WikiLink { .. }) && self.selected.1 < node.index()
-            });
+    /// Renders the page for the current viewport, unless the render is cached already
+    fn ensure_rendered(&mut self) {
+        let width = self.viewport.width;
+        let key = self.render_key(width);
+        let (page, renderer) = (&self.page, &self.renderer);
+        let mut sections = PageSections {
+            cache: &mut self.section_cache,
+            document: &self.page.content,
+            width,
+            taxoboxes: self.is_taxobox,
+            collapsed: &self.collapsed,
+            language_hints: &self.language_hints,
+        };
+        let stats = &mut self.render_stats;
+        self.render_cache.get_or_render(key, || {
+            info!("rebuilding cache for '{}'", width);
+            let start = Instant::now();
+            let rendered = render_page(page, renderer, &mut sections);
+            stats.record_render(width, start.elapsed());
+            rendered
+        });
+    }
 
-        if let Some(selectable_node) = selectable_node {
-            let first_index = selectable_node.index();
-            let last_index = selectable_node
-                .last_child()
-                .map(|child| child.index())
-                .unwrap_or(first_index);
-            self.selected = (first_index, last_index);
-        }
+    /// Returns the cached render for the current viewport
+    fn rendered_page(&self) -> Option<&RenderedDocument> {
+        self.render_cache
+            .peek(&self.render_key(self.viewport.width))
     }
 
-    fn select_last(&mut self) {
-        if self.page.content.nth(0).is_none() {
-            return;
-        }
+    /// Drops every cached render except the most recent one, used when the page is not shown
+    pub fn shrink_cache(&mut self) {
+        debug!("shrinking '{}' cached renders", self.render_cache.len());
+        self.render_cache.shrink();
+        self.section_cache.clear();
+    }
 
-        let selectable_node = self
-            .page
-            .content
-            .nth(0)
-            .unwrap()
-            .descendants()
-            .filter(|node| {
-                matches!(node.data(), &Data::WikiLink { .. }) && node.index() > self.selected.1
-            })
-            .last();
+    fn switch_renderer(&mut self, renderer: Renderer) {
+        self.renderer = renderer;
+        self.reset_render_state();
+    }
 
-        if let Some(selectable_node) = selectable_node {
-            let first_index = selectable_node.index();
-            let last_index = selectable_node
-                .last_child()
-                .map(|child| child.index())
-                .unwrap_or(first_index);
-            self.selected = (first_index, last_index);
+    /// Resets everything that refers to positions in a rendered page
+    fn reset_render_state(&mut self) {
+        if LINK_SELECT {
+            self.selected = (0, 0);
         }
+        self.table_cell = None;
+        self.width_anchor = None;
+        self.invalidate_positions();
     }
 
-    fn resize(&mut self, width: u16, height: u16) {
-        self.viewport.width = width;
-        self.viewport.height = height;
-
-        self.flush_cache();
+    /// Drops the positions taken from the previous render. The link selection refers to nodes
+    /// instead and stays valid
+    fn invalidate_positions(&mut self) {
+        // the sentence positions are only valid for the rendered document they were taken from
+        self.stop_speech();
+        self.highlight_selection = None;
+        self.note_input = None;
     }
-}
 
-impl Component for PageComponent {
-    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
-        match key.code {
-            KeyCode::Char('r') if has_modifier!(key, Modifier::CONTROL) => {
-                Action::Page(PageAction::SwitchRenderer(self.renderer.next())).into()
-            }
-            KeyCode::Left if has_modifier!(key, Modifier::SHIFT) => {
-                Action::Page(PageAction::SelectFirstLink).into()
-            }
-            KeyCode::Right if has_modifier!(key, Modifier::SHIFT) => {
-                Action::Page(PageAction::SelectLastLink).into()
+    /// Returns the index of the section the node belongs to
+    fn section_of(&self, index: usize) -> Option<usize> {
+        let mut node = self.page.content.nth(index)?;
+        loop {
+            if matches!(node.data(), Data::Section { .. }) {
+                return Some(node.index());
             }
-            KeyCode::Up if has_modifier!(key, Modifier::SHIFT) => {
-                Action::Page(PageAction::SelectTopLink).into()
+            node = node.parent()?;
+        }
+    }
+
+    /// Returns whether the node is a descendant of the section
+    fn is_in_section(&self, index: usize, section: usize) -> bool {
+        let mut node = self.page.content.nth(index);
+        while let Some(current) = node {
+            if current.index() == section {
+                return true;
             }
-            KeyCode::Down if has_modifier!(key, Modifier::SHIFT) => {
-                Action::Page(PageAction::SelectBottomLink).into()
+            node = current.parent();
+        }
+        false
+    }
+
+    /// Returns whether the node is hidden in a collapsed section, box or table. The header of a
+    /// collapsed section and the first rows of a collapsed table are still shown
+    fn is_hidden(&self, index: usize) -> bool {
+        let mut node = match self.page.content.nth(index) {
+            Some(node) => node,
+            None => return false,
+        };
+        while let Some(parent) = node.parent() {
+            if self.collapsed.contains(&parent.index()) {
+                let is_hidden = match parent.data() {
+                    Data::Table => table_rows(parent)
+                        .position(|row| row.index() == node.index())
+                        .is_some_and(|row| row >= config().renderer.table_rows),
+                    _ => !matches!(node.data(), Data::Header { .. }),
+                };
+                if is_hidden {
+                    return true;
+                }
             }
-            KeyCode::Left => Action::Page(PageAction::SelectPrevLink).into(),
-            KeyCode::Right => Action::Page(PageAction::SelectNextLink).into(),
-            _ => ActionResult::Ignored,
+            node = parent;
         }
+        false
     }
 
-    fn keymap(&self) -> super::help::Keymap {
-        vec![
-            (
-                key_event!('r', Modifier::CONTROL),
-                Action::Page(PageAction::SwitchRenderer(self.renderer.next())).into(),
-            ),
-            (
-                key_event!(Key::Left, Modifier::SHIFT),
-                Action::Page(PageAction::SelectFirstLink).into(),
-            ),
-            (
-                key_event!(Key::Left),
-                Action::Page(PageAction::SelectPrevLink).into(),
-            ),
-            (
-                key_event!(Key::Right, Modifier::SHIFT),
-                Action::Page(PageAction::SelectLastLink).into(),
-            ),
-            (
-                key_event!(Key::Right),
-                Action::Page(PageAction::SelectNextLink).into(),
-            ),
-            (
-                key_event!(Key::Up, Modifier::SHIFT),
-                Action::Page(PageAction::SelectTopLink).into(),
-            ),
-            (
-                key_event!(Key::Down, Modifier::SHIFT),
-                Action::Page(PageAction::SelectBottomLink).into(),
-            ),
-        ]
+    /// Returns the innermost section shown at the top of the viewport
+    fn section_at_top(&self) -> Option<usize> {
+        let line = self.viewport.top() as usize;
+        self.rendered_page().and_then(|rendered_page| {
+            rendered_page
+                .sections
+                .iter()
+                .rev()
+                .find(|(_, lines)| lines.contains(&line))
+                .map(|(section, _)| *section)
+        })
     }
 
-    fn update(&mut self, action: Action) -> ActionResult {
-        match action {
-            Action::Page(page_action) => match page_action {
-                PageAction::SwitchRenderer(renderer) => self.switch_renderer(renderer),
+    /// Returns the heading of the innermost section at the top of the viewport, `None` in the
+    /// lead section
+    pub fn current_heading(&self) -> Option<String> {
+        self.section_heading(self.section_at_top()?)
+    }
 
-                PageAction::SelectFirstLink => self.select_first(),
-                PageAction::SelectLastLink => self.select_last(),
+    /// Returns the heading of the section, `None` for the lead section
+    fn section_heading(&self, section: usize) -> Option<String> {
+        self.page
+            .content
+            .nth(section)?
+            .children()
+            .find(|node| matches!(node.data(), Data::Header { .. }))
+            .map(taxobox::text)
+    }
 
-                PageAction::SelectTopLink | PageAction::SelectBottomLink => todo!(),
+    /// Returns how far the page is scrolled, in percent. A page fitting into the viewport is
+    /// scrolled completely
+    pub fn scroll_percent(&self) -> u16 {
+        let lines = self
+            .rendered_page()
+            .map(|rendered_page| rendered_page.lines.len())
+            .unwrap_or_default();
+        let max_y = lines.saturating_sub(self.viewport.height as usize);
+        if max_y == 0 {
+            return 100;
+        }
+        ((self.viewport.y as usize).min(max_y) * 100 / max_y) as u16
+    }
 
-                PageAction::SelectPrevLink => self.select_prev(),
-                PageAction::SelectNextLink => self.select_next(),
-            },
-            Action::ScrollUp(amount) => self.scroll_up(amount),
-            Action::ScrollDown(amount) => self.scroll_down(amount),
+    /// Returns the last line shown and how long the page is, `None` when the page wasn't
+    /// rendered yet
+    pub fn reading_position(&self) -> Option<ReadingPosition> {
+        let lines = self.rendered_page()?.lines.len();
+        Some(ReadingPosition {
+            line: (self.viewport.bottom() as usize).min(lines),
+            lines,
+        })
+    }
 
-            Action::ScrollHalfUp => self.scroll_up(self.viewport.height / 2),
-            Action::ScrollHalfDown => self.scroll_down(self.viewport.height / 2),
+    /// Returns the position of the text at the top of the viewport, see [`text_anchor`]. It's
+    /// `None` when the page wasn't rendered yet
+    pub fn top_anchor(&self) -> Option<(usize, usize)> {
+        text_anchor(&self.rendered_page()?.lines, self.viewport.top() as usize)
+    }
 
-            Action::ScrollToTop => self.viewport.y = 0,
-            Action::ScrollToBottom => {
-                self.viewport.y = self
-                    .render_cache
-                    .get(&self.viewport.width)
-                    .map(|doc| doc.lines.len() as u16)
-                    .unwrap_or(self.viewport.y)
-            }
+    /// Scrolls the text of the anchor to the top of the viewport once the page is first rendered,
+    /// see [`text_anchor`]. Like after a width change, the anchor is kept until the page is
+    /// scrolled
+    pub fn scroll_to_anchor(&mut self, anchor: (usize, usize)) {
+        self.width_anchor = Some((anchor, self.viewport.top()));
+    }
 
-            Action::Resize(width, heigth) => self.resize(width, heigth),
-            _ => return ActionResult::Ignored,
+    /// Scrolls to the header of the section, or of the closest shown section containing it
+    fn scroll_to_section(&mut self, section: usize) {
+        self.ensure_rendered();
+
+        let mut node = self.page.content.nth(section);
+        while let Some(current) = node {
+            let start = self.rendered_page().and_then(|rendered_page| {
+                rendered_page
+                    .sections
+                    .iter()
+                    .find(|(index, _)| *index == current.index())
+                    .map(|(_, lines)| lines.start)
+            });
+            if let Some(start) = start {
+                self.viewport.y = start as u16;
+                return;
+            }
+            node = current.parent();
         }
-        ActionResult::consumed()
     }
 
-    fn render(&mut self, f: &mut Frame, area: Rect) {
-        let area = padded_rect(area, 1, 1);
-        let page_area = if SCROLLBAR {
-            area.inner(&Margin {
-                vertical: 0,
+    /// Collapses or expands the section at the top of the viewport. The lead section can't be
+    /// collapsed, as it has no header
+    fn toggle_section(&mut self) {
+        let section = match self.section_at_top() {
+            Some(section) => section,
+            None => return,
+        };
+        let is_lead = self
+            .page
+            .content
+            .nth(section)
+            .map(|node| matches!(node.data(), Data::Section { id: 0 }))
+            .unwrap_or(true);
+        if is_lead {
+            return;
+        }
+
+        if !self.collapsed.remove(&section) {
+            self.collapsed.insert(section);
+        }
+        self.reset_render_state();
+        self.scroll_to_section(section);
+    }
+
+    /// Expands every section when some are collapsed, otherwise collapses the top-level sections.
+    /// Collapsible boxes are left as they are
+    fn toggle_all_sections(&mut self) {
+        let section = self.section_at_top();
+        let content = &self.page.content;
+        let is_section = |index: &usize| {
+            matches!(
+                content.nth(*index).map(|node| node.data()),
+                Some(Data::Section { .. })
+            )
+        };
+        if self.collapsed.iter().any(is_section) {
+            self.collapsed.retain(|index| !is_section(index));
+        } else {
+            self.collapsed.extend(top_level_sections(&self.page));
+        }
+
+        self.reset_render_state();
+        match section {
+            Some(section) => self.scroll_to_section(section),
+            None => self.viewport.y = 0,
+        }
+    }
+
+    /// Collapses or expands the first collapsible box whose summary is shown in the viewport. The
+    /// footer of related pages and long tables are collapsed like a box
+    fn toggle_collapsible(&mut self) {
+        let content = &self.page.content;
+        let (top, height) = (self.viewport.top() as usize, self.viewport.height as usize);
+        let collapsible = self.rendered_page().and_then(|rendered_page| {
+            rendered_page
+                .lines
+                .iter()
+                .skip(top)
+                .take(height)
+                .flatten()
+                .map(|word| word.index)
+                .find(|index| {
+                    matches!(
+                        content.nth(*index).map(|node| node.data()),
+                        Some(Data::Collapsible { .. } | Data::RelatedPages | Data::Table)
+                    )
+                })
+        });
+        let collapsible = match collapsible {
+            Some(collapsible) => collapsible,
+            None => return info!("there is no collapsible box in view"),
+        };
+
+        if !self.collapsed.remove(&collapsible) {
+            self.collapsed.insert(collapsible);
+        }
+        self.reset_render_state();
+    }
+
+    /// Shows or hides the taxobox. The page is scrolled to the top, where the taxobox is shown
+    fn toggle_taxobox(&mut self) {
+        self.is_taxobox = !self.is_taxobox;
+        self.reset_render_state();
+        self.viewport.y = 0;
+    }
+
+    /// Expands the collapsed sections and boxes containing the node, so it's shown
+    fn expand_to(&mut self, index: usize) {
+        let mut is_expanded = false;
+        let mut node = self.page.content.nth(index);
+        while let Some(current) = node {
+            is_expanded |= self.collapsed.remove(&current.index());
+            node = current.parent();
+        }
+
+        if is_expanded {
+            self.reset_render_state();
+            self.ensure_rendered();
+        }
+    }
+
+    /// Returns the range of rendered lines that belong to the section found at or after
+    /// `from_line`
+    fn section_lines(&self, from_line: usize) -> Option<Range<usize>> {
+        fn first_index(line: &[Word]) -> Option<usize> {
+            line.iter()
+                .find(|word| word.index != usize::MAX)
+                .map(|word| word.index)
+        }
+
+        let lines = &self.rendered_page()?.lines;
+        let (first, index) = lines
+            .iter()
+            .enumerate()
+            .skip(from_line)
+            .find_map(|(i, line)| first_index(line).map(|index| (i, index)))?;
+
+        let section = match self.section_of(index) {
+            Some(section) => section,
+            None => return Some(first..lines.len()),
+        };
+        let in_section = |line: &[Word]| {
+            first_index(line)
+                .map(|index| self.is_in_section(index, section))
+                .unwrap_or(true)
+        };
+
+        let mut start = first;
+        while start > 0 && in_section(&lines[start - 1]) {
+            start -= 1;
+        }
+        let mut end = first + 1;
+        while end < lines.len() && in_section(&lines[end]) {
+            end += 1;
+        }
+
+        Some(start..end)
+    }
+
+    fn start_speech(&mut self, from_line: usize) {
+        let action_tx = match self.action_tx {
+            Some(ref action_tx) => action_tx.clone(),
+            None => return,
+        };
+        let range = match self.section_lines(from_line) {
+            Some(range) => range,
+            None => return,
+        };
+        let sentences = match self.rendered_page() {
+            Some(rendered_page) => {
+                split_sentences(&rendered_page.lines[range.clone()], range.start)
+            }
+            None => return,
+        };
+
+        self.speaker = Speaker::speak(
+            &config().tts.command,
+            sentences
+                .iter()
+                .map(|sentence| sentence.text.clone())
+                .collect(),
+            action_tx,
+        );
+        self.sentences = sentences;
+        self.spoken_sentence = None;
+        self.speech_end = range.end;
+    }
+
+    fn toggle_speech(&mut self) {
+        match self.speaker {
+            Some(ref speaker) => speaker.toggle_pause(),
+            None => self.start_speech(self.viewport.top() as usize),
+        }
+    }
+
+    /// Returns whether the speaker of the session is still reading this page
+    fn is_speaking(&self, session: u64) -> bool {
+        self.speaker
+            .as_ref()
+            .is_some_and(|speaker| speaker.session() == session)
+    }
+
+    pub fn stop_speech(&mut self) {
+        self.speaker = None;
+        self.sentences.clear();
+        self.spoken_sentence = None;
+    }
+
+    fn speak_next_section(&mut self) {
+        let next_line = if self.speaker.is_some() {
+            self.speech_end
+        } else {
+            match self.section_lines(self.viewport.top() as usize) {
+                Some(range) => range.end,
+                None => return,
+            }
+        };
+
+        self.stop_speech();
+        self.start_speech(next_line);
+    }
+
+    fn speech_progress(&mut self, n: usize) {
+        self.spoken_sentence = Some(n);
+
+        // keep the spoken sentence in view
+        if let Some(sentence) = self.sentences.get(n) {
+            self.keep_in_view(sentence.start.0);
+        }
+    }
+
+    /// Scrolls as little as possible to show the line
+    fn scroll_into_view(&mut self, line: usize) {
+        let line = line as u16;
+        if self.is_paginated {
+            self.viewport.y = self.page_start(line);
+        } else if line < self.viewport.top() {
+            self.viewport.y = line;
+        } else if self.viewport.bottom() <= line {
+            self.viewport.y = line + 1 - self.viewport.height.max(1);
+        }
+    }
+
+    fn keep_in_view(&mut self, line: usize) {
+        let line = line as u16;
+        if self.is_paginated {
+            self.viewport.y = self.page_start(line);
+        } else if line < self.viewport.top() || self.viewport.bottom() <= line {
+            self.viewport.y = line;
+        }
+    }
+
+    /// Finds the passages and annotated highlights in the current render, when they were taken
+    /// from a different one
+    fn update_highlights(&mut self) {
+        let key = self.render_key(self.viewport.width);
+        if self.highlights_key.as_ref() == Some(&key) {
+            return;
+        }
+
+        let passages = match self.rendered_page() {
+            Some(rendered_page) => split_sentences(&rendered_page.lines, 0),
+            None => return,
+        };
+        self.highlights = annotations()
+            .for_page(&self.page.title)
+            .filter_map(|annotation| find_passage(&passages, &annotation.text))
+            .collect();
+        self.passages = passages;
+        self.highlights_key = Some(key);
+    }
+
+    fn toggle_highlight_mode(&mut self) {
+        if self.highlight_selection.take().is_some() {
+            return;
+        }
+
+        let top = self.viewport.top() as usize;
+        if let Some(first) = self
+            .passages
+            .iter()
+            .position(|passage| passage.start.0 >= top)
+        {
+            self.highlight_selection = Some(first..first + 1);
+        }
+    }
+
+    fn select_passage(&mut self, forward: bool) {
+        let selection = match self.highlight_selection {
+            Some(ref selection) => selection,
+            None => return,
+        };
+
+        let passage = if forward {
+            selection.end.min(self.passages.len().saturating_sub(1))
+        } else {
+            selection.start.saturating_sub(1)
+        };
+        self.highlight_selection = Some(passage..passage + 1);
+        self.keep_in_view(self.passages[passage].start.0);
+    }
+
+    fn extend_passage(&mut self) {
+        if let Some(ref mut selection) = self.highlight_selection {
+            if selection.end < self.passages.len() {
+                selection.end += 1;
+                let line = self.passages[selection.end - 1].end.0;
+                self.keep_in_view(line);
+            }
+        }
+    }
+
+    fn passages_text(&self, selection: Range<usize>) -> String {
+        self.passages[selection]
+            .iter()
+            .map(|passage| passage.text.as_str())
+            .collect::<Vec<&str>>()
+            .join(" ")
+    }
+
+    /// Yanks the selected passages and leaves the highlight mode
+    fn yank_passage(&mut self) {
+        let selection = match self.highlight_selection.take() {
+            Some(selection) => selection,
+            None => return,
+        };
+
+        let text = self.passages_text(selection);
+        yank_ring().yank(Yank::new(
+            YankKind::Passage,
+            text.clone(),
+            self.page.title.clone(),
+            vec![("text", text), ("page", self.page.title.clone())],
+        ));
+    }
+
+    /// Whether the note of an annotation is being written, it's lost unless it's saved
+    pub fn has_unsaved_annotation(&self) -> bool {
+        self.note_input.is_some()
+    }
+
+    fn save_annotation(&mut self) {
+        let (selection, note) = match (self.highlight_selection.take(), self.note_input.take()) {
+            (Some(selection), Some(note)) => (selection, note),
+            _ => return,
+        };
+
+        annotations().add(Annotation {
+            page: self.page.title.clone(),
+            text: self.passages_text(selection),
+            note: note.value().trim().to_string(),
+            created: chrono::Utc::now().timestamp(),
+            language: Some(self.page.language.clone()),
+        });
+        self.highlights_key = None;
+    }
+
+    fn render_note_input(&self, f: &mut Frame, area: Rect) {
+        let input = match self.note_input {
+            Some(ref input) => input,
+            None => return,
+        };
+
+        let area = Rect {
+            y: area.bottom().saturating_sub(NOTE_INPUT_HEIGHT),
+            height: NOTE_INPUT_HEIGHT.min(area.height),
+            ..centered_rect(area, NOTE_INPUT_WIDTH, 100)
+        };
+        let scroll = input.visual_scroll(area.width.saturating_sub(2) as usize);
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Paragraph::new(input.value())
+                .scroll((0, scroll as u16))
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_type(BorderType::Rounded)
+                        .border_style(Style::default().fg(Color::Yellow))
+                        .title(tr("page.note")),
+                ),
+            area,
+        );
+        f.set_cursor(
+            area.x + (input.visual_cursor().max(scroll) - scroll) as u16 + 1,
+            area.y + 1,
+        );
+    }
+
+    fn scroll_down(&mut self, amount: u16) {
+        self.viewport.y += amount;
+    }
+
+    fn scroll_up(&mut self, amount: u16) {
+        self.viewport.y = self.viewport.y.saturating_sub(amount);
+    }
+
+    /// Returns the first line of the reading page containing the line
+    fn page_start(&self, line: u16) -> u16 {
+        line - line % self.viewport.height.max(1)
+    }
+
+    /// Returns the shown reading page, starting at 0, and the number of reading pages
+    fn reading_page(&self) -> (usize, usize) {
+        let height = self.viewport.height.max(1) as usize;
+        let lines = self
+            .rendered_page()
+            .map(|rendered_page| rendered_page.lines.len())
+            .unwrap_or_default();
+        (
+            self.viewport.y as usize / height,
+            // the page with the last line
+            lines.saturating_sub(1) / height + 1,
+        )
+    }
+
+    /// Moves the viewport to the start of the reading page it's on, which is the last one when
+    /// it's scrolled past the end of the page
+    fn snap_to_page(&mut self) {
+        let lines = match self.rendered_page() {
+            Some(rendered_page) => rendered_page.lines.len(),
+            None => return,
+        };
+        let line = self.viewport.y.min(lines.saturating_sub(1) as u16);
+        self.viewport.y = self.page_start(line);
+    }
+
+    /// Turns to the next or previous reading page. The first link on the new page is selected
+    /// when the selected one isn't shown anymore
+    fn turn_page(&mut self, forward: bool) {
+        let (page, pages) = self.reading_page();
+        let page = if forward {
+            (page + 1).min(pages - 1)
+        } else {
+            page.saturating_sub(1)
+        };
+        self.viewport.y = (page * self.viewport.height.max(1) as usize) as u16;
+
+        if self.selected_line().is_none() {
+            self.select_first_shown_link();
+        }
+    }
+
+    /// Selects the link that starts first in the viewport, if there is any
+    fn select_first_shown_link(&mut self) {
+        let (top, bottom) = (
+            self.viewport.top() as usize,
+            self.viewport.bottom() as usize,
+        );
+        let first = self.rendered_page().and_then(|rendered_page| {
+            rendered_page
+                .links
+                .iter()
+                .filter_map(|link| {
+                    link.segments
+                        .iter()
+                        .find(|(line, _)| top <= *line && *line < bottom)
+                        .map(|(line, words)| ((*line, words.start), link.nodes))
+                })
+                .min_by_key(|(position, _)| *position)
+                .map(|(_, nodes)| nodes)
+        });
+        if let Some(nodes) = first {
+            self.selected = nodes;
+        }
+    }
+
+    /// Returns how far the page can be scrolled horizontally, which is only possible when lines
+    /// (of preformatted blocks) are wider than the viewport
+    fn max_scroll_x(&self) -> u16 {
+        let widest = self
+            .rendered_page()
+            .and_then(|rendered_page| {
+                rendered_page
+                    .lines
+                    .iter()
+                    .map(|line| {
+                        line.iter()
+                            .map(|word| word.width + word.whitespace_width)
+                            .sum::<f64>() as u16
+                    })
+                    .max()
+            })
+            .unwrap_or_default();
+        widest.saturating_sub(self.viewport.width)
+    }
+
+    fn scroll_right(&mut self) {
+        self.viewport.x = (self.viewport.x + HORIZONTAL_SCROLL_STEP).min(self.max_scroll_x());
+    }
+
+    fn scroll_left(&mut self) {
+        self.viewport.x = self.viewport.x.saturating_sub(HORIZONTAL_SCROLL_STEP);
+    }
+
+    /// Narrows or widens the text by the amount of zoom steps
+    fn zoom_by(&mut self, steps: i16) {
+        let zoom = self.zoom as i16 + steps * ZOOM_STEP as i16;
+        self.set_zoom(zoom.clamp(MIN_ZOOM as i16, MAX_ZOOM as i16) as u16);
+    }
+
+    /// Renders the page with the zoom right away instead of waiting like for a resize, keeping
+    /// the text at the top of the viewport in view
+    fn set_zoom(&mut self, zoom: u16) {
+        if zoom == self.zoom {
+            return;
+        }
+        self.zoom = zoom;
+        if self.page_width == 0 {
+            // the page wasn't shown yet
+            return;
+        }
+        let (_, width) = self.column_layout(zoomed_width(self.page_width, zoom));
+        self.pending_width = None;
+        self.change_width(width);
+    }
+
+    /// Returns where the table was rendered in the current render
+    fn rendered_table(&self, table: usize) -> Option<&RenderedTable> {
+        self.rendered_page()?
+            .tables
+            .iter()
+            .find(|rendered_table| rendered_table.index == table)
+    }
+
+    /// Selects the first cell of the first table row in view, or of the first table below the
+    /// viewport. Leaves the table mode when it's active
+    fn toggle_table_mode(&mut self) {
+        if self.table_cell.take().is_some() {
+            return;
+        }
+
+        let top = self.viewport.top() as usize;
+        let cell = self.rendered_page().and_then(|rendered_page| {
+            rendered_page.tables.iter().find_map(|table| {
+                let row = table.rows.iter().position(|(line, _)| top <= *line)?;
+                Some(TableCell {
+                    table: table.index,
+                    row,
+                    column: 0,
+                })
+            })
+        });
+        match cell {
+            Some(cell) => {
+                self.table_cell = Some(cell);
+                self.show_table_cell();
+            }
+            None => info!("no table to select on the page"),
+        }
+    }
+
+    /// Moves the selected table cell by the amount of rows and columns, staying inside of the
+    /// table
+    fn move_table_cell(&mut self, rows: isize, columns: isize) {
+        let cell = match self.table_cell {
+            Some(cell) => cell,
+            None => return,
+        };
+        let table = match self.rendered_table(cell.table) {
+            Some(table) if !table.rows.is_empty() => table,
+            _ => return,
+        };
+
+        let row = (cell.row as isize + rows).clamp(0, table.rows.len() as isize - 1) as usize;
+        // rows may have less cells than the ones around them
+        let cells = table.rows[row].1.len() as isize;
+        let column = (cell.column as isize + columns).clamp(0, (cells - 1).max(0)) as usize;
+        self.table_cell = Some(TableCell {
+            row,
+            column,
+            ..cell
+        });
+        self.show_table_cell();
+    }
+
+    /// Returns the line and columns of the selected table cell in the current render
+    fn table_cell_position(&self) -> Option<(usize, Range<u16>)> {
+        let cell = self.table_cell?;
+        let table = self.rendered_table(cell.table)?;
+        let (line, _) = table.rows.get(cell.row)?;
+        let (start, width) = table.columns.get(cell.column)?;
+        Some((*line, *start..start + width))
+    }
+
+    /// Scrolls the selected table cell into view, horizontally too when the table is wider than
+    /// the page
+    fn show_table_cell(&mut self) {
+        let (line, columns) = match self.table_cell_position() {
+            Some(position) => position,
+            None => return,
+        };
+
+        self.scroll_into_view(line);
+        let width = columns.end - columns.start;
+        if columns.start < self.viewport.x || self.viewport.width < width {
+            self.viewport.x = columns.start;
+        } else if self.viewport.x + self.viewport.width < columns.end {
+            self.viewport.x = columns.end - self.viewport.width;
+        }
+        self.viewport.x = self.viewport.x.min(self.max_scroll_x());
+    }
+
+    /// Draws the whole text of the selected table cell at the bottom of the area, together with
+    /// the header of its column
+    fn render_table_cell(&self, f: &mut Frame, area: Rect) {
+        let cell = match self.table_cell {
+            Some(cell) => cell,
+            None => return,
+        };
+        let cell_text = |row: usize| {
+            let (_, cells) = self.rendered_table(cell.table)?.rows.get(row)?;
+            let node = self.page.content.nth(*cells.get(cell.column)?)?;
+            let is_header = matches!(node.data(), Data::TableCell { header: true });
+            Some((taxobox::text(node), is_header))
+        };
+        let (text, _) = match cell_text(cell.row) {
+            Some(text) => text,
+            None => return,
+        };
+
+        let mut spans = vec![
+            Span::raw(tr_args(
+                "page.table_cell",
+                &[
+                    ("row", (cell.row + 1).to_string()),
+                    ("column", (cell.column + 1).to_string()),
+                ],
+            ))
+            .dark_gray(),
+            Span::raw(" | ").dark_gray(),
+        ];
+        match cell_text(0) {
+            Some((header, true)) if cell.row > 0 => {
+                spans.push(Span::raw(format!("{}: ", header)).bold())
+            }
+            _ => {}
+        }
+        spans.push(Span::raw(text));
+        render_info_line(f, area, spans);
+    }
+
+    /// Outlines the section at the top of the viewport, or the lead section above the first
+    /// heading
+    fn outline_section(&mut self) {
+        let section = self.section_at_top().or_else(|| {
+            self.page
+                .content
+                .nth(0)?
+                .descendants()
+                .find(|node| matches!(node.data(), Data::Section { .. }))
+                .map(|node| node.index())
+        });
+        let outline = section
+            .and_then(|section| self.page.content.nth(section))
+            .map(|section| outline_section(section, config().behavior.outline_sentences))
+            .unwrap_or_default();
+        if outline.is_empty() {
+            return info!("there is no section to outline");
+        }
+        self.outline = Some(outline);
+    }
+
+    /// Shows the outline of a section over the page, every section with its first sentences
+    fn render_outline(&self, f: &mut Frame, area: Rect) {
+        let outline = match self.outline {
+            Some(ref outline) => outline,
+            None => return,
+        };
+
+        let mut lines = Vec::new();
+        for section in outline {
+            let indent = "  ".repeat(section.depth);
+            let heading = match section.heading.as_str() {
+                "" => self.page.title.clone(),
+                heading => heading.to_string(),
+            };
+            lines.push(Line::from(
+                Span::raw(format!("{}{}", indent, heading)).bold(),
+            ));
+            let text = match section.sentences.is_empty() {
+                true => Span::raw(format!("{}{}", indent, tr("page.outline_empty"))).dark_gray(),
+                false => Span::raw(format!("{}{}", indent, section.sentences.join(" "))),
+            };
+            lines.push(Line::from(text));
+            lines.push(Line::default());
+        }
+
+        let area = centered_rect(area, 80, 80);
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(Color::Yellow))
+                    .title(tr("page.outline")),
+            ),
+            area,
+        );
+    }
+
+    /// Shows the expansion of the selected abbreviation at the bottom of the page
+    fn render_abbreviation(&self, f: &mut Frame, area: Rect) {
+        let node = match self.page.content.nth(self.selected.0) {
+            Some(node) => node,
+            None => return,
+        };
+        if let Data::Abbreviation {
+            title: Some(ref expansion),
+        } = node.data()
+        {
+            let spans = vec![
+                Span::raw(format!("{}: ", taxobox::text(node))).bold(),
+                Span::raw(expansion.clone()),
+            ];
+            render_info_line(f, area, spans);
+        }
+    }
+
+    /// Draws the notice of the title the page was redirected from in the first line of the area
+    fn render_redirect_notice(&self, f: &mut Frame, area: Rect) {
+        let source = match self.page.redirected_from {
+            Some(ref source) => source,
+            None => return,
+        };
+        f.render_widget(
+            Paragraph::new(Line::from(vec![
+                Span::raw(tr_args(
+                    "page.redirected_from",
+                    &[("title", source.clone())],
+                ))
+                .italic(),
+                Span::raw(format!(" {}", tr("page.redirect_notice_keys"))).dark_gray(),
+            ])),
+            Rect { height: 1, ..area },
+        );
+    }
+
+    /// Draws the notice that the page was edited since it was loaded in the first line of the area
+    fn render_update_notice(&self, f: &mut Frame, area: Rect) {
+        f.render_widget(
+            Paragraph::new(Line::from(vec![
+                Span::raw(tr("page.updated_notice")).italic(),
+                Span::raw(format!(" {}", tr("page.updated_notice_keys"))).dark_gray(),
+            ])),
+            Rect { height: 1, ..area },
+        );
+    }
+
+    /// Returns the first line of the selected link that is shown in the viewport
+    fn selected_line(&self) -> Option<usize> {
+        let (top, bottom) = (
+            self.viewport.top() as usize,
+            self.viewport.bottom() as usize,
+        );
+        self.rendered_page()?
+            .link(self.selected)?
+            .segments
+            .iter()
+            .map(|(line, _)| *line)
+            .find(|line| top <= *line && *line < bottom)
+    }
+
+    /// Selects the first link on the next (or previous) line that contains another link than the
+    /// selected one and scrolls it into view. Starts at the edge of the viewport when the
+    /// selection isn't shown. Returns whether a link was found
+    fn select_line(&mut self, forward: bool) -> bool {
+        let rendered_page = match self.rendered_page() {
+            Some(rendered_page) => rendered_page,
+            None => return false,
+        };
+        let top = self.viewport.top() as usize;
+        let bottom = (self.viewport.bottom() as usize).min(rendered_page.lines.len());
+        let selected_line = self.selected_line();
+
+        // the part of every other link that is closest to the starting line in the direction
+        let candidates = rendered_page
+            .links
+            .iter()
+            .filter(|link| link.nodes != self.selected)
+            .filter_map(|link| {
+                let segment = if forward {
+                    let start = selected_line.map(|line| line + 1).unwrap_or(top);
+                    link.segments.iter().find(|(line, _)| start <= *line)
+                } else {
+                    let end = selected_line.unwrap_or(bottom);
+                    link.segments.iter().rev().find(|(line, _)| *line < end)
+                };
+                segment.map(|(line, words)| (*line, words.start, link))
+            });
+        // the first link on the closest line
+        let found = if forward {
+            candidates.min_by_key(|(line, word, _)| (*line, *word))
+        } else {
+            candidates.max_by_key(|(line, word, _)| (*line, Reverse(*word)))
+        }
+        .map(|(_, _, link)| (link.nodes, link.first_line(), link.last_line()));
+
+        match found {
+            Some((nodes, first_line, last_line)) => {
+                self.selected = nodes;
+                // shows as much of a wrapped link as fits, at least its first line
+                self.scroll_into_view(last_line);
+                self.scroll_into_view(first_line);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves the selection by the amount of lines containing links. When there are no more
+    /// links, the page is scrolled instead
+    fn move_selection(&mut self, forward: bool, amount: u16) {
+        for _ in 0..amount {
+            if self.select_line(forward) {
+                continue;
+            }
+            if forward {
+                self.scroll_down(1);
+            } else {
+                self.scroll_up(1);
+            }
+        }
+    }
+
+    fn select_first(&mut self) {
+        if self.page.content.nth(0).is_none() {
+            return;
+        }
+
+        let selectable_node = self
+            .page
+            .content
+            .nth(0)
+            .unwrap()
+            .descendants()
+            .find(|node| is_link(node.data()) && !self.is_hidden(node.index()));
+
+        if let Some(selectable_node) = selectable_node {
+            self.selected = link_nodes(selectable_node);
+        }
+    }
+
+    fn select_prev(&mut self) {
+        if self.page.content.nth(0).is_none() {
+            return;
+        }
+
+        let selectable_node = self
+            .page
+            .content
+            .nth(0)
+            .unwrap()
+            .descendants()
+            .filter(|node| {
+                is_link(node.data())
+                    && !self.is_hidden(node.index())
+                    && node.index() < self.selected.0
+            })
+            .last();
+
+        if let Some(selectable_node) = selectable_node {
+            self.selected = link_nodes(selectable_node);
+        }
+    }
+
+    fn select_next(&mut self) {
+        if self.page.content.nth(0).is_none() {
+            return;
+        }
+
+        let selectable_node = self
+            .page
+            .content
+            .nth(0)
+            .unwrap()
+            .descendants()
+            .find(|node| {
+                is_link(node.data())
+                    && !self.is_hidden(node.index())
+                    && self.selected.1 < node.index()
+            });
+
+        if let Some(selectable_node) = selectable_node {
+            self.selected = link_nodes(selectable_node);
+        }
+    }
+
+    fn select_last(&mut self) {
+        if self.page.content.nth(0).is_none() {
+            return;
+        }
+
+        let selectable_node = self
+            .page
+            .content
+            .nth(0)
+            .unwrap()
+            .descendants()
+            .filter(|node| {
+                is_link(node.data())
+                    && !self.is_hidden(node.index())
+                    && node.index() > self.selected.1
+            })
+            .last();
+
+        if let Some(selectable_node) = selectable_node {
+            self.selected = link_nodes(selectable_node);
+        }
+    }
+
+    /// Returns the sections matching the contents filter, the best match first, together with
+    /// the positions of the matched characters in their text
+    fn filtered_sections(&self) -> Vec<(&Section, Vec<usize>)> {
+        let sections = self.page.sections.iter().flatten();
+        let query = match self.contents_filter {
+            Some(ref input) if !input.value().is_empty() => input.value(),
+            _ => return sections.map(|section| (section, Vec::new())).collect(),
+        };
+
+        rank_matches(query, sections, |section| section.text.as_str())
+            .into_iter()
+            .map(|(section, matched)| (section, matched.positions))
+            .collect()
+    }
+
+    /// Scrolls to the header of the section with the anchor
+    fn jump_to_section(&mut self, anchor: &str) {
+        let header = self
+            .page
+            .content
+            .nodes
+            .iter()
+            .position(|node| matches!(node.data, Data::Header { ref id, .. } if id == anchor));
+        let header = match header {
+            Some(header) => header,
+            // the top section has no header
+            None => {
+                self.viewport.y = 0;
+                return;
+            }
+        };
+
+        self.expand_to(header);
+        if let Some(line) = self.first_line_of(header) {
+            self.viewport.y = line as u16;
+        }
+    }
+
+    /// Marks the text at the top of the viewport as the place where the reader stopped
+    fn set_scroll_mark(&mut self) {
+        let anchor = self.rendered_page().and_then(|rendered_page| {
+            text_anchor(&rendered_page.lines, self.viewport.top() as usize)
+        });
+        if let Some(anchor) = anchor {
+            scroll_marks().set(&self.page, anchor);
+            self.scroll_mark = Some(anchor);
+            info!("marked the place in '{}'", self.page.title);
+        }
+    }
+
+    /// Returns the line of the marked place in the current render
+    fn scroll_mark_line(&self) -> Option<usize> {
+        anchor_line(&self.rendered_page()?.lines, self.scroll_mark?)
+    }
+
+    /// Scrolls the marked place to the top of the viewport, opening the sections it's in
+    fn jump_to_scroll_mark(&mut self) {
+        let (node, _) = match self.scroll_mark {
+            Some(scroll_mark) => scroll_mark,
+            None => {
+                info!("no place is marked in '{}'", self.page.title);
+                return;
+            }
+        };
+
+        self.expand_to(node);
+        if let Some(line) = self.scroll_mark_line() {
+            self.viewport.y = match self.is_paginated {
+                true => self.page_start(line as u16),
+                false => line as u16,
+            };
+        }
+    }
+
+    /// Returns the first rendered line that contains the node or one of its descendants
+    fn first_line_of(&self, index: usize) -> Option<usize> {
+        self.rendered_page().and_then(|rendered_page| {
+            rendered_page.lines.iter().position(|line| {
+                line.iter()
+                    .any(|word| word.index != usize::MAX && self.is_in_section(word.index, index))
+            })
+        })
+    }
+
+    /// Returns the action for the selected link, if there is one for its kind
+    fn open_selected(&self) -> ActionResult {
+        match self
+            .page
+            .content
+            .nth(self.selected.0)
+            .map(|node| node.data())
+        {
+            Some(Data::Citation { number, .. }) => {
+                Action::Page(PageAction::JumpToReference(*number)).into()
+            }
+            Some(Data::ExternalLink { href, .. }) => Action::Page(PageAction::ExternalLink(
+                ExternalLinkAction::Show(href.clone()),
+            ))
+            .into(),
+            Some(Data::MediaLink { href, .. }) => match media_link_title(href) {
+                Some(title) => Action::Page(PageAction::Media(MediaAction::Show(title))).into(),
+                None => ActionResult::Ignored,
+            },
+            Some(data @ Data::WikiLink { .. }) => match link_title(data) {
+                Some(title) if is_file_title(&title) => {
+                    Action::Page(PageAction::Media(MediaAction::Show(title))).into()
+                }
+                _ => ActionResult::Ignored,
+            },
+            _ => ActionResult::Ignored,
+        }
+    }
+
+    /// Opens the archived snapshot of the selected external link, without trying the link itself
+    fn open_selected_snapshot(&self) -> ActionResult {
+        match self
+            .page
+            .content
+            .nth(self.selected.0)
+            .map(|node| node.data())
+        {
+            Some(Data::ExternalLink { href, .. }) => ActionPacket::single(Action::Page(
+                PageAction::ExternalLink(ExternalLinkAction::Show(href.clone())),
+            ))
+            .action(Action::Page(PageAction::ExternalLink(
+                ExternalLinkAction::OpenSnapshot,
+            )))
+            .into(),
+            _ => ActionResult::Ignored,
+        }
+    }
+
+    fn update_external_link(&mut self, action: ExternalLinkAction) -> ActionResult {
+        match action {
+            ExternalLinkAction::Show(url) => {
+                self.external_link = Some(ExternalLinkComponent::new(url, self.action_tx.clone()))
+            }
+            ExternalLinkAction::Close => self.external_link = None,
+            action => {
+                return match self.external_link {
+                    Some(ref mut dialog) => {
+                        dialog.update(Action::Page(PageAction::ExternalLink(action)))
+                    }
+                    None => ActionResult::Ignored,
+                }
+            }
+        }
+        ActionResult::consumed()
+    }
+
+    fn update_media(&mut self, action: MediaAction) -> ActionResult {
+        match action {
+            MediaAction::Show(title) => {
+                self.media = Some(MediaComponent::new(
+                    title,
+                    &self.page.language,
+                    self.action_tx.clone(),
+                ))
+            }
+            MediaAction::Close => self.media = None,
+            action => {
+                return match self.media {
+                    Some(ref mut dialog) => dialog.update(Action::Page(PageAction::Media(action))),
+                    None => ActionResult::Ignored,
+                }
+            }
+        }
+        ActionResult::consumed()
+    }
+
+    /// Returns the title of the page the selected link points to, including red links
+    fn selected_link_title(&self) -> Option<String> {
+        link_title(self.page.content.nth(self.selected.0)?.data())
+    }
+
+    /// Peeks at the page the selected link points to, red links can't be peeked at
+    fn peek_selected_link(&self) -> ActionResult {
+        match self
+            .page
+            .content
+            .nth(self.selected.0)
+            .map(|node| node.data())
+        {
+            Some(Data::RedLink { .. }) => ActionResult::Ignored,
+            _ => match self.selected_link_title() {
+                Some(title) => Action::LoadPeek(title).into(),
+                None => ActionResult::Ignored,
+            },
+        }
+    }
+
+    /// Adds the page the selected link points to to the fetch queue. Red links are queued too, so
+    /// they are fetched once the page was created
+    fn queue_selected_link(&self) -> ActionResult {
+        match self.selected_link_title() {
+            Some(title) => Action::QueueFetch(title).into(),
+            None => ActionResult::Ignored,
+        }
+    }
+
+    /// Returns the url of the linked page as a yank, which runs the `link_yanked` hook
+    fn link_yank(&self, title: String) -> Yank {
+        let language_code = self.page.language.code();
+        let url = article_url(language_code, &title);
+        Yank::new(
+            YankKind::Link,
+            url.clone(),
+            title.clone(),
+            vec![
+                ("url", url),
+                ("title", title),
+                ("language", language_code.to_string()),
+                ("page", self.page.title.clone()),
+            ],
+        )
+    }
+
+    /// Hands the selected link to the `link_yanked` hook, which can copy it to the clipboard
+    fn yank_selected_link(&self) {
+        if let Some(title) = self.selected_link_title() {
+            yank_ring().yank(self.link_yank(title));
+        }
+    }
+
+    /// Opens a menu with the formats the page can be cited in, and adding it to the bibliography
+    /// when one is configured
+    fn show_cite_menu(&self) -> ActionResult {
+        let mut menu = ContextMenu::new(tr("page.cite"))
+            .item(
+                'p',
+                tr("page.cite_plain"),
+                vec![Action::Page(PageAction::Cite(CitationFormat::Plain))],
+            )
+            .item(
+                'b',
+                tr("page.cite_bibtex"),
+                vec![Action::Page(PageAction::Cite(CitationFormat::Bibtex))],
+            )
+            .item(
+                'j',
+                tr("page.cite_csl_json"),
+                vec![Action::Page(PageAction::Cite(CitationFormat::CslJson))],
+            );
+        if config().export.bibliography.is_some() {
+            menu = menu.item(
+                'a',
+                tr("page.add_to_bibliography"),
+                vec![Action::Page(PageAction::AddToBibliography)],
+            );
+        }
+        Action::OpenContextMenu(menu).into()
+    }
+
+    /// Hands a citation of the page, accessed today, to the `text_yanked` hook
+    fn yank_citation(&self, format: CitationFormat) {
+        let text = cite(&self.page, format, Local::now().date_naive());
+        yank_ring().yank(Yank::new(
+            YankKind::Citation,
+            text.clone(),
+            self.page.title.clone(),
+            vec![("text", text), ("page", self.page.title.clone())],
+        ));
+    }
+
+    fn add_to_bibliography(&self) {
+        let path = match config().export.bibliography {
+            Some(ref path) => path,
+            None => return,
+        };
+        if let Err(error) = add_to_bibliography(path, &self.page, Local::now().date_naive()) {
+            error!("unable to add the page to the bibliography: {:?}", error);
+        }
+    }
+
+    /// Opens a menu with actions for the page the selected link points to. None of them leaves
+    /// the page. They are in a menu because `b`, `T` and `p` are taken on the page already
+    fn show_link_menu(&self) -> ActionResult {
+        let title = match self.selected_link_title() {
+            Some(title) => title,
+            None => return ActionResult::Ignored,
+        };
+
+        let menu = ContextMenu::new(title.clone())
+            .item(
+                'p',
+                tr("page.preview_link"),
+                vec![Action::Page(PageAction::PreviewLink(title.clone()))],
+            )
+            .item(
+                'e',
+                tr("page.peek_link"),
+                vec![Action::LoadPeek(title.clone())],
+            )
+            .item(
+                'b',
+                tr("page.bookmark_link"),
+                vec![Action::AddBookmark(
+                    title.clone(),
+                    self.page.language.clone(),
+                )],
+            )
+            .item(
+                'r',
+                tr("page.read_link_next"),
+                vec![Action::QueueReading(
+                    title.clone(),
+                    self.page.language.clone(),
+                )],
+            )
+            .item(
+                'y',
+                tr("page.copy_link_url"),
+                vec![Action::Yank(self.link_yank(title.clone()))],
+            )
+            .item(
+                'T',
+                tr("page.open_in_background"),
+                vec![Action::LoadPageInBackground(title)],
+            );
+        Action::OpenContextMenu(menu).into()
+    }
+
+    /// Fetches the summary of the linked page in the background and shows it once it's loaded
+    fn preview_link(&mut self, title: String) {
+        let tx = match self.action_tx {
+            Some(ref tx) => tx.clone(),
+            None => return,
+        };
+        let backend = content_source().backend(self.page.language.wikipedia_endpoint());
+
+        self.preview = Some(None);
+        tokio::spawn(async move {
+            match backend.fetch_summary(&title).await {
+                Ok(summary) => tx
+                    .send(Action::Page(PageAction::FinishPreview(summary)))
+                    .unwrap(),
+                Err(error) => {
+                    warn!("Unable to fetch the preview of '{}': {:?}", title, error);
+                    tx.send(Action::Page(PageAction::ClosePreview)).unwrap();
+                }
+            }
+        });
+    }
+
+    /// Scrolls to the note with the number in the reference list
+    fn jump_to_reference(&mut self, number: usize) {
+        let reference =
+            self.page.content.nodes.iter().position(
+                |node| matches!(node.data, Data::Reference { number: n, .. } if n == number),
+            );
+        if let Some(reference) = reference {
+            self.expand_to(reference);
+        }
+        match reference.and_then(|reference| self.first_line_of(reference)) {
+            Some(line) => self.viewport.y = line as u16,
+            None => warn!("unable to find the reference [{}]", number),
+        }
+    }
+
+    /// Starts searching for text in the page, or edits the query of the current search
+    fn find_in_page(&mut self) {
+        match self.find {
+            Some(ref mut find) => find.is_typing = true,
+            None => {
+                self.find = Some(PageFind {
+                    input: Input::default(),
+                    is_typing: true,
+                    section: None,
+                    matches: Vec::new(),
+                    current: None,
+                    key: None,
+                })
+            }
+        }
+    }
+
+    /// Finds the matches of the query in the current render, when they were found in a different
+    /// one or the query changed
+    fn update_find_matches(&mut self) {
+        let key = self.render_key(self.viewport.width);
+        let (query, section) = match self.find {
+            Some(ref find) if find.key.as_ref() != Some(&key) => {
+                (find.input.value().to_string(), find.section)
+            }
+            _ => return,
+        };
+        let rendered_page = match self.rendered_page() {
+            Some(rendered_page) => rendered_page,
+            None => return,
+        };
+
+        let mut matches = find_matches(&rendered_page.lines, &query);
+        if let Some(section) = section {
+            matches.retain(|((line, word), _)| {
+                let index = rendered_page.lines[*line][*word].index;
+                index != usize::MAX && self.is_in_section(index, section)
+            });
+        }
+        if let Some(ref mut find) = self.find {
+            find.matches = matches;
+            find.current = None;
+            find.key = Some(key);
+        }
+    }
+
+    /// Limits the matches to the section at the top of the page and its subsections, or finds
+    /// them in the whole page again
+    fn toggle_find_scope(&mut self) {
+        let section = self.section_at_top();
+        if let Some(ref mut find) = self.find {
+            find.section = match find.section {
+                Some(_) => None,
+                None => section,
+            };
+            find.key = None;
+        }
+    }
+
+    fn submit_find(&mut self) {
+        match self.find {
+            Some(ref find) if find.input.value().trim().is_empty() => self.find = None,
+            Some(ref mut find) => {
+                find.is_typing = false;
+                self.jump_to_match(true);
+            }
+            None => {}
+        }
+    }
+
+    /// Scrolls to the next (or previous) match. Without a match scrolled to yet, the first match
+    /// after (or before) the top of the page is scrolled to
+    fn jump_to_match(&mut self, forward: bool) {
+        self.ensure_rendered();
+        self.update_find_matches();
+        let top = self.viewport.top() as usize;
+        let find = match self.find {
+            Some(ref mut find) => find,
+            None => return,
+        };
+        if find.matches.is_empty() {
+            return info!("'{}' was not found", find.input.value());
+        }
+
+        let count = find.matches.len();
+        let current = match find.current {
+            Some(current) if forward => (current + 1) % count,
+            Some(current) => (current + count - 1) % count,
+            None if forward => find
+                .matches
+                .iter()
+                .position(|(start, _)| start.0 >= top)
+                .unwrap_or(0),
+            None => find
+                .matches
+                .iter()
+                .rposition(|(start, _)| start.0 < top)
+                .unwrap_or(count - 1),
+        };
+        find.current = Some(current);
+        let line = find.matches[current].0 .0;
+        self.scroll_into_view(line);
+    }
+
+    /// Draws the query, how many matches were found where and the keys at the bottom of the page
+    fn render_find(&self, f: &mut Frame, area: Rect) {
+        let find = match self.find {
+            Some(ref find) => find,
+            None => return,
+        };
+
+        let scope = match find.section {
+            Some(section) => match self.section_heading(section) {
+                Some(heading) => tr_args("page.find_in_section", &[("section", heading)]),
+                None => tr("page.find_in_lead").to_string(),
+            },
+            None => tr("page.find_in_page").to_string(),
+        };
+        let count = find.matches.len().to_string();
+        let status = match find.current {
+            _ if find.input.value().trim().is_empty() => scope,
+            Some(current) => tr_args(
+                "page.find_match",
+                &[
+                    ("match", (current + 1).to_string()),
+                    ("matches", count),
+                    ("scope", scope),
+                ],
+            ),
+            None => tr_args("page.find_matches", &[("matches", count), ("scope", scope)]),
+        };
+        let keys = match find.is_typing {
+            true => tr("page.find_input_keys"),
+            false => tr("page.find_keys"),
+        };
+
+        let area = Rect {
+            y: area.bottom().saturating_sub(1),
+            height: area.height.min(1),
+            ..area
+        };
+        let query = format!("/{}", find.input.value());
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Paragraph::new(Line::from(vec![
+                Span::raw(query.clone()).yellow(),
+                Span::raw(format!("  {} ", status)),
+                Span::raw(keys).dark_gray(),
+            ])),
+            area,
+        );
+        if find.is_typing {
+            let cursor = query
+                .chars()
+                .count()
+                .min(area.width.saturating_sub(1) as usize);
+            f.set_cursor(area.x + cursor as u16, area.y);
+        }
+    }
+
+    fn jump_to_contents_match(&mut self) {
+        let anchor = self
+            .filtered_sections()
+            .first()
+            .map(|(section, _)| section.anchor.clone());
+        self.contents_filter = None;
+        if let Some(anchor) = anchor {
+            self.jump_to_section(&anchor);
+        }
+    }
+
+    fn open_contents_match_section(&mut self) -> ActionResult {
+        let section = self
+            .filtered_sections()
+            .first()
+            .map(|(section, _)| (*section).clone());
+        self.contents_filter = None;
+        match section {
+            Some(section) => Action::LoadSection(self.page.title.clone(), section).into(),
+            None => ActionResult::consumed(),
+        }
+    }
+
+    fn render_contents(&self, f: &mut Frame, area: Rect) {
+        let block = Block::default()
+            .title(tr("page.contents"))
+            .borders(Borders::LEFT);
+        let inner_area = block.inner(area);
+        f.render_widget(block, area);
+
+        let list_area = match self.contents_filter {
+            Some(ref input) => {
+                let [input_area, list_area] = {
+                    let rects = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints([Constraint::Min(1), Constraint::Percentage(100)])
+                        .split(inner_area);
+                    [rects[0], rects[1]]
+                };
+                let width = input_area.width.saturating_sub(1) as usize;
+                let scroll = input.visual_scroll(width);
+                f.render_widget(
+                    Paragraph::new(format!("/{}", input.value())).scroll((0, scroll as u16)),
+                    input_area,
+                );
+                f.set_cursor(
+                    input_area.x + (input.visual_cursor().max(scroll) - scroll) as u16 + 1,
+                    input_area.y,
+                );
+                list_area
+            }
+            None => inner_area,
+        };
+
+        let items: Vec<ListItem> = self
+            .filtered_sections()
+            .into_iter()
+            .map(|(section, positions)| {
+                let depth = (section.header_kind.clone() as usize).saturating_sub(1);
+                let mut spans = vec![Span::raw(format!(
+                    "{}{} ",
+                    " ".repeat(depth * 2),
+                    section.number
+                ))];
+                spans.extend(highlight_matches(&section.text, &positions));
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+
+        // the top match is the one jumped to
+        let mut state = ListState::default();
+        if self.contents_filter.is_some() {
+            state.select(Some(0));
+        }
+        let contents =
+            List::new(items).highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        f.render_stateful_widget(contents, list_area, &mut state);
+    }
+
+    fn open_link_graph(&self) -> ActionResult {
+        ActionPacket::default()
+            .action(Action::SwitchContextLinkGraph)
+            .action(Action::LinkGraph(LinkGraphAction::Open(
+                self.page.title.clone(),
+            )))
+            .into()
+    }
+
+    /// Changes the width the page is rendered with once the available width stopped changing
+    ///
+    /// Until then the previous render is shown, so a terminal that's being resized doesn't render
+    /// (and cache) the page for every width in between. Widths that are cached already are
+    /// switched to right away. The available width is checked at `now`
+    fn settle_width(&mut self, width: u16, now: Instant) {
+        if width == self.viewport.width {
+            self.pending_width = None;
+            return;
+        }
+
+        let is_settled = match self.pending_width {
+            Some((pending, since)) if pending == width => {
+                now.saturating_duration_since(since) >= RESIZE_DEBOUNCE
+            }
+            _ => {
+                self.pending_width = Some((width, now));
+                false
+            }
+        };
+        let is_cached = self.render_cache.peek(&self.render_key(width)).is_some();
+        if !is_settled && !is_cached && self.rendered_page().is_some() {
+            return;
+        }
+
+        self.pending_width = None;
+        self.change_width(width);
+    }
+
+    /// Renders the page with the width, keeping the text at the top of the viewport in view
+    fn change_width(&mut self, width: u16) {
+        let top = self.viewport.top();
+        let anchor = match self.width_anchor {
+            Some((anchor, line)) if line == top => Some(anchor),
+            _ => self
+                .rendered_page()
+                .and_then(|rendered_page| text_anchor(&rendered_page.lines, top as usize)),
+        };
+
+        self.viewport.width = width;
+        self.invalidate_positions();
+        self.ensure_rendered();
+
+        let line = anchor.and_then(|anchor| anchor_line(&self.rendered_page()?.lines, anchor));
+        match line {
+            Some(line) => self.viewport.y = line as u16,
+            None => self.clamp_viewport(),
+        }
+        self.width_anchor = anchor.map(|anchor| (anchor, self.viewport.top()));
+    }
+
+    /// Returns how many columns the page is shown in when it's as wide as the width, and how
+    /// wide each of them is
+    fn column_layout(&self, width: u16) -> (u16, u16) {
+        let columns = self.column_count(width);
+        let column_width = width.saturating_sub(COLUMN_GAP * (columns - 1)) / columns;
+        (columns, column_width)
+    }
+
+    /// Returns how many columns the page is shown in when it's as wide as the width
+    fn column_count(&self, width: u16) -> u16 {
+        let fits_two = width >= 2 * COLUMN_MIN_WIDTH + COLUMN_GAP;
+        match self.columns {
+            Columns::Two if fits_two => 2,
+            Columns::Auto if fits_two && width >= COLUMNS_AUTO_MIN_WIDTH => 2,
+            _ => 1,
+        }
+    }
+
+    /// Scrolls the first line of the selected link to the position in the viewport
+    fn align_selection(&mut self, position: ViewportPosition) {
+        let line = match self
+            .rendered_page()
+            .and_then(|rendered_page| rendered_page.link(self.selected))
+            .and_then(|link| link.segments.first())
+        {
+            Some((line, _)) => *line as u16,
+            None => return,
+        };
+        let height = self.viewport.height;
+        self.viewport.y = match position {
+            ViewportPosition::Top => line,
+            ViewportPosition::Center => line.saturating_sub(height / 2),
+            ViewportPosition::Bottom => line.saturating_sub(height.saturating_sub(1)),
+        };
+        self.clamp_viewport();
+    }
+
+    /// Keeps the viewport inside of the rendered page
+    fn clamp_viewport(&mut self) {
+        let lines = match self.rendered_page() {
+            Some(rendered_page) => rendered_page.lines.len(),
+            None => return,
+        };
+        self.viewport.y = self.viewport.y.min(lines.saturating_sub(1) as u16);
+        self.viewport.x = self.viewport.x.min(self.max_scroll_x());
+    }
+}
+
+impl Component for PageComponent {
+    fn init(&mut self, action_tx: mpsc::UnboundedSender<Action>) -> anyhow::Result<()> {
+        self.action_tx = Some(action_tx);
+        Ok(())
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        if let Some(ref mut dialog) = self.external_link {
+            return dialog.handle_key_events(key);
+        }
+
+        if let Some(ref mut dialog) = self.media {
+            return dialog.handle_key_events(key);
+        }
+
+        // the preview is closed by any key
+        if self.preview.is_some() {
+            return Action::Page(PageAction::ClosePreview).into();
+        }
+        if self.outline.is_some() {
+            return Action::Page(PageAction::CloseOutline).into();
+        }
+
+        if let Some(ref mut input) = self.note_input {
+            return match key.code {
+                KeyCode::Enter => Action::Page(PageAction::SaveAnnotation).into(),
+                KeyCode::Esc => Action::Page(PageAction::CancelNote).into(),
+                _ => {
+                    input.handle_event(&crossterm::event::Event::Key(key));
+                    ActionResult::consumed()
+                }
+            };
+        }
+
+        if let Some(ref mut input) = self.contents_filter {
+            return match key.code {
+                KeyCode::Enter => Action::Page(PageAction::JumpToContentsMatch).into(),
+                KeyCode::Char('o') if has_modifier!(key, Modifier::CONTROL) => {
+                    Action::Page(PageAction::OpenContentsMatchSection).into()
+                }
+                KeyCode::Esc => Action::Page(PageAction::CloseContentsFilter).into(),
+                _ => {
+                    input.handle_event(&crossterm::event::Event::Key(key));
+                    ActionResult::consumed()
+                }
+            };
+        }
+
+        if let Some(ref mut find) = self.find {
+            if find.is_typing {
+                return match key.code {
+                    KeyCode::Enter => Action::Page(PageAction::SubmitFind).into(),
+                    KeyCode::Tab => Action::Page(PageAction::ToggleFindScope).into(),
+                    KeyCode::Esc => Action::Page(PageAction::CloseFind).into(),
+                    _ => {
+                        find.input.handle_event(&crossterm::event::Event::Key(key));
+                        find.key = None;
+                        ActionResult::consumed()
+                    }
+                };
+            }
+            match key.code {
+                KeyCode::Char('n') => return Action::Page(PageAction::NextMatch).into(),
+                KeyCode::Char('N') => return Action::Page(PageAction::PrevMatch).into(),
+                KeyCode::Tab => return Action::Page(PageAction::ToggleFindScope).into(),
+                KeyCode::Esc => return Action::Page(PageAction::CloseFind).into(),
+                _ => {}
+            }
+        }
+
+        if self.table_cell.is_some() {
+            match key.code {
+                KeyCode::Left | KeyCode::Char('h') => {
+                    return Action::Page(PageAction::SelectCellLeft).into()
+                }
+                KeyCode::Right | KeyCode::Char('l') => {
+                    return Action::Page(PageAction::SelectCellRight).into()
+                }
+                KeyCode::Up | KeyCode::Char('k') => {
+                    return Action::Page(PageAction::SelectCellAbove).into()
+                }
+                KeyCode::Down | KeyCode::Char('j') => {
+                    return Action::Page(PageAction::SelectCellBelow).into()
+                }
+                KeyCode::Esc => return Action::Page(PageAction::ToggleTableMode).into(),
+                _ => {}
+            }
+        }
+
+        if self.highlight_selection.is_some() {
+            match key.code {
+                KeyCode::Char(']') => return Action::Page(PageAction::SelectNextPassage).into(),
+                KeyCode::Char('[') => return Action::Page(PageAction::SelectPrevPassage).into(),
+                KeyCode::Char('}') => return Action::Page(PageAction::ExtendPassage).into(),
+                KeyCode::Enter => return Action::Page(PageAction::EditNote).into(),
+                KeyCode::Char('y') => return Action::Page(PageAction::YankPassage).into(),
+                KeyCode::Esc => return Action::Page(PageAction::ToggleHighlightMode).into(),
+                _ => {}
+            }
+        }
+
+        match key.code {
+            KeyCode::Char('r') if has_modifier!(key, Modifier::CONTROL) => {
+                Action::Page(PageAction::SwitchRenderer(self.renderer.next())).into()
+            }
+            KeyCode::Left if has_modifier!(key, Modifier::SHIFT) => {
+                Action::Page(PageAction::SelectFirstLink).into()
+            }
+            KeyCode::Right if has_modifier!(key, Modifier::SHIFT) => {
+                Action::Page(PageAction::SelectLastLink).into()
+            }
+            KeyCode::Up if has_modifier!(key, Modifier::SHIFT) => {
+                Action::Page(PageAction::SelectTopLink).into()
+            }
+            KeyCode::Down if has_modifier!(key, Modifier::SHIFT) => {
+                Action::Page(PageAction::SelectBottomLink).into()
+            }
+            KeyCode::Left => Action::Page(PageAction::SelectPrevLink).into(),
+            KeyCode::Right => Action::Page(PageAction::SelectNextLink).into(),
+            KeyCode::Up => Action::ScrollUp(1).into(),
+            KeyCode::Down => Action::ScrollDown(1).into(),
+            KeyCode::Char('c') => Action::Page(PageAction::ToggleContents).into(),
+            KeyCode::Char('/') if self.shows_contents() => {
+                Action::Page(PageAction::FilterContents).into()
+            }
+            KeyCode::Char('f') if has_modifier!(key, Modifier::CONTROL) => {
+                Action::Page(PageAction::FindInPage).into()
+            }
+            KeyCode::Char('t') if has_modifier!(key, Modifier::CONTROL) => {
+                Action::Page(PageAction::StopSpeech).into()
+            }
+            KeyCode::Char('t') => Action::Page(PageAction::ToggleSpeech).into(),
+            KeyCode::Char('T') => Action::Page(PageAction::SpeakNextSection).into(),
+            KeyCode::Char('L') => self.open_link_graph(),
+            KeyCode::Char('v') => Action::Page(PageAction::ToggleHighlightMode).into(),
+            KeyCode::Char('E') => Action::Page(PageAction::ExportMarkdown).into(),
+            KeyCode::Char('X') => Action::Page(PageAction::ExportEpub).into(),
+            KeyCode::Char('Q') => Action::Page(PageAction::ShowCiteMenu).into(),
+            KeyCode::Char('M') => Action::Page(PageAction::ToggleBookmark).into(),
+            KeyCode::Char('`') => Action::Page(PageAction::SetScrollMark).into(),
+            KeyCode::Char('\'') => Action::Page(PageAction::JumpToScrollMark).into(),
+            KeyCode::Char('S') => Action::Page(PageAction::OutlineSection).into(),
+            KeyCode::Char('R') if self.page.redirected_from.is_some() => {
+                Action::Page(PageAction::OpenRedirectSource).into()
+            }
+            KeyCode::Char('U') if self.newer_revision.is_some() => {
+                Action::Page(PageAction::ReloadLatestRevision).into()
+            }
+            KeyCode::Char('x') if self.newer_revision.is_some() => {
+                Action::Page(PageAction::DismissUpdateNotice).into()
+            }
+            KeyCode::Char('x') if self.is_redirect_notice => {
+                Action::Page(PageAction::DismissRedirectNotice).into()
+            }
+            KeyCode::Char('a') => Action::Page(PageAction::QueueSelectedLink).into(),
+            KeyCode::Char('y') => Action::Page(PageAction::YankSelectedLink).into(),
+            KeyCode::Char('<') => Action::Page(PageAction::ScrollLeft).into(),
+            KeyCode::Char('>') => Action::Page(PageAction::ScrollRight).into(),
+            KeyCode::Char('+') => Action::Page(PageAction::ZoomIn).into(),
+            KeyCode::Char('-') => Action::Page(PageAction::ZoomOut).into(),
+            KeyCode::Char('=') => Action::Page(PageAction::ResetZoom).into(),
+            KeyCode::Char('z') => Action::Page(PageAction::ToggleSection).into(),
+            KeyCode::Char('Z') => Action::Page(PageAction::ToggleAllSections).into(),
+            KeyCode::Char('e') => Action::Page(PageAction::ToggleCollapsible).into(),
+            KeyCode::Char('b') => Action::Page(PageAction::ToggleTaxobox).into(),
+            KeyCode::Char('m') => Action::Page(PageAction::ToggleTableMode).into(),
+            KeyCode::Char('W') => self.open_selected_snapshot(),
+            KeyCode::Char('D') if render_stats::is_enabled() => {
+                Action::Page(PageAction::ToggleRenderStats).into()
+            }
+            KeyCode::Char('o') => Action::Page(PageAction::ShowLinkMenu).into(),
+            KeyCode::Char('N') => Action::Page(PageAction::TogglePagination).into(),
+            KeyCode::Char(' ') | KeyCode::PageDown => Action::Page(PageAction::NextPage).into(),
+            KeyCode::Backspace | KeyCode::PageUp => Action::Page(PageAction::PrevPage).into(),
+            KeyCode::Enter if has_modifier!(key, Modifier::ALT) => {
+                Action::Page(PageAction::PeekSelectedLink).into()
+            }
+            KeyCode::Enter => self.open_selected(),
+            _ => ActionResult::Ignored,
+        }
+    }
+
+    fn keymap(&self) -> super::help::Keymap {
+        let mut keymap = vec![
+            (
+                key_event!('r', Modifier::CONTROL),
+                Action::Page(PageAction::SwitchRenderer(self.renderer.next())).into(),
+            ),
+            (
+                key_event!(Key::Left, Modifier::SHIFT),
+                Action::Page(PageAction::SelectFirstLink).into(),
+            ),
+            (
+                key_event!(Key::Left),
+                Action::Page(PageAction::SelectPrevLink).into(),
+            ),
+            (
+                key_event!(Key::Right, Modifier::SHIFT),
+                Action::Page(PageAction::SelectLastLink).into(),
+            ),
+            (
+                key_event!(Key::Right),
+                Action::Page(PageAction::SelectNextLink).into(),
+            ),
+            (
+                key_event!(Key::Up, Modifier::SHIFT),
+                Action::Page(PageAction::SelectTopLink).into(),
+            ),
+            (
+                key_event!(Key::Down, Modifier::SHIFT),
+                Action::Page(PageAction::SelectBottomLink).into(),
+            ),
+            (key_event!(Key::Up), Action::ScrollUp(1).into()),
+            (key_event!(Key::Down), Action::ScrollDown(1).into()),
+            (
+                key_event!('c'),
+                Action::Page(PageAction::ToggleContents).into(),
+            ),
+            (
+                key_event!('/'),
+                Action::Page(PageAction::FilterContents).into(),
+            ),
+            (
+                key_event!('f', Modifier::CONTROL),
+                Action::Page(PageAction::FindInPage).into(),
+            ),
+            (
+                key_event!(Key::Tab),
+                Action::Page(PageAction::ToggleFindScope).into(),
+            ),
+            (key_event!('n'), Action::Page(PageAction::NextMatch).into()),
+            (
+                key_event!('N', Modifier::SHIFT),
+                Action::Page(PageAction::PrevMatch).into(),
+            ),
+            (
+                key_event!('t'),
+                Action::Page(PageAction::ToggleSpeech).into(),
+            ),
+            (
+                key_event!('T', Modifier::SHIFT),
+                Action::Page(PageAction::SpeakNextSection).into(),
+            ),
+            (
+                key_event!('L', Modifier::SHIFT),
+                Action::SwitchContextLinkGraph.into(),
+            ),
+            (
+                key_event!('v'),
+                Action::Page(PageAction::ToggleHighlightMode).into(),
+            ),
+            (
+                key_event!(']'),
+                Action::Page(PageAction::SelectNextPassage).into(),
+            ),
+            (
+                key_event!('['),
+                Action::Page(PageAction::SelectPrevPassage).into(),
+            ),
+            (
+                key_event!('}'),
+                Action::Page(PageAction::ExtendPassage).into(),
+            ),
+            (
+                key_event!('y'),
+                Action::Page(PageAction::YankPassage).into(),
+            ),
+            (
+                key_event!('t', Modifier::CONTROL),
+                Action::Page(PageAction::StopSpeech).into(),
+            ),
+            (
+                key_event!('o', Modifier::CONTROL),
+                Action::Page(PageAction::OpenContentsMatchSection).into(),
+            ),
+            (
+                key_event!('E', Modifier::SHIFT),
+                Action::Page(PageAction::ExportMarkdown).into(),
+            ),
+            (
+                key_event!('X', Modifier::SHIFT),
+                Action::Page(PageAction::ExportEpub).into(),
+            ),
+            (
+                key_event!('Q', Modifier::SHIFT),
+                Action::Page(PageAction::ShowCiteMenu).into(),
+            ),
+            (
+                key_event!('M', Modifier::SHIFT),
+                Action::Page(PageAction::ToggleBookmark).into(),
+            ),
+            (
+                key_event!('`'),
+                Action::Page(PageAction::SetScrollMark).into(),
+            ),
+            (
+                key_event!('\''),
+                Action::Page(PageAction::JumpToScrollMark).into(),
+            ),
+            (
+                key_event!('R', Modifier::SHIFT),
+                Action::Page(PageAction::OpenRedirectSource).into(),
+            ),
+            (
+                key_event!('x'),
+                Action::Page(PageAction::DismissRedirectNotice).into(),
+            ),
+            (
+                key_event!('U', Modifier::SHIFT),
+                Action::Page(PageAction::ReloadLatestRevision).into(),
+            ),
+            (
+                key_event!('x'),
+                Action::Page(PageAction::DismissUpdateNotice).into(),
+            ),
+            (
+                key_event!('a'),
+                Action::Page(PageAction::QueueSelectedLink).into(),
+            ),
+            (
+                key_event!(Key::Enter, Modifier::ALT),
+                Action::Page(PageAction::PeekSelectedLink).into(),
+            ),
+            (
+                key_event!('S', Modifier::SHIFT),
+                Action::Page(PageAction::OutlineSection).into(),
+            ),
+            (
+                key_event!('y'),
+                Action::Page(PageAction::YankSelectedLink).into(),
+            ),
+            (key_event!('<'), Action::Page(PageAction::ScrollLeft).into()),
+            (
+                key_event!('>'),
+                Action::Page(PageAction::ScrollRight).into(),
+            ),
+            (key_event!('+'), Action::Page(PageAction::ZoomIn).into()),
+            (key_event!('-'), Action::Page(PageAction::ZoomOut).into()),
+            (key_event!('='), Action::Page(PageAction::ResetZoom).into()),
+            (
+                key_event!('z'),
+                Action::Page(PageAction::ToggleSection).into(),
+            ),
+            (
+                key_event!('Z', Modifier::SHIFT),
+                Action::Page(PageAction::ToggleAllSections).into(),
+            ),
+            (
+                key_event!('e'),
+                Action::Page(PageAction::ToggleCollapsible).into(),
+            ),
+            (
+                key_event!('b'),
+                Action::Page(PageAction::ToggleTaxobox).into(),
+            ),
+            (
+                key_event!('m'),
+                Action::Page(PageAction::ToggleTableMode).into(),
+            ),
+            (
+                key_event!('W', Modifier::SHIFT),
+                Action::Page(PageAction::ExternalLink(ExternalLinkAction::OpenSnapshot)).into(),
+            ),
+            (
+                key_event!('o'),
+                Action::Page(PageAction::ShowLinkMenu).into(),
+            ),
+            (
+                key_event!('N', Modifier::SHIFT),
+                Action::Page(PageAction::TogglePagination).into(),
+            ),
+            (key_event!(' '), Action::Page(PageAction::NextPage).into()),
+            (
+                key_event!(Key::Backspace),
+                Action::Page(PageAction::PrevPage).into(),
+            ),
+        ];
+        // the cells are only moved between in table mode, otherwise the keys scroll
+        if self.table_cell.is_some() {
+            keymap.extend([
+                (
+                    key_event!('h'),
+                    Action::Page(PageAction::SelectCellLeft).into(),
+                ),
+                (
+                    key_event!('l'),
+                    Action::Page(PageAction::SelectCellRight).into(),
+                ),
+                (
+                    key_event!('k'),
+                    Action::Page(PageAction::SelectCellAbove).into(),
+                ),
+                (
+                    key_event!('j'),
+                    Action::Page(PageAction::SelectCellBelow).into(),
+                ),
+            ]);
+        }
+        if render_stats::is_enabled() {
+            keymap.push((
+                key_event!('D', Modifier::SHIFT),
+                Action::Page(PageAction::ToggleRenderStats).into(),
+            ));
+        }
+        keymap
+    }
+
+    fn is_typing(&self) -> bool {
+        self.contents_filter.is_some()
+            || self.note_input.is_some()
+            || matches!(
+                self.find,
+                Some(PageFind {
+                    is_typing: true,
+                    ..
+                })
+            )
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        match action {
+            Action::Page(page_action) => match page_action {
+                PageAction::SwitchRenderer(renderer) => self.switch_renderer(renderer),
+
+                PageAction::SelectFirstLink => self.select_first(),
+                PageAction::SelectLastLink => self.select_last(),
+
+                PageAction::SelectTopLink | PageAction::SelectBottomLink => todo!(),
+
+                PageAction::SelectPrevLink => self.select_prev(),
+                PageAction::SelectNextLink => self.select_next(),
+
+                PageAction::ScrollLeft => self.scroll_left(),
+                PageAction::ScrollRight => self.scroll_right(),
+                PageAction::ZoomIn => self.zoom_by(-1),
+                PageAction::ZoomOut => self.zoom_by(1),
+                PageAction::ResetZoom => self.set_zoom(MAX_ZOOM),
+                PageAction::ToggleSection => self.toggle_section(),
+                PageAction::ToggleAllSections => self.toggle_all_sections(),
+                PageAction::ToggleCollapsible => self.toggle_collapsible(),
+                PageAction::ToggleTaxobox => self.toggle_taxobox(),
+                PageAction::JumpToReference(number) => self.jump_to_reference(number),
+                PageAction::QueueSelectedLink => return self.queue_selected_link(),
+                PageAction::PeekSelectedLink => return self.peek_selected_link(),
+                PageAction::OutlineSection => self.outline_section(),
+                PageAction::CloseOutline => self.outline = None,
+                PageAction::AlignSelection(position) => self.align_selection(position),
+                PageAction::YankSelectedLink => self.yank_selected_link(),
+
+                PageAction::ToggleContents => self.is_contents = !self.is_contents,
+                PageAction::FilterContents => self.contents_filter = Some(Input::default()),
+                PageAction::JumpToContentsMatch => self.jump_to_contents_match(),
+                PageAction::CloseContentsFilter => self.contents_filter = None,
+                PageAction::OpenContentsMatchSection => return self.open_contents_match_section(),
+
+                PageAction::FindInPage => self.find_in_page(),
+                PageAction::SubmitFind => self.submit_find(),
+                PageAction::CloseFind => self.find = None,
+                PageAction::ToggleFindScope => self.toggle_find_scope(),
+                PageAction::NextMatch => self.jump_to_match(true),
+                PageAction::PrevMatch => self.jump_to_match(false),
+
+                PageAction::ToggleSpeech => self.toggle_speech(),
+                PageAction::StopSpeech => self.stop_speech(),
+                PageAction::SpeakNextSection => self.speak_next_section(),
+                PageAction::SpeechProgress(session, n) if self.is_speaking(session) => {
+                    self.speech_progress(n)
+                }
+                PageAction::SpeechFinished(session) if self.is_speaking(session) => {
+                    self.stop_speech()
+                }
+                // reported by a speaker that was stopped since
+                PageAction::SpeechProgress(..) | PageAction::SpeechFinished(_) => {}
+
+                PageAction::ToggleHighlightMode => self.toggle_highlight_mode(),
+                PageAction::SelectNextPassage => self.select_passage(true),
+                PageAction::SelectPrevPassage => self.select_passage(false),
+                PageAction::ExtendPassage => self.extend_passage(),
+                PageAction::EditNote => {
+                    if self.highlight_selection.is_some() {
+                        self.note_input = Some(Input::default());
+                    }
+                }
+                PageAction::SaveAnnotation => self.save_annotation(),
+                PageAction::YankPassage => self.yank_passage(),
+                PageAction::ToggleBookmark => {
+                    let removed = bookmarks().remove(&self.page.title, &self.page.language);
+                    match removed {
+                        Some(bookmark) => {
+                            info!("removed the bookmark of '{}'", self.page.title);
+                            record(Undoable::Bookmark(bookmark));
+                        }
+                        None => {
+                            bookmarks().toggle(&self.page);
+                            info!("bookmarked '{}'", self.page.title);
+                        }
+                    }
+                }
+                PageAction::SetScrollMark => self.set_scroll_mark(),
+                PageAction::JumpToScrollMark => self.jump_to_scroll_mark(),
+                PageAction::DismissRedirectNotice => self.is_redirect_notice = false,
+                PageAction::ReloadLatestRevision => {
+                    return Action::ReloadPage(self.page.title.clone()).into()
+                }
+                PageAction::DismissUpdateNotice => self.newer_revision = None,
+                PageAction::OpenRedirectSource => {
+                    if let Some(ref source) = self.page.redirected_from {
+                        return Action::LoadRedirect(source.clone()).into();
+                    }
+                }
+                PageAction::CancelNote => self.note_input = None,
+
+                PageAction::ExportMarkdown => match save_markdown(&self.page) {
+                    Ok(path) => {
+                        let mut placeholders = article_placeholders(&self.page);
+                        placeholders.push(("path", path.display().to_string()));
+                        run_hook(Hook::ArticleExported, placeholders);
+                    }
+                    Err(error) => error!("unable to export the page: {:?}", error),
+                },
+                PageAction::ExportText => match save_text(&self.page) {
+                    Ok(path) => {
+                        let mut placeholders = article_placeholders(&self.page);
+                        placeholders.push(("path", path.display().to_string()));
+                        run_hook(Hook::ArticleExported, placeholders);
+                    }
+                    Err(error) => error!("unable to export the page: {:?}", error),
+                },
+                PageAction::ExportHtml => {
+                    let page = self.page.clone();
+                    let name = format!("{}.html", page.title);
+                    spawn_task(TaskKind::Export, name, async move {
+                        match save_html(&page).await {
+                            Ok(path) => {
+                                let mut placeholders = article_placeholders(&page);
+                                placeholders.push(("path", path.display().to_string()));
+                                run_hook(Hook::ArticleExported, placeholders);
+                            }
+                            Err(error) => error!("unable to export the page: {:?}", error),
+                        }
+                    });
+                }
+                PageAction::ExportEpub => {
+                    if let Err(error) =
+                        save_epub(&self.page.title, std::slice::from_ref(&self.page))
+                    {
+                        error!("unable to export the page: {:?}", error);
+                    }
+                }
+                PageAction::ExportTo(format, path) => match save_dump(&self.page, format, &path) {
+                    Ok(()) => {
+                        let mut placeholders = article_placeholders(&self.page);
+                        placeholders.push(("path", path.display().to_string()));
+                        run_hook(Hook::ArticleExported, placeholders);
+                    }
+                    Err(error) => error!("unable to export the page: {:?}", error),
+                },
+                PageAction::ShowCiteMenu => return self.show_cite_menu(),
+                PageAction::Cite(format) => self.yank_citation(format),
+                PageAction::AddToBibliography => self.add_to_bibliography(),
+
+                PageAction::ToggleTableMode => self.toggle_table_mode(),
+                PageAction::SelectCellLeft => self.move_table_cell(0, -1),
+                PageAction::SelectCellRight => self.move_table_cell(0, 1),
+                PageAction::SelectCellAbove => self.move_table_cell(-1, 0),
+                PageAction::SelectCellBelow => self.move_table_cell(1, 0),
+
+                PageAction::ExternalLink(action) => return self.update_external_link(action),
+                PageAction::Media(action) => return self.update_media(action),
+                PageAction::ToggleRenderStats => self.is_render_stats = !self.is_render_stats,
+
+                PageAction::ShowLinkMenu => return self.show_link_menu(),
+                PageAction::PreviewLink(title) => self.preview_link(title),
+                PageAction::FinishPreview(summary) => {
+                    // the preview was closed while it was loading
+                    if self.preview.is_some() {
+                        self.preview = Some(Some(summary));
+                    }
+                }
+                PageAction::ClosePreview => self.preview = None,
+
+                PageAction::TogglePagination => self.is_paginated = !self.is_paginated,
+                PageAction::NextPage if self.is_paginated => self.turn_page(true),
+                PageAction::NextPage => self.scroll_down(self.viewport.height),
+                PageAction::PrevPage if self.is_paginated => self.turn_page(false),
+                PageAction::PrevPage => self.scroll_up(self.viewport.height),
+            },
+            // a reading page is turned at once, however far the page would be scrolled
+            Action::ScrollUp(_) | Action::ScrollHalfUp
+                if self.is_paginated && config().behavior.navigation == Navigation::Scroll =>
+            {
+                self.turn_page(false)
+            }
+            Action::ScrollDown(_) | Action::ScrollHalfDown
+                if self.is_paginated && config().behavior.navigation == Navigation::Scroll =>
+            {
+                self.turn_page(true)
+            }
+            Action::ScrollUp(amount) => match config().behavior.navigation {
+                Navigation::Scroll => self.scroll_up(amount),
+                Navigation::Select => self.move_selection(false, amount),
+            },
+            Action::ScrollDown(amount) => match config().behavior.navigation {
+                Navigation::Scroll => self.scroll_down(amount),
+                Navigation::Select => self.move_selection(true, amount),
+            },
+
+            Action::ScrollHalfUp => self.scroll_up(self.viewport.height / 2),
+            Action::ScrollHalfDown => self.scroll_down(self.viewport.height / 2),
+
+            Action::ScrollToTop => self.viewport.y = 0,
+            Action::ScrollToBottom => {
+                self.viewport.y = self
+                    .rendered_page()
+                    .map(|doc| doc.lines.len() as u16)
+                    .unwrap_or(self.viewport.y)
+            }
+
+            // the page adapts to the new size when it's rendered the next time
+            Action::Resize(..) => self.clamp_viewport(),
+            _ => return ActionResult::Ignored,
+        }
+        ActionResult::consumed()
+    }
+
+    fn render(&mut self, f: &mut Frame, area: Rect) {
+        let layout = ResponsiveLayout::new(area);
+        self.is_narrow = layout.is_narrow();
+
+        let area = if self.shows_contents() {
+            let chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([
+                    Constraint::Percentage(100 - self.contents_width),
+                    Constraint::Percentage(self.contents_width),
+                ])
+                .split(area);
+            self.render_contents(f, chunks[1]);
+            chunks[0]
+        } else {
+            area
+        };
+
+        let area = padded_rect(area, 1, 1);
+        let area = if self.newer_revision.is_some() {
+            self.render_update_notice(f, area);
+            Rect {
+                y: area.y + 1,
+                height: area.height.saturating_sub(1),
+                ..area
+            }
+        } else {
+            area
+        };
+        let area = if self.is_redirect_notice {
+            self.render_redirect_notice(f, area);
+            Rect {
+                y: area.y + 1,
+                height: area.height.saturating_sub(1),
+                ..area
+            }
+        } else {
+            area
+        };
+        let show_scrollbar = SCROLLBAR && layout.shows_scrollbar();
+        let page_area = if show_scrollbar {
+            area.inner(&Margin {
+                vertical: 0,
                 horizontal: 2, // for the scrollbar
             })
         } else {
             area
         };
+        // the last line shows which reading page is shown
+        let page_area = if self.is_paginated {
+            Rect {
+                height: page_area.height.saturating_sub(1),
+                ..page_area
+            }
+        } else {
+            page_area
+        };
 
-        self.viewport.width = page_area.width;
-        self.viewport.height = page_area.height;
+        // zooming in narrows the text, it stays in the middle of the page
+        self.page_width = page_area.width;
+        let text_width = zoomed_width(page_area.width, self.zoom);
+        let page_area = Rect {
+            x: page_area.x + (page_area.width - text_width) / 2,
+            width: text_width,
+            ..page_area
+        };
 
-        let rendered_page = match self.render_cache.get(&page_area.width) {
-            Some(rendered_page) => rendered_page,
-            None => {
-                let rendered_page = self.render_page(page_area.width);
-                info!("rebuilding cache for '{}'", page_area.width);
-                self.render_cache.insert(page_area.width, rendered_page);
-                self.render_cache.get(&page_area.width).unwrap()
-            }
+        // the text continues at the top of the next column, so the viewport is as high as all
+        // columns together and scrolling half a page moves the text by one column
+        let (columns, column_width) = self.column_layout(page_area.width);
+        self.viewport.height = page_area.height * columns;
+        self.settle_width(column_width, Instant::now());
+
+        let key = self.render_key(self.viewport.width);
+        self.ensure_rendered();
+        self.update_highlights();
+        self.update_find_matches();
+        if self.is_paginated {
+            // the viewport may have been moved anywhere, like to the header of a section
+            self.snap_to_page();
+            let (page, pages) = self.reading_page();
+            f.render_widget(
+                Paragraph::new(
+                    Span::raw(tr_args(
+                        "page.reading_page",
+                        &[
+                            ("page", (page + 1).to_string()),
+                            ("pages", pages.to_string()),
+                        ],
+                    ))
+                    .dark_gray(),
+                )
+                .alignment(Alignment::Center),
+                Rect {
+                    y: page_area.bottom(),
+                    height: area.bottom().saturating_sub(page_area.bottom()),
+                    ..page_area
+                },
+            );
+        }
+        let rendered_page = self.render_cache.peek(&key).unwrap();
+
+        let spoken_sentence = self.spoken_sentence.and_then(|n| self.sentences.get(n));
+        let selected_passage = self.highlight_selection.as_ref().map(|selection| {
+            (
+                self.passages[selection.start].start,
+                self.passages[selection.end - 1].end,
+            )
+        });
+        let is_in = |(start, end): (WordPosition, WordPosition), position: WordPosition| {
+            start <= position && position <= end
+        };
+        let table_cell = self.table_cell_position();
+        let selected_link = rendered_page.link(self.selected);
+        let (matches, current_match) = match self.find {
+            Some(ref find) => (find.matches.as_slice(), find.current),
+            None => (&[][..], None),
         };
 
         let lines: Vec<Line> = rendered_page
             .lines
             .iter()
+            .enumerate()
             .skip(self.viewport.top() as usize)
-            .take(self.viewport.bottom() as usize)
-            .map(|line| {
+            .take(self.viewport.height as usize)
+            .map(|(line_idx, line)| {
                 let mut spans: Vec<Span> = Vec::new();
+                let mut x = 0;
                 line.iter()
-                    .map(|word| {
+                    .enumerate()
+                    .map(|(word_idx, word)| {
                         let mut span = Span::styled(
                             format!(
                                 "{}{}",
@@ -350,11 +3262,63 @@ impl Component for PageComponent {
                             word.style,
                         );
 
-                        if let Some(node) = word.node(&self.page.content) {
-                            let index = node.index();
-                            if self.selected.0 <= index && index <= self.selected.1 {
+                        if spoken_sentence
+                            .map(|sentence| sentence.contains((line_idx, word_idx)))
+                            .unwrap_or(false)
+                        {
+                            span.patch_style(Style::new().add_modifier(Modifier::REVERSED))
+                        }
+
+                        let position = (line_idx, word_idx);
+                        if self
+                            .highlights
+                            .iter()
+                            .any(|highlight| is_in(*highlight, position))
+                        {
+                            span.patch_style(Style::new().black().on_yellow())
+                        }
+                        if selected_passage
+                            .map(|selected| is_in(selected, position))
+                            .unwrap_or(false)
+                        {
+                            span.patch_style(Style::new().white().on_blue())
+                        }
+                        match matches.iter().position(|m| is_in(*m, position)) {
+                            Some(n) if Some(n) == current_match => {
+                                span.patch_style(Style::new().black().on_light_red())
+                            }
+                            Some(_) => span.patch_style(Style::new().black().on_cyan()),
+                            None => {}
+                        }
+
+                        if table_cell
+                            .as_ref()
+                            .map(|(line, columns)| *line == line_idx && columns.contains(&x))
+                            .unwrap_or(false)
+                        {
+                            span.patch_style(Style::new().add_modifier(Modifier::REVERSED))
+                        }
+                        x += (word.width + word.whitespace_width) as u16;
+
+                        let link_words = selected_link
+                            .and_then(|link| link.words_on(line_idx))
+                            .filter(|words| words.contains(&word_idx));
+                        match link_words {
+                            Some(words) if word_idx + 1 < words.end => {
                                 span.patch_style(Style::new().add_modifier(Modifier::UNDERLINED))
                             }
+                            // the whitespace after the last word of the link on the line is not
+                            // underlined
+                            Some(_) => {
+                                let style = span.style;
+                                spans.push(Span::styled(
+                                    word.content.clone(),
+                                    style.add_modifier(Modifier::UNDERLINED),
+                                ));
+                                span =
+                                    Span::styled(" ".repeat(word.whitespace_width as usize), style);
+                            }
+                            None => {}
                         }
 
                         spans.push(span);
@@ -367,19 +3331,588 @@ impl Component for PageComponent {
             })
             .collect();
 
-        if SCROLLBAR {
-            let scrollbar = Scrollbar::default()
-                .begin_symbol(None)
-                .end_symbol(None)
-                .track_symbol(Some(" "))
-                .track_style(Style::new().black().on_black())
-                .thumb_style(Style::new().blue())
-                .orientation(ScrollbarOrientation::VerticalRight);
-            let mut scrollbar_state = ScrollbarState::new(rendered_page.lines.len())
-                .position(self.viewport.top() as usize);
-            f.render_stateful_widget(scrollbar, area, &mut scrollbar_state);
+        if show_scrollbar {
+            let top = self.viewport.top() as usize;
+            let section_starts: Vec<usize> = rendered_page
+                .sections
+                .iter()
+                .map(|(_, lines)| lines.start)
+                .collect();
+            let current_section = rendered_page
+                .sections
+                .iter()
+                .rev()
+                .find(|(_, lines)| lines.contains(&top))
+                .map(|(_, lines)| lines.clone());
+            let scroll_mark = self
+                .scroll_mark
+                .and_then(|anchor| anchor_line(&rendered_page.lines, anchor));
+            f.render_widget(
+                SectionScrollbar::new(
+                    rendered_page.lines.len(),
+                    top..self.viewport.bottom() as usize,
+                )
+                .section_starts(&section_starts)
+                .current_section(current_section)
+                .mark(scroll_mark),
+                area,
+            );
+        }
+
+        // the width may have changed since the page was last scrolled horizontally
+        self.viewport.x = self.viewport.x.min(self.max_scroll_x());
+        let mut lines = lines.into_iter();
+        for column in 0..columns {
+            let column_area = Rect {
+                x: page_area.x + column * (column_width + COLUMN_GAP),
+                width: column_width,
+                ..page_area
+            };
+            if column > 0 {
+                f.render_widget(
+                    Block::default()
+                        .borders(Borders::LEFT)
+                        .border_style(Style::new().dark_gray()),
+                    Rect {
+                        x: column_area.x - COLUMN_GAP / 2 - 1,
+                        width: 1,
+                        ..page_area
+                    },
+                );
+            }
+            let column_lines: Vec<Line> = lines.by_ref().take(page_area.height as usize).collect();
+            f.render_widget(
+                Paragraph::new(column_lines).scroll((0, self.viewport.x)),
+                column_area,
+            );
+        }
+        if self.table_cell.is_some() {
+            self.render_table_cell(f, page_area);
+        } else {
+            self.render_abbreviation(f, page_area);
+        }
+        self.render_find(f, page_area);
+        self.render_note_input(f, page_area);
+        if self.is_render_stats {
+            render_stats::render_overlay(
+                f,
+                page_area,
+                &self.page,
+                &self.render_stats,
+                &self.render_cache,
+            );
+        }
+        if let Some(ref mut dialog) = self.external_link {
+            dialog.render(f, page_area);
+        }
+        if let Some(ref mut dialog) = self.media {
+            dialog.render(f, page_area);
+        }
+        if let Some(ref preview) = self.preview {
+            render_summary_popup(f, page_area, preview.as_ref());
+        }
+        self.render_outline(f, page_area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyEvent};
+    use ratatui::{backend::TestBackend, Terminal};
+    use wiki_api::{languages::Language, page::Page};
+
+    use super::{table_rows, PageComponent, TableCell, MAX_ZOOM, MIN_ZOOM, RESIZE_DEBOUNCE};
+    use crate::{
+        action::{Action, ActionResult, PageAction},
+        bench::{parse_html, transform_document},
+        components::Component,
+        config::{config, Columns, RendererConfig},
+        renderer::default_renderer::render_document_with,
+    };
+
+    fn long_page() -> PageComponent {
+        let paragraphs: String = (0..100)
+            .map(|n| {
+                format!(
+                    "<p>Paragraph {} is long enough to wrap at most widths.</p>",
+                    n
+                )
+            })
+            .collect();
+        page_of("Resizing", &paragraphs)
+    }
+
+    fn page_of(title: &str, html: &str) -> PageComponent {
+        let html = format!(r#"<section data-mw-section-id="0">{}</section>"#, html);
+        page_with_sections(title, &html)
+    }
+
+    /// Returns the page of the html, which has to bring its own sections
+    fn page_with_sections(title: &str, html: &str) -> PageComponent {
+        PageComponent::new(Page {
+            title: title.to_string(),
+            pageid: 0,
+            content: transform_document(parse_html(html)),
+            language: Language::default(),
+            language_links: None,
+            sections: None,
+            revision_id: None,
+            is_disambiguation: false,
+            quality: None,
+            parse_time: None,
+            redirected_from: None,
+        })
+    }
+
+    fn draw(terminal: &mut Terminal<TestBackend>, page: &mut PageComponent, size: (u16, u16)) {
+        terminal.backend_mut().resize(size.0, size.1);
+        terminal.draw(|f| page.render(f, f.size())).unwrap();
+    }
+
+    /// Lets the pending width of the page settle, as if the debounce ran out
+    fn settle(page: &mut PageComponent) {
+        if let Some((width, since)) = page.pending_width {
+            page.settle_width(width, since + RESIZE_DEBOUNCE);
+        }
+    }
+
+    /// Returns the index of the first node shown at the top of the viewport
+    fn top_node(page: &PageComponent) -> usize {
+        page.rendered_page()
+            .unwrap()
+            .lines
+            .iter()
+            .skip(page.viewport.y as usize)
+            .flatten()
+            .map(|word| word.index)
+            .find(|index| *index != usize::MAX)
+            .unwrap()
+    }
+
+    #[test]
+    fn resize_storm() {
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        let mut page = long_page();
+        draw(&mut terminal, &mut page, (80, 24));
+        let width = page.viewport.width;
+        page.update(Action::ScrollDown(40));
+
+        // the size changes every frame, so none of the widths in between is rendered
+        for n in 0..200 {
+            let size = (20 + n % 100, 5 + n % 40);
+            draw(&mut terminal, &mut page, size);
+            page.update(Action::Resize(size.0, size.1));
+        }
+        assert_eq!(page.viewport.width, width);
+        assert_eq!(page.render_cache.len(), 1);
+
+        let top = top_node(&page);
+        draw(&mut terminal, &mut page, (40, 24));
+        settle(&mut page);
+        draw(&mut terminal, &mut page, (40, 24));
+        assert!(page.viewport.width < width);
+        assert_eq!(page.render_cache.len(), 2);
+        assert_eq!(top_node(&page), top);
+
+        // going back to a cached width doesn't have to wait
+        draw(&mut terminal, &mut page, (80, 24));
+        assert_eq!(page.viewport.width, width);
+    }
+
+    #[test]
+    fn resize_keeps_position_in_paragraph() {
+        let words: Vec<String> = (0..2000).map(|n| format!("word{}", n)).collect();
+        let mut page = page_of("Long paragraph", &format!("<p>{}</p>", words.join(" ")));
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        draw(&mut terminal, &mut page, (80, 24));
+        page.update(Action::ScrollDown(100));
+
+        let first_word = |page: &PageComponent| {
+            page.rendered_page().unwrap().lines[page.viewport.y as usize]
+                .iter()
+                .find(|word| word.index != usize::MAX)
+                .unwrap()
+                .content
+                .clone()
+        };
+        let top = first_word(&page);
+        for width in [40, 120, 80] {
+            draw(&mut terminal, &mut page, (width, 24));
+            settle(&mut page);
+            draw(&mut terminal, &mut page, (width, 24));
+
+            let line = &page.rendered_page().unwrap().lines[page.viewport.y as usize];
+            assert!(
+                line.iter().any(|word| word.content == top),
+                "'{}' isn't at the top at the width {}",
+                top,
+                width
+            );
+        }
+    }
+
+    #[test]
+    fn resize_clamps_viewport() {
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        let mut page = long_page();
+        draw(&mut terminal, &mut page, (80, 24));
+
+        page.update(Action::ScrollToBottom);
+        page.update(Action::Resize(80, 24));
+        let lines = page.rendered_page().unwrap().lines.len();
+        assert!((page.viewport.y as usize) < lines);
+        draw(&mut terminal, &mut page, (80, 24));
+    }
+
+    #[test]
+    fn pagination() {
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        let mut page = long_page();
+        page.update(Action::Page(PageAction::TogglePagination));
+        draw(&mut terminal, &mut page, (80, 24));
+        let height = page.viewport.height;
+        let (_, pages) = page.reading_page();
+        assert!(pages > 2);
+
+        page.update(Action::Page(PageAction::NextPage));
+        assert_eq!(page.viewport.y, height);
+        assert_eq!(page.reading_page().0, 1);
+        page.update(Action::Page(PageAction::PrevPage));
+        assert_eq!(page.viewport.y, 0);
+
+        // the viewport moves to the start of the reading page with the bottom of the page
+        page.update(Action::ScrollToBottom);
+        draw(&mut terminal, &mut page, (80, 24));
+        assert_eq!(page.reading_page(), (pages - 1, pages));
+        assert_eq!(page.viewport.y % height, 0);
+        page.update(Action::Page(PageAction::NextPage));
+        assert_eq!(page.reading_page().0, pages - 1);
+    }
+
+    #[test]
+    fn columns() {
+        let mut terminal = Terminal::new(TestBackend::new(200, 24)).unwrap();
+        let mut single = long_page();
+        draw(&mut terminal, &mut single, (200, 24));
+        let mut page = long_page();
+        page.columns = Columns::Two;
+        draw(&mut terminal, &mut page, (200, 24));
+        let height = single.viewport.height as usize;
+        assert_eq!(page.viewport.height as usize, 2 * height);
+        assert!(page.viewport.width < single.viewport.width / 2);
+
+        // the text continues at the top of the right column
+        let (row, text) = page.rendered_page().unwrap().lines[height..2 * height]
+            .iter()
+            .map(|line| {
+                line.iter()
+                    .map(|word| {
+                        format!(
+                            "{}{}",
+                            word.content,
+                            " ".repeat(word.whitespace_width as usize)
+                        )
+                    })
+                    .collect::<String>()
+            })
+            .enumerate()
+            .find(|(_, text)| !text.trim().is_empty())
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        // the left column ends before this, no matter the padding and the scrollbar
+        let shown: String = (page.viewport.width + 4..200)
+            .map(|x| buffer.get(x, 1 + row as u16).symbol.clone())
+            .collect();
+        assert!(shown.contains(text.trim()));
+
+        assert_eq!(page.column_count(80), 1);
+    }
+
+    #[test]
+    fn section_renders() {
+        let sections: String = (0..=3)
+            .map(|n| {
+                format!(
+                    r#"<section data-mw-section-id="{n}"><h2 id="S{n}">Section {n}</h2><p>Text of section {n}.</p></section>"#,
+                    n = n
+                )
+            })
+            .collect();
+        let mut page = page_with_sections("Sections", &sections);
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        draw(&mut terminal, &mut page, (80, 24));
+        let renders = page.section_cache.len();
+        assert!(renders > 1);
+
+        // collapsing a section renders only that section again
+        let section = *super::top_level_sections(&page.page).iter().nth(1).unwrap();
+        page.collapsed.insert(section);
+        draw(&mut terminal, &mut page, (80, 24));
+        assert_eq!(page.section_cache.len(), renders + 1);
+
+        let options = RendererConfig {
+            taxoboxes: page.is_taxobox,
+            ..config().renderer.clone()
+        };
+        let full = render_document_with(
+            &page.page.content,
+            page.viewport.width,
+            &options,
+            &page.collapsed,
+            &page.language_hints,
+        );
+        assert_eq!(
+            format!("{:?}", page.rendered_page().unwrap().lines),
+            format!("{:?}", full.lines)
+        );
+    }
+
+    #[test]
+    fn zoom() {
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        let mut page = long_page();
+        draw(&mut terminal, &mut page, (80, 24));
+        let width = page.viewport.width;
+
+        // the zoom renders right away instead of waiting like a resize
+        page.update(Action::Page(PageAction::ZoomIn));
+        assert!(page.viewport.width < width);
+        assert!(page.rendered_page().is_some());
+        draw(&mut terminal, &mut page, (80, 24));
+        assert!(page.viewport.width < width);
+
+        for _ in 0..10 {
+            page.update(Action::Page(PageAction::ZoomIn));
+        }
+        assert_eq!(page.zoom, MIN_ZOOM);
+        page.update(Action::Page(PageAction::ResetZoom));
+        assert_eq!(page.zoom, MAX_ZOOM);
+        assert_eq!(page.viewport.width, width);
+    }
+
+    #[test]
+    fn link_menu() {
+        let mut page = page_of(
+            "Languages",
+            r#"<p>Written in <a rel="mw:WikiLink" href="./Go_(language)" title="Go (language)">Go</a>.</p>"#,
+        );
+        assert!(!page.show_link_menu().is_consumed());
+
+        page.update(Action::Page(PageAction::SelectFirstLink));
+        match page.show_link_menu() {
+            ActionResult::Consumed(packet) => {
+                let packet = format!("{:?}", packet);
+                assert!(packet.starts_with("OpenContextMenu"));
+                assert!(packet.contains(r#"LoadPageInBackground("Go (language)")"#));
+            }
+            ActionResult::Ignored => panic!("no menu for the selected link"),
+        }
+    }
+
+    #[test]
+    fn media_links() {
+        let mut page = page_of(
+            "Fox",
+            concat!(
+                r#"<p><a rel="mw:MediaLink" href="//upload.wikimedia.org/a/a0/Fox_call.ogg" title="Fox call.ogg">Call</a> "#,
+                r#"and <a rel="mw:WikiLink" href="./File:Fox.jpg" title="File:Fox.jpg">picture</a>.</p>"#
+            ),
+        );
+        page.update(Action::Page(PageAction::SelectFirstLink));
+        match page.open_selected() {
+            ActionResult::Consumed(packet) => assert_eq!(
+                format!("{:?}", packet),
+                r#"Page(Media(Show("File:Fox call.ogg")))"#
+            ),
+            ActionResult::Ignored => panic!("the media link can't be opened"),
+        }
+
+        page.update(Action::Page(PageAction::SelectNextLink));
+        match page.open_selected() {
+            ActionResult::Consumed(packet) => assert_eq!(
+                format!("{:?}", packet),
+                r#"Page(Media(Show("File:Fox.jpg")))"#
+            ),
+            ActionResult::Ignored => panic!("the file link can't be opened"),
+        }
+    }
+
+    #[test]
+    fn table_mode() {
+        let header: String = (0..8)
+            .map(|n| format!("<th>Column number {}</th>", n))
+            .collect();
+        let row: String = (0..8).map(|n| format!("<td>Cell {}</td>", n)).collect();
+        let mut terminal = Terminal::new(TestBackend::new(40, 24)).unwrap();
+        let mut page = page_of(
+            "Table",
+            &format!(
+                "<p>Before</p><table><tr>{}</tr><tr>{}</tr><tr><td>Short row</td></tr></table>",
+                header, row
+            ),
+        );
+        draw(&mut terminal, &mut page, (40, 24));
+        let table = page.rendered_page().unwrap().tables[0].index;
+        let has_cell_keys =
+            |page: &PageComponent| format!("{:?}", page.keymap()).contains("SelectCellBelow");
+        assert!(!has_cell_keys(&page));
+
+        page.update(Action::Page(PageAction::ToggleTableMode));
+        assert!(has_cell_keys(&page));
+        for _ in 0..10 {
+            page.update(Action::Page(PageAction::SelectCellRight));
+        }
+        page.update(Action::Page(PageAction::SelectCellBelow));
+        assert_eq!(
+            page.table_cell,
+            Some(TableCell {
+                table,
+                row: 1,
+                column: 7
+            })
+        );
+        // the table is wider than the page, it's scrolled to show the cell
+        assert!(page.viewport.x > 0);
+
+        page.update(Action::Page(PageAction::SelectCellBelow));
+        assert_eq!(
+            page.table_cell.map(|cell| (cell.row, cell.column)),
+            Some((2, 0))
+        );
+        assert_eq!(page.viewport.x, 0);
+        draw(&mut terminal, &mut page, (40, 24));
+
+        page.update(Action::Page(PageAction::ToggleTableMode));
+        assert_eq!(page.table_cell, None);
+    }
+
+    #[test]
+    fn long_tables() {
+        let rows: String = (0..60)
+            .map(|n| format!("<tr><td>Row {}</td></tr>", n))
+            .collect();
+        let mut terminal = Terminal::new(TestBackend::new(40, 24)).unwrap();
+        let mut page = page_of("Table", &format!("<table>{}</table>", rows));
+        draw(&mut terminal, &mut page, (40, 24));
+        let table = page.rendered_page().unwrap().tables[0].index;
+        let last_row = page
+            .page
+            .content
+            .nth(table)
+            .and_then(|table| table_rows(table).last())
+            .unwrap()
+            .index();
+
+        // only the first rows are rendered until the table is expanded
+        assert_eq!(page.rendered_page().unwrap().tables[0].rows.len(), 50);
+        assert!(page.is_hidden(last_row));
+
+        let summary = page
+            .rendered_page()
+            .unwrap()
+            .lines
+            .iter()
+            .position(|line| line.iter().any(|word| word.index == table))
+            .unwrap();
+        page.viewport.y = summary as u16;
+        page.update(Action::Page(PageAction::ToggleCollapsible));
+        draw(&mut terminal, &mut page, (40, 24));
+        assert_eq!(page.rendered_page().unwrap().tables[0].rows.len(), 60);
+        assert!(!page.is_hidden(last_row));
+    }
+
+    #[test]
+    fn stale_speech_progress() {
+        let mut page = long_page();
+        page.spoken_sentence = Some(1);
+
+        // the speaker of these sessions was stopped, its progress is ignored
+        page.update(Action::Page(PageAction::SpeechProgress(5, 2)));
+        assert_eq!(page.spoken_sentence, Some(1));
+        page.update(Action::Page(PageAction::SpeechFinished(5)));
+        assert_eq!(page.spoken_sentence, Some(1));
+    }
+
+    #[test]
+    fn reload() {
+        let html = |word: &str| {
+            format!(
+                r#"<p>A {} page about <a rel="mw:WikiLink" href="./Go_(language)" title="Go (language)">Go</a> and <a rel="mw:WikiLink" href="./Rust" title="Rust">Rust</a>.</p>"#,
+                word
+            )
+        };
+        let mut page = page_of("Languages", &html("short"));
+        let mut terminal = Terminal::new(TestBackend::new(40, 24)).unwrap();
+        draw(&mut terminal, &mut page, (40, 24));
+
+        page.update(Action::Page(PageAction::SelectFirstLink));
+        page.update(Action::Page(PageAction::SelectNextLink));
+        assert_eq!(page.selected_link_title().as_deref(), Some("Rust"));
+
+        page.set_newer_revision(2);
+        assert_eq!(page.newer_revision, Some(2));
+        let packet = match page.update(Action::Page(PageAction::ReloadLatestRevision)) {
+            ActionResult::Consumed(packet) => format!("{:?}", packet),
+            ActionResult::Ignored => panic!("the page wasn't reloaded"),
+        };
+        assert_eq!(packet, r#"ReloadPage("Languages")"#);
+
+        // the new revision moves the link to other nodes, it's still selected
+        let edited = page_of("Languages", &html("<b>much</b> longer"));
+        page.reload(edited.page.clone());
+        assert_eq!(page.newer_revision, None);
+        assert_eq!(page.selected_link_title().as_deref(), Some("Rust"));
+        draw(&mut terminal, &mut page, (40, 24));
+    }
+
+    #[test]
+    fn find_in_section() {
+        let mut page = page_of(
+            "Fox",
+            concat!(
+                "<p>The fox is a mammal.</p>",
+                r#"<section data-mw-section-id="1"><h2 id="Habitat">Habitat</h2>"#,
+                "<p>The fox lives in forests. Foxes hunt at night.</p></section>",
+                r#"<section data-mw-section-id="2"><h2 id="Diet">Diet</h2>"#,
+                "<p>A fox eats mice.</p></section>",
+            ),
+        );
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        draw(&mut terminal, &mut page, (80, 24));
+
+        page.update(Action::Page(PageAction::FindInPage));
+        assert!(page.is_typing());
+        for c in "FOX".chars() {
+            page.handle_key_events(KeyEvent::from(KeyCode::Char(c)));
         }
+        page.update(Action::Page(PageAction::SubmitFind));
+        assert!(!page.is_typing());
+        let find = page.find.as_ref().unwrap();
+        assert_eq!((find.matches.len(), find.current), (4, Some(0)));
+
+        // only the matches of the section at the top of the page are left
+        let habitat = page
+            .rendered_page()
+            .unwrap()
+            .sections
+            .iter()
+            .find(|(section, _)| page.section_heading(*section).as_deref() == Some("Habitat"))
+            .map(|(_, lines)| lines.start)
+            .unwrap();
+        page.viewport.y = habitat as u16;
+        page.update(Action::Page(PageAction::ToggleFindScope));
+        page.update(Action::Page(PageAction::NextMatch));
+        let find = page.find.as_ref().unwrap();
+        assert_eq!((find.matches.len(), find.current), (2, Some(0)));
+        draw(&mut terminal, &mut page, (80, 24));
+
+        page.update(Action::Page(PageAction::PrevMatch));
+        assert_eq!(page.find.as_ref().unwrap().current, Some(1));
+        page.update(Action::Page(PageAction::ToggleFindScope));
+        page.update(Action::Page(PageAction::NextMatch));
+        assert_eq!(page.find.as_ref().unwrap().matches.len(), 4);
 
-        f.render_widget(Paragraph::new(lines), page_area);
+        page.update(Action::Page(PageAction::CloseFind));
+        assert!(page.find.is_none());
     }
 }
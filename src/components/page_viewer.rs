@@ -2,32 +2,71 @@ use crossterm::event::KeyCode;
 use ratatui::{
     prelude::{Alignment, Rect},
     style::{Color, Style},
-    widgets::{Block, BorderType, Borders, Paragraph},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
 };
 use tokio::sync::mpsc::UnboundedSender;
-use wiki_api::page::Page;
+use tracing::{error, info};
+use wiki_api::{languages::Language, page::Page};
 
 use crate::{
     action::{Action, ActionResult, PageViewerAction},
+    app::resize_pane,
+    bookmarks::bookmarks,
+    bug_report::PageReport,
+    config::{config, persist_value},
+    history::history,
+    hooks::{article_placeholders, run_hook, Hook},
+    i18n::{tr, tr_args},
+    image_cache::ImagePrefetcher,
     key_event,
     terminal::Frame,
     ui::centered_rect,
+    workspaces::{Workspace, WorkspacePage},
 };
 
-use super::{page::PageComponent, Component};
+use super::{page::PageComponent, status::PageStatus, Component};
+
+/// At most this many peeks are stacked on top of a page, a deeper peek replaces the topmost one
+const MAX_PEEK_DEPTH: usize = 4;
 
 /// Can display multiple pages and supports selecting between them
 /// Responsible for fetching the pages and managing them (NOT rendering)
-#[derive(Default)]
 pub struct PageViewer {
     page: Vec<PageComponent>,
     page_n: usize,
+    /// Pages peeked at from the shown page, each one from the one below it. They are shown in an
+    /// overlay, so the shown page keeps its reading position
+    peeks: Vec<PageComponent>,
 
     is_processing: bool,
+    /// Title of a page that was requested while offline and isn't cached
+    unavailable: Option<String>,
+
+    contents_width: u16,
+    /// Downloads the images of the shown page in the background
+    images: ImagePrefetcher,
 
     action_tx: Option<UnboundedSender<Action>>,
 }
 
+impl Default for PageViewer {
+    fn default() -> Self {
+        Self {
+            page: Vec::new(),
+            page_n: 0,
+            peeks: Vec::new(),
+
+            is_processing: false,
+            unavailable: None,
+
+            contents_width: config().ui.contents_width,
+            images: ImagePrefetcher::default(),
+
+            action_tx: None,
+        }
+    }
+}
+
 impl PageViewer {
     fn current_page_mut(&mut self) -> Option<&mut PageComponent> {
         self.page.get_mut(self.page_n)
@@ -37,14 +76,288 @@ impl PageViewer {
         self.page.get(self.page_n)
     }
 
+    /// Returns what the status bar shows about the displayed page. The title of a section shown
+    /// on its own includes its heading
+    pub fn current_page_status(&self) -> Option<PageStatus> {
+        self.current_page().map(|page| PageStatus {
+            title: match page.section() {
+                Some(heading) => format!("{} § {}", page.page().title, heading),
+                None => page.page().title.clone(),
+            },
+            quality: page.page().quality,
+            page_views: page.page_views().cloned(),
+            section: page.current_heading(),
+            scroll_percent: page.scroll_percent(),
+        })
+    }
+
+    /// Returns the title, language and revision of the displayed page, which is checked for
+    /// newer revisions. Sections shown on their own aren't checked
+    pub fn current_page_revision(&self) -> Option<(String, Language, usize)> {
+        let page = self
+            .current_page()
+            .filter(|page| page.section().is_none())?;
+        let page = page.page();
+        Some((page.title.clone(), page.language.clone(), page.revision_id?))
+    }
+
+    /// Returns what a bug report contains about the displayed page
+    pub fn current_page_report(&self) -> Option<PageReport> {
+        self.current_page().map(|page| page.report())
+    }
+
+    /// Whether the note of an annotation is being written on one of the open pages
+    pub fn has_unsaved_annotation(&self) -> bool {
+        self.page.iter().any(|page| page.has_unsaved_annotation())
+    }
+
+    /// Returns the open pages as a workspace with the name, `None` when no page is open. Sections
+    /// shown on their own are saved as their whole page
+    pub fn workspace(&self, name: String) -> Option<Workspace> {
+        if self.page.is_empty() {
+            return None;
+        }
+
+        let pages = self
+            .page
+            .iter()
+            .map(|page| WorkspacePage {
+                title: page.page().title.clone(),
+                language: page.page().language.clone(),
+                anchor: page
+                    .section()
+                    .is_none()
+                    .then(|| page.top_anchor())
+                    .flatten(),
+            })
+            .collect();
+        Some(Workspace {
+            name,
+            pages,
+            active: self.page_n,
+            saved: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    /// Returns the open pages and peeks that show the whole page with the title
+    fn pages_titled<'a>(
+        &'a mut self,
+        title: &'a str,
+    ) -> impl Iterator<Item = &'a mut PageComponent> + 'a {
+        self.page
+            .iter_mut()
+            .chain(self.peeks.iter_mut())
+            .filter(move |page| page.section().is_none() && page.page().title == title)
+    }
+
+    /// Remembers in the history how far the shown page was read, before it's closed or another
+    /// page is shown. Sections shown on their own aren't the whole page
+    pub fn save_reading_position(&self) {
+        let page = match self.current_page() {
+            Some(page) if page.section().is_none() => page,
+            _ => return,
+        };
+        if let Some(position) = page.reading_position() {
+            history().set_position(&page.page().title, position);
+        }
+    }
+
+    fn push_page(&mut self, mut page: PageComponent) {
+        self.save_reading_position();
+        if let Some(current_page) = self.current_page_mut() {
+            // the page is hidden, it isn't read aloud anymore
+            current_page.stop_speech();
+            current_page.shrink_cache();
+        }
+
+        if let Some(ref action_tx) = self.action_tx {
+            if let Err(error) = page.init(action_tx.clone()) {
+                error!("unable to initialize the page: {:?}", error);
+            }
+        }
+
+        run_hook(Hook::PageOpened, article_placeholders(page.page()));
+        self.images.prefetch(page.page());
+        self.unavailable = None;
+        self.peeks.clear();
+        self.page_n = self.page.len();
+        self.page.push(page);
+    }
+
     fn display_page(&mut self, page: Page) {
+        history().record(&page.title);
+        bookmarks().mark_read(&page);
+        self.push_page(PageComponent::new(page));
+    }
+
+    /// Puts the page right below the shown page, which stays where it is
+    fn display_page_in_background(&mut self, page: Page) {
+        let mut page = PageComponent::new(page);
+        if let Some(ref action_tx) = self.action_tx {
+            if let Err(error) = page.init(action_tx.clone()) {
+                error!("unable to initialize the page: {:?}", error);
+            }
+        }
+
+        if self.page.is_empty() {
+            self.page.push(page);
+            self.page_n = 0;
+            return;
+        }
+        self.page.insert(self.page_n, page);
+        self.page_n += 1;
+    }
+
+    fn display_peek(&mut self, page: Page) {
+        let mut page = PageComponent::new(page);
+        if let Some(ref action_tx) = self.action_tx {
+            if let Err(error) = page.init(action_tx.clone()) {
+                error!("unable to initialize the page: {:?}", error);
+            }
+        }
+
+        if self.peeks.len() >= MAX_PEEK_DEPTH {
+            info!("peeks can't be nested deeper, replacing the topmost one");
+            self.peeks.pop();
+        }
+        self.peeks.push(page);
+    }
+
+    /// Opens the topmost peek like any other page, keeping where it was scrolled to
+    fn promote_peek(&mut self) {
+        let page = match self.peeks.pop() {
+            Some(page) => page,
+            None => return,
+        };
+        self.peeks.clear();
+
+        history().record(&page.page().title);
+        bookmarks().mark_read(page.page());
+        self.push_page(page);
+    }
+
+    /// Shows a single section of a page on top of the other pages, like a new tab
+    fn display_section(&mut self, page: Page, heading: String) {
+        self.push_page(PageComponent::new(page).with_section(heading));
+    }
+
+    /// Moves an already loaded page to the top, loads the page when it's not loaded
+    fn reopen_page(&mut self, title: String) -> ActionResult {
+        let index = match self
+            .page
+            .iter()
+            .position(|page| page.page().title == title && page.section().is_none())
+        {
+            Some(index) => index,
+            None => return Action::LoadPage(title).into(),
+        };
+
+        self.save_reading_position();
+        if let Some(current_page) = self.current_page_mut() {
+            // the page is hidden, it isn't read aloud anymore
+            current_page.stop_speech();
+            current_page.shrink_cache();
+        }
+
+        history().record(&title);
+        self.unavailable = None;
+        let page = self.page.remove(index);
+        bookmarks().mark_read(page.page());
+        run_hook(Hook::PageOpened, article_placeholders(page.page()));
+        self.images.prefetch(page.page());
         self.page_n = self.page.len();
-        self.page.push(PageComponent::new(page));
+        self.page.push(page);
+        ActionResult::consumed()
     }
 
     fn pop(&mut self) {
-        self.page.pop();
+        // the unavailable screen is dismissed first
+        if self.unavailable.take().is_some() {
+            return;
+        }
+        self.save_reading_position();
+        if let Some(mut page) = self.page.pop() {
+            page.stop_speech();
+        }
         self.page_n = self.page_n.saturating_sub(1);
+
+        // the images of the page that is shown again are mostly stored already
+        match self.page.get(self.page_n) {
+            Some(page) => self.images.prefetch(page.page()),
+            None => self.images.cancel(),
+        }
+    }
+
+    /// Replaces the open pages with the pages of a workspace. The shown page is put on top of the
+    /// others, so closing it shows the pages below it like before
+    fn display_workspace(&mut self, pages: Vec<(Page, Option<(usize, usize)>)>, active: usize) {
+        self.save_reading_position();
+        self.unavailable = None;
+        self.peeks.clear();
+        self.page.clear();
+        for (page, anchor) in pages {
+            let mut page = PageComponent::new(page);
+            if let Some(anchor) = anchor {
+                page.scroll_to_anchor(anchor);
+            }
+            if let Some(ref action_tx) = self.action_tx {
+                if let Err(error) = page.init(action_tx.clone()) {
+                    error!("unable to initialize the page: {:?}", error);
+                }
+            }
+            self.page.push(page);
+        }
+
+        if active < self.page.len() {
+            let page = self.page.remove(active);
+            self.page.push(page);
+        }
+        self.page_n = self.page.len().saturating_sub(1);
+
+        let page = match self.page.last() {
+            Some(page) => page.page(),
+            None => return self.images.cancel(),
+        };
+        history().record(&page.title);
+        bookmarks().mark_read(page);
+        run_hook(Hook::PageOpened, article_placeholders(page));
+        self.images.prefetch(page);
+    }
+
+    /// Draws the topmost peek over the shown page, which stays visible around it
+    fn render_peek(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let depth = self.peeks.len();
+        let contents_width = self.contents_width;
+        let peek = match self.peeks.last_mut() {
+            Some(peek) => peek,
+            None => return,
+        };
+
+        let area = centered_rect(area, 90, 85);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(Color::Yellow))
+            .title(tr_args(
+                "page.peek",
+                &[
+                    ("title", peek.page().title.clone()),
+                    ("depth", depth.to_string()),
+                ],
+            ));
+        f.render_widget(Clear, area);
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+
+        peek.contents_width = contents_width;
+        peek.render(f, inner);
+    }
+
+    fn resize_contents(&mut self, amount: i16) {
+        self.contents_width = resize_pane(self.contents_width, amount);
+        if let Err(error) = persist_value("ui", "contents_width", self.contents_width as i64) {
+            error!("unable to save the contents width: {:?}", error);
+        }
     }
 }
 
@@ -55,18 +368,51 @@ impl Component for PageViewer {
     }
 
     fn handle_key_events(&mut self, key: crossterm::event::KeyEvent) -> ActionResult {
-        if matches!(key.code, KeyCode::Esc) {
-            return Action::PageViewer(PageViewerAction::PopPage).into();
+        if let Some(peek) = self.peeks.last_mut() {
+            let result = peek.handle_key_events(key);
+            if result.is_consumed() {
+                return result;
+            }
+            return match key.code {
+                KeyCode::Esc => Action::PageViewer(PageViewerAction::PopPeek).into(),
+                KeyCode::Enter => Action::PageViewer(PageViewerAction::PromotePeek).into(),
+                _ => ActionResult::Ignored,
+            };
         }
 
-        if let Some(page) = self.current_page_mut() {
-            return page.handle_key_events(key);
+        // modes of the page, like the table mode, are left with Esc before the page is closed
+        if self.unavailable.is_none() {
+            if let Some(page) = self.current_page_mut() {
+                let result = page.handle_key_events(key);
+                if result.is_consumed() {
+                    return result;
+                }
+            }
+        }
+
+        if matches!(key.code, KeyCode::Esc) {
+            return Action::PageViewer(PageViewerAction::PopPage).into();
         }
 
         ActionResult::Ignored
     }
 
     fn keymap(&self) -> super::help::Keymap {
+        if let Some(peek) = self.peeks.last() {
+            let mut keymap = vec![
+                (
+                    key_event!(Key::Esc),
+                    Action::PageViewer(PageViewerAction::PopPeek).into(),
+                ),
+                (
+                    key_event!(Key::Enter),
+                    Action::PageViewer(PageViewerAction::PromotePeek).into(),
+                ),
+            ];
+            keymap.append(&mut peek.keymap());
+            return keymap;
+        }
+
         let mut keymap = vec![(
             key_event!(Key::Esc),
             Action::PageViewer(PageViewerAction::PopPage).into(),
@@ -79,15 +425,56 @@ impl Component for PageViewer {
         keymap
     }
 
+    fn is_typing(&self) -> bool {
+        match self.peeks.last() {
+            Some(peek) => peek.is_typing(),
+            None => self.current_page().is_some_and(|page| page.is_typing()),
+        }
+    }
+
     fn update(&mut self, action: Action) -> ActionResult {
         match action {
             Action::PageViewer(page_viewer_action) => match page_viewer_action {
                 PageViewerAction::DisplayPage(page) => self.display_page(page),
+                PageViewerAction::DisplaySection(page, heading) => {
+                    self.display_section(page, heading)
+                }
                 PageViewerAction::PopPage => self.pop(),
+                PageViewerAction::ReopenPage(title) => return self.reopen_page(title),
+                PageViewerAction::ShowUnavailable(title) => self.unavailable = Some(title),
+                PageViewerAction::DisplayPageInBackground(page) => {
+                    self.display_page_in_background(page)
+                }
+                PageViewerAction::DisplayPeek(page) => self.display_peek(page),
+                PageViewerAction::PopPeek => {
+                    self.peeks.pop();
+                }
+                PageViewerAction::PromotePeek => self.promote_peek(),
+                PageViewerAction::DisplayWorkspace(pages, active) => {
+                    self.display_workspace(pages, active)
+                }
+                PageViewerAction::UpdateAvailable(title, revision) => self
+                    .pages_titled(&title)
+                    .for_each(|page| page.set_newer_revision(revision)),
+                PageViewerAction::FinishPageViews(title, page_views) => self
+                    .pages_titled(&title)
+                    .for_each(|page| page.set_page_views(page_views.clone())),
+                PageViewerAction::FinishLanguageHints(title, titles) => self
+                    .pages_titled(&title)
+                    .for_each(|page| page.set_language_hints(titles.clone())),
+                PageViewerAction::ReloadPage(page) => {
+                    let title = page.title.clone();
+                    self.pages_titled(&title)
+                        .for_each(|shown| shown.reload(page.clone()));
+                }
             },
+            Action::ResizeContents(amount) => self.resize_contents(amount),
             Action::EnterProcessing => self.is_processing = true,
             Action::EnterNormal => self.is_processing = false,
             _ => {
+                if let Some(peek) = self.peeks.last_mut() {
+                    return peek.update(action);
+                }
                 if let Some(page) = self.current_page_mut() {
                     return page.update(action);
                 }
@@ -107,18 +494,141 @@ impl Component for PageViewer {
                 area,
             );
             f.render_widget(
-                Paragraph::new("Processing").alignment(Alignment::Center),
+                Paragraph::new(tr("page.processing")).alignment(Alignment::Center),
                 centered_rect(area, 100, 50),
             );
             return;
         }
+        if let Some(ref title) = self.unavailable {
+            f.render_widget(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(Color::Red)),
+                area,
+            );
+            f.render_widget(
+                Paragraph::new(tr_args("page.unavailable", &[("title", title.to_string())]))
+                    .alignment(Alignment::Center)
+                    .wrap(Wrap { trim: true }),
+                centered_rect(area, 100, 50),
+            );
+            return;
+        }
+        let contents_width = self.contents_width;
         if let Some(page) = self.current_page_mut() {
+            page.contents_width = contents_width;
             page.render(f, area);
+            self.render_peek(f, area);
             return;
         }
         f.render_widget(
-            Paragraph::new("No page opened").alignment(Alignment::Center),
+            Paragraph::new(tr("page.no_page")).alignment(Alignment::Center),
             centered_rect(area, 100, 50),
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use wiki_api::{languages::Language, page::Page};
+
+    use super::{PageViewer, MAX_PEEK_DEPTH};
+    use crate::{
+        action::{Action, ActionResult, PageViewerAction},
+        bench::{parse_html, transform_document},
+        components::Component,
+        key_event,
+    };
+
+    fn page(title: &str) -> Page {
+        Page {
+            title: title.to_string(),
+            pageid: 0,
+            content: transform_document(parse_html("<p>Text</p>")),
+            language: Language::default(),
+            language_links: None,
+            sections: None,
+            revision_id: None,
+            is_disambiguation: false,
+            quality: None,
+            parse_time: None,
+            redirected_from: None,
+        }
+    }
+
+    fn titles(pages: &[super::PageComponent]) -> Vec<String> {
+        pages.iter().map(|page| page.page().title.clone()).collect()
+    }
+
+    #[test]
+    fn peeks() {
+        let mut viewer = PageViewer::default();
+        viewer.update(Action::PageViewer(PageViewerAction::DisplayPage(page(
+            "Rust",
+        ))));
+        for title in ["Ownership", "Borrow checker"] {
+            viewer.update(Action::PageViewer(PageViewerAction::DisplayPeek(page(
+                title,
+            ))));
+        }
+        assert_eq!(titles(&viewer.peeks), ["Ownership", "Borrow checker"]);
+
+        // Esc goes back to the peek below, Enter opens it as a page
+        match viewer.handle_key_events(key_event!(Key::Esc)) {
+            ActionResult::Consumed(packet) => {
+                assert_eq!(format!("{:?}", packet), "PageViewer(PopPeek)")
+            }
+            ActionResult::Ignored => panic!("Esc doesn't close the peek"),
+        }
+        viewer.update(Action::PageViewer(PageViewerAction::PopPeek));
+        viewer.update(Action::PageViewer(PageViewerAction::PromotePeek));
+        assert!(viewer.peeks.is_empty());
+        assert_eq!(titles(&viewer.page), ["Rust", "Ownership"]);
+        assert_eq!(viewer.page_n, 1);
+
+        for n in 0..=MAX_PEEK_DEPTH {
+            viewer.update(Action::PageViewer(PageViewerAction::DisplayPeek(page(
+                &n.to_string(),
+            ))));
+        }
+        assert_eq!(viewer.peeks.len(), MAX_PEEK_DEPTH);
+        assert_eq!(
+            viewer.peeks.last().unwrap().page().title,
+            MAX_PEEK_DEPTH.to_string()
+        );
+    }
+
+    #[test]
+    fn workspaces() {
+        let mut viewer = PageViewer::default();
+        assert!(viewer.workspace("Empty".to_string()).is_none());
+
+        viewer.update(Action::PageViewer(PageViewerAction::DisplayWorkspace(
+            vec![
+                (page("Rust"), None),
+                (page("Ownership"), Some((3, 12))),
+                (page("Borrow checker"), None),
+            ],
+            1,
+        )));
+        // the shown page is put on top of the others
+        assert_eq!(
+            titles(&viewer.page),
+            ["Rust", "Borrow checker", "Ownership"]
+        );
+        assert_eq!(viewer.page_n, 2);
+
+        let workspace = viewer.workspace("Rust".to_string()).unwrap();
+        assert_eq!(workspace.name, "Rust");
+        assert_eq!(workspace.active, 2);
+        assert_eq!(
+            workspace
+                .pages
+                .iter()
+                .map(|page| page.title.as_str())
+                .collect::<Vec<_>>(),
+            ["Rust", "Borrow checker", "Ownership"]
+        );
+    }
+}
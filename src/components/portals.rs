@@ -0,0 +1,357 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span, Text},
+    widgets::{Block, BorderType, Borders, HighlightSpacing, List, ListItem, Paragraph},
+};
+use tokio::sync::mpsc;
+use tracing::error;
+use wiki_api::{
+    backend::{ActionBackend, PageBackend},
+    languages::Language,
+    portals::{portal_sections, PortalSection},
+};
+
+use crate::{
+    action::{Action, ActionResult, PortalsAction},
+    i18n::tr,
+    key_event,
+    terminal::Frame,
+    ui::{centered_rect, ScrollBehaviour, StatefulList},
+};
+
+use super::Component;
+
+/// Main topic portals of the English Wikipedia, as listed on its main page
+const PORTALS: [&str; 8] = [
+    "Arts",
+    "Biography",
+    "Geography",
+    "History",
+    "Mathematics",
+    "Science",
+    "Society",
+    "Technology",
+];
+
+fn portal_page(portal: &str) -> String {
+    format!("Portal:{}", portal)
+}
+
+/// Lists the topic portals of Wikipedia and the articles selected on them, as a starting point
+/// to discover articles without searching
+pub struct PortalsComponent {
+    portals: StatefulList<&'static str>,
+
+    /// The portal the sections were loaded from, `None` while the portals are listed
+    portal: Option<String>,
+    sections: StatefulList<PortalSection>,
+    selected_article: usize,
+
+    is_processing: bool,
+
+    action_tx: Option<mpsc::UnboundedSender<Action>>,
+}
+
+impl Default for PortalsComponent {
+    fn default() -> Self {
+        let mut portals = StatefulList::with_items(PORTALS.to_vec())
+            .scroll_behavior(ScrollBehaviour::StickToEnds);
+        portals.next();
+
+        Self {
+            portals,
+
+            portal: None,
+            sections: StatefulList::with_items(Vec::new())
+                .scroll_behavior(ScrollBehaviour::StickToEnds),
+            selected_article: 0,
+
+            is_processing: false,
+
+            action_tx: None,
+        }
+    }
+}
+
+impl PortalsComponent {
+    fn load(&mut self, portal: String) {
+        let tx = self.action_tx.clone().unwrap();
+        // the portal names are the ones of the English Wikipedia
+        let backend = ActionBackend::new(Language::English.wikipedia_endpoint());
+
+        self.is_processing = true;
+        tokio::spawn(async move {
+            match backend
+                .fetch_page(&portal_page(&portal), &Language::English)
+                .await
+            {
+                Ok(page) => tx.send(Action::Portals(PortalsAction::FinishLoading(
+                    portal,
+                    portal_sections(&page.content),
+                ))),
+                Err(error) => {
+                    error!("Unable to load the portal: {:?}", error);
+                    tx.send(Action::Portals(PortalsAction::AbortLoading))
+                }
+            }
+            .unwrap();
+        });
+    }
+
+    fn finish_loading(&mut self, portal: String, sections: Vec<PortalSection>) {
+        self.is_processing = false;
+        self.portal = Some(portal);
+        self.sections =
+            StatefulList::with_items(sections).scroll_behavior(ScrollBehaviour::StickToEnds);
+        self.sections.next();
+        self.selected_article = 0;
+    }
+
+    fn select_article(&mut self, offset: isize) {
+        let article_count = self
+            .sections
+            .selected()
+            .map(|section| section.articles.len())
+            .unwrap_or_default();
+        if article_count == 0 {
+            return;
+        }
+        self.selected_article =
+            (self.selected_article as isize + offset).rem_euclid(article_count as isize) as usize;
+    }
+
+    fn open_selected(&mut self) -> ActionResult {
+        if self.portal.is_none() {
+            if let Some(portal) = self.portals.selected() {
+                return Action::Portals(PortalsAction::Load(portal.to_string())).into();
+            }
+            return ActionResult::Ignored;
+        }
+
+        if let Some(article) = self
+            .sections
+            .selected()
+            .and_then(|section| section.articles.get(self.selected_article))
+        {
+            return Action::LoadPage(article.clone()).into();
+        }
+        ActionResult::Ignored
+    }
+
+    /// Opens the selected portal page itself in the page viewer
+    fn open_portal_page(&self) -> ActionResult {
+        let portal = match self.portal {
+            Some(ref portal) => Some(portal.as_str()),
+            None => self.portals.selected().copied(),
+        };
+        match portal {
+            Some(portal) => Action::LoadPage(portal_page(portal)).into(),
+            None => ActionResult::Ignored,
+        }
+    }
+
+    fn render_portals(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let items: Vec<ListItem> = self
+            .portals
+            .get_items()
+            .iter()
+            .map(|portal| ListItem::new(portal.to_string()))
+            .collect();
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(tr("portals.title")),
+            )
+            .highlight_symbol("| ")
+            .highlight_spacing(HighlightSpacing::Always)
+            .highlight_style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::ITALIC),
+            );
+        f.render_stateful_widget(list, area, self.portals.get_state_mut());
+    }
+
+    fn render_sections(&mut self, f: &mut Frame<'_>, area: Rect) {
+        if self.sections.get_items().is_empty() {
+            f.render_widget(
+                Paragraph::new(tr("portals.empty")).alignment(Alignment::Center),
+                centered_rect(area, 100, 50),
+            );
+            return;
+        }
+
+        let selected_section = self.sections.get_state_mut().selected();
+        let items: Vec<ListItem> = self
+            .sections
+            .get_items()
+            .iter()
+            .enumerate()
+            .map(|(i, section)| {
+                let heading = if section.heading.is_empty() {
+                    "Introduction"
+                } else {
+                    &section.heading
+                };
+                let mut text = Text::from(Span::raw(heading.to_string()).red());
+
+                let articles: Vec<Span> = section
+                    .articles
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(n, article)| {
+                        let span = Span::raw(article.clone());
+                        let span = if selected_section == Some(i) && n == self.selected_article {
+                            span.blue().underlined()
+                        } else {
+                            span.blue()
+                        };
+                        [span, Span::raw(" | ")]
+                    })
+                    .collect();
+                text.lines.push(Line::from(articles));
+                ListItem::new(text)
+            })
+            .collect();
+
+        let title = self.portal.as_deref().map(portal_page).unwrap_or_default();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_symbol("| ")
+            .highlight_spacing(HighlightSpacing::Always)
+            .highlight_style(Style::default().add_modifier(Modifier::ITALIC));
+        f.render_stateful_widget(list, area, self.sections.get_state_mut());
+    }
+}
+
+impl Component for PortalsComponent {
+    fn init(&mut self, action_tx: mpsc::UnboundedSender<Action>) -> anyhow::Result<()> {
+        self.action_tx = Some(action_tx);
+        Ok(())
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        if self.is_processing {
+            return ActionResult::Ignored;
+        }
+
+        match key.code {
+            KeyCode::Enter => Action::Portals(PortalsAction::OpenSelected).into(),
+            KeyCode::Left => Action::Portals(PortalsAction::SelectPrevArticle).into(),
+            KeyCode::Right => Action::Portals(PortalsAction::SelectNextArticle).into(),
+            KeyCode::Char('o') => Action::Portals(PortalsAction::OpenPortalPage).into(),
+            KeyCode::Backspace | KeyCode::Esc if self.portal.is_some() => {
+                Action::Portals(PortalsAction::ShowPortals).into()
+            }
+            _ => ActionResult::Ignored,
+        }
+    }
+
+    fn keymap(&self) -> super::help::Keymap {
+        vec![
+            (
+                key_event!(Key::Enter),
+                Action::Portals(PortalsAction::OpenSelected).into(),
+            ),
+            (
+                key_event!(Key::Left),
+                Action::Portals(PortalsAction::SelectPrevArticle).into(),
+            ),
+            (
+                key_event!(Key::Right),
+                Action::Portals(PortalsAction::SelectNextArticle).into(),
+            ),
+            (
+                key_event!('o'),
+                Action::Portals(PortalsAction::OpenPortalPage).into(),
+            ),
+            (
+                key_event!(Key::Backspace),
+                Action::Portals(PortalsAction::ShowPortals).into(),
+            ),
+        ]
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        match action {
+            Action::Portals(portals_action) => match portals_action {
+                PortalsAction::Load(portal) => self.load(portal),
+                PortalsAction::FinishLoading(portal, sections) => {
+                    self.finish_loading(portal, sections)
+                }
+                PortalsAction::AbortLoading => self.is_processing = false,
+                PortalsAction::OpenSelected => return self.open_selected(),
+                PortalsAction::OpenPortalPage => return self.open_portal_page(),
+                PortalsAction::SelectPrevArticle => self.select_article(-1),
+                PortalsAction::SelectNextArticle => self.select_article(1),
+                PortalsAction::ShowPortals => self.portal = None,
+            },
+            Action::ScrollUp(n) => {
+                for _ in 0..n {
+                    match self.portal {
+                        Some(_) => self.sections.previous(),
+                        None => self.portals.previous(),
+                    }
+                }
+                self.selected_article = 0;
+            }
+            Action::ScrollDown(n) => {
+                for _ in 0..n {
+                    match self.portal {
+                        Some(_) => self.sections.next(),
+                        None => self.portals.next(),
+                    }
+                }
+                self.selected_article = 0;
+            }
+            Action::UnselectScroll => match self.portal {
+                Some(_) => self.sections.unselect(),
+                None => self.portals.unselect(),
+            },
+            _ => return ActionResult::Ignored,
+        }
+        ActionResult::consumed()
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        if self.is_processing {
+            f.render_widget(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(Color::Yellow)),
+                area,
+            );
+            f.render_widget(
+                Paragraph::new(tr("portals.loading")).alignment(Alignment::Center),
+                centered_rect(area, 100, 50),
+            );
+            return;
+        }
+
+        let [info_area, list_area] = {
+            let rects = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(1), Constraint::Percentage(100)])
+                .split(area);
+            [rects[0], rects[1]]
+        };
+
+        let info = match self.portal {
+            Some(_) => {
+                "Left / Right: select an article | o: open the portal | Backspace: all portals"
+            }
+            None => "Browse Wikipedia by topic | o: open the portal page",
+        };
+        f.render_widget(Paragraph::new(info), info_area);
+
+        match self.portal {
+            Some(_) => self.render_sections(f, list_area),
+            None => self.render_portals(f, list_area),
+        }
+    }
+}
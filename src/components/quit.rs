@@ -0,0 +1,97 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::Rect,
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
+};
+
+use crate::{
+    action::{Action, ActionResult, ExitMode},
+    i18n::tr,
+    tasks::{tasks, TaskKind},
+    terminal::Frame,
+    ui::centered_rect,
+};
+
+use super::Component;
+
+const DIALOG_WIDTH: u16 = 60;
+const DIALOG_HEIGHT: u16 = 14;
+
+/// Asks whether to quit while background tasks are running or the note of an annotation is being
+/// written. The running tasks are listed until the dialog is closed
+pub struct QuitComponent {
+    unsaved_annotation: bool,
+}
+
+impl QuitComponent {
+    pub fn new(unsaved_annotation: bool) -> Self {
+        Self { unsaved_annotation }
+    }
+
+    fn lines(&self) -> Vec<Line<'static>> {
+        let mut lines = vec![Line::from(tr("quit.summary")), Line::default()];
+
+        let tasks = tasks();
+        for (kind, key) in [
+            (TaskKind::Download, "quit.downloads"),
+            (TaskKind::Export, "quit.exports"),
+            (TaskKind::Fetch, "quit.fetches"),
+        ] {
+            let names = tasks.names(kind);
+            if names.is_empty() {
+                continue;
+            }
+            lines.push(Line::from(vec![
+                Span::raw(format!("{}: ", tr(key))).bold(),
+                Span::raw(names.join(", ")),
+            ]));
+        }
+        if tasks.is_empty() {
+            lines.push(Line::from(tr("quit.no_tasks").dark_gray()));
+        }
+        if self.unsaved_annotation {
+            lines.push(Line::from(tr("quit.annotation").yellow()));
+        }
+
+        lines.push(Line::default());
+        lines.push(Line::from(tr("quit.keys").dark_gray()));
+        lines
+    }
+}
+
+impl Component for QuitComponent {
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        match key.code {
+            KeyCode::Char('b') | KeyCode::Enter => Action::Exit(ExitMode::WaitForTasks).into(),
+            KeyCode::Char('c') => Action::Exit(ExitMode::CancelTasks).into(),
+            KeyCode::Esc | KeyCode::Char('a') | KeyCode::Char('n') => Action::CancelQuit.into(),
+            // the dialog is modal
+            _ => ActionResult::consumed(),
+        }
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let height = DIALOG_HEIGHT.min(area.height);
+        let area = Rect {
+            y: area.y + (area.height - height) / 2,
+            height,
+            ..centered_rect(area, DIALOG_WIDTH, 100)
+        };
+
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Paragraph::new(self.lines())
+                .wrap(Wrap { trim: false })
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_type(BorderType::Rounded)
+                        .border_style(Style::default().fg(Color::Red))
+                        .title(tr("quit.title")),
+                ),
+            area,
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Borders, HighlightSpacing, List, ListItem, ListState, Paragraph},
+};
+
+use crate::{
+    action::{Action, ActionPacket, ActionResult, ReadingQueueAction},
+    history::history,
+    i18n::{tr, tr_args},
+    key_event,
+    reading_queue::reading_queue,
+    terminal::Frame,
+    ui::centered_rect,
+    undo::{record, Undoable},
+};
+
+use super::{history::progress_span, Component};
+
+/// Lists the queued pages in the order they are read in, the selected one can be moved
+#[derive(Default)]
+pub struct ReadingQueueComponent {
+    state: ListState,
+}
+
+impl ReadingQueueComponent {
+    fn select(&mut self, offset: isize) {
+        let len = reading_queue().all().len();
+        if len == 0 {
+            return self.state.select(None);
+        }
+
+        let selected = match self.state.selected() {
+            Some(selected) => (selected as isize + offset).clamp(0, len as isize - 1) as usize,
+            None => 0,
+        };
+        self.state.select(Some(selected));
+    }
+
+    fn open_selected(&mut self) -> ActionResult {
+        let page = match self.state.selected() {
+            Some(selected) => reading_queue().remove(selected),
+            None => None,
+        };
+        self.select(0);
+        match page {
+            Some(page) => ActionPacket::single(Action::SwitchLanguage(page.language))
+                .action(Action::LoadPage(page.title))
+                .into(),
+            None => ActionResult::Ignored,
+        }
+    }
+
+    fn remove_selected(&mut self) {
+        if let Some(selected) = self.state.selected() {
+            let removed = reading_queue().remove(selected);
+            if let Some(page) = removed {
+                record(Undoable::QueuedPage(selected, page));
+            }
+            self.select(0);
+        }
+    }
+
+    fn move_selected(&mut self, offset: isize) {
+        if let Some(selected) = self.state.selected() {
+            let moved = reading_queue().move_page(selected, offset);
+            if let Some(position) = moved {
+                self.state.select(Some(position));
+            }
+        }
+    }
+}
+
+impl Component for ReadingQueueComponent {
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        match key.code {
+            KeyCode::Enter => Action::ReadingQueue(ReadingQueueAction::OpenSelected).into(),
+            KeyCode::Char('d') => Action::ReadingQueue(ReadingQueueAction::RemoveSelected).into(),
+            KeyCode::Char('K') => Action::ReadingQueue(ReadingQueueAction::MoveSelected(-1)).into(),
+            KeyCode::Char('J') => Action::ReadingQueue(ReadingQueueAction::MoveSelected(1)).into(),
+            _ => ActionResult::Ignored,
+        }
+    }
+
+    fn keymap(&self) -> super::help::Keymap {
+        vec![
+            (
+                key_event!(Key::Enter),
+                Action::ReadingQueue(ReadingQueueAction::OpenSelected).into(),
+            ),
+            (
+                key_event!('d'),
+                Action::ReadingQueue(ReadingQueueAction::RemoveSelected).into(),
+            ),
+            (
+                key_event!('K', Modifier::SHIFT),
+                Action::ReadingQueue(ReadingQueueAction::MoveSelected(-1)).into(),
+            ),
+            (
+                key_event!('J', Modifier::SHIFT),
+                Action::ReadingQueue(ReadingQueueAction::MoveSelected(1)).into(),
+            ),
+        ]
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        match action {
+            Action::ReadingQueue(queue_action) => match queue_action {
+                ReadingQueueAction::OpenSelected => return self.open_selected(),
+                ReadingQueueAction::RemoveSelected => self.remove_selected(),
+                ReadingQueueAction::MoveSelected(offset) => self.move_selected(offset),
+            },
+            Action::ScrollUp(n) => self.select(-(n as isize)),
+            Action::ScrollDown(n) => self.select(n as isize),
+            Action::UnselectScroll => self.state.select(None),
+            _ => return ActionResult::Ignored,
+        }
+        ActionResult::consumed()
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let [info_area, list_area] = {
+            let rects = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(1), Constraint::Percentage(100)])
+                .split(area);
+            [rects[0], rects[1]]
+        };
+
+        let pages = reading_queue().all().to_vec();
+        f.render_widget(
+            Paragraph::new(tr_args(
+                "reading_queue.info",
+                &[("pages", pages.len().to_string())],
+            )),
+            info_area,
+        );
+
+        if pages.is_empty() {
+            f.render_widget(
+                Paragraph::new(tr("reading_queue.empty")).alignment(Alignment::Center),
+                centered_rect(list_area, 100, 50),
+            );
+            return;
+        }
+
+        let items: Vec<ListItem> = pages
+            .iter()
+            .enumerate()
+            .map(|(n, page)| {
+                let mut spans = vec![
+                    Span::raw(format!("{}. ", n + 1)).dark_gray(),
+                    Span::raw(page.title.clone()),
+                    Span::raw(format!(" ({})", page.language.name())).dark_gray(),
+                ];
+                if let Some(percent) = history().progress(&page.title) {
+                    spans.push(progress_span(percent));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(tr("reading_queue.title")),
+            )
+            .highlight_symbol("| ")
+            .highlight_spacing(HighlightSpacing::Always)
+            .highlight_style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::ITALIC),
+            );
+        f.render_stateful_widget(list, list_area, &mut self.state);
+    }
+}
@@ -0,0 +1,168 @@
+use std::{collections::BTreeMap, hash::Hash, mem::size_of, time::Duration};
+
+use ratatui::{
+    prelude::Rect,
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+};
+use wiki_api::{
+    document::{Data, Document, Raw},
+    page::Page,
+};
+
+use crate::{
+    config::config,
+    renderer::{cache::RenderCache, RenderedDocument, Word},
+    terminal::Frame,
+};
+
+const OVERLAY_WIDTH: u16 = 40;
+
+/// Returns whether the render statistics can be shown
+pub fn is_enabled() -> bool {
+    cfg!(debug_assertions) || config().ui.render_stats
+}
+
+/// How long rendering the page took, shown in the debug overlay of the page
+#[derive(Debug, Default)]
+pub struct RenderStats {
+    /// Duration of the latest render for every width the page was rendered in
+    render_times: BTreeMap<u16, Duration>,
+}
+
+impl RenderStats {
+    pub fn record_render(&mut self, width: u16, duration: Duration) {
+        self.render_times.insert(width, duration);
+    }
+}
+
+/// Rough estimate of the memory used by the nodes of the document, in bytes. Only the text of
+/// the nodes is counted, other strings like link targets are ignored
+fn document_size(document: &Document) -> usize {
+    let text: usize = document
+        .nodes
+        .iter()
+        .map(|node| match node.data {
+            Data::Text { ref contents } => contents.capacity(),
+            _ => 0,
+        })
+        .sum();
+    document.nodes.capacity() * size_of::<Raw>() + text
+}
+
+/// Rough estimate of the memory used by the rendered document, in bytes
+fn rendered_size(rendered: &RenderedDocument) -> usize {
+    rendered
+        .lines
+        .iter()
+        .map(|line| {
+            line.capacity() * size_of::<Word>()
+                + line
+                    .iter()
+                    .map(|word| word.content.capacity())
+                    .sum::<usize>()
+        })
+        .sum::<usize>()
+        + rendered.lines.capacity() * size_of::<Vec<Word>>()
+}
+
+fn format_size(bytes: usize) -> String {
+    const UNITS: [&str; 3] = ["KiB", "MiB", "GiB"];
+
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.2} ms", duration.as_secs_f64() * 1000.0)
+}
+
+fn stat(name: &str, value: String) -> Line<'static> {
+    Line::from(vec![
+        Span::raw(format!("{}: ", name)).bold(),
+        Span::raw(value),
+    ])
+}
+
+/// Draws the statistics of the page in the top right corner of the area
+pub fn render_overlay<K: Clone + Eq + Hash>(
+    f: &mut Frame<'_>,
+    area: Rect,
+    page: &Page,
+    stats: &RenderStats,
+    cache: &RenderCache<K>,
+) {
+    let cache_size: usize = cache.documents().map(rendered_size).sum();
+    let mut lines = vec![
+        stat(
+            "Parse time",
+            page.parse_time
+                .map(format_duration)
+                .unwrap_or_else(|| "unknown".to_string()),
+        ),
+        stat(
+            "Nodes",
+            format!(
+                "{} (~{})",
+                page.content.nodes.len(),
+                format_size(document_size(&page.content))
+            ),
+        ),
+        stat(
+            "Cached renders",
+            format!(
+                "{}/{} (~{})",
+                cache.len(),
+                cache.capacity(),
+                format_size(cache_size)
+            ),
+        ),
+        Line::from(Span::raw("Render time per width").bold()),
+    ];
+    lines.extend(
+        stats
+            .render_times
+            .iter()
+            .map(|(width, duration)| stat(&format!("  {}", width), format_duration(*duration))),
+    );
+
+    let width = OVERLAY_WIDTH.min(area.width);
+    let area = Rect {
+        x: area.right() - width,
+        width,
+        height: (lines.len() as u16 + 2).min(area.height),
+        ..area
+    };
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(Color::Magenta))
+                .title("Render statistics"),
+        ),
+        area,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_size;
+
+    #[test]
+    fn sizes() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(3 * 1024 * 1024), "3.0 MiB");
+    }
+}
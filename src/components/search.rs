@@ -1,27 +1,50 @@
+use std::{collections::HashMap, ops::Range, sync::Arc};
+
 use anyhow::{anyhow, Result};
 use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{
     prelude::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style, Stylize},
     text::{Line, Span, Text},
-    widgets::{Block, BorderType, Borders, HighlightSpacing, List, ListItem, Paragraph},
+    widgets::{Block, BorderType, Borders, HighlightSpacing, List, ListItem, ListState, Paragraph},
 };
 use tokio::sync::mpsc;
-use tracing::error;
+use tracing::{error, warn};
+use tui_input::{backend::crossterm::EventHandler, Input};
 use wiki_api::{
+    assessment::{fetch_qualities, Quality},
+    backend::Summary,
+    error::ApiError,
     languages::Language,
-    search::{Search as ApiSearch, SearchContinue, SearchInfo, SearchRequest, SearchResult},
+    pageviews::{fetch_page_views, PageViews},
+    search::{
+        Namespace, Search as ApiSearch, SearchContinue, SearchInfo, SearchRequest, SearchResult,
+    },
+    zim::ZimBackend,
     Endpoint,
 };
 
 use crate::{
-    action::{Action, ActionPacket, ActionResult, SearchAction},
+    action::{Action, ActionPacket, ActionResult, FailedRequest, PageViewerAction, SearchAction},
+    annotations::annotations,
+    config::config,
+    export::article_url,
+    fuzzy::closest_match,
+    i18n::tr,
     key_event,
+    local_search::{local_results, LocalResult, LocalSource},
+    sources::content_source,
     terminal::Frame,
-    ui::{centered_rect, ScrollBehaviour, StatefulList},
+    ui::{centered_rect, render_summary_popup, ScrollBehaviour, StatefulList},
+    yank_ring::{Yank, YankKind},
 };
 
-use super::Component;
+use super::{context_menu::ContextMenu, status::page_views_summary, Component};
+
+/// Below this amount of results, a correction of the query is suggested
+const FEW_RESULTS: usize = 5;
+/// At most this many titles of a ZIM archive are listed
+const ARCHIVE_RESULTS: usize = 50;
 
 #[derive(Default, Debug, PartialEq, Eq)]
 enum Mode {
@@ -35,9 +58,28 @@ pub struct SearchComponent {
     endpoint: Option<Endpoint>,
     language: Option<Language>,
 
+    /// Pages on this device matching the query, shown above the results of the wiki
+    local_results: StatefulList<LocalResult>,
     search_results: StatefulList<SearchResult>,
+    /// Namespaces that are searched. With more than one, the results are grouped by their
+    /// namespace, in this order
+    namespaces: Vec<Namespace>,
+    /// Groups of results that only show their header
+    collapsed: Vec<Namespace>,
+    /// Selection and scroll position of the rendered rows, when the results are grouped
+    rows_state: ListState,
     search_info: Option<SearchInfo>,
     continue_search: Option<SearchContinue>,
+    qualities: HashMap<String, Quality>,
+    /// How often the results were viewed lately
+    page_views: HashMap<String, PageViews>,
+    /// Corrected query, offered when the search found (almost) nothing
+    suggestion: Option<String>,
+    /// Query field docked above the results, `Some` while it's being edited
+    query_input: Option<Input>,
+    /// Summary of a result shown over the results, `Some` while the preview is open. It's
+    /// `None` while it's loading
+    preview: Option<Option<Summary>>,
 
     action_tx: Option<mpsc::UnboundedSender<Action>>,
 }
@@ -49,10 +91,20 @@ impl Default for SearchComponent {
             endpoint: None,
             language: None,
 
+            local_results: StatefulList::with_items(Vec::new())
+                .scroll_behavior(ScrollBehaviour::StickToEnds),
             search_results: StatefulList::with_items(Vec::new())
                 .scroll_behavior(ScrollBehaviour::StickToEnds),
+            namespaces: Vec::new(),
+            collapsed: Vec::new(),
+            rows_state: ListState::default(),
             search_info: None,
             continue_search: None,
+            qualities: HashMap::new(),
+            page_views: HashMap::new(),
+            suggestion: None,
+            query_input: None,
+            preview: None,
 
             action_tx: None,
         }
@@ -60,6 +112,12 @@ impl Default for SearchComponent {
 }
 
 impl SearchComponent {
+    /// Searches the Wikipedia edition in the language from now on
+    pub fn set_language(&mut self, language: Language) {
+        self.endpoint = Some(language.wikipedia_endpoint());
+        self.language = Some(language);
+    }
+
     fn build_search(&self, query: String) -> Result<SearchRequest> {
         let endpoint = self
             .endpoint
@@ -73,12 +131,30 @@ impl SearchComponent {
         Ok(ApiSearch::builder()
             .query(query)
             .endpoint(endpoint)
-            .language(language))
+            .language(language)
+            .namespaces(self.namespaces.clone()))
+    }
+
+    /// Searches the pages on this device, their results are shown before the wiki answers
+    fn search_locally(&mut self, query: &str) {
+        let results = match self.language {
+            Some(ref language) => local_results(query, language, config().behavior.local_results),
+            None => Vec::new(),
+        };
+        self.local_results =
+            StatefulList::with_items(results).scroll_behavior(ScrollBehaviour::StickToEnds);
+        if !self.local_results.get_items().is_empty() {
+            self.local_results.next();
+        }
     }
 
     fn start_search(&mut self, query: String) -> ActionResult {
+        self.search_locally(&query);
+        if let Some(zim) = content_source().zim() {
+            return self.search_archive(zim.clone(), query);
+        }
         let tx = self.action_tx.clone().unwrap();
-        let search_request = match self.build_search(query) {
+        let search_request = match self.build_search(query.clone()) {
             Ok(search_request) => search_request,
             Err(error) => {
                 error!("Unable to build the search request: {:?}", error);
@@ -93,7 +169,18 @@ impl SearchComponent {
                 Ok(search) => tx
                     .send(Action::Search(SearchAction::FinshSearch(search)))
                     .unwrap(),
-                Err(error) => error!("Unable to complete the search: {:?}", error),
+                Err(error) => {
+                    error!("Unable to complete the search: {:?}", error);
+                    if let Some(api_error) = ApiError::find(&error) {
+                        tx.send(Action::ShowApiError(
+                            FailedRequest::Search(query.clone()),
+                            api_error.clone(),
+                        ))
+                        .unwrap();
+                    }
+                    tx.send(Action::Search(SearchAction::FailSearch(query)))
+                        .unwrap();
+                }
             };
             tx.send(Action::EnterNormal).unwrap();
         });
@@ -101,63 +188,609 @@ impl SearchComponent {
         ActionResult::consumed()
     }
 
+    /// Searches the titles of the ZIM archive pages are read from, instead of the wiki
+    fn search_archive(&mut self, zim: Arc<ZimBackend>, query: String) -> ActionResult {
+        let tx = self.action_tx.clone().unwrap();
+        let language = self.language.clone().unwrap_or_default();
+        tokio::spawn(async move {
+            tx.send(Action::EnterProcessing).unwrap();
+            tx.send(Action::Search(SearchAction::ClearSearchResults))
+                .unwrap();
+            let archive_query = query.clone();
+            let result = tokio::task::spawn_blocking(move || {
+                zim.search(&archive_query, &language, ARCHIVE_RESULTS)
+            })
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|search| search);
+            match result {
+                Ok(search) => tx
+                    .send(Action::Search(SearchAction::FinshSearch(search)))
+                    .unwrap(),
+                Err(error) => {
+                    error!("Unable to search the archive: {:?}", error);
+                    tx.send(Action::Search(SearchAction::FailSearch(query)))
+                        .unwrap();
+                }
+            }
+            tx.send(Action::EnterNormal).unwrap();
+        });
+
+        ActionResult::consumed()
+    }
+
     fn finish_search(&mut self, mut search: ApiSearch) -> ActionResult {
+        // archives don't know the quality or the views of their pages
+        if !content_source().is_archive() {
+            let titles: Vec<String> = search.results.iter().map(|x| x.title.clone()).collect();
+            self.load_qualities(search.endpoint.clone(), titles.clone());
+            self.load_page_views(search.endpoint.clone(), titles);
+        }
+
         self.search_results
             .get_items_mut()
             .append(&mut search.results);
-        self.search_results.next();
+        if self.is_grouped() {
+            self.sort_into_groups();
+        }
+        if !self.local_results.is_selected() && !self.search_results.is_selected() {
+            self.search_results.next();
+        }
 
         self.continue_search = search.continue_data().take();
+
+        if self.search_results.get_items().len() < FEW_RESULTS {
+            self.suggestion = search
+                .info
+                .suggestion
+                .clone()
+                .or_else(|| local_suggestion(&search.info.query));
+        }
         self.search_info = Some(search.info);
 
         ActionResult::consumed()
     }
 
+    fn is_grouped(&self) -> bool {
+        self.namespaces.len() > 1
+    }
+
+    /// Orders the results by their group, keeping the order of the wiki inside of every group
+    /// and the selected result
+    fn sort_into_groups(&mut self) {
+        let selected = self.search_results.selected().map(|result| result.pageid);
+        let namespaces = &self.namespaces;
+        self.search_results.get_items_mut().sort_by_key(|result| {
+            namespaces
+                .iter()
+                .position(|namespace| *namespace == result.namespace)
+                .unwrap_or(namespaces.len())
+        });
+        if let Some(pageid) = selected {
+            let index = self
+                .search_results
+                .get_items()
+                .iter()
+                .position(|result| result.pageid == pageid);
+            self.search_results.get_state_mut().select(index);
+        }
+    }
+
+    /// Whether the group of the result is collapsed
+    fn is_collapsed(&self, index: usize) -> bool {
+        self.is_grouped()
+            && self
+                .search_results
+                .get_items()
+                .get(index)
+                .is_some_and(|result| self.collapsed.contains(&result.namespace))
+    }
+
+    /// Whether the result isn't shown because its group is collapsed. The first result of a
+    /// collapsed group stands for the group and is selected when the header is
+    fn is_hidden(&self, index: usize) -> bool {
+        let results = self.search_results.get_items();
+        self.is_collapsed(index)
+            && index > 0
+            && results[index - 1].namespace == results[index].namespace
+    }
+
+    /// Collapses or expands the group of the selected result
+    fn toggle_group(&mut self) -> ActionResult {
+        let namespace = match self.search_results.selected() {
+            Some(result) if self.is_grouped() => result.namespace,
+            _ => return ActionResult::Ignored,
+        };
+
+        if let Some(position) = self.collapsed.iter().position(|other| *other == namespace) {
+            self.collapsed.remove(position);
+            return ActionResult::consumed();
+        }
+        self.collapsed.push(namespace);
+        let first = self
+            .search_results
+            .get_items()
+            .iter()
+            .position(|result| result.namespace == namespace);
+        self.search_results.get_state_mut().select(first);
+        ActionResult::consumed()
+    }
+
+    /// Selects the next shown result of the wiki
+    fn next_result(&mut self) {
+        let start = self.search_results.get_state_mut().selected();
+        loop {
+            let before = self.search_results.get_state_mut().selected();
+            self.search_results.next();
+            match self.search_results.get_state_mut().selected() {
+                // only hidden results are left
+                Some(index) if self.is_hidden(index) && before == Some(index) => {
+                    self.search_results.get_state_mut().select(start);
+                    return;
+                }
+                Some(index) if self.is_hidden(index) => {}
+                _ => return,
+            }
+        }
+    }
+
+    /// Selects the previous shown result of the wiki. The first result is always shown
+    fn previous_result(&mut self) {
+        self.search_results.previous();
+        while let Some(index) = self.search_results.get_state_mut().selected() {
+            if !self.is_hidden(index) {
+                break;
+            }
+            self.search_results.previous();
+        }
+    }
+
+    /// Query of the displayed results
+    fn query(&self) -> Option<&str> {
+        self.search_info.as_ref().map(|info| info.query.as_str())
+    }
+
+    fn edit_query(&mut self) -> ActionResult {
+        let query = self.query().unwrap_or_default().to_string();
+        self.query_input = Some(Input::new(query));
+        ActionResult::consumed()
+    }
+
+    /// Searches for the edited query. When the query didn't change, the previous results are
+    /// kept as they are, including the selection and scroll position
+    fn submit_query(&mut self) -> ActionResult {
+        let query = match self.query_input.take() {
+            Some(input) => input.value().trim().to_string(),
+            None => return ActionResult::Ignored,
+        };
+
+        if query.is_empty() || Some(query.as_str()) == self.query() {
+            return ActionResult::consumed();
+        }
+        Action::Search(SearchAction::StartSearch(query)).into()
+    }
+
+    fn accept_suggestion(&mut self) -> ActionResult {
+        match self.suggestion.take() {
+            Some(suggestion) => Action::Search(SearchAction::StartSearch(suggestion)).into(),
+            None => ActionResult::Ignored,
+        }
+    }
+
+    /// Fetches the quality ratings of the results in the background
+    fn load_qualities(&self, endpoint: Endpoint, titles: Vec<String>) {
+        let tx = self.action_tx.clone().unwrap();
+        tokio::spawn(async move {
+            match fetch_qualities(endpoint, &titles).await {
+                Ok(qualities) => tx
+                    .send(Action::Search(SearchAction::FinishQualities(qualities)))
+                    .unwrap(),
+                Err(error) => warn!("Unable to fetch the quality of the results: {:?}", error),
+            }
+        });
+    }
+
+    /// Fetches how often the results were viewed lately in the background
+    fn load_page_views(&self, endpoint: Endpoint, titles: Vec<String>) {
+        let tx = self.action_tx.clone().unwrap();
+        tokio::spawn(async move {
+            match fetch_page_views(endpoint, &titles).await {
+                Ok(page_views) => tx
+                    .send(Action::Search(SearchAction::FinishPageViews(page_views)))
+                    .unwrap(),
+                Err(error) => warn!("Unable to fetch the views of the results: {:?}", error),
+            }
+        });
+    }
+
+    /// Title of the selected result, either one from this device or one of the wiki
+    fn selected_title(&self) -> Option<&str> {
+        match self.local_results.selected() {
+            Some(result) => Some(&result.title),
+            None => self
+                .search_results
+                .selected()
+                .map(|result| result.title.as_str()),
+        }
+    }
+
+    fn is_selected(&self) -> bool {
+        self.local_results.is_selected() || self.search_results.is_selected()
+    }
+
+    /// Moves the selection down, from the last result on this device to the first one of the
+    /// wiki
+    fn select_next(&mut self) {
+        let local_len = self.local_results.get_items().len();
+        match self.local_results.get_state_mut().selected() {
+            Some(n) if n + 1 >= local_len && !self.search_results.get_items().is_empty() => {
+                self.local_results.unselect();
+                self.next_result();
+            }
+            Some(_) => self.local_results.next(),
+            None if local_len > 0 && !self.search_results.is_selected() => {
+                self.local_results.next()
+            }
+            None => self.next_result(),
+        }
+    }
+
+    /// Moves the selection up, from the first result of the wiki to the last one on this device
+    fn select_previous(&mut self) {
+        let local_len = self.local_results.get_items().len();
+        let is_first = self.search_results.get_state_mut().selected() == Some(0);
+        if is_first && local_len > 0 {
+            self.search_results.unselect();
+            self.local_results
+                .get_state_mut()
+                .select(Some(local_len - 1));
+        } else if self.local_results.is_selected()
+            || (local_len > 0 && !self.search_results.is_selected())
+        {
+            self.local_results.previous();
+        } else {
+            self.previous_result();
+        }
+    }
+
     fn open_selected_result(&self) -> ActionResult {
-        if let Some(selected_result) = self.search_results.selected() {
+        // the header of a collapsed group is selected
+        let is_header = self
+            .search_results
+            .get_state()
+            .selected()
+            .is_some_and(|index| self.is_collapsed(index));
+        if !self.local_results.is_selected() && is_header {
+            return Action::Search(SearchAction::ToggleGroup).into();
+        }
+
+        if let Some(title) = self.selected_title() {
             return ActionPacket::default()
                 .action(Action::ClearSearchBar)
-                .action(Action::LoadPage(selected_result.title.clone()))
+                .action(Action::LoadPage(title.to_string()))
                 .into();
         }
         ActionResult::Ignored
     }
 
+    /// Opens a menu with everything that can be done with the selected result
+    fn show_result_menu(&self) -> ActionResult {
+        let result = match self.search_results.selected() {
+            Some(result) => result,
+            None => return ActionResult::Ignored,
+        };
+
+        let title = result.title.clone();
+        let language_code = result.language.code();
+        let url = article_url(language_code, &title);
+        let yank = Yank::new(
+            YankKind::Link,
+            url.clone(),
+            title.clone(),
+            vec![
+                ("url", url.clone()),
+                ("title", title.clone()),
+                ("language", language_code.to_string()),
+            ],
+        );
+
+        let menu = ContextMenu::new(title.clone())
+            .item(
+                'o',
+                tr("search.open"),
+                vec![
+                    Action::ClearSearchBar,
+                    Action::SwitchContextPage,
+                    Action::PageViewer(PageViewerAction::ReopenPage(title.clone())),
+                ],
+            )
+            .item(
+                't',
+                tr("search.open_new_tab"),
+                vec![Action::ClearSearchBar, Action::LoadPage(title.clone())],
+            )
+            .item(
+                'w',
+                tr("search.open_browser"),
+                vec![Action::OpenInBrowser(url)],
+            )
+            .item(
+                'b',
+                tr("search.bookmark"),
+                vec![Action::AddBookmark(title.clone(), result.language.clone())],
+            )
+            .item(
+                'r',
+                tr("search.read_next"),
+                vec![Action::QueueReading(title.clone(), result.language.clone())],
+            )
+            .item('y', tr("search.copy_url"), vec![Action::Yank(yank)])
+            .item(
+                'p',
+                tr("search.preview"),
+                vec![Action::Search(SearchAction::PreviewResult(title))],
+            );
+        Action::OpenContextMenu(menu).into()
+    }
+
+    /// Fetches the summary of the result in the background and shows it once it's loaded
+    fn preview_result(&mut self, title: String) -> ActionResult {
+        let endpoint = match self.endpoint {
+            Some(ref endpoint) => endpoint.clone(),
+            None => return ActionResult::consumed(),
+        };
+        let backend = content_source().backend(endpoint);
+
+        self.preview = Some(None);
+        let tx = self.action_tx.clone().unwrap();
+        tokio::spawn(async move {
+            match backend.fetch_summary(&title).await {
+                Ok(summary) => tx
+                    .send(Action::Search(SearchAction::FinishPreview(summary)))
+                    .unwrap(),
+                Err(error) => {
+                    warn!("Unable to fetch the preview of '{}': {:?}", title, error);
+                    tx.send(Action::Search(SearchAction::ClosePreview)).unwrap();
+                }
+            }
+        });
+        ActionResult::consumed()
+    }
+
+    /// Renders the summary of the previewed result over the results
+    fn render_preview(&self, f: &mut Frame<'_>, area: Rect) {
+        if let Some(ref preview) = self.preview {
+            render_summary_popup(f, area, preview.as_ref());
+        }
+    }
+
+    /// Adds the selected result to the fetch queue
+    fn queue_selected_result(&self) -> ActionResult {
+        if let Some(title) = self.selected_title() {
+            return Action::QueueFetch(title.to_string()).into();
+        }
+        ActionResult::Ignored
+    }
+
     fn clear_search_results(&mut self) -> ActionResult {
         self.search_results = StatefulList::with_items(Vec::new());
+        self.rows_state = ListState::default();
         self.continue_search = None;
         self.search_info = None;
+        self.qualities.clear();
+        self.page_views.clear();
+        self.suggestion = None;
 
         ActionResult::consumed()
     }
+
+    /// Renders the pages on this device matching the query, separated from the results of the
+    /// wiki
+    fn render_local_results(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let items: Vec<ListItem> = self
+            .local_results
+            .get_items()
+            .iter()
+            .map(|result| {
+                let source = match result.source {
+                    LocalSource::Bookmark => tr("search.source_bookmark"),
+                    LocalSource::History => tr("search.source_history"),
+                    LocalSource::Cache => tr("search.source_cache"),
+                };
+                ListItem::new(Line::from(vec![
+                    Span::raw(result.title.clone()).red(),
+                    Span::raw(format!(" ({})", source)).dark_gray(),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(tr("search.local_results")),
+            )
+            .highlight_symbol("| ")
+            .highlight_spacing(HighlightSpacing::Always)
+            .highlight_style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::ITALIC),
+            );
+        f.render_stateful_widget(list, area, self.local_results.get_state_mut());
+    }
+
+    /// Renders the query field above the results
+    fn render_query(&self, f: &mut Frame<'_>, area: Rect) {
+        const LABEL: &str = "Query: ";
+
+        let input = match self.query_input {
+            Some(ref input) => input,
+            None => {
+                let query = self.query().unwrap_or_default().to_string();
+                f.render_widget(
+                    Paragraph::new(Line::from(vec![
+                        Span::raw(LABEL).dark_gray(),
+                        Span::raw(query),
+                        Span::raw(" [/ to edit]").dark_gray(),
+                    ])),
+                    area,
+                );
+                return;
+            }
+        };
+
+        let label_width = LABEL.len() as u16;
+        let width = area.width.saturating_sub(label_width + 1) as usize;
+        let scroll = input.visual_scroll(width);
+        f.render_widget(Paragraph::new(Span::raw(LABEL).yellow()), area);
+        f.render_widget(
+            Paragraph::new(input.value()).scroll((0, scroll as u16)),
+            Rect {
+                x: area.x + label_width,
+                width: area.width.saturating_sub(label_width),
+                ..area
+            },
+        );
+        f.set_cursor(
+            area.x + label_width + (input.visual_cursor().max(scroll) - scroll) as u16,
+            area.y,
+        );
+    }
+}
+
+/// Splits the results, which are sorted by their group, into the ranges of the groups
+fn group_ranges(results: &[SearchResult]) -> Vec<Range<usize>> {
+    let mut groups: Vec<Range<usize>> = Vec::new();
+    for (index, result) in results.iter().enumerate() {
+        match groups.last_mut() {
+            Some(group) if results[group.start].namespace == result.namespace => {
+                group.end = index + 1
+            }
+            _ => groups.push(index..index + 1),
+        }
+    }
+    groups
+}
+
+/// Header of the group of results in the namespace
+fn group_label(namespace: Namespace) -> &'static str {
+    match namespace {
+        Namespace::Main => tr("search.group_articles"),
+        Namespace::Category => tr("search.group_categories"),
+        Namespace::Template => tr("search.group_templates"),
+        Namespace::Help => tr("search.group_help"),
+        Namespace::File => tr("search.group_files"),
+        _ => tr("search.group_other"),
+    }
+}
+
+/// Corrects the query using the titles of the locally known pages, works without a connection
+fn local_suggestion(query: &str) -> Option<String> {
+    let annotations = annotations();
+    closest_match(
+        query,
+        annotations
+            .all()
+            .iter()
+            .map(|annotation| annotation.page.as_str()),
+    )
+    .map(|title| title.to_string())
 }
 
 impl Component for SearchComponent {
     fn init(&mut self, sender: mpsc::UnboundedSender<Action>) -> anyhow::Result<()> {
         self.action_tx = Some(sender);
-        // FIXME: the endpoint and language should be set by the root component
-        self.endpoint = Some(Endpoint::parse("https://en.wikipedia.org/w/api.php").unwrap());
-        self.language = Some(Language::default());
+        self.set_language(config().api.language.clone());
+        self.namespaces = config()
+            .api
+            .search_namespaces
+            .iter()
+            .map(|namespace| namespace.namespace())
+            .collect();
         Ok(())
     }
 
     fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        if let Some(ref mut input) = self.query_input {
+            return match key.code {
+                KeyCode::Enter => Action::Search(SearchAction::SubmitQuery).into(),
+                KeyCode::Esc => Action::Search(SearchAction::CancelQueryEdit).into(),
+                _ => {
+                    input.handle_event(&crossterm::event::Event::Key(key));
+                    ActionResult::consumed()
+                }
+            };
+        }
+
+        // the preview is closed by any key
+        if self.preview.is_some() {
+            return Action::Search(SearchAction::ClosePreview).into();
+        }
+
         match self.mode {
             Mode::Normal => match key.code {
-                KeyCode::Enter if self.search_results.is_selected() => {
+                KeyCode::Enter if self.is_selected() => {
+                    Action::Search(SearchAction::OpenSearchResult).into()
+                }
+                KeyCode::Char('a') if self.is_selected() => {
+                    Action::Search(SearchAction::QueueSearchResult).into()
+                }
+                KeyCode::Char('m') if self.search_results.is_selected() => {
+                    Action::Search(SearchAction::ShowResultMenu).into()
+                }
+                KeyCode::Char('c') if self.is_grouped() && self.search_results.is_selected() => {
+                    Action::Search(SearchAction::ToggleGroup).into()
+                }
+                KeyCode::Tab if self.suggestion.is_some() => {
+                    Action::Search(SearchAction::AcceptSuggestion).into()
+                }
+                KeyCode::Char('/') if self.search_info.is_some() => {
+                    Action::Search(SearchAction::EditQuery).into()
+                }
+                _ => ActionResult::Ignored,
+            },
+            // the results on this device are shown while the wiki is searched
+            Mode::Processing => match key.code {
+                KeyCode::Enter if self.local_results.is_selected() => {
                     Action::Search(SearchAction::OpenSearchResult).into()
                 }
                 _ => ActionResult::Ignored,
             },
-            Mode::Processing => ActionResult::Ignored,
         }
     }
 
     fn keymap(&self) -> super::help::Keymap {
-        vec![(
-            key_event!(Key::Enter),
-            ActionPacket::single(Action::Search(SearchAction::OpenSearchResult)),
-        )]
+        vec![
+            (
+                key_event!(Key::Enter),
+                ActionPacket::single(Action::Search(SearchAction::OpenSearchResult)),
+            ),
+            (
+                key_event!('a'),
+                ActionPacket::single(Action::Search(SearchAction::QueueSearchResult)),
+            ),
+            (
+                key_event!('m'),
+                ActionPacket::single(Action::Search(SearchAction::ShowResultMenu)),
+            ),
+            (
+                key_event!('c'),
+                ActionPacket::single(Action::Search(SearchAction::ToggleGroup)),
+            ),
+            (
+                key_event!(Key::Tab),
+                ActionPacket::single(Action::Search(SearchAction::AcceptSuggestion)),
+            ),
+            (
+                key_event!('/'),
+                ActionPacket::single(Action::Search(SearchAction::EditQuery)),
+            ),
+        ]
+    }
+
+    fn is_typing(&self) -> bool {
+        self.query_input.is_some()
     }
 
     fn update(&mut self, action: Action) -> ActionResult {
@@ -165,8 +798,42 @@ impl Component for SearchComponent {
             Action::Search(search_action) => match search_action {
                 SearchAction::StartSearch(query) => self.start_search(query),
                 SearchAction::FinshSearch(search) => self.finish_search(search),
+                SearchAction::FailSearch(query) => {
+                    self.suggestion = local_suggestion(&query);
+                    ActionResult::consumed()
+                }
+                SearchAction::AcceptSuggestion => self.accept_suggestion(),
+                SearchAction::EditQuery => self.edit_query(),
+                SearchAction::SubmitQuery => self.submit_query(),
+                SearchAction::CancelQueryEdit => {
+                    self.query_input = None;
+                    ActionResult::consumed()
+                }
+                SearchAction::FinishQualities(qualities) => {
+                    self.qualities.extend(qualities);
+                    ActionResult::consumed()
+                }
+                SearchAction::FinishPageViews(page_views) => {
+                    self.page_views.extend(page_views);
+                    ActionResult::consumed()
+                }
                 SearchAction::ClearSearchResults => self.clear_search_results(),
                 SearchAction::OpenSearchResult => self.open_selected_result(),
+                SearchAction::QueueSearchResult => self.queue_selected_result(),
+                SearchAction::ShowResultMenu => self.show_result_menu(),
+                SearchAction::ToggleGroup => self.toggle_group(),
+                SearchAction::PreviewResult(title) => self.preview_result(title),
+                SearchAction::FinishPreview(summary) => {
+                    // the preview was closed while it was loading
+                    if self.preview.is_some() {
+                        self.preview = Some(Some(summary));
+                    }
+                    ActionResult::consumed()
+                }
+                SearchAction::ClosePreview => {
+                    self.preview = None;
+                    ActionResult::consumed()
+                }
             },
             Action::EnterNormal => {
                 self.mode = Mode::Normal;
@@ -178,17 +845,18 @@ impl Component for SearchComponent {
             }
             Action::ScrollUp(n) => {
                 for _ in 0..n {
-                    self.search_results.previous()
+                    self.select_previous()
                 }
                 ActionResult::consumed()
             }
             Action::ScrollDown(n) => {
                 for _ in 0..n {
-                    self.search_results.next()
+                    self.select_next()
                 }
                 ActionResult::consumed()
             }
             Action::UnselectScroll => {
+                self.local_results.unselect();
                 self.search_results.unselect();
                 ActionResult::consumed()
             }
@@ -197,6 +865,18 @@ impl Component for SearchComponent {
     }
 
     fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let area = if self.local_results.get_items().is_empty() {
+            area
+        } else {
+            let height = self.local_results.get_items().len() as u16 + 2;
+            let rects = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(height), Constraint::Min(0)])
+                .split(area);
+            self.render_local_results(f, rects[0]);
+            rects[1]
+        };
+
         if self.mode == Mode::Processing {
             f.render_widget(
                 Block::default()
@@ -206,34 +886,54 @@ impl Component for SearchComponent {
                 area,
             );
             f.render_widget(
-                Paragraph::new("Processing Search. Please wait...").alignment(Alignment::Center),
+                Paragraph::new(tr("search.processing")).alignment(Alignment::Center),
                 centered_rect(area, 100, 50),
             );
             return;
         }
 
         if self.search_results.get_items().is_empty() {
+            let message = match self.suggestion {
+                Some(ref suggestion) => {
+                    format!(
+                        "Did you mean '{}'? Press [Tab] to search for it",
+                        suggestion
+                    )
+                }
+                // the wiki found nothing or couldn't be reached, the local results are enough
+                None if !self.local_results.get_items().is_empty() => return,
+                None => "Start a search to view the results!".to_string(),
+            };
             f.render_widget(
-                Paragraph::new("Start a search to view the results!").alignment(Alignment::Center),
+                Paragraph::new(message).alignment(Alignment::Center),
                 centered_rect(area, 100, 50),
             );
             return;
         }
 
-        let [info_area, results_area] = {
+        let [query_area, info_area, results_area] = {
             let rects = Layout::default()
                 .direction(Direction::Vertical)
-                .constraints([Constraint::Min(1), Constraint::Percentage(100)])
+                .constraints([
+                    Constraint::Min(1),
+                    Constraint::Min(1),
+                    Constraint::Percentage(100),
+                ])
                 .split(area);
-            [rects[0], rects[1]]
+            [rects[0], rects[1], rects[2]]
         };
+        self.render_query(f, query_area);
 
         if let Some(ref search_info) = self.search_info {
-            let info = Paragraph::new(format!(
+            let mut info = format!(
                 "Results: {} | Language: {}",
                 search_info.total_hits.unwrap_or_default(),
                 search_info.language.name()
-            ));
+            );
+            if let Some(ref suggestion) = self.suggestion {
+                info.push_str(&format!(" | Did you mean '{}'? [Tab]", suggestion));
+            }
+            let info = Paragraph::new(info);
 
             f.render_widget(info, info_area);
         }
@@ -241,34 +941,79 @@ impl Component for SearchComponent {
         // TODO: Somehow implement list item margin
         let results_list_width = results_area.width.saturating_sub(3); // HACK: subtract 3 for
                                                                        // border and highlight symbol
-        let items: Vec<ListItem> = self
-            .search_results
-            .get_items()
-            .iter()
-            .map(|result| {
-                let snippet = result.snippet.clone().unwrap();
-                let mut cleaned_snippet = String::new();
-                for slice in snippet
-                    .split(r#"<span class="searchmatch">"#)
-                    .collect::<Vec<&str>>()
-                {
-                    let split_slice: Vec<&str> = slice.split("</span>").collect();
-                    cleaned_snippet.push_str(&split_slice.join(""));
-                }
+        let result_item = |result: &SearchResult| {
+            let snippet = result.snippet.clone().unwrap();
+            let mut cleaned_snippet = String::new();
+            for slice in snippet
+                .split(r#"<span class="searchmatch">"#)
+                .collect::<Vec<&str>>()
+            {
+                let split_slice: Vec<&str> = slice.split("</span>").collect();
+                cleaned_snippet.push_str(&split_slice.join(""));
+            }
 
-                let mut text = Text::from(Span::raw(result.title.clone()).red());
-                text.lines.append(
-                    &mut textwrap::wrap(&cleaned_snippet, results_list_width as usize)
-                        .iter()
-                        .map(|s| Line::from(s.to_string()))
-                        .collect(),
-                );
-                ListItem::new(text)
-            })
-            .collect();
+            let mut title = vec![Span::raw(result.title.clone()).red()];
+            if let Some(quality) = self.qualities.get(&result.title) {
+                title.push(Span::raw(format!(" {}", quality.badge())).yellow());
+            }
+            if let Some(summary) = self
+                .page_views
+                .get(&result.title)
+                .and_then(page_views_summary)
+            {
+                title.push(Span::raw(format!(" {}", summary)).dark_gray());
+            }
+            let mut text = Text::from(Line::from(title));
+            text.lines.append(
+                &mut textwrap::wrap(&cleaned_snippet, results_list_width as usize)
+                    .iter()
+                    .map(|s| Line::from(s.to_string()))
+                    .collect(),
+            );
+            ListItem::new(text)
+        };
+
+        let mut selected_row = None;
+        let items: Vec<ListItem> = if self.is_grouped() {
+            let results = self.search_results.get_items();
+            let selected = self.search_results.get_state().selected();
+            let mut items = Vec::new();
+            for group in group_ranges(results) {
+                let namespace = results[group.start].namespace;
+                let is_collapsed = self.collapsed.contains(&namespace);
+                if is_collapsed && selected == Some(group.start) {
+                    selected_row = Some(items.len());
+                }
+                items.push(ListItem::new(Line::from(vec![
+                    Span::raw(if is_collapsed { "▸ " } else { "▾ " }),
+                    Span::raw(group_label(namespace)).bold(),
+                    Span::raw(format!(" ({})", group.len())).dark_gray(),
+                ])));
+                if is_collapsed {
+                    continue;
+                }
+                for index in group {
+                    if selected == Some(index) {
+                        selected_row = Some(items.len());
+                    }
+                    items.push(result_item(&results[index]));
+                }
+            }
+            items
+        } else {
+            self.search_results
+                .get_items()
+                .iter()
+                .map(result_item)
+                .collect()
+        };
 
         let items = List::new(items)
-            .block(Block::default().borders(Borders::ALL).title("Results"))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(tr("search.results")),
+            )
             .repeat_highlight_symbol(true)
             .highlight_symbol("| ")
             .highlight_spacing(HighlightSpacing::Always)
@@ -277,6 +1022,49 @@ impl Component for SearchComponent {
                     .bg(Color::DarkGray)
                     .add_modifier(Modifier::ITALIC),
             );
-        f.render_stateful_widget(items, results_area, self.search_results.get_state_mut());
+        if self.is_grouped() {
+            self.rows_state.select(selected_row);
+            f.render_stateful_widget(items, results_area, &mut self.rows_state);
+        } else {
+            f.render_stateful_widget(items, results_area, self.search_results.get_state_mut());
+        }
+        self.render_preview(f, results_area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiki_api::{
+        languages::Language,
+        search::{Namespace, SearchResult},
+    };
+
+    use super::group_ranges;
+
+    fn result(namespace: Namespace) -> SearchResult {
+        SearchResult {
+            namespace,
+            title: String::new(),
+            pageid: 0,
+            language: Language::English,
+            endpoint: Language::English.wikipedia_endpoint(),
+            size: None,
+            wordcount: None,
+            snippet: None,
+            timestamp: None,
+        }
+    }
+
+    #[test]
+    fn groups() {
+        let results = [
+            result(Namespace::Main),
+            result(Namespace::Main),
+            result(Namespace::Category),
+            result(Namespace::Help),
+            result(Namespace::Help),
+        ];
+        assert_eq!(group_ranges(&results), vec![0..2, 2..3, 3..5]);
+        assert!(group_ranges(&[]).is_empty());
     }
 }
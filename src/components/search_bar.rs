@@ -5,10 +5,15 @@ use ratatui::{
     text::Text,
     widgets::{Block, BorderType, Borders, Paragraph},
 };
+use tracing::{error, warn};
 use tui_input::{backend::crossterm::EventHandler, Input};
+use wiki_api::{geosearch::Coordinates, languages::Language};
 
 use crate::{
-    action::{Action, ActionResult, SearchAction},
+    action::{Action, ActionPacket, ActionResult, NearbyAction, PageAction, SearchAction},
+    citation::CitationFormat,
+    flashcards::FlashcardTarget,
+    renderer::epub::EpubSource,
     terminal::Frame,
     ui::centered_rect,
 };
@@ -16,6 +21,7 @@ use crate::{
 use super::Component;
 
 const EMPTY_PROMPT: &str = "Search Wikipedia";
+const COMMAND_PREFIX: char = ':';
 const SEARCH_BAR_X: u16 = 50;
 
 pub const SEARCH_BAR_HEIGTH: u16 = 3;
@@ -31,8 +37,129 @@ impl SearchBarComponent {
         self.input = Input::default();
     }
 
-    pub fn submit(&self) -> Action {
-        Action::Search(SearchAction::StartSearch(self.input.value().to_string()))
+    /// Returns the actions for the entered search query or, when it starts with `:`, command
+    pub fn submit(&self) -> ActionPacket {
+        let value = self.input.value();
+        match value.strip_prefix(COMMAND_PREFIX) {
+            Some(command) => Self::run_command(command),
+            None => ActionPacket::default()
+                .action(Action::SwitchContextSearch)
+                .action(Action::Search(SearchAction::StartSearch(value.to_string()))),
+        }
+    }
+
+    fn run_command(command: &str) -> ActionPacket {
+        let (name, args) = command
+            .trim()
+            .split_once(' ')
+            .unwrap_or((command.trim(), ""));
+
+        match name {
+            "nearby" => {
+                let coordinates = if args.trim().is_empty() {
+                    None
+                } else {
+                    match args.parse::<Coordinates>() {
+                        Ok(coordinates) => Some(coordinates),
+                        Err(error) => {
+                            error!("invalid coordinates '{}': {:?}", args, error);
+                            return ActionPacket::default();
+                        }
+                    }
+                };
+                ActionPacket::default()
+                    .action(Action::SwitchContextNearby)
+                    .action(Action::Nearby(NearbyAction::Search(coordinates)))
+            }
+            "lang" | "language" => {
+                if args.trim().is_empty() {
+                    return ActionPacket::single(Action::SwitchContextLanguages);
+                }
+                match Language::find(args) {
+                    Some(language) => ActionPacket::single(Action::SwitchLanguage(language)),
+                    None => {
+                        error!("unknown language '{}'", args.trim());
+                        ActionPacket::default()
+                    }
+                }
+            }
+            "epub" => match args.trim().split_once(' ').unwrap_or((args.trim(), "")) {
+                ("", _) => ActionPacket::default()
+                    .action(Action::SwitchContextPage)
+                    .action(Action::Page(PageAction::ExportEpub)),
+                ("bookmarks", _) => ActionPacket::single(Action::ExportEpub(EpubSource::Bookmarks)),
+                ("category", category) if !category.trim().is_empty() => ActionPacket::single(
+                    Action::ExportEpub(EpubSource::Category(category.trim().to_string())),
+                ),
+                _ => {
+                    error!("expected ':epub', ':epub bookmarks' or ':epub category <name>'");
+                    ActionPacket::default()
+                }
+            },
+            "text" => ActionPacket::default()
+                .action(Action::SwitchContextPage)
+                .action(Action::Page(PageAction::ExportText)),
+            "html" => ActionPacket::default()
+                .action(Action::SwitchContextPage)
+                .action(Action::Page(PageAction::ExportHtml)),
+            "cite" => {
+                let action = match args.trim() {
+                    "" | "plain" => PageAction::Cite(CitationFormat::Plain),
+                    "bibtex" => PageAction::Cite(CitationFormat::Bibtex),
+                    "csl" | "csl_json" => PageAction::Cite(CitationFormat::CslJson),
+                    "add" => PageAction::AddToBibliography,
+                    _ => {
+                        error!("expected ':cite', ':cite bibtex', ':cite csl' or ':cite add'");
+                        return ActionPacket::default();
+                    }
+                };
+                ActionPacket::default()
+                    .action(Action::SwitchContextPage)
+                    .action(Action::Page(action))
+            }
+            "workspace" => match args.trim().split_once(' ').unwrap_or((args.trim(), "")) {
+                ("", _) => ActionPacket::single(Action::ToggleWorkspaces),
+                ("save", name) if !name.trim().is_empty() => {
+                    ActionPacket::single(Action::SaveWorkspace(name.trim().to_string()))
+                }
+                ("load", name) if !name.trim().is_empty() => {
+                    ActionPacket::single(Action::LoadWorkspace(name.trim().to_string()))
+                }
+                ("delete", name) if !name.trim().is_empty() => {
+                    ActionPacket::single(Action::DeleteWorkspace(name.trim().to_string()))
+                }
+                _ => {
+                    error!(
+                        "expected ':workspace', ':workspace save <name>', ':workspace load <name>' \
+                         or ':workspace delete <name>'"
+                    );
+                    ActionPacket::default()
+                }
+            },
+            "har" => ActionPacket::single(Action::ExportHar),
+            "anki" => match args.trim() {
+                "" => ActionPacket::single(Action::ExportFlashcards(FlashcardTarget::Tsv)),
+                "send" => {
+                    ActionPacket::single(Action::ExportFlashcards(FlashcardTarget::AnkiConnect))
+                }
+                _ => {
+                    error!("expected ':anki' or ':anki send'");
+                    ActionPacket::default()
+                }
+            },
+            "bugreport" => match args.trim() {
+                "" => ActionPacket::single(Action::BugReport(false)),
+                "copy" => ActionPacket::single(Action::BugReport(true)),
+                _ => {
+                    error!("expected ':bugreport' or ':bugreport copy'");
+                    ActionPacket::default()
+                }
+            },
+            _ => {
+                warn!("unknown command '{}'", name);
+                ActionPacket::default()
+            }
+        }
     }
 }
 
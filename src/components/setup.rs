@@ -0,0 +1,150 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::Rect,
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
+};
+use tui_input::{backend::crossterm::EventHandler, Input};
+use wiki_api::languages::Language;
+
+use crate::{
+    action::{Action, ActionResult},
+    config::config_path,
+    i18n::{tr, tr_args},
+    setup::FirstRun,
+    terminal::Frame,
+    ui::centered_rect,
+};
+
+use super::Component;
+
+const DIALOG_WIDTH: u16 = 60;
+const DIALOG_HEIGHT: u16 = 12;
+
+/// Welcomes the user on the first run and tells which language pages are loaded in, chosen from
+/// the language of the system. Another language can be entered instead
+pub struct SetupComponent {
+    first_run: FirstRun,
+    /// The language being entered instead of the chosen one
+    language_input: Option<Input>,
+    /// Whether the entered language wasn't found
+    is_unknown: bool,
+}
+
+impl SetupComponent {
+    pub fn new(first_run: FirstRun) -> Self {
+        Self {
+            first_run,
+            language_input: None,
+            is_unknown: false,
+        }
+    }
+
+    fn submit_language(&mut self) -> ActionResult {
+        let query = match self.language_input {
+            Some(ref input) => input.value().to_string(),
+            None => return ActionResult::consumed(),
+        };
+        match Language::find(&query) {
+            Some(language) => Action::FinishSetup(Some(language)).into(),
+            None => {
+                self.is_unknown = true;
+                ActionResult::consumed()
+            }
+        }
+    }
+
+    fn lines(&self) -> Vec<Line<'static>> {
+        let language = &self.first_run.language;
+        let key = if self.first_run.detected {
+            "setup.detected"
+        } else {
+            "setup.not_detected"
+        };
+        let path = config_path()
+            .map(|path| path.display().to_string())
+            .unwrap_or_default();
+
+        let mut lines = vec![
+            Line::from(Span::raw(tr("setup.welcome")).bold()),
+            Line::default(),
+            Line::from(tr_args(
+                key,
+                &[
+                    ("language", language.name().to_string()),
+                    ("code", language.code().to_string()),
+                ],
+            )),
+            Line::from(tr_args("setup.saved", &[("path", path)]).dark_gray()),
+            Line::default(),
+        ];
+        match self.language_input {
+            Some(ref input) => {
+                lines.push(Line::from(vec![
+                    Span::raw(format!("{}: ", tr("setup.prompt"))),
+                    Span::raw(input.value().to_string()).yellow(),
+                ]));
+                if self.is_unknown {
+                    lines.push(Line::from(tr("setup.unknown").red()));
+                }
+                lines.push(Line::from(tr("setup.input_keys").dark_gray()));
+            }
+            None => lines.push(Line::from(tr("setup.keys").dark_gray())),
+        }
+        lines
+    }
+}
+
+impl Component for SetupComponent {
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        if let Some(ref mut input) = self.language_input {
+            match key.code {
+                KeyCode::Enter => return self.submit_language(),
+                KeyCode::Esc => self.language_input = None,
+                _ => {
+                    input.handle_event(&crossterm::event::Event::Key(key));
+                    self.is_unknown = false;
+                }
+            }
+            return ActionResult::consumed();
+        }
+
+        match key.code {
+            KeyCode::Enter | KeyCode::Esc => Action::FinishSetup(None).into(),
+            KeyCode::Char('c') => {
+                self.language_input = Some(Input::default());
+                ActionResult::consumed()
+            }
+            // the dialog is modal
+            _ => ActionResult::consumed(),
+        }
+    }
+
+    fn is_typing(&self) -> bool {
+        self.language_input.is_some()
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let height = DIALOG_HEIGHT.min(area.height);
+        let area = Rect {
+            y: area.y + (area.height - height) / 2,
+            height,
+            ..centered_rect(area, DIALOG_WIDTH, 100)
+        };
+
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Paragraph::new(self.lines())
+                .wrap(Wrap { trim: false })
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_type(BorderType::Rounded)
+                        .border_style(Style::default().fg(Color::Green))
+                        .title(tr("setup.title")),
+                ),
+            area,
+        );
+    }
+}
@@ -1,26 +1,263 @@
+use std::fmt::Write;
+
+use chrono::Local;
 use ratatui::{
-    prelude::{Constraint, Direction, Layout, Rect},
+    prelude::{Alignment, Rect},
+    style::Stylize,
+    text::{Line, Span},
     widgets::Paragraph,
 };
+use wiki_api::{
+    assessment::Quality,
+    languages::Language,
+    pageviews::{compact_count, PageViews},
+};
 
-use crate::terminal::Frame;
+use crate::{
+    config::{config, StatusConfig, StatusSegment},
+    i18n::{tr, tr_args},
+    terminal::Frame,
+    ui::ResponsiveLayout,
+};
 
 use super::Component;
 
-const HELP_MSG: &str = "DEVELOPMENT VERSION - Press [?] for help";
-const HELP_MSG_LEN: u16 = HELP_MSG.len() as u16;
-
 pub const STATUS_HEIGHT: u16 = 1;
+/// Amount of bars of the sparkline that shows the trend of the page views
+const SPARKLINE_WIDTH: usize = 8;
+
+/// Sums up the page views as a sparkline of their trend and the views on an average day, `None`
+/// when there are no statistics
+pub fn page_views_summary(page_views: &PageViews) -> Option<String> {
+    let average = page_views.average()?;
+    Some(format!(
+        "{} {}",
+        page_views.sparkline(SPARKLINE_WIDTH),
+        tr_args("status.page_views", &[("views", compact_count(average))])
+    ))
+}
+
+/// What the status bar shows about the displayed page
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PageStatus {
+    /// Title of the page, including the heading of a section shown on its own
+    pub title: String,
+    pub quality: Option<Quality>,
+    /// How often the page was viewed lately, `None` until the views are fetched
+    pub page_views: Option<PageViews>,
+    /// Heading of the section at the top of the viewport, `None` in the lead section
+    pub section: Option<String>,
+    pub scroll_percent: u16,
+}
 
+/// Status bar made of the segments set in the config
 #[derive(Default)]
-pub struct StatusComponent;
+pub struct StatusComponent {
+    page: Option<PageStatus>,
+    is_offline: bool,
+    language: Language,
+    task_count: usize,
+}
+
+impl StatusComponent {
+    /// Sets what is shown about the displayed page, `None` when no page is displayed
+    pub fn set_page(&mut self, page: Option<PageStatus>) {
+        self.page = page;
+    }
+
+    pub fn set_offline(&mut self, is_offline: bool) {
+        self.is_offline = is_offline;
+    }
+
+    pub fn set_language(&mut self, language: Language) {
+        self.language = language;
+    }
+
+    /// Sets how many pages in the fetch queue weren't fetched yet
+    pub fn set_task_count(&mut self, task_count: usize) {
+        self.task_count = task_count;
+    }
+
+    fn network_status(&self) -> Span<'static> {
+        if self.is_offline {
+            Span::raw(tr("status.offline")).red()
+        } else {
+            Span::raw(tr("status.online")).green()
+        }
+    }
+
+    fn clock(clock_format: &str) -> String {
+        let mut clock = String::new();
+        // an invalid format fails when it's written instead of panicking
+        if write!(clock, "{}", Local::now().format(clock_format)).is_err() {
+            clock = Local::now().format("%H:%M").to_string();
+        }
+        clock
+    }
+
+    /// Returns the segment, `None` when there is nothing to show for it
+    fn segment(&self, segment: StatusSegment, config: &StatusConfig) -> Option<Span<'static>> {
+        Some(match segment {
+            StatusSegment::PageTitle => Span::raw(self.page.as_ref()?.title.clone()),
+            StatusSegment::Quality => {
+                let quality = self.page.as_ref()?.quality?;
+                Span::raw(format!("{} {}", quality.badge(), quality)).yellow()
+            }
+            StatusSegment::PageViews => {
+                let page_views = self.page.as_ref()?.page_views.as_ref()?;
+                Span::raw(page_views_summary(page_views)?).cyan()
+            }
+            StatusSegment::Language => Span::raw(self.language.code().to_string()).dark_gray(),
+            StatusSegment::Network => self.network_status(),
+            StatusSegment::ScrollPercent => {
+                Span::raw(format!("{}%", self.page.as_ref()?.scroll_percent))
+            }
+            StatusSegment::Section => {
+                Span::raw(format!("§ {}", self.page.as_ref()?.section.as_ref()?)).italic()
+            }
+            StatusSegment::Clock => Span::raw(Self::clock(&config.clock_format)),
+            StatusSegment::TaskCount if self.task_count == 0 => return None,
+            StatusSegment::TaskCount => Span::raw(tr_args(
+                "status.tasks",
+                &[("count", self.task_count.to_string())],
+            ))
+            .cyan(),
+            StatusSegment::Help => Span::raw(tr("status.help")),
+        })
+    }
+
+    /// Returns the segments that have something to show, with the separator between them
+    fn line(&self, segments: &[StatusSegment], config: &StatusConfig) -> Line<'static> {
+        let mut spans = Vec::new();
+        for span in segments
+            .iter()
+            .filter_map(|segment| self.segment(*segment, config))
+        {
+            if !spans.is_empty() {
+                spans.push(Span::raw(config.separator.clone()).dark_gray());
+            }
+            spans.push(span);
+        }
+        Line::from(spans)
+    }
+}
+
 impl Component for StatusComponent {
     fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
-        let chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(100), Constraint::Min(HELP_MSG_LEN)])
-            .split(area);
+        if ResponsiveLayout::new(area).collapses_status() {
+            // only the offline status is important enough to be shown
+            let mut spans = vec![Span::raw(tr("status.short_help"))];
+            if self.is_offline {
+                spans.insert(0, Span::raw(format!("{} ", tr("status.offline"))).red());
+            }
+            f.render_widget(
+                Paragraph::new(Line::from(spans)).alignment(Alignment::Right),
+                area,
+            );
+            return;
+        }
+
+        let config = &config().status;
+        let left = self.line(&config.left, config);
+        let center = self.line(&config.center, config);
+        let right = self.line(&config.right, config);
+
+        // the right segments are shown completely, the centered ones in the remaining space and
+        // the left ones are cut off
+        let right_width = (right.width() as u16).min(area.width);
+        let right_x = area.right() - right_width;
+        let center_width = (center.width() as u16).min(right_x - area.x);
+        let center_x = (area.x + (area.width - center_width) / 2).min(right_x - center_width);
+        let left_end = if center_width > 0 { center_x } else { right_x };
+        let left_width = match left_end - area.x {
+            width if left_end < area.right() => width.saturating_sub(1),
+            width => width,
+        };
+
+        f.render_widget(
+            Paragraph::new(left),
+            Rect {
+                width: left_width,
+                ..area
+            },
+        );
+        f.render_widget(
+            Paragraph::new(center),
+            Rect {
+                x: center_x,
+                width: center_width,
+                ..area
+            },
+        );
+        f.render_widget(
+            Paragraph::new(right),
+            Rect {
+                x: right_x,
+                width: right_width,
+                ..area
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiki_api::pageviews::PageViews;
+
+    use crate::config::{StatusConfig, StatusSegment};
+
+    use super::{PageStatus, StatusComponent};
+
+    #[test]
+    fn segments() {
+        let mut status = StatusComponent::default();
+        let config = StatusConfig {
+            separator: " | ".to_string(),
+            ..StatusConfig::default()
+        };
+        let text = |status: &StatusComponent, segments: &[StatusSegment]| {
+            status
+                .line(segments, &config)
+                .spans
+                .iter()
+                .map(|span| span.content.to_string())
+                .collect::<String>()
+        };
+
+        let segments = [
+            StatusSegment::PageTitle,
+            StatusSegment::Section,
+            StatusSegment::ScrollPercent,
+            StatusSegment::TaskCount,
+            StatusSegment::Language,
+        ];
+        // segments without anything to show are left out together with their separator
+        assert_eq!(text(&status, &segments), "en");
+
+        status.set_page(Some(PageStatus {
+            title: "Rust".to_string(),
+            quality: None,
+            page_views: None,
+            section: Some("History".to_string()),
+            scroll_percent: 42,
+        }));
+        assert_eq!(text(&status, &segments), "Rust | § History | 42% | en");
 
-        f.render_widget(Paragraph::new(HELP_MSG), chunks[1]);
+        status.set_page(Some(PageStatus {
+            title: "Rust".to_string(),
+            quality: None,
+            page_views: Some(PageViews {
+                daily: vec![1200, 1500, 1800],
+            }),
+            section: None,
+            scroll_percent: 0,
+        }));
+        assert_eq!(
+            text(
+                &status,
+                &[StatusSegment::PageTitle, StatusSegment::PageViews]
+            ),
+            "Rust | ▁▄█ 1.5k/day"
+        );
     }
 }
@@ -0,0 +1,262 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::{Alignment, Constraint, Direction, Layout, Margin, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{
+        Block, BorderType, Borders, Clear, HighlightSpacing, List, ListItem, ListState, Paragraph,
+        Wrap,
+    },
+};
+use tracing::info;
+use tui_input::{backend::crossterm::EventHandler, Input};
+
+use crate::{
+    action::{Action, ActionPacket, ActionResult, WorkspacesAction},
+    i18n::{tr, tr_args},
+    key_event,
+    terminal::Frame,
+    undo::{record, Undoable},
+    workspaces::workspaces,
+};
+
+use super::Component;
+
+/// Popup listing the saved workspaces, the latest saved one first. The open pages are saved as a
+/// workspace after entering its name
+#[derive(Default)]
+pub struct WorkspacesComponent {
+    state: ListState,
+    /// Name of the workspace the open pages are saved as, `Some` while it's entered
+    name_input: Option<Input>,
+}
+
+impl WorkspacesComponent {
+    pub fn new() -> Self {
+        let mut state = ListState::default();
+        if !workspaces().all().is_empty() {
+            state.select(Some(0));
+        }
+        Self {
+            state,
+            name_input: None,
+        }
+    }
+
+    fn selected_name(&self) -> Option<String> {
+        let selected = self.state.selected()?;
+        workspaces()
+            .all()
+            .get(selected)
+            .map(|workspace| workspace.name.clone())
+    }
+
+    fn select(&mut self, offset: isize) {
+        let len = workspaces().all().len();
+        if len == 0 {
+            return self.state.select(None);
+        }
+
+        let selected = match self.state.selected() {
+            Some(selected) => (selected as isize + offset).clamp(0, len as isize - 1) as usize,
+            None => 0,
+        };
+        self.state.select(Some(selected));
+    }
+
+    fn open_selected(&self) -> ActionResult {
+        match self.selected_name() {
+            Some(name) => ActionPacket::single(Action::ToggleWorkspaces)
+                .action(Action::LoadWorkspace(name))
+                .into(),
+            None => ActionResult::consumed(),
+        }
+    }
+
+    fn delete_selected(&mut self) {
+        if let Some(name) = self.selected_name() {
+            if let Some(removed) = workspaces().remove(&name) {
+                record(Undoable::Workspace(removed));
+            }
+            self.select(0);
+        }
+    }
+
+    fn submit_name(&mut self) -> ActionResult {
+        let name = match self.name_input.take() {
+            Some(input) => input.value().trim().to_string(),
+            None => return ActionResult::consumed(),
+        };
+        if name.is_empty() {
+            info!("workspaces need a name");
+            return ActionResult::consumed();
+        }
+
+        // the saved workspace is listed first
+        self.state.select(Some(0));
+        Action::SaveWorkspace(name).into()
+    }
+
+    fn render_name_input(&self, f: &mut Frame<'_>, area: Rect) {
+        let input = match self.name_input {
+            Some(ref input) => input,
+            None => return,
+        };
+
+        let scroll = input.visual_scroll(area.width.saturating_sub(2) as usize);
+        f.render_widget(
+            Paragraph::new(input.value())
+                .scroll((0, scroll as u16))
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_type(BorderType::Rounded)
+                        .border_style(Style::default().fg(Color::Yellow))
+                        .title(tr("workspaces.name")),
+                ),
+            area,
+        );
+        f.set_cursor(
+            area.x + (input.visual_cursor().max(scroll) - scroll) as u16 + 1,
+            area.y + 1,
+        );
+    }
+}
+
+impl Component for WorkspacesComponent {
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        if let Some(ref mut input) = self.name_input {
+            return match key.code {
+                KeyCode::Enter => Action::Workspaces(WorkspacesAction::SubmitName).into(),
+                KeyCode::Esc => Action::Workspaces(WorkspacesAction::CancelName).into(),
+                _ => {
+                    input.handle_event(&crossterm::event::Event::Key(key));
+                    ActionResult::consumed()
+                }
+            };
+        }
+
+        match key.code {
+            KeyCode::Enter | KeyCode::Char('o') => {
+                Action::Workspaces(WorkspacesAction::OpenSelected).into()
+            }
+            KeyCode::Char('d') => Action::Workspaces(WorkspacesAction::DeleteSelected).into(),
+            KeyCode::Char('a') => Action::Workspaces(WorkspacesAction::EnterName).into(),
+            KeyCode::Down | KeyCode::Char('j') => Action::ScrollDown(1).into(),
+            KeyCode::Up | KeyCode::Char('k') => Action::ScrollUp(1).into(),
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('w') => {
+                Action::ToggleWorkspaces.into()
+            }
+            // the popup is modal
+            _ => ActionResult::consumed(),
+        }
+    }
+
+    fn keymap(&self) -> super::help::Keymap {
+        vec![
+            (
+                key_event!(Key::Enter),
+                Action::Workspaces(WorkspacesAction::OpenSelected).into(),
+            ),
+            (
+                key_event!('d'),
+                Action::Workspaces(WorkspacesAction::DeleteSelected).into(),
+            ),
+            (
+                key_event!('a'),
+                Action::Workspaces(WorkspacesAction::EnterName).into(),
+            ),
+        ]
+    }
+
+    fn is_typing(&self) -> bool {
+        self.name_input.is_some()
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        match action {
+            Action::Workspaces(workspaces_action) => match workspaces_action {
+                WorkspacesAction::OpenSelected => return self.open_selected(),
+                WorkspacesAction::DeleteSelected => self.delete_selected(),
+                WorkspacesAction::EnterName => self.name_input = Some(Input::default()),
+                WorkspacesAction::SubmitName => return self.submit_name(),
+                WorkspacesAction::CancelName => self.name_input = None,
+            },
+            Action::ScrollUp(n) => self.select(-(n as isize)),
+            Action::ScrollDown(n) => self.select(n as isize),
+            _ => return ActionResult::Ignored,
+        }
+        ActionResult::consumed()
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(Color::Yellow))
+            .title(tr("workspaces.title"));
+        f.render_widget(Clear, area);
+
+        let list_area = match self.name_input {
+            Some(_) => {
+                let rects = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Length(3), Constraint::Percentage(100)])
+                    .split(area.inner(&Margin::new(1, 1)));
+                f.render_widget(block, area);
+                self.render_name_input(f, rects[0]);
+                rects[1]
+            }
+            None => {
+                let inner = block.inner(area);
+                f.render_widget(block, area);
+                inner
+            }
+        };
+
+        let workspaces = workspaces();
+        if workspaces.all().is_empty() {
+            f.render_widget(
+                Paragraph::new(tr("workspaces.empty"))
+                    .alignment(Alignment::Center)
+                    .wrap(Wrap { trim: true }),
+                list_area,
+            );
+            return;
+        }
+
+        let items: Vec<ListItem> = workspaces
+            .all()
+            .iter()
+            .map(|workspace| {
+                let saved = chrono::DateTime::from_timestamp(workspace.saved, 0)
+                    .map(|saved| saved.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_default();
+                ListItem::new(Line::from(vec![
+                    Span::raw(workspace.name.clone()),
+                    Span::raw(format!(
+                        " {}",
+                        tr_args(
+                            "workspaces.info",
+                            &[
+                                ("pages", workspace.pages.len().to_string()),
+                                ("saved", saved),
+                            ],
+                        )
+                    ))
+                    .dark_gray(),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .highlight_symbol("| ")
+            .highlight_spacing(HighlightSpacing::Always)
+            .highlight_style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::ITALIC),
+            );
+        f.render_stateful_widget(list, list_area, &mut self.state);
+    }
+}
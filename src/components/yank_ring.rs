@@ -0,0 +1,175 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::{Alignment, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{
+        Block, BorderType, Borders, Clear, HighlightSpacing, List, ListItem, ListState, Paragraph,
+        Wrap,
+    },
+};
+
+use crate::{
+    action::{Action, ActionPacket, ActionResult, YankRingAction},
+    i18n::tr,
+    key_event,
+    terminal::Frame,
+    yank_ring::{yank_ring, YankKind},
+};
+
+use super::Component;
+
+/// Popup listing the links and passages yanked in this session, the latest one first
+#[derive(Default)]
+pub struct YankRingComponent {
+    state: ListState,
+}
+
+impl YankRingComponent {
+    pub fn new() -> Self {
+        let mut state = ListState::default();
+        if !yank_ring().all().is_empty() {
+            state.select(Some(0));
+        }
+        Self { state }
+    }
+
+    fn select(&mut self, offset: isize) {
+        let len = yank_ring().all().len();
+        if len == 0 {
+            return self.state.select(None);
+        }
+
+        let selected = match self.state.selected() {
+            Some(selected) => (selected as isize + offset).clamp(0, len as isize - 1) as usize,
+            None => 0,
+        };
+        self.state.select(Some(selected));
+    }
+
+    fn yank_selected(&mut self) -> ActionResult {
+        if let Some(selected) = self.state.selected() {
+            yank_ring().yank_again(selected);
+        }
+        Action::ToggleYankRing.into()
+    }
+
+    fn open_selected(&self) -> ActionResult {
+        let page = self.state.selected().and_then(|selected| {
+            yank_ring()
+                .all()
+                .get(selected)
+                .map(|yank| yank.page.clone())
+        });
+        match page {
+            Some(page) => ActionPacket::single(Action::ToggleYankRing)
+                .action(Action::LoadPage(page))
+                .into(),
+            None => ActionResult::consumed(),
+        }
+    }
+
+    fn delete_selected(&mut self) {
+        if let Some(selected) = self.state.selected() {
+            yank_ring().remove(selected);
+            self.select(0);
+        }
+    }
+}
+
+impl Component for YankRingComponent {
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        match key.code {
+            KeyCode::Enter | KeyCode::Char('y') => {
+                Action::YankRing(YankRingAction::YankSelected).into()
+            }
+            KeyCode::Char('o') => Action::YankRing(YankRingAction::OpenSelected).into(),
+            KeyCode::Char('d') => Action::YankRing(YankRingAction::DeleteSelected).into(),
+            KeyCode::Down | KeyCode::Char('j') => Action::ScrollDown(1).into(),
+            KeyCode::Up | KeyCode::Char('k') => Action::ScrollUp(1).into(),
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('Y') => Action::ToggleYankRing.into(),
+            // the popup is modal
+            _ => ActionResult::consumed(),
+        }
+    }
+
+    fn keymap(&self) -> super::help::Keymap {
+        vec![
+            (
+                key_event!(Key::Enter),
+                Action::YankRing(YankRingAction::YankSelected).into(),
+            ),
+            (
+                key_event!('o'),
+                Action::YankRing(YankRingAction::OpenSelected).into(),
+            ),
+            (
+                key_event!('d'),
+                Action::YankRing(YankRingAction::DeleteSelected).into(),
+            ),
+        ]
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        match action {
+            Action::YankRing(yank_ring_action) => match yank_ring_action {
+                YankRingAction::YankSelected => return self.yank_selected(),
+                YankRingAction::OpenSelected => return self.open_selected(),
+                YankRingAction::DeleteSelected => self.delete_selected(),
+            },
+            Action::ScrollUp(n) => self.select(-(n as isize)),
+            Action::ScrollDown(n) => self.select(n as isize),
+            _ => return ActionResult::Ignored,
+        }
+        ActionResult::consumed()
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(Color::Yellow))
+            .title(tr("yank_ring.title"));
+        f.render_widget(Clear, area);
+
+        let yank_ring = yank_ring();
+        if yank_ring.all().is_empty() {
+            f.render_widget(
+                Paragraph::new(tr("yank_ring.empty"))
+                    .alignment(Alignment::Center)
+                    .wrap(Wrap { trim: true })
+                    .block(block),
+                area,
+            );
+            return;
+        }
+
+        let items: Vec<ListItem> = yank_ring
+            .all()
+            .iter()
+            .map(|yank| {
+                let kind = match yank.kind {
+                    YankKind::Link => tr("yank_ring.link"),
+                    YankKind::Passage => tr("yank_ring.passage"),
+                    YankKind::Citation => tr("yank_ring.citation"),
+                };
+                ListItem::new(Line::from(vec![
+                    Span::raw(format!("{}: ", kind)).dark_gray(),
+                    Span::raw(yank.text.clone()),
+                    Span::raw(format!(" ({})", yank.page)).dark_gray(),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(block)
+            .highlight_symbol("| ")
+            .highlight_spacing(HighlightSpacing::Always)
+            .highlight_style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::ITALIC),
+            );
+        f.render_stateful_widget(list, area, &mut self.state);
+    }
+}
@@ -1,10 +1,1109 @@
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Context, Result};
+use clap::ValueEnum;
 use directories::ProjectDirs;
-use std::path::PathBuf;
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    convert::TryFrom,
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+use toml_edit::{DocumentMut, ImDocument, Item, Table, TableLike};
+use tracing::{info, warn};
+use wiki_api::{
+    backend::BuiltinBackend, languages::Language, search::Namespace, transform::BuiltinTransform,
+    units::UnitSystem,
+};
 
 pub const DATA_ENV: &str = "WIKI_TUI_DATA";
 pub const CONFIG_ENV: &str = "WIKI_TUI_CONFIG";
 
+const CONFIG_FILE: &str = "config.toml";
+const BACKUP_EXTENSION: &str = "toml.bak";
+const TEMP_EXTENSION: &str = "toml.tmp";
+
+/// Version of the current config layout, stored in the `version` key of the config file
+const CONFIG_VERSION: i64 = 1;
+const VERSION_KEY: &str = "version";
+
+/// Upgrades a config from the version at the same index to the next version
+const MIGRATIONS: [fn(&mut DocumentMut); 1] = [migrate_unversioned];
+
+/// Settings of the layout used up to wiki-tui 0.8, by their dotted path, and what became of them.
+/// A setting of a table comes before the table, so it's moved before the rest is dropped
+const LEGACY_KEYS: [(&str, LegacyKey); 11] = [
+    (
+        "api.base_url",
+        LegacyKey::Dropped("the wiki follows 'api.language'"),
+    ),
+    (
+        "api.pre_language",
+        LegacyKey::Dropped("the wiki follows 'api.language'"),
+    ),
+    (
+        "api.post_language",
+        LegacyKey::Dropped("the wiki follows 'api.language'"),
+    ),
+    (
+        "api.language_changed_popup",
+        LegacyKey::Dropped("the popup was removed"),
+    ),
+    (
+        "api.article_language_changed_popup",
+        LegacyKey::Dropped("the popup was removed"),
+    ),
+    ("features.toc", LegacyKey::Moved("ui", "show_contents")),
+    ("features", LegacyKey::Dropped("links are always shown")),
+    (
+        "settings",
+        LegacyKey::Dropped("the contents sidebar is set up in 'ui'"),
+    ),
+    (
+        "theme",
+        LegacyKey::Dropped("the colors are chosen with 'ui.theme'"),
+    ),
+    (
+        "logging",
+        LegacyKey::Dropped("the log level is set with the WIKI_TUI_LOG variable"),
+    ),
+    (
+        "keybindings",
+        LegacyKey::Dropped("keys are bound with 'keys.sequences'"),
+    ),
+];
+
+/// Top-level keys of the old, flat config layout and the table they belong to now
+const FLAT_KEYS: [(&str, &str, &str); 5] = [
+    ("transforms", "parser", "transforms"),
+    ("tts_command", "tts", "command"),
+    ("show_contents", "ui", "show_contents"),
+    ("contents_width", "ui", "contents_width"),
+    ("logger_width", "ui", "logger_width"),
+];
+
+/// Where a setting of an old config layout went
+enum LegacyKey {
+    /// The setting was moved into the table, under the key
+    Moved(&'static str, &'static str),
+    /// The setting has no replacement, for the reason
+    Dropped(&'static str),
+}
+
+/// Stops looking for more problems after this many, in case removing a setting doesn't help
+const MAX_PROBLEMS: usize = 32;
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+static PROBLEMS: OnceLock<Vec<ConfigProblem>> = OnceLock::new();
+
+/// An invalid setting in the config file. The default value is used for it instead
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigProblem {
+    /// Dotted path of the setting, empty when the whole file is affected
+    pub key: String,
+    /// Line and column of the setting in the config file, both starting at 1
+    pub location: Option<(usize, usize)>,
+    /// What is wrong with the setting, including the expected format
+    pub message: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub api: ApiConfig,
+    pub behavior: BehaviorConfig,
+    pub cache: CacheConfig,
+    pub display: DisplayConfig,
+    pub export: ExportConfig,
+    pub hooks: HooksConfig,
+    pub keys: KeysConfig,
+    pub nearby: NearbyConfig,
+    pub parser: ParserConfig,
+    pub remote: RemoteConfig,
+    pub renderer: RendererConfig,
+    pub status: StatusConfig,
+    pub tts: TtsConfig,
+    pub ui: UiConfig,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ApiConfig {
+    /// Language pages are searched and loaded in at startup
+    pub language: Language,
+    /// Which API of the wiki pages are fetched from
+    pub backend: BuiltinBackend,
+    /// Whether to start in offline mode, where pages are only loaded from the page cache
+    pub offline: bool,
+    /// Whether to log every request to the wiki and its response to `requests.log`
+    pub log_requests: bool,
+    /// Whether to store the cookies set by the wikis in the data directory, so logins and
+    /// preferences are kept after closing wiki-tui
+    pub store_cookies: bool,
+    /// Kinds of pages that are searched. The results are grouped by their kind when there's more
+    /// than one
+    pub search_namespaces: Vec<SearchNamespace>,
+    /// A second language the reader knows, the titles of linked pages in it can be shown after
+    /// the links (see `renderer.language_hints`)
+    pub secondary_language: Option<Language>,
+    /// ZIM archive (an offline copy of a wiki made by Kiwix) pages are searched and read from
+    /// instead of the wiki, without any network
+    pub zim: Option<PathBuf>,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            language: Language::default(),
+            backend: BuiltinBackend::default(),
+            offline: false,
+            log_requests: false,
+            store_cookies: true,
+            search_namespaces: vec![SearchNamespace::Articles],
+            secondary_language: None,
+            zim: None,
+        }
+    }
+}
+
+/// A kind of pages that can be searched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchNamespace {
+    Articles,
+    Categories,
+    Templates,
+    Help,
+    Files,
+}
+
+impl SearchNamespace {
+    pub fn namespace(self) -> Namespace {
+        match self {
+            SearchNamespace::Articles => Namespace::Main,
+            SearchNamespace::Categories => Namespace::Category,
+            SearchNamespace::Templates => Namespace::Template,
+            SearchNamespace::Help => Namespace::Help,
+            SearchNamespace::Files => Namespace::File,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BehaviorConfig {
+    /// What the scroll keys do on a page
+    pub navigation: Navigation,
+    /// How many of the latest yanked links and passages are kept during a session
+    pub yank_ring_size: usize,
+    /// Minutes between checks whether the shown page was edited since it was loaded. `0`
+    /// disables the checks
+    pub update_check_interval: u64,
+    /// How many pages from the bookmarks, the history and the page cache are shown above the
+    /// results of a search. `0` only shows the results of the wiki
+    pub local_results: usize,
+    /// How many sentences of every section are shown in the outline of a section
+    pub outline_sentences: usize,
+    /// External command (and its arguments) printing the text of the clipboard, e.g.
+    /// `["wl-paste", "--no-newline"]`. The command of the platform is used when this is empty
+    pub paste_command: Vec<String>,
+}
+
+impl Default for BehaviorConfig {
+    fn default() -> Self {
+        Self {
+            navigation: Navigation::default(),
+            yank_ring_size: 20,
+            update_check_interval: 0,
+            local_results: 5,
+            outline_sentences: 2,
+            paste_command: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Navigation {
+    /// Scroll the page, the link selection stays where it is
+    #[default]
+    Scroll,
+    /// Move the link selection line by line, the page follows the selection
+    Select,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CacheConfig {
+    /// Largest size of the page cache in MiB, the least recently opened pages are removed once
+    /// it's exceeded. `0` doesn't limit the size
+    pub max_size: u64,
+    /// Pages that weren't opened for this many days are removed when purging the page cache
+    pub purge_age: u64,
+    /// Whether the images of a displayed page are downloaded into the image cache in the
+    /// background
+    pub prefetch_images: bool,
+    /// Largest size of the image cache in MiB, the images downloaded first are removed once it's
+    /// exceeded. `0` doesn't limit the size
+    pub max_image_size: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            max_size: 512,
+            purge_age: 30,
+            prefetch_images: false,
+            max_image_size: 128,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DisplayConfig {
+    /// System of units measurements in the text of pages are converted into, the converted
+    /// value is added in parentheses after the measurement. Pages aren't converted when unset
+    pub units: Option<UnitSystem>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExportConfig {
+    /// Directory exported pages are written to, defaults to `exports` in the data directory
+    pub directory: Option<PathBuf>,
+    /// Whether to turn citations into Markdown footnotes, with the references as their
+    /// definitions
+    pub footnotes: bool,
+    /// How links are written into plain text exports
+    pub text_links: TextLinks,
+    /// File citations of pages are added to, a CSL-JSON list when it ends with `.json` and a
+    /// BibTeX database otherwise
+    pub bibliography: Option<PathBuf>,
+    /// Whether to download the images of a page into its HTML export, instead of linking to
+    /// them
+    pub embed_images: bool,
+    /// Directory the original files of media are downloaded to, defaults to `media` in the
+    /// export directory
+    pub media_directory: Option<PathBuf>,
+    /// Where annotations are sent to as flashcards by `:anki send`
+    pub anki: AnkiConfig,
+}
+
+impl Default for ExportConfig {
+    fn default() -> Self {
+        Self {
+            directory: None,
+            footnotes: true,
+            text_links: TextLinks::default(),
+            bibliography: None,
+            embed_images: false,
+            media_directory: None,
+            anki: AnkiConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AnkiConfig {
+    /// Url of the AnkiConnect add-on of a running Anki
+    pub url: String,
+    /// Deck the cards are added to, it has to exist
+    pub deck: String,
+    /// Note type of the cards, it has to exist and have the configured fields
+    pub note_type: String,
+    /// Field of the note type the highlighted passage is written into
+    pub front_field: String,
+    /// Field of the note type the note is written into
+    pub back_field: String,
+    /// Field of the note type the title of the article is written into. Without one, a link to
+    /// the article is added to the back of the card
+    pub title_field: Option<String>,
+    /// Field of the note type the url of the article is written into
+    pub url_field: Option<String>,
+}
+
+impl Default for AnkiConfig {
+    fn default() -> Self {
+        Self {
+            url: "http://127.0.0.1:8765".to_string(),
+            deck: "wiki-tui".to_string(),
+            note_type: "Basic".to_string(),
+            front_field: "Front".to_string(),
+            back_field: "Back".to_string(),
+            title_field: None,
+            url_field: None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum TextLinks {
+    /// Numbered markers in the text, with the urls listed at the end of each section
+    #[default]
+    Footnotes,
+    /// The url in angle brackets right after the text of the link
+    Inline,
+}
+
+/// External commands (and their arguments) that are run on events, see [`crate::hooks`]. An
+/// empty command disables the hook
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HooksConfig {
+    /// Run when a page is displayed, with the `{title}`, `{url}` and `{language}` of the page
+    pub page_opened: Vec<String>,
+    /// Run when the selected link is yanked, with the `{title}`, `{url}` and `{language}` of the
+    /// linked page and the `{page}` the link is on
+    pub link_yanked: Vec<String>,
+    /// Run when highlighted passages are yanked, with their `{text}` and the `{page}` they are on
+    pub text_yanked: Vec<String>,
+    /// Run when a page is exported, with the `{title}`, `{url}` and `{language}` of the page and
+    /// the `{path}` of the exported file
+    pub article_exported: Vec<String>,
+}
+
+/// Sequences of keys, like `gg` in vim, see [`crate::key_sequences`]
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeysConfig {
+    /// Key that `<leader>` stands for in the sequences
+    pub leader: char,
+    /// How long to wait for the next key of a sequence, in milliseconds. Afterwards the keys
+    /// typed so far do what they do on their own
+    pub sequence_timeout: u64,
+    /// The sequences and what they do. Setting them replaces all default sequences
+    pub sequences: BTreeMap<String, KeyCommand>,
+}
+
+impl Default for KeysConfig {
+    fn default() -> Self {
+        Self {
+            leader: '\\',
+            sequence_timeout: 1000,
+            sequences: BTreeMap::from([
+                // `g` and `z` do something on their own, a sequence starting with them would
+                // delay them
+                ("<leader>gg".to_string(), KeyCommand::ScrollToTop),
+                ("<leader>zt".to_string(), KeyCommand::SelectionToTop),
+                ("<leader>zz".to_string(), KeyCommand::SelectionToCenter),
+                ("<leader>zb".to_string(), KeyCommand::SelectionToBottom),
+                ("<leader>b".to_string(), KeyCommand::Bookmarks),
+                ("<leader>h".to_string(), KeyCommand::History),
+                ("<leader>n".to_string(), KeyCommand::Notes),
+                ("<leader>r".to_string(), KeyCommand::ReadingQueue),
+            ]),
+        }
+    }
+}
+
+/// What a key sequence does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyCommand {
+    ScrollToTop,
+    ScrollToBottom,
+    HalfPageDown,
+    HalfPageUp,
+    /// Scrolls the page until the selected link is at the top
+    SelectionToTop,
+    /// Scrolls the page until the selected link is in the middle
+    SelectionToCenter,
+    /// Scrolls the page until the selected link is at the bottom
+    SelectionToBottom,
+    /// Focusses the search bar
+    Search,
+    Bookmarks,
+    History,
+    Notes,
+    ReadingQueue,
+    /// Opens the next page of the reading queue
+    ReadNext,
+    Feed,
+    Cache,
+    Workspaces,
+    Help,
+    CommandPalette,
+    Quit,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NearbyConfig {
+    /// Location used by `:nearby` when no coordinates are given, in the form of `lat,lon`
+    pub home: Option<String>,
+    /// Search radius in meters (at most 10000)
+    pub radius: u32,
+    /// Maximum amount of pages to list
+    pub limit: u32,
+}
+
+impl Default for NearbyConfig {
+    fn default() -> Self {
+        Self {
+            home: None,
+            radius: 10_000,
+            limit: 50,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ParserConfig {
+    /// Post-processing steps that are run over every parsed page, in order
+    pub transforms: Vec<BuiltinTransform>,
+    /// Whether to show maintenance templates, like "This article needs additional citations",
+    /// as notices at the top of an article or section
+    pub show_maintenance_banners: bool,
+}
+
+impl Default for ParserConfig {
+    fn default() -> Self {
+        Self {
+            transforms: BuiltinTransform::defaults(),
+            show_maintenance_banners: true,
+        }
+    }
+}
+
+/// A command other programs can send through the remote socket, see [`crate::remote`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RemoteAction {
+    Open,
+    Search,
+    Language,
+    Export,
+    Quit,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RemoteConfig {
+    /// Whether to listen for commands of other programs on the remote socket
+    pub enabled: bool,
+    /// Path of the socket, `wiki-tui.sock` in the data directory when not set
+    pub socket: Option<PathBuf>,
+    /// Commands that may be sent through the socket, the others are refused
+    pub allow: Vec<RemoteAction>,
+}
+
+impl Default for RemoteConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            socket: None,
+            allow: vec![
+                RemoteAction::Open,
+                RemoteAction::Search,
+                RemoteAction::Language,
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RendererConfig {
+    /// Character horizontal rules and section separators are drawn with
+    pub separator: char,
+    /// Whether to draw a line between the top-level sections of a page
+    pub section_separators: bool,
+    /// Whether to wrap long lines of preformatted blocks instead of letting them overflow, in
+    /// which case the page can be scrolled horizontally
+    pub wrap_preformatted: bool,
+    /// Whether to show the taxobox (or chemistry infobox) of a page. It can also be toggled
+    /// while reading
+    pub taxoboxes: bool,
+    /// How many columns a page is shown in
+    pub columns: Columns,
+    /// Whether links are marked with a symbol of their kind, so they can be told apart without
+    /// their colors
+    pub link_symbols: bool,
+    /// Whether to show the title linked pages have in `api.secondary_language` in parentheses
+    /// after the links
+    pub language_hints: bool,
+    /// Whether external links are colored by the kind of website they point to
+    pub link_domains: bool,
+    /// Kinds of websites by their domain, in addition to the built-in ones. Subdomains are of
+    /// the same kind
+    pub domains: BTreeMap<String, DomainCategory>,
+    /// Tables with more rows are cut off after this many rows until they are expanded. `0`
+    /// always shows every row
+    pub table_rows: usize,
+}
+
+impl Default for RendererConfig {
+    fn default() -> Self {
+        Self {
+            separator: '─',
+            section_separators: false,
+            wrap_preformatted: false,
+            taxoboxes: true,
+            columns: Columns::default(),
+            link_symbols: false,
+            language_hints: false,
+            link_domains: false,
+            domains: BTreeMap::new(),
+            table_rows: 50,
+        }
+    }
+}
+
+/// Kinds of websites external links point to, they are colored by it with
+/// `renderer.link_domains`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DomainCategory {
+    /// Wikipedia and the other Wikimedia projects
+    Wikimedia,
+    /// DOIs, journals and databases of papers
+    Journal,
+    News,
+    Other,
+}
+
+/// How many columns a page is shown in, set as `"auto"`, `1` or `2`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "ColumnsSetting")]
+pub enum Columns {
+    /// Two columns once the page is wide enough, otherwise one
+    Auto,
+    #[default]
+    One,
+    /// Two columns next to each other, read like a newspaper: the text continues at the top of
+    /// the right column
+    Two,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ColumnsSetting {
+    Count(u8),
+    Name(String),
+}
+
+impl TryFrom<ColumnsSetting> for Columns {
+    type Error = String;
+
+    fn try_from(setting: ColumnsSetting) -> Result<Self, Self::Error> {
+        match setting {
+            ColumnsSetting::Count(1) => Ok(Columns::One),
+            ColumnsSetting::Count(2) => Ok(Columns::Two),
+            ColumnsSetting::Name(name) if name == "auto" => Ok(Columns::Auto),
+            ColumnsSetting::Count(count) => Err(format!("invalid value: {count}, expected 1 or 2")),
+            ColumnsSetting::Name(name) => Err(format!(
+                "invalid value: \"{name}\", expected \"auto\", 1 or 2"
+            )),
+        }
+    }
+}
+
+/// Information the status bar can show
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatusSegment {
+    /// Title of the displayed page, including the heading of a section shown on its own
+    PageTitle,
+    /// Quality assessment of the displayed page
+    Quality,
+    /// Trend of the daily views of the displayed page and how often it's viewed on average
+    PageViews,
+    /// Language pages are loaded in
+    Language,
+    /// Whether the app is online or offline
+    Network,
+    /// How far the displayed page is scrolled, in percent
+    ScrollPercent,
+    /// Heading of the section at the top of the displayed page
+    Section,
+    /// The current time, formatted with `status.clock_format`
+    Clock,
+    /// Pages in the fetch queue that weren't fetched yet
+    TaskCount,
+    /// How to open the help
+    Help,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StatusConfig {
+    /// Segments aligned to the left of the status bar, in the order they are shown
+    pub left: Vec<StatusSegment>,
+    /// Segments centered in the status bar
+    pub center: Vec<StatusSegment>,
+    /// Segments aligned to the right of the status bar
+    pub right: Vec<StatusSegment>,
+    /// Text shown between two segments
+    pub separator: String,
+    /// Format of the clock, using the `strftime` syntax
+    pub clock_format: String,
+}
+
+impl StatusConfig {
+    /// Whether the segment is shown on any side of the status bar
+    pub fn shows(&self, segment: StatusSegment) -> bool {
+        [&self.left, &self.center, &self.right]
+            .iter()
+            .any(|segments| segments.contains(&segment))
+    }
+}
+
+impl Default for StatusConfig {
+    fn default() -> Self {
+        Self {
+            left: vec![StatusSegment::PageTitle, StatusSegment::Quality],
+            center: Vec::new(),
+            right: vec![
+                StatusSegment::Language,
+                StatusSegment::Network,
+                StatusSegment::Help,
+            ],
+            separator: " ".to_string(),
+            clock_format: "%H:%M".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TtsConfig {
+    /// External command (and its arguments) the spoken text is piped into, e.g. `["espeak-ng"]`.
+    /// Text-to-speech is disabled when this is empty
+    pub command: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UiConfig {
+    /// Whether to show the contents sidebar next to a page
+    pub show_contents: bool,
+    /// Width of the contents sidebar, in percent of the page width
+    pub contents_width: u16,
+    /// Width of the logger pane, in percent of the screen width
+    pub logger_width: u16,
+    /// How many renders (for different widths and renderers) of a page are kept. As many renders
+    /// of every top-level section are kept, so toggling a section only renders that section again
+    pub render_cache_size: usize,
+    /// Whether pages are opened with all of their top-level sections collapsed
+    pub collapse_sections: bool,
+    /// Whether pages are read page by page, turning to the next one instead of scrolling
+    pub paginate: bool,
+    /// Whether the render statistics of a page can be shown with [D]. They are always available
+    /// in debug builds
+    pub render_stats: bool,
+    /// Language of the UI (like `de`), the language pages are loaded in when not set
+    pub locale: Option<String>,
+    /// Which colors the terminal supports, colors it doesn't are replaced with the closest
+    /// supported ones
+    pub color_level: ColorLevel,
+    /// Built-in set of colors pages are shown in. It can also be switched while reading
+    pub theme: ThemePreset,
+}
+
+/// Built-in sets of colors, see [`crate::ui::Theme`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemePreset {
+    #[default]
+    Default,
+    /// Bright colors on the dark background, for low vision and washed out screens
+    HighContrast,
+    /// Blue and orange instead of red and green, for red-green color blindness
+    Deuteranopia,
+    /// Like `Deuteranopia`, but without dark reds, which look almost black with protanopia
+    Protanopia,
+}
+
+impl ThemePreset {
+    pub const ALL: [ThemePreset; 4] = [
+        ThemePreset::Default,
+        ThemePreset::HighContrast,
+        ThemePreset::Deuteranopia,
+        ThemePreset::Protanopia,
+    ];
+
+    /// Name of the preset in the config file
+    pub fn key(self) -> &'static str {
+        match self {
+            ThemePreset::Default => "default",
+            ThemePreset::HighContrast => "high_contrast",
+            ThemePreset::Deuteranopia => "deuteranopia",
+            ThemePreset::Protanopia => "protanopia",
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorLevel {
+    /// Detect the supported colors from the `COLORTERM` and `TERM` environment variables
+    #[default]
+    Auto,
+    /// Any RGB color
+    TrueColor,
+    /// The 256 color palette
+    Ansi256,
+    /// Only the 16 ANSI colors
+    Ansi16,
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        Self {
+            show_contents: true,
+            contents_width: 20,
+            logger_width: 50,
+            render_cache_size: 4,
+            collapse_sections: false,
+            paginate: false,
+            render_stats: false,
+            locale: None,
+            color_level: ColorLevel::default(),
+            theme: ThemePreset::default(),
+        }
+    }
+}
+
+/// Returns the loaded configuration
+///
+/// When no configuration was loaded yet, the default configuration is used
+pub fn config() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+/// Returns the problems found while loading the configuration file
+pub fn config_problems() -> &'static [ConfigProblem] {
+    PROBLEMS.get().map(Vec::as_slice).unwrap_or_default()
+}
+
+/// Reads the configuration file and makes it available through [`config`]
+///
+/// Configs with an older layout are upgraded (and written back) first. A missing configuration
+/// file is not an error, the default configuration is used instead. Invalid settings are
+/// reported through [`config_problems`] and replaced with their default value
+pub fn load_config() -> Result<()> {
+    let path = config_path()?;
+    let (config, problems) = if path.exists() {
+        info!("loading the config from '{}'", path.display());
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("unable to read the config file '{}'", path.display()))?;
+        parse_config(&path, &contents)
+    } else {
+        info!("no config file found, using the default config");
+        (Config::default(), Vec::new())
+    };
+
+    for problem in problems.iter() {
+        warn!(
+            "invalid setting '{}' in the config, using its default value: {}",
+            problem.key, problem.message
+        );
+    }
+
+    if CONFIG.set(config).is_err() || PROBLEMS.set(problems).is_err() {
+        warn!("the config was already loaded");
+    }
+
+    Ok(())
+}
+
+fn parse_config(path: &Path, contents: &str) -> (Config, Vec<ConfigProblem>) {
+    let mut document: DocumentMut = match contents.parse() {
+        Ok(document) => document,
+        Err(error) => {
+            let error: toml_edit::TomlError = error;
+            let problem = ConfigProblem {
+                key: String::new(),
+                location: error.span().map(|span| line_column(contents, span.start)),
+                message: error.message().to_string(),
+            };
+            return (Config::default(), vec![problem]);
+        }
+    };
+
+    if migrate(&mut document) {
+        match write_config(path, &document.to_string()) {
+            Ok(_) => info!("upgraded the config file to version {}", CONFIG_VERSION),
+            Err(error) => warn!("unable to save the upgraded config: {:?}", error),
+        }
+    }
+
+    let original = ImDocument::parse(contents).ok();
+    deserialize_config(document, |key| {
+        let original = original.as_ref()?;
+        key_offset(original.as_table(), key).map(|offset| line_column(contents, offset))
+    })
+}
+
+/// Deserializes the config, removing every setting that can't be deserialized until the rest of
+/// the config is valid
+///
+/// `locate` returns the location of a setting in the config file. The positions in the document
+/// itself can't be used, because the document might have been upgraded
+fn deserialize_config(
+    mut document: DocumentMut,
+    locate: impl Fn(&[String]) -> Option<(usize, usize)>,
+) -> (Config, Vec<ConfigProblem>) {
+    document.remove(VERSION_KEY);
+
+    let mut problems = Vec::new();
+    while problems.len() < MAX_PROBLEMS {
+        let contents = document.to_string();
+        let error = match toml::from_str(&contents) {
+            Ok(config) => return (config, problems),
+            Err(error) => error,
+        };
+
+        let key = ImDocument::parse(contents.as_str())
+            .ok()
+            .zip(error.span())
+            .and_then(|(parsed, span)| key_at(parsed.as_table(), span.start));
+        let key = match key {
+            Some(key) if remove_key(document.as_table_mut(), &key) => key,
+            // nothing can be removed to fix the config
+            _ => {
+                problems.push(ConfigProblem {
+                    key: String::new(),
+                    location: None,
+                    message: error.message().to_string(),
+                });
+                break;
+            }
+        };
+
+        problems.push(ConfigProblem {
+            location: locate(&key),
+            key: key.join("."),
+            message: error.message().to_string(),
+        });
+    }
+    (Config::default(), problems)
+}
+
+/// Returns the path of the innermost setting whose key or value contains the offset
+fn key_at(table: &dyn TableLike, offset: usize) -> Option<Vec<String>> {
+    let contains = |span: Option<std::ops::Range<usize>>| {
+        span.map(|span| span.contains(&offset)).unwrap_or(false)
+    };
+
+    for (name, item) in table.iter() {
+        if let Some(mut key) = item.as_table_like().and_then(|table| key_at(table, offset)) {
+            key.insert(0, name.to_string());
+            return Some(key);
+        }
+
+        let is_key = table
+            .get_key_value(name)
+            .map(|(key, _)| contains(key.span()))
+            .unwrap_or(false);
+        if is_key || contains(item.span()) {
+            return Some(vec![name.to_string()]);
+        }
+    }
+    None
+}
+
+/// Returns the offset of the setting with the path in the parsed config file
+fn key_offset(table: &dyn TableLike, key: &[String]) -> Option<usize> {
+    let (name, rest) = key.split_first()?;
+    let (key, item) = table.get_key_value(name)?;
+    if rest.is_empty() {
+        return key.span().map(|span| span.start);
+    }
+    key_offset(item.as_table_like()?, rest)
+}
+
+/// Removes the setting with the path, returning whether it existed
+fn remove_key(table: &mut dyn TableLike, key: &[String]) -> bool {
+    match key.split_first() {
+        Some((name, [])) => table.remove(name).is_some(),
+        Some((name, rest)) => table
+            .get_mut(name)
+            .and_then(|item| item.as_table_like_mut())
+            .map(|table| remove_key(table, rest))
+            .unwrap_or(false),
+        None => false,
+    }
+}
+
+/// Converts a byte offset into a line and column, both starting at 1
+fn line_column(contents: &str, offset: usize) -> (usize, usize) {
+    let before = &contents[..offset.min(contents.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rsplit('\n')
+        .next()
+        .map(|line| line.chars().count())
+        .unwrap_or(0)
+        + 1;
+    (line, column)
+}
+
+/// Upgrades the config to the current layout. Returns whether the config was changed
+fn migrate(document: &mut DocumentMut) -> bool {
+    let version = document
+        .get(VERSION_KEY)
+        .and_then(|x| x.as_integer())
+        .unwrap_or(0);
+
+    if version > CONFIG_VERSION {
+        warn!(
+            "the config was written by a newer version of wiki-tui (version {}), some settings \
+             might be ignored",
+            version
+        );
+        return false;
+    }
+    if version == CONFIG_VERSION {
+        return false;
+    }
+
+    for migration in MIGRATIONS.iter().skip(version.max(0) as usize) {
+        migration(document);
+    }
+    document[VERSION_KEY] = toml_edit::value(CONFIG_VERSION);
+    true
+}
+
+/// Upgrades a config written before the config was versioned, in the layout of wiki-tui 0.8 or
+/// in the flat layout that followed it
+fn migrate_unversioned(document: &mut DocumentMut) {
+    migrate_legacy_keys(document);
+    migrate_flat_keys(document);
+}
+
+/// Removes the setting with the dotted path, returning it
+fn take_key(table: &mut dyn TableLike, key: &str) -> Option<Item> {
+    match key.split_once('.') {
+        Some((name, rest)) => take_key(table.get_mut(name)?.as_table_like_mut()?, rest),
+        None => table.remove(key),
+    }
+}
+
+/// Moves the setting into the table, unless the table sets it already
+fn move_key(document: &mut DocumentMut, old_key: &str, value: Item, table: &str, key: &str) {
+    if !document.contains_table(table) {
+        document[table] = Item::Table(Table::new());
+    }
+    if document[table].get(key).is_some() {
+        warn!(
+            "'{}' is deprecated and was removed, '{}.{}' is already set",
+            old_key, table, key
+        );
+        return;
+    }
+
+    warn!(
+        "'{}' is deprecated and was moved to '{}.{}'",
+        old_key, table, key
+    );
+    document[table][key] = value;
+}
+
+/// Moves or removes the settings of the layout used up to wiki-tui 0.8
+fn migrate_legacy_keys(document: &mut DocumentMut) {
+    for (old_key, legacy_key) in LEGACY_KEYS {
+        let value = match take_key(document.as_table_mut(), old_key) {
+            Some(value) => value,
+            None => continue,
+        };
+
+        match legacy_key {
+            LegacyKey::Moved(table, key) => move_key(document, old_key, value, table, key),
+            LegacyKey::Dropped(reason) => {
+                warn!(
+                    "'{}' is no longer supported and was removed, {}",
+                    old_key, reason
+                )
+            }
+        }
+    }
+}
+
+/// Moves the keys of the old, flat layout into their tables
+fn migrate_flat_keys(document: &mut DocumentMut) {
+    for (old_key, table, key) in FLAT_KEYS {
+        if let Some(value) = document.remove(old_key) {
+            move_key(document, old_key, value, table, key);
+        }
+    }
+}
+
+/// Replaces the config file atomically, keeping a backup of the previous file
+fn write_config(path: &Path, contents: &str) -> Result<()> {
+    std::fs::create_dir_all(config_dir()?)?;
+
+    if path.exists() {
+        let backup = path.with_extension(BACKUP_EXTENSION);
+        std::fs::copy(path, &backup).with_context(|| {
+            format!(
+                "unable to back up the config file to '{}'",
+                backup.display()
+            )
+        })?;
+    }
+
+    let temp = path.with_extension(TEMP_EXTENSION);
+    let mut file = File::create(&temp)
+        .with_context(|| format!("unable to create the file '{}'", temp.display()))?;
+    file.write_all(contents.as_bytes())
+        .and_then(|_| file.sync_all())
+        .with_context(|| format!("unable to write the file '{}'", temp.display()))?;
+
+    std::fs::rename(&temp, path)
+        .with_context(|| format!("unable to write the config file '{}'", path.display()))
+}
+
+/// Writes a single value into the configuration file
+///
+/// The rest of the file, including comments and formatting, is left untouched. The loaded
+/// configuration is not updated
+pub fn persist_value(table: &str, key: &str, value: impl Into<toml_edit::Value>) -> Result<()> {
+    let path = config_path()?;
+    let contents = if path.exists() {
+        std::fs::read_to_string(&path)
+            .with_context(|| format!("unable to read the config file '{}'", path.display()))?
+    } else {
+        String::new()
+    };
+
+    let mut document: DocumentMut = contents
+        .parse()
+        .with_context(|| format!("unable to parse the config file '{}'", path.display()))?;
+    if !document.contains_key(VERSION_KEY) {
+        document[VERSION_KEY] = toml_edit::value(CONFIG_VERSION);
+    }
+    set_value(&mut document, table, key, value.into())?;
+
+    write_config(&path, &document.to_string())
+}
+
+/// Sets the key of the table in the config document, adding the table when it's missing. Fails
+/// when the table is set to something else, like `ui = 1`
+fn set_value(
+    document: &mut DocumentMut,
+    table: &str,
+    key: &str,
+    value: toml_edit::Value,
+) -> Result<()> {
+    document
+        .entry(table)
+        .or_insert_with(toml_edit::table)
+        .as_table_like_mut()
+        .ok_or_else(|| anyhow!("'{}' in the config file isn't a table", table))?
+        .insert(key, toml_edit::value(value));
+    Ok(())
+}
+
+pub fn config_path() -> Result<PathBuf> {
+    Ok(config_dir()?.join(CONFIG_FILE))
+}
+
 pub fn project_dir() -> Option<ProjectDirs> {
     ProjectDirs::from("com", "builditluc", "wiki-tui")
 }
@@ -32,3 +1131,108 @@ pub fn config_dir() -> Result<PathBuf> {
 
     Ok(directory)
 }
+
+#[cfg(test)]
+mod tests {
+    use toml_edit::{DocumentMut, ImDocument};
+
+    use super::{
+        deserialize_config, key_offset, line_column, migrate, set_value, ConfigProblem,
+        CONFIG_VERSION,
+    };
+
+    #[test]
+    fn set_values() {
+        let mut document: DocumentMut = "version = 1\n\n[ui]\nlogger_width = 30\n".parse().unwrap();
+        set_value(&mut document, "ui", "contents_width", 25i64.into()).unwrap();
+        set_value(&mut document, "behavior", "navigation", "jump".into()).unwrap();
+        assert_eq!(document["ui"]["contents_width"].as_integer(), Some(25));
+        assert_eq!(document["ui"]["logger_width"].as_integer(), Some(30));
+        assert_eq!(document["behavior"]["navigation"].as_str(), Some("jump"));
+
+        // a value where the table should be isn't replaced
+        let mut document: DocumentMut = "ui = 1\n".parse().unwrap();
+        assert!(set_value(&mut document, "ui", "contents_width", 25i64.into()).is_err());
+        assert_eq!(document["ui"].as_integer(), Some(1));
+    }
+
+    #[test]
+    fn problems() {
+        let contents = "version = 1\n\n[ui]\ncontents_width = \"wide\"\nlogger_width = 30\nshow_content = false\n\n[behavior]\nnavigation = \"jump\"\n";
+        let document: DocumentMut = contents.parse().unwrap();
+        let original = ImDocument::parse(contents).unwrap();
+
+        let (config, problems) = deserialize_config(document, |key| {
+            key_offset(original.as_table(), key).map(|offset| line_column(contents, offset))
+        });
+
+        assert_eq!(config.ui.logger_width, 30);
+        assert_eq!(config.ui.contents_width, 20);
+        assert_eq!(problems.len(), 3);
+        assert_eq!(
+            problems[0],
+            ConfigProblem {
+                key: "ui.contents_width".to_string(),
+                location: Some((4, 1)),
+                message: "invalid type: string \"wide\", expected u16".to_string(),
+            }
+        );
+        // the message goes on to list every setting of the table
+        assert_eq!(problems[1].key, "ui.show_content");
+        assert_eq!(problems[1].location, Some((6, 1)));
+        assert!(problems[1]
+            .message
+            .starts_with("unknown field `show_content`, expected one of"));
+        assert_eq!(
+            problems[2],
+            ConfigProblem {
+                key: "behavior.navigation".to_string(),
+                location: Some((9, 1)),
+                message: "unknown variant `jump`, expected `scroll` or `select`".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn migrate_legacy_config() {
+        // a config file of wiki-tui 0.8
+        let contents = r#"
+[api]
+base_url = "https://en.wikipedia.org/"
+language = "de"
+language_changed_popup = false
+
+[theme]
+background = "white"
+text = "black"
+
+[theme.search_bar]
+title = "red"
+
+[logging]
+enabled = true
+log_level = "Info"
+
+[features]
+links = true
+toc = false
+
+[keybindings]
+down.key = "j"
+down.mode = "shift"
+
+[settings.toc]
+position = "left"
+min_width = 20
+max_width = 60
+"#;
+        let mut document: DocumentMut = contents.parse().unwrap();
+        assert!(migrate(&mut document));
+        assert_eq!(document["version"].as_integer(), Some(CONFIG_VERSION));
+
+        let (config, problems) = deserialize_config(document, |_| None);
+        assert!(problems.is_empty(), "{:?}", problems);
+        assert_eq!(config.api.language.code(), "de");
+        assert!(!config.ui.show_contents);
+    }
+}
@@ -0,0 +1,75 @@
+use std::{fs::OpenOptions, io::Write, path::PathBuf};
+
+use anyhow::{Context, Result};
+use tracing::{error, info};
+use wiki_api::cookies::{load_cookies, set_cookie_observer, CookieJar};
+
+use crate::config::{config, data_dir};
+
+const COOKIES_FILE: &str = "cookies.json";
+
+fn path() -> Result<PathBuf> {
+    Ok(data_dir()?.join(COOKIES_FILE))
+}
+
+fn load() -> Result<CookieJar> {
+    let path = path()?;
+    if !path.exists() {
+        return Ok(CookieJar::default());
+    }
+
+    info!("loading the cookies from '{}'", path.display());
+    let contents = std::fs::read_to_string(&path)
+        .with_context(|| format!("unable to read the cookies '{}'", path.display()))?;
+    serde_json::from_str(&contents)
+        .with_context(|| format!("unable to parse the cookies '{}'", path.display()))
+}
+
+/// Writes the cookies into the data directory. They can hold the login to a wiki, so only the
+/// user can read the file
+fn save(cookies: &CookieJar) -> Result<()> {
+    let path = path()?;
+    std::fs::create_dir_all(data_dir()?)?;
+
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    // the file is never readable by others, not even before its permissions are set
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(&path)
+        .with_context(|| format!("unable to create the cookies '{}'", path.display()))?;
+
+    // the mode only applies to new files, not to ones stored by an older version
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))
+            .with_context(|| format!("unable to protect the cookies '{}'", path.display()))?;
+    }
+
+    file.write_all(serde_json::to_string_pretty(cookies)?.as_bytes())
+        .with_context(|| format!("unable to write the cookies '{}'", path.display()))
+}
+
+/// Loads the cookies of the last session and stores them again whenever a wiki changes them, so
+/// a login to a wiki survives restarts. Without storing them, cookies are only kept until
+/// wiki-tui is closed
+pub fn initialize_cookies() {
+    if !config().api.store_cookies {
+        return;
+    }
+
+    match load() {
+        Ok(cookies) => load_cookies(cookies),
+        Err(error) => error!("unable to load the cookies: {:?}", error),
+    }
+    set_cookie_observer(|cookies| {
+        if let Err(error) = save(cookies) {
+            error!("unable to save the cookies: {:?}", error);
+        }
+    });
+}
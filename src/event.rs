@@ -5,6 +5,10 @@ use tokio_stream::StreamExt;
 use tokio_util::sync::CancellationToken;
 use tracing::warn;
 
+/// Resizes are reported once the terminal size didn't change for this long, so a resize storm
+/// results in a single event. Pages wait as long for the width they're shown in to settle
+pub const RESIZE_DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(50);
+
 #[derive(Clone, Copy, Debug)]
 pub enum Event {
     Quit,
@@ -32,10 +36,14 @@ impl EventHandler {
         let task = tokio::spawn(async move {
             let mut reader = crossterm::event::EventStream::new();
             let mut render_interval = tokio::time::interval(render_tick_rate);
+            let mut pending_resize: Option<((u16, u16), tokio::time::Instant)> = None;
 
             loop {
                 let render_delay = render_interval.tick();
                 let crossterm_event = reader.next().fuse();
+                let resize_deadline = pending_resize
+                    .map(|(_, deadline)| deadline)
+                    .unwrap_or_else(tokio::time::Instant::now);
                 tokio::select! {
                     _ = _stop_cancellation_token.cancelled() => break,
                     maybe_event = crossterm_event => match maybe_event {
@@ -44,7 +52,8 @@ impl EventHandler {
                                 event_tx.send(Event::Key(key)).unwrap();
                             },
                             CrosstermEvent::Resize(x, y) => {
-                                event_tx.send(Event::Resize(x, y)).unwrap();
+                                let deadline = tokio::time::Instant::now() + RESIZE_DEBOUNCE;
+                                pending_resize = Some(((x, y), deadline));
                             },
                             _ => {}
                         }
@@ -54,6 +63,11 @@ impl EventHandler {
                         None => {}
                     },
                     _ = render_delay => event_tx.send(Event::RenderTick).unwrap(),
+                    _ = tokio::time::sleep_until(resize_deadline), if pending_resize.is_some() => {
+                        if let Some(((x, y), _)) = pending_resize.take() {
+                            event_tx.send(Event::Resize(x, y)).unwrap();
+                        }
+                    }
                 }
             }
         });
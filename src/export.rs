@@ -0,0 +1,1205 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use clap::ValueEnum;
+use crossterm::style::{style, Attribute, Stylize};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use wiki_api::{
+    document::{Data, HeaderKind, Node},
+    page::{Page, Section},
+    Endpoint,
+};
+
+use crate::{
+    config::{config, data_dir, ExportConfig, TextLinks},
+    page_cache::file_name,
+};
+
+const EXPORT_DIRECTORY: &str = "exports";
+
+/// Formats an article can be printed in with `--dump`, or exported to with the remote `export`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DumpFormat {
+    /// Plain text, like exports with `:text`
+    #[default]
+    #[value(alias = "text")]
+    #[serde(alias = "text")]
+    Plain,
+    /// Plain text with the headers, emphasis and links styled for the terminal
+    Ansi,
+    /// Markdown with a table of contents, the headers carry their anchors as attributes
+    #[value(alias = "md")]
+    #[serde(alias = "md")]
+    Markdown,
+    /// Org with a table of contents, the headers carry their anchors as custom ids
+    Org,
+}
+/// Directory in the export directory media files are downloaded to, unless configured otherwise
+const MEDIA_DIRECTORY: &str = "media";
+
+/// Returns the full Wikipedia url of an article in the language
+pub fn article_url(language_code: &str, title: &str) -> String {
+    let url = format!(
+        "https://{}.wikipedia.org/wiki/{}",
+        language_code,
+        title.replace(' ', "_")
+    );
+    Endpoint::parse(&url)
+        .map(|url| url.to_string())
+        .unwrap_or(url)
+}
+
+/// Indents the lines of poems, it's kept at the start of a line
+const NON_BREAKING_SPACE: char = '\u{a0}';
+
+/// Removes the whitespace at the start of the text, except for the indentation of a poem
+fn trim_indentation(text: &str) -> &str {
+    text.trim_start_matches(|c: char| c.is_whitespace() && c != NON_BREAKING_SPACE)
+}
+
+/// Quotes a string so it can be used as a YAML value
+fn yaml_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Converts the document of a page into Markdown
+struct MarkdownWriter<'a> {
+    language_code: &'a str,
+    footnotes: bool,
+    /// Whether headers carry their anchors as attributes, which pandoc turns into identifiers
+    anchors: bool,
+
+    out: String,
+    /// Markers of the lists the writer is currently in, `None` for unordered lists
+    lists: Vec<Option<usize>>,
+    /// Reference texts, by their number. Only collected when footnotes are enabled
+    definitions: BTreeMap<usize, String>,
+}
+
+impl<'a> MarkdownWriter<'a> {
+    fn new(language_code: &'a str, footnotes: bool, anchors: bool) -> Self {
+        Self {
+            language_code,
+            footnotes,
+            anchors,
+            out: String::new(),
+            lists: Vec::new(),
+            definitions: BTreeMap::new(),
+        }
+    }
+
+    fn is_line_start(&self) -> bool {
+        self.out.is_empty() || self.out.ends_with('\n')
+    }
+
+    fn ensure_newline(&mut self) {
+        if !self.is_line_start() {
+            self.out.push('\n');
+        }
+    }
+
+    /// Starts a new block, separated from the previous one by an empty line
+    fn start_block(&mut self) {
+        // blocks inside of list items are kept on the line of the item
+        if !self.lists.is_empty() {
+            return;
+        }
+        self.ensure_newline();
+        if !self.out.is_empty() && !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+
+    fn write_children(&mut self, node: Node<'_>) {
+        for child in node.children() {
+            self.write_node(child);
+        }
+    }
+
+    /// Writes the children of the node into a separate buffer and returns it
+    fn capture_children(&mut self, node: Node<'_>) -> String {
+        let out = std::mem::take(&mut self.out);
+        let lists = std::mem::take(&mut self.lists);
+        self.write_children(node);
+        self.lists = lists;
+        std::mem::replace(&mut self.out, out)
+    }
+
+    fn write_text(&mut self, text: &str) {
+        if self.is_line_start() || self.out.ends_with(' ') {
+            self.out.push_str(trim_indentation(text));
+        } else {
+            self.out.push_str(text);
+        }
+    }
+
+    fn write_emphasis(&mut self, node: Node<'_>, marker: &str) {
+        let text = self.capture_children(node);
+        if text.trim().is_empty() {
+            return self.write_text(&text);
+        }
+        self.write_text(&format!("{}{}{}", marker, text.trim(), marker));
+    }
+
+    fn write_link(&mut self, node: Node<'_>, url: &str) {
+        let text = self.capture_children(node);
+        let text = text.trim();
+        if text.is_empty() {
+            return self.write_text(&format!("<{}>", url));
+        }
+        self.write_text(&format!("[{}]({})", text, url));
+    }
+
+    fn write_list_item(&mut self, node: Node<'_>, marker: String) {
+        self.ensure_newline();
+        let indent = "    ".repeat(self.lists.len().saturating_sub(1));
+        self.out.push_str(&format!("{}{} ", indent, marker));
+        self.write_children(node);
+    }
+
+    fn write_node(&mut self, node: Node<'_>) {
+        match node.data() {
+            Data::Text { contents } => self.write_text(contents),
+            Data::Header { id, kind } => {
+                self.start_block();
+                let level = match kind {
+                    HeaderKind::Main => 1,
+                    HeaderKind::Sub => 2,
+                    HeaderKind::Section => 3,
+                    HeaderKind::Subsection => 4,
+                    HeaderKind::Minor => 5,
+                    HeaderKind::Detail => 6,
+                };
+                let text = self.capture_children(node);
+                self.out
+                    .push_str(&format!("{} {}", "#".repeat(level), text.trim()));
+                if self.anchors && !id.is_empty() {
+                    self.out.push_str(&format!(" {{#{}}}", anchor_id(id)));
+                }
+                self.out.push_str("\n\n");
+            }
+            Data::Paragraph
+            | Data::Poem
+            | Data::Hatnote
+            | Data::RedirectMessage
+            | Data::Disambiguation
+            | Data::MaintenanceNotice
+            | Data::NavboxTitle
+            | Data::NavboxGroup => {
+                self.start_block();
+                self.write_children(node);
+                self.start_block();
+            }
+            // a backslash at the end of a line is a hard line break
+            Data::LineBreak if self.is_line_start() => self.out.push('\n'),
+            Data::LineBreak => self.out.push_str("\\\n"),
+            // spaces at the start of a line would make a code block out of the poem
+            Data::PoemIndentation { level } => {
+                self.out
+                    .push_str(&NON_BREAKING_SPACE.to_string().repeat(2 * level));
+                self.write_children(node);
+            }
+            Data::HorizontalRule => {
+                self.start_block();
+                self.out.push_str("---\n\n");
+            }
+            // like other tables, taxoboxes are not exported
+            Data::Taxobox => {}
+            Data::Preformatted => {
+                self.start_block();
+                let text: String = node
+                    .descendants()
+                    .filter_map(|node| match node.data() {
+                        Data::Text { contents } => Some(contents.as_str()),
+                        _ => None,
+                    })
+                    .collect();
+                self.out
+                    .push_str(&format!("```\n{}\n```\n\n", text.trim_end_matches('\n')));
+            }
+            Data::OrderedList | Data::UnorderedList => {
+                self.start_block();
+                let marker = match node.data() {
+                    Data::OrderedList => Some(1),
+                    _ => None,
+                };
+                self.lists.push(marker);
+                self.write_children(node);
+                self.lists.pop();
+                self.start_block();
+            }
+            Data::ListItem => {
+                let marker = match self.lists.last_mut() {
+                    Some(Some(n)) => {
+                        *n += 1;
+                        format!("{}.", *n - 1)
+                    }
+                    _ => "-".to_string(),
+                };
+                self.write_list_item(node, marker);
+            }
+            Data::DescriptionListTerm => {
+                self.start_block();
+                self.write_emphasis(node, "**");
+                self.ensure_newline();
+            }
+            Data::DerscriptionListDescription => {
+                self.ensure_newline();
+                self.out.push_str(": ");
+                self.write_children(node);
+                self.ensure_newline();
+            }
+            Data::Bold => self.write_emphasis(node, "**"),
+            Data::Italic => self.write_emphasis(node, "*"),
+            Data::Strikethrough => self.write_emphasis(node, "~~"),
+            Data::WikiLink { href, .. } => {
+                // the arrows linking back to the citations of a reference
+                if href.contains("#cite_ref-") {
+                    return;
+                }
+                let url = article_url(self.language_code, href.trim_start_matches("./"));
+                self.write_link(node, &url);
+            }
+            Data::ExternalLink {
+                href, autonumber, ..
+            } => {
+                if *autonumber {
+                    return self.write_text(&format!(" <{}>", href));
+                }
+                self.write_link(node, href);
+            }
+            Data::Citation { number, .. } => {
+                let citation = if self.footnotes {
+                    format!("[^{}]", number)
+                } else {
+                    format!("[{}]", number)
+                };
+                self.out.push_str(&citation);
+            }
+            Data::Reference { number, .. } if self.footnotes => {
+                let text = self.capture_children(node);
+                let text = text
+                    .split_whitespace()
+                    .collect::<Vec<&str>>()
+                    .join(" ")
+                    .trim_start_matches(|c: char| c == '↑' || c == '^' || c.is_whitespace())
+                    .to_string();
+                self.definitions.entry(*number).or_insert(text);
+            }
+            Data::Reference { number, .. } => {
+                self.write_list_item(node, format!("{}.", number));
+            }
+            _ => self.write_children(node),
+        }
+    }
+
+    fn finish(mut self) -> String {
+        if !self.definitions.is_empty() {
+            self.start_block();
+            for (number, text) in self.definitions.iter() {
+                self.out.push_str(&format!("[^{}]: {}\n", number, text));
+            }
+        }
+
+        let lines: Vec<&str> = self.out.lines().map(|line| line.trim_end()).collect();
+        let mut markdown = lines.join("\n").trim().to_string();
+        while markdown.contains("\n\n\n") {
+            markdown = markdown.replace("\n\n\n", "\n\n");
+        }
+        markdown.push('\n');
+        markdown
+    }
+}
+
+/// Converts the document of a page into plain text, keeping the targets of its links
+struct TextWriter<'a> {
+    language_code: &'a str,
+    links: TextLinks,
+    /// Whether headers, emphasis and links are styled with terminal escape codes
+    ansi: bool,
+
+    out: String,
+    /// Markers of the lists the writer is currently in, `None` for unordered lists
+    lists: Vec<Option<usize>>,
+    /// Urls of the links in the current section that still have to be listed, with their number
+    footnotes: Vec<(usize, String)>,
+    /// Links are numbered through the whole page
+    footnote_count: usize,
+}
+
+impl<'a> TextWriter<'a> {
+    fn new(language_code: &'a str, links: TextLinks, ansi: bool) -> Self {
+        Self {
+            language_code,
+            links,
+            ansi,
+            out: String::new(),
+            lists: Vec::new(),
+            footnotes: Vec::new(),
+            footnote_count: 0,
+        }
+    }
+
+    fn is_line_start(&self) -> bool {
+        self.out.is_empty() || self.out.ends_with('\n')
+    }
+
+    fn ensure_newline(&mut self) {
+        if !self.is_line_start() {
+            self.out.push('\n');
+        }
+    }
+
+    /// Starts a new block, separated from the previous one by an empty line
+    fn start_block(&mut self) {
+        // blocks inside of list items are kept on the line of the item
+        if !self.lists.is_empty() {
+            return;
+        }
+        self.ensure_newline();
+        if !self.out.is_empty() && !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+
+    fn write_children(&mut self, node: Node<'_>) {
+        for child in node.children() {
+            self.write_node(child);
+        }
+    }
+
+    /// Writes the children of the node into a separate buffer and returns it
+    fn capture_children(&mut self, node: Node<'_>) -> String {
+        let out = std::mem::take(&mut self.out);
+        let lists = std::mem::take(&mut self.lists);
+        self.write_children(node);
+        self.lists = lists;
+        std::mem::replace(&mut self.out, out)
+    }
+
+    fn write_text(&mut self, text: &str) {
+        if self.is_line_start() || self.out.ends_with(' ') {
+            self.out.push_str(trim_indentation(text));
+        } else {
+            self.out.push_str(text);
+        }
+    }
+
+    /// Returns the text with the attribute, when the text is styled at all
+    fn styled(&self, text: &str, attribute: Attribute) -> String {
+        match self.ansi && !text.trim().is_empty() {
+            true => style(text).attribute(attribute).to_string(),
+            false => text.to_string(),
+        }
+    }
+
+    fn write_styled(&mut self, node: Node<'_>, attribute: Attribute) {
+        let text = self.capture_children(node);
+        let text = self.styled(&text, attribute);
+        self.write_text(&text);
+    }
+
+    fn write_link(&mut self, text: &str, url: &str) {
+        let text = self.styled(text.trim(), Attribute::Underlined);
+        let text = text.as_str();
+        match self.links {
+            TextLinks::Inline if text.is_empty() => self.write_text(&format!("<{}>", url)),
+            TextLinks::Inline => self.write_text(&format!("{} <{}>", text, url)),
+            TextLinks::Footnotes => {
+                self.footnote_count += 1;
+                self.footnotes.push((self.footnote_count, url.to_string()));
+                self.write_text(&format!("{}[{}]", text, self.footnote_count));
+            }
+        }
+    }
+
+    /// Lists the urls of the links written since the last section started
+    fn write_footnotes(&mut self) {
+        if self.footnotes.is_empty() {
+            return;
+        }
+        self.start_block();
+        for (number, url) in std::mem::take(&mut self.footnotes) {
+            self.out.push_str(&format!("[{}] {}\n", number, url));
+        }
+    }
+
+    fn write_list_item(&mut self, node: Node<'_>, marker: String) {
+        self.ensure_newline();
+        let indent = "    ".repeat(self.lists.len().saturating_sub(1));
+        self.out.push_str(&format!("{}{} ", indent, marker));
+        self.write_children(node);
+    }
+
+    fn write_node(&mut self, node: Node<'_>) {
+        match node.data() {
+            Data::Text { contents } => self.write_text(contents),
+            Data::Header { kind, .. } => {
+                // the links of a section are listed before the next one starts
+                self.write_footnotes();
+                self.start_block();
+                let text = self.capture_children(node);
+                let text = text.trim();
+                let underline = match kind {
+                    HeaderKind::Main | HeaderKind::Sub => Some('='),
+                    HeaderKind::Section => Some('-'),
+                    _ => None,
+                };
+                let styled = self.styled(text, Attribute::Bold);
+                self.out.push_str(&styled);
+                self.out.push('\n');
+                if let Some(underline) = underline {
+                    let width = text.chars().count();
+                    self.out.push_str(&underline.to_string().repeat(width));
+                    self.out.push('\n');
+                }
+                self.out.push('\n');
+            }
+            Data::Paragraph
+            | Data::Poem
+            | Data::Hatnote
+            | Data::RedirectMessage
+            | Data::Disambiguation
+            | Data::MaintenanceNotice
+            | Data::NavboxTitle
+            | Data::NavboxGroup => {
+                self.start_block();
+                self.write_children(node);
+                self.start_block();
+            }
+            Data::LineBreak => self.out.push('\n'),
+            Data::PoemIndentation { level } => {
+                self.out.push_str(&"  ".repeat(*level));
+                self.write_children(node);
+            }
+            Data::HorizontalRule => {
+                self.start_block();
+                self.out.push_str("---\n\n");
+            }
+            // like in Markdown exports, taxoboxes are left out
+            Data::Taxobox => {}
+            Data::Preformatted => {
+                self.start_block();
+                let text: String = node
+                    .descendants()
+                    .filter_map(|node| match node.data() {
+                        Data::Text { contents } => Some(contents.as_str()),
+                        _ => None,
+                    })
+                    .collect();
+                for line in text.trim_end_matches('\n').lines() {
+                    self.out.push_str(&format!("    {}\n", line));
+                }
+                self.out.push('\n');
+            }
+            Data::OrderedList | Data::UnorderedList => {
+                self.start_block();
+                let marker = match node.data() {
+                    Data::OrderedList => Some(1),
+                    _ => None,
+                };
+                self.lists.push(marker);
+                self.write_children(node);
+                self.lists.pop();
+                self.start_block();
+            }
+            Data::ListItem => {
+                let marker = match self.lists.last_mut() {
+                    Some(Some(n)) => {
+                        *n += 1;
+                        format!("{}.", *n - 1)
+                    }
+                    _ => "-".to_string(),
+                };
+                self.write_list_item(node, marker);
+            }
+            Data::DescriptionListTerm => {
+                self.start_block();
+                self.write_children(node);
+                self.ensure_newline();
+            }
+            Data::DerscriptionListDescription => {
+                self.ensure_newline();
+                self.out.push_str("    ");
+                self.write_children(node);
+                self.ensure_newline();
+            }
+            Data::WikiLink { href, .. } => {
+                // the arrows linking back to the citations of a reference
+                if href.contains("#cite_ref-") {
+                    return;
+                }
+                let url = article_url(self.language_code, href.trim_start_matches("./"));
+                let text = self.capture_children(node);
+                self.write_link(&text, &url);
+            }
+            Data::ExternalLink {
+                href, autonumber, ..
+            } => {
+                // the text of numbered links is only their number, which says nothing
+                let text = match autonumber {
+                    true => String::new(),
+                    false => self.capture_children(node),
+                };
+                if text.is_empty() {
+                    self.out.push(' ');
+                }
+                self.write_link(&text, href);
+            }
+            Data::Bold => self.write_styled(node, Attribute::Bold),
+            Data::Italic => self.write_styled(node, Attribute::Italic),
+            Data::Strikethrough => self.write_styled(node, Attribute::CrossedOut),
+            // the numbers of citations would be mistaken for the numbers of links
+            Data::Citation { .. } => {}
+            Data::Reference { number, .. } => {
+                self.write_list_item(node, format!("{}.", number));
+            }
+            _ => self.write_children(node),
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.write_footnotes();
+
+        let lines: Vec<&str> = self.out.lines().map(|line| line.trim_end()).collect();
+        let mut text = lines.join("\n").trim().to_string();
+        while text.contains("\n\n\n") {
+            text = text.replace("\n\n\n", "\n\n");
+        }
+        text.push('\n');
+        text
+    }
+}
+
+/// Converts the document of a page into Org
+struct OrgWriter<'a> {
+    language_code: &'a str,
+    footnotes: bool,
+
+    out: String,
+    /// Markers of the lists the writer is currently in, `None` for unordered lists
+    lists: Vec<Option<usize>>,
+    /// Reference texts, by their number. Only collected when footnotes are enabled
+    definitions: BTreeMap<usize, String>,
+}
+
+impl<'a> OrgWriter<'a> {
+    fn new(language_code: &'a str, footnotes: bool) -> Self {
+        Self {
+            language_code,
+            footnotes,
+            out: String::new(),
+            lists: Vec::new(),
+            definitions: BTreeMap::new(),
+        }
+    }
+
+    fn is_line_start(&self) -> bool {
+        self.out.is_empty() || self.out.ends_with('\n')
+    }
+
+    fn ensure_newline(&mut self) {
+        if !self.is_line_start() {
+            self.out.push('\n');
+        }
+    }
+
+    /// Starts a new block, separated from the previous one by an empty line
+    fn start_block(&mut self) {
+        // blocks inside of list items are kept on the line of the item
+        if !self.lists.is_empty() {
+            return;
+        }
+        self.ensure_newline();
+        if !self.out.is_empty() && !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+
+    fn write_children(&mut self, node: Node<'_>) {
+        for child in node.children() {
+            self.write_node(child);
+        }
+    }
+
+    /// Writes the children of the node into a separate buffer and returns it
+    fn capture_children(&mut self, node: Node<'_>) -> String {
+        let out = std::mem::take(&mut self.out);
+        let lists = std::mem::take(&mut self.lists);
+        self.write_children(node);
+        self.lists = lists;
+        std::mem::replace(&mut self.out, out)
+    }
+
+    fn write_text(&mut self, text: &str) {
+        if self.is_line_start() || self.out.ends_with(' ') {
+            self.out.push_str(trim_indentation(text));
+        } else {
+            self.out.push_str(text);
+        }
+    }
+
+    fn write_emphasis(&mut self, node: Node<'_>, marker: &str) {
+        let text = self.capture_children(node);
+        if text.trim().is_empty() {
+            return self.write_text(&text);
+        }
+        self.write_text(&format!("{}{}{}", marker, text.trim(), marker));
+    }
+
+    fn write_link(&mut self, node: Node<'_>, url: &str) {
+        let text = self.capture_children(node);
+        let text = text.trim();
+        if text.is_empty() {
+            return self.write_text(&format!("[[{}]]", url));
+        }
+        self.write_text(&format!("[[{}][{}]]", url, text));
+    }
+
+    fn write_list_item(&mut self, node: Node<'_>, marker: String) {
+        self.ensure_newline();
+        let indent = "    ".repeat(self.lists.len().saturating_sub(1));
+        self.out.push_str(&format!("{}{} ", indent, marker));
+        self.write_children(node);
+    }
+
+    fn write_node(&mut self, node: Node<'_>) {
+        match node.data() {
+            Data::Text { contents } => self.write_text(contents),
+            Data::Header { id, kind } => {
+                self.start_block();
+                // the title is a keyword, so the sections of the page start at the top level
+                let level = match kind {
+                    HeaderKind::Main | HeaderKind::Sub => 1,
+                    HeaderKind::Section => 2,
+                    HeaderKind::Subsection => 3,
+                    HeaderKind::Minor => 4,
+                    HeaderKind::Detail => 5,
+                };
+                let text = self.capture_children(node);
+                self.out
+                    .push_str(&format!("{} {}\n", "*".repeat(level), text.trim()));
+                if !id.is_empty() {
+                    self.out.push_str(&format!(
+                        ":PROPERTIES:\n:CUSTOM_ID: {}\n:END:\n",
+                        anchor_id(id)
+                    ));
+                }
+                self.out.push('\n');
+            }
+            Data::Paragraph
+            | Data::Poem
+            | Data::Hatnote
+            | Data::RedirectMessage
+            | Data::Disambiguation
+            | Data::MaintenanceNotice
+            | Data::NavboxTitle
+            | Data::NavboxGroup => {
+                self.start_block();
+                self.write_children(node);
+                self.start_block();
+            }
+            // two backslashes at the end of a line are a hard line break
+            Data::LineBreak if self.is_line_start() => self.out.push('\n'),
+            Data::LineBreak => self.out.push_str("\\\\\n"),
+            Data::PoemIndentation { level } => {
+                self.out
+                    .push_str(&NON_BREAKING_SPACE.to_string().repeat(2 * level));
+                self.write_children(node);
+            }
+            Data::HorizontalRule => {
+                self.start_block();
+                self.out.push_str("-----\n\n");
+            }
+            // like in Markdown exports, taxoboxes are left out
+            Data::Taxobox => {}
+            Data::Preformatted => {
+                self.start_block();
+                let text: String = node
+                    .descendants()
+                    .filter_map(|node| match node.data() {
+                        Data::Text { contents } => Some(contents.as_str()),
+                        _ => None,
+                    })
+                    .collect();
+                self.out.push_str(&format!(
+                    "#+BEGIN_EXAMPLE\n{}\n#+END_EXAMPLE\n\n",
+                    text.trim_end_matches('\n')
+                ));
+            }
+            Data::OrderedList | Data::UnorderedList => {
+                self.start_block();
+                let marker = match node.data() {
+                    Data::OrderedList => Some(1),
+                    _ => None,
+                };
+                self.lists.push(marker);
+                self.write_children(node);
+                self.lists.pop();
+                self.start_block();
+            }
+            Data::ListItem => {
+                let marker = match self.lists.last_mut() {
+                    Some(Some(n)) => {
+                        *n += 1;
+                        format!("{}.", *n - 1)
+                    }
+                    _ => "-".to_string(),
+                };
+                self.write_list_item(node, marker);
+            }
+            Data::DescriptionListTerm => {
+                self.start_block();
+                self.write_emphasis(node, "*");
+                self.ensure_newline();
+            }
+            Data::DerscriptionListDescription => {
+                self.ensure_newline();
+                self.out.push_str("  ");
+                self.write_children(node);
+                self.ensure_newline();
+            }
+            Data::Bold => self.write_emphasis(node, "*"),
+            Data::Italic => self.write_emphasis(node, "/"),
+            Data::Strikethrough => self.write_emphasis(node, "+"),
+            Data::WikiLink { href, .. } => {
+                // the arrows linking back to the citations of a reference
+                if href.contains("#cite_ref-") {
+                    return;
+                }
+                let url = article_url(self.language_code, href.trim_start_matches("./"));
+                self.write_link(node, &url);
+            }
+            Data::ExternalLink {
+                href, autonumber, ..
+            } => {
+                if *autonumber {
+                    return self.write_text(&format!(" [[{}]]", href));
+                }
+                self.write_link(node, href);
+            }
+            Data::Citation { number, .. } => {
+                let citation = if self.footnotes {
+                    format!("[fn:{}]", number)
+                } else {
+                    format!("[{}]", number)
+                };
+                self.out.push_str(&citation);
+            }
+            Data::Reference { number, .. } if self.footnotes => {
+                let text = self.capture_children(node);
+                let text = text
+                    .split_whitespace()
+                    .collect::<Vec<&str>>()
+                    .join(" ")
+                    .trim_start_matches(|c: char| c == '↑' || c == '^' || c.is_whitespace())
+                    .to_string();
+                self.definitions.entry(*number).or_insert(text);
+            }
+            Data::Reference { number, .. } => {
+                self.write_list_item(node, format!("{}.", number));
+            }
+            _ => self.write_children(node),
+        }
+    }
+
+    fn finish(mut self) -> String {
+        if !self.definitions.is_empty() {
+            self.start_block();
+            for (number, text) in self.definitions.iter() {
+                self.out.push_str(&format!("[fn:{}] {}\n", number, text));
+            }
+        }
+
+        let lines: Vec<&str> = self.out.lines().map(|line| line.trim_end()).collect();
+        let mut org = lines.join("\n").trim().to_string();
+        while org.contains("\n\n\n") {
+            org = org.replace("\n\n\n", "\n\n");
+        }
+        org.push('\n');
+        org
+    }
+}
+
+/// Turns the anchor of a section into an identifier pandoc and Org accept, they only consist of
+/// letters, digits and a few punctuation characters
+fn anchor_id(anchor: &str) -> String {
+    anchor
+        .chars()
+        .map(
+            |c| match c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | ':') {
+                true => c,
+                false => '_',
+            },
+        )
+        .collect()
+}
+
+/// Returns the sections listed in a table of contents of the page with their depth, the
+/// outermost sections have a depth of 0
+fn toc_sections(page: &Page) -> Vec<(usize, &Section)> {
+    let sections: Vec<&Section> = page
+        .sections
+        .iter()
+        .flatten()
+        .filter(|section| section.index != 0)
+        .collect();
+    let top = sections
+        .iter()
+        .map(|section| section.header_kind.clone() as usize)
+        .min()
+        .unwrap_or_default();
+    sections
+        .into_iter()
+        .map(|section| (section.header_kind.clone() as usize - top, section))
+        .collect()
+}
+
+/// Returns a table of contents linking to the anchors of the sections, `None` for pages
+/// without sections. `link` formats the link to a section from its text and identifier
+fn table_of_contents(page: &Page, title: &str, link: fn(&str, &str) -> String) -> Option<String> {
+    let sections = toc_sections(page);
+    if sections.is_empty() {
+        return None;
+    }
+
+    let mut toc = format!("{}\n\n", title);
+    for (depth, section) in sections {
+        toc.push_str(&format!(
+            "{}- {}\n",
+            "    ".repeat(depth),
+            link(&section.text, &anchor_id(&section.anchor))
+        ));
+    }
+    Some(toc)
+}
+
+fn text(page: &Page, links: TextLinks, ansi: bool) -> String {
+    let mut writer = TextWriter::new(page.language.code(), links, ansi);
+    let mut node = page.content.nth(0);
+    while let Some(current) = node {
+        writer.write_node(current);
+        node = current.next();
+    }
+
+    let underline = "=".repeat(page.title.chars().count());
+    let title = writer.styled(&page.title, Attribute::Bold);
+    format!("{}\n{}\n\n{}", title, underline, writer.finish())
+}
+
+/// Converts a page into plain text, starting with its title. Depending on `links`, the urls of
+/// links are either written right after them or numbered and listed at the end of each section
+pub fn export_text(page: &Page, links: TextLinks) -> String {
+    text(page, links, false)
+}
+
+fn markdown(page: &Page, footnotes: bool, toc: bool, retrieved: DateTime<Utc>) -> String {
+    let language_code = page.language.code();
+
+    let mut frontmatter = vec![
+        "---".to_string(),
+        format!("title: {}", yaml_string(&page.title)),
+        format!(
+            "url: {}",
+            yaml_string(&article_url(language_code, &page.title))
+        ),
+        format!("language: {}", yaml_string(language_code)),
+        format!(
+            "retrieved: {}",
+            yaml_string(&retrieved.to_rfc3339_opts(SecondsFormat::Secs, true))
+        ),
+    ];
+    let sections: Vec<&str> = page
+        .sections
+        .iter()
+        .flatten()
+        .filter(|section| section.index != 0)
+        .map(|section| section.text.as_str())
+        .collect();
+    if sections.is_empty() {
+        frontmatter.push("sections: []".to_string());
+    } else {
+        frontmatter.push("sections:".to_string());
+        frontmatter.extend(
+            sections
+                .iter()
+                .map(|section| format!("  - {}", yaml_string(section))),
+        );
+    }
+    frontmatter.push("---".to_string());
+
+    let mut writer = MarkdownWriter::new(language_code, footnotes, toc);
+    let mut node = page.content.nth(0);
+    while let Some(current) = node {
+        writer.write_node(current);
+        node = current.next();
+    }
+
+    let toc = match toc {
+        true => table_of_contents(page, "**Contents**", |text, id| {
+            format!("[{}](#{})", text, id)
+        }),
+        false => None,
+    };
+    match toc {
+        Some(toc) => format!("{}\n\n{}\n{}", frontmatter.join("\n"), toc, writer.finish()),
+        None => format!("{}\n\n{}", frontmatter.join("\n"), writer.finish()),
+    }
+}
+
+/// Converts a page into Markdown, starting with a YAML frontmatter describing the page
+///
+/// Links to other articles are converted into full Wikipedia urls. When `footnotes` is set,
+/// citations become Markdown footnotes, with the references as their definitions at the end
+pub fn export_markdown(page: &Page, footnotes: bool, retrieved: DateTime<Utc>) -> String {
+    markdown(page, footnotes, false, retrieved)
+}
+
+/// Converts a page into Org, starting with keywords describing the page and a table of contents
+/// linking to the custom ids of the sections
+pub fn export_org(page: &Page, footnotes: bool, retrieved: DateTime<Utc>) -> String {
+    let keywords = [
+        format!("#+TITLE: {}", page.title),
+        format!("#+LANGUAGE: {}", page.language.code()),
+        format!("#+DATE: {}", retrieved.format("[%Y-%m-%d %a %H:%M]")),
+    ];
+
+    let mut writer = OrgWriter::new(page.language.code(), footnotes);
+    let mut node = page.content.nth(0);
+    while let Some(current) = node {
+        writer.write_node(current);
+        node = current.next();
+    }
+
+    match table_of_contents(page, "*Contents*", |text, id| {
+        format!("[[#{}][{}]]", id, text)
+    }) {
+        Some(toc) => format!("{}\n\n{}\n{}", keywords.join("\n"), toc, writer.finish()),
+        None => format!("{}\n\n{}", keywords.join("\n"), writer.finish()),
+    }
+}
+
+/// Converts a page into the format it's printed in with `--dump`. The Markdown and Org of dumps
+/// start with a table of contents linking to the sections, whose headers carry their anchors
+pub fn export_dump(
+    page: &Page,
+    format: DumpFormat,
+    links: TextLinks,
+    footnotes: bool,
+    retrieved: DateTime<Utc>,
+) -> String {
+    match format {
+        DumpFormat::Plain => text(page, links, false),
+        DumpFormat::Ansi => text(page, links, true),
+        DumpFormat::Markdown => markdown(page, footnotes, true, retrieved),
+        DumpFormat::Org => export_org(page, footnotes, retrieved),
+    }
+}
+
+/// Returns the directory exported pages are written to
+pub fn export_directory(export_config: &ExportConfig) -> Result<PathBuf> {
+    match export_config.directory {
+        Some(ref directory) => Ok(directory.clone()),
+        None => Ok(data_dir()?.join(EXPORT_DIRECTORY)),
+    }
+}
+
+/// Returns the directory the original files of media are downloaded to
+pub fn media_directory(export_config: &ExportConfig) -> Result<PathBuf> {
+    match export_config.media_directory {
+        Some(ref directory) => Ok(directory.clone()),
+        None => Ok(export_directory(export_config)?.join(MEDIA_DIRECTORY)),
+    }
+}
+
+/// Writes the export of the page into the export directory and returns the path of the file
+pub fn save_export(page: &Page, extension: &str, contents: String) -> Result<PathBuf> {
+    let directory = export_directory(&config().export)?;
+    std::fs::create_dir_all(&directory).with_context(|| {
+        format!(
+            "unable to create the export directory '{}'",
+            directory.display()
+        )
+    })?;
+
+    let path = directory.join(format!("{}.{}", file_name(&page.title), extension));
+    std::fs::write(&path, contents)
+        .with_context(|| format!("unable to write the export '{}'", path.display()))?;
+
+    info!("exported '{}' to '{}'", page.title, path.display());
+    Ok(path)
+}
+
+/// Exports the page as Markdown into the export directory and returns the path of the file
+pub fn save_markdown(page: &Page) -> Result<PathBuf> {
+    let markdown = export_markdown(page, config().export.footnotes, Utc::now());
+    save_export(page, "md", markdown)
+}
+
+/// Exports the page in the format to the path, like it's printed with `--dump`
+pub fn save_dump(page: &Page, format: DumpFormat, path: &Path) -> Result<()> {
+    let export = &config().export;
+    let contents = export_dump(
+        page,
+        format,
+        export.text_links,
+        export.footnotes,
+        Utc::now(),
+    );
+    std::fs::write(path, contents)
+        .with_context(|| format!("unable to write the export '{}'", path.display()))?;
+
+    info!("exported '{}' to '{}'", page.title, path.display());
+    Ok(())
+}
+
+/// Exports the page as plain text into the export directory and returns the path of the file
+pub fn save_text(page: &Page) -> Result<PathBuf> {
+    let text = export_text(page, config().export.text_links);
+    save_export(page, "txt", text)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use wiki_api::{
+        document::{Document, HeaderKind},
+        languages::Language,
+        page::{Page, Section},
+        parser::{Parser, WikipediaParser},
+        transform::{DocumentTransform, NumberReferences},
+    };
+
+    use super::{export_dump, export_markdown, export_text, DumpFormat};
+    use crate::config::TextLinks;
+
+    fn page() -> Page {
+        let content = Document {
+            nodes: WikipediaParser::parse_document(
+                r#"<section data-mw-section-id="0"><p><b>Rust</b> is a <a rel="mw:WikiLink" href="./Programming_language" title="Programming language">programming language</a>.<sup class="mw-ref reference"><a href="./Rust#cite_note-a-1"><span class="mw-reflink-text">[1]</span></a></sup></p></section><section data-mw-section-id="1"><h2 id="References">References</h2><ol class="mw-references references"><li id="cite_note-a-1"><span class="mw-reference-text">See <a rel="mw:ExtLink" href="https://www.rust-lang.org">the website</a>.</span></li></ol></section>"#,
+            )
+            .nodes(),
+        };
+        Page {
+            title: "Rust: \"the language\"".to_string(),
+            pageid: 0,
+            content: NumberReferences.transform(content),
+            language: Language::default(),
+            language_links: None,
+            sections: Some(vec![
+                Section {
+                    index: 0,
+                    header_kind: HeaderKind::Main,
+                    text: "(Top)".to_string(),
+                    number: "".to_string(),
+                    anchor: "Content_Top".to_string(),
+                },
+                Section {
+                    index: 1,
+                    header_kind: HeaderKind::Sub,
+                    text: "References".to_string(),
+                    number: "1".to_string(),
+                    anchor: "References".to_string(),
+                },
+            ]),
+            revision_id: None,
+            is_disambiguation: false,
+            quality: None,
+            parse_time: None,
+            redirected_from: None,
+        }
+    }
+
+    #[test]
+    fn markdown() {
+        let page = page();
+        let retrieved = chrono::Utc.with_ymd_and_hms(2023, 11, 2, 12, 0, 0).unwrap();
+
+        assert_eq!(
+            export_markdown(&page, true, retrieved),
+            r#"---
+title: "Rust: \"the language\""
+url: "https://en.wikipedia.org/wiki/Rust:_%22the_language%22"
+language: "en"
+retrieved: "2023-11-02T12:00:00Z"
+sections:
+  - "References"
+---
+
+**Rust** is a [programming language](https://en.wikipedia.org/wiki/Programming_language).[^1]
+
+## References
+
+[^1]: See [the website](https://www.rust-lang.org).
+"#
+        );
+        assert!(export_markdown(&page, false, retrieved)
+            .contains("language).[1]\n\n## References\n\n1. See [the website]"));
+
+        assert_eq!(
+            export_text(&page, TextLinks::Footnotes),
+            r#"Rust: "the language"
+====================
+
+Rust is a programming language[1].
+
+[1] https://en.wikipedia.org/wiki/Programming_language
+
+References
+==========
+
+1. See the website[2].
+
+[2] https://www.rust-lang.org
+"#
+        );
+        assert!(export_text(&page, TextLinks::Inline).contains(
+            "programming language <https://en.wikipedia.org/wiki/Programming_language>.\n\nReferences"
+        ));
+    }
+
+    #[test]
+    fn dump() {
+        let page = page();
+        let retrieved = chrono::Utc.with_ymd_and_hms(2023, 11, 2, 12, 0, 0).unwrap();
+        let dump = |format| export_dump(&page, format, TextLinks::Footnotes, true, retrieved);
+
+        assert_eq!(
+            dump(DumpFormat::Plain),
+            export_text(&page, TextLinks::Footnotes)
+        );
+        assert!(dump(DumpFormat::Ansi)
+            .contains("\u{1b}[1mRust\u{1b}[0m is a \u{1b}[4mprogramming language\u{1b}[0m[1]."));
+
+        let markdown = dump(DumpFormat::Markdown);
+        assert!(markdown.contains("---\n\n**Contents**\n\n- [References](#References)\n\n**Rust**"));
+        assert!(markdown.contains("\n## References {#References}\n"));
+
+        assert_eq!(
+            dump(DumpFormat::Org),
+            r#"#+TITLE: Rust: "the language"
+#+LANGUAGE: en
+#+DATE: [2023-11-02 Thu 12:00]
+
+*Contents*
+
+- [[#References][References]]
+
+*Rust* is a [[https://en.wikipedia.org/wiki/Programming_language][programming language]].[fn:1]
+
+* References
+:PROPERTIES:
+:CUSTOM_ID: References
+:END:
+
+[fn:1] See [[https://www.rust-lang.org][the website]].
+"#
+        );
+    }
+}
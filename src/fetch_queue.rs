@@ -0,0 +1,156 @@
+use std::{
+    path::PathBuf,
+    sync::{Mutex, MutexGuard, OnceLock},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+use wiki_api::languages::Language;
+
+use crate::config::data_dir;
+
+const FETCH_QUEUE_FILE: &str = "fetch_queue.json";
+
+static FETCH_QUEUE: OnceLock<Mutex<FetchQueue>> = OnceLock::new();
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FetchStatus {
+    Queued,
+    Fetching,
+    Fetched,
+    /// The fetch failed with the error
+    Failed(String),
+}
+
+impl FetchStatus {
+    /// Whether the page still has to be fetched
+    pub fn is_pending(&self) -> bool {
+        matches!(self, FetchStatus::Queued | FetchStatus::Failed(_))
+    }
+}
+
+/// A page that is fetched into the page cache later
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueuedPage {
+    pub title: String,
+    pub language: Language,
+    /// Unix timestamp of when the page was queued
+    pub added: i64,
+    pub status: FetchStatus,
+}
+
+/// Pages that are fetched once the app is online, or when requested, stored in the data
+/// directory
+#[derive(Default)]
+pub struct FetchQueue {
+    pages: Vec<QueuedPage>,
+}
+
+impl FetchQueue {
+    fn path() -> Result<PathBuf> {
+        Ok(data_dir()?.join(FETCH_QUEUE_FILE))
+    }
+
+    fn load() -> Result<Self> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        info!("loading the fetch queue from '{}'", path.display());
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("unable to read the fetch queue '{}'", path.display()))?;
+        let mut pages: Vec<QueuedPage> = serde_json::from_str(&contents)
+            .with_context(|| format!("unable to parse the fetch queue '{}'", path.display()))?;
+
+        // fetches that were running when the app was closed have to be started again
+        for page in pages.iter_mut() {
+            if page.status == FetchStatus::Fetching {
+                page.status = FetchStatus::Queued;
+            }
+        }
+        Ok(Self { pages })
+    }
+
+    fn save(&self) {
+        let result = Self::path().and_then(|path| {
+            std::fs::create_dir_all(data_dir()?)?;
+            std::fs::write(&path, serde_json::to_string_pretty(&self.pages)?)
+                .with_context(|| format!("unable to write the fetch queue '{}'", path.display()))
+        });
+
+        if let Err(error) = result {
+            error!("unable to save the fetch queue: {:?}", error);
+        }
+    }
+
+    pub fn all(&self) -> &[QueuedPage] {
+        &self.pages
+    }
+
+    /// Returns the pages that still have to be fetched
+    pub fn pending(&self) -> Vec<QueuedPage> {
+        self.pages
+            .iter()
+            .filter(|page| page.status.is_pending())
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the queued page with the title in the language. The same title can be queued in
+    /// several languages, each is fetched from its own wiki
+    fn find_mut(&mut self, title: &str, language: &Language) -> Option<&mut QueuedPage> {
+        self.pages
+            .iter_mut()
+            .find(|page| page.title == title && &page.language == language)
+    }
+
+    /// Queues the page, unless it's queued already. Fetched pages are queued again
+    pub fn add(&mut self, title: &str, language: &Language) {
+        match self.find_mut(title, language) {
+            Some(page) if page.status == FetchStatus::Fetched => page.status = FetchStatus::Queued,
+            Some(_) => return,
+            None => self.pages.push(QueuedPage {
+                title: title.to_string(),
+                language: language.clone(),
+                added: chrono::Utc::now().timestamp(),
+                status: FetchStatus::Queued,
+            }),
+        }
+        self.save();
+    }
+
+    pub fn set_status(&mut self, title: &str, language: &Language, status: FetchStatus) {
+        if let Some(page) = self.find_mut(title, language) {
+            page.status = status;
+            self.save();
+        }
+    }
+
+    pub fn remove(&mut self, title: &str, language: &Language) {
+        self.pages
+            .retain(|page| page.title != title || &page.language != language);
+        self.save();
+    }
+
+    /// Removes every page that was fetched already
+    pub fn clear_fetched(&mut self) {
+        self.pages
+            .retain(|page| page.status != FetchStatus::Fetched);
+        self.save();
+    }
+}
+
+/// Returns the stored fetch queue, loading it on first use
+pub fn fetch_queue() -> MutexGuard<'static, FetchQueue> {
+    FETCH_QUEUE
+        .get_or_init(|| {
+            Mutex::new(FetchQueue::load().unwrap_or_else(|error| {
+                error!("unable to load the fetch queue: {:?}", error);
+                FetchQueue::default()
+            }))
+        })
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
@@ -0,0 +1,292 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, Context, Result};
+use reqwest::{header::CONTENT_TYPE, Client};
+use serde_json::{json, Value};
+use tracing::{error, info};
+
+use crate::{
+    annotations::{annotations, Annotation},
+    config::{config, AnkiConfig},
+    export::{article_url, export_directory},
+    renderer::epub::escape,
+    tasks::{spawn_task, TaskKind},
+};
+
+const FLASHCARDS_FILE: &str = "flashcards.tsv";
+/// Version of the AnkiConnect API the requests are written for
+const ANKI_CONNECT_VERSION: u64 = 6;
+/// Tag added to every card, so the cards of wiki-tui can be found in Anki
+const TAG: &str = "wiki-tui";
+
+/// Where the annotations are exported to as flashcards
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlashcardTarget {
+    /// A tab-separated file in the export directory, which Anki imports with `File > Import`
+    Tsv,
+    /// A running Anki with the AnkiConnect add-on, the cards are added to the configured deck
+    AnkiConnect,
+}
+
+/// A card of a highlighted passage, the passage is asked for and the note is the answer
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Flashcard {
+    front: String,
+    back: String,
+    title: String,
+    url: String,
+    tags: Vec<String>,
+}
+
+impl Flashcard {
+    fn new(annotation: &Annotation) -> Self {
+        let language = annotation
+            .language
+            .clone()
+            .unwrap_or_else(|| config().api.language.clone());
+        Self {
+            front: annotation.text.clone(),
+            back: annotation.note.clone(),
+            title: annotation.page.clone(),
+            url: article_url(language.code(), &annotation.page),
+            // tags can't contain spaces
+            tags: vec![TAG.to_string(), annotation.page.replace(' ', "_")],
+        }
+    }
+}
+
+/// Replaces the characters that would end a field of the tab-separated file
+fn tsv_field(text: &str) -> String {
+    text.split(['\t', '\n', '\r'])
+        .filter(|part| !part.trim().is_empty())
+        .map(str::trim)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Writes the cards as a tab-separated file, with the header lines telling Anki how to import
+/// it. The title and url of the article are kept in their own columns
+fn flashcards_tsv(cards: &[Flashcard]) -> String {
+    let mut tsv = String::from(
+        "#separator:tab\n#html:false\n#columns:Front\tBack\tTitle\tURL\tTags\n#tags column:5\n",
+    );
+    for card in cards {
+        let fields = [
+            tsv_field(&card.front),
+            tsv_field(&card.back),
+            tsv_field(&card.title),
+            card.url.clone(),
+            card.tags.join(" "),
+        ];
+        tsv.push_str(&fields.join("\t"));
+        tsv.push('\n');
+    }
+    tsv
+}
+
+/// Returns the `addNotes` request of AnkiConnect for the cards. Without a field for the title in
+/// the note type, a link to the article is added to the back of the card
+fn anki_connect_request(cards: &[Flashcard], anki: &AnkiConfig) -> Value {
+    let notes: Vec<Value> = cards
+        .iter()
+        .map(|card| {
+            let source = format!(
+                "<a href=\"{}\">{}</a>",
+                escape(&card.url),
+                escape(&card.title)
+            );
+            let mut back = escape(&card.back);
+            let mut fields = serde_json::Map::new();
+            match anki.title_field {
+                Some(ref field) => {
+                    fields.insert(field.clone(), json!(escape(&card.title)));
+                }
+                None => {
+                    if !back.is_empty() {
+                        back.push_str("<br><br>");
+                    }
+                    back.push_str(&source);
+                }
+            }
+            if let Some(ref field) = anki.url_field {
+                fields.insert(field.clone(), json!(card.url));
+            }
+            fields.insert(anki.front_field.clone(), json!(escape(&card.front)));
+            fields.insert(anki.back_field.clone(), json!(back));
+
+            json!({
+                "deckName": anki.deck,
+                "modelName": anki.note_type,
+                "fields": fields,
+                "tags": card.tags,
+                "options": { "allowDuplicate": false },
+            })
+        })
+        .collect();
+
+    json!({
+        "action": "addNotes",
+        "version": ANKI_CONNECT_VERSION,
+        "params": { "notes": notes },
+    })
+}
+
+/// Returns how many cards were added from the response of AnkiConnect. Cards that couldn't be
+/// added, like duplicates, are `null` in the result
+fn parse_anki_connect_response(response: &Value) -> Result<usize> {
+    if let Some(error) = response.get("error").and_then(|x| x.as_str()) {
+        bail!("AnkiConnect returned an error: {}", error);
+    }
+    let result = response
+        .get("result")
+        .and_then(|x| x.as_array())
+        .ok_or_else(|| anyhow!("the response of AnkiConnect contains no result"))?;
+    Ok(result.iter().filter(|id| !id.is_null()).count())
+}
+
+/// Writes the annotations as flashcards into the export directory and returns the path of the
+/// file
+pub fn save_flashcards(annotations: &[Annotation]) -> Result<PathBuf> {
+    if annotations.is_empty() {
+        bail!("there are no annotations to export");
+    }
+    let cards: Vec<Flashcard> = annotations.iter().map(Flashcard::new).collect();
+
+    let directory = export_directory(&config().export)?;
+    std::fs::create_dir_all(&directory).with_context(|| {
+        format!(
+            "unable to create the export directory '{}'",
+            directory.display()
+        )
+    })?;
+
+    let path = directory.join(FLASHCARDS_FILE);
+    std::fs::write(&path, flashcards_tsv(&cards))
+        .with_context(|| format!("unable to write the export '{}'", path.display()))?;
+
+    info!(
+        "exported {} flashcards to '{}'",
+        cards.len(),
+        path.display()
+    );
+    Ok(path)
+}
+
+/// Adds the annotations as cards to Anki through AnkiConnect and returns how many were added
+///
+/// # Error
+///
+/// This function returns an error when one of the following things happens:
+/// - Anki isn't running or AnkiConnect isn't installed
+/// - AnkiConnect returned an error, like for a deck or note type that doesn't exist
+pub async fn send_to_anki(annotations: &[Annotation]) -> Result<usize> {
+    if annotations.is_empty() {
+        bail!("there are no annotations to export");
+    }
+    let cards: Vec<Flashcard> = annotations.iter().map(Flashcard::new).collect();
+    let anki = &config().export.anki;
+
+    let response = Client::new()
+        .post(anki.url.as_str())
+        .header(CONTENT_TYPE, "application/json")
+        .body(anki_connect_request(&cards, anki).to_string())
+        .send()
+        .await
+        .with_context(|| {
+            format!(
+                "unable to reach AnkiConnect at '{}', is Anki running?",
+                anki.url
+            )
+        })?
+        .text()
+        .await
+        .context("failed reading the response of AnkiConnect")?;
+    let response: Value = serde_json::from_str(&response)
+        .context("failed interpreting the response of AnkiConnect as json")?;
+
+    let added = parse_anki_connect_response(&response)?;
+    info!(
+        "added {} of {} flashcards to Anki",
+        added,
+        annotations.len()
+    );
+    Ok(added)
+}
+
+/// Exports all annotations as flashcards, sending them to Anki happens in the background
+pub fn export_flashcards(target: FlashcardTarget) {
+    let annotations = annotations().all().to_vec();
+    match target {
+        FlashcardTarget::Tsv => {
+            if let Err(error) = save_flashcards(&annotations) {
+                error!("unable to export the flashcards: {:?}", error);
+            }
+        }
+        FlashcardTarget::AnkiConnect => spawn_task(TaskKind::Export, "Anki", async move {
+            if let Err(error) = send_to_anki(&annotations).await {
+                error!("unable to add the flashcards to Anki: {:?}", error);
+            }
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{
+        anki_connect_request, flashcards_tsv, parse_anki_connect_response, AnkiConfig, Flashcard,
+    };
+
+    fn card() -> Flashcard {
+        Flashcard {
+            front: "Foxes\tare\nsmall".to_string(),
+            back: "omnivorous <mammals>".to_string(),
+            title: "Red fox".to_string(),
+            url: "https://en.wikipedia.org/wiki/Red_fox".to_string(),
+            tags: vec!["wiki-tui".to_string(), "Red_fox".to_string()],
+        }
+    }
+
+    #[test]
+    fn tsv() {
+        let tsv = flashcards_tsv(&[card()]);
+        assert!(tsv.starts_with("#separator:tab\n#html:false\n"));
+        assert!(tsv.ends_with(
+            "Foxes are small\tomnivorous <mammals>\tRed fox\t\
+             https://en.wikipedia.org/wiki/Red_fox\twiki-tui Red_fox\n"
+        ));
+    }
+
+    #[test]
+    fn anki_connect() {
+        let request = anki_connect_request(&[card()], &AnkiConfig::default());
+        let note = &request["params"]["notes"][0];
+        assert_eq!(request["action"], "addNotes");
+        assert_eq!(note["deckName"], "wiki-tui");
+        assert_eq!(note["fields"]["Front"], "Foxes\tare\nsmall");
+        assert_eq!(
+            note["fields"]["Back"],
+            "omnivorous &lt;mammals&gt;<br><br>\
+             <a href=\"https://en.wikipedia.org/wiki/Red_fox\">Red fox</a>"
+        );
+
+        let anki = AnkiConfig {
+            title_field: Some("Title".to_string()),
+            url_field: Some("URL".to_string()),
+            ..AnkiConfig::default()
+        };
+        let note = &anki_connect_request(&[card()], &anki)["params"]["notes"][0];
+        assert_eq!(note["fields"]["Back"], "omnivorous &lt;mammals&gt;");
+        assert_eq!(note["fields"]["Title"], "Red fox");
+        assert_eq!(
+            note["fields"]["URL"],
+            "https://en.wikipedia.org/wiki/Red_fox"
+        );
+
+        let response = json!({ "result": [1496198395707u64, null], "error": null });
+        assert_eq!(parse_anki_connect_response(&response).unwrap(), 1);
+        let response = json!({ "result": null, "error": "deck was not found" });
+        assert!(parse_anki_connect_response(&response).is_err());
+    }
+}
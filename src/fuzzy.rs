@@ -0,0 +1,231 @@
+/// Amount of single character edits (insertions, deletions, substitutions) needed to turn one
+/// string into the other. The comparison ignores the case
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, a_char) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(a_char != b_char);
+            current[j + 1] = substitution.min(prev[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut current);
+    }
+    prev[b.len()]
+}
+
+/// Returns the candidate closest to the query, if it's close enough to be a likely correction
+///
+/// Candidates equal to the query (ignoring the case) are skipped
+pub fn closest_match<'a>(
+    query: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let max_distance = (query.chars().count() / 3).max(2);
+    candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(query, candidate), candidate))
+        .filter(|&(distance, _)| 0 < distance && distance <= max_distance)
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, candidate)| candidate)
+}
+
+const SCORE_MATCH: i64 = 16;
+const SCORE_GAP_START: i64 = -3;
+const SCORE_GAP_EXTENSION: i64 = -1;
+/// Bonus of a matched character that starts a word
+const BONUS_BOUNDARY: i64 = 8;
+/// Bonus of a matched uppercase letter after a lowercase one, or a digit after a letter
+const BONUS_CAMEL: i64 = 7;
+/// Least bonus of a matched character right after the previously matched one
+const BONUS_CONSECUTIVE: i64 = 4;
+/// The bonus of the first character of the query counts this many times
+const BONUS_FIRST_CHAR_MULTIPLIER: i64 = 2;
+
+/// How well a query matches a candidate, see [`fuzzy_match`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    /// Higher scores are better matches
+    pub score: i64,
+    /// Indices of the characters of the candidate that were matched, in order
+    pub positions: Vec<usize>,
+}
+
+/// Returns the bonus of matching the character, depending on the character before it
+fn bonus(prev: Option<char>, current: char) -> i64 {
+    match prev {
+        None => BONUS_BOUNDARY,
+        Some(prev) if !prev.is_alphanumeric() && current.is_alphanumeric() => BONUS_BOUNDARY,
+        Some(prev) if prev.is_lowercase() && current.is_uppercase() => BONUS_CAMEL,
+        Some(prev) if !prev.is_numeric() && current.is_numeric() => BONUS_CAMEL,
+        _ => 0,
+    }
+}
+
+fn lowercase(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// Matches the characters of the query in order, but not necessarily next to each other, like
+/// skim and fzf do. Returns `None` when the candidate doesn't contain them. The comparison
+/// ignores the case
+///
+/// Of all the ways the query can be matched, the best scoring one is used. Every matched
+/// character scores, more so at the start of a word or right after the previous match, while
+/// gaps between the matched characters cost
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<FuzzyMatch> {
+    let query: Vec<char> = query.chars().map(lowercase).collect();
+    let original: Vec<char> = candidate.chars().collect();
+    let candidate: Vec<char> = original.iter().copied().map(lowercase).collect();
+    if query.len() > candidate.len() {
+        return None;
+    }
+    if query.is_empty() {
+        return Some(FuzzyMatch {
+            score: 0,
+            positions: Vec::new(),
+        });
+    }
+
+    let bonuses: Vec<i64> = (0..original.len())
+        .map(|j| bonus(j.checked_sub(1).map(|prev| original[prev]), original[j]))
+        .collect();
+
+    // the best score of matching the query up to `i` with its character `i` at `j`, and the
+    // position the previous character was matched at
+    let mut scores: Vec<Vec<Option<(i64, usize)>>> = vec![vec![None; candidate.len()]; query.len()];
+    for (i, query_char) in query.iter().enumerate() {
+        for j in i..candidate.len() {
+            if candidate[j] != *query_char {
+                continue;
+            }
+            if i == 0 {
+                scores[i][j] = Some((SCORE_MATCH + bonuses[j] * BONUS_FIRST_CHAR_MULTIPLIER, 0));
+                continue;
+            }
+
+            scores[i][j] = (i - 1..j)
+                .filter_map(|k| scores[i - 1][k].map(|(score, _)| (score, k)))
+                .map(|(score, k)| {
+                    let score = match j - k - 1 {
+                        0 => score + SCORE_MATCH + bonuses[j].max(BONUS_CONSECUTIVE),
+                        gap => {
+                            score
+                                + SCORE_MATCH
+                                + bonuses[j]
+                                + SCORE_GAP_START
+                                + SCORE_GAP_EXTENSION * (gap as i64 - 1)
+                        }
+                    };
+                    (score, k)
+                })
+                .max_by_key(|(score, _)| *score);
+        }
+    }
+
+    let last = query.len() - 1;
+    let (mut j, score) = scores[last]
+        .iter()
+        .enumerate()
+        .filter_map(|(j, score)| score.map(|(score, _)| (j, score)))
+        .max_by_key(|(_, score)| *score)?;
+
+    let mut positions = vec![0; query.len()];
+    for i in (0..query.len()).rev() {
+        positions[i] = j;
+        j = scores[i][j].map(|(_, prev)| prev).unwrap_or_default();
+    }
+    Some(FuzzyMatch { score, positions })
+}
+
+/// Ranks the candidates by how well the query matches them, the best match first. Candidates
+/// that don't match are left out, the ones that match equally well keep their order
+pub fn rank_matches<'s, T>(
+    query: &str,
+    candidates: impl IntoIterator<Item = T>,
+    text: impl Fn(&T) -> &'s str,
+) -> Vec<(T, FuzzyMatch)> {
+    let mut matches: Vec<(T, FuzzyMatch)> = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let matched = fuzzy_match(query, text(&candidate))?;
+            Some((candidate, matched))
+        })
+        .collect();
+    matches.sort_by_key(|(_, matched)| std::cmp::Reverse(matched.score));
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{closest_match, edit_distance, fuzzy_match, rank_matches};
+
+    #[test]
+    fn distances() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("", "fox"), 3);
+        assert_eq!(edit_distance("fox", ""), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        // the case is ignored, other characters are compared as they are
+        assert_eq!(edit_distance("Red Fox", "red fox"), 0);
+        assert_eq!(edit_distance("Straße", "strasse"), 2);
+    }
+
+    #[test]
+    fn closest() {
+        assert_eq!(closest_match("fox", []), None);
+        assert_eq!(closest_match("", ["fox"]), None);
+        // the query itself is no correction
+        assert_eq!(closest_match("Fox", ["fox"]), None);
+        assert_eq!(
+            closest_match("programing", ["program", "programming"]),
+            Some("programming")
+        );
+
+        // of equally close candidates, the first one is suggested
+        assert_eq!(closest_match("cat", ["bat", "cut", "car"]), Some("bat"));
+        assert_eq!(closest_match("cat", ["cart", "bat"]), Some("cart"));
+    }
+
+    #[test]
+    fn distance_threshold() {
+        // short queries allow two edits, longer ones a third of their length
+        assert_eq!(closest_match("rust", ["ru"]), Some("ru"));
+        assert_eq!(closest_match("rust", ["r"]), None);
+        assert_eq!(closest_match("rust", ["crustacean"]), None);
+        assert_eq!(
+            closest_match("photosinthesys", ["photosynthesis"]),
+            Some("photosynthesis")
+        );
+        assert_eq!(closest_match("photosinthesys", ["photography"]), None);
+    }
+
+    #[test]
+    fn positions() {
+        let matched = fuzzy_match("abc", "xAxbxc").unwrap();
+        assert_eq!(matched.positions, vec![1, 3, 5]);
+
+        // the characters that start words are preferred over the first ones found
+        let matched = fuzzy_match("hs", "the history section").unwrap();
+        assert_eq!(matched.positions, vec![4, 12]);
+
+        assert_eq!(fuzzy_match("abc", "acb"), None);
+        assert_eq!(
+            fuzzy_match("", "abc").unwrap().positions,
+            Vec::<usize>::new()
+        );
+    }
+
+    #[test]
+    fn ranking() {
+        let candidates = ["Strings", "Getting started", "Unrelated", "gs"];
+        let ranked: Vec<&str> = rank_matches("gs", candidates, |candidate| *candidate)
+            .into_iter()
+            .map(|(candidate, _)| candidate)
+            .collect();
+        assert_eq!(ranked, vec!["gs", "Getting started", "Strings"]);
+    }
+}
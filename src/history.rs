@@ -0,0 +1,153 @@
+use std::{
+    path::PathBuf,
+    sync::{Mutex, MutexGuard, OnceLock},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use crate::config::data_dir;
+
+const HISTORY_FILE: &str = "history.json";
+
+static HISTORY: OnceLock<Mutex<HistoryStore>> = OnceLock::new();
+
+/// A page that was visited at least once
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Title of the visited page
+    pub title: String,
+    /// Unix timestamp of the last visit
+    pub last_visited: i64,
+    /// How often the page was visited
+    pub visits: usize,
+    /// Where the page was left the last time it was read, missing for pages that weren't
+    /// rendered yet
+    #[serde(default)]
+    pub position: Option<ReadingPosition>,
+}
+
+/// How far a page was read: the last line that was shown and how many lines the page was long.
+/// Both depend on the width the page was rendered with, but how far it was read in percent
+/// hardly does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReadingPosition {
+    pub line: usize,
+    pub lines: usize,
+}
+
+impl ReadingPosition {
+    /// Returns how much of the page was read, in percent
+    pub fn percent(&self) -> u16 {
+        if self.lines == 0 {
+            return 100;
+        }
+        (self.line.min(self.lines) * 100 / self.lines) as u16
+    }
+}
+
+/// Every visited page, stored in the data directory
+#[derive(Default)]
+pub struct HistoryStore {
+    entries: Vec<HistoryEntry>,
+}
+
+impl HistoryStore {
+    fn path() -> Result<PathBuf> {
+        Ok(data_dir()?.join(HISTORY_FILE))
+    }
+
+    fn load() -> Result<Self> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        info!("loading the history from '{}'", path.display());
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("unable to read the history '{}'", path.display()))?;
+        let entries = serde_json::from_str(&contents)
+            .with_context(|| format!("unable to parse the history '{}'", path.display()))?;
+        Ok(Self { entries })
+    }
+
+    fn save(&self) {
+        let result = Self::path().and_then(|path| {
+            std::fs::create_dir_all(data_dir()?)?;
+            std::fs::write(&path, serde_json::to_string_pretty(&self.entries)?)
+                .with_context(|| format!("unable to write the history '{}'", path.display()))
+        });
+
+        if let Err(error) = result {
+            error!("unable to save the history: {:?}", error);
+        }
+    }
+
+    pub fn all(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    /// Records a visit of the page happening now
+    pub fn record(&mut self, title: &str) {
+        let now = chrono::Utc::now().timestamp();
+        match self.entries.iter_mut().find(|entry| entry.title == title) {
+            Some(entry) => {
+                entry.last_visited = now;
+                entry.visits += 1;
+            }
+            None => self.entries.push(HistoryEntry {
+                title: title.to_string(),
+                last_visited: now,
+                visits: 1,
+                position: None,
+            }),
+        }
+        self.save();
+    }
+
+    /// Remembers where the visited page was left
+    pub fn set_position(&mut self, title: &str, position: ReadingPosition) {
+        let entry = match self.entries.iter_mut().find(|entry| entry.title == title) {
+            Some(entry) if entry.position != Some(position) => entry,
+            _ => return,
+        };
+        entry.position = Some(position);
+        self.save();
+    }
+
+    /// Returns how much of the page was read the last time, in percent
+    pub fn progress(&self, title: &str) -> Option<u16> {
+        self.entries
+            .iter()
+            .find(|entry| entry.title == title)
+            .and_then(|entry| entry.position)
+            .map(|position| position.percent())
+    }
+}
+
+/// Returns the stored history, loading it on first use
+pub fn history() -> MutexGuard<'static, HistoryStore> {
+    HISTORY
+        .get_or_init(|| {
+            Mutex::new(HistoryStore::load().unwrap_or_else(|error| {
+                error!("unable to load the history: {:?}", error);
+                HistoryStore::default()
+            }))
+        })
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReadingPosition;
+
+    #[test]
+    fn percent() {
+        let percent = |line, lines| ReadingPosition { line, lines }.percent();
+        assert_eq!(percent(30, 120), 25);
+        assert_eq!(percent(130, 120), 100);
+        assert_eq!(percent(0, 0), 100);
+    }
+}
@@ -0,0 +1,157 @@
+use std::process::Stdio;
+
+use tokio::process::Command;
+use tracing::{debug, info, warn};
+use wiki_api::page::Page;
+
+use crate::{config::config, export::article_url};
+
+/// Prefix of the environment variables the placeholders are passed in as well
+const ENV_PREFIX: &str = "WIKI_TUI_";
+
+/// Events users can run an external command on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hook {
+    /// A page was displayed
+    PageOpened,
+    /// The selected link of a page was yanked
+    LinkYanked,
+    /// Highlighted passages of a page were yanked
+    TextYanked,
+    /// A page was exported as Markdown
+    ArticleExported,
+}
+
+impl Hook {
+    /// Name of the hook in the configuration
+    pub fn name(&self) -> &'static str {
+        match self {
+            Hook::PageOpened => "page_opened",
+            Hook::LinkYanked => "link_yanked",
+            Hook::TextYanked => "text_yanked",
+            Hook::ArticleExported => "article_exported",
+        }
+    }
+
+    /// Configured command and its arguments, empty when the hook is not used
+    fn command(&self) -> &'static [String] {
+        let hooks = &config().hooks;
+        match self {
+            Hook::PageOpened => &hooks.page_opened,
+            Hook::LinkYanked => &hooks.link_yanked,
+            Hook::TextYanked => &hooks.text_yanked,
+            Hook::ArticleExported => &hooks.article_exported,
+        }
+    }
+}
+
+/// Values of the placeholders for an event, by their name without the braces
+pub type Placeholders = Vec<(&'static str, String)>;
+
+/// Returns the placeholders describing an article: `{title}`, `{url}` and `{language}`
+pub fn article_placeholders(page: &Page) -> Placeholders {
+    let language_code = page.language.code();
+    vec![
+        ("title", page.title.clone()),
+        ("url", article_url(language_code, &page.title)),
+        ("language", language_code.to_string()),
+    ]
+}
+
+/// Replaces every `{name}` in the argument with the value of the placeholder. Unknown
+/// placeholders are kept as they are
+pub fn substitute(argument: &str, placeholders: &[(&'static str, String)]) -> String {
+    let mut substituted = String::with_capacity(argument.len());
+    let mut rest = argument;
+    while let Some(start) = rest.find('{') {
+        substituted.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+
+        let placeholder = after.find('}').and_then(|end| {
+            placeholders
+                .iter()
+                .find(|(name, _)| *name == &after[..end])
+                .map(|(_, value)| (end, value))
+        });
+        match placeholder {
+            Some((end, value)) => {
+                substituted.push_str(value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                substituted.push('{');
+                rest = after;
+            }
+        }
+    }
+    substituted.push_str(rest);
+    substituted
+}
+
+/// Runs the command configured for the hook in the background, doing nothing when there is none
+///
+/// The placeholders are substituted in every argument. They are also passed as environment
+/// variables (`{title}` as `WIKI_TUI_TITLE`), which is safer to use in shell scripts
+pub fn run_hook(hook: Hook, placeholders: Placeholders) {
+    let (program, args) = match hook.command().split_first() {
+        Some((program, args)) => (program, args),
+        None => {
+            debug!("no command configured for the '{}' hook", hook.name());
+            return;
+        }
+    };
+
+    let mut command = Command::new(substitute(program, &placeholders));
+    command
+        .args(args.iter().map(|arg| substitute(arg, &placeholders)))
+        .envs(
+            placeholders
+                .iter()
+                .map(|(name, value)| (format!("{}{}", ENV_PREFIX, name.to_uppercase()), value)),
+        )
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+
+    info!("running the '{}' hook", hook.name());
+    tokio::spawn(async move {
+        match command.output().await {
+            Ok(output) if output.status.success() => {
+                debug!("the '{}' hook finished", hook.name())
+            }
+            Ok(output) => warn!(
+                "the '{}' hook failed with {}: {}",
+                hook.name(),
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            Err(error) => warn!("unable to run the '{}' hook: {:?}", hook.name(), error),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::substitute;
+
+    #[test]
+    fn placeholders() {
+        let placeholders = vec![
+            ("title", "Red fox".to_string()),
+            ("url", "https://en.wikipedia.org/wiki/Red_fox".to_string()),
+        ];
+
+        assert_eq!(
+            substitute("- [{title}]({url}) {title}", &placeholders),
+            "- [Red fox](https://en.wikipedia.org/wiki/Red_fox) Red fox"
+        );
+        assert_eq!(
+            substitute("{path} {{title}}", &placeholders),
+            "{path} {Red fox}"
+        );
+
+        // values are not substituted again
+        let placeholders = vec![("title", "{url}".to_string()), ("url", "".to_string())];
+        assert_eq!(substitute("{title}", &placeholders), "{url}");
+    }
+}
@@ -0,0 +1,133 @@
+use std::{collections::HashMap, sync::OnceLock};
+
+use tracing::{info, warn};
+
+use crate::{config::config, hooks::substitute};
+
+/// String tables shipped with wiki-tui, by their locale. The first one is the fallback for
+/// strings missing in the other tables
+const LOCALES: [(&str, &str); 2] = [
+    ("en", include_str!("../locales/en.toml")),
+    ("de", include_str!("../locales/de.toml")),
+];
+
+/// UI strings by their dotted key, like `status.help`
+type Strings = HashMap<String, String>;
+
+struct Translations {
+    strings: Strings,
+    fallback: Strings,
+}
+
+static TRANSLATIONS: OnceLock<Translations> = OnceLock::new();
+
+/// Collects the strings of the table and its nested tables, prefixing their keys with the name
+/// of their table
+fn flatten(prefix: &str, table: &toml::Table, strings: &mut Strings) {
+    for (name, value) in table.iter() {
+        let key = if prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", prefix, name)
+        };
+        match value {
+            toml::Value::String(string) => {
+                strings.insert(key, string.to_string());
+            }
+            toml::Value::Table(table) => flatten(&key, table, strings),
+            _ => warn!("the UI string '{}' is not a string", key),
+        }
+    }
+}
+
+fn parse_strings(locale: &str, contents: &str) -> Strings {
+    let mut strings = Strings::new();
+    match contents.parse::<toml::Table>() {
+        Ok(table) => flatten("", &table, &mut strings),
+        Err(error) => warn!("unable to parse the '{}' UI strings: {:?}", locale, error),
+    }
+    strings
+}
+
+/// Returns the locale the UI is shown in, which is the configured locale or else the language
+/// pages are loaded in
+pub fn locale() -> String {
+    config()
+        .ui
+        .locale
+        .clone()
+        .unwrap_or_else(|| config().api.language.code().to_string())
+}
+
+fn translations() -> &'static Translations {
+    TRANSLATIONS.get_or_init(|| {
+        let locale = locale();
+        // regional variants like `de-ch` use the strings of their language
+        let language = locale.split('-').next().unwrap_or_default();
+        let table = LOCALES
+            .iter()
+            .find(|(name, _)| *name == locale)
+            .or_else(|| LOCALES.iter().find(|(name, _)| *name == language));
+
+        let strings = match table {
+            Some((name, contents)) => parse_strings(name, contents),
+            None => {
+                info!("the UI is not translated into '{}', using English", locale);
+                Strings::new()
+            }
+        };
+        let (fallback_name, fallback) = LOCALES[0];
+        Translations {
+            strings,
+            fallback: parse_strings(fallback_name, fallback),
+        }
+    })
+}
+
+/// Returns the UI string with the key in the current locale, falling back to English. Unknown
+/// keys are returned as they are
+pub fn tr(key: &'static str) -> &'static str {
+    let translations = translations();
+    match translations
+        .strings
+        .get(key)
+        .or_else(|| translations.fallback.get(key))
+    {
+        Some(string) => string,
+        None => {
+            warn!("missing the UI string '{}'", key);
+            key
+        }
+    }
+}
+
+/// Returns the UI string with the key like [`tr`], replacing every `{name}` with the value of
+/// the placeholder
+pub fn tr_args(key: &'static str, placeholders: &[(&'static str, String)]) -> String {
+    substitute(tr(key), placeholders)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_strings, LOCALES};
+
+    #[test]
+    fn locales() {
+        let (fallback_name, fallback) = LOCALES[0];
+        let fallback = parse_strings(fallback_name, fallback);
+        assert!(!fallback.is_empty());
+
+        for (name, contents) in LOCALES.iter().skip(1) {
+            let strings = parse_strings(name, contents);
+            assert!(!strings.is_empty(), "the '{}' strings are empty", name);
+            for key in strings.keys() {
+                assert!(
+                    fallback.contains_key(key),
+                    "'{}' of the '{}' strings is not an English string",
+                    key,
+                    name
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,308 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+
+use anyhow::{Context, Result};
+use tokio::sync::Semaphore;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+use url::Url;
+use wiki_api::{
+    images::{fetch_image, image_url, Image},
+    page::Page,
+};
+
+use crate::{
+    config::{config, data_dir},
+    renderer::html::image_sources,
+};
+
+const IMAGES_DIRECTORY: &str = "images";
+/// At most this many images are downloaded at the same time
+const MAX_DOWNLOADS: usize = 4;
+
+/// Extensions of the stored images by their media type. Images of other types are stored as PNG
+const EXTENSIONS: [(&str, &str); 5] = [
+    ("image/png", "png"),
+    ("image/jpeg", "jpg"),
+    ("image/gif", "gif"),
+    ("image/svg+xml", "svg"),
+    ("image/webp", "webp"),
+];
+
+/// Hashes the url with FNV-1a. Unlike the hasher of the standard library, the hash stays the same
+/// between releases, so the stored images are found again after an update
+fn hash_url(url: &Url) -> u64 {
+    url.as_str()
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+/// Name of the file the image is stored in, the hash of its url followed by the extension of
+/// its media type
+fn image_file_name(url: &Url, mime_type: &str) -> String {
+    let extension = EXTENSIONS
+        .iter()
+        .find(|(mime, _)| *mime == mime_type)
+        .map_or("png", |(_, extension)| extension);
+    format!("{:016x}.{}", hash_url(url), extension)
+}
+
+fn cache_dir() -> Result<PathBuf> {
+    Ok(data_dir()?.join(IMAGES_DIRECTORY))
+}
+
+fn load_from(directory: &Path, url: &Url) -> Result<Option<Image>> {
+    for (mime_type, _) in EXTENSIONS {
+        let path = directory.join(image_file_name(url, mime_type));
+        if path.exists() {
+            let data = std::fs::read(&path)
+                .with_context(|| format!("unable to read the image '{}'", path.display()))?;
+            return Ok(Some(Image {
+                mime_type: mime_type.to_string(),
+                data,
+            }));
+        }
+    }
+    Ok(None)
+}
+
+fn store_in(directory: &Path, url: &Url, image: &Image) -> Result<()> {
+    std::fs::create_dir_all(directory)
+        .with_context(|| format!("unable to create the image cache '{}'", directory.display()))?;
+    let path = directory.join(image_file_name(url, &image.mime_type));
+    std::fs::write(&path, &image.data)
+        .with_context(|| format!("unable to write the image '{}'", path.display()))
+}
+
+/// Returns the stored image of the url, `None` when it wasn't downloaded yet
+pub fn cached_image(url: &Url) -> Result<Option<Image>> {
+    load_from(&cache_dir()?, url)
+}
+
+/// Stores the image downloaded from the url in the data directory
+pub fn store_image(url: &Url, image: &Image) -> Result<()> {
+    store_in(&cache_dir()?, url, image)
+}
+
+/// Returns the image from the image cache, downloading and storing it when it isn't there yet
+pub async fn load_image(url: Url) -> Result<Image> {
+    if let Some(image) = cached_image(&url)? {
+        return Ok(image);
+    }
+
+    let image = fetch_image(url.clone()).await?;
+    if let Err(error) = store_image(&url, &image) {
+        warn!("unable to cache the image '{}': {:?}", url, error);
+    }
+    Ok(image)
+}
+
+/// Returns the files that have to be removed for the images to fit into `max_size` bytes, the
+/// ones that were downloaded first. The files are given with their size and modification time
+fn oldest_images(mut files: Vec<(PathBuf, u64, SystemTime)>, max_size: u64) -> Vec<PathBuf> {
+    let mut size: u64 = files.iter().map(|(_, file_size, _)| file_size).sum();
+    files.sort_by_key(|(_, _, modified)| *modified);
+
+    files
+        .into_iter()
+        .take_while(|(_, file_size, _)| {
+            let is_over = size > max_size;
+            size = size.saturating_sub(*file_size);
+            is_over
+        })
+        .map(|(path, _, _)| path)
+        .collect()
+}
+
+/// Removes the images downloaded first until the image cache fits into the size configured in
+/// `cache.max_image_size`, returns how many were removed
+pub fn enforce_max_size() -> Result<usize> {
+    let max_size = match config().cache.max_image_size {
+        0 => return Ok(0),
+        max_size => max_size * 1024 * 1024,
+    };
+    let cache_dir = cache_dir()?;
+    if !cache_dir.exists() {
+        return Ok(0);
+    }
+
+    let mut files = Vec::new();
+    for file in std::fs::read_dir(&cache_dir)? {
+        let file = file?;
+        let metadata = file.metadata()?;
+        if metadata.is_file() {
+            files.push((file.path(), metadata.len(), metadata.modified()?));
+        }
+    }
+
+    let evicted = oldest_images(files, max_size);
+    for path in evicted.iter() {
+        std::fs::remove_file(path)
+            .with_context(|| format!("unable to remove the image '{}'", path.display()))?;
+    }
+    if !evicted.is_empty() {
+        info!(
+            "removed {} images to keep the image cache below {} MiB",
+            evicted.len(),
+            config().cache.max_image_size
+        );
+    }
+    Ok(evicted.len())
+}
+
+/// Downloads the image into the image cache, unless it's already there. Waits for a free
+/// download slot first
+async fn prefetch_image(downloads: &Semaphore, url: Url) {
+    let _permit = match downloads.acquire().await {
+        Ok(permit) => permit,
+        Err(_) => return,
+    };
+    match cached_image(&url) {
+        Ok(Some(_)) => {}
+        Ok(None) => match fetch_image(url.clone()).await {
+            Ok(image) => {
+                if let Err(error) = store_image(&url, &image) {
+                    warn!("unable to cache the image '{}': {:?}", url, error);
+                }
+            }
+            Err(error) => debug!("unable to prefetch the image '{}': {:?}", url, error),
+        },
+        Err(error) => warn!("unable to look up the image '{}': {:?}", url, error),
+    }
+}
+
+/// Downloads the images of the displayed page into the image cache in the background, so they
+/// are ready once they are needed
+///
+/// Only a few images are downloaded at the same time. Displaying another page cancels the
+/// downloads of the page before, the images that were already stored are kept
+pub struct ImagePrefetcher {
+    downloads: Arc<Semaphore>,
+    cancel: CancellationToken,
+}
+
+impl Default for ImagePrefetcher {
+    fn default() -> Self {
+        Self {
+            downloads: Arc::new(Semaphore::new(MAX_DOWNLOADS)),
+            cancel: CancellationToken::new(),
+        }
+    }
+}
+
+impl ImagePrefetcher {
+    /// Starts downloading the images of the page, cancelling the downloads of the page before.
+    /// Nothing is downloaded unless `cache.prefetch_images` is set
+    pub fn prefetch(&mut self, page: &Page) {
+        self.cancel();
+        if !config().cache.prefetch_images {
+            return;
+        }
+
+        let endpoint = page.language.wikipedia_endpoint();
+        let urls: Vec<Url> = image_sources(page)
+            .iter()
+            .filter_map(|src| image_url(src, &endpoint).ok())
+            .collect();
+        if urls.is_empty() {
+            return;
+        }
+
+        let title = page.title.clone();
+        let downloads = self.downloads.clone();
+        let cancel = self.cancel.clone();
+        tokio::spawn(async move {
+            let count = urls.len();
+            let prefetch = futures::future::join_all(
+                urls.into_iter()
+                    .map(|url| prefetch_image(downloads.as_ref(), url)),
+            );
+            tokio::select! {
+                _ = cancel.cancelled() => {
+                    debug!("cancelled prefetching the images of '{}'", title);
+                    return;
+                }
+                _ = prefetch => debug!("prefetched {} images of '{}'", count, title),
+            }
+
+            let enforced = tokio::task::spawn_blocking(enforce_max_size).await;
+            if let Ok(Err(error)) = enforced {
+                warn!("unable to limit the size of the image cache: {:?}", error);
+            }
+        });
+    }
+
+    /// Cancels the downloads that haven't finished yet
+    pub fn cancel(&mut self) {
+        self.cancel.cancel();
+        self.cancel = CancellationToken::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        path::PathBuf,
+        time::{Duration, SystemTime},
+    };
+
+    use url::Url;
+    use wiki_api::images::Image;
+
+    use super::{image_file_name, load_from, oldest_images, store_in};
+
+    fn url() -> Url {
+        Url::parse("https://upload.wikimedia.org/wikipedia/commons/a/a0/Fox.jpg").unwrap()
+    }
+
+    #[test]
+    fn file_names() {
+        let name = image_file_name(&url(), "image/jpeg");
+        assert!(name.ends_with(".jpg"));
+        // the hash doesn't change between runs
+        assert_eq!(name, image_file_name(&url(), "image/jpeg"));
+        assert_eq!(name.len(), 16 + ".jpg".len());
+        assert!(image_file_name(&url(), "image/x-icon").ends_with(".png"));
+    }
+
+    #[test]
+    fn store_and_load() {
+        let directory =
+            std::env::temp_dir().join(format!("wiki-tui-images-{}", std::process::id()));
+        let image = Image {
+            mime_type: "image/svg+xml".to_string(),
+            data: b"<svg/>".to_vec(),
+        };
+
+        assert_eq!(load_from(&directory, &url()).unwrap(), None);
+        store_in(&directory, &url(), &image).unwrap();
+        assert_eq!(load_from(&directory, &url()).unwrap(), Some(image));
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn eviction() {
+        let at = |seconds| SystemTime::UNIX_EPOCH + Duration::from_secs(seconds);
+        let files = vec![
+            (PathBuf::from("new"), 300, at(3)),
+            (PathBuf::from("old"), 200, at(1)),
+            (PathBuf::from("middle"), 100, at(2)),
+        ];
+
+        assert!(oldest_images(files.clone(), 600).is_empty());
+        assert_eq!(
+            oldest_images(files.clone(), 400),
+            vec![PathBuf::from("old")]
+        );
+        assert_eq!(
+            oldest_images(files, 300),
+            vec![PathBuf::from("old"), PathBuf::from("middle")]
+        );
+    }
+}
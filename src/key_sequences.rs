@@ -0,0 +1,258 @@
+use std::time::{Duration, Instant};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use tracing::warn;
+
+use crate::{
+    action::{Action, PageAction, ViewportPosition},
+    config::{KeyCommand, KeysConfig},
+};
+
+/// A key of a sequence. Shift isn't part of it for characters, it's part of the character
+type Key = (KeyCode, KeyModifiers);
+
+fn key_of(event: KeyEvent) -> Key {
+    match event.code {
+        KeyCode::Char(_) => (event.code, event.modifiers - KeyModifiers::SHIFT),
+        code => (code, event.modifiers),
+    }
+}
+
+/// Parses a sequence like `gg`, `<leader>b` or `<c-x>z`. Besides characters, a sequence can
+/// contain `<leader>`, `<space>`, `<tab>`, `<lt>` for `<` and `<c-x>` for a character pressed
+/// together with control
+pub fn parse_sequence(sequence: &str, leader: char) -> Result<Vec<Key>, String> {
+    let mut keys = Vec::new();
+    let mut rest = sequence;
+    while let Some(c) = rest.chars().next() {
+        if c != '<' {
+            keys.push((KeyCode::Char(c), KeyModifiers::NONE));
+            rest = &rest[c.len_utf8()..];
+            continue;
+        }
+
+        let end = rest
+            .find('>')
+            .ok_or_else(|| format!("'<' isn't closed in '{}', use '<lt>' for '<'", sequence))?;
+        let name = rest[1..end].to_lowercase();
+        keys.push(match name.as_str() {
+            "leader" => (KeyCode::Char(leader), KeyModifiers::NONE),
+            "space" => (KeyCode::Char(' '), KeyModifiers::NONE),
+            "tab" => (KeyCode::Tab, KeyModifiers::NONE),
+            "lt" => (KeyCode::Char('<'), KeyModifiers::NONE),
+            control => match control
+                .strip_prefix("c-")
+                .map(|c| c.chars().collect::<Vec<_>>())
+            {
+                Some(c) if c.len() == 1 => (KeyCode::Char(c[0]), KeyModifiers::CONTROL),
+                _ => return Err(format!("unknown key '<{}>' in '{}'", name, sequence)),
+            },
+        });
+        rest = &rest[end + 1..];
+    }
+
+    if keys.len() < 2 {
+        return Err(format!("'{}' isn't a sequence of keys", sequence));
+    }
+    Ok(keys)
+}
+
+/// Returns the action the command of a sequence runs
+pub fn command_action(command: KeyCommand) -> Action {
+    match command {
+        KeyCommand::ScrollToTop => Action::ScrollToTop,
+        KeyCommand::ScrollToBottom => Action::ScrollToBottom,
+        KeyCommand::HalfPageDown => Action::ScrollHalfDown,
+        KeyCommand::HalfPageUp => Action::ScrollHalfUp,
+        KeyCommand::SelectionToTop => {
+            Action::Page(PageAction::AlignSelection(ViewportPosition::Top))
+        }
+        KeyCommand::SelectionToCenter => {
+            Action::Page(PageAction::AlignSelection(ViewportPosition::Center))
+        }
+        KeyCommand::SelectionToBottom => {
+            Action::Page(PageAction::AlignSelection(ViewportPosition::Bottom))
+        }
+        KeyCommand::Search => Action::EnterSearchBar,
+        KeyCommand::Bookmarks => Action::SwitchContextBookmarks,
+        KeyCommand::History => Action::SwitchContextHistory,
+        KeyCommand::Notes => Action::SwitchContextNotes,
+        KeyCommand::ReadingQueue => Action::SwitchContextReadingQueue,
+        KeyCommand::ReadNext => Action::ReadNext,
+        KeyCommand::Feed => Action::SwitchContextFeed,
+        KeyCommand::Cache => Action::SwitchContextCache,
+        KeyCommand::Workspaces => Action::ToggleWorkspaces,
+        KeyCommand::Help => Action::ToggleShowHelp,
+        KeyCommand::CommandPalette => Action::ToggleCommandPalette,
+        KeyCommand::Quit => Action::Quit,
+    }
+}
+
+/// Recognizes the configured key sequences in the pressed keys
+///
+/// A key that can start a sequence waits for the next key. When the keys typed so far can't be
+/// continued to a sequence, or the next key doesn't come in time, they are handled on their own
+/// like they would be without any sequences
+pub struct KeySequences {
+    sequences: Vec<(Vec<Key>, KeyCommand)>,
+    timeout: Duration,
+    pending: Vec<KeyEvent>,
+    since: Instant,
+}
+
+impl KeySequences {
+    pub fn new(config: &KeysConfig) -> Self {
+        let sequences = config
+            .sequences
+            .iter()
+            .filter_map(
+                |(sequence, command)| match parse_sequence(sequence, config.leader) {
+                    Ok(keys) => Some((keys, *command)),
+                    Err(error) => {
+                        warn!("ignoring the key sequence: {}", error);
+                        None
+                    }
+                },
+            )
+            .collect();
+
+        Self {
+            sequences,
+            timeout: Duration::from_millis(config.sequence_timeout),
+            pending: Vec::new(),
+            since: Instant::now(),
+        }
+    }
+
+    /// Adds the pressed key to the keys typed so far. Returns the keys that are handled on their
+    /// own, in the order they were pressed, and the command of the sequence that was completed
+    pub fn push(&mut self, key: KeyEvent) -> (Vec<KeyEvent>, Option<KeyCommand>) {
+        let mut single_keys = self.expire();
+        self.pending.push(key);
+
+        while !self.pending.is_empty() {
+            let keys: Vec<Key> = self.pending.iter().copied().map(key_of).collect();
+            if let Some((_, command)) = self.sequences.iter().find(|(seq, _)| *seq == keys) {
+                self.pending.clear();
+                return (single_keys, Some(*command));
+            }
+            if self.sequences.iter().any(|(seq, _)| seq.starts_with(&keys)) {
+                self.since = Instant::now();
+                break;
+            }
+            // the first key doesn't start a sequence, but the keys after it might
+            single_keys.push(self.pending.remove(0));
+        }
+        (single_keys, None)
+    }
+
+    /// Returns the keys typed so far once the next key of the sequence didn't come in time
+    pub fn expire(&mut self) -> Vec<KeyEvent> {
+        if self.pending.is_empty() || self.since.elapsed() < self.timeout {
+            return Vec::new();
+        }
+        std::mem::take(&mut self.pending)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use super::{parse_sequence, KeySequences};
+    use crate::config::{KeyCommand, KeysConfig};
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    fn sequences(timeout: u64) -> KeySequences {
+        KeySequences::new(&KeysConfig {
+            leader: '\\',
+            sequence_timeout: timeout,
+            sequences: BTreeMap::from([
+                ("gg".to_string(), KeyCommand::ScrollToTop),
+                ("zz".to_string(), KeyCommand::SelectionToCenter),
+                ("<leader>b".to_string(), KeyCommand::Bookmarks),
+            ]),
+        })
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            parse_sequence("<leader>b", ' '),
+            Ok(vec![
+                (KeyCode::Char(' '), KeyModifiers::NONE),
+                (KeyCode::Char('b'), KeyModifiers::NONE)
+            ])
+        );
+        assert_eq!(
+            parse_sequence("<c-x><lt>", '\\'),
+            Ok(vec![
+                (KeyCode::Char('x'), KeyModifiers::CONTROL),
+                (KeyCode::Char('<'), KeyModifiers::NONE)
+            ])
+        );
+        assert!(parse_sequence("g", '\\').is_err());
+        assert!(parse_sequence("<leader", '\\').is_err());
+        assert!(parse_sequence("<f13>a", '\\').is_err());
+    }
+
+    #[test]
+    fn sequences_and_single_keys() {
+        let mut sequences = sequences(1000);
+
+        assert_eq!(sequences.push(key('g')), (vec![], None));
+        assert_eq!(
+            sequences.push(key('g')),
+            (vec![], Some(KeyCommand::ScrollToTop))
+        );
+
+        assert_eq!(sequences.push(key('\\')), (vec![], None));
+        assert_eq!(
+            sequences.push(key('b')),
+            (vec![], Some(KeyCommand::Bookmarks))
+        );
+
+        // keys that don't start a sequence are handled right away
+        assert_eq!(sequences.push(key('j')), (vec![key('j')], None));
+
+        // the second key starts another sequence
+        assert_eq!(sequences.push(key('z')), (vec![], None));
+        assert_eq!(sequences.push(key('g')), (vec![key('z')], None));
+        assert_eq!(
+            sequences.push(key('g')),
+            (vec![], Some(KeyCommand::ScrollToTop))
+        );
+    }
+
+    #[test]
+    fn default_sequences_start_with_leader() {
+        let config = KeysConfig::default();
+        for sequence in config.sequences.keys() {
+            let keys = parse_sequence(sequence, config.leader).unwrap();
+            assert_eq!(
+                keys[0],
+                (KeyCode::Char(config.leader), KeyModifiers::NONE),
+                "{}",
+                sequence
+            );
+        }
+    }
+
+    #[test]
+    fn timeout() {
+        let mut sequences = sequences(0);
+
+        assert_eq!(sequences.push(key('z')), (vec![], None));
+        assert_eq!(sequences.expire(), vec![key('z')]);
+        assert_eq!(sequences.expire(), vec![]);
+
+        // the late key starts over
+        assert_eq!(sequences.push(key('g')), (vec![], None));
+        assert_eq!(sequences.push(key('g')), (vec![key('g')], None));
+    }
+}
@@ -1,13 +1,45 @@
 pub mod action;
+pub mod annotations;
 pub mod app;
+pub mod bench;
+pub mod bookmarks;
+pub mod browser;
+pub mod bug_report;
+pub mod citation;
 pub mod cli;
+pub mod clipboard;
 pub mod components;
 pub mod config;
+pub mod cookies;
 pub mod event;
+pub mod export;
+pub mod fetch_queue;
+pub mod flashcards;
+pub mod fuzzy;
+pub mod history;
+pub mod hooks;
+pub mod i18n;
+pub mod image_cache;
 pub mod key_macros;
+pub mod key_sequences;
+pub mod local_search;
 pub mod logging;
+pub mod page_cache;
 pub mod page_loader;
 pub mod panic_handler;
+pub mod reading_queue;
+pub mod remote;
 pub mod renderer;
+pub mod replay;
+pub mod request_log;
+pub mod scroll_marks;
+pub mod setup;
+pub mod sources;
+pub mod speech;
+pub mod startup;
+pub mod tasks;
 pub mod terminal;
 pub mod ui;
+pub mod undo;
+pub mod workspaces;
+pub mod yank_ring;
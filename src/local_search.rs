@@ -0,0 +1,144 @@
+use tracing::warn;
+use wiki_api::languages::Language;
+
+use crate::{bookmarks::bookmarks, history::history, page_cache::cache_entries};
+
+/// Where a page found on this device comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LocalSource {
+    Bookmark,
+    History,
+    Cache,
+}
+
+/// A page on this device matching the query of a search
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalResult {
+    pub title: String,
+    pub source: LocalSource,
+}
+
+/// Ranks how well the title matches the query, lower is better. Every word of the query has to
+/// be part of the title, ignoring the case. `None` means no match
+fn match_rank(query: &str, title: &str) -> Option<usize> {
+    let (query, title) = (query.trim().to_lowercase(), title.to_lowercase());
+    if query.is_empty() || !query.split_whitespace().all(|word| title.contains(word)) {
+        return None;
+    }
+
+    Some(if title == query {
+        0
+    } else if title.starts_with(&query) {
+        1
+    } else {
+        2
+    })
+}
+
+/// Returns the best matching candidates, each title only once with the source it's ranked
+/// highest in. Better matches come first, then bookmarks, visited and cached pages
+fn rank(
+    query: &str,
+    candidates: impl IntoIterator<Item = (String, LocalSource)>,
+    limit: usize,
+) -> Vec<LocalResult> {
+    let mut matches: Vec<(usize, LocalResult)> = Vec::new();
+    for (title, source) in candidates {
+        let rank = match match_rank(query, &title) {
+            Some(rank) => rank,
+            None => continue,
+        };
+        match matches.iter_mut().find(|(_, result)| result.title == title) {
+            Some((_, result)) => result.source = result.source.min(source),
+            None => matches.push((rank, LocalResult { title, source })),
+        }
+    }
+
+    matches.sort_by(|(a_rank, a), (b_rank, b)| {
+        a_rank
+            .cmp(b_rank)
+            .then(a.source.cmp(&b.source))
+            .then_with(|| a.title.cmp(&b.title))
+    });
+    matches
+        .into_iter()
+        .take(limit)
+        .map(|(_, result)| result)
+        .collect()
+}
+
+/// Searches the bookmarks, the history and the page cache for pages in the language matching the
+/// query. This doesn't need a connection and finishes right away
+pub fn local_results(query: &str, language: &Language, limit: usize) -> Vec<LocalResult> {
+    if limit == 0 {
+        return Vec::new();
+    }
+
+    let mut candidates: Vec<(String, LocalSource)> = bookmarks()
+        .all()
+        .iter()
+        .filter(|bookmark| &bookmark.language == language)
+        .map(|bookmark| (bookmark.title.clone(), LocalSource::Bookmark))
+        .collect();
+    // the history doesn't know the language of a page
+    candidates.extend(
+        history()
+            .all()
+            .iter()
+            .map(|entry| (entry.title.clone(), LocalSource::History)),
+    );
+    match cache_entries() {
+        Ok(entries) => candidates.extend(
+            entries
+                .into_iter()
+                .filter(|entry| &entry.language == language)
+                .map(|entry| (entry.title, LocalSource::Cache)),
+        ),
+        Err(error) => warn!("unable to search the page cache: {:?}", error),
+    }
+
+    rank(query, candidates, limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{match_rank, rank, LocalSource};
+
+    #[test]
+    fn ranking() {
+        assert_eq!(match_rank("rust", "Rust"), Some(0));
+        assert_eq!(match_rank("rust", "Rust (programming language)"), Some(1));
+        assert_eq!(
+            match_rank("language rust", "Rust (programming language)"),
+            Some(2)
+        );
+        assert_eq!(match_rank("go", "Rust"), None);
+        assert_eq!(match_rank(" ", "Rust"), None);
+
+        let candidates = vec![
+            (
+                "Rust (programming language)".to_string(),
+                LocalSource::Cache,
+            ),
+            ("Rust Belt".to_string(), LocalSource::History),
+            ("Rust".to_string(), LocalSource::Cache),
+            ("Rust Belt".to_string(), LocalSource::Bookmark),
+            ("Go".to_string(), LocalSource::Bookmark),
+        ];
+        let results: Vec<(String, LocalSource)> = rank("rust", candidates, 3)
+            .into_iter()
+            .map(|result| (result.title, result.source))
+            .collect();
+        assert_eq!(
+            results,
+            [
+                ("Rust".to_string(), LocalSource::Cache),
+                ("Rust Belt".to_string(), LocalSource::Bookmark),
+                (
+                    "Rust (programming language)".to_string(),
+                    LocalSource::Cache
+                ),
+            ]
+        );
+    }
+}
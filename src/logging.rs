@@ -5,12 +5,14 @@ use tracing_subscriber::{self, prelude::*, EnvFilter};
 use crate::config;
 
 const LOG_ENV: &str = "WIKI_TUI_LOG";
+/// Name of the log file in the data directory
+pub const LOG_FILE: &str = "wiki-tui.log";
 
 pub fn initialize_logging() -> Result<()> {
     let directory = config::data_dir()?;
     std::fs::create_dir_all(directory.clone())
         .context(format!("{directory:?} could not be created"))?;
-    let log_path = directory.join("wiki-tui.log");
+    let log_path = directory.join(LOG_FILE);
     let log_file = std::fs::File::create(log_path)?;
 
     let env_filter = EnvFilter::from_env(LOG_ENV);
@@ -3,21 +3,32 @@ use std::sync::Arc;
 use anyhow::Result;
 use tokio::sync::{mpsc, Mutex};
 use wiki_tui::{
-    action::{Action, ActionResult},
+    action::{Action, ActionResult, ExitMode},
     app::AppComponent,
     cli::match_cli,
     components::Component,
+    config::load_config,
+    cookies::initialize_cookies,
     event::EventHandler,
     logging::initialize_logging,
     panic_handler::initialize_panic_handler,
+    request_log::initialize_request_log,
+    setup::initialize_config,
+    startup::{format_startup_profile, is_profiling_startup, startup_phase, startup_phases},
+    tasks::{cancel_tasks, tasks, wait_for_tasks},
     terminal::Tui,
     trace_dbg,
+    ui::downgrade_colors,
 };
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    println!(
-        r#"
+    // commands printing to stdout, like --dump, exit before the notice is printed
+    let actions = startup_phase("command line", match_cli);
+
+    if !is_profiling_startup() {
+        println!(
+            r#"
 IMPORTANT:
 wiki-tui is going through a major rewrite (we're going async and switching backends, among other 
 things). Please note that this is a DEVELOPMENT version and can / will include:
@@ -31,22 +42,38 @@ Please feel free to try out this version, and report bugs / suggestions / etc.!
 Thank you!
 - Builditluc
     "#
-    );
-
-    let actions = match_cli();
+        );
+    }
 
-    initialize_logging()?;
-    initialize_panic_handler()?;
+    // the subsystems only the interface needs are initialized here, after the commands that
+    // exit right away
+    startup_phase("logging", initialize_logging)?;
+    startup_phase("panic handler", initialize_panic_handler)?;
+    // on the first run, the config is generated with the language of the system
+    let first_run = initialize_config().await;
+    startup_phase("config", load_config)?;
+    startup_phase("request log", initialize_request_log);
+    startup_phase("cookies", initialize_cookies);
 
     let (action_tx, mut action_rx) = mpsc::unbounded_channel();
 
-    let app_component = Arc::new(Mutex::new(AppComponent::default()));
+    let app_component = startup_phase("components", || -> Result<_> {
+        let mut app_component = AppComponent::default();
+        app_component.init(action_tx.clone())?;
+        if let Some(first_run) = first_run {
+            app_component.show_setup(first_run);
+        }
+        Ok(Arc::new(Mutex::new(app_component)))
+    })?;
     let mut should_quit = false;
+    let mut exit_mode = ExitMode::CancelTasks;
+    let mut is_first_frame = true;
 
-    app_component.lock().await.init(action_tx.clone())?;
-
-    let mut tui = Tui::new()?;
-    tui.enter()?;
+    let mut tui = startup_phase("terminal", || -> Result<_> {
+        let tui = Tui::new()?;
+        tui.enter()?;
+        Ok(tui)
+    })?;
 
     let _action_tx = action_tx.clone();
     let _root = app_component.clone();
@@ -81,11 +108,26 @@ Thank you!
             match action {
                 Action::RenderTick => {
                     let mut app_component = app_component.lock().await;
-                    tui.terminal
-                        .draw(|frame| app_component.render(frame, frame.size()))
-                        .unwrap();
+                    let mut draw = || {
+                        tui.terminal
+                            .draw(|frame| {
+                                app_component.render(frame, frame.size());
+                                downgrade_colors(frame.buffer_mut());
+                            })
+                            .unwrap();
+                    };
+                    if is_first_frame {
+                        startup_phase("first frame", draw);
+                        is_first_frame = false;
+                        should_quit |= is_profiling_startup();
+                    } else {
+                        draw();
+                    }
+                }
+                Action::Exit(mode) => {
+                    should_quit = true;
+                    exit_mode = mode;
                 }
-                Action::Quit => should_quit = true,
                 action => match app_component.lock().await.update(action) {
                     ActionResult::Consumed(action) => action.send(&action_tx),
                     ActionResult::Ignored => {}
@@ -99,5 +141,16 @@ Thank you!
     }
 
     tui.exit()?;
+    match exit_mode {
+        ExitMode::WaitForTasks if !tasks().is_empty() => {
+            let count = tasks().len();
+            println!("waiting for {} background task(s) to finish...", count);
+            wait_for_tasks().await;
+        }
+        _ => cancel_tasks(),
+    }
+    if is_profiling_startup() {
+        print!("{}", format_startup_profile(&startup_phases()));
+    }
     Ok(())
 }
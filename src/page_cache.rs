@@ -0,0 +1,727 @@
+use std::{
+    collections::BTreeMap,
+    io::Read,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use bincode::Options;
+use tracing::{debug, info, warn};
+use wiki_api::{languages::Language, page::Page, redirects::normalize_title};
+
+use crate::config::{config, data_dir};
+
+const PAGES_DIRECTORY: &str = "pages";
+/// When every stored page was last opened, by its path relative to the page cache. File systems
+/// often don't track the access time, so it's tracked here
+const ACCESS_FILE: &str = "access.json";
+
+/// Serializes the updates of the access times, pages are stored from several tasks at once
+static ACCESS_LOCK: Mutex<()> = Mutex::new(());
+
+/// Start of every stored page, followed by the format version as a little endian `u32`
+const FORMAT_MAGIC: &[u8; 4] = b"WTPC";
+/// Version of the format pages are stored in. It has to be increased whenever the stored data
+/// changes, like a new field of `Page` or a new variant of `Data`, because the format doesn't
+/// describe itself. Pages stored in another version are dropped and fetched again
+const FORMAT_VERSION: u32 = 6;
+const HEADER_LENGTH: usize = FORMAT_MAGIC.len() + 4;
+
+/// Replaces the characters that are not allowed in file names
+pub fn file_name(title: &str) -> String {
+    title
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c => c,
+        })
+        .collect()
+}
+
+fn cache_dir() -> Result<PathBuf> {
+    Ok(data_dir()?.join(PAGES_DIRECTORY))
+}
+
+fn page_path_with(language: &Language, title: &str, extension: &str) -> Result<PathBuf> {
+    Ok(cache_dir()?.join(language.code()).join(format!(
+        "{}.{}",
+        file_name(&normalize_title(title)),
+        extension
+    )))
+}
+
+fn page_path(language: &Language, title: &str) -> Result<PathBuf> {
+    page_path_with(language, title, "bin")
+}
+
+/// Path of the file holding the title a redirecting title leads to, pages are stored under the
+/// title they were redirected to
+fn alias_path(language: &Language, title: &str) -> Result<PathBuf> {
+    page_path_with(language, title, "redirect")
+}
+
+/// Returns the title the page is stored under, the title it redirects to when it's an alias
+fn stored_title(language: &Language, title: &str) -> String {
+    alias_path(language, title)
+        .and_then(|path| Ok(std::fs::read_to_string(path)?))
+        .ok()
+        .map(|target| target.trim().to_string())
+        .filter(|target| !target.is_empty())
+        .unwrap_or_else(|| title.to_string())
+}
+
+/// Path of a page stored as JSON, like it was done before pages were stored pre-parsed
+fn legacy_page_path(language: &Language, title: &str) -> Result<PathBuf> {
+    page_path_with(language, title, "json")
+}
+
+fn header() -> [u8; HEADER_LENGTH] {
+    let mut header = [0; HEADER_LENGTH];
+    header[..FORMAT_MAGIC.len()].copy_from_slice(FORMAT_MAGIC);
+    header[FORMAT_MAGIC.len()..].copy_from_slice(&FORMAT_VERSION.to_le_bytes());
+    header
+}
+
+/// The section indices are not stored, they are the position of the section
+fn number_sections(page: &mut Page) {
+    if let Some(ref mut sections) = page.sections {
+        for (index, section) in sections.iter_mut().enumerate() {
+            section.index = index;
+        }
+    }
+}
+
+/// Serializes the parsed page, so loading it again doesn't need to parse any HTML
+pub fn encode_page(page: &Page) -> Result<Vec<u8>> {
+    let mut bytes = header().to_vec();
+    bincode::DefaultOptions::new()
+        .serialize_into(&mut bytes, page)
+        .context("unable to serialize the page")?;
+    Ok(bytes)
+}
+
+/// Deserializes a page serialized with [`encode_page`], `None` when it was serialized in
+/// another format version
+pub fn decode_page(bytes: &[u8]) -> Result<Option<Page>> {
+    if !bytes.starts_with(&header()) {
+        return Ok(None);
+    }
+
+    let mut page: Page = bincode::DefaultOptions::new()
+        .deserialize(&bytes[HEADER_LENGTH..])
+        .context("unable to deserialize the page")?;
+    number_sections(&mut page);
+    Ok(Some(page))
+}
+
+/// Returns whether the file starts with the header of the current format
+fn has_current_format(path: &Path) -> bool {
+    let mut bytes = [0; HEADER_LENGTH];
+    std::fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut bytes))
+        .map(|_| bytes == header())
+        .unwrap_or(false)
+}
+
+/// Stores the page in the data directory, so it can be loaded while offline
+pub fn store_page(page: &Page) -> Result<()> {
+    let path = page_path(&page.language, &page.title)?;
+    if let Some(directory) = path.parent() {
+        std::fs::create_dir_all(directory).with_context(|| {
+            format!("unable to create the page cache '{}'", directory.display())
+        })?;
+    }
+
+    std::fs::write(&path, encode_page(page)?)
+        .with_context(|| format!("unable to write the cached page '{}'", path.display()))?;
+    debug!("cached '{}' at '{}'", page.title, path.display());
+    record_access(&path);
+
+    // a stored page isn't an alias anymore, it would hide the page
+    let _ = std::fs::remove_file(alias_path(&page.language, &page.title)?);
+    // the page is looked up by the title that was requested, which may redirect to it
+    if let Some(ref source) = page.redirected_from {
+        if normalize_title(source) != normalize_title(&page.title) {
+            let alias = alias_path(&page.language, source)?;
+            std::fs::write(&alias, &page.title).with_context(|| {
+                format!("unable to write the cached redirect '{}'", alias.display())
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns whether the page was stored before, in the current format or as JSON
+pub fn is_cached(language: &Language, title: &str) -> bool {
+    let title = &stored_title(language, title);
+    page_path(language, title)
+        .map(|path| has_current_format(&path))
+        .unwrap_or(false)
+        || legacy_page_path(language, title)
+            .map(|path| path.exists())
+            .unwrap_or(false)
+}
+
+/// Loads a stored page, `None` when the page was never stored or was stored in an older format.
+/// Pages stored as JSON are converted into the current format
+pub fn cached_page(language: &Language, title: &str) -> Result<Option<Page>> {
+    let title = &stored_title(language, title);
+    let path = page_path(language, title)?;
+    if path.exists() {
+        let bytes = std::fs::read(&path)
+            .with_context(|| format!("unable to read the cached page '{}'", path.display()))?;
+        let page = decode_page(&bytes)
+            .with_context(|| format!("unable to load the cached page '{}'", path.display()))?;
+        match page {
+            Some(_) => record_access(&path),
+            None => {
+                info!(
+                    "dropping '{}', it was stored in an older format",
+                    path.display()
+                );
+                std::fs::remove_file(&path).with_context(|| {
+                    format!("unable to remove the cached page '{}'", path.display())
+                })?;
+            }
+        }
+        return Ok(page);
+    }
+
+    let legacy_path = legacy_page_path(language, title)?;
+    if !legacy_path.exists() {
+        return Ok(None);
+    }
+
+    let contents = std::fs::read_to_string(&legacy_path)
+        .with_context(|| format!("unable to read the cached page '{}'", legacy_path.display()))?;
+    let mut page: Page = serde_json::from_str(&contents).with_context(|| {
+        format!(
+            "unable to parse the cached page '{}'",
+            legacy_path.display()
+        )
+    })?;
+    number_sections(&mut page);
+
+    store_page(&page)?;
+    std::fs::remove_file(&legacy_path).with_context(|| {
+        format!(
+            "unable to remove the cached page '{}'",
+            legacy_path.display()
+        )
+    })?;
+    debug!("converted the cached page '{}'", legacy_path.display());
+    Ok(Some(page))
+}
+
+fn unix_time(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+/// Key of the page in the access times, its path relative to the page cache
+fn access_key(cache_dir: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(cache_dir).ok()?;
+    Some(relative.to_string_lossy().replace('\\', "/"))
+}
+
+fn load_access_times(cache_dir: &Path) -> BTreeMap<String, i64> {
+    std::fs::read_to_string(cache_dir.join(ACCESS_FILE))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn save_access_times(cache_dir: &Path, access_times: &BTreeMap<String, i64>) -> Result<()> {
+    let path = cache_dir.join(ACCESS_FILE);
+    std::fs::write(&path, serde_json::to_string(access_times)?)
+        .with_context(|| format!("unable to write the access times '{}'", path.display()))
+}
+
+/// Changes the stored access times, while no one else does
+fn update_access_times(
+    cache_dir: &Path,
+    update: impl FnOnce(&mut BTreeMap<String, i64>),
+) -> Result<()> {
+    let _lock = ACCESS_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let mut access_times = load_access_times(cache_dir);
+    update(&mut access_times);
+    save_access_times(cache_dir, &access_times)
+}
+
+/// Remembers that the stored page was just opened
+fn record_access(path: &Path) {
+    let result = cache_dir().and_then(|cache_dir| {
+        let key = match access_key(&cache_dir, path) {
+            Some(key) => key,
+            None => return Ok(()),
+        };
+        update_access_times(&cache_dir, |access_times| {
+            access_times.insert(key, unix_time(SystemTime::now()));
+        })
+    });
+    if let Err(error) = result {
+        warn!(
+            "unable to record the access of the cached page: {:?}",
+            error
+        );
+    }
+}
+
+/// A page stored in the page cache
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheEntry {
+    /// Title of the page, as far as it's allowed in a file name
+    pub title: String,
+    pub language: Language,
+    pub path: PathBuf,
+    /// Size of the stored page in bytes
+    pub size: u64,
+    /// Unix timestamp of when the page was last opened or stored
+    pub accessed: i64,
+}
+
+/// Lists every page in the page cache, the largest first
+pub fn cache_entries() -> Result<Vec<CacheEntry>> {
+    let cache_dir = cache_dir()?;
+    if !cache_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let access_times = load_access_times(&cache_dir);
+
+    let mut entries = Vec::new();
+    for language_dir in std::fs::read_dir(&cache_dir)
+        .with_context(|| format!("unable to read the page cache '{}'", cache_dir.display()))?
+    {
+        let language_dir = language_dir?.path();
+        if !language_dir.is_dir() {
+            continue;
+        }
+        let language = match language_dir.file_name() {
+            Some(code) => Language::from(code.to_string_lossy().as_ref()),
+            None => continue,
+        };
+
+        for file in std::fs::read_dir(&language_dir)? {
+            let path = file?.path();
+            let is_page = matches!(
+                path.extension().and_then(|extension| extension.to_str()),
+                Some("bin" | "json")
+            );
+            if !is_page {
+                continue;
+            }
+
+            let metadata = std::fs::metadata(&path)?;
+            let accessed = access_key(&cache_dir, &path)
+                .and_then(|key| access_times.get(&key).copied())
+                .or_else(|| metadata.modified().ok().map(unix_time))
+                .unwrap_or_default();
+            entries.push(CacheEntry {
+                title: path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                language: language.clone(),
+                path,
+                size: metadata.len(),
+                accessed,
+            });
+        }
+    }
+
+    entries.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.title.cmp(&b.title)));
+    Ok(entries)
+}
+
+/// Removes the stored pages, returns how many were removed
+pub fn remove_entries(entries: &[CacheEntry]) -> Result<usize> {
+    let cache_dir = cache_dir()?;
+    let mut removed = Vec::new();
+    for entry in entries {
+        match std::fs::remove_file(&entry.path) {
+            Ok(_) => removed.extend(access_key(&cache_dir, &entry.path)),
+            Err(error) => warn!(
+                "unable to remove the cached page '{}': {:?}",
+                entry.path.display(),
+                error
+            ),
+        }
+    }
+
+    if !removed.is_empty() {
+        update_access_times(&cache_dir, |access_times| {
+            for key in removed.iter() {
+                access_times.remove(key);
+            }
+        })?;
+    }
+    Ok(removed.len())
+}
+
+/// Writes removed pages back with their contents and access times, returns how many were
+/// restored
+pub fn restore_entries(pages: &[(CacheEntry, Vec<u8>)]) -> Result<usize> {
+    let cache_dir = cache_dir()?;
+    let mut restored = Vec::new();
+    for (entry, contents) in pages {
+        if let Some(parent) = entry.path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("unable to create '{}'", parent.display()))?;
+        }
+        std::fs::write(&entry.path, contents).with_context(|| {
+            format!(
+                "unable to restore the cached page '{}'",
+                entry.path.display()
+            )
+        })?;
+        if let Some(key) = access_key(&cache_dir, &entry.path) {
+            restored.push((key, entry.accessed));
+        }
+    }
+
+    update_access_times(&cache_dir, |access_times| {
+        access_times.extend(restored);
+    })?;
+    Ok(pages.len())
+}
+
+/// Returns the pages that weren't opened for longer than `age`
+pub fn older_than(entries: &[CacheEntry], age: Duration, now: SystemTime) -> Vec<CacheEntry> {
+    let limit = unix_time(now) - age.as_secs() as i64;
+    entries
+        .iter()
+        .filter(|entry| entry.accessed < limit)
+        .cloned()
+        .collect()
+}
+
+/// Returns the least recently used pages that have to be removed for the cache to fit into
+/// `max_size` bytes
+pub fn least_recently_used(entries: &[CacheEntry], max_size: u64) -> Vec<CacheEntry> {
+    let mut size: u64 = entries.iter().map(|entry| entry.size).sum();
+    let mut entries = entries.to_vec();
+    entries.sort_by_key(|entry| entry.accessed);
+
+    entries
+        .into_iter()
+        .take_while(|entry| {
+            let is_over = size > max_size;
+            size = size.saturating_sub(entry.size);
+            is_over
+        })
+        .collect()
+}
+
+/// Removes the least recently used pages until the page cache fits into the size configured in
+/// `cache.max_size`, returns how many were removed
+pub fn enforce_max_size() -> Result<usize> {
+    let max_size = match config().cache.max_size {
+        0 => return Ok(0),
+        max_size => max_size * 1024 * 1024,
+    };
+
+    let evicted = least_recently_used(&cache_entries()?, max_size);
+    if evicted.is_empty() {
+        return Ok(0);
+    }
+    let removed = remove_entries(&evicted)?;
+    info!(
+        "removed {} least recently used pages to keep the page cache below {} MiB",
+        removed,
+        config().cache.max_size
+    );
+    Ok(removed)
+}
+
+/// Returns whether the page the alias leads to is still stored
+fn redirects_to_page(language_dir: &Path, alias: &Path) -> bool {
+    std::fs::read_to_string(alias)
+        .map(|target| {
+            let name = file_name(&normalize_title(target.trim()));
+            ["bin", "json"].iter().any(|extension| {
+                language_dir
+                    .join(format!("{}.{}", name, extension))
+                    .exists()
+            })
+        })
+        .unwrap_or(false)
+}
+
+/// Cleans up the page cache: removes pages stored in an older format, files that aren't pages,
+/// redirects to pages that are gone, empty language folders and the access times of pages that
+/// are gone. Returns how many files and folders were removed
+pub fn vacuum() -> Result<usize> {
+    let cache_dir = cache_dir()?;
+    if !cache_dir.exists() {
+        return Ok(0);
+    }
+
+    let mut removed = 0;
+    for language_dir in std::fs::read_dir(&cache_dir)? {
+        let language_dir = language_dir?.path();
+        if !language_dir.is_dir() {
+            continue;
+        }
+
+        for file in std::fs::read_dir(&language_dir)? {
+            let path = file?.path();
+            let is_outdated = match path.extension().and_then(|extension| extension.to_str()) {
+                Some("bin") => !has_current_format(&path),
+                // pages stored as JSON are converted once they are opened
+                Some("json") => false,
+                Some("redirect") => !redirects_to_page(&language_dir, &path),
+                _ => true,
+            };
+            if is_outdated && path.is_file() {
+                debug!("vacuum: removing '{}'", path.display());
+                std::fs::remove_file(&path)
+                    .with_context(|| format!("unable to remove '{}'", path.display()))?;
+                removed += 1;
+            }
+        }
+
+        let is_empty = std::fs::read_dir(&language_dir)?.next().is_none();
+        if is_empty {
+            std::fs::remove_dir(&language_dir)
+                .with_context(|| format!("unable to remove '{}'", language_dir.display()))?;
+            removed += 1;
+        }
+    }
+
+    update_access_times(&cache_dir, |access_times| {
+        access_times.retain(|key, _| cache_dir.join(key).exists());
+    })?;
+    info!("vacuumed the page cache, removed {} files", removed);
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{
+        de::{self, Visitor},
+        forward_to_deserialize_any, Deserialize, Deserializer,
+    };
+    use wiki_api::{
+        document::{Data, HeaderKind},
+        languages::Language,
+        page::{Page, Section},
+        parser::parse_document,
+    };
+
+    use std::{
+        path::PathBuf,
+        time::{Duration, SystemTime, UNIX_EPOCH},
+    };
+
+    use super::{
+        decode_page, encode_page, least_recently_used, older_than, redirects_to_page, CacheEntry,
+        FORMAT_MAGIC, FORMAT_VERSION,
+    };
+
+    /// Deserializer that only records the names of the variants of an enum or the fields of a
+    /// struct, which is what the stored format depends on
+    struct LayoutRecorder<'a>(&'a mut Vec<&'static str>);
+
+    impl<'de, 'a> Deserializer<'de> for LayoutRecorder<'a> {
+        type Error = de::value::Error;
+
+        fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Self::Error> {
+            Err(de::Error::custom("not an enum or a struct"))
+        }
+
+        fn deserialize_enum<V: Visitor<'de>>(
+            self,
+            _: &'static str,
+            variants: &'static [&'static str],
+            _: V,
+        ) -> Result<V::Value, Self::Error> {
+            self.0.extend(variants);
+            Err(de::Error::custom("recorded"))
+        }
+
+        fn deserialize_struct<V: Visitor<'de>>(
+            self,
+            _: &'static str,
+            fields: &'static [&'static str],
+            _: V,
+        ) -> Result<V::Value, Self::Error> {
+            self.0.extend(fields);
+            Err(de::Error::custom("recorded"))
+        }
+
+        forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+            option unit unit_struct newtype_struct seq tuple tuple_struct map identifier
+            ignored_any
+        }
+    }
+
+    fn layout<'de, T: Deserialize<'de>>() -> Vec<&'static str> {
+        let mut names = Vec::new();
+        let _ = T::deserialize(LayoutRecorder(&mut names));
+        names
+    }
+
+    #[test]
+    fn format_version_matches_layout() {
+        // when this fails, the stored data changed: increase `FORMAT_VERSION` and update the
+        // layout and the version here together
+        assert_eq!(FORMAT_VERSION, 6);
+        assert_eq!(
+            layout::<Page>(),
+            [
+                "title",
+                "pageid",
+                "content",
+                "language",
+                "language_links",
+                "sections",
+                "revision_id",
+                "is_disambiguation",
+                "quality",
+                "parse_time",
+                "redirected_from",
+            ]
+        );
+        assert_eq!(
+            layout::<Data>(),
+            [
+                "Section",
+                "Header",
+                "Text",
+                "Division",
+                "Paragraph",
+                "HorizontalRule",
+                "Preformatted",
+                "LineBreak",
+                "Poem",
+                "PoemIndentation",
+                "Span",
+                "Reflink",
+                "Citation",
+                "Reference",
+                "Hatnote",
+                "RedirectMessage",
+                "Disambiguation",
+                "MaintenanceNotice",
+                "Collapsible",
+                "RelatedPages",
+                "Navbox",
+                "NavboxTitle",
+                "NavboxGroup",
+                "NavboxLabel",
+                "Taxobox",
+                "TaxoboxHeading",
+                "TaxoboxRow",
+                "TaxoboxLabel",
+                "TaxoboxValue",
+                "Table",
+                "TableCaption",
+                "TableRow",
+                "TableCell",
+                "OrderedList",
+                "UnorderedList",
+                "ListItem",
+                "DescriptionList",
+                "DescriptionListTerm",
+                "DerscriptionListDescription",
+                "Bold",
+                "Italic",
+                "Strikethrough",
+                "Underline",
+                "Small",
+                "Mark",
+                "Abbreviation",
+                "WikiLink",
+                "RedLink",
+                "MediaLink",
+                "ExternalLink",
+                "Image",
+                "Unknown",
+            ]
+        );
+    }
+
+    #[test]
+    fn encode_decode() {
+        let page = Page {
+            title: "Ferris".to_string(),
+            pageid: 42,
+            content: parse_document("<p>The <b>crab</b> of <a href=\"./Rust\">Rust</a></p>"),
+            language: Language::from("de"),
+            language_links: None,
+            sections: Some(vec![Section {
+                index: 0,
+                header_kind: HeaderKind::Sub,
+                text: "Origin".to_string(),
+                number: "1".to_string(),
+                anchor: "Origin".to_string(),
+            }]),
+            revision_id: Some(7),
+            is_disambiguation: false,
+            quality: None,
+            parse_time: None,
+            redirected_from: None,
+        };
+        let mut bytes = encode_page(&page).unwrap();
+        assert!(decode_page(&bytes).unwrap() == Some(page));
+
+        // a page stored in another version is dropped
+        bytes[FORMAT_MAGIC.len()] += 1;
+        assert!(decode_page(&bytes).unwrap().is_none());
+    }
+
+    #[test]
+    fn eviction() {
+        let entry = |title: &str, size: u64, accessed: i64| CacheEntry {
+            title: title.to_string(),
+            language: Language::default(),
+            path: PathBuf::from(format!("en/{}.bin", title)),
+            size,
+            accessed,
+        };
+        let entries = vec![
+            entry("Rust", 300, 30),
+            entry("Ferris", 100, 10),
+            entry("Cargo", 200, 20),
+        ];
+        let titles = |entries: Vec<CacheEntry>| -> Vec<String> {
+            entries.into_iter().map(|entry| entry.title).collect()
+        };
+
+        assert!(least_recently_used(&entries, 600).is_empty());
+        assert_eq!(titles(least_recently_used(&entries, 500)), ["Ferris"]);
+        assert_eq!(
+            titles(least_recently_used(&entries, 350)),
+            ["Ferris", "Cargo"]
+        );
+        assert_eq!(least_recently_used(&entries, 0).len(), 3);
+
+        let now = UNIX_EPOCH + Duration::from_secs(40);
+        assert_eq!(
+            titles(older_than(&entries, Duration::from_secs(25), now)),
+            ["Ferris"]
+        );
+        assert!(older_than(&entries, Duration::from_secs(60), SystemTime::UNIX_EPOCH).is_empty());
+    }
+
+    #[test]
+    fn redirect_aliases() {
+        let directory =
+            std::env::temp_dir().join(format!("wiki-tui-redirects-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let alias = directory.join("Ferris the crab.redirect");
+        std::fs::write(&alias, "Ferris (mascot)").unwrap();
+
+        assert!(!redirects_to_page(&directory, &alias));
+        std::fs::write(directory.join("Ferris (mascot).bin"), b"").unwrap();
+        assert!(redirects_to_page(&directory, &alias));
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
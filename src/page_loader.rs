@@ -1,45 +1,663 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
 use tokio::sync::mpsc::UnboundedSender;
-use tracing::error;
-use wiki_api::{languages::Language, page::Page, Endpoint};
+use tracing::{error, info, warn};
+use wiki_api::{
+    assessment::fetch_qualities,
+    backend::PageBackend,
+    categories::fetch_category_members,
+    disambiguation::disambiguation_targets,
+    document::{Data, Document},
+    error::ApiError,
+    languages::Language,
+    links::fetch_language_titles,
+    page::{Page, Section},
+    pageviews::fetch_page_views,
+    revisions::fetch_latest_revisions,
+    transform::{
+        apply_transforms, ConvertUnits, DocumentTransform, NumberReferences,
+        StripMaintenanceNotices,
+    },
+    Endpoint,
+};
+
+use crate::{
+    action::{Action, DisambiguationAction, FailedRequest, PageViewerAction},
+    bookmarks::bookmarks,
+    components::page::link_title,
+    config::{config, StatusSegment},
+    fetch_queue::{fetch_queue, FetchStatus},
+    page_cache::{cached_page, enforce_max_size, is_cached, store_page},
+    renderer::epub::{save_epub, EpubSource},
+    sources::content_source,
+    tasks::{spawn_task, TaskKind},
+    workspaces::Workspace,
+};
+
+/// At most this many articles of a category are exported
+const EPUB_MAX_CATEGORY_PAGES: usize = 200;
+
+/// Removes the least recently opened pages in the background once the page cache is larger than
+/// configured
+fn enforce_cache_size() {
+    tokio::task::spawn_blocking(|| {
+        if let Err(error) = enforce_max_size() {
+            warn!("unable to limit the size of the page cache: {:?}", error);
+        }
+    });
+}
+
+/// Runs the configured transforms over the content of a fetched page, converts its units and
+/// numbers its references
+fn prepare_content(content: Document) -> Document {
+    let mut content = apply_transforms(content, &config().parser.transforms);
+    if !config().parser.show_maintenance_banners {
+        content = StripMaintenanceNotices.transform(content);
+    }
+    if let Some(system) = config().display.units {
+        content = ConvertUnits(system).transform(content);
+    }
+    NumberReferences.transform(content)
+}
 
-use crate::action::{Action, PageViewerAction};
+/// Fetches the page and prepares it for displaying. Every fetched page is stored in the page
+/// cache
+async fn fetch_page(
+    backend: &dyn PageBackend,
+    endpoint: Endpoint,
+    title: &str,
+    language: &Language,
+) -> Result<Page> {
+    let mut page = backend.fetch_page(title, language).await?;
+
+    page.content = prepare_content(page.content);
+    // archives don't know the quality of their pages
+    if !content_source().is_archive() {
+        match fetch_qualities(endpoint, &[page.title.clone()]).await {
+            Ok(mut qualities) => page.quality = qualities.remove(&page.title),
+            Err(error) => warn!("unable to fetch the page quality: {:?}", error),
+        }
+    }
+
+    match store_page(&page) {
+        Ok(_) => enforce_cache_size(),
+        Err(error) => warn!("unable to cache the page: {:?}", error),
+    }
+    Ok(page)
+}
+
+/// Loads the page in the configured language without a running app, from the page cache when
+/// configured to be offline
+pub async fn load_page_once(title: &str) -> Result<Page> {
+    let language = config().api.language.clone();
+    if config().api.offline {
+        return cached_page(&language, title)?
+            .ok_or_else(|| anyhow!("'{}' is not available offline", title));
+    }
+
+    let endpoint = language.wikipedia_endpoint();
+    let backend = content_source().backend(endpoint.clone());
+    fetch_page(backend.as_ref(), endpoint, title, &language).await
+}
+
+/// Displays the page, or lets the user choose between the listed pages when it's a
+/// disambiguation page
+fn show_page(tx: &UnboundedSender<Action>, page: Page) {
+    if page.is_disambiguation && !disambiguation_targets(&page.content).is_empty() {
+        tx.send(Action::SwitchContextDisambiguation).unwrap();
+        tx.send(Action::Disambiguation(DisambiguationAction::Show(page)))
+            .unwrap()
+    } else {
+        tx.send(Action::PageViewer(PageViewerAction::DisplayPage(page)))
+            .unwrap()
+    }
+}
+
+/// Fetches how often the page was viewed lately in the background, when the status bar shows it
+fn load_page_views(tx: &UnboundedSender<Action>, endpoint: Endpoint, title: String) {
+    if !config().status.shows(StatusSegment::PageViews) || content_source().is_archive() {
+        return;
+    }
+
+    let tx = tx.clone();
+    tokio::spawn(async move {
+        match fetch_page_views(endpoint, std::slice::from_ref(&title)).await {
+            Ok(mut page_views) => {
+                if let Some(page_views) = page_views.remove(&title) {
+                    tx.send(Action::PageViewer(PageViewerAction::FinishPageViews(
+                        title, page_views,
+                    )))
+                    .unwrap();
+                }
+            }
+            Err(error) => warn!("unable to fetch the views of '{}': {:?}", title, error),
+        }
+    });
+}
+
+/// Fetches the titles the pages linked from the page have in `api.secondary_language` in the
+/// background, when they are shown after the links
+fn load_language_hints(tx: &UnboundedSender<Action>, endpoint: Endpoint, page: &Page) {
+    let language = match config().api.secondary_language {
+        Some(ref language) if config().renderer.language_hints && language != &page.language => {
+            language.clone()
+        }
+        _ => return,
+    };
+    if content_source().is_archive() {
+        return;
+    }
+
+    let mut titles: Vec<String> = page
+        .content
+        .nodes
+        .iter()
+        .filter(|node| matches!(node.data, Data::WikiLink { .. }))
+        .filter_map(|node| link_title(&node.data))
+        .collect();
+    titles.sort();
+    titles.dedup();
+    if titles.is_empty() {
+        return;
+    }
+
+    let tx = tx.clone();
+    let title = page.title.clone();
+    tokio::spawn(async move {
+        match fetch_language_titles(endpoint, &titles, &language).await {
+            Ok(translations) => tx
+                .send(Action::PageViewer(PageViewerAction::FinishLanguageHints(
+                    title,
+                    translations,
+                )))
+                .unwrap(),
+            Err(error) => warn!(
+                "unable to fetch the titles of the links of '{}' in '{}': {:?}",
+                title,
+                language.name(),
+                error
+            ),
+        }
+    });
+}
 
 /// Responsible for loading a page
 pub struct PageLoader {
     endpoint: Endpoint,
     language: Language,
+    backend: Arc<dyn PageBackend>,
+
+    /// When offline, pages are only loaded from the page cache
+    is_offline: bool,
 
     action_tx: UnboundedSender<Action>,
 }
 
 impl PageLoader {
-    pub fn new(endpoint: Endpoint, language: Language, action_tx: UnboundedSender<Action>) -> Self {
+    pub fn new(language: Language, action_tx: UnboundedSender<Action>) -> Self {
+        let endpoint = language.wikipedia_endpoint();
+        let backend = content_source().backend(endpoint.clone());
+        info!("fetching pages using the '{}' backend", backend.name());
+        enforce_cache_size();
+
         Self {
             endpoint,
             language,
+            backend,
+            is_offline: config().api.offline,
             action_tx,
         }
     }
 
-    pub fn load_page(&self, title: String) {
-        let page_request = Page::builder()
-            .page(title)
-            .endpoint(self.endpoint.clone())
-            .language(self.language.clone());
+    pub fn language(&self) -> &Language {
+        &self.language
+    }
+
+    /// Loads pages from the Wikipedia edition in the language from now on
+    pub fn set_language(&mut self, language: Language) {
+        info!("switched the language to '{}'", language.name());
+        self.endpoint = language.wikipedia_endpoint();
+        self.backend = content_source().backend(self.endpoint.clone());
+        self.language = language;
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.is_offline
+    }
+
+    /// Switches between online and offline mode. When going online, the queued pages are fetched
+    pub fn toggle_offline(&mut self) {
+        self.is_offline = !self.is_offline;
+        info!(
+            "switched to {} mode",
+            if self.is_offline { "offline" } else { "online" }
+        );
+
+        if !self.is_offline {
+            self.fetch_queued();
+        }
+    }
+
+    pub fn load_page(&mut self, title: String) {
+        if self.is_offline {
+            return self.load_cached_page(title);
+        }
+
+        let backend = self.backend.clone();
+        let language = self.language.clone();
 
+        let endpoint = self.endpoint.clone();
         let tx = self.action_tx.clone();
         tokio::spawn(async move {
             tx.send(Action::SwitchContextPage).unwrap();
             tx.send(Action::EnterProcessing).unwrap();
 
-            match page_request.fetch().await {
-                Ok(page) => tx
-                    .send(Action::PageViewer(PageViewerAction::DisplayPage(page)))
-                    .unwrap(),
-                Err(error) => error!("Unable to fetch the page: {:?}", error),
+            match fetch_page(backend.as_ref(), endpoint.clone(), &title, &language).await {
+                Ok(page) => {
+                    let title = page.title.clone();
+                    load_language_hints(&tx, endpoint.clone(), &page);
+                    show_page(&tx, page);
+                    load_page_views(&tx, endpoint, title);
+                }
+                Err(error) => match ApiError::find(&error) {
+                    Some(api_error) => {
+                        warn!("the wiki refused to return '{}': {}", title, api_error);
+                        tx.send(Action::ShowApiError(
+                            FailedRequest::Page(title),
+                            api_error.clone(),
+                        ))
+                        .unwrap();
+                    }
+                    None => error!("Unable to fetch the page: {:?}", error),
+                },
             };
 
             tx.send(Action::EnterNormal).unwrap();
         });
     }
+
+    /// Loads the page without showing it or the processing screen, the shown page stays where
+    /// it is
+    pub fn load_page_in_background(&mut self, title: String) {
+        self.load_quietly(title, PageViewerAction::DisplayPageInBackground);
+    }
+
+    /// Checks whether there is a newer revision of the page than the loaded one, in the
+    /// background. Nothing is checked while offline or reading an archive
+    pub fn check_for_update(&self, title: String, language: Language, revision: usize) {
+        if self.is_offline || content_source().is_archive() {
+            return;
+        }
+
+        let tx = self.action_tx.clone();
+        tokio::spawn(async move {
+            let endpoint = language.wikipedia_endpoint();
+            match fetch_latest_revisions(endpoint, std::slice::from_ref(&title)).await {
+                Ok(revisions) => match revisions.get(&title) {
+                    Some(latest) if *latest > revision => {
+                        info!("'{}' was edited since it was loaded", title);
+                        tx.send(Action::PageViewer(PageViewerAction::UpdateAvailable(
+                            title, *latest,
+                        )))
+                        .unwrap();
+                    }
+                    _ => {}
+                },
+                Err(error) => warn!("unable to check '{}' for changes: {:?}", title, error),
+            }
+        });
+    }
+
+    /// Fetches the latest revision of the page and replaces the shown pages with it, without
+    /// the processing screen
+    pub fn reload_page(&mut self, title: String) {
+        if self.is_offline {
+            return info!("'{}' can't be reloaded while offline", title);
+        }
+        self.load_quietly(title, PageViewerAction::ReloadPage);
+    }
+
+    /// Loads the page to peek at it on top of the shown page
+    pub fn load_peek(&mut self, title: String) {
+        self.load_quietly(title, PageViewerAction::DisplayPeek);
+    }
+
+    /// Loads the page without switching to the page view or showing the processing screen, and
+    /// hands it to the page viewer with the action
+    fn load_quietly(&mut self, title: String, display: fn(Page) -> PageViewerAction) {
+        if self.is_offline && !is_cached(&self.language, &title) {
+            info!("'{}' is not available offline, queueing it", title);
+            fetch_queue().add(&title, &self.language);
+            return;
+        }
+
+        let backend = self.backend.clone();
+        let language = self.language.clone();
+        let endpoint = self.endpoint.clone();
+        let is_offline = self.is_offline;
+        let tx = self.action_tx.clone();
+
+        tokio::spawn(async move {
+            let result = if is_offline {
+                cached_page(&language, &title).and_then(|page| {
+                    page.ok_or_else(|| anyhow!("'{}' was removed from the page cache", title))
+                })
+            } else {
+                fetch_page(backend.as_ref(), endpoint, &title, &language).await
+            };
+
+            match result {
+                Ok(page) => {
+                    info!("loaded '{}' in the background", page.title);
+                    tx.send(Action::PageViewer(display(page))).unwrap();
+                }
+                Err(error) => error!("Unable to load the page in the background: {:?}", error),
+            }
+        });
+    }
+
+    fn load_cached_page(&mut self, title: String) {
+        if !is_cached(&self.language, &title) {
+            info!("'{}' is not available offline, queueing it", title);
+            fetch_queue().add(&title, &self.language);
+            self.action_tx.send(Action::SwitchContextPage).unwrap();
+            self.action_tx
+                .send(Action::PageViewer(PageViewerAction::ShowUnavailable(title)))
+                .unwrap();
+            return;
+        }
+
+        let language = self.language.clone();
+        let tx = self.action_tx.clone();
+        tokio::spawn(async move {
+            tx.send(Action::SwitchContextPage).unwrap();
+            tx.send(Action::EnterProcessing).unwrap();
+
+            match cached_page(&language, &title) {
+                Ok(Some(page)) => show_page(&tx, page),
+                Ok(None) => warn!("'{}' was removed from the page cache", title),
+                Err(error) => error!("Unable to load the cached page: {:?}", error),
+            }
+
+            tx.send(Action::EnterNormal).unwrap();
+        });
+    }
+
+    /// Loads only the section of the page, which is always fetched from the action API. Sections
+    /// are neither cached nor recorded in the history
+    pub fn load_section(&mut self, title: String, section: Section) {
+        if self.is_offline {
+            warn!("sections can't be loaded on their own while offline");
+            return;
+        }
+        if content_source().is_archive() {
+            warn!("sections can't be loaded on their own from a ZIM archive");
+            return;
+        }
+
+        let endpoint = self.endpoint.clone();
+        let language = self.language.clone();
+        let tx = self.action_tx.clone();
+        tokio::spawn(async move {
+            tx.send(Action::SwitchContextPage).unwrap();
+            tx.send(Action::EnterProcessing).unwrap();
+
+            let result = Page::builder()
+                .page(title)
+                .endpoint(endpoint)
+                .language(language)
+                .section(section.index)
+                .fetch()
+                .await;
+            match result {
+                Ok(mut page) => {
+                    page.content = prepare_content(page.content);
+                    tx.send(Action::PageViewer(PageViewerAction::DisplaySection(
+                        page,
+                        section.text,
+                    )))
+                    .unwrap();
+                }
+                Err(error) => error!("Unable to fetch the section: {:?}", error),
+            }
+
+            tx.send(Action::EnterNormal).unwrap();
+        });
+    }
+
+    /// Loads the redirect page with the title itself instead of the page it redirects to. Like
+    /// sections, redirects are not cached
+    pub fn load_redirect(&mut self, title: String) {
+        if self.is_offline {
+            warn!("redirects can't be opened without following them while offline");
+            return;
+        }
+
+        let backend = self.backend.clone();
+        let language = self.language.clone();
+        let tx = self.action_tx.clone();
+        tokio::spawn(async move {
+            tx.send(Action::SwitchContextPage).unwrap();
+            tx.send(Action::EnterProcessing).unwrap();
+
+            match backend
+                .fetch_page_without_redirects(&title, &language)
+                .await
+            {
+                Ok(mut page) => {
+                    page.content = prepare_content(page.content);
+                    tx.send(Action::PageViewer(PageViewerAction::DisplayPage(page)))
+                        .unwrap();
+                }
+                Err(error) => error!("Unable to fetch the redirect: {:?}", error),
+            }
+
+            tx.send(Action::EnterNormal).unwrap();
+        });
+    }
+
+    /// Loads the pages of the workspace, each from the wiki of its language, and shows them
+    /// instead of the open pages. Pages that can't be loaded are left out, while offline only the
+    /// cached pages are loaded
+    pub fn load_workspace(&mut self, workspace: Workspace) {
+        let language = self.language.clone();
+        let backend = self.backend.clone();
+        let is_offline = self.is_offline;
+        let tx = self.action_tx.clone();
+        tokio::spawn(async move {
+            tx.send(Action::SwitchContextPage).unwrap();
+            tx.send(Action::EnterProcessing).unwrap();
+
+            let mut pages = Vec::new();
+            let mut active = 0;
+            for (n, saved) in workspace.pages.into_iter().enumerate() {
+                let result = if is_offline {
+                    cached_page(&saved.language, &saved.title).and_then(|page| {
+                        page.ok_or_else(|| anyhow!("'{}' is not available offline", saved.title))
+                    })
+                } else {
+                    let endpoint = saved.language.wikipedia_endpoint();
+                    let backend = if saved.language == language {
+                        backend.clone()
+                    } else {
+                        content_source().backend(endpoint.clone())
+                    };
+                    fetch_page(backend.as_ref(), endpoint, &saved.title, &saved.language).await
+                };
+
+                match result {
+                    Ok(page) => {
+                        // the shown page is the closest loaded page below it when it's left out
+                        if n <= workspace.active {
+                            active = pages.len();
+                        }
+                        pages.push((page, saved.anchor));
+                    }
+                    Err(error) => warn!(
+                        "unable to load '{}' of the workspace '{}': {:?}",
+                        saved.title, workspace.name, error
+                    ),
+                }
+            }
+
+            if pages.is_empty() {
+                warn!(
+                    "none of the pages of the workspace '{}' could be loaded",
+                    workspace.name
+                );
+            } else {
+                info!(
+                    "opened {} pages of the workspace '{}'",
+                    pages.len(),
+                    workspace.name
+                );
+                tx.send(Action::PageViewer(PageViewerAction::DisplayWorkspace(
+                    pages, active,
+                )))
+                .unwrap();
+            }
+            tx.send(Action::EnterNormal).unwrap();
+        });
+    }
+
+    /// Adds the page to the fetch queue. The page is fetched right away when online
+    pub fn queue_page(&mut self, title: String) {
+        info!("queueing '{}' to be fetched later", title);
+        fetch_queue().add(&title, &self.language);
+        if !self.is_offline {
+            self.fetch_queued();
+        }
+    }
+
+    /// Fetches the pending pages of the fetch queue into the page cache, without displaying them
+    ///
+    /// This also works while offline, when the user asks for it
+    pub fn fetch_queued(&self) {
+        let pending = {
+            let mut queue = fetch_queue();
+            let pending = queue.pending();
+            for page in pending.iter() {
+                queue.set_status(&page.title, &page.language, FetchStatus::Fetching);
+            }
+            pending
+        };
+        if pending.is_empty() {
+            return;
+        }
+
+        let language = self.language.clone();
+        let backend = self.backend.clone();
+        let name = match pending.len() {
+            1 => pending[0].title.clone(),
+            count => format!("{} pages", count),
+        };
+        spawn_task(TaskKind::Fetch, name, async move {
+            for page in pending {
+                // pages queued in another language are fetched from their own wiki
+                let endpoint = page.language.wikipedia_endpoint();
+                let backend = if page.language == language {
+                    backend.clone()
+                } else {
+                    content_source().backend(endpoint.clone())
+                };
+                let result =
+                    fetch_page(backend.as_ref(), endpoint, &page.title, &page.language).await;
+                let status = match result {
+                    Ok(_) => {
+                        info!("fetched the queued page '{}'", page.title);
+                        FetchStatus::Fetched
+                    }
+                    Err(error) => {
+                        error!("Unable to fetch the queued page: {:?}", error);
+                        FetchStatus::Failed(error.to_string())
+                    }
+                };
+                fetch_queue().set_status(&page.title, &page.language, status);
+            }
+        });
+    }
+
+    /// Compiles the articles of the source into a single EPUB. Cached pages are taken from the
+    /// page cache, the other ones are fetched unless offline
+    pub fn export_epub(&self, source: EpubSource) {
+        let (title, titles) = match source {
+            EpubSource::Bookmarks => {
+                let titles: Vec<(String, Language)> = bookmarks()
+                    .all()
+                    .iter()
+                    .map(|bookmark| (bookmark.title.clone(), bookmark.language.clone()))
+                    .collect();
+                ("Bookmarks".to_string(), Some(titles))
+            }
+            EpubSource::Category(category) => (category, None),
+        };
+        if self.is_offline && titles.is_none() {
+            warn!("the articles of a category can't be listed while offline");
+            return;
+        }
+
+        let language = self.language.clone();
+        let backend = self.backend.clone();
+        let is_offline = self.is_offline;
+        let tx = self.action_tx.clone();
+        spawn_task(TaskKind::Export, format!("{}.epub", title), async move {
+            tx.send(Action::EnterProcessing).unwrap();
+
+            let titles = match titles {
+                Some(titles) => titles,
+                None => match fetch_category_members(
+                    language.wikipedia_endpoint(),
+                    &title,
+                    EPUB_MAX_CATEGORY_PAGES,
+                )
+                .await
+                {
+                    Ok(members) => members
+                        .into_iter()
+                        .map(|member| (member, language.clone()))
+                        .collect(),
+                    Err(error) => {
+                        error!("Unable to list the articles of the category: {:?}", error);
+                        return tx.send(Action::EnterNormal).unwrap();
+                    }
+                },
+            };
+
+            let mut pages = Vec::with_capacity(titles.len());
+            for (title, page_language) in titles {
+                match cached_page(&page_language, &title) {
+                    Ok(Some(page)) => {
+                        pages.push(page);
+                        continue;
+                    }
+                    Ok(None) => {}
+                    Err(error) => warn!("unable to load the cached page: {:?}", error),
+                }
+                if is_offline {
+                    warn!("'{}' is not available offline, leaving it out", title);
+                    continue;
+                }
+
+                let endpoint = page_language.wikipedia_endpoint();
+                let backend = if page_language == language {
+                    backend.clone()
+                } else {
+                    content_source().backend(endpoint.clone())
+                };
+                match fetch_page(backend.as_ref(), endpoint, &title, &page_language).await {
+                    Ok(page) => pages.push(page),
+                    Err(error) => error!("Unable to fetch '{}' for the export: {:?}", title, error),
+                }
+            }
+
+            if pages.is_empty() {
+                warn!("no pages to export as '{}'", title);
+            } else if let Err(error) = save_epub(&title, &pages) {
+                error!("Unable to export the pages: {:?}", error);
+            }
+
+            tx.send(Action::EnterNormal).unwrap();
+        });
+    }
 }
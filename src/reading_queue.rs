@@ -0,0 +1,169 @@
+use std::{
+    path::PathBuf,
+    sync::{Mutex, MutexGuard, OnceLock},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+use wiki_api::languages::Language;
+
+use crate::config::data_dir;
+
+const READING_QUEUE_FILE: &str = "reading_queue.json";
+
+static READING_QUEUE: OnceLock<Mutex<ReadingQueue>> = OnceLock::new();
+
+/// A page that is queued to be read next
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueuedPage {
+    pub title: String,
+    pub language: Language,
+    /// Unix timestamp of when the page was queued
+    pub added: i64,
+}
+
+/// Moves the entry at the position by the offset, staying inside the list. Returns where the
+/// entry is now
+fn reorder<T>(entries: &mut Vec<T>, position: usize, offset: isize) -> Option<usize> {
+    if position >= entries.len() {
+        return None;
+    }
+    let target = (position as isize + offset).clamp(0, entries.len() as isize - 1) as usize;
+    let entry = entries.remove(position);
+    entries.insert(target, entry);
+    Some(target)
+}
+
+/// Pages to read next in their order, stored in the data directory. Unlike the bookmarks, a page
+/// leaves the queue once it's read
+#[derive(Default)]
+pub struct ReadingQueue {
+    pages: Vec<QueuedPage>,
+}
+
+impl ReadingQueue {
+    fn path() -> Result<PathBuf> {
+        Ok(data_dir()?.join(READING_QUEUE_FILE))
+    }
+
+    fn load() -> Result<Self> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        info!("loading the reading queue from '{}'", path.display());
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("unable to read the reading queue '{}'", path.display()))?;
+        let pages = serde_json::from_str(&contents)
+            .with_context(|| format!("unable to parse the reading queue '{}'", path.display()))?;
+        Ok(Self { pages })
+    }
+
+    fn save(&self) {
+        let result = Self::path().and_then(|path| {
+            std::fs::create_dir_all(data_dir()?)?;
+            std::fs::write(&path, serde_json::to_string_pretty(&self.pages)?)
+                .with_context(|| format!("unable to write the reading queue '{}'", path.display()))
+        });
+
+        if let Err(error) = result {
+            error!("unable to save the reading queue: {:?}", error);
+        }
+    }
+
+    pub fn all(&self) -> &[QueuedPage] {
+        &self.pages
+    }
+
+    fn position(&self, title: &str, language: &Language) -> Option<usize> {
+        self.pages
+            .iter()
+            .position(|page| page.title == title && &page.language == language)
+    }
+
+    /// Queues the page after the other pages. Returns false when it's already queued
+    pub fn push(&mut self, title: &str, language: &Language) -> bool {
+        if self.position(title, language).is_some() {
+            return false;
+        }
+        self.pages.push(QueuedPage {
+            title: title.to_string(),
+            language: language.clone(),
+            added: chrono::Utc::now().timestamp(),
+        });
+        self.save();
+        true
+    }
+
+    /// Removes the page to read next from the queue and returns it
+    pub fn pop(&mut self) -> Option<QueuedPage> {
+        if self.pages.is_empty() {
+            return None;
+        }
+        let page = self.pages.remove(0);
+        self.save();
+        Some(page)
+    }
+
+    /// Removes the page at the position from the queue and returns it
+    pub fn remove(&mut self, position: usize) -> Option<QueuedPage> {
+        if position >= self.pages.len() {
+            return None;
+        }
+        let page = self.pages.remove(position);
+        self.save();
+        Some(page)
+    }
+
+    /// Queues a removed page again at its position, unless it was queued since
+    pub fn restore(&mut self, position: usize, page: QueuedPage) {
+        if self.position(&page.title, &page.language).is_some() {
+            return;
+        }
+        self.pages.insert(position.min(self.pages.len()), page);
+        self.save();
+    }
+
+    /// Moves the page at the position by the offset, negative offsets move it towards the front.
+    /// Returns where the page is now
+    pub fn move_page(&mut self, position: usize, offset: isize) -> Option<usize> {
+        let target = reorder(&mut self.pages, position, offset)?;
+        self.save();
+        Some(target)
+    }
+}
+
+/// Returns the stored reading queue, loading it on first use
+pub fn reading_queue() -> MutexGuard<'static, ReadingQueue> {
+    READING_QUEUE
+        .get_or_init(|| {
+            Mutex::new(ReadingQueue::load().unwrap_or_else(|error| {
+                error!("unable to load the reading queue: {:?}", error);
+                ReadingQueue::default()
+            }))
+        })
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::reorder;
+
+    #[test]
+    fn reorder_entries() {
+        let mut entries = vec!['a', 'b', 'c', 'd'];
+        assert_eq!(reorder(&mut entries, 2, -1), Some(1));
+        assert_eq!(entries, ['a', 'c', 'b', 'd']);
+
+        // entries stay inside the list
+        assert_eq!(reorder(&mut entries, 1, -5), Some(0));
+        assert_eq!(entries, ['c', 'a', 'b', 'd']);
+        assert_eq!(reorder(&mut entries, 3, 1), Some(3));
+        assert_eq!(entries, ['c', 'a', 'b', 'd']);
+
+        assert_eq!(reorder(&mut entries, 4, 1), None);
+    }
+}
@@ -0,0 +1,405 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::error;
+use wiki_api::languages::Language;
+
+use crate::{
+    action::{Action, ActionPacket, PageAction, SearchAction},
+    config::{config, data_dir, RemoteAction},
+    export::DumpFormat,
+};
+
+/// Name of the socket in the data directory, unless `remote.socket` is configured
+const SOCKET_NAME: &str = "wiki-tui.sock";
+
+/// A command another program sends to a running wiki-tui through the remote socket, one per
+/// line. It's either written as JSON, like `{"action": "open", "title": "Rust"}`, or like a
+/// command of the search bar, like `open "Rust"`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum RemoteCommand {
+    /// Opens the page with the title
+    Open { title: String },
+    /// Searches for the query
+    Search { query: String },
+    /// Switches the language pages are searched and loaded in
+    Language { code: String },
+    /// Exports the shown page in the format to the path
+    Export { format: DumpFormat, path: PathBuf },
+    /// Quits, asking first when tasks are running
+    Quit,
+}
+
+/// Removes the quotes around an argument
+fn unquote(argument: &str) -> String {
+    let argument = argument.trim();
+    for quote in ['"', '\''] {
+        if let Some(inner) = argument
+            .strip_prefix(quote)
+            .and_then(|argument| argument.strip_suffix(quote))
+        {
+            return inner.to_string();
+        }
+    }
+    argument.to_string()
+}
+
+impl RemoteCommand {
+    /// Parses a line sent through the socket
+    pub fn parse(line: &str) -> Result<Self> {
+        let line = line.trim();
+        if line.starts_with('{') {
+            return serde_json::from_str(line).context("the command isn't valid JSON");
+        }
+
+        let (name, args) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let argument = || -> Result<String> {
+            let argument = unquote(args);
+            if argument.is_empty() {
+                bail!("'{}' expects an argument", name);
+            }
+            Ok(argument)
+        };
+
+        Ok(match name {
+            "open" => RemoteCommand::Open { title: argument()? },
+            "search" => RemoteCommand::Search { query: argument()? },
+            "lang" | "language" => RemoteCommand::Language { code: argument()? },
+            "export" => {
+                let (format, path) = args
+                    .trim()
+                    .split_once(char::is_whitespace)
+                    .ok_or_else(|| anyhow!("expected 'export <format> <path>'"))?;
+                RemoteCommand::Export {
+                    format: DumpFormat::from_str(format, true)
+                        .map_err(|_| anyhow!("unknown export format '{}'", format))?,
+                    path: PathBuf::from(unquote(path)),
+                }
+            }
+            "quit" => RemoteCommand::Quit,
+            "" => bail!("the command is empty"),
+            name => bail!("unknown command '{}'", name),
+        })
+    }
+
+    /// The name the command is allowed with in `remote.allow`
+    pub fn kind(&self) -> RemoteAction {
+        match self {
+            RemoteCommand::Open { .. } => RemoteAction::Open,
+            RemoteCommand::Search { .. } => RemoteAction::Search,
+            RemoteCommand::Language { .. } => RemoteAction::Language,
+            RemoteCommand::Export { .. } => RemoteAction::Export,
+            RemoteCommand::Quit => RemoteAction::Quit,
+        }
+    }
+
+    /// Returns the actions the command stands for
+    pub fn packet(self) -> Result<ActionPacket> {
+        Ok(match self {
+            RemoteCommand::Open { title } => {
+                ActionPacket::single(Action::ExitSearchBar).action(Action::LoadPage(title))
+            }
+            RemoteCommand::Search { query } => ActionPacket::single(Action::ExitSearchBar)
+                .action(Action::SwitchContextSearch)
+                .action(Action::Search(SearchAction::StartSearch(query))),
+            RemoteCommand::Language { code } => {
+                let language =
+                    Language::find(&code).ok_or_else(|| anyhow!("unknown language '{}'", code))?;
+                ActionPacket::single(Action::SwitchLanguage(language))
+            }
+            RemoteCommand::Export { format, path } => {
+                ActionPacket::single(Action::Page(PageAction::ExportTo(format, path)))
+            }
+            RemoteCommand::Quit => ActionPacket::single(Action::Quit),
+        })
+    }
+}
+
+/// Parses the line and returns its actions, when the command is in the allowlist
+fn handle_line(line: &str, allow: &[RemoteAction]) -> Result<ActionPacket> {
+    let command = RemoteCommand::parse(line)?;
+    if !allow.contains(&command.kind()) {
+        let name = format!("{:?}", command.kind()).to_lowercase();
+        bail!(
+            "'{}' isn't allowed, it has to be added to remote.allow",
+            name
+        );
+    }
+    command.packet()
+}
+
+/// Returns the path of the remote socket
+pub fn socket_path() -> Result<PathBuf> {
+    match config().remote.socket {
+        Some(ref path) => Ok(path.clone()),
+        None => Ok(data_dir()?.join(SOCKET_NAME)),
+    }
+}
+
+#[cfg(unix)]
+mod socket {
+    use std::{
+        fs::{DirBuilder, Permissions},
+        os::unix::fs::{DirBuilderExt, FileTypeExt, PermissionsExt},
+        path::Path,
+    };
+
+    use anyhow::{bail, Context, Result};
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+        net::{UnixListener, UnixStream},
+        sync::mpsc::UnboundedSender,
+    };
+    use tracing::{info, warn};
+
+    use super::{handle_line, SOCKET_NAME};
+    use crate::{action::Action, config::config};
+
+    /// Binds the socket so only the user can connect to it. It's created in a private directory
+    /// next to the path and only moved to the path once it's protected, others can't connect in
+    /// between
+    fn bind_private(path: &Path) -> Result<UnixListener> {
+        let directory = path.parent().unwrap_or_else(|| Path::new(""));
+        let private = directory.join(format!(".wiki-tui-remote-{}", std::process::id()));
+        DirBuilder::new()
+            .mode(0o700)
+            .create(&private)
+            .with_context(|| format!("unable to create '{}'", private.display()))?;
+
+        let staged = private.join(SOCKET_NAME);
+        let listener = UnixListener::bind(&staged)
+            .with_context(|| format!("unable to listen on '{}'", path.display()))
+            .and_then(|listener| {
+                std::fs::set_permissions(&staged, Permissions::from_mode(0o600)).with_context(
+                    || format!("unable to protect the socket '{}'", path.display()),
+                )?;
+                std::fs::rename(&staged, path).with_context(|| {
+                    format!("unable to move the socket to '{}'", path.display())
+                })?;
+                Ok(listener)
+            });
+        let _ = std::fs::remove_dir_all(&private);
+        listener
+    }
+
+    /// Listens on the socket until the app closes, a socket left behind by an earlier run is
+    /// replaced
+    pub async fn listen(path: &Path, action_tx: UnboundedSender<Action>) -> Result<()> {
+        if let Ok(metadata) = std::fs::symlink_metadata(path) {
+            if !metadata.file_type().is_socket() {
+                bail!("'{}' exists and isn't a socket", path.display());
+            }
+            if UnixStream::connect(path).await.is_ok() {
+                bail!("another wiki-tui is listening on '{}'", path.display());
+            }
+            std::fs::remove_file(path)
+                .with_context(|| format!("unable to remove the old socket '{}'", path.display()))?;
+        }
+        if let Some(directory) = path.parent() {
+            std::fs::create_dir_all(directory)?;
+        }
+
+        let listener = bind_private(path)?;
+        info!("listening for remote commands on '{}'", path.display());
+
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let action_tx = action_tx.clone();
+            tokio::spawn(async move {
+                if let Err(error) = serve(stream, action_tx).await {
+                    warn!("the remote connection failed: {:?}", error);
+                }
+            });
+        }
+    }
+
+    /// Runs every line sent through the connection and answers it with `ok` or the error
+    async fn serve(stream: UnixStream, action_tx: UnboundedSender<Action>) -> Result<()> {
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let reply = match handle_line(&line, &config().remote.allow) {
+                Ok(packet) => {
+                    info!("running the remote command '{}'", line.trim());
+                    packet.send(&action_tx);
+                    "ok\n".to_string()
+                }
+                Err(error) => {
+                    warn!("refused the remote command '{}': {:?}", line.trim(), error);
+                    format!("error: {:#}\n", error)
+                }
+            };
+            writer.write_all(reply.as_bytes()).await?;
+        }
+        Ok(())
+    }
+
+    /// Sends the command to the running wiki-tui and returns its answer
+    pub async fn send(path: &Path, command: &str) -> Result<String> {
+        let stream = UnixStream::connect(path)
+            .await
+            .with_context(|| format!("no wiki-tui is listening on '{}'", path.display()))?;
+        let (reader, mut writer) = stream.into_split();
+        writer
+            .write_all(format!("{}\n", command.trim()).as_bytes())
+            .await?;
+        writer.shutdown().await?;
+
+        let mut reply = String::new();
+        BufReader::new(reader).read_line(&mut reply).await?;
+        Ok(reply.trim_end().to_string())
+    }
+}
+
+/// Listens for commands of other programs on the remote socket in the background, when it's
+/// enabled
+pub fn start_remote(action_tx: UnboundedSender<Action>) {
+    if !config().remote.enabled {
+        return;
+    }
+
+    #[cfg(unix)]
+    {
+        let path = match socket_path() {
+            Ok(path) => path,
+            Err(error) => return error!("unable to find the remote socket: {:?}", error),
+        };
+        tokio::spawn(async move {
+            if let Err(error) = socket::listen(&path, action_tx).await {
+                error!("stopped listening for remote commands: {:?}", error);
+            }
+        });
+    }
+    #[cfg(not(unix))]
+    {
+        drop(action_tx);
+        tracing::warn!("the remote socket is only supported on unix systems");
+    }
+}
+
+/// Sends the command to the running wiki-tui through the remote socket and returns its answer,
+/// `ok` or the error
+pub async fn send_command(command: &str) -> Result<String> {
+    #[cfg(unix)]
+    {
+        socket::send(&socket_path()?, command).await
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = command;
+        bail!("the remote socket is only supported on unix systems")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{handle_line, RemoteCommand};
+    use crate::{config::RemoteAction, export::DumpFormat};
+
+    #[test]
+    fn parse_commands() {
+        assert_eq!(
+            RemoteCommand::parse(r#"open "Red fox""#).unwrap(),
+            RemoteCommand::Open {
+                title: "Red fox".to_string()
+            }
+        );
+        assert_eq!(
+            RemoteCommand::parse("search  rust programming ").unwrap(),
+            RemoteCommand::Search {
+                query: "rust programming".to_string()
+            }
+        );
+        assert_eq!(
+            RemoteCommand::parse("export md '/tmp/red fox.md'").unwrap(),
+            RemoteCommand::Export {
+                format: DumpFormat::Markdown,
+                path: PathBuf::from("/tmp/red fox.md")
+            }
+        );
+        assert_eq!(RemoteCommand::parse("quit").unwrap(), RemoteCommand::Quit);
+        assert!(RemoteCommand::parse("open").is_err());
+        assert!(RemoteCommand::parse("export pdf /tmp/x.pdf").is_err());
+        assert!(RemoteCommand::parse("delete everything").is_err());
+    }
+
+    #[test]
+    fn json_commands() {
+        let command = RemoteCommand::Export {
+            format: DumpFormat::Org,
+            path: PathBuf::from("/tmp/x.org"),
+        };
+        let json = serde_json::to_string(&command).unwrap();
+        assert_eq!(
+            json,
+            r#"{"action":"export","format":"org","path":"/tmp/x.org"}"#
+        );
+        assert_eq!(RemoteCommand::parse(&json).unwrap(), command);
+        assert_eq!(
+            RemoteCommand::parse(r#"{"action": "search", "query": "fox"}"#).unwrap(),
+            RemoteCommand::Search {
+                query: "fox".to_string()
+            }
+        );
+        assert!(RemoteCommand::parse(r#"{"action": "open"}"#).is_err());
+    }
+
+    #[test]
+    fn allowlist() {
+        let allow = [RemoteAction::Open, RemoteAction::Search];
+        assert!(handle_line("open Rust", &allow).is_ok());
+        assert!(handle_line("quit", &allow).is_err());
+        assert!(handle_line("export md /tmp/x.md", &allow).is_err());
+        assert!(handle_line("language de", &[RemoteAction::Language]).is_ok());
+        assert!(handle_line("language notalanguage", &[RemoteAction::Language]).is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn socket_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let directory =
+            std::env::temp_dir().join(format!("wiki-tui-remote-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+
+        // a mistyped path to a file of the user is left alone
+        let file = directory.join("notes.txt");
+        std::fs::write(&file, "keep me").unwrap();
+        let (action_tx, _action_rx) = tokio::sync::mpsc::unbounded_channel();
+        assert!(super::socket::listen(&file, action_tx.clone())
+            .await
+            .is_err());
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "keep me");
+
+        let path = directory.join("wiki-tui.sock");
+        let socket = path.clone();
+        let listener = tokio::spawn(async move { super::socket::listen(&socket, action_tx).await });
+        while !path.exists() {
+            assert!(!listener.is_finished());
+            tokio::task::yield_now().await;
+        }
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert!(tokio::net::UnixStream::connect(&path).await.is_ok());
+
+        listener.abort();
+        let mut entries: Vec<_> = std::fs::read_dir(&directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        entries.sort();
+        assert_eq!(entries, ["notes.txt", "wiki-tui.sock"]);
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,157 @@
+use std::{collections::HashMap, hash::Hash};
+
+use super::RenderedDocument;
+
+/// Keeps the most recently used rendered documents, evicting the least recently used one when
+/// full. Renders of single sections are kept the same way
+pub struct RenderCache<K, V = RenderedDocument> {
+    entries: HashMap<K, V>,
+    /// Keys ordered from the least to the most recently used
+    usage: Vec<K>,
+    capacity: usize,
+}
+
+impl<K: Clone + Eq + Hash, V> RenderCache<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            usage: Vec::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns every cached rendered document, in no particular order
+    pub fn documents(&self) -> impl Iterator<Item = &V> {
+        self.entries.values()
+    }
+
+    /// Returns the rendered document without changing its usage
+    pub fn peek(&self, key: &K) -> Option<&V> {
+        self.entries.get(key)
+    }
+
+    /// Returns the rendered document for the key, rendering and caching it when it's missing
+    pub fn get_or_render(&mut self, key: K, render: impl FnOnce() -> V) -> &V {
+        if !self.entries.contains_key(&key) {
+            while self.entries.len() >= self.capacity {
+                self.evict();
+            }
+            self.entries.insert(key.clone(), render());
+        }
+
+        self.usage.retain(|used| used != &key);
+        self.usage.push(key.clone());
+        self.entries.get(&key).unwrap()
+    }
+
+    /// Removes every rendered document except the most recently used one
+    pub fn shrink(&mut self) {
+        while self.entries.len() > 1 {
+            self.evict();
+        }
+    }
+
+    /// Removes every rendered document
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.usage.clear();
+    }
+
+    fn evict(&mut self) {
+        if self.usage.is_empty() {
+            // every entry is tracked, this only happens when the cache is empty
+            self.entries.clear();
+            return;
+        }
+        let key = self.usage.remove(0);
+        self.entries.remove(&key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RenderCache;
+    use crate::renderer::RenderedDocument;
+
+    /// A rendered document told apart by its number of lines
+    fn document(lines: usize) -> RenderedDocument {
+        RenderedDocument {
+            lines: vec![Vec::new(); lines],
+            sections: Vec::new(),
+            tables: Vec::new(),
+            links: Vec::new(),
+        }
+    }
+
+    fn cached(cache: &RenderCache<u16>, key: u16) -> bool {
+        cache.peek(&key).is_some()
+    }
+
+    #[test]
+    fn eviction_order() {
+        let mut cache = RenderCache::new(2);
+        cache.get_or_render(1, || document(1));
+        cache.get_or_render(2, || document(2));
+        // using the first render makes the second one the least recently used
+        cache.get_or_render(1, || unreachable!());
+        cache.get_or_render(3, || document(3));
+
+        assert_eq!(cache.len(), 2);
+        assert!(cached(&cache, 1));
+        assert!(!cached(&cache, 2));
+        assert!(cached(&cache, 3));
+        assert_eq!(cache.peek(&3).unwrap().lines.len(), 3);
+
+        // peeking doesn't count as using it
+        assert!(cached(&cache, 1));
+        cache.get_or_render(4, || document(4));
+        assert!(!cached(&cache, 1));
+        assert!(cached(&cache, 3));
+    }
+
+    #[test]
+    fn capacity() {
+        assert_eq!(RenderCache::<u16>::new(0).capacity(), 1);
+
+        let mut cache = RenderCache::new(3);
+        for key in 0..10 {
+            cache.get_or_render(key, || document(0));
+            assert!(cache.len() <= cache.capacity());
+        }
+        assert_eq!(cache.len(), 3);
+        assert!((7..10).all(|key| cached(&cache, key)));
+    }
+
+    #[test]
+    fn shrink() {
+        let mut cache = RenderCache::new(3);
+        cache.shrink();
+        assert!(cache.is_empty());
+
+        cache.get_or_render(1, || document(1));
+        cache.get_or_render(2, || document(2));
+        cache.get_or_render(1, || unreachable!());
+        cache.shrink();
+
+        assert_eq!(cache.len(), 1);
+        assert!(cached(&cache, 1));
+
+        cache.clear();
+        assert!(cache.is_empty());
+        // nothing is left to evict for the next render
+        cache.get_or_render(2, || document(2));
+        assert_eq!(cache.len(), 1);
+    }
+}
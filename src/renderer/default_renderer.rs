@@ -1,34 +1,148 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Range,
+};
+
 use ratatui::style::{Color, Modifier, Style};
 use textwrap::wrap_algorithms::{wrap_optimal_fit, Penalties};
 use tracing::warn;
-use wiki_api::document::{Data, Document, HeaderKind, Node};
+use wiki_api::{
+    document::{Data, Document, HeaderKind, Node},
+    navbox::navbox_title,
+    taxobox,
+};
 
-use crate::renderer::Word;
+use crate::{
+    config::{config, DomainCategory, RendererConfig},
+    i18n::{tr, tr_args},
+    renderer::{is_long_table, link_domains::domain_category, table_rows, Word},
+    ui::{theme, Theme},
+};
 
-use super::RenderedDocument;
+use super::{RenderedDocument, RenderedLink, RenderedTable};
 
 const DISAMBIGUATION_PADDING: u8 = 1;
 const DISAMBIGUATION_PREFIX: char = '|';
 
+const MAINTENANCE_PADDING: u8 = 1;
+const MAINTENANCE_PREFIX: char = '!';
+
 const LIST_PADDING: u8 = 1;
 const LIST_PREFIX: char = '-';
 
-struct Renderer {
+/// Shown in front of links when `renderer.link_symbols` is set, wiki links don't have a symbol
+const RED_LINK_SYMBOL: &str = "?";
+const MEDIA_LINK_SYMBOL: &str = "▣";
+const EXTERNAL_LINK_SYMBOL: &str = "↗";
+
+const POEM_PADDING: u8 = 2;
+/// Lines of a poem that are too long continue on the next line, indented by this
+const POEM_CONTINUATION_PADDING: u8 = 2;
+/// Width of a level of indentation of a line of a poem
+const POEM_INDENTATION: u8 = 2;
+const NON_BREAKING_SPACE: char = '\u{a0}';
+
+/// Spaces between the label and the value column of a taxobox
+const TAXOBOX_COLUMN_GAP: u16 = 2;
+/// The label column of a taxobox takes up at most this fraction of the width
+const TAXOBOX_MAX_LABEL_FRACTION: u16 = 3;
+
+/// Width of the separator between two columns of a table
+const TABLE_COLUMN_GAP: u16 = 3;
+const TABLE_COLUMN_SEPARATOR: char = '│';
+/// Columns of a table are never wider than this, longer cells are truncated
+const TABLE_MAX_COLUMN_WIDTH: u16 = 40;
+/// Columns are not shrunk below this width to fit the table, the table overflows instead
+const TABLE_MIN_COLUMN_WIDTH: u16 = 8;
+/// Width the cells of a table are rendered in before they are truncated to their column
+const TABLE_CELL_RENDER_WIDTH: u16 = 1024;
+/// Shown at the end of a truncated cell
+const TRUNCATION_MARKER: char = '…';
+
+/// Indentation of the groups of a navbox below its title
+const NAVBOX_GROUP_PADDING: u8 = 2;
+
+/// Shown in front of the header of a collapsed section and the summary of a collapsed box
+const COLLAPSED_MARKER: &str = "▸";
+/// Shown in front of the summary of an expanded box
+const EXPANDED_MARKER: &str = "▾";
+
+/// Whether the row of a table only has header cells, and the index and rendered words of every
+/// cell in the row
+type RowCells = (bool, Vec<(usize, Vec<Word>)>);
+
+struct Renderer<'a> {
     rendered_lines: Vec<Vec<Word>>,
     current_line: Vec<Word>,
     width: u16,
 
     text_style: Style,
+    theme: Theme,
 
     left_padding: u8,
     prefix: Option<char>,
+    /// Extra padding of the lines a line is wrapped into
+    continuation_padding: u8,
+
+    /// Character horizontal lines are drawn with
+    separator: char,
+    /// Whether to draw a line between top-level sections
+    section_separators: bool,
+
+    /// Whether the text currently rendered is preformatted and must not be wrapped or trimmed
+    preformatted: bool,
+    /// Whether to wrap preformatted lines that are too long
+    wrap_preformatted: bool,
+    /// Whether the text currently rendered is part of a poem, where non-breaking spaces at the
+    /// start of a line indent it
+    poem: bool,
+
+    /// Whether to render taxoboxes
+    taxoboxes: bool,
+    /// Whether to mark links with a symbol of their kind
+    link_symbols: bool,
+    /// Whether to color external links by the kind of website they point to, and the configured
+    /// kinds of websites
+    link_domains: bool,
+    domains: BTreeMap<String, DomainCategory>,
+
+    /// Indices of the sections of which only the header is rendered
+    collapsed: &'a BTreeSet<usize>,
+    /// Titles of the linked pages in the secondary language, by the index of the link
+    language_hints: &'a BTreeMap<usize, String>,
+    /// Rendered sections and the lines they span, in the order they appear
+    sections: Vec<(usize, Range<usize>)>,
+
+    /// Whether a table is being rendered. Tables nested in it are rendered as text
+    in_table: bool,
+    /// Rows a collapsed table is cut off after
+    table_rows: usize,
+    tables: Vec<RenderedTable>,
+
+    /// First and last node of every rendered link, in the order they were rendered
+    links: Vec<(usize, usize)>,
+
+    /// Earlier renders of the top-level sections, they're only rendered when missing
+    section_renders: Option<&'a mut dyn SectionRenders>,
 }
 
-impl<'a> Renderer {
-    fn render_document(document: &'a Document, width: u16) -> RenderedDocument {
+impl<'a> Renderer<'a> {
+    fn render_document(
+        document: &'a Document,
+        width: u16,
+        options: &RendererConfig,
+        collapsed: &'a BTreeSet<usize>,
+        language_hints: &'a BTreeMap<usize, String>,
+        section_renders: Option<&'a mut dyn SectionRenders>,
+    ) -> RenderedDocument {
         if document.nodes.is_empty() {
             warn!("document contains no nodes, aborting the render");
-            return RenderedDocument { lines: Vec::new() };
+            return RenderedDocument {
+                lines: Vec::new(),
+                sections: Vec::new(),
+                tables: Vec::new(),
+                links: Vec::new(),
+            };
         }
 
         let mut renderer = Renderer {
@@ -37,15 +151,45 @@ impl<'a> Renderer {
             width,
 
             text_style: Style::default(),
+            theme: theme(),
 
             left_padding: 0,
             prefix: None,
+            continuation_padding: 0,
+
+            separator: options.separator,
+            section_separators: options.section_separators,
+
+            preformatted: false,
+            wrap_preformatted: options.wrap_preformatted,
+            poem: false,
+
+            taxoboxes: options.taxoboxes,
+            link_symbols: options.link_symbols,
+            link_domains: options.link_domains,
+            domains: options.domains.clone(),
+
+            collapsed,
+            language_hints,
+            sections: Vec::new(),
+
+            in_table: false,
+            table_rows: options.table_rows,
+            tables: Vec::new(),
+
+            links: Vec::new(),
+
+            section_renders,
         };
 
         renderer.render_node(document.nth(0).unwrap());
 
+        let links = link_segments(&renderer.rendered_lines, &renderer.links);
         RenderedDocument {
             lines: renderer.rendered_lines,
+            sections: renderer.sections,
+            tables: renderer.tables,
+            links,
         }
     }
 
@@ -75,6 +219,10 @@ impl<'a> Renderer {
     ///
     /// The whitespace word has an index of `usize::MAX` and a width of `0` to not interfere with text wrapping. Note: If there already is a whitespace at the end of the current line, no whitespace will be added!
     fn add_whitespace(&mut self) {
+        if self.preformatted {
+            return;
+        }
+
         if self
             .current_line
             .last()
@@ -140,14 +288,18 @@ impl<'a> Renderer {
 
         // if the first word doesn't fit onto the current line, the line wrapping algorithm gets confuesed.
         // that means we have to clear it in this case
+        let mut padding = self.left_padding;
         if words.first().map(|word| word.width).unwrap_or_default() > remaining_width {
             remaining_width = self.width as f64;
+            if !self.current_line.is_empty() {
+                padding = padding.saturating_add(self.continuation_padding);
+            }
             self.clear_line();
         }
 
         if self.current_line.is_empty() {
-            remaining_width -= self.left_padding as f64;
-            self.current_line.push(self.n_whitespace(self.left_padding));
+            remaining_width -= padding as f64;
+            self.current_line.push(self.n_whitespace(padding));
             if let Some(prefix) = self.prefix {
                 self.current_line.push(Word {
                     index: usize::MAX,
@@ -190,8 +342,9 @@ impl<'a> Renderer {
         }
 
         // indent the current line
+        let padding = self.left_padding.saturating_add(self.continuation_padding);
         for line in wrapped_lines.iter_mut() {
-            line.insert(0, self.n_whitespace(self.left_padding));
+            line.insert(0, self.n_whitespace(padding));
         }
 
         if let Some(last_line) = wrapped_lines.pop() {
@@ -252,7 +405,7 @@ impl<'a> Renderer {
         let remaining_width = (self.width as usize) - self.current_width();
         let line = Word {
             index: usize::MAX,
-            content: "─".repeat(remaining_width),
+            content: self.separator.to_string().repeat(remaining_width),
             style: self.text_style,
             width: remaining_width as f64,
             whitespace_width: 0.0,
@@ -276,7 +429,86 @@ impl<'a> Renderer {
 
         self.ensure_empty_line();
 
-        self.render_children(node);
+        if is_top_level_section(node) {
+            if let Some(renders) = self.section_renders.take() {
+                let render = renders.get_or_render(node, &mut || self.render_detached(node));
+                self.section_renders = Some(renders);
+                self.append_section(render);
+                return;
+            }
+        }
+        self.render_section_contents(node);
+    }
+
+    /// Renders the section on its own, without the lines rendered before it. It starts below an
+    /// empty line, the one every section starts below
+    fn render_detached(&mut self, node: Node<'a>) -> RenderedSection {
+        let lines = std::mem::replace(&mut self.rendered_lines, vec![Vec::new()]);
+        let sections = std::mem::take(&mut self.sections);
+        let tables = std::mem::take(&mut self.tables);
+        let links = std::mem::take(&mut self.links);
+
+        self.render_section_contents(node);
+
+        RenderedSection {
+            lines: std::mem::replace(&mut self.rendered_lines, lines),
+            sections: std::mem::replace(&mut self.sections, sections),
+            tables: std::mem::replace(&mut self.tables, tables),
+            links: std::mem::replace(&mut self.links, links),
+        }
+    }
+
+    /// Appends a section rendered on its own, its first line is the empty line the rendered
+    /// lines end with
+    fn append_section(&mut self, render: RenderedSection) {
+        let offset = self.rendered_lines.len() - 1;
+        self.rendered_lines.extend(render.lines.into_iter().skip(1));
+        self.sections.extend(
+            render
+                .sections
+                .into_iter()
+                .map(|(index, lines)| (index, lines.start + offset..lines.end + offset)),
+        );
+        self.tables
+            .extend(render.tables.into_iter().map(|mut table| {
+                table.rows.iter_mut().for_each(|(line, _)| *line += offset);
+                table
+            }));
+        self.links.extend(render.links);
+    }
+
+    fn render_section_contents(&mut self, node: Node<'a>) {
+        if self.section_separators && is_top_level_section(node) {
+            self.render_separator();
+        }
+
+        let start = self.rendered_lines.len();
+        let position = self.sections.len();
+        self.sections.push((node.index(), start..start));
+
+        if self.collapsed.contains(&node.index()) {
+            if let Some(header) = node
+                .children()
+                .find(|child| matches!(child.data(), Data::Header { .. }))
+            {
+                self.render_header(header);
+            }
+        } else {
+            self.render_children(node);
+        }
+
+        self.ensure_empty_line();
+        self.sections[position].1.end = self.rendered_lines.len();
+    }
+
+    /// Draws a full-width line in the separator color, surrounded by empty lines
+    fn render_separator(&mut self) {
+        self.ensure_empty_line();
+
+        let text_style = self.text_style;
+        self.text_style = Style::default().fg(self.theme.separator);
+        self.add_horizontal_line();
+        self.text_style = text_style;
 
         self.ensure_empty_line();
     }
@@ -292,7 +524,22 @@ impl<'a> Renderer {
         if !matches!(kind, &HeaderKind::Main | &HeaderKind::Sub) {
             self.add_modifier(Modifier::BOLD);
         }
-        self.set_text_fg(Color::Red);
+        self.set_text_fg(self.theme.heading);
+
+        let is_collapsed = node
+            .parent()
+            .map(|section| self.collapsed.contains(&section.index()))
+            .unwrap_or(false);
+        if is_collapsed {
+            self.wrap_append(vec![Word {
+                index: node.index(),
+                content: COLLAPSED_MARKER.to_string(),
+                style: self.text_style,
+                width: 1.0,
+                whitespace_width: 1.0,
+                penalty_width: 0.0,
+            }]);
+        }
 
         self.render_children(node);
 
@@ -318,6 +565,24 @@ impl<'a> Renderer {
             }
         };
 
+        if self.preformatted {
+            self.append_preformatted(node, contents);
+            return;
+        }
+
+        // the lines of poems are indented with non-breaking spaces
+        if self.poem && self.current_line.is_empty() {
+            let indentation = contents
+                .trim_start_matches(' ')
+                .chars()
+                .take_while(|c| *c == NON_BREAKING_SPACE)
+                .count();
+            if indentation > 0 {
+                let padding = self.left_padding.saturating_add(indentation as u8);
+                self.current_line.push(self.n_whitespace(padding));
+            }
+        }
+
         const TEXT_SPECIAL_CHARACTERS: [char; 9] = [',', '.', ':', ';', '\"', '\'', '!', '@', '%'];
         if contents.starts_with(TEXT_SPECIAL_CHARACTERS) && self.is_last_whitespace() {
             self.current_line.pop();
@@ -352,6 +617,102 @@ impl<'a> Renderer {
         self.ensure_empty_line();
     }
 
+    /// Appends the text line by line, keeping its whitespace. Long lines are only wrapped when
+    /// enabled, otherwise they overflow the width of the render
+    fn append_preformatted(&mut self, node: Node<'a>, contents: &str) {
+        for (i, line) in contents.split('\n').enumerate() {
+            if i > 0 {
+                self.rendered_lines
+                    .push(std::mem::take(&mut self.current_line));
+            }
+
+            let mut line: Vec<char> = line.replace('\t', "    ").chars().collect();
+            while !line.is_empty() {
+                if self.current_line.is_empty() {
+                    self.current_line.push(self.n_whitespace(self.left_padding));
+                }
+
+                let remaining_width = (self.width as usize).saturating_sub(self.current_width());
+                let length = if self.wrap_preformatted {
+                    line.len().min(remaining_width)
+                } else {
+                    line.len()
+                };
+                // the line is full, continue on the next one
+                if length == 0 {
+                    self.clear_line();
+                    if self.left_padding as usize >= self.width as usize {
+                        break;
+                    }
+                    continue;
+                }
+
+                let content: String = line.drain(..length).collect();
+                self.current_line.push(Word {
+                    index: node.index(),
+                    width: content.chars().count() as f64,
+                    content,
+                    style: self.text_style,
+                    whitespace_width: 0.0,
+                    penalty_width: 0.0,
+                });
+            }
+        }
+    }
+
+    fn render_preformatted(&mut self, node: Node<'a>) {
+        self.ensure_empty_line();
+
+        self.preformatted = true;
+        self.render_children(node);
+        self.preformatted = false;
+
+        self.clear_line();
+        self.ensure_empty_line();
+    }
+
+    /// Ends the current line. A break at the start of a line leaves an empty line, but never
+    /// more than one in a row
+    fn render_line_break(&mut self) {
+        if self.preformatted {
+            self.rendered_lines
+                .push(std::mem::take(&mut self.current_line));
+            return;
+        }
+
+        if !self.current_line.is_empty() {
+            self.clear_line();
+        } else if !self.rendered_lines.is_empty() && !self.is_last_empty() {
+            self.rendered_lines.push(Vec::new());
+        }
+    }
+
+    /// Renders the lines of the poem as they are, indented and with the lines that are too long
+    /// continuing further indented on the next line
+    fn render_poem(&mut self, node: Node<'a>) {
+        self.ensure_empty_line();
+
+        let (poem, continuation_padding) = (self.poem, self.continuation_padding);
+        self.poem = true;
+        self.continuation_padding = POEM_CONTINUATION_PADDING;
+        self.add_n_padding(POEM_PADDING);
+
+        self.render_children(node);
+
+        self.remove_n_padding(POEM_PADDING);
+        self.poem = poem;
+        self.continuation_padding = continuation_padding;
+
+        self.ensure_empty_line();
+    }
+
+    fn render_poem_indentation(&mut self, node: Node<'a>, level: usize) {
+        let padding = POEM_INDENTATION.saturating_mul(level.min(u8::MAX as usize) as u8);
+        self.add_n_padding(padding);
+        self.render_children(node);
+        self.remove_n_padding(padding);
+    }
+
     fn render_span(&mut self, node: Node<'a>) {
         self.render_children(node);
         self.add_whitespace();
@@ -359,7 +720,7 @@ impl<'a> Renderer {
 
     fn render_reflink(&mut self, node: Node<'a>) {
         self.add_modifier(Modifier::ITALIC);
-        self.set_text_fg(Color::Gray);
+        self.set_text_fg(self.theme.reference);
 
         self.render_children(node);
 
@@ -369,6 +730,58 @@ impl<'a> Renderer {
         self.add_whitespace();
     }
 
+    fn render_citation(&mut self, node: Node<'a>) {
+        let Data::Citation { number, .. } = node.data() else {
+            warn!("expected citation data, got other data");
+            return;
+        };
+
+        self.add_modifier(Modifier::ITALIC);
+        self.set_text_fg(self.theme.reference);
+
+        // the number is rendered instead of the children, so it always matches the registry
+        let content = format!("[{number}]");
+        self.wrap_append(vec![Word {
+            index: node.index(),
+            width: content.chars().count() as f64,
+            content,
+            style: self.text_style,
+            whitespace_width: 0.0,
+            penalty_width: 0.0,
+        }]);
+
+        self.reset_text_fg();
+        self.remove_modifier(Modifier::ITALIC);
+
+        self.add_whitespace();
+    }
+
+    fn render_reference(&mut self, node: Node<'a>) {
+        let Data::Reference { number, .. } = node.data() else {
+            warn!("expected reference data, got other data");
+            return;
+        };
+
+        let prefix = format!("{number}.");
+        let padding = prefix.chars().count() as u8 + 1;
+
+        self.clear_line();
+        self.current_line.push(Word {
+            index: usize::MAX,
+            content: format!("{}{prefix}", " ".repeat(self.left_padding as usize)),
+            style: Style::default(),
+            width: prefix.chars().count() as f64,
+            whitespace_width: 1.0,
+            penalty_width: 0.0,
+        });
+        self.add_n_padding(padding);
+
+        self.render_children(node);
+
+        self.remove_n_padding(padding);
+        self.clear_line();
+    }
+
     fn render_disambiguation(&mut self, node: Node<'a>) {
         self.ensure_empty_line();
 
@@ -385,6 +798,409 @@ impl<'a> Renderer {
         self.ensure_empty_line();
     }
 
+    /// Maintenance notices are dimmed and marked like hatnotes. Notices following each other are
+    /// kept together, without empty lines between them
+    fn render_maintenance_notice(&mut self, node: Node<'a>) {
+        let is_notice = |sibling: Option<Node<'a>>| {
+            sibling.map(|sibling| sibling.data() == &Data::MaintenanceNotice) == Some(true)
+        };
+        if !is_notice(node.prev()) {
+            self.ensure_empty_line();
+        }
+
+        self.add_modifier(Modifier::DIM);
+        self.set_text_fg(self.theme.notice);
+        self.add_n_padding(MAINTENANCE_PADDING);
+        self.set_prefix(MAINTENANCE_PREFIX);
+
+        self.render_children(node);
+
+        self.reset_prefix();
+        self.remove_n_padding(MAINTENANCE_PADDING);
+        self.reset_text_fg();
+        self.remove_modifier(Modifier::DIM);
+
+        if is_notice(node.next()) {
+            self.clear_line();
+        } else {
+            self.ensure_empty_line();
+        }
+    }
+
+    /// Collapsible boxes are a line with their summary, followed by their content when they are
+    /// expanded. The words of the line belong to the box, so it can be found in the rendered lines
+    fn render_collapsible(&mut self, node: Node<'a>) {
+        let Data::Collapsible { summary, .. } = node.data() else {
+            warn!("expected collapsible data, got other data");
+            return;
+        };
+        self.render_collapsible_box(node, summary);
+    }
+
+    /// The footer of related pages is collapsible like a box, its summary lists the titles of
+    /// its navboxes
+    fn render_related_pages(&mut self, node: Node<'a>) {
+        let titles: Vec<String> = node
+            .children()
+            .map(navbox_title)
+            .filter(|title| !title.is_empty())
+            .collect();
+        let summary = match titles.is_empty() {
+            true => tr("page.related_pages").to_string(),
+            false => format!("{}: {}", tr("page.related_pages"), titles.join(", ")),
+        };
+        self.render_collapsible_box(node, &summary);
+    }
+
+    /// Navboxes are their title followed by a line for each of their groups
+    fn render_navbox(&mut self, node: Node<'a>) {
+        self.ensure_empty_line();
+        self.render_children(node);
+        self.clear_line();
+    }
+
+    fn render_navbox_title(&mut self, node: Node<'a>) {
+        self.clear_line();
+        self.add_modifier(Modifier::BOLD);
+        self.set_text_fg(self.theme.heading);
+        self.render_children(node);
+        self.reset_text_fg();
+        self.remove_modifier(Modifier::BOLD);
+        self.clear_line();
+    }
+
+    /// Groups of a navbox are indented below its title, the lines they wrap into even more
+    fn render_navbox_group(&mut self, node: Node<'a>) {
+        self.clear_line();
+        self.add_n_padding(NAVBOX_GROUP_PADDING);
+        let continuation_padding = self.continuation_padding;
+        self.continuation_padding = NAVBOX_GROUP_PADDING;
+        self.render_children(node);
+        self.clear_line();
+        self.continuation_padding = continuation_padding;
+        self.remove_n_padding(NAVBOX_GROUP_PADDING);
+    }
+
+    /// Renders a line with the summary of the box, followed by its content when it's expanded
+    fn render_collapsible_box(&mut self, node: Node<'a>, summary: &str) {
+        let is_collapsed = self.collapsed.contains(&node.index());
+
+        self.ensure_empty_line();
+        self.render_summary(node, is_collapsed, summary);
+
+        if !is_collapsed {
+            self.render_children(node);
+        }
+
+        self.ensure_empty_line();
+    }
+
+    /// Renders the summary of the collapsible node on its own line, behind a marker of whether
+    /// it's collapsed. The words belong to the node, so it can be found in the rendered lines
+    fn render_summary(&mut self, node: Node<'a>, is_collapsed: bool, summary: &str) {
+        let marker = match is_collapsed {
+            true => COLLAPSED_MARKER,
+            false => EXPANDED_MARKER,
+        };
+        let style = self.text_style.fg(self.theme.heading);
+        let mut words: Vec<Word> = std::iter::once(marker)
+            .chain(summary.split_whitespace())
+            .map(|word| Word {
+                index: node.index(),
+                content: word.to_string(),
+                style,
+                width: word.chars().count() as f64,
+                whitespace_width: 1.0,
+                penalty_width: 0.0,
+            })
+            .collect();
+        if let Some(word) = words.last_mut() {
+            word.whitespace_width = 0.0;
+        }
+        self.wrap_append(words);
+        self.clear_line();
+    }
+
+    /// Renders the children of the node into lines of the given width, without adding them to
+    /// the rendered lines. Empty lines are left out
+    fn render_cell(&mut self, node: Node<'a>, width: u16) -> Vec<Vec<Word>> {
+        let rendered_lines = std::mem::take(&mut self.rendered_lines);
+        let current_line = std::mem::take(&mut self.current_line);
+        let (outer_width, left_padding, prefix) = (self.width, self.left_padding, self.prefix);
+        self.width = width;
+        self.left_padding = 0;
+        self.prefix = None;
+
+        self.render_children(node);
+        self.clear_line();
+
+        let lines = std::mem::replace(&mut self.rendered_lines, rendered_lines);
+        self.current_line = current_line;
+        self.width = outer_width;
+        self.left_padding = left_padding;
+        self.prefix = prefix;
+
+        lines
+            .into_iter()
+            .filter(|line| line.iter().any(|word| !word.content.is_empty()))
+            .collect()
+    }
+
+    /// Renders the taxobox as a table, with the labels aligned in a column to the left of their
+    /// values. Headings and rows without a label span the whole width
+    fn render_taxobox(&mut self, node: Node<'a>) {
+        if !self.taxoboxes {
+            return;
+        }
+
+        self.ensure_empty_line();
+
+        let label_width = node
+            .children()
+            .filter_map(|row| {
+                row.children()
+                    .find(|cell| matches!(cell.data(), Data::TaxoboxLabel))
+            })
+            .map(|label| taxobox::text(label).chars().count() as u16)
+            .max()
+            .unwrap_or_default()
+            .min(self.width / TAXOBOX_MAX_LABEL_FRACTION);
+        let value_width = self
+            .width
+            .saturating_sub(label_width + TAXOBOX_COLUMN_GAP)
+            .max(1);
+
+        for child in node.children() {
+            match child.data() {
+                Data::TaxoboxHeading => {
+                    self.clear_line();
+                    self.add_modifier(Modifier::BOLD);
+                    self.render_children(child);
+                    self.remove_modifier(Modifier::BOLD);
+                    self.clear_line();
+                }
+                Data::TaxoboxRow => self.render_taxobox_row(child, label_width, value_width),
+                _ => warn!("expected taxobox heading or row, got other data"),
+            }
+        }
+
+        self.ensure_empty_line();
+    }
+
+    fn render_taxobox_row(&mut self, node: Node<'a>, label_width: u16, value_width: u16) {
+        let label = node
+            .children()
+            .find(|cell| matches!(cell.data(), Data::TaxoboxLabel));
+        let value = match node
+            .children()
+            .find(|cell| matches!(cell.data(), Data::TaxoboxValue))
+        {
+            Some(value) => value,
+            None => return,
+        };
+
+        self.clear_line();
+        let label = match label {
+            Some(label) => label,
+            None => {
+                let width = self.width;
+                let lines = self.render_cell(value, width);
+                self.rendered_lines.extend(lines);
+                return;
+            }
+        };
+
+        self.add_modifier(Modifier::BOLD);
+        let mut labels = self.render_cell(label, label_width.max(1)).into_iter();
+        self.remove_modifier(Modifier::BOLD);
+        let mut values = self.render_cell(value, value_width).into_iter();
+
+        loop {
+            let (label, value) = (labels.next(), values.next());
+            if label.is_none() && value.is_none() {
+                break;
+            }
+
+            let mut line = label.unwrap_or_default();
+            let width: f64 = line
+                .iter()
+                .map(|word| word.width + word.whitespace_width)
+                .sum();
+            let padding = (label_width + TAXOBOX_COLUMN_GAP).saturating_sub(width as u16);
+            line.push(self.n_whitespace(padding.min(u8::MAX as u16) as u8));
+            line.extend(value.unwrap_or_default());
+            self.rendered_lines.push(line);
+        }
+    }
+
+    /// Renders the cell into a single line, with the style of a header cell when it is one
+    fn render_table_cell(&mut self, node: Node<'a>) -> Vec<Word> {
+        let is_header = matches!(node.data(), Data::TableCell { header: true });
+        if is_header {
+            self.add_modifier(Modifier::BOLD);
+        }
+        let lines = self.render_cell(node, TABLE_CELL_RENDER_WIDTH);
+        if is_header {
+            self.remove_modifier(Modifier::BOLD);
+        }
+
+        let mut words: Vec<Word> = Vec::new();
+        for mut line in lines {
+            // the lines of the cell are joined with a space
+            if let Some(last) = words.last_mut() {
+                last.whitespace_width = last.whitespace_width.max(1.0);
+            }
+            words.append(&mut line);
+        }
+        if let Some(last) = words.last_mut() {
+            last.whitespace_width = 0.0;
+        }
+        words
+    }
+
+    /// Renders the table with every row on a single line and the cells aligned in columns.
+    /// Cells that are wider than their column are truncated, a table that does not fit into the
+    /// width even with its columns shrunk overflows it
+    ///
+    /// A long table that is collapsed is cut off after its first rows, the other rows aren't
+    /// rendered at all. A line below it tells how many rows are left out
+    fn render_table(&mut self, node: Node<'a>) {
+        if self.in_table {
+            self.render_children(node);
+            self.add_whitespace();
+            return;
+        }
+
+        self.ensure_empty_line();
+        self.in_table = true;
+
+        if let Some(caption) = node
+            .children()
+            .find(|child| matches!(child.data(), Data::TableCaption))
+        {
+            self.add_modifier(Modifier::ITALIC);
+            self.render_children(caption);
+            self.remove_modifier(Modifier::ITALIC);
+            self.clear_line();
+        }
+
+        let is_long = is_long_table(node, self.table_rows);
+        let is_cut_off = is_long && self.collapsed.contains(&node.index());
+        let shown_rows = match is_cut_off {
+            true => self.table_rows,
+            false => usize::MAX,
+        };
+        let rows: Vec<RowCells> = table_rows(node)
+            .take(shown_rows)
+            .map(|row| {
+                let is_header = row
+                    .children()
+                    .all(|cell| matches!(cell.data(), Data::TableCell { header: true }));
+                let cells = row
+                    .children()
+                    .filter(|cell| matches!(cell.data(), Data::TableCell { .. }))
+                    .map(|cell| (cell.index(), self.render_table_cell(cell)))
+                    .collect();
+                (is_header, cells)
+            })
+            .collect();
+
+        let mut widths: Vec<u16> = Vec::new();
+        for (_, cells) in rows.iter() {
+            for (column, (_, words)) in cells.iter().enumerate() {
+                let width = (words_width(words) as u16).min(TABLE_MAX_COLUMN_WIDTH);
+                match widths.get_mut(column) {
+                    Some(column_width) => *column_width = (*column_width).max(width),
+                    None => widths.push(width),
+                }
+            }
+        }
+        let gaps = TABLE_COLUMN_GAP * widths.len().saturating_sub(1) as u16;
+        fit_columns(
+            &mut widths,
+            self.width
+                .saturating_sub(self.left_padding as u16)
+                .saturating_sub(gaps),
+        );
+
+        let mut columns = Vec::with_capacity(widths.len());
+        let mut x = self.left_padding as u16;
+        for width in widths.iter() {
+            columns.push((x, *width));
+            x += width + TABLE_COLUMN_GAP;
+        }
+        let table_width = x.saturating_sub(TABLE_COLUMN_GAP + self.left_padding as u16);
+
+        let separator_style = Style::default().fg(self.theme.separator);
+        let mut table = RenderedTable {
+            index: node.index(),
+            columns,
+            rows: Vec::new(),
+        };
+        let mut was_header = false;
+        self.clear_line();
+        for (is_header, cells) in rows {
+            // the header rows are separated from the rows below them
+            if was_header && !is_header {
+                self.rendered_lines.push(vec![
+                    self.n_whitespace(self.left_padding),
+                    Word {
+                        index: usize::MAX,
+                        content: self.separator.to_string().repeat(table_width as usize),
+                        style: separator_style,
+                        width: table_width as f64,
+                        whitespace_width: 0.0,
+                        penalty_width: 0.0,
+                    },
+                ]);
+            }
+            was_header = is_header;
+
+            let mut line = vec![self.n_whitespace(self.left_padding)];
+            let mut indices = Vec::with_capacity(cells.len());
+            let column_count = cells.len();
+            for (column, (index, words)) in cells.into_iter().enumerate() {
+                indices.push(index);
+                let words = truncate_words(words, widths[column]);
+                if column + 1 == column_count {
+                    line.extend(words);
+                    break;
+                }
+
+                let padding = widths[column].saturating_sub(words_width(&words) as u16) + 1;
+                line.extend(words);
+                line.push(self.n_whitespace(padding as u8));
+                line.push(Word {
+                    index: usize::MAX,
+                    content: TABLE_COLUMN_SEPARATOR.to_string(),
+                    style: separator_style,
+                    width: 1.0,
+                    whitespace_width: 1.0,
+                    penalty_width: 0.0,
+                });
+            }
+
+            table.rows.push((self.rendered_lines.len(), indices));
+            self.rendered_lines.push(line);
+        }
+
+        self.tables.push(table);
+        self.in_table = false;
+
+        if is_long {
+            let row_count = table_rows(node).count();
+            let summary = match is_cut_off {
+                true => tr_args(
+                    "page.more_table_rows",
+                    &[("rows", (row_count - self.table_rows).to_string())],
+                ),
+                false => tr_args("page.all_table_rows", &[("rows", row_count.to_string())]),
+            };
+            self.render_summary(node, is_cut_off, &summary);
+        }
+        self.ensure_empty_line();
+    }
+
     fn render_list(&mut self, node: Node<'a>) {
         self.ensure_empty_line();
 
@@ -438,7 +1254,7 @@ impl<'a> Renderer {
 
     fn render_italic(&mut self, node: Node<'a>) {
         self.add_modifier(Modifier::ITALIC);
-        self.set_text_fg(Color::Blue);
+        self.set_text_fg(self.theme.link);
 
         self.render_children(node);
 
@@ -447,59 +1263,165 @@ impl<'a> Renderer {
         self.add_whitespace();
     }
 
-    fn render_wiki_link(&mut self, node: Node<'a>) {
-        self.set_text_fg(Color::Blue);
+    /// Renders the children of an inline formatting element with the modifier added to their style
+    fn render_with_modifier(&mut self, node: Node<'a>, modifier: Modifier) {
+        self.add_modifier(modifier);
+
         self.render_children(node);
-        self.reset_text_fg();
 
+        self.remove_modifier(modifier);
         self.add_whitespace();
     }
 
-    fn render_red_link(&mut self, node: Node<'a>) {
-        self.add_modifier(Modifier::ITALIC);
-        self.set_text_fg(Color::Red);
+    /// Highlighted text is colored instead of reversed, which already marks the spoken sentence
+    fn render_mark(&mut self, node: Node<'a>) {
+        self.set_text_fg(self.theme.mark);
 
         self.render_children(node);
 
         self.reset_text_fg();
-        self.remove_modifier(Modifier::ITALIC);
         self.add_whitespace();
     }
 
-    fn render_media_link(&mut self, node: Node<'a>) {
-        self.add_modifier(Modifier::ITALIC);
-        self.set_text_fg(Color::Blue);
+    /// Abbreviations with an expansion are colored, the expansion is shown when they are selected
+    fn render_abbreviation(&mut self, node: Node<'a>) {
+        self.set_text_fg(self.theme.abbreviation);
+        self.render_children(node);
+        self.reset_text_fg();
 
+        self.add_whitespace();
+    }
+
+    /// Renders the children of a link in the color, with the symbol of its kind in front of them
+    /// when link symbols are enabled
+    fn render_link(&mut self, node: Node<'a>, color: Color, symbol: Option<&str>) {
+        self.set_text_fg(color);
+        self.add_modifier(self.theme.link_modifier);
+
+        if let Some(symbol) = symbol.filter(|_| self.link_symbols) {
+            self.wrap_append(vec![Word {
+                index: node.index(),
+                content: symbol.to_string(),
+                style: self.text_style,
+                width: symbol.chars().count() as f64,
+                whitespace_width: 0.0,
+                penalty_width: 0.0,
+            }]);
+        }
         self.render_children(node);
 
+        self.remove_modifier(self.theme.link_modifier);
         self.reset_text_fg();
+    }
+
+    fn render_wiki_link(&mut self, node: Node<'a>) {
+        self.render_link(node, self.theme.link, None);
+        if let Some(hint) = self.language_hints.get(&node.index()) {
+            self.render_language_hint(node, hint);
+        }
+        self.add_whitespace();
+    }
+
+    /// Renders the title of the linked page in the secondary language in parentheses. It
+    /// belongs to the link, so it's selected together with it
+    fn render_language_hint(&mut self, node: Node<'a>, hint: &str) {
+        self.add_whitespace();
+        let style = self
+            .text_style
+            .fg(self.theme.reference)
+            .add_modifier(Modifier::ITALIC);
+        let hint = format!("({})", hint);
+        let mut words: Vec<Word> = hint
+            .split_whitespace()
+            .map(|word| Word {
+                index: node.index(),
+                content: word.to_string(),
+                style,
+                width: word.chars().count() as f64,
+                whitespace_width: 1.0,
+                penalty_width: 0.0,
+            })
+            .collect();
+        if let Some(word) = words.last_mut() {
+            word.whitespace_width = 0.0;
+        }
+        self.wrap_append(words);
+    }
+
+    fn render_red_link(&mut self, node: Node<'a>) {
+        self.add_modifier(Modifier::ITALIC);
+        self.render_link(node, self.theme.red_link, Some(RED_LINK_SYMBOL));
         self.remove_modifier(Modifier::ITALIC);
         self.add_whitespace();
     }
 
-    fn render_external_link(&mut self, node: Node<'a>) {
+    fn render_media_link(&mut self, node: Node<'a>) {
         self.add_modifier(Modifier::ITALIC);
-        self.set_text_fg(Color::Blue);
+        self.render_link(node, self.theme.link, Some(MEDIA_LINK_SYMBOL));
+        self.remove_modifier(Modifier::ITALIC);
+        self.add_whitespace();
+    }
 
-        self.render_children(node);
+    /// Returns the color of the external link, by the kind of website it points to when enabled
+    fn external_link_color(&self, node: Node<'a>) -> Color {
+        let href = match node.data() {
+            Data::ExternalLink { href, .. } if self.link_domains => href,
+            _ => return self.theme.link,
+        };
+        match domain_category(href, &self.domains) {
+            DomainCategory::Wikimedia => self.theme.wikimedia_link,
+            DomainCategory::Journal => self.theme.journal_link,
+            DomainCategory::News => self.theme.news_link,
+            DomainCategory::Other => self.theme.link,
+        }
+    }
 
-        self.reset_text_fg();
+    fn render_external_link(&mut self, node: Node<'a>) {
+        self.add_modifier(Modifier::ITALIC);
+        let color = self.external_link_color(node);
+        self.render_link(node, color, Some(EXTERNAL_LINK_SYMBOL));
         self.remove_modifier(Modifier::ITALIC);
         self.add_whitespace();
     }
 
     fn render_node(&mut self, node: Node<'a>) {
+        if is_link(node.data()) {
+            self.links.push(link_nodes(node));
+        }
+
         match node.data() {
             Data::Section { id: _ } => self.render_section(node),
             Data::Header { id: _, kind: _ } => self.render_header(node),
             Data::Text { contents: _ } => self.render_text(node),
             Data::Division => self.render_block_element(node),
             Data::Paragraph => self.render_block_element(node),
+            Data::HorizontalRule => self.render_separator(),
+            Data::Preformatted => self.render_preformatted(node),
+            Data::LineBreak => self.render_line_break(),
+            Data::Poem => self.render_poem(node),
+            Data::PoemIndentation { level } => self.render_poem_indentation(node, *level),
             Data::Span => self.render_span(node),
             Data::Reflink => self.render_reflink(node),
+            Data::Citation { .. } => self.render_citation(node),
+            Data::Reference { .. } => self.render_reference(node),
             Data::Hatnote => self.render_block_element(node),
             Data::RedirectMessage => self.render_block_element(node),
             Data::Disambiguation => self.render_disambiguation(node),
+            Data::MaintenanceNotice => self.render_maintenance_notice(node),
+            Data::Collapsible { .. } => self.render_collapsible(node),
+            Data::RelatedPages => self.render_related_pages(node),
+            Data::Navbox => self.render_navbox(node),
+            Data::NavboxTitle => self.render_navbox_title(node),
+            Data::NavboxGroup => self.render_navbox_group(node),
+            Data::NavboxLabel => self.render_bold(node),
+            Data::Taxobox => self.render_taxobox(node),
+            Data::TaxoboxHeading | Data::TaxoboxRow | Data::TaxoboxLabel | Data::TaxoboxValue => {
+                self.render_children(node)
+            }
+            Data::Table => self.render_table(node),
+            Data::TableCaption | Data::TableRow | Data::TableCell { .. } => {
+                self.render_children(node)
+            }
             Data::OrderedList => self.render_list(node),
             Data::UnorderedList => self.render_list(node),
             Data::ListItem => self.render_list_item(node),
@@ -508,6 +1430,13 @@ impl<'a> Renderer {
             Data::DerscriptionListDescription => self.render_description_list_description(node),
             Data::Bold => self.render_bold(node),
             Data::Italic => self.render_italic(node),
+            Data::Strikethrough => self.render_with_modifier(node, Modifier::CROSSED_OUT),
+            Data::Underline => self.render_with_modifier(node, Modifier::UNDERLINED),
+            // terminals can't shrink text, it's dimmed instead
+            Data::Small => self.render_with_modifier(node, Modifier::DIM),
+            Data::Mark => self.render_mark(node),
+            Data::Abbreviation { title: Some(_) } => self.render_abbreviation(node),
+            Data::Abbreviation { title: None } => self.render_span(node),
             Data::WikiLink { href: _, title: _ } => self.render_wiki_link(node),
             Data::RedLink { title: _ } => self.render_red_link(node),
             Data::MediaLink { href: _, title: _ } => self.render_media_link(node),
@@ -516,11 +1445,201 @@ impl<'a> Renderer {
                 title: _,
                 autonumber: _,
             } => self.render_external_link(node),
+            // terminals can't show images, they are only exported
+            Data::Image { .. } => {}
             Data::Unknown => self.render_children(node),
         }
     }
 }
 
+/// Returns whether the node is a link that can be selected
+pub fn is_link(data: &Data) -> bool {
+    matches!(
+        data,
+        Data::WikiLink { .. }
+            | Data::RedLink { .. }
+            | Data::MediaLink { .. }
+            | Data::ExternalLink { .. }
+            | Data::Citation { .. }
+            | Data::Abbreviation { title: Some(_) }
+    )
+}
+
+/// Returns the index of the first and the last node of the link, which is its last descendant
+/// and not necessarily its last child
+pub fn link_nodes(node: Node<'_>) -> (usize, usize) {
+    let last = node
+        .descendants()
+        .map(|descendant| descendant.index())
+        .max()
+        .unwrap_or(node.index());
+    (node.index(), last)
+}
+
+/// Finds the words of every link in the lines and groups them into one segment per line. Links
+/// without any rendered words are left out
+fn link_segments(lines: &[Vec<Word>], links: &[(usize, usize)]) -> Vec<RenderedLink> {
+    let mut rendered: Vec<RenderedLink> = links
+        .iter()
+        .map(|nodes| RenderedLink {
+            nodes: *nodes,
+            segments: Vec::new(),
+        })
+        .collect();
+
+    for (line_idx, line) in lines.iter().enumerate() {
+        for (word_idx, word) in line.iter().enumerate() {
+            if word.index == usize::MAX {
+                continue;
+            }
+            // links are rendered in the order of their nodes and don't overlap
+            let link = match links.partition_point(|(first, _)| *first <= word.index) {
+                0 => continue,
+                n if word.index <= links[n - 1].1 => n - 1,
+                _ => continue,
+            };
+
+            let segments = &mut rendered[link].segments;
+            match segments.last_mut() {
+                Some((line, words)) if *line == line_idx => words.end = word_idx + 1,
+                _ => segments.push((line_idx, word_idx..word_idx + 1)),
+            }
+        }
+    }
+
+    rendered.retain(|link| !link.segments.is_empty());
+    rendered
+}
+
+/// Returns the width of the words, including the whitespace after them
+fn words_width(words: &[Word]) -> f64 {
+    words
+        .iter()
+        .map(|word| word.width + word.whitespace_width)
+        .sum()
+}
+
+/// Cuts off the words that don't fit into the width and marks the cut with the truncation marker
+fn truncate_words(words: Vec<Word>, width: u16) -> Vec<Word> {
+    let width = width as f64;
+    let mut used = 0.0;
+    let mut truncated: Vec<Word> = Vec::with_capacity(words.len());
+    for mut word in words {
+        if used + word.width > width {
+            let length = (width - used - 1.0).max(0.0) as usize;
+            if length > 0 {
+                word.content = word.content.chars().take(length).collect();
+                word.content.push(TRUNCATION_MARKER);
+                word.width = word.content.chars().count() as f64;
+                word.whitespace_width = 0.0;
+                truncated.push(word);
+            } else if let Some(last) = truncated.last_mut().filter(|last| last.width >= 1.0) {
+                // nothing of the word fits, the marker takes the place of the space after the
+                // last word or of its last character
+                if last.whitespace_width >= 1.0 {
+                    last.whitespace_width = 0.0;
+                    last.width += 1.0;
+                } else {
+                    last.content.pop();
+                }
+                last.content.push(TRUNCATION_MARKER);
+            }
+            break;
+        }
+
+        used += word.width;
+        word.whitespace_width = word.whitespace_width.min(width - used);
+        used += word.whitespace_width;
+        truncated.push(word);
+    }
+    truncated
+}
+
+/// Shrinks the widest columns until they fit into the width together. Columns are not shrunk
+/// below [`TABLE_MIN_COLUMN_WIDTH`]
+fn fit_columns(widths: &mut [u16], width: u16) {
+    while widths.iter().sum::<u16>() > width {
+        match widths.iter_mut().max() {
+            Some(widest) if *widest > TABLE_MIN_COLUMN_WIDTH => *widest -= 1,
+            _ => return,
+        }
+    }
+}
+
+/// Returns whether the node is a section that is not nested in another section and is not the
+/// lead section of the page
+pub fn is_top_level_section(node: Node<'_>) -> bool {
+    let is_lead = matches!(node.data(), Data::Section { id: 0 });
+    let is_nested = node
+        .parent()
+        .map(|parent| matches!(parent.data(), Data::Section { .. }))
+        .unwrap_or(false);
+    !is_lead && !is_nested
+}
+
+/// A top-level section rendered on its own, starting with the empty line above it. It's kept
+/// to be put together with the other sections again when another part of the page changes
+#[derive(Debug, Clone)]
+pub struct RenderedSection {
+    lines: Vec<Vec<Word>>,
+    sections: Vec<(usize, Range<usize>)>,
+    tables: Vec<RenderedTable>,
+    links: Vec<(usize, usize)>,
+}
+
+/// Renders of top-level sections kept from earlier renders of the document. Each of them only
+/// depends on the section itself, so when a section is collapsed or a link of it gets a
+/// language hint, the other sections are taken from here instead of being rendered again
+pub trait SectionRenders {
+    /// Returns the render of the section, calling `render` when there's no render that is still
+    /// valid
+    fn get_or_render(
+        &mut self,
+        section: Node<'_>,
+        render: &mut dyn FnMut() -> RenderedSection,
+    ) -> RenderedSection;
+}
+
 pub fn render_document(document: &Document, width: u16) -> RenderedDocument {
-    Renderer::render_document(document, width)
+    Renderer::render_document(
+        document,
+        width,
+        &config().renderer,
+        &BTreeSet::new(),
+        &BTreeMap::new(),
+        None,
+    )
+}
+
+/// Renders the document with the given options instead of the ones from the configuration.
+/// Only the headers of the `collapsed` sections are rendered, the `language_hints` are shown
+/// after the links with their index
+pub fn render_document_with(
+    document: &Document,
+    width: u16,
+    options: &RendererConfig,
+    collapsed: &BTreeSet<usize>,
+    language_hints: &BTreeMap<usize, String>,
+) -> RenderedDocument {
+    Renderer::render_document(document, width, options, collapsed, language_hints, None)
+}
+
+/// Renders the document like [`render_document_with`], taking the top-level sections that
+/// didn't change from the `sections` rendered before
+pub fn render_document_sections(
+    document: &Document,
+    width: u16,
+    options: &RendererConfig,
+    collapsed: &BTreeSet<usize>,
+    language_hints: &BTreeMap<usize, String>,
+    sections: &mut dyn SectionRenders,
+) -> RenderedDocument {
+    Renderer::render_document(
+        document,
+        width,
+        options,
+        collapsed,
+        language_hints,
+        Some(sections),
+    )
 }
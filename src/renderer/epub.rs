@@ -0,0 +1,515 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use tracing::info;
+use wiki_api::{
+    document::{Data, HeaderKind, Node},
+    page::Page,
+};
+
+use crate::{
+    config::config,
+    export::{article_url, export_directory},
+    page_cache::file_name,
+};
+
+const MIMETYPE: &str = "application/epub+zip";
+
+const CONTAINER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>
+"#;
+
+/// Articles that are compiled into a single EPUB
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EpubSource {
+    /// Every bookmarked page
+    Bookmarks,
+    /// The articles in the category, in the current language
+    Category(String),
+}
+
+/// Escapes the text so it can be used in XML text and attribute values
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn chapter_file(chapter: usize) -> String {
+    format!("chapter-{}.xhtml", chapter)
+}
+
+/// Converts the document of a page into the XHTML body of a chapter
+struct XhtmlWriter<'a> {
+    language_code: &'a str,
+    /// Chapter of every page in the book, by the title of the page
+    chapters: &'a HashMap<String, usize>,
+
+    out: String,
+}
+
+impl<'a> XhtmlWriter<'a> {
+    fn write_children(&mut self, node: Node<'_>) {
+        for child in node.children() {
+            self.write_node(child);
+        }
+    }
+
+    /// Writes the children of the node, surrounded by the element
+    fn write_element(&mut self, node: Node<'_>, element: &str) {
+        self.out.push_str(&format!("<{}>", element));
+        self.write_children(node);
+        self.out.push_str(&format!("</{}>", element));
+    }
+
+    fn write_link(&mut self, node: Node<'_>, href: &str) {
+        self.out.push_str(&format!("<a href=\"{}\">", escape(href)));
+        self.write_children(node);
+        self.out.push_str("</a>");
+    }
+
+    /// Returns where the link to the article points to: its chapter when the article is part of
+    /// the book, the article on Wikipedia otherwise
+    fn wiki_link_target(&self, href: &str, title: Option<&str>) -> String {
+        let (path, anchor) = match href.split_once('#') {
+            Some((path, anchor)) => (path, Some(anchor)),
+            None => (href, None),
+        };
+        let title = match title {
+            Some(title) => title.to_string(),
+            None => path.trim_start_matches("./").replace('_', " "),
+        };
+
+        match self.chapters.get(&title) {
+            Some(chapter) => match anchor {
+                Some(anchor) => format!("{}#{}", chapter_file(*chapter), anchor),
+                None => chapter_file(*chapter),
+            },
+            None => article_url(self.language_code, href.trim_start_matches("./")),
+        }
+    }
+
+    fn write_node(&mut self, node: Node<'_>) {
+        match node.data() {
+            Data::Text { contents } => self.out.push_str(&escape(contents)),
+            Data::Header { id, kind } => {
+                let level = match kind {
+                    HeaderKind::Main => 1,
+                    HeaderKind::Sub => 2,
+                    HeaderKind::Section => 3,
+                    HeaderKind::Subsection => 4,
+                    HeaderKind::Minor => 5,
+                    HeaderKind::Detail => 6,
+                };
+                self.out
+                    .push_str(&format!("<h{} id=\"{}\">", level, escape(id)));
+                self.write_children(node);
+                self.out.push_str(&format!("</h{}>\n", level));
+            }
+            Data::Paragraph
+            | Data::Hatnote
+            | Data::RedirectMessage
+            | Data::MaintenanceNotice
+            | Data::NavboxTitle
+            | Data::NavboxGroup => {
+                self.write_element(node, "p");
+                self.out.push('\n');
+            }
+            Data::Division | Data::Disambiguation => {
+                self.write_element(node, "div");
+                self.out.push('\n');
+            }
+            Data::HorizontalRule => self.out.push_str("<hr/>\n"),
+            Data::Preformatted => {
+                self.write_element(node, "pre");
+                self.out.push('\n');
+            }
+            Data::LineBreak => self.out.push_str("<br/>"),
+            Data::Poem => {
+                self.out.push_str("<div style=\"margin-left: 2em;\">");
+                self.write_children(node);
+                self.out.push_str("</div>\n");
+            }
+            Data::PoemIndentation { level } => {
+                self.out.push_str(&format!(
+                    "<span style=\"display: inline-block; margin-left: {}em;\">",
+                    level
+                ));
+                self.write_children(node);
+                self.out.push_str("</span>");
+            }
+            // like in the Markdown export, taxoboxes are left out
+            Data::Taxobox => {}
+            Data::Table => {
+                self.write_element(node, "table");
+                self.out.push('\n');
+            }
+            Data::TableCaption => self.write_element(node, "caption"),
+            Data::TableRow => self.write_element(node, "tr"),
+            Data::TableCell { header: true } => self.write_element(node, "th"),
+            Data::TableCell { header: false } => self.write_element(node, "td"),
+            Data::OrderedList => self.write_element(node, "ol"),
+            Data::UnorderedList => self.write_element(node, "ul"),
+            Data::ListItem => self.write_element(node, "li"),
+            Data::DescriptionList => self.write_element(node, "dl"),
+            Data::DescriptionListTerm => self.write_element(node, "dt"),
+            Data::DerscriptionListDescription => self.write_element(node, "dd"),
+            Data::Bold | Data::NavboxLabel => self.write_element(node, "b"),
+            Data::Italic => self.write_element(node, "i"),
+            Data::Strikethrough => self.write_element(node, "s"),
+            Data::Underline => self.write_element(node, "u"),
+            Data::Small => self.write_element(node, "small"),
+            Data::Mark => self.write_element(node, "mark"),
+            Data::Abbreviation { title: Some(title) } => {
+                self.out
+                    .push_str(&format!("<abbr title=\"{}\">", escape(title)));
+                self.write_children(node);
+                self.out.push_str("</abbr>");
+            }
+            Data::WikiLink { href, title } => {
+                // the arrows linking back to the citations of a reference
+                if href.contains("#cite_ref-") {
+                    return;
+                }
+                let target = self.wiki_link_target(href, title.as_deref());
+                self.write_link(node, &target);
+            }
+            Data::ExternalLink {
+                href, autonumber, ..
+            } => {
+                if *autonumber {
+                    return self.out.push_str(&format!(
+                        "<a href=\"{}\">{}</a>",
+                        escape(href),
+                        escape(href)
+                    ));
+                }
+                self.write_link(node, href);
+            }
+            Data::Citation { note, number } => self.out.push_str(&format!(
+                "<sup><a href=\"#{}\">[{}]</a></sup>",
+                escape(note),
+                number
+            )),
+            Data::Reference { note, .. } => {
+                self.out.push_str(&format!("<li id=\"{}\">", escape(note)));
+                self.write_children(node);
+                self.out.push_str("</li>\n");
+            }
+            _ => self.write_children(node),
+        }
+    }
+}
+
+/// Returns the XHTML document of the chapter showing the page
+fn chapter_xhtml(page: &Page, chapters: &HashMap<String, usize>) -> String {
+    let language_code = page.language.code();
+    let mut writer = XhtmlWriter {
+        language_code,
+        chapters,
+        out: String::new(),
+    };
+    let mut node = page.content.nth(0);
+    while let Some(current) = node {
+        writer.write_node(current);
+        node = current.next();
+    }
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xml:lang="{language}" lang="{language}">
+<head><title>{title}</title></head>
+<body>
+<h1>{title}</h1>
+{body}
+<p><a href="{url}">{url}</a></p>
+</body>
+</html>
+"#,
+        language = escape(language_code),
+        title = escape(&page.title),
+        body = writer.out,
+        url = escape(&article_url(language_code, &page.title)),
+    )
+}
+
+/// Returns the table of contents, listing the chapters
+fn nav_xhtml(title: &str, pages: &[Page]) -> String {
+    let chapters: String = pages
+        .iter()
+        .enumerate()
+        .map(|(n, page)| {
+            format!(
+                "<li><a href=\"{}\">{}</a></li>\n",
+                chapter_file(n + 1),
+                escape(&page.title)
+            )
+        })
+        .collect();
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops">
+<head><title>{title}</title></head>
+<body>
+<nav epub:type="toc" id="toc">
+<h1>{title}</h1>
+<ol>
+{chapters}</ol>
+</nav>
+</body>
+</html>
+"#,
+        title = escape(title),
+        chapters = chapters,
+    )
+}
+
+/// Returns the package document with the metadata of the book and its reading order
+fn content_opf(title: &str, pages: &[Page], created: DateTime<Utc>) -> String {
+    let language = pages
+        .first()
+        .map(|page| page.language.code())
+        .unwrap_or("en");
+    let manifest: String = (1..=pages.len())
+        .map(|n| {
+            format!(
+                "    <item id=\"chapter-{n}\" href=\"{file}\" media-type=\"application/xhtml+xml\"/>\n",
+                n = n,
+                file = chapter_file(n)
+            )
+        })
+        .collect();
+    let spine: String = (1..=pages.len())
+        .map(|n| format!("    <itemref idref=\"chapter-{}\"/>\n", n))
+        .collect();
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="book-id">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:identifier id="book-id">wiki-tui-{id}</dc:identifier>
+    <dc:title>{title}</dc:title>
+    <dc:language>{language}</dc:language>
+    <dc:creator>Wikipedia contributors</dc:creator>
+    <dc:publisher>wiki-tui</dc:publisher>
+    <dc:rights>CC BY-SA 4.0</dc:rights>
+    <meta property="dcterms:modified">{modified}</meta>
+  </metadata>
+  <manifest>
+    <item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>
+{manifest}  </manifest>
+  <spine>
+{spine}  </spine>
+</package>
+"#,
+        id = created.timestamp(),
+        title = escape(title),
+        language = escape(language),
+        modified = created.to_rfc3339_opts(SecondsFormat::Secs, true),
+        manifest = manifest,
+        spine = spine,
+    )
+}
+
+/// Compiles the pages into an EPUB with one chapter per page, in the given order
+///
+/// Links between the pages are rewritten to point to their chapters, links to other articles
+/// point to Wikipedia
+pub fn export_epub(title: &str, pages: &[Page], created: DateTime<Utc>) -> Vec<u8> {
+    let chapters: HashMap<String, usize> = pages
+        .iter()
+        .enumerate()
+        .map(|(n, page)| (page.title.clone(), n + 1))
+        .collect();
+
+    let mut zip = StoredZip::default();
+    // the mimetype has to come first, readers identify the format by it
+    zip.add("mimetype", MIMETYPE.as_bytes());
+    zip.add("META-INF/container.xml", CONTAINER.as_bytes());
+    zip.add(
+        "OEBPS/content.opf",
+        content_opf(title, pages, created).as_bytes(),
+    );
+    zip.add("OEBPS/nav.xhtml", nav_xhtml(title, pages).as_bytes());
+    for (n, page) in pages.iter().enumerate() {
+        zip.add(
+            &format!("OEBPS/{}", chapter_file(n + 1)),
+            chapter_xhtml(page, &chapters).as_bytes(),
+        );
+    }
+    zip.finish()
+}
+
+/// Exports the pages as an EPUB into the export directory and returns the path of the file
+pub fn save_epub(title: &str, pages: &[Page]) -> Result<PathBuf> {
+    let directory = export_directory(&config().export)?;
+    std::fs::create_dir_all(&directory).with_context(|| {
+        format!(
+            "unable to create the export directory '{}'",
+            directory.display()
+        )
+    })?;
+
+    let path = directory.join(format!("{}.epub", file_name(title)));
+    std::fs::write(&path, export_epub(title, pages, Utc::now()))
+        .with_context(|| format!("unable to write the export '{}'", path.display()))?;
+
+    info!(
+        "exported {} pages as '{}' to '{}'",
+        pages.len(),
+        title,
+        path.display()
+    );
+    Ok(path)
+}
+
+/// Returns the CRC-32 checksum of the data, as used by ZIP archives
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+/// ZIP archive whose files are stored without compression, which is all EPUB needs
+#[derive(Default)]
+struct StoredZip {
+    out: Vec<u8>,
+    central_directory: Vec<u8>,
+    files: u16,
+}
+
+impl StoredZip {
+    /// Writes the header fields shared by the local header and the central directory entry
+    fn common_header(out: &mut Vec<u8>, name: &str, data: &[u8]) {
+        out.extend_from_slice(&20u16.to_le_bytes()); // version needed to extract
+        out.extend_from_slice(&0u16.to_le_bytes()); // flags
+        out.extend_from_slice(&0u16.to_le_bytes()); // stored, no compression
+        out.extend_from_slice(&0u16.to_le_bytes()); // modification time
+        out.extend_from_slice(&0x21u16.to_le_bytes()); // modification date, 1980-01-01
+        out.extend_from_slice(&crc32(data).to_le_bytes());
+        out.extend_from_slice(&(data.len() as u32).to_le_bytes()); // compressed size
+        out.extend_from_slice(&(data.len() as u32).to_le_bytes()); // uncompressed size
+        out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        out.extend_from_slice(&0u16.to_le_bytes()); // extra field length
+    }
+
+    fn add(&mut self, name: &str, data: &[u8]) {
+        let offset = self.out.len() as u32;
+
+        self.out.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        Self::common_header(&mut self.out, name, data);
+        self.out.extend_from_slice(name.as_bytes());
+        self.out.extend_from_slice(data);
+
+        let entry = &mut self.central_directory;
+        entry.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        entry.extend_from_slice(&20u16.to_le_bytes()); // version made by
+        Self::common_header(entry, name, data);
+        entry.extend_from_slice(&0u16.to_le_bytes()); // comment length
+        entry.extend_from_slice(&0u16.to_le_bytes()); // disk number
+        entry.extend_from_slice(&0u16.to_le_bytes()); // internal attributes
+        entry.extend_from_slice(&0u32.to_le_bytes()); // external attributes
+        entry.extend_from_slice(&offset.to_le_bytes());
+        entry.extend_from_slice(name.as_bytes());
+
+        self.files += 1;
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let offset = self.out.len() as u32;
+        let size = self.central_directory.len() as u32;
+        self.out.append(&mut self.central_directory);
+
+        self.out.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+        self.out.extend_from_slice(&0u16.to_le_bytes()); // number of this disk
+        self.out.extend_from_slice(&0u16.to_le_bytes()); // disk with the central directory
+        self.out.extend_from_slice(&self.files.to_le_bytes());
+        self.out.extend_from_slice(&self.files.to_le_bytes());
+        self.out.extend_from_slice(&size.to_le_bytes());
+        self.out.extend_from_slice(&offset.to_le_bytes());
+        self.out.extend_from_slice(&0u16.to_le_bytes()); // comment length
+        self.out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use chrono::TimeZone;
+    use wiki_api::{
+        document::Document,
+        languages::Language,
+        page::Page,
+        parser::{Parser, WikipediaParser},
+    };
+
+    use super::{chapter_xhtml, crc32, export_epub};
+
+    fn page(title: &str, html: &str) -> Page {
+        Page {
+            title: title.to_string(),
+            pageid: 0,
+            content: Document {
+                nodes: WikipediaParser::parse_document(html).nodes(),
+            },
+            language: Language::default(),
+            language_links: None,
+            sections: None,
+            revision_id: None,
+            is_disambiguation: false,
+            quality: None,
+            parse_time: None,
+            redirected_from: None,
+        }
+    }
+
+    #[test]
+    fn epub() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+
+        let rust = page(
+            "Rust",
+            r#"<section data-mw-section-id="0"><p>Written like <a rel="mw:WikiLink" href="./C%2B%2B#Syntax" title="C++">C++</a> &amp; <a rel="mw:WikiLink" href="./Go_(language)" title="Go (language)">Go</a>.</p></section>"#,
+        );
+        let cpp = page(
+            "C++",
+            r#"<section data-mw-section-id="0"><p>C++</p></section>"#,
+        );
+        let chapters = HashMap::from([("Rust".to_string(), 1), ("C++".to_string(), 2)]);
+
+        let chapter = chapter_xhtml(&rust, &chapters);
+        assert!(chapter.contains(
+            r#"<p>Written like <a href="chapter-2.xhtml#Syntax">C++</a> &amp; <a href="https://en.wikipedia.org/wiki/Go_(language)">Go</a>.</p>"#
+        ));
+
+        let created = chrono::Utc.with_ymd_and_hms(2023, 11, 2, 12, 0, 0).unwrap();
+        let epub = export_epub("Languages", &[rust, cpp], created);
+        // the uncompressed mimetype directly follows the first local header
+        assert_eq!(&epub[..4], b"PK\x03\x04");
+        assert_eq!(&epub[30..58], b"mimetypeapplication/epub+zip");
+        assert_eq!(&epub[epub.len() - 22..epub.len() - 18], b"PK\x05\x06");
+    }
+}
@@ -0,0 +1,505 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use anyhow::Result;
+use chrono::{DateTime, SecondsFormat, Utc};
+use ratatui::style::Color;
+use tracing::{info, warn};
+use wiki_api::{
+    document::{Data, HeaderKind, Node},
+    images::{image_url, Image},
+    page::Page,
+    Endpoint,
+};
+
+use crate::{
+    config::config,
+    export::{article_url, save_export},
+    i18n::tr,
+    image_cache::load_image,
+    ui::{color_rgb, theme, Theme},
+};
+
+use super::epub::escape;
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Background of the page, terminals usually have a dark one
+const BACKGROUND: Color = Color::Rgb(30, 30, 30);
+const FOREGROUND: Color = Color::Gray;
+
+/// Elements and the colors the default renderer shows them in with the theme
+fn colors(theme: &Theme) -> [(&'static str, Color); 11] {
+    [
+        ("h1, h2, h3, h4, h5, h6", theme.heading),
+        ("a, i", theme.link),
+        (".red-link", theme.red_link),
+        ("sup.citation a", theme.reference),
+        ("mark", theme.mark),
+        ("abbr", theme.abbreviation),
+        (".notice", theme.notice),
+        (".disambiguation", Color::Gray),
+        ("figcaption, footer", Color::DarkGray),
+        ("th", Color::White),
+        ("hr", theme.separator),
+    ]
+}
+
+/// Styles that don't depend on the colors
+const LAYOUT: &str = "body { max-width: 50em; margin: 2em auto; padding: 0 1em; \
+font-family: sans-serif; line-height: 1.5; }
+a.external, .red-link, sup.citation a { font-style: italic; }
+mark { background: none; }
+hr { border: none; border-top: 1px solid; }
+table { border-collapse: collapse; margin: 1em 0; }
+th, td { border: 1px solid #555; padding: 0.2em 0.5em; text-align: left; vertical-align: top; }
+figure { margin: 1em 0; }
+figure img { max-width: 100%; height: auto; }
+pre { overflow-x: auto; }
+.poem { margin-left: 2em; }
+footer { margin-top: 3em; font-size: small; }
+";
+
+fn css_color(color: Color) -> String {
+    match color_rgb(color) {
+        Some((r, g, b)) => format!("#{:02x}{:02x}{:02x}", r, g, b),
+        None => "inherit".to_string(),
+    }
+}
+
+/// Returns the styles of the export, using the colors the page has in the terminal
+fn stylesheet() -> String {
+    let mut css = format!(
+        "html {{ background: {}; color: {}; }}\n",
+        css_color(BACKGROUND),
+        css_color(FOREGROUND)
+    );
+    for (selector, color) in colors(&theme()) {
+        css.push_str(&format!(
+            "{} {{ color: {}; }}\n",
+            selector,
+            css_color(color)
+        ));
+    }
+    css.push_str(LAYOUT);
+    css
+}
+
+/// Encodes the data in Base64, with padding
+fn base64(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity((data.len() + 2) / 3 * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let value = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for n in 0..4 {
+            if n <= chunk.len() {
+                let index = (value >> (18 - 6 * n)) & 0x3f;
+                encoded.push(BASE64_ALPHABET[index as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Returns the data URI containing the image
+fn data_uri(image: &Image) -> String {
+    format!("data:{};base64,{}", image.mime_type, base64(&image.data))
+}
+
+/// Converts the document of a page into the HTML body of a standalone file
+struct HtmlWriter<'a> {
+    language_code: &'a str,
+    endpoint: &'a Endpoint,
+    /// Data URIs of the embedded images, by their source
+    images: &'a HashMap<String, String>,
+
+    out: String,
+}
+
+impl<'a> HtmlWriter<'a> {
+    fn write_children(&mut self, node: Node<'_>) {
+        for child in node.children() {
+            self.write_node(child);
+        }
+    }
+
+    /// Writes the children of the node, surrounded by the element. The element can have
+    /// attributes, like `p class="notice"`
+    fn write_element(&mut self, node: Node<'_>, element: &str) {
+        self.out.push_str(&format!("<{}>", element));
+        self.write_children(node);
+        let name = element.split(' ').next().unwrap_or(element);
+        self.out.push_str(&format!("</{}>", name));
+    }
+
+    fn write_link(&mut self, node: Node<'_>, href: &str, class: Option<&str>) {
+        let class = class
+            .map(|class| format!(" class=\"{}\"", class))
+            .unwrap_or_default();
+        self.out
+            .push_str(&format!("<a href=\"{}\"{}>", escape(href), class));
+        self.write_children(node);
+        self.out.push_str("</a>");
+    }
+
+    /// Returns the absolute url of a source in the page
+    fn absolute_url(&self, src: &str) -> String {
+        image_url(src, self.endpoint)
+            .map(String::from)
+            .unwrap_or_else(|_| src.to_string())
+    }
+
+    fn write_image(&mut self, src: &str, caption: Option<&str>) {
+        let src = match self.images.get(src) {
+            Some(data_uri) => data_uri.clone(),
+            None => self.absolute_url(src),
+        };
+        let alt = caption.map(escape).unwrap_or_default();
+        self.out.push_str(&format!(
+            "<figure><img src=\"{}\" alt=\"{}\"/>",
+            escape(&src),
+            alt
+        ));
+        if caption.is_some() {
+            self.out
+                .push_str(&format!("<figcaption>{}</figcaption>", alt));
+        }
+        self.out.push_str("</figure>\n");
+    }
+
+    fn write_node(&mut self, node: Node<'_>) {
+        match node.data() {
+            Data::Text { contents } => self.out.push_str(&escape(contents)),
+            Data::Header { id, kind } => {
+                let level = match kind {
+                    HeaderKind::Main => 1,
+                    HeaderKind::Sub => 2,
+                    HeaderKind::Section => 3,
+                    HeaderKind::Subsection => 4,
+                    HeaderKind::Minor => 5,
+                    HeaderKind::Detail => 6,
+                };
+                self.out
+                    .push_str(&format!("<h{} id=\"{}\">", level, escape(id)));
+                self.write_children(node);
+                self.out.push_str(&format!("</h{}>\n", level));
+            }
+            Data::Paragraph => {
+                self.write_element(node, "p");
+                self.out.push('\n');
+            }
+            Data::Hatnote | Data::RedirectMessage => {
+                self.write_element(node, "p class=\"hatnote\"");
+                self.out.push('\n');
+            }
+            Data::MaintenanceNotice => {
+                self.write_element(node, "p class=\"notice\"");
+                self.out.push('\n');
+            }
+            // the box is collapsed like on the wiki, browsers expand it when it's clicked
+            Data::Collapsible { summary, collapsed } => {
+                let open = if *collapsed { "" } else { " open" };
+                self.out.push_str(&format!(
+                    "<details{}><summary>{}</summary>\n",
+                    open,
+                    escape(summary)
+                ));
+                self.write_children(node);
+                self.out.push_str("</details>\n");
+            }
+            Data::RelatedPages => {
+                self.out.push_str(&format!(
+                    "<details class=\"related-pages\"><summary>{}</summary>\n",
+                    escape(tr("page.related_pages"))
+                ));
+                self.write_children(node);
+                self.out.push_str("</details>\n");
+            }
+            Data::Navbox => {
+                self.write_element(node, "div class=\"navbox\"");
+                self.out.push('\n');
+            }
+            Data::NavboxTitle => {
+                self.write_element(node, "p class=\"navbox-title\"");
+                self.out.push('\n');
+            }
+            Data::NavboxGroup => {
+                self.write_element(node, "p");
+                self.out.push('\n');
+            }
+            Data::Division => {
+                self.write_element(node, "div");
+                self.out.push('\n');
+            }
+            Data::Disambiguation => {
+                self.write_element(node, "div class=\"disambiguation\"");
+                self.out.push('\n');
+            }
+            Data::HorizontalRule => self.out.push_str("<hr/>\n"),
+            Data::Preformatted => {
+                self.write_element(node, "pre");
+                self.out.push('\n');
+            }
+            Data::LineBreak => self.out.push_str("<br/>"),
+            Data::Poem => {
+                self.write_element(node, "div class=\"poem\"");
+                self.out.push('\n');
+            }
+            Data::PoemIndentation { level } => {
+                self.out.push_str(&format!(
+                    "<span style=\"display: inline-block; margin-left: {}em;\">",
+                    level
+                ));
+                self.write_children(node);
+                self.out.push_str("</span>");
+            }
+            Data::Table | Data::Taxobox => {
+                self.write_element(node, "table");
+                self.out.push('\n');
+            }
+            Data::TableCaption => self.write_element(node, "caption"),
+            Data::TableRow | Data::TaxoboxRow => self.write_element(node, "tr"),
+            Data::TaxoboxHeading => {
+                self.out.push_str("<tr><th colspan=\"2\">");
+                self.write_children(node);
+                self.out.push_str("</th></tr>");
+            }
+            Data::TableCell { header: true } | Data::TaxoboxLabel => self.write_element(node, "th"),
+            Data::TableCell { header: false } | Data::TaxoboxValue => {
+                self.write_element(node, "td")
+            }
+            Data::OrderedList => self.write_element(node, "ol"),
+            Data::UnorderedList => self.write_element(node, "ul"),
+            Data::ListItem => self.write_element(node, "li"),
+            Data::DescriptionList => self.write_element(node, "dl"),
+            Data::DescriptionListTerm => self.write_element(node, "dt"),
+            Data::DerscriptionListDescription => self.write_element(node, "dd"),
+            Data::Bold | Data::NavboxLabel => self.write_element(node, "b"),
+            Data::Italic => self.write_element(node, "i"),
+            Data::Strikethrough => self.write_element(node, "s"),
+            Data::Underline => self.write_element(node, "u"),
+            Data::Small => self.write_element(node, "small"),
+            Data::Mark => self.write_element(node, "mark"),
+            Data::Abbreviation { title: Some(title) } => {
+                self.out
+                    .push_str(&format!("<abbr title=\"{}\">", escape(title)));
+                self.write_children(node);
+                self.out.push_str("</abbr>");
+            }
+            Data::WikiLink { href, .. } => {
+                // the arrows linking back to the citations of a reference
+                if href.contains("#cite_ref-") {
+                    return;
+                }
+                let url = article_url(self.language_code, href.trim_start_matches("./"));
+                self.write_link(node, &url, None);
+            }
+            Data::RedLink { .. } => self.write_element(node, "span class=\"red-link\""),
+            Data::MediaLink { href, .. } => {
+                let url = self.absolute_url(href);
+                self.write_link(node, &url, None);
+            }
+            Data::ExternalLink {
+                href,
+                autonumber: true,
+                ..
+            } => self.out.push_str(&format!(
+                "<a href=\"{}\" class=\"external\">{}</a>",
+                escape(href),
+                escape(href)
+            )),
+            Data::ExternalLink { href, .. } => self.write_link(node, href, Some("external")),
+            Data::Citation { note, number } => self.out.push_str(&format!(
+                "<sup class=\"citation\"><a href=\"#{}\">[{}]</a></sup>",
+                escape(note),
+                number
+            )),
+            Data::Reference { note, .. } => {
+                self.out.push_str(&format!("<li id=\"{}\">", escape(note)));
+                self.write_children(node);
+                self.out.push_str("</li>\n");
+            }
+            Data::Image { src, caption } => self.write_image(src, caption.as_deref()),
+            _ => self.write_children(node),
+        }
+    }
+}
+
+/// Returns the sources of the images in the page, each one once
+pub fn image_sources(page: &Page) -> Vec<String> {
+    let mut sources: Vec<String> = Vec::new();
+    for node in page.content.nodes.iter() {
+        if let Data::Image { ref src, .. } = node.data {
+            if !sources.contains(src) {
+                sources.push(src.clone());
+            }
+        }
+    }
+    sources
+}
+
+/// Converts a page into a standalone HTML file, styled like the page is shown in the terminal
+///
+/// Links are absolute, so they keep working wherever the file is opened. The images in `images`,
+/// data URIs by their source, are embedded into the file, the others are linked to
+pub fn export_html(
+    page: &Page,
+    images: &HashMap<String, String>,
+    exported: DateTime<Utc>,
+) -> String {
+    let language_code = page.language.code();
+    let endpoint = page.language.wikipedia_endpoint();
+    let mut writer = HtmlWriter {
+        language_code,
+        endpoint: &endpoint,
+        images,
+        out: String::new(),
+    };
+    let mut node = page.content.nth(0);
+    while let Some(current) = node {
+        writer.write_node(current);
+        node = current.next();
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="{language}">
+<head>
+<meta charset="utf-8"/>
+<meta name="viewport" content="width=device-width, initial-scale=1"/>
+<title>{title}</title>
+<style>
+{style}</style>
+</head>
+<body>
+<article>
+<h1>{title}</h1>
+{body}
+</article>
+<footer>
+<p>From <a href="{url}">{url}</a>, exported on {exported}. The text is available under the
+<a href="https://creativecommons.org/licenses/by-sa/4.0/">CC BY-SA 4.0</a> license.</p>
+</footer>
+</body>
+</html>
+"#,
+        language = escape(language_code),
+        title = escape(&page.title),
+        style = stylesheet(),
+        body = writer.out,
+        url = escape(&article_url(language_code, &page.title)),
+        exported = exported.to_rfc3339_opts(SecondsFormat::Secs, true),
+    )
+}
+
+/// Loads the images of the page from the image cache, downloading the missing ones, and returns
+/// them as data URIs, by their source. Images that can't be downloaded are left out, the export
+/// links to them instead
+pub async fn embed_images(page: &Page) -> HashMap<String, String> {
+    let endpoint = page.language.wikipedia_endpoint();
+    let sources = image_sources(page);
+    let mut images = HashMap::new();
+    for src in sources.iter().cloned() {
+        let image = match image_url(&src, &endpoint) {
+            Ok(url) => load_image(url).await,
+            Err(error) => Err(error),
+        };
+        match image {
+            Ok(image) => {
+                images.insert(src, data_uri(&image));
+            }
+            Err(error) => warn!("unable to embed the image '{}': {:?}", src, error),
+        }
+    }
+    info!("embedded {} of {} images", images.len(), sources.len());
+    images
+}
+
+/// Exports the page as HTML into the export directory and returns the path of the file. The
+/// images are embedded when `export.embed_images` is set
+pub async fn save_html(page: &Page) -> Result<PathBuf> {
+    let images = match config().export.embed_images {
+        true => embed_images(page).await,
+        false => HashMap::new(),
+    };
+    save_export(page, "html", export_html(page, &images, Utc::now()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use chrono::TimeZone;
+    use wiki_api::{
+        document::Document,
+        languages::Language,
+        page::Page,
+        parser::{Parser, WikipediaParser},
+    };
+
+    use super::{base64, export_html, image_sources};
+
+    #[test]
+    fn encode() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn html() {
+        let page = Page {
+            title: "Red fox".to_string(),
+            pageid: 0,
+            content: Document {
+                nodes: WikipediaParser::parse_document(
+                    r#"<section data-mw-section-id="0"><figure typeof="mw:File/Thumb"><a href="./File:Fox.jpg"><img src="//upload.wikimedia.org/fox.jpg" alt="A fox"/></a><figcaption>A <b>red</b> fox</figcaption></figure><figure><img src="//upload.wikimedia.org/den.png"/></figure><p>The <a rel="mw:WikiLink" href="./Fox" title="Fox">fox</a> &amp; <a rel="mw:ExtLink" href="https://example.org">others</a>.</p></section>"#,
+                )
+                .nodes(),
+            },
+            language: Language::default(),
+            language_links: None,
+            sections: None,
+            revision_id: None,
+            is_disambiguation: false,
+            quality: None,
+            parse_time: None,
+            redirected_from: None,
+        };
+        assert_eq!(
+            image_sources(&page),
+            vec![
+                "//upload.wikimedia.org/fox.jpg",
+                "//upload.wikimedia.org/den.png"
+            ]
+        );
+
+        let images = HashMap::from([(
+            "//upload.wikimedia.org/fox.jpg".to_string(),
+            "data:image/jpeg;base64,Zm94".to_string(),
+        )]);
+        let exported = chrono::Utc.with_ymd_and_hms(2024, 3, 7, 12, 0, 0).unwrap();
+        let html = export_html(&page, &images, exported);
+
+        assert!(html.contains(concat!(
+            r#"<figure><img src="data:image/jpeg;base64,Zm94" alt="A red fox"/>"#,
+            "<figcaption>A red fox</figcaption></figure>"
+        )));
+        assert!(html.contains(r#"<img src="https://upload.wikimedia.org/den.png" alt=""/>"#));
+        assert!(html.contains(concat!(
+            r#"The <a href="https://en.wikipedia.org/wiki/Fox">fox</a> &amp; "#,
+            r#"<a href="https://example.org" class="external">others</a>."#
+        )));
+        // headers are red like in the terminal
+        assert!(html.contains("h6 { color: #cd0000; }"));
+        assert!(html.contains("exported on 2024-03-07T12:00:00Z"));
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::BTreeMap;
+
+use url::Url;
+
+use crate::config::DomainCategory;
+
+/// Domains of the Wikimedia projects
+const WIKIMEDIA_DOMAINS: &[&str] = &[
+    "mediawiki.org",
+    "wikibooks.org",
+    "wikidata.org",
+    "wikifunctions.org",
+    "wikimedia.org",
+    "wikinews.org",
+    "wikipedia.org",
+    "wikiquote.org",
+    "wikisource.org",
+    "wikiversity.org",
+    "wikivoyage.org",
+    "wiktionary.org",
+];
+
+/// Domains of DOIs, journals, preprint servers and databases of papers
+const JOURNAL_DOMAINS: &[&str] = &[
+    "acm.org",
+    "adsabs.harvard.edu",
+    "arxiv.org",
+    "biorxiv.org",
+    "cambridge.org",
+    "doi.org",
+    "handle.net",
+    "ieee.org",
+    "jstor.org",
+    "nature.com",
+    "ncbi.nlm.nih.gov",
+    "oup.com",
+    "plos.org",
+    "science.org",
+    "sciencedirect.com",
+    "semanticscholar.org",
+    "springer.com",
+    "tandfonline.com",
+    "wiley.com",
+];
+
+/// Domains of news sites that are often cited
+const NEWS_DOMAINS: &[&str] = &[
+    "aljazeera.com",
+    "apnews.com",
+    "bbc.co.uk",
+    "bbc.com",
+    "bloomberg.com",
+    "cnn.com",
+    "economist.com",
+    "faz.net",
+    "ft.com",
+    "independent.co.uk",
+    "latimes.com",
+    "lemonde.fr",
+    "npr.org",
+    "nytimes.com",
+    "reuters.com",
+    "spiegel.de",
+    "sueddeutsche.de",
+    "telegraph.co.uk",
+    "theguardian.com",
+    "time.com",
+    "washingtonpost.com",
+    "wsj.com",
+    "zeit.de",
+];
+
+/// Whether the host is the domain or one of its subdomains
+fn is_on_domain(host: &str, domain: &str) -> bool {
+    host.strip_suffix(domain)
+        .map(|rest| rest.is_empty() || rest.ends_with('.'))
+        .unwrap_or(false)
+}
+
+/// Returns the kind of website the external link points to. The configured domains are checked
+/// before the built-in ones, so they can move a built-in domain into another kind
+pub fn domain_category(
+    href: &str,
+    configured: &BTreeMap<String, DomainCategory>,
+) -> DomainCategory {
+    // links can be relative to the protocol, like `//doi.org/10.1000/182`
+    let url = match href.strip_prefix("//") {
+        Some(rest) => Url::parse(&format!("https://{}", rest)),
+        None => Url::parse(href),
+    };
+    let host = match url.as_ref().ok().and_then(|url| url.host_str()) {
+        Some(host) => host.to_lowercase(),
+        None => return DomainCategory::Other,
+    };
+
+    // the longest configured domain is the most specific one
+    let configured = configured
+        .iter()
+        .filter(|(domain, _)| is_on_domain(&host, &domain.to_lowercase()))
+        .max_by_key(|(domain, _)| domain.len());
+    if let Some((_, category)) = configured {
+        return *category;
+    }
+
+    let builtin = [
+        (WIKIMEDIA_DOMAINS, DomainCategory::Wikimedia),
+        (JOURNAL_DOMAINS, DomainCategory::Journal),
+        (NEWS_DOMAINS, DomainCategory::News),
+    ];
+    builtin
+        .iter()
+        .find(|(domains, _)| domains.iter().any(|domain| is_on_domain(&host, domain)))
+        .map(|(_, category)| *category)
+        .unwrap_or(DomainCategory::Other)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::domain_category;
+    use crate::config::DomainCategory;
+
+    #[test]
+    fn categories() {
+        let none = BTreeMap::new();
+        let category = |href| domain_category(href, &none);
+        assert_eq!(
+            category("https://commons.wikimedia.org/wiki/File:Fox.jpg"),
+            DomainCategory::Wikimedia
+        );
+        assert_eq!(category("//doi.org/10.1000/182"), DomainCategory::Journal);
+        assert_eq!(
+            category("https://www.BBC.co.uk/news/science"),
+            DomainCategory::News
+        );
+        // only whole labels of the domain count
+        assert_eq!(category("https://notnature.com/"), DomainCategory::Other);
+        assert_eq!(category("./Red_fox"), DomainCategory::Other);
+
+        let configured = BTreeMap::from([
+            ("example.org".to_string(), DomainCategory::Journal),
+            ("blogs.nature.com".to_string(), DomainCategory::News),
+        ]);
+        assert_eq!(
+            domain_category("https://papers.example.org/1", &configured),
+            DomainCategory::Journal
+        );
+        assert_eq!(
+            domain_category("https://blogs.nature.com/fox", &configured),
+            DomainCategory::News
+        );
+        assert_eq!(
+            domain_category("https://www.nature.com/articles/1", &configured),
+            DomainCategory::Journal
+        );
+    }
+}
@@ -1,10 +1,16 @@
+pub mod cache;
 pub mod default_renderer;
+pub mod epub;
+pub mod html;
+pub mod link_domains;
 #[cfg(debug_assertions)]
 pub mod test_renderer;
 
+use std::ops::Range;
+
 use ratatui::style::Style;
 use textwrap::core::Fragment;
-use wiki_api::document::{Document, Node};
+use wiki_api::document::{Data, Document, Node};
 
 #[derive(Debug, Clone)]
 pub struct Word {
@@ -45,4 +51,77 @@ impl Fragment for Word {
 #[derive(Debug)]
 pub struct RenderedDocument {
     pub lines: Vec<Vec<Word>>,
+    /// Index of every rendered section together with the lines it spans. Sections are listed in
+    /// the order they start, so a nested section comes after its parent
+    pub sections: Vec<(usize, Range<usize>)>,
+    /// Layout of every rendered table, in the order they appear
+    pub tables: Vec<RenderedTable>,
+    /// Where every rendered link ended up, in the order they appear
+    pub links: Vec<RenderedLink>,
+}
+
+impl RenderedDocument {
+    /// Returns where the link with the first and last node was rendered
+    pub fn link(&self, nodes: (usize, usize)) -> Option<&RenderedLink> {
+        self.links.iter().find(|link| link.nodes == nodes)
+    }
+}
+
+/// Where the rows and columns of a table were rendered. Every row is rendered into a single line
+#[derive(Debug, Clone)]
+pub struct RenderedTable {
+    /// Index of the table node
+    pub index: usize,
+    /// First column and width of every column of the table
+    pub columns: Vec<(u16, u16)>,
+    /// Line of every row, together with the indices of the cells in the row
+    pub rows: Vec<(usize, Vec<usize>)>,
+}
+
+/// Returns the rows of the table, in the order they are rendered in
+pub fn table_rows<'a>(table: Node<'a>) -> impl Iterator<Item = Node<'a>> {
+    table
+        .children()
+        .filter(|row| matches!(row.data(), Data::TableRow))
+}
+
+/// Returns whether the table has more rows than a collapsed table shows. With `max_rows` being
+/// `0`, no table is too long
+pub fn is_long_table(table: Node<'_>, max_rows: usize) -> bool {
+    max_rows != 0 && table_rows(table).nth(max_rows).is_some()
+}
+
+/// Where a link was rendered. A link that is wrapped over several lines has a segment on every
+/// line it spans
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedLink {
+    /// Index of the first and the last node of the link
+    pub nodes: (usize, usize),
+    /// Line of every part of the link together with the words on that line, from top to bottom.
+    /// Whitespace between the words of the link is part of the segment
+    pub segments: Vec<(usize, Range<usize>)>,
+}
+
+impl RenderedLink {
+    pub fn first_line(&self) -> usize {
+        self.segments
+            .first()
+            .map(|(line, _)| *line)
+            .unwrap_or_default()
+    }
+
+    pub fn last_line(&self) -> usize {
+        self.segments
+            .last()
+            .map(|(line, _)| *line)
+            .unwrap_or_default()
+    }
+
+    /// Returns the words of the link on the line
+    pub fn words_on(&self, line: usize) -> Option<&Range<usize>> {
+        self.segments
+            .iter()
+            .find(|(segment_line, _)| *segment_line == line)
+            .map(|(_, words)| words)
+    }
 }
@@ -1,7 +1,11 @@
+use std::collections::{BTreeMap, BTreeSet};
+
 use ratatui::style::Style;
 use wiki_api::document::{Document, Node};
 
-use super::{RenderedDocument, Word};
+use crate::config::RendererConfig;
+
+use super::{default_renderer::render_document_with, RenderedDocument, Word};
 
 #[derive(Clone, Debug)]
 struct Descendants<'a> {
@@ -78,7 +82,12 @@ pub fn render_tree_data(document: &Document) -> RenderedDocument {
         }])
     }
 
-    RenderedDocument { lines }
+    RenderedDocument {
+        lines,
+        sections: Vec::new(),
+        tables: Vec::new(),
+        links: Vec::new(),
+    }
 }
 
 pub fn render_tree_raw(document: &Document) -> RenderedDocument {
@@ -102,7 +111,12 @@ pub fn render_tree_raw(document: &Document) -> RenderedDocument {
         }])
     }
 
-    RenderedDocument { lines }
+    RenderedDocument {
+        lines,
+        sections: Vec::new(),
+        tables: Vec::new(),
+        links: Vec::new(),
+    }
 }
 
 pub fn render_nodes_raw(document: &Document) -> RenderedDocument {
@@ -120,5 +134,51 @@ pub fn render_nodes_raw(document: &Document) -> RenderedDocument {
         }])
     }
 
-    RenderedDocument { lines }
+    RenderedDocument {
+        lines,
+        sections: Vec::new(),
+        tables: Vec::new(),
+        links: Vec::new(),
+    }
+}
+
+/// Renders the document with the default renderer and its default options into plain text
+///
+/// The output only depends on the document and the width, not on the configuration or the
+/// terminal, which makes it suitable for snapshot tests. Styles are dropped and trailing
+/// whitespace is removed from every line
+pub fn render_plain_text(document: &Document, width: u16) -> String {
+    render_plain_text_collapsed(document, width, &BTreeSet::new())
+}
+
+/// Like [`render_plain_text`], but only renders the headers of the `collapsed` sections and the
+/// summaries of the `collapsed` boxes
+pub fn render_plain_text_collapsed(
+    document: &Document,
+    width: u16,
+    collapsed: &BTreeSet<usize>,
+) -> String {
+    let rendered = render_document_with(
+        document,
+        width,
+        &RendererConfig::default(),
+        collapsed,
+        &BTreeMap::new(),
+    );
+    let mut text = String::new();
+    for line in rendered.lines.iter() {
+        let line: String = line
+            .iter()
+            .map(|word| {
+                format!(
+                    "{}{}",
+                    word.content,
+                    " ".repeat(word.whitespace_width as usize)
+                )
+            })
+            .collect();
+        text.push_str(line.trim_end());
+        text.push('\n');
+    }
+    text
 }
@@ -0,0 +1,201 @@
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use serde_json::Value;
+use tracing::{info, warn};
+use url::Url;
+use wiki_api::http::{set_replayer, Replayed};
+
+/// A request of an HTTP Archive together with the response it got
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recording {
+    pub method: String,
+    pub url: Url,
+    pub response: Replayed,
+}
+
+impl Recording {
+    /// Whether the recording answers the request. The method, host and path have to be the same
+    /// and every query parameter of the recording has to be part of the request, other
+    /// parameters of the request are ignored
+    fn matches(&self, method: &str, url: &Url) -> bool {
+        if !self.method.eq_ignore_ascii_case(method)
+            || self.url.host_str() != url.host_str()
+            || self.url.path() != url.path()
+        {
+            return false;
+        }
+        self.url
+            .query_pairs()
+            .all(|pair| url.query_pairs().any(|other| other == pair))
+    }
+}
+
+fn har_entry(entry: &Value) -> Result<Recording> {
+    let field = |value: &Value, name: &str| -> Result<String> {
+        value
+            .get(name)
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("the entry has no '{}'", name))
+    };
+    let request = entry
+        .get("request")
+        .ok_or_else(|| anyhow!("the entry has no request"))?;
+    let response = entry
+        .get("response")
+        .ok_or_else(|| anyhow!("the entry has no response"))?;
+
+    let url = Url::parse(&field(request, "url")?).context("the request url is invalid")?;
+    let response_url = match field(response, "redirectURL") {
+        Ok(redirect) if !redirect.is_empty() => {
+            Url::parse(&redirect).context("the redirect url is invalid")?
+        }
+        _ => url.clone(),
+    };
+    let headers = response
+        .get("headers")
+        .and_then(Value::as_array)
+        .map(|headers| {
+            headers
+                .iter()
+                .filter_map(|header| {
+                    Some((field(header, "name").ok()?, field(header, "value").ok()?))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(Recording {
+        method: field(request, "method")?,
+        url,
+        response: Replayed {
+            status: response
+                .get("status")
+                .and_then(Value::as_u64)
+                .ok_or_else(|| anyhow!("the response has no status"))? as u16,
+            url: response_url,
+            headers,
+            body: response
+                .get("content")
+                .map(|content| field(content, "text"))
+                .transpose()?
+                .unwrap_or_default(),
+        },
+    })
+}
+
+/// Reads the requests and their responses of an HTTP Archive, like the ones exported from the
+/// request log
+pub fn parse_har(contents: &str) -> Result<Vec<Recording>> {
+    let har: Value = serde_json::from_str(contents).context("the archive is not valid json")?;
+    har.get("log")
+        .and_then(|log| log.get("entries"))
+        .and_then(Value::as_array)
+        .ok_or_else(|| anyhow!("the archive has no entries"))?
+        .iter()
+        .enumerate()
+        .map(|(n, entry)| har_entry(entry).with_context(|| format!("invalid entry {}", n)))
+        .collect()
+}
+
+/// Returns the recording answering the request. When several recordings answer it, the one with
+/// the most query parameters is used, the first one of them when they have the same number
+pub fn find_recording<'a>(
+    recordings: &'a [Recording],
+    method: &str,
+    url: &Url,
+) -> Option<&'a Recording> {
+    recordings
+        .iter()
+        .filter(|recording| recording.matches(method, url))
+        // the last one of the recordings with the most parameters is returned by max_by_key
+        .rev()
+        .max_by_key(|recording| recording.url.query_pairs().count())
+}
+
+/// Answers every request to a wiki with the recorded responses of the HTTP Archive instead of
+/// sending it, which makes a session reproducible without a network. Requests that weren't
+/// recorded fail
+pub fn replay_har(path: &Path) -> Result<()> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("unable to read the archive '{}'", path.display()))?;
+    let recordings = parse_har(&contents)
+        .with_context(|| format!("unable to parse the archive '{}'", path.display()))?;
+
+    info!(
+        "replaying {} requests of '{}'",
+        recordings.len(),
+        path.display()
+    );
+    set_replayer(move |method, url| {
+        let recording = find_recording(&recordings, method, url);
+        if recording.is_none() {
+            warn!("no recorded response for '{}'", url);
+        }
+        recording.map(|recording| recording.response.clone())
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use super::{find_recording, parse_har};
+
+    const HAR: &str = r#"{
+        "log": {
+            "entries": [
+                {
+                    "request": { "method": "GET", "url": "https://en.wikipedia.org/w/api.php?action=parse" },
+                    "response": { "status": 200, "headers": [], "content": { "text": "any page" } }
+                },
+                {
+                    "request": { "method": "GET", "url": "https://en.wikipedia.org/w/api.php?action=parse&page=Rust" },
+                    "response": {
+                        "status": 200,
+                        "headers": [{ "name": "content-type", "value": "application/json" }],
+                        "content": { "text": "the page" }
+                    }
+                }
+            ]
+        }
+    }"#;
+
+    #[test]
+    fn find() {
+        let recordings = parse_har(HAR).unwrap();
+        let find = |method: &str, url: &str| {
+            find_recording(&recordings, method, &Url::parse(url).unwrap())
+                .map(|recording| recording.response.body.as_str())
+        };
+
+        assert_eq!(
+            find(
+                "GET",
+                "https://en.wikipedia.org/w/api.php?action=parse&format=json&page=Rust"
+            ),
+            Some("the page")
+        );
+        assert_eq!(
+            find(
+                "GET",
+                "https://en.wikipedia.org/w/api.php?action=parse&page=Go"
+            ),
+            Some("any page")
+        );
+        assert_eq!(
+            find("POST", "https://en.wikipedia.org/w/api.php?action=parse"),
+            None
+        );
+        assert_eq!(
+            find("GET", "https://de.wikipedia.org/w/api.php?action=parse"),
+            None
+        );
+        assert_eq!(
+            recordings[1].response.headers,
+            vec![("content-type".to_string(), "application/json".to_string())]
+        );
+    }
+}
@@ -0,0 +1,316 @@
+use std::{
+    collections::VecDeque,
+    fs::{File, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    sync::{Mutex, MutexGuard, OnceLock},
+};
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::{json, Value};
+use tracing::{error, info};
+use wiki_api::http::{set_recorder, Exchange};
+
+use crate::{
+    config::{config, data_dir},
+    export::export_directory,
+};
+
+const LOG_FILE: &str = "requests.log";
+/// The log is rotated once it's larger than this
+const MAX_LOG_SIZE: u64 = 4 * 1024 * 1024;
+/// Rotated logs that are kept, `requests.1.log` being the newest one
+const ROTATED_LOGS: usize = 3;
+/// Bodies are cut after this many bytes in the log
+const LOGGED_BODY_LENGTH: usize = 8 * 1024;
+/// The latest requests of the session that are kept for exporting them, with their whole bodies
+const SESSION_SIZE: usize = 50;
+/// Headers whose values are replaced in the log
+const COOKIE_HEADERS: [&str; 2] = ["cookie", "set-cookie"];
+const REDACTED_COOKIE: &str = "<redacted>";
+
+static REQUEST_LOG: OnceLock<Mutex<RequestLog>> = OnceLock::new();
+
+/// Requests of this session and the file they are logged to
+struct RequestLog {
+    file: Option<File>,
+    session: VecDeque<Exchange>,
+}
+
+impl RequestLog {
+    fn path(n: usize) -> Result<PathBuf> {
+        let directory = data_dir()?;
+        Ok(match n {
+            0 => directory.join(LOG_FILE),
+            n => directory.join(format!("requests.{}.log", n)),
+        })
+    }
+
+    /// Moves every log one place further back, dropping the oldest one
+    fn rotate() -> Result<()> {
+        for n in (0..ROTATED_LOGS).rev() {
+            let path = Self::path(n)?;
+            if path.exists() {
+                std::fs::rename(&path, Self::path(n + 1)?)
+                    .with_context(|| format!("unable to rotate '{}'", path.display()))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn open() -> Result<File> {
+        let path = Self::path(0)?;
+        let is_full = std::fs::metadata(&path)
+            .map(|metadata| metadata.len() >= MAX_LOG_SIZE)
+            .unwrap_or(false);
+        if is_full {
+            Self::rotate()?;
+        }
+
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("unable to open the request log '{}'", path.display()))
+    }
+
+    fn record(&mut self, exchange: &Exchange) {
+        if let Err(error) = self.write(exchange) {
+            error!("unable to log the request: {:?}", error);
+        }
+
+        self.session.push_back(exchange.clone());
+        if self.session.len() > SESSION_SIZE {
+            self.session.pop_front();
+        }
+    }
+
+    /// Appends the exchange as a single line of JSON, opening a new log when the current one is
+    /// full
+    fn write(&mut self, exchange: &Exchange) -> Result<()> {
+        let is_full = match self.file {
+            Some(ref file) => file.metadata()?.len() >= MAX_LOG_SIZE,
+            None => true,
+        };
+        if is_full {
+            self.file = None;
+            self.file = Some(Self::open()?);
+        }
+
+        let mut line = serde_json::to_string(&log_entry(exchange))?;
+        line.push('\n');
+        self.file
+            .as_mut()
+            .unwrap()
+            .write_all(line.as_bytes())
+            .context("unable to write the request log")
+    }
+}
+
+fn request_log() -> MutexGuard<'static, RequestLog> {
+    REQUEST_LOG
+        .get_or_init(|| {
+            Mutex::new(RequestLog {
+                file: None,
+                session: VecDeque::new(),
+            })
+        })
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Cuts the text after at most `length` bytes, at a character boundary
+fn truncate(text: &str, length: usize) -> &str {
+    if text.len() <= length {
+        return text;
+    }
+    let mut end = length;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+fn started(exchange: &Exchange) -> String {
+    DateTime::<Utc>::from(exchange.started).to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// The cookies of a wiki can hold a login, so their values are never logged
+fn headers(headers: &[(String, String)]) -> Value {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if COOKIE_HEADERS
+                .iter()
+                .any(|header| name.eq_ignore_ascii_case(header))
+            {
+                REDACTED_COOKIE
+            } else {
+                value.as_str()
+            };
+            json!({ "name": name, "value": value })
+        })
+        .collect()
+}
+
+fn log_entry(exchange: &Exchange) -> Value {
+    json!({
+        "started": started(exchange),
+        "duration_ms": exchange.duration.as_millis() as u64,
+        "method": exchange.method,
+        "url": exchange.url.as_str(),
+        "request_headers": headers(&exchange.request_headers),
+        "status": exchange.status,
+        "response_url": exchange.response_url.as_str(),
+        "response_headers": headers(&exchange.response_headers),
+        "body_length": exchange.body.len(),
+        "body": truncate(&exchange.body, LOGGED_BODY_LENGTH),
+    })
+}
+
+fn har_entry(exchange: &Exchange) -> Value {
+    let mime_type = exchange
+        .response_headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+        .map(|(_, value)| value.as_str())
+        .unwrap_or_default();
+    let query: Value = exchange
+        .url
+        .query_pairs()
+        .map(|(name, value)| json!({ "name": name, "value": value }))
+        .collect();
+    let time = exchange.duration.as_secs_f64() * 1000.0;
+
+    json!({
+        "startedDateTime": started(exchange),
+        "time": time,
+        "request": {
+            "method": exchange.method,
+            "url": exchange.url.as_str(),
+            "httpVersion": "HTTP/1.1",
+            "cookies": [],
+            "headers": headers(&exchange.request_headers),
+            "queryString": query,
+            "headersSize": -1,
+            "bodySize": 0,
+        },
+        "response": {
+            "status": exchange.status,
+            "statusText": "",
+            "httpVersion": "HTTP/1.1",
+            "cookies": [],
+            "headers": headers(&exchange.response_headers),
+            "content": {
+                "size": exchange.body.len(),
+                "mimeType": mime_type,
+                "text": exchange.body,
+            },
+            "redirectURL": if exchange.response_url != exchange.url {
+                exchange.response_url.as_str()
+            } else {
+                ""
+            },
+            "headersSize": -1,
+            "bodySize": exchange.body.len(),
+        },
+        "cache": {},
+        "timings": { "send": 0, "wait": time, "receive": 0 },
+    })
+}
+
+/// Returns the exchanges as an HTTP Archive, which browsers and other tools can open
+fn har(exchanges: &[Exchange]) -> Value {
+    json!({
+        "log": {
+            "version": "1.2",
+            "creator": { "name": "wiki-tui", "version": env!("CARGO_PKG_VERSION") },
+            "entries": exchanges.iter().map(har_entry).collect::<Vec<Value>>(),
+        }
+    })
+}
+
+/// Logs every request to the wiki when it's enabled in the config
+pub fn initialize_request_log() {
+    if !config().api.log_requests {
+        return;
+    }
+    info!("logging the requests to '{}'", LOG_FILE);
+    set_recorder(|exchange| request_log().record(exchange));
+}
+
+/// Exports the latest requests of the session as an HTTP Archive into the export directory and
+/// returns the path of the file
+pub fn export_har() -> Result<PathBuf> {
+    let exchanges: Vec<Exchange> = request_log().session.iter().cloned().collect();
+    if exchanges.is_empty() {
+        bail!("no requests were recorded, is `api.log_requests` enabled?");
+    }
+
+    let directory = export_directory(&config().export)?;
+    std::fs::create_dir_all(&directory).with_context(|| {
+        format!(
+            "unable to create the export directory '{}'",
+            directory.display()
+        )
+    })?;
+
+    let path = directory.join(format!(
+        "session-{}.har",
+        Utc::now().format("%Y-%m-%d-%H%M%S")
+    ));
+    let contents = serde_json::to_string_pretty(&har(&exchanges))?;
+    std::fs::write(&path, contents)
+        .with_context(|| format!("unable to write the export '{}'", path.display()))?;
+
+    info!(
+        "exported {} requests to '{}'",
+        exchanges.len(),
+        path.display()
+    );
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use wiki_api::{http::Exchange, Endpoint};
+
+    use super::{har, log_entry, truncate, LOGGED_BODY_LENGTH, REDACTED_COOKIE};
+
+    #[test]
+    fn entries() {
+        assert_eq!(truncate("Zürich", 2), "Z");
+        assert_eq!(truncate("Zürich", 3), "Zü");
+
+        let url =
+            Endpoint::parse("https://en.wikipedia.org/w/api.php?action=parse&page=Rust").unwrap();
+        let exchange = Exchange {
+            started: SystemTime::UNIX_EPOCH + Duration::from_secs(60),
+            duration: Duration::from_millis(250),
+            method: "GET".to_string(),
+            url: url.clone(),
+            request_headers: vec![("cookie".to_string(), "wikiSession=abc123".to_string())],
+            status: 200,
+            response_url: url,
+            response_headers: vec![("content-type".to_string(), "application/json".to_string())],
+            body: "x".repeat(LOGGED_BODY_LENGTH + 10),
+        };
+
+        let entry = log_entry(&exchange);
+        assert_eq!(entry["started"], "1970-01-01T00:01:00.000Z");
+        assert_eq!(entry["duration_ms"], 250);
+        assert_eq!(entry["body_length"], LOGGED_BODY_LENGTH + 10);
+        assert_eq!(entry["body"].as_str().unwrap().len(), LOGGED_BODY_LENGTH);
+        assert_eq!(entry["request_headers"][0]["value"], REDACTED_COOKIE);
+
+        let har = har(&[exchange]);
+        let entry = &har["log"]["entries"][0];
+        assert_eq!(entry["request"]["queryString"][1]["value"], "Rust");
+        assert_eq!(entry["response"]["content"]["mimeType"], "application/json");
+        assert_eq!(entry["response"]["redirectURL"], "");
+        assert_eq!(entry["time"], 250.0);
+    }
+}
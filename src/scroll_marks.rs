@@ -0,0 +1,110 @@
+use std::{
+    path::PathBuf,
+    sync::{Mutex, MutexGuard, OnceLock},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+use wiki_api::{languages::Language, page::Page};
+
+use crate::config::data_dir;
+
+const SCROLL_MARKS_FILE: &str = "scroll_marks.json";
+
+static SCROLL_MARKS: OnceLock<Mutex<ScrollMarks>> = OnceLock::new();
+
+/// The place in a page the reader marked as where they stopped. Unlike the scroll position, it
+/// only moves when it's marked again, so parts of the page can be re-read without losing it
+///
+/// The place is the text at the top of the viewport, which is found again no matter the width
+/// the page is rendered with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScrollMark {
+    pub title: String,
+    pub language: Language,
+    /// Node of the first word at the top of the viewport
+    pub node: usize,
+    /// How many characters of the node come before that word
+    pub offset: usize,
+    /// Unix timestamp of when the place was marked
+    pub marked: i64,
+}
+
+/// The scroll mark of every page that has one, stored in the data directory
+#[derive(Default)]
+pub struct ScrollMarks {
+    marks: Vec<ScrollMark>,
+}
+
+impl ScrollMarks {
+    fn path() -> Result<PathBuf> {
+        Ok(data_dir()?.join(SCROLL_MARKS_FILE))
+    }
+
+    fn load() -> Result<Self> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        info!("loading the scroll marks from '{}'", path.display());
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("unable to read the scroll marks '{}'", path.display()))?;
+        let marks = serde_json::from_str(&contents)
+            .with_context(|| format!("unable to parse the scroll marks '{}'", path.display()))?;
+        Ok(Self { marks })
+    }
+
+    fn save(&self) {
+        let result = Self::path().and_then(|path| {
+            std::fs::create_dir_all(data_dir()?)?;
+            std::fs::write(&path, serde_json::to_string_pretty(&self.marks)?)
+                .with_context(|| format!("unable to write the scroll marks '{}'", path.display()))
+        });
+
+        if let Err(error) = result {
+            error!("unable to save the scroll marks: {:?}", error);
+        }
+    }
+
+    fn position(&self, title: &str, language: &Language) -> Option<usize> {
+        self.marks
+            .iter()
+            .position(|mark| mark.title == title && &mark.language == language)
+    }
+
+    /// Returns the node and offset of the marked place in the page
+    pub fn get(&self, page: &Page) -> Option<(usize, usize)> {
+        self.position(&page.title, &page.language)
+            .map(|position| (self.marks[position].node, self.marks[position].offset))
+    }
+
+    /// Marks the place in the page, replacing the place marked before
+    pub fn set(&mut self, page: &Page, (node, offset): (usize, usize)) {
+        if let Some(position) = self.position(&page.title, &page.language) {
+            self.marks.remove(position);
+        }
+        self.marks.push(ScrollMark {
+            title: page.title.clone(),
+            language: page.language.clone(),
+            node,
+            offset,
+            marked: chrono::Utc::now().timestamp(),
+        });
+        self.save();
+    }
+}
+
+/// Returns the stored scroll marks, loading them on first use
+pub fn scroll_marks() -> MutexGuard<'static, ScrollMarks> {
+    SCROLL_MARKS
+        .get_or_init(|| {
+            Mutex::new(ScrollMarks::load().unwrap_or_else(|error| {
+                error!("unable to load the scroll marks: {:?}", error);
+                ScrollMarks::default()
+            }))
+        })
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
@@ -0,0 +1,132 @@
+use std::time::Duration;
+
+use tracing::{info, warn};
+use wiki_api::languages::{has_wikipedia, Language};
+
+use crate::config::{config_path, persist_value};
+
+/// Environment variables the locale is read from, by their precedence
+const LOCALE_VARIABLES: [&str; 3] = ["LC_ALL", "LC_MESSAGES", "LANG"];
+/// How long checking for a Wikipedia in the detected language may take, it's used unchecked
+/// afterwards
+const VERIFY_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// The language chosen on the first run, before there is a config file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FirstRun {
+    pub language: Language,
+    /// Whether the language is the one of the system, English is used otherwise
+    pub detected: bool,
+}
+
+/// Returns the language code of a locale like `de_DE.UTF-8@euro`. The `C` and `POSIX` locales
+/// don't have a language
+fn locale_language(locale: &str) -> Option<String> {
+    let language = locale
+        .split(['_', '.', '@', '-'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    match language.as_str() {
+        "" | "c" | "posix" => None,
+        _ => Some(language),
+    }
+}
+
+/// Returns the language of the system locale when there's a Wikipedia in it. Like for the
+/// messages of other programs, the first variable that is set is used
+fn system_language() -> Option<Language> {
+    let locale = LOCALE_VARIABLES
+        .iter()
+        .filter_map(|variable| std::env::var(variable).ok())
+        .find(|value| !value.is_empty())?;
+    let code = locale_language(&locale)?;
+    let language = Language::find(&code);
+    if language.is_none() {
+        info!(
+            "there is no Wikipedia in the language of the locale '{}'",
+            locale
+        );
+    }
+    language
+}
+
+/// Detects the language of the system and checks whether it has an open Wikipedia edition,
+/// falling back to English. When the check fails, like while offline, the language is trusted
+pub async fn detect_language() -> FirstRun {
+    let language = match system_language() {
+        Some(language) => language,
+        None => {
+            return FirstRun {
+                language: Language::default(),
+                detected: false,
+            }
+        }
+    };
+
+    let detected = match tokio::time::timeout(VERIFY_TIMEOUT, has_wikipedia(&language)).await {
+        Ok(Ok(has_wikipedia)) => has_wikipedia,
+        Ok(Err(error)) => {
+            warn!(
+                "unable to check for a Wikipedia in '{}', using it anyway: {:?}",
+                language.code(),
+                error
+            );
+            true
+        }
+        Err(_) => {
+            warn!(
+                "checking for a Wikipedia in '{}' timed out, using it anyway",
+                language.code()
+            );
+            true
+        }
+    };
+
+    if !detected {
+        info!(
+            "there is no open Wikipedia in '{}', using English",
+            language.code()
+        );
+        return FirstRun {
+            language: Language::default(),
+            detected,
+        };
+    }
+    FirstRun { language, detected }
+}
+
+/// Chooses the language pages are loaded in on the first run and writes it into a new config
+/// file, which is loaded afterwards. Returns `None` when there already is a config file
+pub async fn initialize_config() -> Option<FirstRun> {
+    match config_path() {
+        Ok(path) if !path.exists() => {}
+        _ => return None,
+    }
+
+    let first_run = detect_language().await;
+    info!(
+        "first run, loading pages in '{}'",
+        first_run.language.code()
+    );
+    if let Err(error) = persist_value("api", "language", first_run.language.code()) {
+        warn!("unable to write the generated config: {:?}", error);
+    }
+    Some(first_run)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::locale_language;
+
+    #[test]
+    fn locales() {
+        assert_eq!(locale_language("de_DE.UTF-8").as_deref(), Some("de"));
+        assert_eq!(locale_language("fr_FR@euro").as_deref(), Some("fr"));
+        assert_eq!(locale_language("pt-BR").as_deref(), Some("pt"));
+        assert_eq!(locale_language("es").as_deref(), Some("es"));
+        assert_eq!(locale_language("C.UTF-8"), None);
+        assert_eq!(locale_language("POSIX"), None);
+        assert_eq!(locale_language(""), None);
+    }
+}
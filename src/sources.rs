@@ -0,0 +1,100 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, OnceLock},
+};
+
+use tracing::{error, info};
+use wiki_api::{
+    backend::{ActionBackend, PageBackend},
+    zim::ZimBackend,
+    Endpoint,
+};
+
+use crate::config::config;
+
+/// The archive passed with `--zim`, it's read instead of `api.zim`
+static ZIM_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+static CONTENT_SOURCE: OnceLock<ContentSource> = OnceLock::new();
+
+/// Where pages are searched and read from
+pub enum ContentSource {
+    /// The wiki of the language, through the configured API
+    Wiki,
+    /// A ZIM archive, read without any network. Every language reads from the same archive
+    Zim(Arc<ZimBackend>),
+}
+
+impl ContentSource {
+    /// Creates the backend pages are fetched with, for the wiki with the endpoint. Falls back to
+    /// the action API when the configured backend can't be created
+    pub fn backend(&self, endpoint: Endpoint) -> Arc<dyn PageBackend> {
+        if let ContentSource::Zim(ref zim) = self {
+            return zim.clone();
+        }
+
+        match config().api.backend.backend(endpoint.clone()) {
+            Ok(backend) => backend.into(),
+            Err(error) => {
+                error!("unable to create the configured backend: {:?}", error);
+                Arc::new(ActionBackend::new(endpoint))
+            }
+        }
+    }
+
+    /// The archive pages are read from, `None` when they are fetched from the wiki
+    pub fn zim(&self) -> Option<&Arc<ZimBackend>> {
+        match self {
+            ContentSource::Zim(zim) => Some(zim),
+            ContentSource::Wiki => None,
+        }
+    }
+
+    /// Whether pages are read from an archive, nothing that needs the wiki is fetched then
+    pub fn is_archive(&self) -> bool {
+        self.zim().is_some()
+    }
+}
+
+/// Reads the archive instead of the one configured in `api.zim`. Has to be called before the
+/// content source is first used
+pub fn set_zim_override(path: PathBuf) {
+    if ZIM_OVERRIDE.set(path).is_err() {
+        error!("the ZIM archive was already chosen");
+    }
+}
+
+fn zim_path() -> Option<&'static Path> {
+    ZIM_OVERRIDE
+        .get()
+        .map(PathBuf::as_path)
+        .or(config().api.zim.as_deref())
+}
+
+/// Returns where pages are searched and read from. The archive is opened on the first call, when
+/// it can't be opened pages are fetched from the wiki
+pub fn content_source() -> &'static ContentSource {
+    CONTENT_SOURCE.get_or_init(|| {
+        let path = match zim_path() {
+            Some(path) => path,
+            None => return ContentSource::Wiki,
+        };
+        match ZimBackend::open(path) {
+            Ok(zim) => {
+                info!(
+                    "reading pages from the ZIM archive '{}' with {} entries",
+                    path.display(),
+                    zim.archive().entry_count()
+                );
+                ContentSource::Zim(Arc::new(zim))
+            }
+            Err(error) => {
+                error!(
+                    "unable to open the ZIM archive '{}', fetching pages from the wiki: {:?}",
+                    path.display(),
+                    error
+                );
+                ContentSource::Wiki
+            }
+        }
+    })
+}
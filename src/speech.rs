@@ -0,0 +1,214 @@
+use std::{
+    process::Stdio,
+    sync::{
+        atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use tokio::{io::AsyncWriteExt, process::Command, sync::mpsc::UnboundedSender};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, warn};
+
+use crate::{
+    action::{Action, PageAction},
+    renderer::Word,
+};
+
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+const SENTENCE_TERMINATORS: [char; 3] = ['.', '!', '?'];
+
+/// The session of the next speaker, so the progress of a stopped speaker can be told apart
+static NEXT_SESSION: AtomicU64 = AtomicU64::new(0);
+
+/// A position in a rendered document: (line, word)
+pub type WordPosition = (usize, usize);
+
+/// A sentence of a rendered document, spanning from the first to the last word (inclusive)
+#[derive(Debug, Clone)]
+pub struct Sentence {
+    pub text: String,
+    pub start: WordPosition,
+    pub end: WordPosition,
+}
+
+impl Sentence {
+    pub fn contains(&self, position: WordPosition) -> bool {
+        self.start <= position && position <= self.end
+    }
+}
+
+/// Splits the given lines of a rendered document into sentences
+///
+/// Words not belonging to a node (whitespace, prefixes, lines) are skipped
+pub fn split_sentences(lines: &[Vec<Word>], first_line: usize) -> Vec<Sentence> {
+    let mut sentences = Vec::new();
+    let mut current: Option<Sentence> = None;
+
+    for (line_idx, line) in lines.iter().enumerate() {
+        for (word_idx, word) in line.iter().enumerate() {
+            if word.index == usize::MAX || word.content.trim().is_empty() {
+                continue;
+            }
+
+            let position = (first_line + line_idx, word_idx);
+            let sentence = current.get_or_insert_with(|| Sentence {
+                text: String::new(),
+                start: position,
+                end: position,
+            });
+
+            if !sentence.text.is_empty() {
+                sentence.text.push(' ');
+            }
+            sentence.text.push_str(&word.content);
+            sentence.end = position;
+
+            if word.content.ends_with(SENTENCE_TERMINATORS) {
+                sentences.extend(current.take());
+            }
+        }
+    }
+
+    sentences.extend(current);
+    sentences
+}
+
+/// Reads sentences aloud by piping them, one after another, into an external text-to-speech
+/// command
+///
+/// The progress is reported back with `PageAction::SpeechProgress` and
+/// `PageAction::SpeechFinished`, tagged with the session of the speaker. Dropping the speaker
+/// stops the playback.
+pub struct Speaker {
+    session: u64,
+    paused: Arc<AtomicBool>,
+    pid: Arc<AtomicU32>,
+    cancellation_token: CancellationToken,
+}
+
+impl Speaker {
+    pub fn speak(
+        command: &[String],
+        sentences: Vec<String>,
+        action_tx: UnboundedSender<Action>,
+    ) -> Option<Speaker> {
+        let (program, args) = match command.split_first() {
+            Some((program, args)) => (program.to_owned(), args.to_vec()),
+            None => {
+                warn!("no text-to-speech command configured");
+                return None;
+            }
+        };
+
+        let session = NEXT_SESSION.fetch_add(1, Ordering::Relaxed);
+        let paused = Arc::new(AtomicBool::new(false));
+        let pid = Arc::new(AtomicU32::new(0));
+        let cancellation_token = CancellationToken::new();
+
+        let _paused = paused.clone();
+        let _pid = pid.clone();
+        let _cancellation_token = cancellation_token.clone();
+        tokio::spawn(async move {
+            for (n, sentence) in sentences.into_iter().enumerate() {
+                while _paused.load(Ordering::Relaxed) {
+                    tokio::select! {
+                        _ = _cancellation_token.cancelled() => return,
+                        _ = tokio::time::sleep(PAUSE_POLL_INTERVAL) => {}
+                    }
+                }
+
+                action_tx
+                    .send(Action::Page(PageAction::SpeechProgress(session, n)))
+                    .unwrap();
+
+                let mut child = match Command::new(&program)
+                    .args(&args)
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .kill_on_drop(true)
+                    .spawn()
+                {
+                    Ok(child) => child,
+                    Err(error) => {
+                        error!("unable to start the text-to-speech command: {:?}", error);
+                        break;
+                    }
+                };
+                _pid.store(child.id().unwrap_or_default(), Ordering::Relaxed);
+
+                if let Some(mut stdin) = child.stdin.take() {
+                    if let Err(error) = stdin.write_all(sentence.as_bytes()).await {
+                        warn!("unable to write to the text-to-speech command: {:?}", error);
+                    }
+                }
+
+                tokio::select! {
+                    _ = _cancellation_token.cancelled() => {
+                        let _ = child.kill().await;
+                        return;
+                    }
+                    status = child.wait() => debug!("text-to-speech command exited: {:?}", status),
+                }
+                _pid.store(0, Ordering::Relaxed);
+            }
+
+            action_tx
+                .send(Action::Page(PageAction::SpeechFinished(session)))
+                .unwrap();
+        });
+
+        Some(Speaker {
+            session,
+            paused,
+            pid,
+            cancellation_token,
+        })
+    }
+
+    /// Identifies the playback in the progress it reports
+    pub fn session(&self) -> u64 {
+        self.session
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Pauses or resumes the playback. The currently running command is suspended as well
+    pub fn toggle_pause(&self) {
+        let paused = !self.is_paused();
+        self.paused.store(paused, Ordering::Relaxed);
+
+        #[cfg(unix)]
+        {
+            let pid = self.pid.load(Ordering::Relaxed);
+            if pid != 0 {
+                let signal = if paused { libc::SIGSTOP } else { libc::SIGCONT };
+                // SAFETY: sending a signal to the child process we spawned
+                unsafe { libc::kill(pid as libc::pid_t, signal) };
+            }
+        }
+    }
+
+    pub fn stop(&self) {
+        // the child could be suspended and would never be killed otherwise
+        #[cfg(unix)]
+        {
+            let pid = self.pid.load(Ordering::Relaxed);
+            if pid != 0 && self.is_paused() {
+                // SAFETY: sending a signal to the child process we spawned
+                unsafe { libc::kill(pid as libc::pid_t, libc::SIGCONT) };
+            }
+        }
+        self.cancellation_token.cancel();
+    }
+}
+
+impl Drop for Speaker {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
@@ -0,0 +1,110 @@
+use std::{
+    fmt::Write,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+/// Steps of the startup that ran so far, in order
+static PHASES: Mutex<Vec<StartupPhase>> = Mutex::new(Vec::new());
+/// Whether the time each step took is printed once wiki-tui started
+static PROFILE: AtomicBool = AtomicBool::new(false);
+
+/// A step of the startup, like loading the config, and how long it took
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StartupPhase {
+    pub name: &'static str,
+    pub duration: Duration,
+}
+
+/// Prints the time every step of the startup took once wiki-tui started, and quits right after
+pub fn enable_startup_profile() {
+    PROFILE.store(true, Ordering::Relaxed);
+}
+
+pub fn is_profiling_startup() -> bool {
+    PROFILE.load(Ordering::Relaxed)
+}
+
+/// Runs a step of the startup and remembers how long it took
+pub fn startup_phase<T>(name: &'static str, step: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let result = step();
+    PHASES
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .push(StartupPhase {
+            name,
+            duration: start.elapsed(),
+        });
+    result
+}
+
+/// Returns the steps of the startup that ran so far
+pub fn startup_phases() -> Vec<StartupPhase> {
+    PHASES
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+}
+
+/// Formats the steps as a table with the time each one took and its share of the whole startup
+pub fn format_startup_profile(phases: &[StartupPhase]) -> String {
+    let total: Duration = phases.iter().map(|phase| phase.duration).sum();
+    let width = phases
+        .iter()
+        .map(|phase| phase.name.len())
+        .chain(std::iter::once("total".len()))
+        .max()
+        .unwrap_or_default();
+    let millis = |duration: Duration| duration.as_secs_f64() * 1000.0;
+
+    let mut profile = String::new();
+    for phase in phases {
+        let share = if total.is_zero() {
+            0.0
+        } else {
+            phase.duration.as_secs_f64() / total.as_secs_f64() * 100.0
+        };
+        let _ = writeln!(
+            profile,
+            "{:width$}  {:>9.2} ms  {:>5.1} %",
+            phase.name,
+            millis(phase.duration),
+            share
+        );
+    }
+    let _ = writeln!(profile, "{:width$}  {:>9.2} ms", "total", millis(total));
+    profile
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{format_startup_profile, StartupPhase};
+
+    #[test]
+    fn profile() {
+        let phases = [
+            StartupPhase {
+                name: "config",
+                duration: Duration::from_micros(1500),
+            },
+            StartupPhase {
+                name: "first frame",
+                duration: Duration::from_micros(4500),
+            },
+        ];
+
+        assert_eq!(
+            format_startup_profile(&phases),
+            "config            1.50 ms   25.0 %\n\
+             first frame       4.50 ms   75.0 %\n\
+             total             6.00 ms\n"
+        );
+        assert_eq!(format_startup_profile(&[]), "total       0.00 ms\n");
+    }
+}
@@ -0,0 +1,143 @@
+use std::{
+    future::Future,
+    sync::{Mutex, MutexGuard, OnceLock},
+    time::Duration,
+};
+
+use tokio::task::AbortHandle;
+use tracing::info;
+
+/// How often [`wait_for_tasks`] checks whether the tasks finished
+const WAIT_INTERVAL: Duration = Duration::from_millis(100);
+
+static TASKS: OnceLock<Mutex<Tasks>> = OnceLock::new();
+
+/// What a background task does, the tasks are summarized by their kind before quitting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskKind {
+    /// Downloading a file, like the original of a media file
+    Download,
+    /// Exporting pages into a file
+    Export,
+    /// Fetching queued pages into the page cache
+    Fetch,
+}
+
+struct Task {
+    id: usize,
+    kind: TaskKind,
+    name: String,
+    handle: AbortHandle,
+}
+
+/// The background tasks that are still running and would be lost when quitting
+#[derive(Default)]
+pub struct Tasks {
+    tasks: Vec<Task>,
+    next_id: usize,
+}
+
+impl Tasks {
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// Returns the names of the running tasks of the kind, like the file that is downloaded
+    pub fn names(&self, kind: TaskKind) -> Vec<&str> {
+        self.tasks
+            .iter()
+            .filter(|task| task.kind == kind)
+            .map(|task| task.name.as_str())
+            .collect()
+    }
+
+    fn remove(&mut self, id: usize) {
+        self.tasks.retain(|task| task.id != id);
+    }
+}
+
+/// Unregisters a task once its future is dropped, after it finished or was cancelled
+struct TaskGuard(usize);
+
+impl Drop for TaskGuard {
+    fn drop(&mut self) {
+        tasks().remove(self.0);
+    }
+}
+
+/// Returns the running background tasks
+pub fn tasks() -> MutexGuard<'static, Tasks> {
+    TASKS
+        .get_or_init(|| Mutex::new(Tasks::default()))
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Spawns the future as a background task, it's registered until it finishes or is cancelled
+pub fn spawn_task<F>(kind: TaskKind, name: impl Into<String>, future: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    // the task can't unregister itself before it's registered while the lock is held
+    let mut tasks = tasks();
+    let id = tasks.next_id;
+    tasks.next_id += 1;
+
+    let handle = tokio::spawn(async move {
+        let _guard = TaskGuard(id);
+        future.await;
+    });
+    tasks.tasks.push(Task {
+        id,
+        kind,
+        name: name.into(),
+        handle: handle.abort_handle(),
+    });
+}
+
+/// Cancels all running background tasks
+pub fn cancel_tasks() {
+    // the tasks unregister themselves when they are dropped, which needs the lock
+    let cancelled = std::mem::take(&mut tasks().tasks);
+    for task in cancelled {
+        info!("cancelling the task '{}'", task.name);
+        task.handle.abort();
+    }
+}
+
+/// Waits until all running background tasks finished
+pub async fn wait_for_tasks() {
+    while !tasks().is_empty() {
+        tokio::time::sleep(WAIT_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cancel_tasks, spawn_task, tasks, wait_for_tasks, TaskKind};
+
+    #[tokio::test]
+    async fn registry() {
+        let (finish_tx, finish_rx) = tokio::sync::oneshot::channel::<()>();
+        spawn_task(TaskKind::Download, "Fox.jpg", async move {
+            let _ = finish_rx.await;
+        });
+        spawn_task(TaskKind::Export, "Rust.epub", std::future::pending());
+        assert_eq!(tasks().names(TaskKind::Download), vec!["Fox.jpg"]);
+        assert_eq!(tasks().len(), 2);
+
+        finish_tx.send(()).unwrap();
+        while tasks().len() == 2 {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(tasks().names(TaskKind::Download), Vec::<&str>::new());
+
+        cancel_tasks();
+        wait_for_tasks().await;
+        assert!(tasks().is_empty());
+    }
+}
@@ -0,0 +1,207 @@
+use std::sync::OnceLock;
+
+use ratatui::{buffer::Buffer, style::Color};
+use tracing::info;
+
+use crate::config::{config, ColorLevel};
+
+static COLOR_LEVEL: OnceLock<ColorLevel> = OnceLock::new();
+
+/// The 16 ANSI colors with the values most terminals use for them (the xterm defaults)
+const ANSI_COLORS: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// Levels of the 6x6x6 color cube of the 256 color palette
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Guesses which colors the terminal supports from the environment variables terminals set
+pub fn detect_color_level(colorterm: Option<&str>, term: Option<&str>) -> ColorLevel {
+    if matches!(colorterm, Some("truecolor" | "24bit")) {
+        return ColorLevel::TrueColor;
+    }
+    match term {
+        Some(term) if term.contains("truecolor") || term.contains("direct") => {
+            ColorLevel::TrueColor
+        }
+        Some(term) if term.contains("256color") => ColorLevel::Ansi256,
+        _ => ColorLevel::Ansi16,
+    }
+}
+
+/// Returns the colors the terminal supports, as configured in `ui.color_level` or detected at
+/// the first call
+pub fn color_level() -> ColorLevel {
+    *COLOR_LEVEL.get_or_init(|| match config().ui.color_level {
+        ColorLevel::Auto => {
+            let level = detect_color_level(
+                std::env::var("COLORTERM").ok().as_deref(),
+                std::env::var("TERM").ok().as_deref(),
+            );
+            info!("detected the color level '{:?}' of the terminal", level);
+            level
+        }
+        level => level,
+    })
+}
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let square = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    square(r1, r2) + square(g1, g2) + square(b1, b2)
+}
+
+/// Returns the values of an entry of the 256 color palette
+fn indexed_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => ANSI_COLORS[index as usize].1,
+        16..=231 => {
+            let index = index - 16;
+            (
+                CUBE_LEVELS[(index / 36) as usize],
+                CUBE_LEVELS[(index / 6 % 6) as usize],
+                CUBE_LEVELS[(index % 6) as usize],
+            )
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            (gray, gray, gray)
+        }
+    }
+}
+
+/// Returns the values of the color. The named colors get the values most terminals use for them,
+/// `None` stands for the default color of the terminal
+pub fn color_rgb(color: Color) -> Option<(u8, u8, u8)> {
+    match color {
+        Color::Reset => None,
+        Color::Rgb(r, g, b) => Some((r, g, b)),
+        Color::Indexed(index) => Some(indexed_rgb(index)),
+        color => ANSI_COLORS
+            .iter()
+            .find(|(ansi, _)| *ansi == color)
+            .map(|(_, rgb)| *rgb),
+    }
+}
+
+/// Returns the closest entry of the 256 color palette, from its color cube or its gray ramp
+fn nearest_indexed(rgb: (u8, u8, u8)) -> u8 {
+    let cube_index = |value: u8| {
+        CUBE_LEVELS
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, level)| (**level as i32 - value as i32).abs())
+            .map(|(index, _)| index as u8)
+            .unwrap_or_default()
+    };
+    let cube = 16 + 36 * cube_index(rgb.0) + 6 * cube_index(rgb.1) + cube_index(rgb.2);
+
+    let average = (rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3;
+    let gray = 232 + (average.saturating_sub(3) / 10).min(23) as u8;
+
+    if distance(indexed_rgb(gray), rgb) < distance(indexed_rgb(cube), rgb) {
+        gray
+    } else {
+        cube
+    }
+}
+
+fn nearest_ansi(rgb: (u8, u8, u8)) -> Color {
+    ANSI_COLORS
+        .iter()
+        .min_by_key(|(_, ansi)| distance(*ansi, rgb))
+        .map(|(color, _)| *color)
+        .unwrap_or(Color::Reset)
+}
+
+/// Maps the color to the closest one the terminal supports. The named colors are supported by
+/// every terminal
+pub fn downgrade_color(color: Color, level: ColorLevel) -> Color {
+    match (color, level) {
+        (_, ColorLevel::Auto | ColorLevel::TrueColor) => color,
+        (Color::Rgb(r, g, b), ColorLevel::Ansi256) => Color::Indexed(nearest_indexed((r, g, b))),
+        (Color::Rgb(r, g, b), ColorLevel::Ansi16) => nearest_ansi((r, g, b)),
+        (Color::Indexed(index), ColorLevel::Ansi16) if index < 16 => ANSI_COLORS[index as usize].0,
+        (Color::Indexed(index), ColorLevel::Ansi16) => nearest_ansi(indexed_rgb(index)),
+        (color, _) => color,
+    }
+}
+
+/// Replaces the colors of the drawn frame the terminal doesn't support with the closest ones it
+/// does, instead of leaving it to the terminal to ignore or misinterpret them
+pub fn downgrade_colors(buffer: &mut Buffer) {
+    let level = color_level();
+    if level == ColorLevel::TrueColor {
+        return;
+    }
+    for cell in buffer.content.iter_mut() {
+        cell.fg = downgrade_color(cell.fg, level);
+        cell.bg = downgrade_color(cell.bg, level);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Color;
+
+    use super::{detect_color_level, downgrade_color};
+    use crate::config::ColorLevel;
+
+    #[test]
+    fn levels() {
+        assert_eq!(
+            detect_color_level(Some("truecolor"), Some("xterm-256color")),
+            ColorLevel::TrueColor
+        );
+        assert_eq!(
+            detect_color_level(None, Some("xterm-256color")),
+            ColorLevel::Ansi256
+        );
+        assert_eq!(detect_color_level(None, Some("linux")), ColorLevel::Ansi16);
+        assert_eq!(detect_color_level(None, None), ColorLevel::Ansi16);
+    }
+
+    #[test]
+    fn downgrade() {
+        let orange = Color::Rgb(255, 135, 0);
+        assert_eq!(downgrade_color(orange, ColorLevel::TrueColor), orange);
+        assert_eq!(
+            downgrade_color(orange, ColorLevel::Ansi256),
+            Color::Indexed(208)
+        );
+        assert_eq!(
+            downgrade_color(Color::Rgb(128, 128, 128), ColorLevel::Ansi256),
+            Color::Indexed(244)
+        );
+        assert_eq!(
+            downgrade_color(Color::Rgb(10, 10, 200), ColorLevel::Ansi16),
+            Color::Blue
+        );
+        assert_eq!(
+            downgrade_color(Color::Indexed(9), ColorLevel::Ansi16),
+            Color::LightRed
+        );
+        assert_eq!(
+            downgrade_color(Color::Indexed(196), ColorLevel::Ansi16),
+            Color::LightRed
+        );
+        assert_eq!(
+            downgrade_color(Color::Yellow, ColorLevel::Ansi16),
+            Color::Yellow
+        );
+    }
+}
@@ -0,0 +1,54 @@
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::Span,
+};
+
+const MATCH_STYLE: Style = Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD);
+
+/// Splits the text into spans, highlighting the characters at the positions, like the ones
+/// matched by [`crate::fuzzy::fuzzy_match`]
+pub fn highlight_matches(text: &str, positions: &[usize]) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut current = String::new();
+    let mut is_match = false;
+    for (n, c) in text.chars().enumerate() {
+        let is_position = positions.contains(&n);
+        if is_position != is_match && !current.is_empty() {
+            spans.push(span(std::mem::take(&mut current), is_match));
+        }
+        is_match = is_position;
+        current.push(c);
+    }
+    if !current.is_empty() {
+        spans.push(span(current, is_match));
+    }
+    spans
+}
+
+fn span(text: String, is_match: bool) -> Span<'static> {
+    match is_match {
+        true => Span::styled(text, MATCH_STYLE),
+        false => Span::raw(text),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::text::Span;
+
+    use super::{highlight_matches, MATCH_STYLE};
+
+    #[test]
+    fn spans() {
+        assert_eq!(
+            highlight_matches("History", &[0, 1, 5]),
+            vec![
+                Span::styled("Hi", MATCH_STYLE),
+                Span::raw("sto"),
+                Span::styled("r", MATCH_STYLE),
+                Span::raw("y"),
+            ]
+        );
+        assert_eq!(highlight_matches("abc", &[]), vec![Span::raw("abc")]);
+    }
+}
@@ -1,8 +1,20 @@
 mod centered_rect;
+mod colors;
+mod highlight;
 mod padded_rect;
+mod responsive;
+mod section_scrollbar;
 mod stateful_list;
+mod summary_popup;
+mod theme;
 
 pub use centered_rect::centered_rect;
+pub use colors::{color_level, color_rgb, downgrade_colors};
+pub use highlight::highlight_matches;
 pub use padded_rect::padded_rect;
+pub use responsive::{render_too_small, ResponsiveLayout};
+pub use section_scrollbar::SectionScrollbar;
+pub use summary_popup::render_summary_popup;
+pub use theme::{set_theme_preset, theme, theme_preset, Theme};
 
 pub use stateful_list::{ScrollBehaviour, StatefulList};
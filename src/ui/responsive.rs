@@ -0,0 +1,127 @@
+use ratatui::{
+    prelude::{Alignment, Rect},
+    style::Stylize,
+    widgets::{Paragraph, Wrap},
+};
+
+use crate::{i18n::tr_args, terminal::Frame};
+
+/// Below this width nothing but the "terminal too small" screen is shown
+pub const MIN_WIDTH: u16 = 30;
+/// Below this height nothing but the "terminal too small" screen is shown
+pub const MIN_HEIGHT: u16 = 8;
+
+/// Below this width the contents sidebar and the scrollbar are hidden and the status bar is
+/// collapsed
+pub const COMPACT_WIDTH: u16 = 60;
+/// Below this height the status bar is hidden
+pub const COMPACT_HEIGHT: u16 = 15;
+
+/// Decides which parts of the interface fit into an area
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResponsiveLayout {
+    width: u16,
+    height: u16,
+}
+
+impl ResponsiveLayout {
+    pub fn new(area: Rect) -> Self {
+        Self {
+            width: area.width,
+            height: area.height,
+        }
+    }
+
+    /// Whether the area is below the hard minimums and can't show the interface at all
+    pub fn is_too_small(&self) -> bool {
+        self.width < MIN_WIDTH || self.height < MIN_HEIGHT
+    }
+
+    pub fn is_narrow(&self) -> bool {
+        self.width < COMPACT_WIDTH
+    }
+
+    pub fn shows_sidebar(&self) -> bool {
+        !self.is_narrow()
+    }
+
+    pub fn shows_scrollbar(&self) -> bool {
+        !self.is_narrow()
+    }
+
+    pub fn shows_status(&self) -> bool {
+        self.height >= COMPACT_HEIGHT
+    }
+
+    /// Whether the status bar only shows the most important information
+    pub fn collapses_status(&self) -> bool {
+        self.is_narrow()
+    }
+}
+
+/// Replaces the whole interface with a message asking to enlarge the terminal
+pub fn render_too_small(f: &mut Frame<'_>, area: Rect) {
+    let message = tr_args(
+        "ui.too_small",
+        &[
+            ("width", area.width.to_string()),
+            ("height", area.height.to_string()),
+            ("min_width", MIN_WIDTH.to_string()),
+            ("min_height", MIN_HEIGHT.to_string()),
+        ],
+    );
+    let paragraph = Paragraph::new(message)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true })
+        .red();
+
+    let y = area.height.saturating_sub(2) / 2;
+    let area = Rect::new(area.x, area.y + y, area.width, area.height - y);
+    f.render_widget(paragraph, area);
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::prelude::Rect;
+
+    use super::ResponsiveLayout;
+
+    fn layout(width: u16, height: u16) -> ResponsiveLayout {
+        ResponsiveLayout::new(Rect::new(0, 0, width, height))
+    }
+
+    #[test]
+    fn too_small() {
+        assert!(layout(0, 0).is_too_small());
+        assert!(layout(20, 40).is_too_small());
+        assert!(layout(120, 5).is_too_small());
+        assert!(!layout(40, 10).is_too_small());
+        assert!(!layout(120, 40).is_too_small());
+    }
+
+    #[test]
+    fn narrow() {
+        let phone = layout(40, 10);
+        assert!(!phone.shows_sidebar());
+        assert!(!phone.shows_scrollbar());
+        assert!(phone.collapses_status());
+        assert!(!phone.shows_status());
+
+        let narrow = layout(59, 40);
+        assert!(!narrow.shows_sidebar());
+        assert!(!narrow.shows_scrollbar());
+        assert!(narrow.shows_status());
+    }
+
+    #[test]
+    fn regular() {
+        for (width, height) in [(60, 15), (80, 24), (200, 60)] {
+            let regular = layout(width, height);
+            assert!(!regular.is_too_small());
+            assert!(regular.shows_sidebar());
+            assert!(regular.shows_scrollbar());
+            assert!(regular.shows_status());
+            assert!(!regular.collapses_status());
+        }
+    }
+}
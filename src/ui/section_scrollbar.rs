@@ -0,0 +1,173 @@
+use std::ops::Range;
+
+use ratatui::{
+    buffer::Buffer,
+    prelude::Rect,
+    style::{Color, Style},
+    widgets::Widget,
+};
+
+const TRACK_SYMBOL: &str = " ";
+const THUMB_SYMBOL: &str = "┃";
+const TICK_SYMBOL: &str = "─";
+const THUMB_TICK_SYMBOL: &str = "╂";
+const MARK_SYMBOL: &str = "◆";
+const MARK_COLOR: Color = Color::Yellow;
+
+/// Vertical scrollbar in the rightmost column of its area that doubles as a map of the page: the
+/// rows where sections begin are marked with a tick and the rows of the section at the top of
+/// the viewport have a lighter track. The place the reader marked as where they stopped is shown
+/// as a diamond
+pub struct SectionScrollbar<'a> {
+    /// How many lines there are to scroll through
+    length: usize,
+    /// The visible lines
+    viewport: Range<usize>,
+    /// First line of every section, in any order
+    section_starts: &'a [usize],
+    /// Lines of the section at the top of the viewport
+    current_section: Option<Range<usize>>,
+    /// Line of the place the reader marked
+    mark: Option<usize>,
+}
+
+impl<'a> SectionScrollbar<'a> {
+    pub fn new(length: usize, viewport: Range<usize>) -> Self {
+        Self {
+            length,
+            viewport,
+            section_starts: &[],
+            current_section: None,
+            mark: None,
+        }
+    }
+
+    pub fn section_starts(mut self, section_starts: &'a [usize]) -> Self {
+        self.section_starts = section_starts;
+        self
+    }
+
+    pub fn current_section(mut self, current_section: Option<Range<usize>>) -> Self {
+        self.current_section = current_section;
+        self
+    }
+
+    pub fn mark(mut self, mark: Option<usize>) -> Self {
+        self.mark = mark;
+        self
+    }
+
+    /// Returns the lines shown by the row of a scrollbar with the height. Every row stands for
+    /// at least one line
+    fn lines_of(&self, row: u16, height: u16) -> Range<usize> {
+        let start = row as usize * self.length / height as usize;
+        let end = (row as usize + 1) * self.length / height as usize;
+        start..end.max(start + 1)
+    }
+
+    /// Returns the rows of the thumb in a scrollbar with the height
+    fn thumb(&self, height: u16) -> Range<u16> {
+        let height = height as usize;
+        if self.length <= self.viewport.len() {
+            return 0..height as u16;
+        }
+        let start = self.viewport.start * height / self.length;
+        // rounded up, the thumb covers every row showing a visible line
+        let end = ((self.viewport.end * height) as f64 / self.length as f64).ceil() as usize;
+        start.min(height - 1) as u16..end.clamp(start + 1, height) as u16
+    }
+}
+
+fn overlaps(a: &Range<usize>, b: &Range<usize>) -> bool {
+    a.start < b.end && b.start < a.end
+}
+
+impl Widget for SectionScrollbar<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.width == 0 || area.height == 0 {
+            return;
+        }
+
+        let x = area.right() - 1;
+        let thumb = self.thumb(area.height);
+        for row in 0..area.height {
+            let lines = self.lines_of(row, area.height);
+            let is_tick = self
+                .section_starts
+                .iter()
+                // every page starts with a section, a tick at the top tells nothing
+                .any(|start| *start != 0 && lines.contains(start));
+            let is_thumb = thumb.contains(&row);
+            let is_current = self
+                .current_section
+                .as_ref()
+                .map(|section| overlaps(section, &lines))
+                .unwrap_or(false);
+
+            let is_mark = self.mark.is_some_and(|mark| lines.contains(&mark));
+
+            let symbol = match (is_mark, is_thumb, is_tick) {
+                (true, _, _) => MARK_SYMBOL,
+                (false, true, true) => THUMB_TICK_SYMBOL,
+                (false, true, false) => THUMB_SYMBOL,
+                (false, false, true) => TICK_SYMBOL,
+                (false, false, false) => TRACK_SYMBOL,
+            };
+            let fg = match (is_mark, is_thumb) {
+                (true, _) => MARK_COLOR,
+                (false, true) => Color::Blue,
+                (false, false) => Color::Gray,
+            };
+            let style = Style::new().fg(fg).bg(if is_current {
+                Color::DarkGray
+            } else {
+                Color::Black
+            });
+            buf.get_mut(x, area.y + row)
+                .set_symbol(symbol)
+                .set_style(style);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{buffer::Buffer, prelude::Rect, style::Color, widgets::Widget};
+
+    use super::SectionScrollbar;
+
+    #[test]
+    fn sections() {
+        let area = Rect::new(0, 0, 1, 10);
+        let mut buf = Buffer::empty(area);
+        SectionScrollbar::new(100, 20..30)
+            .section_starts(&[0, 20, 55])
+            .current_section(Some(20..55))
+            .render(area, &mut buf);
+
+        let symbols: String = (0..10).map(|y| buf.get(0, y).symbol.clone()).collect();
+        assert_eq!(symbols, "  ╂  ─    ");
+
+        let current: Vec<bool> = (0..10)
+            .map(|y| buf.get(0, y).bg == Color::DarkGray)
+            .collect();
+        assert_eq!(
+            current,
+            [false, false, true, true, true, true, false, false, false, false]
+        );
+    }
+
+    #[test]
+    fn mark() {
+        let area = Rect::new(0, 0, 1, 10);
+        let mut buf = Buffer::empty(area);
+        SectionScrollbar::new(100, 20..30)
+            .section_starts(&[0, 55])
+            .mark(Some(71))
+            .render(area, &mut buf);
+
+        let symbols: String = (0..10).map(|y| buf.get(0, y).symbol.clone()).collect();
+        assert_eq!(symbols, "  ┃  ─ ◆  ");
+        assert_eq!(buf.get(0, 7).fg, Color::Yellow);
+    }
+}
@@ -0,0 +1,54 @@
+use ratatui::{
+    prelude::{Alignment, Rect},
+    style::{Color, Style, Stylize},
+    text::{Line, Span, Text},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
+};
+use wiki_api::backend::Summary;
+
+use crate::{i18n::tr, terminal::Frame};
+
+use super::centered_rect;
+
+/// Renders the summary of a page in a popup centered in the area, or a loading message when the
+/// summary is `None`
+pub fn render_summary_popup(f: &mut Frame<'_>, area: Rect, summary: Option<&Summary>) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::Yellow));
+    let area = centered_rect(area, 80, 60);
+    f.render_widget(Clear, area);
+
+    let summary = match summary {
+        Some(summary) => summary,
+        None => {
+            f.render_widget(
+                Paragraph::new(tr("search.loading_preview"))
+                    .alignment(Alignment::Center)
+                    .block(block),
+                area,
+            );
+            return;
+        }
+    };
+
+    let mut text = Text::default();
+    if let Some(ref description) = summary.description {
+        text.lines
+            .push(Line::from(Span::raw(description.clone()).italic()));
+        text.lines.push(Line::default());
+    }
+    text.lines.extend(
+        summary
+            .extract
+            .lines()
+            .map(|line| Line::from(line.to_string())),
+    );
+    f.render_widget(
+        Paragraph::new(text)
+            .wrap(Wrap { trim: true })
+            .block(block.title(summary.title.clone())),
+        area,
+    );
+}
@@ -0,0 +1,146 @@
+use std::sync::RwLock;
+
+use ratatui::style::{Color, Modifier};
+
+use crate::config::{config, ThemePreset};
+
+/// Preset chosen while reading, the configured one is used until then
+static CHOSEN_PRESET: RwLock<Option<ThemePreset>> = RwLock::new(None);
+
+/// Colors of the elements of a page
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub heading: Color,
+    /// Links to other pages, to files and to other websites, and italic text
+    pub link: Color,
+    /// Links to pages that don't exist
+    pub red_link: Color,
+    /// External links to the Wikimedia projects, with `renderer.link_domains`
+    pub wikimedia_link: Color,
+    /// External links to journals and papers, with `renderer.link_domains`
+    pub journal_link: Color,
+    /// External links to news sites, with `renderer.link_domains`
+    pub news_link: Color,
+    /// Citations and references to footnotes
+    pub reference: Color,
+    /// Highlighted passages
+    pub mark: Color,
+    pub abbreviation: Color,
+    /// Maintenance notices and other warnings
+    pub notice: Color,
+    /// Horizontal rules, section separators and the borders of tables
+    pub separator: Color,
+    /// Added to the style of every link
+    pub link_modifier: Modifier,
+}
+
+impl Theme {
+    pub fn preset(preset: ThemePreset) -> Self {
+        match preset {
+            ThemePreset::Default => Theme {
+                heading: Color::Red,
+                link: Color::Blue,
+                red_link: Color::Red,
+                wikimedia_link: Color::LightBlue,
+                journal_link: Color::Green,
+                news_link: Color::Magenta,
+                reference: Color::Gray,
+                mark: Color::Yellow,
+                abbreviation: Color::Cyan,
+                notice: Color::Yellow,
+                separator: Color::DarkGray,
+                link_modifier: Modifier::empty(),
+            },
+            ThemePreset::HighContrast => Theme {
+                heading: Color::White,
+                link: Color::LightCyan,
+                red_link: Color::LightYellow,
+                wikimedia_link: Color::LightBlue,
+                journal_link: Color::LightGreen,
+                news_link: Color::LightMagenta,
+                reference: Color::White,
+                mark: Color::LightYellow,
+                abbreviation: Color::LightGreen,
+                notice: Color::LightYellow,
+                separator: Color::White,
+                link_modifier: Modifier::UNDERLINED,
+            },
+            // colors of the Okabe-Ito palette, which stay distinct for most kinds of color
+            // blindness
+            ThemePreset::Deuteranopia => Theme {
+                heading: Color::Rgb(230, 159, 0),
+                link: Color::Rgb(86, 180, 233),
+                red_link: Color::Rgb(213, 94, 0),
+                wikimedia_link: Color::Rgb(0, 114, 178),
+                journal_link: Color::Rgb(0, 158, 115),
+                news_link: Color::Rgb(204, 121, 167),
+                reference: Color::Gray,
+                mark: Color::Rgb(240, 228, 66),
+                abbreviation: Color::Rgb(204, 121, 167),
+                notice: Color::Rgb(240, 228, 66),
+                separator: Color::DarkGray,
+                link_modifier: Modifier::empty(),
+            },
+            ThemePreset::Protanopia => Theme {
+                heading: Color::Rgb(240, 228, 66),
+                link: Color::Rgb(86, 180, 233),
+                red_link: Color::Rgb(230, 159, 0),
+                wikimedia_link: Color::Rgb(0, 114, 178),
+                journal_link: Color::Rgb(0, 158, 115),
+                news_link: Color::Rgb(204, 121, 167),
+                reference: Color::Gray,
+                mark: Color::Rgb(240, 228, 66),
+                abbreviation: Color::Rgb(0, 158, 115),
+                notice: Color::Rgb(230, 159, 0),
+                separator: Color::DarkGray,
+                link_modifier: Modifier::empty(),
+            },
+        }
+    }
+}
+
+/// Returns the preset pages are shown in, the one chosen while reading or the configured one
+pub fn theme_preset() -> ThemePreset {
+    let chosen = *CHOSEN_PRESET
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    chosen.unwrap_or(config().ui.theme)
+}
+
+/// Shows pages in the preset from now on. Pages rendered in another preset are rendered again
+pub fn set_theme_preset(preset: ThemePreset) {
+    *CHOSEN_PRESET
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(preset);
+}
+
+/// Returns the colors pages are shown in
+pub fn theme() -> Theme {
+    Theme::preset(theme_preset())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::ThemePreset;
+
+    use super::Theme;
+
+    #[test]
+    fn presets_tell_links_apart() {
+        for preset in ThemePreset::ALL {
+            let theme = Theme::preset(preset);
+            assert_ne!(theme.link, theme.red_link, "{:?}", preset);
+            assert_ne!(theme.link, theme.heading, "{:?}", preset);
+
+            let domains = [
+                theme.link,
+                theme.wikimedia_link,
+                theme.journal_link,
+                theme.news_link,
+            ];
+            for (n, color) in domains.iter().enumerate() {
+                assert!(!domains[n + 1..].contains(color), "{:?}", preset);
+            }
+        }
+    }
+}
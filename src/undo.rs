@@ -0,0 +1,263 @@
+use std::{
+    collections::VecDeque,
+    fs::OpenOptions,
+    io::Write,
+    path::PathBuf,
+    sync::{Mutex, MutexGuard, OnceLock},
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::json;
+use tracing::{error, info, warn};
+
+use crate::{
+    annotations::{annotations, Annotation},
+    bookmarks::{bookmarks, Bookmark},
+    config::data_dir,
+    page_cache::{restore_entries, CacheEntry},
+    reading_queue::{reading_queue, QueuedPage},
+    workspaces::{workspaces, Workspace},
+};
+
+const AUDIT_LOG_FILE: &str = "audit.log";
+/// At most this many operations can be undone, the oldest ones are forgotten first
+const UNDO_DEPTH: usize = 10;
+/// Removed cached pages are only kept for undoing while they are smaller than this together
+const MAX_UNDO_SIZE: u64 = 32 * 1024 * 1024;
+
+static UNDO_STACK: OnceLock<Mutex<UndoStack>> = OnceLock::new();
+
+/// A destructive operation that can be reverted, holding everything needed to do so
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Undoable {
+    /// A removed bookmark
+    Bookmark(Bookmark),
+    /// A deleted annotation, together with its place in the list of annotations
+    Annotation(usize, Annotation),
+    /// Pages removed from the page cache, together with the contents of their files
+    CachedPages(Vec<(CacheEntry, Vec<u8>)>),
+    /// A deleted workspace
+    Workspace(Workspace),
+    /// A page removed from the reading queue, together with its place in the queue
+    QueuedPage(usize, QueuedPage),
+}
+
+impl Undoable {
+    /// Reads the cached pages before they are removed, so the removal can be undone. Returns
+    /// `None` when they can't be read or are too large to keep around
+    pub fn cached_pages(entries: &[CacheEntry]) -> Option<Self> {
+        let size: u64 = entries.iter().map(|entry| entry.size).sum();
+        if size > MAX_UNDO_SIZE {
+            return None;
+        }
+
+        let mut pages = Vec::new();
+        for entry in entries {
+            match std::fs::read(&entry.path) {
+                Ok(contents) => pages.push((entry.clone(), contents)),
+                Err(error) => {
+                    warn!(
+                        "unable to keep '{}' for undoing its removal: {:?}",
+                        entry.path.display(),
+                        error
+                    );
+                    return None;
+                }
+            }
+        }
+        Some(Undoable::CachedPages(pages))
+    }
+
+    /// Describes the operation for the audit log and the messages
+    pub fn description(&self) -> String {
+        match self {
+            Undoable::Bookmark(bookmark) => format!(
+                "removed the bookmark of '{}' ({})",
+                bookmark.title,
+                bookmark.language.code()
+            ),
+            Undoable::Annotation(_, annotation) => {
+                format!("deleted an annotation of '{}'", annotation.page)
+            }
+            Undoable::CachedPages(pages) => {
+                format!("removed {} pages from the page cache", pages.len())
+            }
+            Undoable::Workspace(workspace) => {
+                format!("deleted the workspace '{}'", workspace.name)
+            }
+            Undoable::QueuedPage(_, page) => format!(
+                "removed '{}' ({}) from the reading queue",
+                page.title,
+                page.language.code()
+            ),
+        }
+    }
+
+    /// Brings back what the operation removed
+    fn revert(self) -> Result<()> {
+        match self {
+            Undoable::Bookmark(bookmark) => bookmarks().restore(bookmark),
+            Undoable::Annotation(index, annotation) => annotations().restore(index, annotation),
+            Undoable::CachedPages(pages) => {
+                restore_entries(&pages).context("unable to restore the cached pages")?;
+            }
+            Undoable::Workspace(workspace) => workspaces().restore(workspace),
+            Undoable::QueuedPage(position, page) => reading_queue().restore(position, page),
+        }
+        Ok(())
+    }
+}
+
+/// The latest destructive operations, the newest one last
+#[derive(Default)]
+struct UndoStack {
+    operations: VecDeque<Undoable>,
+}
+
+impl UndoStack {
+    fn push(&mut self, operation: Undoable) {
+        self.operations.push_back(operation);
+        if self.operations.len() > UNDO_DEPTH {
+            self.operations.pop_front();
+        }
+    }
+
+    fn pop(&mut self) -> Option<Undoable> {
+        self.operations.pop_back()
+    }
+}
+
+fn undo_stack() -> MutexGuard<'static, UndoStack> {
+    UNDO_STACK
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn audit_log_path() -> Result<PathBuf> {
+    Ok(data_dir()?.join(AUDIT_LOG_FILE))
+}
+
+/// Formats an entry of the audit log, a single line of JSON
+fn audit_line(time: DateTime<Utc>, event: &str, description: &str) -> String {
+    json!({
+        "time": time.to_rfc3339_opts(SecondsFormat::Secs, true),
+        "event": event,
+        "description": description,
+    })
+    .to_string()
+}
+
+fn append_audit_line(event: &str, description: &str) {
+    let result = audit_log_path().and_then(|path| {
+        std::fs::create_dir_all(data_dir()?)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("unable to open the audit log '{}'", path.display()))?;
+        writeln!(file, "{}", audit_line(Utc::now(), event, description))
+            .with_context(|| format!("unable to write the audit log '{}'", path.display()))
+    });
+
+    if let Err(error) = result {
+        error!(
+            "unable to log '{}' in the audit log: {:?}",
+            description, error
+        );
+    }
+}
+
+/// Writes a destructive operation that can't be undone into the audit log
+pub fn audit(description: &str) {
+    append_audit_line("removed", description);
+}
+
+/// Writes the destructive operation into the audit log and remembers it, so it can be undone
+pub fn record(operation: Undoable) {
+    append_audit_line("removed", &operation.description());
+    undo_stack().push(operation);
+}
+
+/// Reverts the latest destructive operation that wasn't undone yet. Returns its description,
+/// `None` when there is nothing left to undo
+pub fn undo() -> Result<Option<String>> {
+    // the stack isn't locked while reverting, the stores lock themselves
+    let operation = match undo_stack().pop() {
+        Some(operation) => operation,
+        None => return Ok(None),
+    };
+
+    let description = operation.description();
+    operation.revert()?;
+    append_audit_line("undone", &description);
+    info!("undone: {}", description);
+    Ok(Some(description))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use wiki_api::languages::Language;
+
+    use super::{audit_line, UndoStack, Undoable, UNDO_DEPTH};
+    use crate::annotations::Annotation;
+
+    fn annotation(n: usize) -> Undoable {
+        Undoable::Annotation(
+            n,
+            Annotation {
+                page: format!("Page {}", n),
+                text: String::new(),
+                note: String::new(),
+                created: 0,
+                language: None,
+            },
+        )
+    }
+
+    #[test]
+    fn stack_depth() {
+        let mut stack = UndoStack::default();
+        for n in 0..UNDO_DEPTH + 2 {
+            stack.push(annotation(n));
+        }
+
+        assert_eq!(stack.pop(), Some(annotation(UNDO_DEPTH + 1)));
+        assert_eq!(stack.pop(), Some(annotation(UNDO_DEPTH)));
+        // the two oldest operations were forgotten
+        for _ in 2..UNDO_DEPTH {
+            assert!(stack.pop().is_some());
+        }
+        assert_eq!(stack.pop(), None);
+    }
+
+    #[test]
+    fn audit_lines() {
+        let time = Utc.with_ymd_and_hms(2024, 3, 1, 12, 30, 0).unwrap();
+        assert_eq!(
+            audit_line(time, "removed", "deleted an annotation of 'Fox'"),
+            concat!(
+                r#"{"description":"deleted an annotation of 'Fox'","#,
+                r#""event":"removed","time":"2024-03-01T12:30:00Z"}"#
+            )
+        );
+    }
+
+    #[test]
+    fn descriptions() {
+        let bookmark = Undoable::Bookmark(crate::bookmarks::Bookmark {
+            title: "Fox".to_string(),
+            language: Language::English,
+            added: 0,
+            read_revision: None,
+            latest_revision: None,
+        });
+        assert_eq!(bookmark.description(), "removed the bookmark of 'Fox' (en)");
+        assert_eq!(
+            Undoable::CachedPages(Vec::new()).description(),
+            "removed 0 pages from the page cache"
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use std::{
+    path::PathBuf,
+    sync::{Mutex, MutexGuard, OnceLock},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+use wiki_api::languages::Language;
+
+use crate::config::data_dir;
+
+const WORKSPACES_FILE: &str = "workspaces.json";
+
+static WORKSPACES: OnceLock<Mutex<Workspaces>> = OnceLock::new();
+
+/// An open page of a workspace and where it was scrolled to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkspacePage {
+    pub title: String,
+    pub language: Language,
+    /// Node and offset of the text at the top of the viewport, which is found again no matter
+    /// the width the page is rendered with
+    #[serde(default)]
+    pub anchor: Option<(usize, usize)>,
+}
+
+/// A named set of open pages that can be opened again later
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Workspace {
+    pub name: String,
+    /// The open pages, from the bottom to the top of the stack of pages
+    pub pages: Vec<WorkspacePage>,
+    /// Index of the shown page
+    pub active: usize,
+    /// Unix timestamp of when the workspace was last saved
+    pub saved: i64,
+}
+
+/// The saved workspaces, stored in the data directory
+#[derive(Default)]
+pub struct Workspaces {
+    workspaces: Vec<Workspace>,
+}
+
+impl Workspaces {
+    fn path() -> Result<PathBuf> {
+        Ok(data_dir()?.join(WORKSPACES_FILE))
+    }
+
+    fn load() -> Result<Self> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        info!("loading the workspaces from '{}'", path.display());
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("unable to read the workspaces '{}'", path.display()))?;
+        let workspaces = serde_json::from_str(&contents)
+            .with_context(|| format!("unable to parse the workspaces '{}'", path.display()))?;
+        Ok(Self { workspaces })
+    }
+
+    fn save(&self) {
+        let result = Self::path().and_then(|path| {
+            std::fs::create_dir_all(data_dir()?)?;
+            std::fs::write(&path, serde_json::to_string_pretty(&self.workspaces)?)
+                .with_context(|| format!("unable to write the workspaces '{}'", path.display()))
+        });
+
+        if let Err(error) = result {
+            error!("unable to save the workspaces: {:?}", error);
+        }
+    }
+
+    /// Returns the workspaces, the latest saved one first
+    pub fn all(&self) -> &[Workspace] {
+        &self.workspaces
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Workspace> {
+        self.workspaces
+            .iter()
+            .find(|workspace| workspace.name == name)
+    }
+
+    /// Saves the workspace, replacing the one with the same name
+    pub fn store(&mut self, workspace: Workspace) {
+        self.workspaces
+            .retain(|stored| stored.name != workspace.name);
+        self.workspaces.insert(0, workspace);
+        self.save();
+    }
+
+    /// Deletes the workspace with the name, returns it when there was one
+    pub fn remove(&mut self, name: &str) -> Option<Workspace> {
+        let position = self
+            .workspaces
+            .iter()
+            .position(|workspace| workspace.name == name)?;
+        let workspace = self.workspaces.remove(position);
+        self.save();
+        Some(workspace)
+    }
+
+    /// Adds a deleted workspace again, unless one with the same name was saved since
+    pub fn restore(&mut self, workspace: Workspace) {
+        if self.get(&workspace.name).is_some() {
+            return;
+        }
+        let position = self
+            .workspaces
+            .iter()
+            .position(|stored| stored.saved < workspace.saved)
+            .unwrap_or(self.workspaces.len());
+        self.workspaces.insert(position, workspace);
+        self.save();
+    }
+}
+
+/// Returns the saved workspaces, loading them on first use
+pub fn workspaces() -> MutexGuard<'static, Workspaces> {
+    WORKSPACES
+        .get_or_init(|| {
+            Mutex::new(Workspaces::load().unwrap_or_else(|error| {
+                error!("unable to load the workspaces: {:?}", error);
+                Workspaces::default()
+            }))
+        })
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
@@ -0,0 +1,129 @@
+use std::{
+    collections::VecDeque,
+    sync::{Mutex, MutexGuard, OnceLock},
+};
+
+use crate::{
+    config::config,
+    hooks::{run_hook, Hook, Placeholders},
+};
+
+static YANK_RING: OnceLock<Mutex<YankRing>> = OnceLock::new();
+
+/// What was yanked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum YankKind {
+    /// A link to another page
+    Link,
+    /// Highlighted passages of a page
+    Passage,
+    /// A citation of a page
+    Citation,
+}
+
+impl YankKind {
+    /// Hook that is run when something of this kind is yanked
+    fn hook(&self) -> Hook {
+        match self {
+            YankKind::Link => Hook::LinkYanked,
+            YankKind::Passage | YankKind::Citation => Hook::TextYanked,
+        }
+    }
+}
+
+/// Something that was yanked in this session
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Yank {
+    pub kind: YankKind,
+    /// What is shown in the list of yanks, the url of a link or the text of a passage
+    pub text: String,
+    /// Page that is opened for the yank, the linked page or the page the passage is on
+    pub page: String,
+    /// Placeholders of the hook, kept to yank it again
+    placeholders: Placeholders,
+}
+
+impl Yank {
+    pub fn new(kind: YankKind, text: String, page: String, placeholders: Placeholders) -> Self {
+        Self {
+            kind,
+            text,
+            page,
+            placeholders,
+        }
+    }
+}
+
+/// The most recent yanks of the session, the latest one first. They are not stored, the ring is
+/// empty on every start
+#[derive(Default)]
+pub struct YankRing {
+    yanks: VecDeque<Yank>,
+}
+
+impl YankRing {
+    pub fn all(&self) -> &VecDeque<Yank> {
+        &self.yanks
+    }
+
+    /// Adds the yank to the front, removing the same yank from further back and the oldest
+    /// yanks that don't fit into the ring anymore
+    fn push(&mut self, yank: Yank, capacity: usize) {
+        self.yanks.retain(|other| other != &yank);
+        self.yanks.push_front(yank);
+        self.yanks.truncate(capacity);
+    }
+
+    /// Runs the hook for the yank and remembers it
+    pub fn yank(&mut self, yank: Yank) {
+        run_hook(yank.kind.hook(), yank.placeholders.clone());
+        self.push(yank, config().behavior.yank_ring_size);
+    }
+
+    /// Yanks an older entry again, which moves it to the front
+    pub fn yank_again(&mut self, n: usize) {
+        if let Some(yank) = self.yanks.remove(n) {
+            self.yank(yank);
+        }
+    }
+
+    pub fn remove(&mut self, n: usize) {
+        self.yanks.remove(n);
+    }
+}
+
+/// Returns the yanks of this session
+pub fn yank_ring() -> MutexGuard<'static, YankRing> {
+    YANK_RING
+        .get_or_init(|| Mutex::new(YankRing::default()))
+        .lock()
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Yank, YankKind, YankRing};
+
+    fn link(title: &str) -> Yank {
+        Yank::new(
+            YankKind::Link,
+            title.to_string(),
+            title.to_string(),
+            Vec::new(),
+        )
+    }
+
+    #[test]
+    fn ring() {
+        let mut ring = YankRing::default();
+        for title in ["Rust", "Ferris", "Cargo", "Rust"] {
+            ring.push(link(title), 3);
+        }
+        let titles: Vec<&str> = ring.all().iter().map(|yank| yank.page.as_str()).collect();
+        assert_eq!(titles, ["Rust", "Cargo", "Ferris"]);
+
+        ring.push(link("Crate"), 3);
+        let titles: Vec<&str> = ring.all().iter().map(|yank| yank.page.as_str()).collect();
+        assert_eq!(titles, ["Crate", "Rust", "Cargo"]);
+    }
+}
@@ -0,0 +1,267 @@
+//! Renders the documents in `tests/fixtures/snapshots` at several widths and compares the plain
+//! text against the golden files in `tests/snapshots`
+//!
+//! After an intended change to the renderer, review and accept the new output with
+//! `cargo insta review` (or run the tests with `INSTA_UPDATE=always`)
+
+// the test renderers are only available in debug builds
+#![cfg(debug_assertions)]
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
+
+use wiki_api::{
+    document::{Data, Document},
+    parser::{parse_document_with, ParserMode},
+};
+use wiki_tui::{
+    bench::parse_html,
+    bench::transform_document,
+    config::RendererConfig,
+    renderer::{
+        default_renderer::render_document_with,
+        test_renderer::{render_plain_text, render_plain_text_collapsed},
+    },
+};
+
+const WIDTHS: [u16; 3] = [30, 60, 100];
+
+fn read_fixture(name: &str) -> String {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/snapshots")
+        .join(format!("{name}.html"));
+    std::fs::read_to_string(path).expect("unable to read the fixture")
+}
+
+fn load_fixture(name: &str) -> Document {
+    transform_document(parse_html(&read_fixture(name)))
+}
+
+/// Parses the fixture `mobile_{name}`, the mobile-html output of the page, and checks that it's
+/// rendered just like the Parsoid HTML of the page in the fixture `name`
+fn assert_mobile_html_renders_like(name: &str) {
+    let parsoid = load_fixture(name);
+    let mobile = transform_document(parse_document_with(
+        &read_fixture(&format!("mobile_{name}")),
+        ParserMode::MobileHtml,
+    ));
+
+    for width in WIDTHS {
+        assert_eq!(
+            render_plain_text(&mobile, width),
+            render_plain_text(&parsoid, width),
+            "the mobile-html of '{name}' is rendered differently at the width {width}"
+        );
+    }
+}
+
+fn assert_render_snapshots(name: &str) {
+    let document = load_fixture(name);
+
+    for width in WIDTHS {
+        insta::assert_snapshot!(
+            format!("{name}_{width}"),
+            render_plain_text(&document, width)
+        );
+    }
+}
+
+#[test]
+fn paragraphs() {
+    assert_render_snapshots("paragraphs");
+}
+
+#[test]
+fn lists() {
+    assert_render_snapshots("lists");
+}
+
+#[test]
+fn sections() {
+    assert_render_snapshots("sections");
+}
+
+#[test]
+fn references() {
+    assert_render_snapshots("references");
+}
+
+#[test]
+fn preformatted() {
+    assert_render_snapshots("preformatted");
+}
+
+#[test]
+fn poem() {
+    assert_render_snapshots("poem");
+}
+
+#[test]
+fn wrapped_poem_lines() {
+    let document = transform_document(parse_html(concat!(
+        r#"<div class="poem"><p>Short line<br/>"#,
+        "A line of the poem that is far too long to fit onto a single line</p></div>"
+    )));
+    let text = render_plain_text(&document, 30);
+    let lines: Vec<&str> = text.lines().filter(|line| !line.is_empty()).collect();
+
+    assert_eq!(lines[0], "  Short line");
+    assert!(lines[1].starts_with("  A line"));
+    assert!(lines.len() > 2);
+    // the continuation of a line is indented further than the line itself
+    for line in &lines[2..] {
+        assert!(line.starts_with("    ") && !line.starts_with("     "));
+    }
+}
+
+#[test]
+fn taxobox() {
+    assert_render_snapshots("taxobox");
+}
+
+#[test]
+fn table() {
+    assert_render_snapshots("table");
+}
+
+#[test]
+fn mobile_html_sections() {
+    assert_mobile_html_renders_like("sections");
+}
+
+#[test]
+fn mobile_html_table() {
+    assert_mobile_html_renders_like("table");
+}
+
+#[test]
+fn collapsed_sections() {
+    let document = load_fixture("sections");
+    let collapsed: BTreeSet<usize> = document
+        .nodes
+        .iter()
+        .position(|node| matches!(node.data, Data::Section { id: 1 }))
+        .into_iter()
+        .collect();
+
+    insta::assert_snapshot!(
+        "sections_collapsed_60",
+        render_plain_text_collapsed(&document, 60, &collapsed)
+    );
+}
+
+#[test]
+fn collapsible_boxes() {
+    let document = transform_document(parse_html(concat!(
+        r#"<section data-mw-section-id="0"><p>Text</p>"#,
+        // navboxes are gathered at the end of the page, this box collapses the same way
+        r#"<div><table class="nowraplinks mw-collapsible autocollapse"><tbody>"#,
+        r#"<tr><th colspan="2" class="navbox-title"><div class="navbar plainlinks hlist"><ul><li>v</li><li>t</li><li>e</li></ul></div><div>Programming languages</div></th></tr>"#,
+        r#"<tr><td>Rust and C</td></tr></tbody></table></div>"#,
+        r#"<div class="mw-collapsible"><p>Proof</p><p>It follows.</p></div></section>"#
+    )));
+    let boxes: Vec<(usize, &str, bool)> = document
+        .nodes
+        .iter()
+        .filter_map(|node| match node.data {
+            Data::Collapsible {
+                ref summary,
+                collapsed,
+            } => Some((node.index, summary.as_str(), collapsed)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(boxes.len(), 2);
+    assert_eq!((boxes[0].1, boxes[0].2), ("Programming languages", true));
+    assert_eq!((boxes[1].1, boxes[1].2), ("Proof It follows.", false));
+
+    let collapsed = BTreeSet::from([boxes[0].0]);
+    let text = render_plain_text_collapsed(&document, 60, &collapsed);
+    assert!(text.contains("▸ Programming languages"));
+    assert!(!text.contains("Rust and C"));
+    assert!(text.contains("▾ Proof It follows."));
+
+    let text = render_plain_text_collapsed(&document, 60, &BTreeSet::new());
+    assert!(text.contains("▾ Programming languages"));
+    assert!(text.contains("Rust and C"));
+}
+
+#[test]
+fn wrapped_links() {
+    let document = load_fixture("links");
+    let rendered = render_document_with(
+        &document,
+        30,
+        &RendererConfig::default(),
+        &BTreeSet::new(),
+        &BTreeMap::new(),
+    );
+
+    assert_eq!(rendered.links.len(), 1);
+    let link = &rendered.links[0];
+    assert!(link.first_line() < link.last_line());
+
+    let text: Vec<String> = link
+        .segments
+        .iter()
+        .map(|(line, words)| {
+            rendered.lines[*line][words.clone()]
+                .iter()
+                .map(|word| {
+                    format!(
+                        "{}{}",
+                        word.content,
+                        " ".repeat(word.whitespace_width as usize)
+                    )
+                })
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        })
+        .collect();
+    assert_eq!(
+        text.join(" "),
+        "a link with a rather long anchor text that has to be wrapped over lines"
+    );
+}
+
+#[test]
+fn language_hints() {
+    let document = load_fixture("links");
+    let link = document
+        .nodes
+        .iter()
+        .position(|node| matches!(node.data, Data::WikiLink { .. }))
+        .unwrap();
+    let hints = BTreeMap::from([(link, "Lang und breit".to_string())]);
+    let rendered = render_document_with(
+        &document,
+        200,
+        &RendererConfig::default(),
+        &BTreeSet::new(),
+        &hints,
+    );
+
+    let text: String = rendered
+        .lines
+        .iter()
+        .flatten()
+        .map(|word| {
+            format!(
+                "{}{}",
+                word.content,
+                " ".repeat(word.whitespace_width as usize)
+            )
+        })
+        .collect();
+    assert!(text.contains("over lines (Lang und breit) and some text"));
+    // the hint is selected together with the link
+    assert_eq!(rendered.links.len(), 1);
+    let (line, words) = rendered.links[0].segments.last().unwrap();
+    assert_eq!(
+        rendered.lines[*line][words.clone()].last().unwrap().content,
+        "breit)"
+    );
+}
@@ -0,0 +1,161 @@
+//! Drives the whole app with key presses and checks what's drawn on the screen. The requests to
+//! the wiki are answered with the recorded responses in `tests/fixtures/replay`
+//!
+//! Recordings are HTTP Archives like the ones exported from the request log. A recording only
+//! needs the query parameters that tell its request apart from the others, see
+//! `wiki_tui::replay::find_recording`
+
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{backend::TestBackend, Terminal};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use wiki_tui::{
+    action::{Action, ActionResult},
+    app::AppComponent,
+    components::Component,
+    config::{CONFIG_ENV, DATA_ENV},
+    event::Event,
+    replay::replay_har,
+};
+
+const WIDTH: u16 = 100;
+const HEIGHT: u16 = 30;
+/// How long a text may take to be shown, the replayed requests answer right away
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The app with the loop of `main`, run step by step by the test
+struct App {
+    app: AppComponent,
+    terminal: Terminal<TestBackend>,
+    action_tx: UnboundedSender<Action>,
+    action_rx: UnboundedReceiver<Action>,
+}
+
+impl App {
+    /// Starts the app, answering its requests with the recording `name`. Every test file runs in
+    /// its own process, so the recording can only be chosen once per file
+    fn start(name: &str) -> Self {
+        // the config, history, bookmarks and page cache of the user are left alone and don't
+        // change what the app does
+        let data = std::env::temp_dir().join(format!("wiki-tui-replay-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&data);
+        std::env::set_var(DATA_ENV, &data);
+        std::env::set_var(CONFIG_ENV, &data);
+
+        let recording = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/replay")
+            .join(format!("{name}.har"));
+        replay_har(&recording).expect("unable to replay the recording");
+
+        let (action_tx, action_rx) = mpsc::unbounded_channel();
+        let mut app = AppComponent::default();
+        app.init(action_tx.clone())
+            .expect("unable to start the app");
+
+        Self {
+            app,
+            terminal: Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap(),
+            action_tx,
+            action_rx,
+        }
+    }
+
+    fn press(&mut self, code: KeyCode) {
+        self.press_with(code, KeyModifiers::NONE);
+    }
+
+    fn press_with(&mut self, code: KeyCode, modifiers: KeyModifiers) {
+        self.run_actions();
+        let event = Event::Key(KeyEvent::new(code, modifiers));
+        if let ActionResult::Consumed(actions) = self.app.handle_events(Some(event)) {
+            actions.send(&self.action_tx);
+        }
+        self.run_actions();
+    }
+
+    fn type_text(&mut self, text: &str) {
+        text.chars().for_each(|c| self.press(KeyCode::Char(c)));
+    }
+
+    /// Runs the actions that were sent so far, like the main loop does
+    fn run_actions(&mut self) {
+        while let Ok(action) = self.action_rx.try_recv() {
+            if matches!(action, Action::RenderTick | Action::Quit) {
+                continue;
+            }
+            if let ActionResult::Consumed(actions) = self.app.update(action) {
+                actions.send(&self.action_tx);
+            }
+        }
+    }
+
+    /// Draws the app and returns the lines on the screen
+    fn screen(&mut self) -> String {
+        let app = &mut self.app;
+        let buffer = self
+            .terminal
+            .draw(|f| app.render(f, f.size()))
+            .unwrap()
+            .buffer
+            .clone();
+
+        let mut screen = String::new();
+        for y in 0..buffer.area.height {
+            for x in 0..buffer.area.width {
+                screen.push_str(&buffer.get(x, y).symbol);
+            }
+            screen.push('\n');
+        }
+        screen
+    }
+
+    /// Waits until the screen shows the text while the requests are answered in the background
+    async fn wait_for(&mut self, text: &str) -> String {
+        let start = Instant::now();
+        loop {
+            self.run_actions();
+            let screen = self.screen();
+            if screen.contains(text) {
+                return screen;
+            }
+            if start.elapsed() > TIMEOUT {
+                panic!(
+                    "'{}' wasn't shown in time, the screen was:\n{}",
+                    text, screen
+                );
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn search_open_follow_link_and_go_back() {
+    let mut app = App::start("search_and_follow");
+
+    // the search bar is focussed right away
+    app.type_text("Rust");
+    app.press(KeyCode::Enter);
+    let screen = app.wait_for("Rust (programming language)").await;
+    assert!(screen.contains("is an iron oxide"));
+
+    // the first result is selected
+    app.press(KeyCode::Enter);
+    app.wait_for("is a programming language").await;
+
+    // the link is peeked first and opened from there
+    app.press_with(KeyCode::Left, KeyModifiers::SHIFT);
+    app.press_with(KeyCode::Enter, KeyModifiers::ALT);
+    app.wait_for("is a software community").await;
+    app.press(KeyCode::Enter);
+    let screen = app.wait_for("Mozilla is a software community").await;
+    assert!(!screen.contains("is a programming language"));
+
+    app.press(KeyCode::Esc);
+    let screen = app.wait_for("is a programming language").await;
+    assert!(!screen.contains("is a software community"));
+}
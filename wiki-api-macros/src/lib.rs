@@ -95,7 +95,7 @@ pub fn parse_languages(input: TokenStream) -> TokenStream {
         use serde::{Serialize, Deserialize};
 
         #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-        #[serde(from = "String")]
+        #[serde(from = "String", into = "String")]
         pub enum Language{
             #variants
         }
@@ -133,6 +133,12 @@ pub fn parse_languages(input: TokenStream) -> TokenStream {
             }
         }
 
+        impl From<Language> for String {
+            fn from(language: Language) -> Self {
+                language.code().to_string()
+            }
+        }
+
         impl From<String> for Language {
             fn from(s: String) -> Self {
                 match s.to_lowercase().as_ref() {
@@ -0,0 +1,37 @@
+//! Fetches a page from the English Wikipedia and prints its sections and links
+//!
+//! ```sh
+//! cargo run -p wiki-api --example fetch_page -- "Red fox"
+//! ```
+
+use anyhow::{Context, Result};
+use wiki_api::{
+    backend::{ActionBackend, PageBackend},
+    document::Data,
+    languages::Language,
+};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let title = std::env::args()
+        .nth(1)
+        .context("usage: fetch_page <title>")?;
+
+    let language = Language::English;
+    let backend = ActionBackend::new(language.wikipedia_endpoint());
+    let page = backend.fetch_page(&title, &language).await?;
+
+    println!("{}", page.title);
+    for section in page.sections.iter().flatten() {
+        println!("  {} {}", section.number, section.text);
+    }
+
+    let links = page
+        .content
+        .nodes
+        .iter()
+        .filter(|node| matches!(node.data, Data::WikiLink { .. }))
+        .count();
+    println!("{} links to other pages", links);
+    Ok(())
+}
@@ -0,0 +1,29 @@
+//! Parses a page saved as HTML and prints its taxobox as JSON, without any network access
+//!
+//! ```sh
+//! cargo run -p wiki-api --example parse_html -- page.html
+//! ```
+
+use anyhow::{Context, Result};
+use wiki_api::{
+    parser::parse_document,
+    taxobox::taxobox_groups,
+    transform::{apply_transforms, BuiltinTransform},
+};
+
+fn main() -> Result<()> {
+    let path = std::env::args()
+        .nth(1)
+        .context("usage: parse_html <file>")?;
+    let html =
+        std::fs::read_to_string(&path).with_context(|| format!("unable to read '{}'", path))?;
+
+    let document = apply_transforms(parse_document(&html), &BuiltinTransform::defaults());
+    println!("{} nodes", document.nodes.len());
+
+    match taxobox_groups(&document) {
+        Some(groups) => println!("{}", serde_json::to_string_pretty(&groups)?),
+        None => println!("the page has no taxobox"),
+    }
+    Ok(())
+}
@@ -0,0 +1,124 @@
+use anyhow::{Context, Result};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt::Display};
+
+use crate::{http::send, Endpoint, TITLES_PER_REQUEST};
+
+/// Quality rating of an article, based on the assessments of the wiki projects it belongs to
+///
+/// Only the highest ratings are represented, articles with a lower rating don't have a quality
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Quality {
+    Good,
+    FeaturedList,
+    Featured,
+}
+
+impl Quality {
+    /// Parses an assessment class like `FA` or `GA`
+    pub fn from_class(class: &str) -> Option<Quality> {
+        match class.trim().to_uppercase().as_str() {
+            "FA" => Some(Quality::Featured),
+            "FL" => Some(Quality::FeaturedList),
+            "GA" => Some(Quality::Good),
+            _ => None,
+        }
+    }
+
+    /// A short badge to display next to the title of an article
+    pub fn badge(&self) -> &'static str {
+        match self {
+            Quality::Featured => "★",
+            Quality::FeaturedList => "☆",
+            Quality::Good => "GA",
+        }
+    }
+}
+
+impl Display for Quality {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Quality::Featured => write!(f, "Featured article"),
+            Quality::FeaturedList => write!(f, "Featured list"),
+            Quality::Good => write!(f, "Good article"),
+        }
+    }
+}
+
+/// Fetches the quality of the given articles
+///
+/// Articles without a (high enough) rating are missing in the returned map. When the wiki doesn't
+/// assess its articles, the map is empty
+///
+/// # Error
+///
+/// This function returns an error when one of the following things happens:
+/// - The request to the server could not be made
+/// - The server returned an error
+/// - The returned result could not be interpreted as json
+pub async fn fetch_qualities(
+    endpoint: Endpoint,
+    titles: &[String],
+) -> Result<HashMap<String, Quality>> {
+    let mut qualities = HashMap::new();
+
+    for titles in titles.chunks(TITLES_PER_REQUEST) {
+        let response = send(
+            Client::new()
+                .get(endpoint.clone())
+                .query(&[
+                    ("action", "query"),
+                    ("format", "json"),
+                    ("formatversion", "2"),
+                    ("prop", "pageassessments|pageprops"),
+                    ("palimit", "max"),
+                ])
+                .query(&[("titles", titles.join("|"))]),
+        )
+        .await
+        .context("failed sending the request")?
+        .error_for_status()
+        .context("the server returned an error")?;
+
+        let res_json = response.api_json()?;
+
+        let pages = res_json
+            .get("query")
+            .and_then(|x| x.get("pages"))
+            .and_then(|x| x.as_array())
+            .cloned()
+            .unwrap_or_default();
+
+        for page in pages {
+            let title = match page.get("title").and_then(|x| x.as_str()) {
+                Some(title) => title.to_string(),
+                None => continue,
+            };
+
+            // disambiguation pages inherit the assessments of the projects but are never rated
+            if page
+                .get("pageprops")
+                .and_then(|x| x.get("disambiguation"))
+                .is_some()
+            {
+                continue;
+            }
+
+            let quality = page
+                .get("pageassessments")
+                .and_then(|x| x.as_object())
+                .into_iter()
+                .flat_map(|projects| projects.values())
+                .filter_map(|project| project.get("class").and_then(|x| x.as_str()))
+                .filter_map(Quality::from_class)
+                .max();
+
+            if let Some(quality) = quality {
+                qualities.insert(title, quality);
+            }
+        }
+    }
+
+    Ok(qualities)
+}
@@ -0,0 +1,645 @@
+use std::{future::Future, pin::Pin, time::Instant};
+
+use anyhow::{anyhow, Context, Result};
+use reqwest::Client;
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+    document::{Data, Document, HeaderKind},
+    http::send,
+    languages::Language,
+    page::{Page, Section},
+    parser::{parse_document, parse_document_with, ParserMode},
+    redirects::normalize_title,
+    Endpoint,
+};
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Short description of a page, without its content
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Summary {
+    pub title: String,
+    /// Short description of the page, e.g. "Programming language"
+    pub description: Option<String>,
+    /// Plain text of the introduction of the page
+    pub extract: String,
+}
+
+/// Fetches pages and their summaries from a wiki
+///
+/// Every backend talks to a different API of the wiki, but returns the same data
+pub trait PageBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Fetches the page with the title, following redirects
+    fn fetch_page<'a>(
+        &'a self,
+        title: &'a str,
+        language: &'a Language,
+    ) -> BoxFuture<'a, Result<Page>>;
+
+    /// Fetches the page with the title as it is, a redirect is returned instead of the page it
+    /// redirects to
+    fn fetch_page_without_redirects<'a>(
+        &'a self,
+        title: &'a str,
+        language: &'a Language,
+    ) -> BoxFuture<'a, Result<Page>>;
+
+    /// Fetches the summary of the page with the title, following redirects
+    fn fetch_summary<'a>(&'a self, title: &'a str) -> BoxFuture<'a, Result<Summary>>;
+}
+
+/// The backends that are shipped with wiki-api
+///
+/// These can be selected by name (in snake_case) from a configuration file
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BuiltinBackend {
+    /// The action API (`api.php`). See [`ActionBackend`]
+    #[default]
+    Action,
+    /// The MediaWiki REST API (`rest.php`) and the Wikimedia REST API (`rest_v1`). See
+    /// [`RestBackend`]
+    Rest,
+    /// The mobile-html output of the Wikimedia REST API (`rest_v1`). See [`MobileHtmlBackend`]
+    MobileHtml,
+}
+
+impl BuiltinBackend {
+    /// Creates the backend for the wiki with the action API `endpoint`
+    pub fn backend(self, endpoint: Endpoint) -> Result<Box<dyn PageBackend>> {
+        Ok(match self {
+            BuiltinBackend::Action => Box::new(ActionBackend::new(endpoint)),
+            BuiltinBackend::Rest => Box::new(RestBackend::from_action_endpoint(&endpoint)?),
+            BuiltinBackend::MobileHtml => {
+                Box::new(MobileHtmlBackend::from_action_endpoint(&endpoint)?)
+            }
+        })
+    }
+}
+
+/// Fetches pages using the action API (`/w/api.php`)
+pub struct ActionBackend {
+    endpoint: Endpoint,
+}
+
+impl ActionBackend {
+    pub fn new(endpoint: Endpoint) -> Self {
+        Self { endpoint }
+    }
+
+    async fn summary(&self, title: &str) -> Result<Summary> {
+        let response = send(Client::new().get(self.endpoint.clone()).query(&[
+            ("action", "query"),
+            ("format", "json"),
+            ("formatversion", "2"),
+            ("prop", "extracts|description"),
+            ("exintro", "1"),
+            ("explaintext", "1"),
+            ("redirects", "1"),
+            ("titles", title),
+        ]))
+        .await
+        .context("failed sending the request")?
+        .error_for_status()
+        .context("the server returned an error")?;
+
+        let res_json = response.api_json()?;
+        let page = res_json
+            .get("query")
+            .and_then(|x| x.get("pages"))
+            .and_then(|x| x.get(0))
+            .ok_or_else(|| anyhow!("missing the page"))?;
+        if page.get("missing").is_some() {
+            return Err(anyhow!("the page '{}' doesn't exist", title));
+        }
+
+        Ok(Summary {
+            title: page
+                .get("title")
+                .and_then(|x| x.as_str())
+                .unwrap_or(title)
+                .to_string(),
+            description: page
+                .get("description")
+                .and_then(|x| x.as_str())
+                .map(|x| x.to_string()),
+            extract: page
+                .get("extract")
+                .and_then(|x| x.as_str())
+                .unwrap_or_default()
+                .to_string(),
+        })
+    }
+}
+
+impl PageBackend for ActionBackend {
+    fn name(&self) -> &'static str {
+        "action"
+    }
+
+    fn fetch_page<'a>(
+        &'a self,
+        title: &'a str,
+        language: &'a Language,
+    ) -> BoxFuture<'a, Result<Page>> {
+        Box::pin(
+            Page::builder()
+                .page(title)
+                .endpoint(self.endpoint.clone())
+                .language(language.clone())
+                .redirects(true)
+                .fetch(),
+        )
+    }
+
+    fn fetch_page_without_redirects<'a>(
+        &'a self,
+        title: &'a str,
+        language: &'a Language,
+    ) -> BoxFuture<'a, Result<Page>> {
+        Box::pin(
+            Page::builder()
+                .page(title)
+                .endpoint(self.endpoint.clone())
+                .language(language.clone())
+                .fetch(),
+        )
+    }
+
+    fn fetch_summary<'a>(&'a self, title: &'a str) -> BoxFuture<'a, Result<Summary>> {
+        Box::pin(self.summary(title))
+    }
+}
+
+#[derive(Deserialize)]
+struct RestPage {
+    id: usize,
+    title: String,
+    latest: Option<RestRevision>,
+    html: String,
+}
+
+#[derive(Deserialize)]
+struct RestRevision {
+    id: usize,
+}
+
+#[derive(Deserialize)]
+struct RestSummary {
+    title: String,
+    description: Option<String>,
+    #[serde(default)]
+    extract: String,
+}
+
+/// Fetches pages using the MediaWiki REST API (`/w/rest.php`) and summaries using the Wikimedia
+/// REST API (`/api/rest_v1`)
+///
+/// The REST API doesn't return the language links of a page, so they are always missing
+pub struct RestBackend {
+    /// Root of the MediaWiki REST API, e.g. `https://en.wikipedia.org/w/rest.php/v1/`
+    rest_endpoint: Endpoint,
+    /// Root of the Wikimedia REST API, e.g. `https://en.wikipedia.org/api/rest_v1/`
+    rest_v1_endpoint: Endpoint,
+}
+
+impl RestBackend {
+    pub fn new(rest_endpoint: Endpoint, rest_v1_endpoint: Endpoint) -> Self {
+        Self {
+            rest_endpoint,
+            rest_v1_endpoint,
+        }
+    }
+
+    /// Derives the REST endpoints from the action API endpoint of the same wiki
+    ///
+    /// `https://en.wikipedia.org/w/api.php` becomes `https://en.wikipedia.org/w/rest.php/v1/` and
+    /// `https://en.wikipedia.org/api/rest_v1/`
+    pub fn from_action_endpoint(endpoint: &Endpoint) -> Result<Self> {
+        let rest_endpoint = endpoint
+            .join("rest.php/v1/")
+            .context("failed building the rest.php endpoint")?;
+        let rest_v1_endpoint = endpoint
+            .join("/api/rest_v1/")
+            .context("failed building the rest_v1 endpoint")?;
+        Ok(Self::new(rest_endpoint, rest_v1_endpoint))
+    }
+
+    /// Appends the path segments to the endpoint, escaping them (slashes in titles included)
+    fn url(endpoint: &Endpoint, segments: &[&str]) -> Result<Url> {
+        let mut url = endpoint.clone();
+        url.path_segments_mut()
+            .map_err(|_| anyhow!("the endpoint '{}' can't have a path", endpoint))?
+            .pop_if_empty()
+            .extend(segments);
+        Ok(url)
+    }
+
+    async fn get_json<T: for<'de> Deserialize<'de>>(url: Url) -> Result<T> {
+        let response = send(Client::new().get(url))
+            .await
+            .context("failed sending the request")?;
+
+        let text = response
+            .error_for_status()
+            .context("the server returned an error")?
+            .text();
+        serde_json::from_str(&text).context("failed interpreting the response as json")
+    }
+
+    /// Fetches the page, the server answers a redirect with the location of the page it
+    /// redirects to unless `follow_redirects` is false
+    async fn page(&self, title: &str, language: &Language, follow_redirects: bool) -> Result<Page> {
+        let mut url = Self::url(&self.rest_endpoint, &["page", title, "with_html"])?;
+        if !follow_redirects {
+            url.query_pairs_mut().append_pair("redirect", "no");
+        }
+        let page: RestPage = Self::get_json(url).await?;
+
+        // the redirects are followed by the client, only the title tells that there were any
+        let redirected_from = Some(title.to_string())
+            .filter(|title| normalize_title(title) != normalize_title(&page.title));
+
+        let is_disambiguation = page.html.contains("mw:PageProp/disambiguation");
+        let parse_start = Instant::now();
+        let content = parse_document(&page.html);
+        let parse_time = parse_start.elapsed();
+        let sections = sections_from_document(&content);
+
+        Ok(Page {
+            title: page.title,
+            pageid: page.id,
+            content,
+            language: language.clone(),
+            language_links: None,
+            sections: Some(sections),
+            revision_id: page.latest.map(|revision| revision.id),
+            is_disambiguation,
+            quality: None,
+            parse_time: Some(parse_time),
+            redirected_from,
+        })
+    }
+
+    async fn summary(&self, title: &str) -> Result<Summary> {
+        let url = Self::url(&self.rest_v1_endpoint, &["page", "summary", title])?;
+        let summary: RestSummary = Self::get_json(url).await?;
+        Ok(Summary {
+            title: summary.title,
+            description: summary.description,
+            extract: summary.extract,
+        })
+    }
+}
+
+impl PageBackend for RestBackend {
+    fn name(&self) -> &'static str {
+        "rest"
+    }
+
+    fn fetch_page<'a>(
+        &'a self,
+        title: &'a str,
+        language: &'a Language,
+    ) -> BoxFuture<'a, Result<Page>> {
+        Box::pin(self.page(title, language, true))
+    }
+
+    fn fetch_page_without_redirects<'a>(
+        &'a self,
+        title: &'a str,
+        language: &'a Language,
+    ) -> BoxFuture<'a, Result<Page>> {
+        Box::pin(self.page(title, language, false))
+    }
+
+    fn fetch_summary<'a>(&'a self, title: &'a str) -> BoxFuture<'a, Result<Summary>> {
+        Box::pin(self.summary(title))
+    }
+}
+
+/// Metadata of a page that the mobile-html output stores in its head
+#[derive(Debug, Default, PartialEq, Eq)]
+struct MobileMetadata {
+    title: Option<String>,
+    pageid: Option<usize>,
+    revision_id: Option<usize>,
+}
+
+impl MobileMetadata {
+    fn from_html(html: &str) -> Self {
+        let document = Html::parse_document(html);
+        let select = |selector: &str| {
+            let selector = Selector::parse(selector).expect("the selector is valid");
+            document.select(&selector).next()
+        };
+
+        let title = select("title")
+            .map(|title| title.text().collect::<String>())
+            .filter(|title| !title.trim().is_empty());
+        let pageid = select(r#"meta[property="mw:pageId"]"#)
+            .and_then(|meta| meta.value().attr("content"))
+            .and_then(|id| id.parse().ok());
+        // e.g. `https://en.wikipedia.org/wiki/Special:Redirect/revision/1180000000`
+        let revision_id = select("html")
+            .and_then(|html| html.value().attr("about"))
+            .and_then(|about| about.rsplit_once("revision/"))
+            .and_then(|(_, id)| id.parse().ok());
+
+        Self {
+            title,
+            pageid,
+            revision_id,
+        }
+    }
+}
+
+/// Fetches pages using the mobile-html output of the Wikimedia REST API (`/api/rest_v1`), which
+/// the Wikipedia apps display, and summaries using the same API
+///
+/// The mobile-html output is parsed with [`ParserMode::MobileHtml`]. Like the REST API, it
+/// doesn't contain the language links of a page, so they are always missing
+pub struct MobileHtmlBackend {
+    /// Root of the Wikimedia REST API, e.g. `https://en.wikipedia.org/api/rest_v1/`
+    rest_v1_endpoint: Endpoint,
+}
+
+impl MobileHtmlBackend {
+    pub fn new(rest_v1_endpoint: Endpoint) -> Self {
+        Self { rest_v1_endpoint }
+    }
+
+    /// Derives the REST endpoint from the action API endpoint of the same wiki
+    ///
+    /// `https://en.wikipedia.org/w/api.php` becomes `https://en.wikipedia.org/api/rest_v1/`
+    pub fn from_action_endpoint(endpoint: &Endpoint) -> Result<Self> {
+        let rest_v1_endpoint = endpoint
+            .join("/api/rest_v1/")
+            .context("failed building the rest_v1 endpoint")?;
+        Ok(Self::new(rest_v1_endpoint))
+    }
+
+    /// Fetches the page, the server answers a redirect with the location of the page it
+    /// redirects to unless `follow_redirects` is false
+    async fn page(&self, title: &str, language: &Language, follow_redirects: bool) -> Result<Page> {
+        let mut url = RestBackend::url(&self.rest_v1_endpoint, &["page", "mobile-html", title])?;
+        if !follow_redirects {
+            url.query_pairs_mut().append_pair("redirect", "false");
+        }
+        let html = send(Client::new().get(url))
+            .await
+            .context("failed sending the request")?
+            .error_for_status()
+            .context("the server returned an error")?
+            .text();
+
+        let metadata = MobileMetadata::from_html(&html);
+        let page_title = metadata.title.unwrap_or_else(|| normalize_title(title));
+        let redirected_from = Some(title.to_string())
+            .filter(|title| normalize_title(title) != normalize_title(&page_title));
+
+        let is_disambiguation = html.contains("mw:PageProp/disambiguation");
+        let parse_start = Instant::now();
+        let content = parse_document_with(&html, ParserMode::MobileHtml);
+        let parse_time = parse_start.elapsed();
+        let sections = sections_from_document(&content);
+
+        Ok(Page {
+            title: page_title,
+            pageid: metadata
+                .pageid
+                .ok_or_else(|| anyhow!("missing the id of the page '{}'", title))?,
+            content,
+            language: language.clone(),
+            language_links: None,
+            sections: Some(sections),
+            revision_id: metadata.revision_id,
+            is_disambiguation,
+            quality: None,
+            parse_time: Some(parse_time),
+            redirected_from,
+        })
+    }
+
+    async fn summary(&self, title: &str) -> Result<Summary> {
+        let url = RestBackend::url(&self.rest_v1_endpoint, &["page", "summary", title])?;
+        let summary: RestSummary = RestBackend::get_json(url).await?;
+        Ok(Summary {
+            title: summary.title,
+            description: summary.description,
+            extract: summary.extract,
+        })
+    }
+}
+
+impl PageBackend for MobileHtmlBackend {
+    fn name(&self) -> &'static str {
+        "mobile_html"
+    }
+
+    fn fetch_page<'a>(
+        &'a self,
+        title: &'a str,
+        language: &'a Language,
+    ) -> BoxFuture<'a, Result<Page>> {
+        Box::pin(self.page(title, language, true))
+    }
+
+    fn fetch_page_without_redirects<'a>(
+        &'a self,
+        title: &'a str,
+        language: &'a Language,
+    ) -> BoxFuture<'a, Result<Page>> {
+        Box::pin(self.page(title, language, false))
+    }
+
+    fn fetch_summary<'a>(&'a self, title: &'a str) -> BoxFuture<'a, Result<Summary>> {
+        Box::pin(self.summary(title))
+    }
+}
+
+/// Builds the table of contents from the headers of the document, numbered like the one
+/// returned by the action API
+///
+/// The index of a section is the position of its header, which is what Parsoid uses as the id
+/// of the section
+pub fn sections_from_document(document: &Document) -> Vec<Section> {
+    let mut sections = vec![Section {
+        index: 0,
+        header_kind: HeaderKind::Main,
+        text: "(Top)".to_string(),
+        number: "".to_string(),
+        anchor: "Content_Top".to_string(),
+    }];
+    let mut counters = [0usize; 5];
+
+    for raw in document.nodes.iter() {
+        let (id, kind) = match raw.data {
+            Data::Header { ref id, ref kind } => (id, kind),
+            _ => continue,
+        };
+        // the table of contents starts with the second header level
+        let (level, header_kind) = match kind {
+            HeaderKind::Main => continue,
+            HeaderKind::Sub => (0, HeaderKind::Main),
+            HeaderKind::Section => (1, HeaderKind::Sub),
+            HeaderKind::Subsection => (2, HeaderKind::Section),
+            HeaderKind::Minor => (3, HeaderKind::Subsection),
+            HeaderKind::Detail => (4, HeaderKind::Minor),
+        };
+
+        counters[level] += 1;
+        counters.iter_mut().skip(level + 1).for_each(|n| *n = 0);
+        let number = counters[..=level]
+            .iter()
+            .filter(|n| **n != 0)
+            .map(|n| n.to_string())
+            .collect::<Vec<String>>()
+            .join(".");
+
+        let text: String = document
+            .nth(raw.index)
+            .map(|header| {
+                header
+                    .descendants()
+                    .filter_map(|node| match node.data() {
+                        Data::Text { contents } => Some(contents.as_str()),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        sections.push(Section {
+            index: sections.len(),
+            header_kind,
+            text: text.split_whitespace().collect::<Vec<&str>>().join(" "),
+            number,
+            anchor: id.clone(),
+        });
+    }
+
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sections_from_document, MobileMetadata, RestBackend};
+    use crate::{
+        document::{Document, HeaderKind},
+        parser::{parse_document_with, Parser, ParserMode, WikipediaParser},
+        Endpoint,
+    };
+
+    #[test]
+    fn rest_urls() {
+        let backend = RestBackend::from_action_endpoint(
+            &Endpoint::parse("https://en.wikipedia.org/w/api.php").unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            backend.rest_endpoint.as_str(),
+            "https://en.wikipedia.org/w/rest.php/v1/"
+        );
+        assert_eq!(
+            RestBackend::url(&backend.rest_endpoint, &["page", "AC/DC", "with_html"])
+                .unwrap()
+                .as_str(),
+            "https://en.wikipedia.org/w/rest.php/v1/page/AC%2FDC/with_html"
+        );
+        assert_eq!(
+            RestBackend::url(&backend.rest_v1_endpoint, &["page", "summary", "Rust"])
+                .unwrap()
+                .as_str(),
+            "https://en.wikipedia.org/api/rest_v1/page/summary/Rust"
+        );
+    }
+
+    #[test]
+    fn sections() {
+        let document = Document {
+            nodes: WikipediaParser::parse_document(
+                r#"<section data-mw-section-id="0"><p>Intro</p></section><section data-mw-section-id="1"><h2 id="History">History</h2><section data-mw-section-id="2"><h3 id="Early_days">Early <i>days</i></h3></section></section><section data-mw-section-id="3"><h2 id="See_also">See also</h2></section>"#,
+            )
+            .nodes(),
+        };
+
+        let sections: Vec<(usize, HeaderKind, String, String, String)> =
+            sections_from_document(&document)
+                .into_iter()
+                .map(|section| {
+                    (
+                        section.index,
+                        section.header_kind,
+                        section.text,
+                        section.number,
+                        section.anchor,
+                    )
+                })
+                .collect();
+        assert_eq!(
+            sections,
+            vec![
+                (
+                    0,
+                    HeaderKind::Main,
+                    "(Top)".to_string(),
+                    "".to_string(),
+                    "Content_Top".to_string()
+                ),
+                (
+                    1,
+                    HeaderKind::Main,
+                    "History".to_string(),
+                    "1".to_string(),
+                    "History".to_string()
+                ),
+                (
+                    2,
+                    HeaderKind::Sub,
+                    "Early days".to_string(),
+                    "1.1".to_string(),
+                    "Early_days".to_string()
+                ),
+                (
+                    3,
+                    HeaderKind::Main,
+                    "See also".to_string(),
+                    "2".to_string(),
+                    "See_also".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn mobile_html() {
+        let html = r#"<!DOCTYPE html><html about="https://en.wikipedia.org/wiki/Special:Redirect/revision/1180000000"><head><title>Rust</title><meta property="mw:pageId" content="29414838"></head><body><div id="pcs"><header><h1>Rust</h1><p class="pcs-edit-section-title-description">Programming language</p></header><section data-mw-section-id="0"><p>Intro</p></section><section data-mw-section-id="1"><div class="pcs-edit-section-header v2"><h2 id="History">History</h2><span class="pcs-edit-section-link-container"><a href="/w/index.php?action=edit&section=1">edit</a></span></div></section><section data-mw-section-id="2"><div class="pcs-edit-section-header v2"><h3 id="Early_days">Early <i>days</i></h3></div><span class="pcs-lazy-load-placeholder pcs-lazy-load-placeholder-pending" data-src="//upload.wikimedia.org/Rust.png"></span></section><section data-mw-section-id="3"><div class="pcs-edit-section-header v2"><h2 id="See_also">See also</h2></div></section></div></body></html>"#;
+
+        assert_eq!(
+            MobileMetadata::from_html(html),
+            MobileMetadata {
+                title: Some("Rust".to_string()),
+                pageid: Some(29414838),
+                revision_id: Some(1180000000),
+            }
+        );
+
+        // the sections are nested like in the Parsoid HTML, without the mobile additions
+        let mobile = parse_document_with(html, ParserMode::MobileHtml);
+        let parsoid = parse_document_with(
+            r#"<section data-mw-section-id="0"><p>Intro</p></section><section data-mw-section-id="1"><h2 id="History">History</h2><section data-mw-section-id="2"><h3 id="Early_days">Early <i>days</i></h3></section></section><section data-mw-section-id="3"><h2 id="See_also">See also</h2></section>"#,
+            ParserMode::Parsoid,
+        );
+        assert_eq!(
+            sections_from_document(&mobile),
+            sections_from_document(&parsoid)
+        );
+        assert_eq!(mobile, parsoid);
+    }
+}
@@ -0,0 +1,123 @@
+use anyhow::{Context, Result};
+use reqwest::Client;
+
+use crate::{http::send, search::Namespace, Endpoint};
+
+/// Prefix of the canonical name of a category, which every wiki accepts in addition to its
+/// localized one
+const CATEGORY_PREFIX: &str = "Category:";
+
+/// Returns the full title of the category, adding the namespace when it's missing
+fn category_title(category: &str) -> String {
+    let category = category.trim();
+    if category
+        .to_lowercase()
+        .starts_with(&CATEGORY_PREFIX.to_lowercase())
+    {
+        category.to_string()
+    } else {
+        format!("{}{}", CATEGORY_PREFIX, category)
+    }
+}
+
+/// Returns the titles of the members listed in a query response
+fn parse_members(response: &serde_json::Value) -> Vec<String> {
+    response
+        .get("query")
+        .and_then(|x| x.get("categorymembers"))
+        .and_then(|x| x.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|member| member.get("title").and_then(|x| x.as_str()))
+        .map(|x| x.to_string())
+        .collect()
+}
+
+/// Fetches the titles of the articles in the category, sorted like the category page sorts
+/// them
+///
+/// The category can be given with or without its namespace. Only articles in the main namespace
+/// are returned, at most `limit` of them
+///
+/// # Error
+///
+/// This function returns an error when one of the following things happens:
+/// - The request to the server could not be made
+/// - The server returned an error
+/// - The returned result could not be interpreted as json
+pub async fn fetch_category_members(
+    endpoint: Endpoint,
+    category: &str,
+    limit: usize,
+) -> Result<Vec<String>> {
+    let mut members = Vec::new();
+    let mut continue_from: Option<String> = None;
+
+    loop {
+        let mut params = vec![
+            ("cmtitle", category_title(category)),
+            ("cmnamespace", (Namespace::Main as usize).to_string()),
+            ("cmlimit", "max".to_string()),
+        ];
+        if let Some(continue_from) = continue_from.take() {
+            params.push(("cmcontinue", continue_from));
+        }
+
+        let response = send(
+            Client::new()
+                .get(endpoint.clone())
+                .query(&[
+                    ("action", "query"),
+                    ("format", "json"),
+                    ("formatversion", "2"),
+                    ("list", "categorymembers"),
+                ])
+                .query(&params),
+        )
+        .await
+        .context("failed sending the request")?
+        .error_for_status()
+        .context("the server returned an error")?;
+
+        let res_json = response.api_json()?;
+
+        members.extend(parse_members(&res_json));
+
+        continue_from = res_json
+            .get("continue")
+            .and_then(|x| x.get("cmcontinue"))
+            .and_then(|x| x.as_str())
+            .map(|x| x.to_string());
+
+        if continue_from.is_none() || members.len() >= limit {
+            break;
+        }
+    }
+
+    members.truncate(limit);
+    Ok(members)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{category_title, parse_members};
+
+    #[test]
+    fn members() {
+        assert_eq!(
+            category_title(" Rivers of Germany"),
+            "Category:Rivers of Germany"
+        );
+        assert_eq!(category_title("category:Lakes"), "category:Lakes");
+
+        let response = serde_json::json!({
+            "query": {
+                "categorymembers": [
+                    { "pageid": 1, "ns": 0, "title": "Rhine" },
+                    { "pageid": 2, "ns": 0, "title": "Danube" }
+                ]
+            }
+        });
+        assert_eq!(parse_members(&response), ["Rhine", "Danube"]);
+    }
+}
@@ -0,0 +1,399 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    sync::{Mutex, MutexGuard, OnceLock},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tracing::{debug, warn};
+use url::Url;
+
+type Observer = Box<dyn Fn(&CookieJar) + Send + Sync>;
+
+static JAR: Mutex<CookieJar> = Mutex::new(CookieJar::new());
+static OBSERVER: OnceLock<Observer> = OnceLock::new();
+
+/// Names of the months in the dates of the `Expires` attribute
+const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+/// A cookie set by a wiki
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "StoredCookie")]
+pub struct Cookie {
+    value: String,
+    /// Unix timestamp of when the cookie expires, `None` for a cookie of the session
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expires: Option<i64>,
+    /// Whether the cookie is only sent over HTTPS
+    secure: bool,
+}
+
+/// A stored cookie, in the current format or as the bare value it was stored as before the
+/// expiry date and the `Secure` attribute were kept
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredCookie {
+    Value(String),
+    Cookie {
+        value: String,
+        #[serde(default)]
+        expires: Option<i64>,
+        #[serde(default)]
+        secure: bool,
+    },
+}
+
+impl From<StoredCookie> for Cookie {
+    fn from(cookie: StoredCookie) -> Self {
+        match cookie {
+            StoredCookie::Value(value) => Cookie {
+                value,
+                expires: None,
+                secure: false,
+            },
+            StoredCookie::Cookie {
+                value,
+                expires,
+                secure,
+            } => Cookie {
+                value,
+                expires,
+                secure,
+            },
+        }
+    }
+}
+
+impl Cookie {
+    fn is_expired(&self, now: i64) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+}
+
+/// Returns the current unix timestamp
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+/// Returns the days between the unix epoch and the date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Parses the date of an `Expires` attribute into a unix timestamp. Wikis send dates like
+/// `Sat, 14 Nov 2026 10:00:00 GMT` or `Sat, 14-Nov-2026 10:00:00 GMT`
+fn parse_expires(date: &str) -> Option<i64> {
+    let date = date.split_once(',').map_or(date, |(_, date)| date);
+    let parts: Vec<&str> = date
+        .split([' ', '-'])
+        .filter(|part| !part.is_empty())
+        .collect();
+    let (day, month, year, time) = match parts.as_slice() {
+        [day, month, year, time, ..] => (day, month, year, time),
+        _ => return None,
+    };
+
+    let day: i64 = day.parse().ok().filter(|day| (1..=31).contains(day))?;
+    let month = MONTHS
+        .iter()
+        .position(|name| month.to_lowercase().starts_with(name))? as i64
+        + 1;
+    let year = match year.parse::<i64>().ok()? {
+        // two digit years, like in `Thu, 01-Jan-70`
+        year @ 0..=69 => year + 2000,
+        year @ 70..=99 => year + 1900,
+        year => year,
+    };
+    let mut time = time.split(':').map(|part| part.parse::<i64>().ok());
+    let (hours, minutes, seconds) = (time.next()??, time.next()??, time.next()??);
+
+    Some(days_from_civil(year, month, day) * 86400 + hours * 3600 + minutes * 60 + seconds)
+}
+
+/// Cookies set by the wikis, grouped by the host that set them
+///
+/// Every cookie is sent back to the host that set it, no matter its path. Domain attributes are
+/// ignored, so a cookie is never shared between the hosts of a domain. Cookies are dropped once
+/// their `Expires` date or `Max-Age` passed, cookies without either are kept as well, which keeps
+/// a login to a wiki alive until the wiki ends it. Cookies with the `Secure` attribute are only
+/// sent over HTTPS
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CookieJar {
+    hosts: BTreeMap<String, BTreeMap<String, Cookie>>,
+}
+
+impl CookieJar {
+    pub const fn new() -> Self {
+        Self {
+            hosts: BTreeMap::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hosts.is_empty()
+    }
+
+    /// Returns the value of the `Cookie` header for a request to the url, `None` when the host
+    /// of the url didn't set any cookies that can be sent to it
+    pub fn header(&self, url: &Url) -> Option<String> {
+        self.header_at(url, unix_now())
+    }
+
+    fn header_at(&self, url: &Url, now: i64) -> Option<String> {
+        let is_https = url.scheme() == "https";
+        let header: Vec<String> = self
+            .hosts
+            .get(url.host_str()?)?
+            .iter()
+            .filter(|(_, cookie)| !cookie.is_expired(now) && (is_https || !cookie.secure))
+            .map(|(name, cookie)| format!("{name}={}", cookie.value))
+            .collect();
+        (!header.is_empty()).then(|| header.join("; "))
+    }
+
+    /// Stores the cookie of a `Set-Cookie` header sent by the url. A cookie without a value or
+    /// whose `Max-Age` or `Expires` date already passed is removed instead. Returns whether the
+    /// jar changed
+    pub fn store(&mut self, url: &Url, set_cookie: &str) -> bool {
+        self.store_at(url, set_cookie, unix_now())
+    }
+
+    fn store_at(&mut self, url: &Url, set_cookie: &str, now: i64) -> bool {
+        let host = match url.host_str() {
+            Some(host) => host,
+            None => return false,
+        };
+
+        let mut parts = set_cookie.split(';');
+        let (name, value) = match parts.next().and_then(|pair| pair.split_once('=')) {
+            Some((name, value)) if !name.trim().is_empty() => (name.trim(), value.trim()),
+            _ => {
+                warn!("ignoring the invalid cookie '{set_cookie}' of '{host}'");
+                return false;
+            }
+        };
+
+        let mut max_age = None;
+        let mut expires = None;
+        let mut secure = false;
+        for attribute in parts {
+            let (key, attribute_value) = attribute.split_once('=').unwrap_or((attribute, ""));
+            let key = key.trim();
+            if key.eq_ignore_ascii_case("max-age") {
+                max_age = attribute_value.trim().parse::<i64>().ok();
+            } else if key.eq_ignore_ascii_case("expires") {
+                expires = parse_expires(attribute_value.trim());
+            } else if key.eq_ignore_ascii_case("secure") {
+                secure = true;
+            }
+        }
+        if secure && url.scheme() != "https" {
+            warn!("ignoring the secure cookie '{name}' that '{host}' set without HTTPS");
+            return false;
+        }
+
+        // `Max-Age` takes precedence over `Expires`
+        let cookie = Cookie {
+            value: value.to_string(),
+            expires: max_age
+                .map(|max_age| now.saturating_add(max_age))
+                .or(expires),
+            secure,
+        };
+
+        let cookies = self.hosts.entry(host.to_string()).or_default();
+        let count = cookies.len();
+        cookies.retain(|_, cookie| !cookie.is_expired(now));
+        let mut changed = cookies.len() != count;
+
+        changed |= if value.is_empty() || cookie.is_expired(now) {
+            cookies.remove(name).is_some()
+        } else {
+            cookies.insert(name.to_string(), cookie.clone()) != Some(cookie)
+        };
+        if cookies.is_empty() {
+            self.hosts.remove(host);
+        }
+        changed
+    }
+}
+
+fn jar() -> MutexGuard<'static, CookieJar> {
+    JAR.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Replaces the cookies sent with the requests of this crate, like the ones stored when the
+/// last session ended
+pub fn load_cookies(cookies: CookieJar) {
+    *jar() = cookies;
+}
+
+/// Returns the cookies the wikis have set so far
+pub fn cookies() -> CookieJar {
+    jar().clone()
+}
+
+/// Calls the function with all cookies whenever a wiki changed one of them, which is useful to
+/// store them for the next session. Only the first observer is used, setting another one
+/// afterwards is ignored
+pub fn set_cookie_observer(observer: impl Fn(&CookieJar) + Send + Sync + 'static) {
+    if OBSERVER.set(Box::new(observer)).is_err() {
+        warn!("an observer of the cookies was already set");
+    }
+}
+
+/// Returns the value of the `Cookie` header for a request to the url
+pub(crate) fn cookie_header(url: &Url) -> Option<String> {
+    jar().header(url)
+}
+
+/// Stores the cookies set by the response headers of the url
+pub(crate) fn store_cookies(url: &Url, headers: &[(String, String)]) {
+    let mut jar = jar();
+    let mut changed = false;
+    for (_, set_cookie) in headers
+        .iter()
+        .filter(|(name, _)| name.eq_ignore_ascii_case("set-cookie"))
+    {
+        changed |= jar.store(url, set_cookie);
+    }
+
+    if !changed {
+        return;
+    }
+    debug!(
+        "'{}' changed its cookies",
+        url.host_str().unwrap_or_default()
+    );
+    if let Some(observer) = OBSERVER.get() {
+        observer(&jar);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use super::{parse_expires, CookieJar};
+
+    /// Sat, 17 Oct 2026 00:00:00 GMT
+    const NOW: i64 = 1_792_195_200;
+
+    #[test]
+    fn store_and_send() {
+        let wiki = Url::parse("https://wiki.example.org/w/api.php").unwrap();
+        let other = Url::parse("https://en.wikipedia.org/w/api.php").unwrap();
+        let mut jar = CookieJar::new();
+
+        assert!(jar.store_at(
+            &wiki,
+            "wikiSession=abc123; path=/; secure; HttpOnly; SameSite=Lax",
+            NOW
+        ));
+        assert!(jar.store_at(
+            &wiki,
+            "wikiUserName=Reader; expires=Sat, 14-Nov-2026 10:00:00 GMT; Max-Age=2592000",
+            NOW
+        ));
+        assert!(!jar.store_at(&wiki, "wikiSession=abc123; path=/; secure", NOW));
+        assert!(!jar.store_at(&wiki, "invalid", NOW));
+
+        assert_eq!(
+            jar.header_at(&wiki, NOW).as_deref(),
+            Some("wikiSession=abc123; wikiUserName=Reader")
+        );
+        assert_eq!(jar.header_at(&other, NOW), None);
+
+        assert!(jar.store_at(
+            &wiki,
+            "wikiSession=deleted; expires=Thu, 01-Jan-1970 00:00:01 GMT; Max-Age=0",
+            NOW
+        ));
+        assert!(jar.store_at(&wiki, "wikiUserName=", NOW));
+        assert!(jar.is_empty());
+    }
+
+    #[test]
+    fn expiry() {
+        let wiki = Url::parse("https://wiki.example.org/w/api.php").unwrap();
+        let mut jar = CookieJar::new();
+
+        // `Expires` without `Max-Age`
+        assert!(jar.store_at(
+            &wiki,
+            "wikiUserName=Reader; expires=Sun, 18 Oct 2026 00:00:00 GMT",
+            NOW
+        ));
+        assert!(jar.store_at(&wiki, "session=1; Max-Age=60", NOW));
+        assert!(!jar.store_at(&wiki, "old=1; expires=Fri, 16-Oct-2026 00:00:00 GMT", NOW));
+        assert_eq!(
+            jar.header_at(&wiki, NOW).as_deref(),
+            Some("session=1; wikiUserName=Reader")
+        );
+
+        // expired cookies aren't sent, and are dropped once the jar changes
+        assert_eq!(
+            jar.header_at(&wiki, NOW + 120).as_deref(),
+            Some("wikiUserName=Reader")
+        );
+        assert_eq!(jar.header_at(&wiki, NOW + 86400), None);
+        assert!(jar.store_at(&wiki, "wikiUserName=", NOW + 120));
+        assert!(jar.is_empty());
+    }
+
+    #[test]
+    fn secure() {
+        let https = Url::parse("https://wiki.example.org/w/api.php").unwrap();
+        let http = Url::parse("http://wiki.example.org/w/api.php").unwrap();
+        let mut jar = CookieJar::new();
+
+        assert!(jar.store_at(&https, "session=1; Secure", NOW));
+        assert!(jar.store_at(&https, "lang=de", NOW));
+        assert_eq!(jar.header_at(&http, NOW).as_deref(), Some("lang=de"));
+        assert_eq!(
+            jar.header_at(&https, NOW).as_deref(),
+            Some("lang=de; session=1")
+        );
+
+        // a secure cookie can't be set without HTTPS
+        assert!(!jar.store_at(&http, "token=1; secure", NOW));
+    }
+
+    #[test]
+    fn expires_dates() {
+        assert_eq!(parse_expires("Sat, 17 Oct 2026 00:00:00 GMT"), Some(NOW));
+        assert_eq!(parse_expires("Sat, 17-Oct-2026 00:00:00 GMT"), Some(NOW));
+        assert_eq!(parse_expires("Thu, 01-Jan-1970 00:00:01 GMT"), Some(1));
+        assert_eq!(parse_expires("Thu, 01-Jan-70 00:00:01 GMT"), Some(1));
+        assert_eq!(
+            parse_expires("Tue, 29 Feb 2028 12:30:15 GMT"),
+            Some(1_835_440_215)
+        );
+        assert_eq!(parse_expires("tomorrow"), None);
+        assert_eq!(parse_expires("Sat, 17 Foo 2026 00:00:00 GMT"), None);
+    }
+
+    #[test]
+    fn stored_cookies() {
+        // cookies stored before their expiry was kept are bare values
+        let jar: CookieJar =
+            serde_json::from_str(r#"{"wiki.example.org": {"session": "abc"}}"#).unwrap();
+        let wiki = Url::parse("https://wiki.example.org/w/api.php").unwrap();
+        assert_eq!(jar.header_at(&wiki, NOW).as_deref(), Some("session=abc"));
+
+        let mut jar = CookieJar::new();
+        jar.store_at(&wiki, "session=abc; Max-Age=60; Secure", NOW);
+        let stored = serde_json::to_string(&jar).unwrap();
+        assert_eq!(serde_json::from_str::<CookieJar>(&stored).unwrap(), jar);
+    }
+}
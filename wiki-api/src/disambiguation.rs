@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+
+use crate::document::{Data, Document, Node};
+
+/// A page listed on a disambiguation page
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisambiguationTarget {
+    /// Title of the listed page
+    pub title: String,
+    /// Text of the list entry without the link, can be empty
+    pub description: String,
+}
+
+/// Returns whether the node belongs to the list item itself and not to a list nested in it
+fn is_in_item(node: Node<'_>, item: Node<'_>) -> bool {
+    let mut current = node.parent();
+    while let Some(parent) = current {
+        if parent.index() == item.index() {
+            return true;
+        }
+        if matches!(parent.data(), Data::ListItem) {
+            return false;
+        }
+        current = parent.parent();
+    }
+    false
+}
+
+/// Concatenates the text of the nodes, collapsing whitespace
+fn text_of<'a>(nodes: impl Iterator<Item = Node<'a>>) -> String {
+    let text: String = nodes
+        .filter_map(|node| match node.data() {
+            Data::Text { contents } => Some(contents.as_str()),
+            _ => None,
+        })
+        .collect();
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// Collects the pages listed on a disambiguation page, in the order they appear
+///
+/// Every list entry that contains a link to an article is a target. Its first link is the page
+/// and the rest of the entry the description
+pub fn disambiguation_targets(document: &Document) -> Vec<DisambiguationTarget> {
+    let mut targets: Vec<DisambiguationTarget> = Vec::new();
+
+    for raw in document.nodes.iter() {
+        if !matches!(raw.data, Data::ListItem) {
+            continue;
+        }
+        let item = match document.nth(raw.index) {
+            Some(item) => item,
+            None => continue,
+        };
+
+        let link = item
+            .descendants()
+            .find(|node| matches!(node.data(), Data::WikiLink { .. }) && is_in_item(*node, item));
+        let (link, title) = match link.map(|link| (link, link.data())) {
+            Some((link, Data::WikiLink { href, title })) => {
+                let title = title
+                    .clone()
+                    .unwrap_or_else(|| href.trim_start_matches("./").replace('_', " ").to_string());
+                (link, title)
+            }
+            _ => continue,
+        };
+        if targets.iter().any(|target| target.title == title) {
+            continue;
+        }
+
+        let link_text = text_of(link.descendants());
+        let text = text_of(item.descendants().filter(|node| is_in_item(*node, item)));
+        let description = text
+            .replacen(&link_text, "", 1)
+            .trim_start_matches(|c: char| c.is_whitespace() || ",;:–—-".contains(c))
+            .trim_end()
+            .to_string();
+
+        targets.push(DisambiguationTarget { title, description });
+    }
+
+    targets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{disambiguation_targets, DisambiguationTarget};
+    use crate::{
+        document::Document,
+        parser::{Parser, WikipediaParser},
+    };
+
+    #[test]
+    fn targets() {
+        let document = Document {
+            nodes: WikipediaParser::parse_document(
+                r#"<p><b>Mercury</b> may refer to:</p><ul><li><a rel="mw:WikiLink" href="./Mercury_(planet)" title="Mercury (planet)">Mercury (planet)</a>, the closest planet to the Sun<ul><li><a rel="mw:WikiLink" href="./Transit_of_Mercury" title="Transit of Mercury">Transit of Mercury</a></li></ul></li><li>No link here</li><li><a rel="mw:WikiLink" href="./Mercury_(element)" title="Mercury (element)">Mercury</a> (element), a chemical element</li></ul>"#,
+            )
+            .nodes(),
+        };
+
+        assert_eq!(
+            disambiguation_targets(&document),
+            vec![
+                DisambiguationTarget {
+                    title: "Mercury (planet)".to_string(),
+                    description: "the closest planet to the Sun".to_string(),
+                },
+                DisambiguationTarget {
+                    title: "Transit of Mercury".to_string(),
+                    description: "".to_string(),
+                },
+                DisambiguationTarget {
+                    title: "Mercury (element)".to_string(),
+                    description: "(element), a chemical element".to_string(),
+                },
+            ]
+        );
+    }
+}
@@ -1,6 +1,7 @@
-use serde_repr::Deserialize_repr;
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Document {
     pub nodes: Vec<Raw>,
 }
@@ -15,9 +16,79 @@ impl Document {
     pub fn nth(&self, n: usize) -> Option<Node> {
         Node::new(self, n)
     }
+
+    /// Rebuilds the document by mapping the data of every node
+    ///
+    /// When `f` returns `None` for a node, that node and all of its descendants are removed from
+    /// the document. The indices of the remaining nodes are reassigned.
+    pub fn filter_map(&self, mut f: impl FnMut(Node<'_>) -> Option<Data>) -> Document {
+        fn copy_node(
+            nodes: &mut Vec<Raw>,
+            node: Node<'_>,
+            parent: Option<usize>,
+            prev: Option<usize>,
+            f: &mut impl FnMut(Node<'_>) -> Option<Data>,
+        ) -> Option<usize> {
+            let data = match f(node) {
+                Some(data) => data,
+                None => return prev,
+            };
+
+            let index = push_node(nodes, data, parent, prev);
+            let mut prev = None;
+            for child in node.children() {
+                prev = copy_node(nodes, child, Some(index), prev, f);
+            }
+            Some(index)
+        }
+
+        let mut nodes = Vec::new();
+        let mut prev = None;
+        let mut root = self.nth(0);
+        while let Some(node) = root {
+            prev = copy_node(&mut nodes, node, None, prev, &mut f);
+            root = node.next();
+        }
+
+        Document { nodes }
+    }
+}
+
+/// Appends a new node to `nodes` and links it to its parent and previous sibling
+pub(crate) fn push_node(
+    nodes: &mut Vec<Raw>,
+    data: Data,
+    parent: Option<usize>,
+    prev: Option<usize>,
+) -> usize {
+    let index = nodes.len();
+
+    nodes.push(Raw {
+        index,
+        parent,
+        prev,
+        next: None,
+        first_child: None,
+        last_child: None,
+        data,
+    });
+
+    if let Some(parent) = parent {
+        let parent = &mut nodes[parent];
+        if parent.first_child.is_none() {
+            parent.first_child = Some(index);
+        }
+        parent.last_child = Some(index);
+    }
+
+    if let Some(prev) = prev {
+        nodes[prev].next = Some(index);
+    }
+
+    index
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Deserialize_repr)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_repr, Deserialize_repr)]
 #[repr(usize)]
 pub enum HeaderKind {
     Main = 1,
@@ -28,7 +99,7 @@ pub enum HeaderKind {
     Detail = 6,
 }
 
-#[derive(Default, Clone, Debug, PartialEq, Eq)]
+#[derive(Default, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Data {
     Section {
         id: usize,
@@ -42,11 +113,70 @@ pub enum Data {
     },
     Division,
     Paragraph,
+    HorizontalRule,
+    /// Block of text whose whitespace and line breaks are kept as they are
+    Preformatted,
+    /// Explicit line break, the text after it continues on the next line
+    LineBreak,
+    /// Poem, lyrics or other verse, whose lines are separated by line breaks
+    Poem,
+    /// Line of a poem that is indented by the level
+    PoemIndentation {
+        level: usize,
+    },
     Span,
     Reflink,
+    /// Marker linking to a note in the reference list
+    ///
+    /// The number is assigned after parsing, see [`crate::references::ReferenceRegistry`]
+    Citation {
+        note: String,
+        number: usize,
+    },
+    /// Note in the reference list
+    Reference {
+        note: String,
+        number: usize,
+    },
     Hatnote,
     RedirectMessage,
     Disambiguation,
+    /// Maintenance template at the top of an article or section, like "This article needs
+    /// additional citations for verification", reduced to a single line of text
+    MaintenanceNotice,
+    /// Box whose content can be collapsed into a one-line summary, like a navbox, a long proof
+    /// or a list of episodes. `collapsed` is set when the wiki shows it collapsed at first
+    Collapsible {
+        summary: String,
+        collapsed: bool,
+    },
+
+    /// Footer with the navboxes of the page, gathered at its end. Shown collapsed at first
+    RelatedPages,
+    /// Navigation box linking to related pages, made of its title and groups of links
+    Navbox,
+    NavboxTitle,
+    /// Row of a navbox, its label followed by its links. Rows without a label span the whole box
+    NavboxGroup,
+    NavboxLabel,
+
+    /// Taxobox or chemistry infobox, made of headings and label-value rows
+    Taxobox,
+    /// Heading of a group of rows in a taxobox
+    TaxoboxHeading,
+    /// Row of a taxobox, a label followed by a value. Rows spanning the whole box have no label
+    TaxoboxRow,
+    TaxoboxLabel,
+    TaxoboxValue,
+
+    Table,
+    /// Title of a table, shown above its rows
+    TableCaption,
+    TableRow,
+    /// Cell of a table row, a header cell (`<th>`) or a data cell (`<td>`)
+    TableCell {
+        header: bool,
+    },
 
     OrderedList,
     UnorderedList,
@@ -58,6 +188,17 @@ pub enum Data {
 
     Bold,
     Italic,
+    /// Struck through text, like `<s>` or `<del>`
+    Strikethrough,
+    /// Underlined text, like `<u>` or `<ins>`
+    Underline,
+    Small,
+    /// Highlighted text (`<mark>`)
+    Mark,
+    /// Abbreviation (`<abbr>`) with its expansion, if it has one
+    Abbreviation {
+        title: Option<String>,
+    },
 
     WikiLink {
         href: String,
@@ -75,12 +216,18 @@ pub enum Data {
         title: Option<String>,
         autonumber: bool,
     },
+    /// Image with its source, see [`crate::images::image_url`], and its caption or alternative
+    /// text. Terminals can't show images, they are only part of some exports
+    Image {
+        src: String,
+        caption: Option<String>,
+    },
 
     #[default]
     Unknown,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Raw {
     pub index: usize,
     pub parent: Option<usize>,
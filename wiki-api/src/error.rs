@@ -0,0 +1,168 @@
+use std::fmt;
+
+/// An error the MediaWiki API returned instead of a result, by its error code. Errors that
+/// aren't known are kept with their code and description
+///
+/// The error can be found in the chain of an [`anyhow::Error`] returned by the requests of this
+/// crate with [`ApiError::find`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiError {
+    /// The replicas of the database are lagging behind (`maxlag`), the request should be retried
+    /// after a while
+    Lagged {
+        /// Seconds to wait before retrying, from the `Retry-After` header
+        retry_after: Option<u64>,
+    },
+    /// The wiki is in read-only mode (`readonly`), usually during maintenance
+    ReadOnly {
+        /// Why the wiki is read-only, as told by the wiki
+        reason: Option<String>,
+    },
+    /// The requested page doesn't exist (`missingtitle`)
+    MissingTitle,
+    /// Too many requests were sent in a short time (`ratelimited` or the status 429)
+    RateLimited {
+        /// Seconds to wait before retrying, from the `Retry-After` header
+        retry_after: Option<u64>,
+    },
+    /// The token sent with the request is invalid (`badtoken`), usually because the session
+    /// expired and the user has to log in again
+    BadToken,
+    /// Any other error code
+    Other { code: String, info: String },
+}
+
+impl ApiError {
+    /// Returns the error of a response of the action API, `None` when the request succeeded.
+    /// Both the default error format (`error`) and the newer ones (`errors`) are understood
+    pub fn from_response(response: &serde_json::Value, retry_after: Option<u64>) -> Option<Self> {
+        let error = response.get("error").or_else(|| {
+            response
+                .get("errors")
+                .and_then(|x| x.as_array())
+                .and_then(|x| x.first())
+        })?;
+        let code = error.get("code").and_then(|x| x.as_str())?;
+        let info = error
+            .get("info")
+            .or_else(|| error.get("text"))
+            .or_else(|| error.get("*"))
+            .and_then(|x| x.as_str())
+            .unwrap_or_default();
+
+        Some(Self::from_code(code, info, retry_after))
+    }
+
+    /// Returns the error of the error code, with its description as told by the wiki
+    pub fn from_code(code: &str, info: &str, retry_after: Option<u64>) -> Self {
+        match code {
+            "maxlag" => Self::Lagged { retry_after },
+            "readonly" => Self::ReadOnly {
+                reason: Some(info.to_string()).filter(|reason| !reason.is_empty()),
+            },
+            "missingtitle" => Self::MissingTitle,
+            "ratelimited" => Self::RateLimited { retry_after },
+            "badtoken" => Self::BadToken,
+            code => Self::Other {
+                code: code.to_string(),
+                info: info.to_string(),
+            },
+        }
+    }
+
+    /// Returns the first `ApiError` in the chain of the error
+    pub fn find(error: &anyhow::Error) -> Option<&Self> {
+        error.chain().find_map(|cause| cause.downcast_ref::<Self>())
+    }
+
+    /// Whether sending the same request again later can succeed
+    pub fn is_temporary(&self) -> bool {
+        matches!(
+            self,
+            Self::Lagged { .. } | Self::ReadOnly { .. } | Self::RateLimited { .. }
+        )
+    }
+
+    /// Seconds to wait before retrying, when the wiki told
+    pub fn retry_after(&self) -> Option<u64> {
+        match self {
+            Self::Lagged { retry_after } | Self::RateLimited { retry_after } => *retry_after,
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Lagged { .. } => write!(f, "the servers of the wiki are lagging behind"),
+            Self::ReadOnly {
+                reason: Some(reason),
+            } => write!(f, "the wiki is read-only: {}", reason),
+            Self::ReadOnly { reason: None } => write!(f, "the wiki is read-only"),
+            Self::MissingTitle => write!(f, "the page doesn't exist"),
+            Self::RateLimited { .. } => write!(f, "too many requests were sent to the wiki"),
+            Self::BadToken => write!(f, "the session token is invalid"),
+            Self::Other { code, info } => write!(f, "the wiki returned '{}': {}", code, info),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Context;
+
+    use super::ApiError;
+
+    #[test]
+    fn from_response() {
+        let maxlag = serde_json::json!({
+            "error": { "code": "maxlag", "info": "Waiting for 10.64.48.35: 7 seconds lagged." }
+        });
+        assert_eq!(
+            ApiError::from_response(&maxlag, Some(5)),
+            Some(ApiError::Lagged {
+                retry_after: Some(5)
+            })
+        );
+
+        let readonly = serde_json::json!({
+            "errors": [{ "code": "readonly", "text": "The wiki is being upgraded." }]
+        });
+        assert_eq!(
+            ApiError::from_response(&readonly, None),
+            Some(ApiError::ReadOnly {
+                reason: Some("The wiki is being upgraded.".to_string())
+            })
+        );
+
+        let missing = serde_json::json!({
+            "error": { "code": "missingtitle", "info": "The page you specified doesn't exist." }
+        });
+        assert_eq!(
+            ApiError::from_response(&missing, None),
+            Some(ApiError::MissingTitle)
+        );
+        assert!(!ApiError::MissingTitle.is_temporary());
+
+        let other = serde_json::json!({ "error": { "code": "nosuchsection", "info": "" } });
+        assert!(matches!(
+            ApiError::from_response(&other, None),
+            Some(ApiError::Other { ref code, .. }) if code == "nosuchsection"
+        ));
+
+        let result = serde_json::json!({ "parse": { "title": "Fox" } });
+        assert_eq!(ApiError::from_response(&result, None), None);
+    }
+
+    #[test]
+    fn find() {
+        let error = Err::<(), _>(ApiError::BadToken)
+            .context("failed fetching the page")
+            .unwrap_err();
+        assert_eq!(ApiError::find(&error), Some(&ApiError::BadToken));
+        assert_eq!(ApiError::find(&anyhow::anyhow!("offline")), None);
+    }
+}
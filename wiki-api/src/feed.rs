@@ -0,0 +1,125 @@
+use anyhow::{anyhow, Context, Result};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+use crate::{http::send, Endpoint};
+
+/// Which kind of "On this day" entries to fetch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OnThisDayKind {
+    /// A curated selection of events
+    Selected,
+    /// Historical events
+    Events,
+    /// Births of notable people
+    Births,
+    /// Deaths of notable people
+    Deaths,
+    /// Fixed holidays and observances
+    Holidays,
+}
+
+impl Display for OnThisDayKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OnThisDayKind::Selected => write!(f, "selected"),
+            OnThisDayKind::Events => write!(f, "events"),
+            OnThisDayKind::Births => write!(f, "births"),
+            OnThisDayKind::Deaths => write!(f, "deaths"),
+            OnThisDayKind::Holidays => write!(f, "holidays"),
+        }
+    }
+}
+
+/// A single entry of the "On this day" feed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Event {
+    /// Short description of the event
+    pub text: String,
+    /// Optional: Year the event took place in. Negative years are BC
+    pub year: Option<i64>,
+    /// Pages that are linked in the description
+    #[serde(default)]
+    pub pages: Vec<EventPage>,
+}
+
+/// A page linked from an [`Event`]
+///
+/// [`Event`]: Event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventPage {
+    #[serde(rename = "titles")]
+    titles: EventPageTitles,
+    /// Optional: Short description of the page
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct EventPageTitles {
+    normalized: String,
+}
+
+impl EventPage {
+    /// Title of the page, with spaces instead of underscores
+    pub fn title(&self) -> &str {
+        &self.titles.normalized
+    }
+}
+
+/// Entries of the "On this day" feed for a specific day
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OnThisDay {
+    pub kind: OnThisDayKind,
+    pub month: u32,
+    pub day: u32,
+    pub events: Vec<Event>,
+}
+
+impl OnThisDay {
+    /// Fetches the "On this day" feed from the REST API of the wiki the endpoint belongs to
+    ///
+    /// # Error
+    ///
+    /// This function returns an error when one of the following things happens:
+    /// - The request to the server could not be made
+    /// - The server returned an error
+    /// - The returned result could not be interpreted as a feed
+    pub async fn fetch(
+        endpoint: Endpoint,
+        kind: OnThisDayKind,
+        month: u32,
+        day: u32,
+    ) -> Result<OnThisDay> {
+        let url = endpoint
+            .join(&format!(
+                "/api/rest_v1/feed/onthisday/{}/{:02}/{:02}",
+                kind, month, day
+            ))
+            .context("failed building the feed url")?;
+
+        let response = send(Client::new().get(url))
+            .await
+            .context("failed sending the request")?
+            .error_for_status()
+            .context("the server returned an error")?;
+
+        let res_json: serde_json::Value = serde_json::from_str(&response.text())
+            .context("failed interpreting the response as json")?;
+
+        let events = res_json
+            .get(kind.to_string())
+            .map(|x| x.to_owned())
+            .ok_or_else(|| anyhow!("missing the '{}' entries", kind))?;
+        let events: Vec<Event> =
+            serde_json::from_value(events).context("failed deserializing the entries")?;
+
+        Ok(OnThisDay {
+            kind,
+            month,
+            day,
+            events,
+        })
+    }
+}
@@ -0,0 +1,190 @@
+use anyhow::{anyhow, bail, Context, Result};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, str::FromStr};
+
+use crate::{http::send, Endpoint};
+
+/// Largest search radius (in meters) the geosearch API supports
+pub const MAX_RADIUS: u32 = 10_000;
+/// Largest amount of results the geosearch API returns at once
+pub const MAX_LIMIT: u32 = 500;
+
+/// A point on the globe, in decimal degrees
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Coordinates {
+    pub lat: f64,
+    pub lon: f64,
+}
+
+// compared with `total_cmp`, so the equality holds even for NaN and `Eq` is sound
+impl PartialEq for Coordinates {
+    fn eq(&self, other: &Self) -> bool {
+        self.lat.total_cmp(&other.lat).is_eq() && self.lon.total_cmp(&other.lon).is_eq()
+    }
+}
+
+impl Eq for Coordinates {}
+
+impl Display for Coordinates {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}|{}", self.lat, self.lon)
+    }
+}
+
+impl FromStr for Coordinates {
+    type Err = anyhow::Error;
+
+    /// Parses coordinates in the form of `lat,lon`, e.g. `52.5163,13.3777`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (lat, lon) = s
+            .split_once(',')
+            .ok_or_else(|| anyhow!("expected coordinates in the form of 'lat,lon'"))?;
+
+        let lat: f64 = lat.trim().parse().context("invalid latitude")?;
+        let lon: f64 = lon.trim().parse().context("invalid longitude")?;
+
+        if !(-90.0..=90.0).contains(&lat) {
+            bail!("the latitude has to be between -90 and 90");
+        }
+        if !(-180.0..=180.0).contains(&lon) {
+            bail!("the longitude has to be between -180 and 180");
+        }
+
+        Ok(Coordinates { lat, lon })
+    }
+}
+
+/// A page that is located near the searched coordinates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeoSearchResult {
+    /// Title of the page
+    pub title: String,
+    /// PageId of the page
+    pub pageid: usize,
+    /// Location of the page
+    #[serde(flatten)]
+    pub coordinates: Coordinates,
+    /// Distance to the searched coordinates, in meters
+    #[serde(rename = "dist")]
+    pub distance: f64,
+}
+
+// like the coordinates, the distance is compared with `total_cmp`
+impl PartialEq for GeoSearchResult {
+    fn eq(&self, other: &Self) -> bool {
+        self.title == other.title
+            && self.pageid == other.pageid
+            && self.coordinates == other.coordinates
+            && self.distance.total_cmp(&other.distance).is_eq()
+    }
+}
+
+impl Eq for GeoSearchResult {}
+
+/// Searches for pages that are located around the given coordinates
+///
+/// The radius (in meters) and the limit are clamped to [`MAX_RADIUS`] and [`MAX_LIMIT`]. The
+/// results are sorted by their distance to the coordinates
+///
+/// # Error
+///
+/// This function returns an error when one of the following things happens:
+/// - The request to the server could not be made
+/// - The server returned an error
+/// - The returned result could not interpreted as a list of pages
+pub async fn geosearch(
+    endpoint: Endpoint,
+    coordinates: Coordinates,
+    radius: u32,
+    limit: u32,
+) -> Result<Vec<GeoSearchResult>> {
+    let response = send(
+        Client::new()
+            .get(endpoint)
+            .query(&[
+                ("action", "query"),
+                ("format", "json"),
+                ("formatversion", "2"),
+                ("list", "geosearch"),
+            ])
+            .query(&[
+                ("gscoord", coordinates.to_string()),
+                ("gsradius", radius.clamp(10, MAX_RADIUS).to_string()),
+                ("gslimit", limit.clamp(1, MAX_LIMIT).to_string()),
+            ]),
+    )
+    .await
+    .context("failed sending the request")?
+    .error_for_status()
+    .context("the server returned an error")?;
+
+    let res_json = response.api_json()?;
+
+    let results = res_json
+        .get("query")
+        .and_then(|x| x.get("geosearch"))
+        .map(|x| x.to_owned())
+        .ok_or_else(|| anyhow!("missing the geosearch results"))?;
+
+    serde_json::from_value(results).context("failed deserializing the geosearch results")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Coordinates, GeoSearchResult};
+
+    #[test]
+    fn parse_coordinates() {
+        let coordinates: Coordinates = "52.5163, 13.3777".parse().unwrap();
+        assert_eq!(
+            coordinates,
+            Coordinates {
+                lat: 52.5163,
+                lon: 13.3777
+            }
+        );
+        assert_eq!(coordinates.to_string(), "52.5163|13.3777");
+
+        assert!("52.5163".parse::<Coordinates>().is_err());
+        assert!("91,0".parse::<Coordinates>().is_err());
+        assert!("0,north".parse::<Coordinates>().is_err());
+
+        let nan = Coordinates {
+            lat: f64::NAN,
+            lon: 0.0,
+        };
+        assert_eq!(nan, nan);
+        assert_ne!(coordinates, nan);
+    }
+
+    #[test]
+    fn serialize_result() {
+        let result = GeoSearchResult {
+            title: "Brandenburg Gate".to_string(),
+            pageid: 3857,
+            coordinates: Coordinates {
+                lat: 52.5163,
+                lon: 13.3777,
+            },
+            distance: 12.5,
+        };
+
+        // results are serialized in the same format the API returns them in
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "title": "Brandenburg Gate",
+                "pageid": 3857,
+                "lat": 52.5163,
+                "lon": 13.3777,
+                "dist": 12.5,
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<GeoSearchResult>(json).unwrap(),
+            result
+        );
+    }
+}
@@ -0,0 +1,202 @@
+use std::{
+    sync::OnceLock,
+    time::{Duration, Instant, SystemTime},
+};
+
+use anyhow::{anyhow, Context, Result};
+use reqwest::{
+    header::{HeaderMap, HeaderValue, COOKIE, RETRY_AFTER},
+    Client, RequestBuilder, StatusCode,
+};
+use tracing::{debug, warn};
+use url::Url;
+
+use crate::{
+    cookies::{cookie_header, store_cookies},
+    error::ApiError,
+};
+
+type Recorder = Box<dyn Fn(&Exchange) + Send + Sync>;
+type Replayer = Box<dyn Fn(&str, &Url) -> Option<Replayed> + Send + Sync>;
+
+static RECORDER: OnceLock<Recorder> = OnceLock::new();
+static REPLAYER: OnceLock<Replayer> = OnceLock::new();
+
+/// A request sent to a wiki together with its response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Exchange {
+    /// When the request was sent
+    pub started: SystemTime,
+    /// How long it took until the whole response was read
+    pub duration: Duration,
+    pub method: String,
+    pub url: Url,
+    pub request_headers: Vec<(String, String)>,
+    pub status: u16,
+    /// Url of the response, which differs from the requested one after a redirect
+    pub response_url: Url,
+    pub response_headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+/// Calls the function with every request sent by this crate and its response, which is useful
+/// to find out what a wiki returned when a page can't be parsed. Only the first recorder is
+/// used, setting another one afterwards is ignored
+pub fn set_recorder(recorder: impl Fn(&Exchange) + Send + Sync + 'static) {
+    if RECORDER.set(Box::new(recorder)).is_err() {
+        warn!("a recorder of the requests was already set");
+    }
+}
+
+/// A recorded response, returned instead of sending a request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replayed {
+    pub status: u16,
+    /// Url of the response, which differs from the requested one after a redirect
+    pub url: Url,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+/// Answers the requests of this crate with the responses returned by the function, called with
+/// the method and the url of a request, instead of sending them. A request without a response
+/// fails, nothing is sent to the wikis anymore. This is useful to test against recorded
+/// responses. Only the first replayer is used, setting another one afterwards is ignored
+pub fn set_replayer(replayer: impl Fn(&str, &Url) -> Option<Replayed> + Send + Sync + 'static) {
+    if REPLAYER.set(Box::new(replayer)).is_err() {
+        warn!("a replayer of the requests was already set");
+    }
+}
+
+/// The response to a request, read completely
+pub(crate) struct Response {
+    pub url: Url,
+    pub status: StatusCode,
+    /// Seconds to wait before sending another request, sent along with some errors
+    pub retry_after: Option<u64>,
+    body: String,
+}
+
+impl Response {
+    /// Turns a client or server error status into an error, too many requests being an
+    /// [`ApiError::RateLimited`]
+    pub fn error_for_status(self) -> Result<Self> {
+        if self.status == StatusCode::TOO_MANY_REQUESTS {
+            return Err(ApiError::RateLimited {
+                retry_after: self.retry_after,
+            }
+            .into());
+        }
+        if self.status.is_client_error() || self.status.is_server_error() {
+            return Err(anyhow!(
+                "'{}' returned the status {}",
+                self.url,
+                self.status
+            ));
+        }
+        Ok(self)
+    }
+
+    pub fn text(self) -> String {
+        self.body
+    }
+
+    /// Interprets the response of the action API as json, turning the error it returned instead
+    /// of a result into an [`ApiError`]
+    pub fn api_json(self) -> Result<serde_json::Value> {
+        let json: serde_json::Value =
+            serde_json::from_str(&self.body).context("failed interpreting the response as json")?;
+        match ApiError::from_response(&json, self.retry_after) {
+            Some(error) => Err(error.into()),
+            None => Ok(json),
+        }
+    }
+}
+
+/// Returns the seconds of a `Retry-After` header, dates aren't sent by the wikis
+fn retry_after(headers: &[(String, String)]) -> Option<u64> {
+    headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(RETRY_AFTER.as_str()))
+        .and_then(|(_, value)| value.trim().parse().ok())
+}
+
+fn header_pairs(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            (
+                name.to_string(),
+                String::from_utf8_lossy(value.as_bytes()).into_owned(),
+            )
+        })
+        .collect()
+}
+
+/// Sends the request and reads the whole response, handing both to the recorder when one is
+/// set. The cookies the host of the request set before are sent along, unless the request has
+/// its own. With a replayer, the recorded response is returned instead of sending the request
+pub(crate) async fn send(request: RequestBuilder) -> Result<Response> {
+    let mut request = request.build().context("failed building the request")?;
+    if !request.headers().contains_key(COOKIE) {
+        if let Some(cookie) = cookie_header(request.url()) {
+            match HeaderValue::from_str(&cookie) {
+                Ok(value) => {
+                    request.headers_mut().insert(COOKIE, value);
+                }
+                Err(error) => warn!("unable to send the cookies: {:?}", error),
+            }
+        }
+    }
+    let method = request.method().to_string();
+    let url = request.url().clone();
+    let request_headers = header_pairs(request.headers());
+
+    let started = SystemTime::now();
+    let start = Instant::now();
+    let (status, response_url, response_headers, body) = match REPLAYER.get() {
+        Some(replayer) => {
+            let replayed = replayer(&method, &url)
+                .ok_or_else(|| anyhow!("no recorded response for '{}'", url))?;
+            let status = StatusCode::from_u16(replayed.status)
+                .context("the recorded response has an invalid status")?;
+            (status, replayed.url, replayed.headers, replayed.body)
+        }
+        None => {
+            let response = Client::new().execute(request).await?;
+            debug!("response url: '{}'", response.url().as_str());
+
+            let status = response.status();
+            let response_url = response.url().clone();
+            let response_headers = header_pairs(response.headers());
+            let body = response
+                .text()
+                .await
+                .context("failed reading the response")?;
+            (status, response_url, response_headers, body)
+        }
+    };
+    store_cookies(&response_url, &response_headers);
+    let retry_after = retry_after(&response_headers);
+
+    if let Some(recorder) = RECORDER.get() {
+        recorder(&Exchange {
+            started,
+            duration: start.elapsed(),
+            method,
+            url,
+            request_headers,
+            status: status.as_u16(),
+            response_url: response_url.clone(),
+            response_headers,
+            body: body.clone(),
+        });
+    }
+
+    Ok(Response {
+        url: response_url,
+        status,
+        retry_after,
+        body,
+    })
+}
@@ -0,0 +1,93 @@
+use anyhow::{Context, Result};
+use reqwest::{header::CONTENT_TYPE, Client};
+use url::Url;
+
+/// An image file with its media type, like the thumbnail of a figure
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    /// Media type of the file, like `image/png`
+    pub mime_type: String,
+    pub data: Vec<u8>,
+}
+
+/// Returns the full url of the source of an image in a page. The sources are usually protocol
+/// relative (`//upload.wikimedia.org/...`) or relative to the page
+pub fn image_url(src: &str, endpoint: &Url) -> Result<Url> {
+    endpoint
+        .join(src)
+        .with_context(|| format!("the image source '{}' is invalid", src))
+}
+
+/// Guesses the media type of an image from the extension of its url
+fn guess_mime_type(url: &Url) -> &'static str {
+    let extension = url
+        .path()
+        .rsplit('.')
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    match extension.as_str() {
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        _ => "image/png",
+    }
+}
+
+/// Downloads the image
+///
+/// Unlike the other requests of this crate, images aren't passed to the recorder and can't be
+/// replayed, their contents aren't text
+///
+/// # Error
+///
+/// This function returns an error when one of the following things happens:
+/// - The request to the server could not be made
+/// - The server returned an error
+pub async fn fetch_image(url: Url) -> Result<Image> {
+    let response = Client::new()
+        .get(url.clone())
+        .send()
+        .await
+        .context("failed sending the request")?
+        .error_for_status()
+        .context("the server returned an error")?;
+
+    let mime_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| value.starts_with("image/"))
+        .map(|value| value.split(';').next().unwrap_or(value).trim().to_string())
+        .unwrap_or_else(|| guess_mime_type(&url).to_string());
+    let data = response
+        .bytes()
+        .await
+        .context("failed reading the image")?
+        .to_vec();
+
+    Ok(Image { mime_type, data })
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use super::{guess_mime_type, image_url};
+
+    #[test]
+    fn urls() {
+        let endpoint = Url::parse("https://en.wikipedia.org/w/api.php").unwrap();
+        let url = image_url(
+            "//upload.wikimedia.org/wikipedia/commons/a/a0/Fox.JPG",
+            &endpoint,
+        )
+        .unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://upload.wikimedia.org/wikipedia/commons/a/a0/Fox.JPG"
+        );
+        assert_eq!(guess_mime_type(&url), "image/jpeg");
+    }
+}
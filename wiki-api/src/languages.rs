@@ -1,3 +1,116 @@
+use anyhow::{anyhow, Context, Result};
+use reqwest::Client;
 use wiki_api_macros::parse_languages;
 
+use crate::{http::send, Endpoint};
+
+/// The action API of Meta-Wiki, which knows every wiki of the Wikimedia projects
+const META_ENDPOINT: &str = "https://meta.wikimedia.org/w/api.php";
+/// Code of Wikipedia among the sites of a language in the site matrix
+const WIKIPEDIA_SITE_CODE: &str = "wiki";
+
 parse_languages!("wiki-api/data/languages.json");
+
+impl Language {
+    /// Finds the language by its code, English name or local name. Unlike the `From`
+    /// implementations, unknown languages are not replaced with the default one
+    pub fn find(query: &str) -> Option<Language> {
+        let query = query.trim().to_lowercase();
+        LANGUAGES
+            .iter()
+            .find(|language| {
+                language.code().to_lowercase() == query
+                    || language.name().to_lowercase() == query
+                    || language.local_name().to_lowercase() == query
+            })
+            .cloned()
+    }
+
+    /// Returns the action API endpoint of the Wikipedia edition in this language
+    pub fn wikipedia_endpoint(&self) -> Endpoint {
+        Endpoint::parse(&format!("https://{}.wikipedia.org/w/api.php", self.code()))
+            .expect("language codes are valid host names")
+    }
+}
+
+/// Returns the codes of the languages with an open Wikipedia edition in a response of the site
+/// matrix
+fn wikipedia_codes(response: &serde_json::Value) -> Result<Vec<String>> {
+    let matrix = response
+        .get("sitematrix")
+        .and_then(|x| x.as_object())
+        .ok_or_else(|| anyhow!("the response contains no site matrix"))?;
+
+    // besides the languages, the matrix contains their count and the `specials` wikis
+    Ok(matrix
+        .values()
+        .filter_map(|language| {
+            let code = language.get("code")?.as_str()?;
+            let has_wikipedia = language.get("site")?.as_array()?.iter().any(|site| {
+                site.get("code").and_then(|x| x.as_str()) == Some(WIKIPEDIA_SITE_CODE)
+                    && site.get("closed").is_none()
+            });
+            has_wikipedia.then(|| code.to_string())
+        })
+        .collect())
+}
+
+/// Whether there is an open Wikipedia edition in the language, according to the site matrix of
+/// Meta-Wiki. Closed editions can still be read, but aren't edited anymore
+///
+/// # Error
+///
+/// This function returns an error when one of the following things happens:
+/// - The request to the server could not be made
+/// - The server returned an error
+/// - The returned result could not be interpreted as json
+pub async fn has_wikipedia(language: &Language) -> Result<bool> {
+    let endpoint = Endpoint::parse(META_ENDPOINT).expect("the endpoint of Meta-Wiki is valid");
+    let response = send(Client::new().get(endpoint).query(&[
+        ("action", "sitematrix"),
+        ("format", "json"),
+        ("formatversion", "2"),
+        ("smtype", "language"),
+        ("smsiteprop", "code"),
+        ("smlangprop", "code|site"),
+    ]))
+    .await
+    .context("failed sending the request")?
+    .error_for_status()
+    .context("the server returned an error")?;
+
+    let res_json = response.api_json()?;
+    Ok(wikipedia_codes(&res_json)?
+        .iter()
+        .any(|code| code == language.code()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{wikipedia_codes, Language};
+
+    #[test]
+    fn find() {
+        assert_eq!(Language::find("es"), Some(Language::from("es")));
+        assert_eq!(Language::find(" German "), Language::find("de"));
+        assert_eq!(Language::find("not a language"), None);
+        assert_eq!(
+            Language::find("de").unwrap().wikipedia_endpoint().as_str(),
+            "https://de.wikipedia.org/w/api.php"
+        );
+    }
+
+    #[test]
+    fn site_matrix() {
+        let response = serde_json::json!({
+            "sitematrix": {
+                "count": 3,
+                "0": { "code": "de", "site": [{ "code": "wiki" }, { "code": "wiktionary" }] },
+                "1": { "code": "aa", "site": [{ "code": "wiki", "closed": true }] },
+                "2": { "code": "ang", "site": [{ "code": "wikibooks" }] },
+                "specials": [{ "code": "meta" }]
+            }
+        });
+        assert_eq!(wikipedia_codes(&response).unwrap(), vec!["de".to_string()]);
+    }
+}
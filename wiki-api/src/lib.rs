@@ -1,10 +1,109 @@
+//! Fetches articles from Wikipedia (and other MediaWiki sites) and parses them into a document
+//! model that can be displayed without a browser
+//!
+//! This is the library behind [wiki-tui](https://github.com/builditluc/wiki-tui), which uses it
+//! like any other crate would. It has no dependency on the user interface.
+//!
+//! - [`backend`] fetches pages and their summaries, [`search`] searches for them
+//! - [`parser`] turns the HTML of a page into a [`document::Document`], a flat tree of nodes
+//! - [`transform`] cleans up a parsed document
+//! - [`disambiguation`], [`navbox`], [`portals`], [`references`] and [`taxobox`] extract
+//!   structured data from a document
+//!
+//! The data types implement `serde`'s `Serialize` and `Deserialize`, so pages and search results
+//! can be stored and loaded again.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use wiki_api::{
+//!     backend::{ActionBackend, PageBackend},
+//!     languages::Language,
+//!     taxobox::taxobox_groups,
+//! };
+//!
+//! # async fn run() -> anyhow::Result<()> {
+//! let language = Language::English;
+//! let backend = ActionBackend::new(language.wikipedia_endpoint());
+//!
+//! let page = backend.fetch_page("Red fox", &language).await?;
+//! for group in taxobox_groups(&page.content).unwrap_or_default() {
+//!     println!("{}: {} rows", group.heading, group.rows.len());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Stability
+//!
+//! The public API follows semantic versioning: while the version is `0.x`, breaking changes
+//! only happen in a new minor version. This is checked for every pull request with
+//! `cargo semver-checks`.
+
 use url::Url;
 
+/// Assessment of the quality of articles
+pub mod assessment;
+/// Fetching pages from the different APIs of a wiki
+pub mod backend;
+/// Articles listed in categories
+pub mod categories;
+/// Cookies set by the wikis, sent back with later requests
+pub mod cookies;
+/// Pages listed on disambiguation pages
+pub mod disambiguation;
+/// The parsed content of a page
 pub mod document;
+/// Errors returned by the MediaWiki API
+pub mod error;
+/// The "On this day" feed
+pub mod feed;
+/// Searching for pages around coordinates
+pub mod geosearch;
+/// Sending requests to a wiki and recording them for debugging
+pub mod http;
+/// Downloading the images shown in pages
+pub mod images;
+/// The languages Wikipedia is available in
 pub mod languages;
+/// Links between pages
+pub mod links;
+/// Media files and their description pages
+pub mod media;
+/// Navboxes, the boxes of related pages at the end of articles
+pub mod navbox;
+/// Summaries of sections made of their leading sentences
+pub mod outline;
+/// Pages and their metadata
 pub mod page;
+/// How often pages were viewed
+pub mod pageviews;
+/// Parsing the HTML of a page into a document
 pub mod parser;
+/// Articles selected on topic portals
+pub mod portals;
+/// Following redirects between pages
+pub mod redirects;
+/// Numbering of references
+pub mod references;
+/// Revisions of pages and what changed between them
+pub mod revisions;
+/// Full-text search
 pub mod search;
+/// Taxoboxes and chemistry infoboxes
+pub mod taxobox;
+/// Cleaning up parsed documents
+pub mod transform;
+/// Converting measurements between metric and imperial units
+pub mod units;
+/// Archived copies of external links in the Wayback Machine
+pub mod wayback;
+/// Reading pages from ZIM archives, the offline copies of wikis made by Kiwix
+pub mod zim;
 
 // TODO: Make Endpoint a real struct
+/// Url of the action API (`api.php`) of a wiki
 pub type Endpoint = Url;
+
+/// Amount of titles the API accepts in a single query
+pub(crate) const TITLES_PER_REQUEST: usize = 50;
@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use reqwest::Client;
+
+use crate::{http::send, languages::Language, search::Namespace, Endpoint, TITLES_PER_REQUEST};
+
+/// Fetches the titles of the articles the page links to
+///
+/// Only links into the main namespace are returned, at most `limit` of them
+///
+/// # Error
+///
+/// This function returns an error when one of the following things happens:
+/// - The request to the server could not be made
+/// - The server returned an error
+/// - The returned result could not be interpreted as json
+pub async fn fetch_links(endpoint: Endpoint, title: &str, limit: usize) -> Result<Vec<String>> {
+    let mut links = Vec::new();
+    let mut continue_from: Option<String> = None;
+
+    loop {
+        let mut params = vec![
+            ("titles", title.to_string()),
+            ("plnamespace", (Namespace::Main as usize).to_string()),
+            ("pllimit", "max".to_string()),
+        ];
+        if let Some(continue_from) = continue_from.take() {
+            params.push(("plcontinue", continue_from));
+        }
+
+        let response = send(
+            Client::new()
+                .get(endpoint.clone())
+                .query(&[
+                    ("action", "query"),
+                    ("format", "json"),
+                    ("formatversion", "2"),
+                    ("prop", "links"),
+                ])
+                .query(&params),
+        )
+        .await
+        .context("failed sending the request")?
+        .error_for_status()
+        .context("the server returned an error")?;
+
+        let res_json = response.api_json()?;
+
+        let titles = res_json
+            .get("query")
+            .and_then(|x| x.get("pages"))
+            .and_then(|x| x.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|page| page.get("links").and_then(|x| x.as_array()))
+            .flatten()
+            .filter_map(|link| link.get("title").and_then(|x| x.as_str()));
+        links.extend(titles.map(|x| x.to_string()));
+
+        continue_from = res_json
+            .get("continue")
+            .and_then(|x| x.get("plcontinue"))
+            .and_then(|x| x.as_str())
+            .map(|x| x.to_string());
+
+        if continue_from.is_none() || links.len() >= limit {
+            break;
+        }
+    }
+
+    links.truncate(limit);
+    Ok(links)
+}
+
+/// Follows the `from`/`to` pairs of the `normalized` or `redirects` list of a query
+fn follow<'a>(pairs: &'a [serde_json::Value], title: &'a str) -> &'a str {
+    pairs
+        .iter()
+        .find(|pair| pair.get("from").and_then(|x| x.as_str()) == Some(title))
+        .and_then(|pair| pair.get("to").and_then(|x| x.as_str()))
+        .unwrap_or(title)
+}
+
+/// Reads the titles of the pages in the language out of a `langlinks` query, by the titles they
+/// were asked for
+fn language_titles(res_json: &serde_json::Value, titles: &[String]) -> HashMap<String, String> {
+    let list = |name| {
+        res_json
+            .get("query")
+            .and_then(|x| x.get(name))
+            .and_then(|x| x.as_array())
+            .cloned()
+            .unwrap_or_default()
+    };
+    let (normalized, redirects, pages) = (list("normalized"), list("redirects"), list("pages"));
+
+    let translated: HashMap<&str, &str> = pages
+        .iter()
+        .filter_map(|page| {
+            let title = page.get("title").and_then(|x| x.as_str())?;
+            let translation = page
+                .get("langlinks")
+                .and_then(|x| x.as_array())
+                .and_then(|x| x.first())
+                .and_then(|x| x.get("title"))
+                .and_then(|x| x.as_str())?;
+            Some((title, translation))
+        })
+        .collect();
+
+    titles
+        .iter()
+        .filter_map(|title| {
+            let page = follow(&redirects, follow(&normalized, title));
+            translated
+                .get(page)
+                .map(|translation| (title.clone(), translation.to_string()))
+        })
+        .collect()
+}
+
+/// Fetches the titles the given pages have in the Wikipedia of another language
+///
+/// Redirects are followed, so the title of a redirect is translated like the page it points
+/// to. Pages that don't exist in the language are missing in the returned map
+///
+/// # Error
+///
+/// This function returns an error when one of the following things happens:
+/// - The request to the server could not be made
+/// - The server returned an error
+/// - The returned result could not be interpreted as json
+pub async fn fetch_language_titles(
+    endpoint: Endpoint,
+    titles: &[String],
+    language: &Language,
+) -> Result<HashMap<String, String>> {
+    let mut translations = HashMap::new();
+
+    for titles in titles.chunks(TITLES_PER_REQUEST) {
+        let response = send(
+            Client::new()
+                .get(endpoint.clone())
+                .query(&[
+                    ("action", "query"),
+                    ("format", "json"),
+                    ("formatversion", "2"),
+                    ("prop", "langlinks"),
+                    ("redirects", "1"),
+                    ("lllimit", "max"),
+                ])
+                .query(&[("lllang", language.code())])
+                .query(&[("titles", titles.join("|"))]),
+        )
+        .await
+        .context("failed sending the request")?
+        .error_for_status()
+        .context("the server returned an error")?;
+
+        let res_json = response.api_json()?;
+        translations.extend(language_titles(&res_json, titles));
+    }
+
+    Ok(translations)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::language_titles;
+
+    #[test]
+    fn language_titles_follow_redirects() {
+        let response = json!({
+            "query": {
+                "normalized": [{ "from": "fox", "to": "Fox" }],
+                "redirects": [{ "from": "Red Fox", "to": "Red fox" }],
+                "pages": [
+                    { "title": "Fox", "langlinks": [{ "lang": "de", "title": "Füchse" }] },
+                    { "title": "Red fox", "langlinks": [{ "lang": "de", "title": "Rotfuchs" }] },
+                    { "title": "Fennec fox" }
+                ]
+            }
+        });
+        let titles = ["fox", "Red Fox", "Fennec fox"].map(String::from);
+
+        let translated = language_titles(&response, &titles);
+        assert_eq!(translated.len(), 2);
+        assert_eq!(translated["fox"], "Füchse");
+        assert_eq!(translated["Red Fox"], "Rotfuchs");
+    }
+}
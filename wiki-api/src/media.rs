@@ -0,0 +1,252 @@
+use anyhow::{anyhow, Context, Result};
+use reqwest::Client;
+use scraper::Html;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{http::send, Endpoint};
+
+/// Namespace prefix of file description pages
+const FILE_NAMESPACE: &str = "File:";
+/// Older prefix of file description pages, still understood by every wiki
+const IMAGE_NAMESPACE: &str = "Image:";
+
+/// What the description page of a media file tells about it, like an image or a recording
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MediaInfo {
+    /// Title of the file description page, like `File:Fox.jpg`
+    pub title: String,
+    pub description: Option<String>,
+    pub author: Option<String>,
+    /// Short name of the license, like `CC BY-SA 4.0`
+    pub license: Option<String>,
+    pub license_url: Option<String>,
+    /// Where the file comes from, like the person or archive that made it available
+    pub credit: Option<String>,
+    /// Size in pixels, `None` for files without one like recordings
+    pub width: Option<u64>,
+    pub height: Option<u64>,
+    /// Size of the original file in bytes
+    pub size: u64,
+    /// Media type of the file, like `image/jpeg`
+    pub mime_type: String,
+    /// Url of the original file
+    pub url: String,
+    /// Url of the file description page
+    pub description_url: String,
+    /// When the current version of the file was uploaded, in ISO 8601
+    pub uploaded: Option<String>,
+}
+
+impl MediaInfo {
+    /// Returns the name of the file without the namespace, like `Fox.jpg`
+    pub fn file_name(&self) -> &str {
+        file_name(&self.title)
+    }
+}
+
+/// Whether the title is the one of a file description page
+pub fn is_file_title(title: &str) -> bool {
+    title.starts_with(FILE_NAMESPACE) || title.starts_with(IMAGE_NAMESPACE)
+}
+
+/// Returns the name of the file without the namespace of its description page
+pub fn file_name(title: &str) -> &str {
+    title
+        .strip_prefix(FILE_NAMESPACE)
+        .or_else(|| title.strip_prefix(IMAGE_NAMESPACE))
+        .unwrap_or(title)
+}
+
+/// Returns the title of the description page of the file a media link points to. Media links
+/// point right to the file, like `//upload.wikimedia.org/wikipedia/commons/a/a0/Fox.ogg`
+pub fn media_link_title(href: &str) -> Option<String> {
+    let name = href
+        .split(['?', '#'])
+        .next()?
+        .rsplit('/')
+        .next()
+        .filter(|name| !name.is_empty())?;
+    // only the percent-encoding is decoded, a plus is part of the name
+    let query = format!("name={}", name.replace('+', "%2B"));
+    let name = url::form_urlencoded::parse(query.as_bytes())
+        .next()
+        .map(|(_, name)| name.replace('_', " "))?;
+    Some(format!("{}{}", FILE_NAMESPACE, file_name(&name)))
+}
+
+/// Turns the HTML of a metadata field into its text, the fields are often links
+fn metadata_text(html: &str) -> String {
+    Html::parse_fragment(html)
+        .root_element()
+        .text()
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Returns the description of the file in a query response for its image info
+fn parse_media_info(response: &serde_json::Value) -> Result<MediaInfo> {
+    let page = response
+        .get("query")
+        .and_then(|x| x.get("pages"))
+        .and_then(|x| x.as_array())
+        .and_then(|x| x.first())
+        .ok_or_else(|| anyhow!("the response contains no page"))?;
+    let title = page
+        .get("title")
+        .and_then(|x| x.as_str())
+        .ok_or_else(|| anyhow!("the page has no title"))?;
+    let info = page
+        .get("imageinfo")
+        .and_then(|x| x.as_array())
+        .and_then(|x| x.first())
+        .ok_or_else(|| anyhow!("'{}' doesn't exist", title))?;
+
+    let string = |name: &str| info.get(name).and_then(|x| x.as_str()).map(str::to_string);
+    let number = |name: &str| info.get(name).and_then(|x| x.as_u64()).filter(|x| *x > 0);
+    let metadata = |name: &str| {
+        info.get("extmetadata")
+            .and_then(|x| x.get(name))
+            .and_then(|x| x.get("value"))
+            .and_then(|x| x.as_str())
+            .map(metadata_text)
+            .filter(|text| !text.is_empty())
+    };
+
+    Ok(MediaInfo {
+        title: title.to_string(),
+        description: metadata("ImageDescription"),
+        author: metadata("Artist").or_else(|| string("user")),
+        license: metadata("LicenseShortName"),
+        license_url: metadata("LicenseUrl"),
+        credit: metadata("Credit"),
+        width: number("width"),
+        height: number("height"),
+        size: number("size").unwrap_or_default(),
+        mime_type: string("mime").unwrap_or_default(),
+        url: string("url").ok_or_else(|| anyhow!("'{}' has no file", title))?,
+        description_url: string("descriptionurl").unwrap_or_default(),
+        uploaded: string("timestamp"),
+    })
+}
+
+/// Fetches what the description page of the file tells about it. Files of Wikimedia Commons are
+/// described by every Wikipedia
+///
+/// # Error
+///
+/// This function returns an error when one of the following things happens:
+/// - The request to the server could not be made
+/// - The server returned an error
+/// - The returned result could not be interpreted as json
+/// - The file doesn't exist
+pub async fn fetch_media_info(endpoint: Endpoint, title: &str) -> Result<MediaInfo> {
+    let response = send(Client::new().get(endpoint).query(&[
+        ("action", "query"),
+        ("format", "json"),
+        ("formatversion", "2"),
+        ("prop", "imageinfo"),
+        ("iiprop", "timestamp|user|size|url|mime|extmetadata"),
+        (
+            "iiextmetadatafilter",
+            "ImageDescription|Artist|LicenseShortName|LicenseUrl|Credit",
+        ),
+        ("titles", title),
+    ]))
+    .await
+    .context("failed sending the request")?
+    .error_for_status()
+    .context("the server returned an error")?;
+
+    let res_json = response.api_json()?;
+    parse_media_info(&res_json)
+}
+
+/// Downloads the original file
+///
+/// Like images, files aren't passed to the recorder and can't be replayed
+///
+/// # Error
+///
+/// This function returns an error when one of the following things happens:
+/// - The request to the server could not be made
+/// - The server returned an error
+pub async fn fetch_media_file(url: Url) -> Result<Vec<u8>> {
+    let bytes = Client::new()
+        .get(url)
+        .send()
+        .await
+        .context("failed sending the request")?
+        .error_for_status()
+        .context("the server returned an error")?
+        .bytes()
+        .await
+        .context("failed reading the file")?;
+    Ok(bytes.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_file_title, media_link_title, parse_media_info};
+
+    #[test]
+    fn titles() {
+        assert!(is_file_title("File:Fox.jpg"));
+        assert!(is_file_title("Image:Fox.jpg"));
+        assert!(!is_file_title("Fox"));
+        assert_eq!(
+            media_link_title("//upload.wikimedia.org/wikipedia/commons/a/a0/Red_fox%27s_call.ogg")
+                .as_deref(),
+            Some("File:Red fox's call.ogg")
+        );
+        assert_eq!(media_link_title("//upload.wikimedia.org/"), None);
+    }
+
+    #[test]
+    fn media_info() {
+        let response = serde_json::json!({
+            "query": {
+                "pages": [{
+                    "ns": 6,
+                    "title": "File:Fox.jpg",
+                    "missing": true,
+                    "known": true,
+                    "imagerepository": "shared",
+                    "imageinfo": [{
+                        "timestamp": "2020-05-01T10:00:00Z",
+                        "user": "Uploader",
+                        "size": 2048000,
+                        "width": 4000,
+                        "height": 3000,
+                        "url": "https://upload.wikimedia.org/wikipedia/commons/a/a0/Fox.jpg",
+                        "descriptionurl": "https://commons.wikimedia.org/wiki/File:Fox.jpg",
+                        "mime": "image/jpeg",
+                        "extmetadata": {
+                            "ImageDescription": { "value": "A <b>red</b> fox\n in the snow" },
+                            "Artist": { "value": "<a href=\"//commons.wikimedia.org/wiki/User:Jane\">Jane</a>" },
+                            "LicenseShortName": { "value": "CC BY-SA 4.0" },
+                            "Credit": { "value": "" }
+                        }
+                    }]
+                }]
+            }
+        });
+
+        let info = parse_media_info(&response).unwrap();
+        assert_eq!(info.file_name(), "Fox.jpg");
+        assert_eq!(info.description.as_deref(), Some("A red fox in the snow"));
+        assert_eq!(info.author.as_deref(), Some("Jane"));
+        assert_eq!(info.license.as_deref(), Some("CC BY-SA 4.0"));
+        assert_eq!(info.license_url, None);
+        assert_eq!(info.credit, None);
+        assert_eq!((info.width, info.height), (Some(4000), Some(3000)));
+        assert_eq!(info.size, 2048000);
+
+        let missing = serde_json::json!({
+            "query": { "pages": [{ "ns": 6, "title": "File:Nothing.jpg", "missing": true }] }
+        });
+        assert!(parse_media_info(&missing).is_err());
+    }
+}
@@ -0,0 +1,158 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    document::{Data, Document, Node},
+    taxobox::text,
+};
+
+/// A row of a navbox: its label and the text of its links, in the order they appear
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NavboxGroup {
+    /// Label of the row, empty for rows spanning the whole box. Rows of nested groups are
+    /// labeled with the label of the row they are nested in as well, like `Species › Extinct`
+    pub label: String,
+    pub links: Vec<String>,
+}
+
+/// A navbox gathered at the end of the page, with its title and rows
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Navbox {
+    /// Title of the navbox, empty when it has none
+    pub title: String,
+    pub groups: Vec<NavboxGroup>,
+}
+
+/// Returns the footer the navboxes of the document are gathered in
+pub fn find_related_pages(document: &Document) -> Option<Node<'_>> {
+    document
+        .nth(0)?
+        .children()
+        .find(|node| matches!(node.data(), Data::RelatedPages))
+}
+
+/// Returns the title of the navbox, empty when it has none
+pub fn navbox_title(navbox: Node<'_>) -> String {
+    navbox
+        .children()
+        .find(|child| matches!(child.data(), Data::NavboxTitle))
+        .map(text)
+        .unwrap_or_default()
+}
+
+/// Collects the navboxes of the document with their rows of links. Returns an empty list when
+/// the document has none
+pub fn navboxes(document: &Document) -> Vec<Navbox> {
+    let footer = match find_related_pages(document) {
+        Some(footer) => footer,
+        None => return Vec::new(),
+    };
+
+    footer
+        .children()
+        .filter(|navbox| matches!(navbox.data(), Data::Navbox))
+        .map(|navbox| Navbox {
+            title: navbox_title(navbox),
+            groups: navbox
+                .children()
+                .filter(|group| matches!(group.data(), Data::NavboxGroup))
+                .map(|group| NavboxGroup {
+                    label: group
+                        .children()
+                        .find(|child| matches!(child.data(), Data::NavboxLabel))
+                        .map(text)
+                        .unwrap_or_default(),
+                    links: group
+                        .children()
+                        .filter(|child| {
+                            matches!(child.data(), Data::WikiLink { .. } | Data::RedLink { .. })
+                        })
+                        .map(text)
+                        .collect(),
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{navboxes, Navbox, NavboxGroup};
+    use crate::{
+        document::{Data, Document},
+        parser::{Parser, WikipediaParser},
+    };
+
+    fn parse(html: &str) -> Document {
+        Document {
+            nodes: WikipediaParser::parse_document(html).nodes(),
+        }
+    }
+
+    fn link(title: &str) -> String {
+        format!(
+            r#"<li><a rel="mw:WikiLink" href="./{0}" title="{0}">{0}</a></li>"#,
+            title
+        )
+    }
+
+    #[test]
+    fn gathered_at_the_end() {
+        let html = format!(
+            concat!(
+                r#"<section data-mw-section-id="0"><p>The red fox is a fox.</p>"#,
+                r#"<div role="navigation" class="navbox"><table class="nowraplinks mw-collapsible autocollapse navbox-inner"><tbody>"#,
+                r#"<tr><th class="navbox-title" colspan="2"><div class="navbar">v t e</div><div>Foxes</div></th></tr>"#,
+                r#"<tr><th class="navbox-group">Vulpes</th><td class="navbox-list"><ul>{}{}</ul></td></tr>"#,
+                r#"<tr><th class="navbox-group">Other</th><td class="navbox-list"><table class="nowraplinks navbox-subgroup"><tbody>"#,
+                r#"<tr><th class="navbox-group">Extinct</th><td class="navbox-list"><ul>{}</ul></td></tr>"#,
+                r#"</tbody></table></td></tr>"#,
+                r#"<tr><td class="navbox-abovebelow" colspan="2">{}</td></tr>"#,
+                r#"<tr><th class="navbox-group">Empty</th><td class="navbox-list"></td></tr>"#,
+                r#"</tbody></table></div></section>"#,
+                r#"<section data-mw-section-id="1"><h2 id="See_also">See also</h2><p>More foxes</p></section>"#
+            ),
+            link("Red fox"),
+            link("Arctic fox"),
+            link("Dire wolf"),
+            link("Canidae")
+        );
+        let document = parse(&html);
+
+        assert_eq!(
+            navboxes(&document),
+            vec![Navbox {
+                title: "Foxes".to_string(),
+                groups: vec![
+                    NavboxGroup {
+                        label: "Vulpes".to_string(),
+                        links: vec!["Red fox".to_string(), "Arctic fox".to_string()],
+                    },
+                    NavboxGroup {
+                        label: "Other › Extinct".to_string(),
+                        links: vec!["Dire wolf".to_string()],
+                    },
+                    NavboxGroup {
+                        label: String::new(),
+                        links: vec!["Canidae".to_string()],
+                    },
+                ],
+            }]
+        );
+
+        // the footer comes after the last section
+        let root = document.nth(0).unwrap();
+        let last = root.children().last().unwrap();
+        assert_eq!(last.data(), &Data::RelatedPages);
+        assert!(last.prev().is_some());
+    }
+
+    #[test]
+    fn without_navboxes() {
+        let document = parse(r#"<section data-mw-section-id="0"><p>Foxes</p></section>"#);
+        assert!(navboxes(&document).is_empty());
+        assert!(!document
+            .nodes
+            .iter()
+            .any(|node| node.data == Data::RelatedPages));
+    }
+}
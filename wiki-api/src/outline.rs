@@ -0,0 +1,211 @@
+use crate::{
+    document::{Data, Node},
+    taxobox::text,
+};
+
+const SENTENCE_TERMINATORS: [char; 3] = ['.', '!', '?'];
+/// Characters that can follow the end of a sentence, like a closing quote
+const CLOSING_PUNCTUATION: [char; 4] = ['"', '\'', ')', '”'];
+/// Abbreviations that end with a period without ending the sentence, in lowercase
+const ABBREVIATIONS: [&str; 10] = [
+    "e.g.", "i.e.", "cf.", "dr.", "mr.", "mrs.", "ms.", "st.", "vs.", "no.",
+];
+
+/// A section summarized by its leading sentences
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionOutline {
+    /// Text of the heading, empty for the lead section
+    pub heading: String,
+    /// How deep the section is nested in the outlined section, `0` for that section itself
+    pub depth: usize,
+    /// The first sentences of the text of the section, without its subsections
+    pub sentences: Vec<String>,
+}
+
+fn ends_sentence(word: &str, next: Option<&str>) -> bool {
+    let word = word.trim_end_matches(CLOSING_PUNCTUATION);
+    if !word.ends_with(SENTENCE_TERMINATORS) {
+        return false;
+    }
+    // initials of a name, like the "J." in "J. R. R. Tolkien"
+    let is_initial = word.chars().count() == 2 && word.starts_with(char::is_uppercase);
+    if is_initial || ABBREVIATIONS.contains(&word.to_lowercase().as_str()) {
+        return false;
+    }
+    // a sentence can't continue in lowercase after its end
+    !next.is_some_and(|next| next.starts_with(char::is_lowercase))
+}
+
+/// Splits the text into sentences, with every run of whitespace collapsed into a single space
+pub fn split_sentences(text: &str) -> Vec<String> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let mut sentences = Vec::new();
+    let mut sentence: Vec<&str> = Vec::new();
+    for (n, word) in words.iter().enumerate() {
+        sentence.push(word);
+        if ends_sentence(word, words.get(n + 1).copied()) {
+            sentences.push(sentence.join(" "));
+            sentence.clear();
+        }
+    }
+    if !sentence.is_empty() {
+        sentences.push(sentence.join(" "));
+    }
+    sentences
+}
+
+/// Returns the text of the node without the markers of its citations
+fn prose(node: Node<'_>, text: &mut String) {
+    match node.data() {
+        Data::Citation { .. } | Data::Reflink => {}
+        Data::Text { contents } => text.push_str(contents),
+        _ => node.children().for_each(|child| prose(child, text)),
+    }
+}
+
+fn leading_sentences(section: Node<'_>, count: usize) -> Vec<String> {
+    let mut sentences = Vec::new();
+    for paragraph in section
+        .children()
+        .filter(|node| matches!(node.data(), Data::Paragraph))
+    {
+        let mut paragraph_text = String::new();
+        prose(paragraph, &mut paragraph_text);
+        sentences.extend(split_sentences(&paragraph_text));
+        if sentences.len() >= count {
+            break;
+        }
+    }
+    sentences.truncate(count);
+    sentences
+}
+
+/// Outlines the section and each of its subsections by their first `count` sentences, which is
+/// enough to tell what a long section is about. Only paragraphs count as text, lists and tables
+/// are skipped
+///
+/// Returns nothing when the node isn't a section
+pub fn outline_section(section: Node<'_>, count: usize) -> Vec<SectionOutline> {
+    if !matches!(section.data(), Data::Section { .. }) {
+        return Vec::new();
+    }
+
+    let depth_of = |node: Node<'_>| {
+        let mut depth = 0;
+        let mut parent = node.parent();
+        while let Some(current) = parent {
+            if current.index() == section.index() {
+                break;
+            }
+            if matches!(current.data(), Data::Section { .. }) {
+                depth += 1;
+            }
+            parent = current.parent();
+        }
+        depth
+    };
+
+    std::iter::once(section)
+        .chain(section.descendants().filter(|node| {
+            node.index() != section.index() && matches!(node.data(), Data::Section { .. })
+        }))
+        .map(|node| SectionOutline {
+            heading: node
+                .children()
+                .find(|child| matches!(child.data(), Data::Header { .. }))
+                .map(text)
+                .unwrap_or_default(),
+            depth: if node.index() == section.index() {
+                0
+            } else {
+                depth_of(node) + 1
+            },
+            sentences: leading_sentences(node, count),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{outline_section, split_sentences};
+    use crate::{
+        document::{Data, Document},
+        parser::{Parser, WikipediaParser},
+    };
+
+    #[test]
+    fn sentences() {
+        assert_eq!(
+            split_sentences("The Rhine  flows north. It is long! Is it?"),
+            ["The Rhine flows north.", "It is long!", "Is it?"]
+        );
+        assert_eq!(
+            split_sentences("Written by J. R. R. Tolkien, e.g. The Hobbit. Another one"),
+            [
+                "Written by J. R. R. Tolkien, e.g. The Hobbit.",
+                "Another one"
+            ]
+        );
+        assert_eq!(
+            split_sentences(r#"He said "stop." Then he left. version 1.2. was out"#),
+            [r#"He said "stop.""#, "Then he left. version 1.2. was out"]
+        );
+    }
+
+    #[test]
+    fn outline() {
+        let html = r#"
+            <section data-mw-section-id="1">
+                <h2 id="History">History</h2>
+                <p>Rivers formed early.<sup class="mw-ref reference"><a href="./Rhine#cite_note-1"><span class="mw-reflink-text">[1]</span></a></sup> They carved valleys. Then came people.</p>
+                <section data-mw-section-id="2">
+                    <h3 id="Antiquity">Antiquity</h3>
+                    <ul><li>A list.</li></ul>
+                    <p>Romans built bridges.</p>
+                    <p>They also built ports. And roads.</p>
+                </section>
+                <section data-mw-section-id="3">
+                    <h3 id="Today">Today</h3>
+                </section>
+            </section>
+        "#;
+        let document = Document {
+            nodes: WikipediaParser::parse_document(html).nodes(),
+        };
+        let root = document.nth(0).unwrap();
+        assert!(outline_section(root, 2).is_empty());
+
+        let section = root
+            .descendants()
+            .find(|node| matches!(node.data(), Data::Section { .. }))
+            .unwrap();
+        let outline = outline_section(section, 2);
+
+        let summary: Vec<(&str, usize, Vec<&str>)> = outline
+            .iter()
+            .map(|section| {
+                (
+                    section.heading.as_str(),
+                    section.depth,
+                    section.sentences.iter().map(|x| x.as_str()).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (
+                    "History",
+                    0,
+                    vec!["Rivers formed early.", "They carved valleys."]
+                ),
+                (
+                    "Antiquity",
+                    1,
+                    vec!["Romans built bridges.", "They also built ports."]
+                ),
+                ("Today", 1, vec![]),
+            ]
+        );
+    }
+}
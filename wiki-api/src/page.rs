@@ -1,12 +1,18 @@
 use crate::{
+    assessment::Quality,
     document::{Document, HeaderKind},
-    parser::{Parser, WikipediaParser},
+    http::{send, Response},
+    parser::parse_document,
+    redirects::{parse_redirects, redirect_source},
 };
 use anyhow::{anyhow, Context, Result};
-use reqwest::{Client, Response};
+use reqwest::Client;
 use scraper::Html;
-use serde::Deserialize;
-use std::fmt::Display;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::Display,
+    time::{Duration, Instant},
+};
 use tracing::{debug, warn};
 use url::Url;
 
@@ -64,7 +70,7 @@ pub enum Link {
 }
 
 // TODO: replace this with Link::Internal
-#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub struct LanguageLink {
     #[serde(rename = "langname")]
     pub name: String,
@@ -75,19 +81,20 @@ pub struct LanguageLink {
     pub url: Url,
 }
 
-#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub struct Section {
-    #[serde(skip_deserializing)]
-    index: usize,
+    /// Position of the section in the page, it's neither fetched nor stored
+    #[serde(skip)]
+    pub index: usize,
     #[serde(rename = "toclevel")]
-    header_kind: HeaderKind,
+    pub header_kind: HeaderKind,
     #[serde(rename = "line")]
-    text: String,
-    number: String,
-    anchor: String,
+    pub text: String,
+    pub number: String,
+    pub anchor: String,
 }
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Page {
     pub title: String,
     pub pageid: usize,
@@ -96,6 +103,23 @@ pub struct Page {
     pub language_links: Option<Vec<LanguageLink>>,
     pub sections: Option<Vec<Section>>,
     pub revision_id: Option<usize>,
+    /// Whether the page only lists other pages sharing its title (See [`disambiguation_targets`]
+    /// for more)
+    ///
+    /// [`disambiguation_targets`]: crate::disambiguation::disambiguation_targets
+    pub is_disambiguation: bool,
+    /// Optional: Quality rating of the page, not fetched together with the page (See
+    /// [`fetch_qualities`] for more)
+    ///
+    /// [`fetch_qualities`]: crate::assessment::fetch_qualities
+    pub quality: Option<Quality>,
+    /// Optional: How long parsing the HTML of the page took, `None` for pages stored before it
+    /// was measured
+    #[serde(default)]
+    pub parse_time: Option<Duration>,
+    /// Optional: Title the page was requested with, when that title redirected to this page
+    #[serde(default)]
+    pub redirected_from: Option<String>,
 }
 
 impl Page {
@@ -121,6 +145,10 @@ impl std::fmt::Debug for Page {
             .field("language_links", &self.language_links.is_some())
             .field("sections", &self.sections.is_some())
             .field("revision_id", &self.revision_id)
+            .field("is_disambiguation", &self.is_disambiguation)
+            .field("quality", &self.quality)
+            .field("parse_time", &self.parse_time)
+            .field("redirected_from", &self.redirected_from)
             .finish()
     }
 }
@@ -225,6 +253,7 @@ pub struct PageBuilder<I, P, E, L> {
     revision: Option<usize>,
     redirects: Option<bool>,
     properties: Option<Vec<Property>>,
+    section: Option<usize>,
 }
 
 pub type PageRequest = PageBuilder<NoPageID, WithPage, WithEndpoint, WithLanguage>;
@@ -240,6 +269,7 @@ impl<E, L> PageBuilder<NoPageID, NoPage, E, L> {
             revision: self.revision,
             redirects: self.redirects,
             properties: self.properties,
+            section: self.section,
             language: self.language,
         }
     }
@@ -253,6 +283,7 @@ impl<E, L> PageBuilder<NoPageID, NoPage, E, L> {
             revision: self.revision,
             redirects: self.redirects,
             properties: self.properties,
+            section: self.section,
             language: self.language,
         }
     }
@@ -267,6 +298,7 @@ impl<I, P, L> PageBuilder<I, P, NoEndpoint, L> {
             revision: self.revision,
             redirects: self.redirects,
             properties: self.properties,
+            section: self.section,
             language: self.language,
         }
     }
@@ -279,6 +311,7 @@ impl<I, P, L> PageBuilder<I, P, NoEndpoint, L> {
             revision: self.revision,
             redirects: self.redirects,
             properties: self.properties,
+            section: self.section,
             language: self.language,
         }
     }
@@ -294,6 +327,7 @@ impl<I, P, E> PageBuilder<I, P, E, NoLanguage> {
             revision: self.revision,
             redirects: self.redirects,
             properties: self.properties,
+            section: self.section,
         }
     }
 }
@@ -316,27 +350,30 @@ impl<I, P, U, L> PageBuilder<I, P, U, L> {
         self.properties = Some(properties);
         self
     }
+
+    /// Only parse the content of the section with this index, `0` being the lead section
+    pub fn section(mut self, section: usize) -> Self {
+        self.section = Some(section);
+        self
+    }
 }
 
 impl<I, P> PageBuilder<I, P, WithEndpoint, WithLanguage> {
     async fn fetch_with_params(self, mut params: Vec<(&str, String)>) -> Result<Page> {
         async fn action_parse(params: Vec<(&str, String)>, endpoint: Url) -> Result<Response> {
-            Client::new()
-                .get(endpoint)
-                .query(&[
-                    ("action", "parse"),
-                    ("format", "json"),
-                    ("formatversion", "2"),
-                    ("parsoid", "true"),
-                ])
-                .query(&params)
-                .send()
-                .await
-                .map(|response| {
-                    debug!("response url: '{}'", response.url().as_str());
-                    response
-                })
-                .context("failed sending the request")
+            send(
+                Client::new()
+                    .get(endpoint)
+                    .query(&[
+                        ("action", "parse"),
+                        ("format", "json"),
+                        ("formatversion", "2"),
+                        ("parsoid", "true"),
+                    ])
+                    .query(&params),
+            )
+            .await
+            .context("failed sending the request")
         }
 
         if let Some(revision) = self.revision {
@@ -347,6 +384,10 @@ impl<I, P> PageBuilder<I, P, WithEndpoint, WithLanguage> {
             params.push(("redirects", redirects.to_string()));
         }
 
+        if let Some(section) = self.section {
+            params.push(("section", section.to_string()));
+        }
+
         if let Some(ref prop) = self.properties {
             let mut prop_str = String::new();
             for prop in prop {
@@ -361,13 +402,7 @@ impl<I, P> PageBuilder<I, P, WithEndpoint, WithLanguage> {
             .error_for_status()
             .context("the server returned an error")?;
 
-        let res_json: serde_json::Value = serde_json::from_str(
-            &response
-                .text()
-                .await
-                .context("failed reading the response")?,
-        )
-        .context("failed interpreting the response as json")?;
+        let res_json = response.api_json()?;
 
         self.serialize_result(res_json)
             .context("failed serializing the returned response")
@@ -388,18 +423,15 @@ impl<I, P> PageBuilder<I, P, WithEndpoint, WithLanguage> {
             .map(|x| x as usize)
             .ok_or_else(|| anyhow!("missing the pageid"))?;
 
+        let parse_start = Instant::now();
         let content = res_json
             .get("parse")
             .and_then(|x| x.get("text"))
             .and_then(|x| x.as_str())
-            .map(|x| {
-                let parser = WikipediaParser::parse_document(x);
-                Document {
-                    nodes: parser.nodes(),
-                }
-            })
+            .map(parse_document)
             // HACK: implement correct errors
             .ok_or(anyhow!("failed parsing the content"))?;
+        let parse_time = parse_start.elapsed();
 
         let language_links = res_json
             .get("parse")
@@ -459,6 +491,17 @@ impl<I, P> PageBuilder<I, P, WithEndpoint, WithLanguage> {
             .and_then(|x| x.as_u64())
             .map(|x| x as usize);
 
+        let is_disambiguation = res_json
+            .get("parse")
+            .and_then(|x| x.get("properties"))
+            .and_then(|x| x.get("disambiguation"))
+            .is_some();
+
+        let redirected_from = redirect_source(&parse_redirects(
+            res_json.get("parse").and_then(|x| x.get("redirects")),
+        ))
+        .context("failed following the redirects")?;
+
         Ok(Page {
             title,
             pageid,
@@ -467,6 +510,10 @@ impl<I, P> PageBuilder<I, P, WithEndpoint, WithLanguage> {
             language_links,
             sections,
             revision_id,
+            is_disambiguation,
+            quality: None,
+            parse_time: Some(parse_time),
+            redirected_from,
         })
     }
 }
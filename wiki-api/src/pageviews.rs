@@ -0,0 +1,180 @@
+use anyhow::{Context, Result};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::{http::send, Endpoint, TITLES_PER_REQUEST};
+
+/// Amount of days the views are fetched for, the API doesn't go back further than 60 days
+pub const DAYS: usize = 60;
+
+/// Characters of a sparkline, from the lowest to the highest bar
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// How often an article was viewed on each of the last days, the oldest day first
+///
+/// Days the statistics aren't available for yet (usually today) are left out
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageViews {
+    pub daily: Vec<u64>,
+}
+
+impl PageViews {
+    /// Views over all days
+    pub fn total(&self) -> u64 {
+        self.daily.iter().sum()
+    }
+
+    /// Views on an average day, `None` when there are no statistics at all
+    pub fn average(&self) -> Option<u64> {
+        match self.daily.len() {
+            0 => None,
+            days => Some(self.total() / days as u64),
+        }
+    }
+
+    /// Draws the trend of the views with at most `width` bars. The days are grouped so each bar
+    /// stands for the same amount of days, the oldest days are left out when they don't fill a
+    /// group
+    pub fn sparkline(&self, width: usize) -> String {
+        if width == 0 || self.daily.is_empty() {
+            return String::new();
+        }
+
+        let days_per_bar = (self.daily.len() + width - 1) / width;
+        let skipped = self.daily.len() % days_per_bar;
+        let bars: Vec<u64> = self.daily[skipped..]
+            .chunks(days_per_bar)
+            .map(|days| days.iter().sum())
+            .collect();
+
+        let min = bars.iter().copied().min().unwrap_or_default();
+        let max = bars.iter().copied().max().unwrap_or_default();
+        bars.iter()
+            .map(|bar| match max - min {
+                // a steady trend is drawn in the middle
+                0 => SPARKS[SPARKS.len() / 2 - 1],
+                range => SPARKS[((bar - min) * (SPARKS.len() as u64 - 1) / range) as usize],
+            })
+            .collect()
+    }
+}
+
+/// Writes a count of views in a short form, like `950`, `12.3k` or `4.1M`
+pub fn compact_count(count: u64) -> String {
+    match count {
+        0..=999 => count.to_string(),
+        1_000..=999_999 => format!("{:.1}k", count as f64 / 1_000.0),
+        _ => format!("{:.1}M", count as f64 / 1_000_000.0),
+    }
+}
+
+/// Fetches how often the given articles were viewed on each of the last [`DAYS`] days
+///
+/// Articles without statistics, like ones that don't exist, are missing in the returned map.
+/// Wikis without the PageViewInfo extension return an empty map
+///
+/// # Error
+///
+/// This function returns an error when one of the following things happens:
+/// - The request to the server could not be made
+/// - The server returned an error
+/// - The returned result could not be interpreted as json
+pub async fn fetch_page_views(
+    endpoint: Endpoint,
+    titles: &[String],
+) -> Result<HashMap<String, PageViews>> {
+    let mut views = HashMap::new();
+
+    for titles in titles.chunks(TITLES_PER_REQUEST) {
+        let response = send(
+            Client::new()
+                .get(endpoint.clone())
+                .query(&[
+                    ("action", "query"),
+                    ("format", "json"),
+                    ("formatversion", "2"),
+                    ("prop", "pageviews"),
+                    ("pvipmetric", "pageviews"),
+                ])
+                .query(&[("pvipdays", DAYS.to_string())])
+                .query(&[("titles", titles.join("|"))]),
+        )
+        .await
+        .context("failed sending the request")?
+        .error_for_status()
+        .context("the server returned an error")?;
+
+        let res_json = response.api_json()?;
+
+        let pages = res_json
+            .get("query")
+            .and_then(|x| x.get("pages"))
+            .and_then(|x| x.as_array())
+            .cloned()
+            .unwrap_or_default();
+
+        for page in pages {
+            let title = match page.get("title").and_then(|x| x.as_str()) {
+                Some(title) => title.to_string(),
+                None => continue,
+            };
+            let days = match page.get("pageviews").and_then(|x| x.as_object()) {
+                Some(days) => days,
+                None => continue,
+            };
+
+            // the days are named by their date, so sorting them by name puts them in order
+            let mut days: Vec<(&String, Option<u64>)> =
+                days.iter().map(|(day, x)| (day, x.as_u64())).collect();
+            days.sort_by(|a, b| a.0.cmp(b.0));
+            let daily: Vec<u64> = days.into_iter().filter_map(|(_, x)| x).collect();
+
+            if !daily.is_empty() {
+                views.insert(title, PageViews { daily });
+            }
+        }
+    }
+
+    Ok(views)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compact_count, PageViews};
+
+    #[test]
+    fn statistics() {
+        let views = PageViews {
+            daily: vec![10, 20, 30, 40],
+        };
+        assert_eq!(views.total(), 100);
+        assert_eq!(views.average(), Some(25));
+        assert_eq!(PageViews::default().average(), None);
+    }
+
+    #[test]
+    fn sparkline() {
+        let views = PageViews {
+            daily: vec![0, 1, 2, 3, 4, 5, 6, 7],
+        };
+        assert_eq!(views.sparkline(8), "▁▂▃▄▅▆▇█");
+        // two days per bar
+        assert_eq!(views.sparkline(4), "▁▃▅█");
+        // the two oldest days don't fill a group of three days
+        assert_eq!(views.sparkline(3), "▁█");
+
+        let steady = PageViews {
+            daily: vec![5, 5, 5],
+        };
+        assert_eq!(steady.sparkline(8), "▄▄▄");
+        assert_eq!(PageViews::default().sparkline(8), "");
+    }
+
+    #[test]
+    fn compact() {
+        assert_eq!(compact_count(950), "950");
+        assert_eq!(compact_count(12_345), "12.3k");
+        assert_eq!(compact_count(4_100_000), "4.1M");
+    }
+}
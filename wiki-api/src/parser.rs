@@ -1,9 +1,420 @@
-use html5ever::{parse_document, tendril::TendrilSink};
+use html5ever::tendril::TendrilSink;
 use markup5ever_rcdom::{Handle, NodeData, RcDom};
 use std::str::FromStr;
 use tracing::{trace, warn};
 
-use crate::document::{Data, HeaderKind, Raw};
+use crate::document::{push_node, Data, Document, HeaderKind, Raw};
+
+/// Classes of the infobox tables that are parsed as taxoboxes: the biological taxoboxes and the
+/// chemistry infoboxes
+const TAXOBOX_CLASSES: [&str; 3] = ["biota", "taxobox", "chembox"];
+
+/// Classes of the parts of a maintenance template that are left out of its notice: the
+/// explanation how to fix the issue and the date it was noticed. The wiki hides them as well
+/// when it shows the templates compactly
+const MAINTENANCE_HIDDEN_CLASSES: [&str; 2] = ["hide-when-compact", "date-container"];
+
+/// Classes of the parts of a collapsible box that are left out of its summary: the links to
+/// view and edit the template of a navbox and the button to expand the box
+const COLLAPSIBLE_HIDDEN_CLASSES: [&str; 2] = ["navbar", "mw-collapsible-toggle"];
+/// Classes of collapsible boxes the wiki shows collapsed at first. Navboxes collapse
+/// automatically
+const COLLAPSED_CLASSES: [&str; 2] = ["mw-collapsed", "autocollapse"];
+/// Elements whose text is separated from the text around them when the text of a box is
+/// collected, they are shown on their own lines
+const TEXT_BLOCK_ELEMENTS: [&str; 12] = [
+    "p", "div", "br", "li", "dt", "dd", "tr", "th", "td", "caption", "table", "pre",
+];
+/// The summary of a collapsible box is cut off after this many characters
+const COLLAPSIBLE_SUMMARY_MAX_CHARS: usize = 80;
+
+/// Separates the label of a row of a navbox from its links
+const NAVBOX_LABEL_SEPARATOR: &str = ": ";
+/// Separates the links of a row of a navbox, like the wiki does in its lists of links
+const NAVBOX_LINK_SEPARATOR: &str = " · ";
+/// Separates the label of a nested group of a navbox from the label of the row it's nested in
+const NAVBOX_SUBGROUP_SEPARATOR: &str = " › ";
+
+/// Returns the local name of the element, `None` for other nodes
+fn element_name(node: &Handle) -> Option<String> {
+    match node.data {
+        NodeData::Element { ref name, .. } => Some(name.local.to_string()),
+        _ => None,
+    }
+}
+
+/// Returns whether the element has the class
+fn has_class(node: &Handle, class: &str) -> bool {
+    match node.data {
+        NodeData::Element { ref attrs, .. } => attrs.borrow().iter().any(|attr| {
+            attr.name.local.as_ref() == "class"
+                && attr.value.split_whitespace().any(|value| value == class)
+        }),
+        _ => false,
+    }
+}
+
+/// Returns the level of the header of the section, looking into the wrapper the mobile-html
+/// output puts around it. `None` when the section has no header, like the lead section
+fn header_level(section: &Handle) -> Option<usize> {
+    section
+        .children
+        .borrow()
+        .iter()
+        .find_map(|child| match element_name(child).as_deref() {
+            Some(name @ ("h1" | "h2" | "h3" | "h4" | "h5" | "h6")) => name[1..].parse().ok(),
+            Some("div") if has_class(child, "pcs-edit-section-header") => header_level(child),
+            _ => None,
+        })
+}
+
+/// Returns the value of the attribute of the element
+fn attribute(node: &Handle, name: &str) -> Option<String> {
+    match node.data {
+        NodeData::Element { ref attrs, .. } => attrs
+            .borrow()
+            .iter()
+            .find(|attr| attr.name.local.as_ref() == name)
+            .map(|attr| attr.value.to_string()),
+        _ => None,
+    }
+}
+
+/// Returns the indentation level of a line of a poem from its style, like
+/// `margin-inline-start: 2em;`, where every `em` is a level
+fn poem_indentation(style: &str) -> Option<usize> {
+    let margin = style
+        .split(';')
+        .filter_map(|declaration| declaration.split_once(':'))
+        .find(|(property, _)| property.trim().starts_with("margin"))?
+        .1
+        .trim();
+    let level: f64 = margin.strip_suffix("em")?.trim().parse().ok()?;
+    Some(level.round().max(1.0) as usize)
+}
+
+/// Returns the source and the alternative text of the image, the node itself or the first one
+/// inside of it. The mobile-html output has placeholders instead of images, with the source in
+/// `data-src`
+fn find_image(node: &Handle) -> Option<(String, Option<String>)> {
+    let src = match element_name(node).as_deref() {
+        Some("img") => attribute(node, "src"),
+        Some("span") if has_class(node, "pcs-lazy-load-placeholder") => attribute(node, "data-src"),
+        _ => None,
+    };
+    if let Some(src) = src {
+        let alt = attribute(node, "alt")
+            .or_else(|| attribute(node, "data-alt"))
+            .filter(|alt| !alt.trim().is_empty());
+        return Some((src, alt));
+    }
+    node.children.borrow().iter().find_map(find_image)
+}
+
+/// Returns the first descendant of the node that is the element
+fn find_element(node: &Handle, name: &str) -> Option<Handle> {
+    node.children.borrow().iter().find_map(|child| {
+        if element_name(child).as_deref() == Some(name) {
+            return Some(child.clone());
+        }
+        find_element(child, name)
+    })
+}
+
+/// Returns the descendants of the node that are the element, without looking into them
+fn find_elements(node: &Handle, name: &str) -> Vec<Handle> {
+    let mut elements = Vec::new();
+    for child in node.children.borrow().iter() {
+        if element_name(child).as_deref() == Some(name) {
+            elements.push(child.clone());
+            continue;
+        }
+        elements.append(&mut find_elements(child, name));
+    }
+    elements
+}
+
+/// Returns whether the node contains any visible text
+fn has_text(node: &Handle) -> bool {
+    match node.data {
+        NodeData::Text { ref contents } => !contents.borrow().trim().is_empty(),
+        NodeData::Element { ref name, .. } if name.local.as_ref() == "style" => false,
+        _ => node.children.borrow().iter().any(has_text),
+    }
+}
+
+/// Returns the first descendant of the node with the class
+fn find_with_class(node: &Handle, class: &str) -> Option<Handle> {
+    node.children.borrow().iter().find_map(|child| {
+        if has_class(child, class) {
+            return Some(child.clone());
+        }
+        find_with_class(child, class)
+    })
+}
+
+/// Returns the maintenance templates nested in the node, like the issues listed by a "multiple
+/// issues" template
+fn nested_maintenance_templates(node: &Handle) -> Vec<Handle> {
+    let mut templates = Vec::new();
+    for child in node.children.borrow().iter() {
+        if has_class(child, "ambox") {
+            templates.push(child.clone());
+            continue;
+        }
+        templates.append(&mut nested_maintenance_templates(child));
+    }
+    templates
+}
+
+/// Returns the text of the node without the elements with one of the classes, with every run of
+/// whitespace collapsed into a single space
+fn text_without(node: &Handle, hidden_classes: &[&str]) -> String {
+    fn collect(node: &Handle, hidden_classes: &[&str], text: &mut String) {
+        match node.data {
+            NodeData::Text { ref contents } => text.push_str(&contents.borrow()),
+            NodeData::Element { ref name, .. } if name.local.as_ref() == "style" => {}
+            NodeData::Element { .. }
+                if hidden_classes.iter().any(|class| has_class(node, class)) => {}
+            _ => {
+                // the text of blocks, like paragraphs or cells, is separated by a space
+                let is_block = element_name(node)
+                    .is_some_and(|name| TEXT_BLOCK_ELEMENTS.contains(&name.as_str()));
+                if is_block {
+                    text.push(' ');
+                }
+                for child in node.children.borrow().iter() {
+                    collect(child, hidden_classes, text);
+                }
+                if is_block {
+                    text.push(' ');
+                }
+            }
+        }
+    }
+
+    let mut text = String::new();
+    collect(node, hidden_classes, &mut text);
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// Returns the text of a maintenance template without the parts that are hidden in its compact
+/// form, with every run of whitespace collapsed into a single space
+fn compact_text(node: &Handle) -> String {
+    text_without(node, &MAINTENANCE_HIDDEN_CLASSES)
+}
+
+/// Returns what a collapsed box shows of itself: its caption, the title of a navbox or its first
+/// header cell, and the start of its text when it has none of them
+fn collapsible_summary(node: &Handle) -> String {
+    let summary = find_element(node, "caption")
+        .or_else(|| find_with_class(node, "navbox-title"))
+        .or_else(|| find_element(node, "th"))
+        .map(|title| text_without(&title, &COLLAPSIBLE_HIDDEN_CLASSES))
+        .filter(|title| !title.is_empty())
+        .unwrap_or_else(|| text_without(node, &COLLAPSIBLE_HIDDEN_CLASSES));
+
+    if summary.chars().count() <= COLLAPSIBLE_SUMMARY_MAX_CHARS {
+        return summary;
+    }
+    let mut summary: String = summary
+        .chars()
+        .take(COLLAPSIBLE_SUMMARY_MAX_CHARS - 1)
+        .collect();
+    summary.push('…');
+    summary
+}
+
+/// Returns the rows of the table, including the ones in its body, head and foot
+fn table_rows(table: &Handle) -> Vec<Handle> {
+    let mut rows = Vec::new();
+    for child in table.children.borrow().iter() {
+        match element_name(child).as_deref() {
+            Some("tr") => rows.push(child.clone()),
+            Some("tbody" | "thead" | "tfoot") => rows.append(&mut table_rows(child)),
+            _ => {}
+        }
+    }
+    rows
+}
+
+/// Title of a navbox and its rows, each with its label and the links in it
+type NavboxRows = (Option<String>, Vec<(Option<String>, Vec<Handle>)>);
+
+/// Returns the rows of the navbox table as their label and the cell with their links. The rows of
+/// a nested group are labeled with the label of the row it's nested in, followed by their own
+fn navbox_rows(table: &Handle, outer_label: Option<&str>) -> Vec<(Option<String>, Handle)> {
+    let mut rows = Vec::new();
+    for row in table_rows(table) {
+        let cells: Vec<Handle> = row
+            .children
+            .borrow()
+            .iter()
+            .filter(|child| matches!(element_name(child).as_deref(), Some("th" | "td")))
+            .cloned()
+            .collect();
+
+        let label = cells
+            .iter()
+            .find(|cell| has_class(cell, "navbox-group"))
+            .map(|cell| text_without(cell, &COLLAPSIBLE_HIDDEN_CLASSES))
+            .filter(|label| !label.is_empty());
+        let label = match (outer_label, label) {
+            (Some(outer), Some(label)) => {
+                Some(format!("{}{}{}", outer, NAVBOX_SUBGROUP_SEPARATOR, label))
+            }
+            (outer, label) => label.or_else(|| outer.map(str::to_string)),
+        };
+
+        for cell in cells
+            .iter()
+            .filter(|cell| has_class(cell, "navbox-list") || has_class(cell, "navbox-abovebelow"))
+        {
+            match find_with_class(cell, "navbox-subgroup") {
+                Some(subgroup) => rows.append(&mut navbox_rows(&subgroup, label.as_deref())),
+                None => rows.push((label.clone(), cell.clone())),
+            }
+        }
+    }
+    rows
+}
+
+/// Returns the title of the navbox and its rows with links, `None` when none of its rows has a
+/// link
+fn navbox_contents(navbox: &Handle) -> Option<NavboxRows> {
+    let table = match element_name(navbox).as_deref() {
+        Some("table") => navbox.clone(),
+        _ => find_element(navbox, "table")?,
+    };
+    let title = find_with_class(navbox, "navbox-title")
+        .map(|title| text_without(&title, &COLLAPSIBLE_HIDDEN_CLASSES))
+        .filter(|title| !title.is_empty());
+
+    let rows: Vec<(Option<String>, Vec<Handle>)> = navbox_rows(&table, None)
+        .into_iter()
+        .map(|(label, cell)| {
+            let links: Vec<Handle> = find_elements(&cell, "a")
+                .into_iter()
+                .filter(has_text)
+                .collect();
+            (label, links)
+        })
+        .filter(|(_, links)| !links.is_empty())
+        .collect();
+    if rows.is_empty() {
+        return None;
+    }
+    Some((title, rows))
+}
+
+/// Which output of the wiki the HTML was taken from
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ParserMode {
+    /// The Parsoid HTML returned by the action API and the MediaWiki REST API
+    #[default]
+    Parsoid,
+    /// The `mobile-html` output of the Wikimedia REST API, which the Wikipedia apps display. It's
+    /// based on the Parsoid HTML, but wraps headers and collapsed tables, marks lazily loaded
+    /// images and lists every section next to each other
+    MobileHtml,
+    /// The HTML of an article in a ZIM archive, as written by mwoffliner. It's based on the
+    /// mobile-html output, but its links to other articles are relative and lost their `rel`
+    Zim,
+}
+
+impl ParserMode {
+    /// Whether the HTML is based on the mobile-html output
+    fn is_mobile(self) -> bool {
+        matches!(self, ParserMode::MobileHtml | ParserMode::Zim)
+    }
+}
+
+/// Whether the link points to another article of the same ZIM archive: its address is relative
+/// and it isn't marked as any other kind of link
+fn is_archive_link(attrs: &[(String, String)]) -> bool {
+    if attrs.iter().any(|(name, _)| name.as_str() == "rel") {
+        return false;
+    }
+    attrs.iter().any(|(name, value)| {
+        name.as_str() == "href"
+            && !value.is_empty()
+            && !value.contains("://")
+            && !value.starts_with(['#', '/'])
+            && !value.starts_with("mailto:")
+    })
+}
+
+/// What the parser does with an element of the mobile-html output that isn't in the Parsoid
+/// HTML
+enum MobileElement {
+    /// The element and its children are left out
+    Skip,
+    /// The children of the element are parsed in its place
+    Unwrap,
+}
+
+impl MobileElement {
+    /// Returns what to do with the element, `None` when it's parsed like in the Parsoid HTML
+    fn of(node: &Handle) -> Option<Self> {
+        let is_container = match node.data {
+            NodeData::Element { ref attrs, .. } => attrs
+                .borrow()
+                .iter()
+                .any(|attr| attr.name.local.as_ref() == "id" && attr.value.as_ref() == "pcs"),
+            _ => false,
+        };
+
+        match element_name(node)?.as_str() {
+            // the title and the description of the page
+            "header" => Some(MobileElement::Skip),
+            "span" if has_class(node, "pcs-edit-section-link-container") => {
+                Some(MobileElement::Skip)
+            }
+            // placeholders of the images that are loaded once they are scrolled into view
+            "span" if has_class(node, "pcs-lazy-load-placeholder") => Some(MobileElement::Skip),
+            // the buttons to expand and collapse a table
+            "div"
+                if has_class(node, "pcs-collapse-table-collapsed-container")
+                    || has_class(node, "pcs-collapse-table-collapsed-bottom") =>
+            {
+                Some(MobileElement::Skip)
+            }
+            "div"
+                if is_container
+                    || has_class(node, "pcs-edit-section-header")
+                    || has_class(node, "pcs-collapse-table-container")
+                    || has_class(node, "pcs-collapse-table-content") =>
+            {
+                Some(MobileElement::Unwrap)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Parses the HTML of a page, as returned by the Parsoid based APIs, into a document
+///
+/// No transforms are applied to the document, see [`crate::transform`] for those
+///
+/// # Example
+///
+/// ```
+/// use wiki_api::{document::Data, parser::parse_document};
+///
+/// let document = parse_document(r#"<section data-mw-section-id="0"><p>Hello</p></section>"#);
+/// assert!(document.nodes.iter().any(|node| node.data == Data::Paragraph));
+/// ```
+pub fn parse_document(html: &str) -> Document {
+    parse_document_with(html, ParserMode::Parsoid)
+}
+
+/// Parses the HTML of a page taken from the output of the wiki the mode stands for. Both modes
+/// result in the same document for the same page
+pub fn parse_document_with(html: &str, mode: ParserMode) -> Document {
+    Document {
+        nodes: WikipediaParser::with_mode(html, mode).nodes(),
+    }
+}
 
 // TODO: remove Parser and replace it with normal functions and helper functions
 pub trait Parser {
@@ -13,9 +424,26 @@ pub trait Parser {
 
 pub struct WikipediaParser {
     nodes: Vec<Raw>,
+    mode: ParserMode,
+    /// Navboxes found in the page, they are parsed into a footer once the rest is parsed
+    navboxes: Vec<Handle>,
 }
 
 impl WikipediaParser {
+    pub fn with_mode(document: &str, mode: ParserMode) -> Self {
+        let mut parser = WikipediaParser {
+            nodes: Vec::new(),
+            mode,
+            navboxes: Vec::new(),
+        };
+
+        let rc_dom = html5ever::parse_document(RcDom::default(), Default::default()).one(document);
+        parser.parse_node(&rc_dom.document, None, None);
+        parser.parse_related_pages();
+
+        parser
+    }
+
     fn parse_node(
         &mut self,
         node: &Handle,
@@ -41,6 +469,16 @@ impl WikipediaParser {
                 ref attrs,
                 ..
             } => {
+                if self.mode.is_mobile() {
+                    match MobileElement::of(node) {
+                        Some(MobileElement::Skip) => return prev,
+                        Some(MobileElement::Unwrap) => {
+                            return self.parse_children(node, parent, prev)
+                        }
+                        None => {}
+                    }
+                }
+
                 let name = name.local.to_string();
                 let attrs: Vec<(String, String)> = attrs
                     .borrow()
@@ -51,11 +489,38 @@ impl WikipediaParser {
                 let data = match name.as_str() {
                     "head" | "style" | "link" => return prev,
 
-                    "table" | "img" | "figure" => {
-                        warn!("unsupported node '{name}'");
+                    "table"
+                        if attrs.iter().any(|(name, value)| {
+                            name.as_str() == "class"
+                                && value.contains("infobox")
+                                && TAXOBOX_CLASSES.iter().any(|class| value.contains(class))
+                        }) =>
+                    {
+                        return Some(self.parse_taxobox(node, parent, prev));
+                    }
+
+                    // article message boxes, the newer ones are divisions instead of tables
+                    "table" | "div" if has_class(node, "ambox") => {
+                        return self.parse_maintenance_notices(node, parent, prev);
+                    }
+
+                    // navboxes are gathered at the end of the page
+                    "table" | "div" if has_class(node, "navbox") => {
+                        self.navboxes.push(node.clone());
                         return prev;
                     }
 
+                    // boxes the wiki lets readers collapse, unless they are left out anyway
+                    "table" | "div"
+                        if has_class(node, "mw-collapsible") && !has_class(node, "noprint") =>
+                    {
+                        return Some(self.parse_collapsible(node, parent, prev));
+                    }
+
+                    "table" => return Some(self.parse_table(node, parent, prev)),
+
+                    "img" | "figure" => return self.parse_image(node, parent, prev),
+
                     "ul" if attrs.iter().any(|(name, value)| {
                         name.as_str() == "class" && value.contains("portalbox")
                     }) =>
@@ -149,7 +614,7 @@ impl WikipediaParser {
 
                     "ol" => Data::OrderedList,
                     "ul" => Data::UnorderedList,
-                    "li" => Data::ListItem,
+                    "li" => self.parse_reference(attrs.iter()).unwrap_or(Data::ListItem),
 
                     "dl" => Data::DescriptionList,
                     "dt" => Data::DescriptionListTerm,
@@ -157,8 +622,56 @@ impl WikipediaParser {
 
                     "b" => Data::Bold,
                     "i" => Data::Italic,
+                    "s" | "del" | "strike" => Data::Strikethrough,
+                    "u" | "ins" => Data::Underline,
+                    "small" => Data::Small,
+                    "mark" => Data::Mark,
+                    "abbr" => Data::Abbreviation {
+                        title: attrs
+                            .iter()
+                            .find(|(name, _)| name.as_str() == "title")
+                            .map(|(_, value)| value.to_owned()),
+                    },
 
                     "p" => Data::Paragraph,
+                    "hr" => Data::HorizontalRule,
+                    "pre" => Data::Preformatted,
+                    "br" => return Some(self.push_node(Data::LineBreak, parent, prev)),
+                    "div"
+                        if attrs.iter().any(|(name, value)| {
+                            name.as_str() == "class"
+                                && value.split_whitespace().any(|class| class == "poem")
+                        }) =>
+                    {
+                        Data::Poem
+                    }
+                    "span"
+                        if attrs.iter().any(|(name, value)| {
+                            name.as_str() == "class" && value.contains("mw-poem-indented")
+                        }) =>
+                    {
+                        Data::PoemIndentation {
+                            level: attrs
+                                .iter()
+                                .find(|(name, _)| name.as_str() == "style")
+                                .and_then(|(_, style)| poem_indentation(style))
+                                .unwrap_or(1),
+                        }
+                    }
+                    "span"
+                        if attrs.iter().any(|(name, value)| {
+                            name.as_str() == "style" && value.contains("line-through")
+                        }) =>
+                    {
+                        Data::Strikethrough
+                    }
+                    "span"
+                        if attrs.iter().any(|(name, value)| {
+                            name.as_str() == "style" && value.contains("underline")
+                        }) =>
+                    {
+                        Data::Underline
+                    }
                     "span" => Data::Span,
 
                     "div"
@@ -177,6 +690,13 @@ impl WikipediaParser {
                         Data::Disambiguation
                     }
 
+                    "a" if attrs.iter().any(|(name, value)| {
+                        name.as_str() == "href" && value.contains("#cite_note-")
+                    }) =>
+                    {
+                        self.parse_citation(attrs.iter()).unwrap_or_default()
+                    }
+
                     "a" if attrs.iter().any(|(name, value)| {
                         name.as_str() == "rel" && value.as_str() == "mw:WikiLink"
                     }) =>
@@ -198,6 +718,10 @@ impl WikipediaParser {
                         self.parse_external_link(attrs.iter()).unwrap_or_default()
                     }
 
+                    "a" if self.mode == ParserMode::Zim && is_archive_link(&attrs) => {
+                        self.parse_wiki_link(attrs.iter()).unwrap_or_default()
+                    }
+
                     "div" => Data::Division,
                     _ => {
                         warn!("unknown node '{name}'");
@@ -206,10 +730,7 @@ impl WikipediaParser {
                 };
 
                 let index = self.push_node(data, parent, prev);
-                let mut prev = None;
-                for child in node.children.borrow().iter() {
-                    prev = self.parse_node(child, Some(index), prev)
-                }
+                self.parse_children(node, Some(index), None);
                 Some(index)
             }
             NodeData::ProcessingInstruction { .. }
@@ -218,29 +739,299 @@ impl WikipediaParser {
         }
     }
 
+    /// Parses the children of the node into the parent, after the `prev` sibling. Returns the
+    /// last parsed sibling
+    ///
+    /// The mobile-html output lists every section next to each other, so its sections are nested
+    /// by the level of their header, just like the Parsoid HTML nests them
+    fn parse_children(
+        &mut self,
+        node: &Handle,
+        parent: Option<usize>,
+        mut prev: Option<usize>,
+    ) -> Option<usize> {
+        // the sections the next ones can be nested into, with the level of their header
+        let mut open_sections: Vec<(usize, usize)> = Vec::new();
+
+        for child in node.children.borrow().iter() {
+            let is_section = element_name(child).as_deref() == Some("section");
+            if !self.mode.is_mobile() || !is_section {
+                prev = self.parse_node(child, parent, prev);
+                continue;
+            }
+
+            // a section without a header closes every section
+            let level = header_level(child).unwrap_or_default();
+            while matches!(open_sections.last(), Some((_, open_level)) if *open_level >= level) {
+                open_sections.pop();
+            }
+
+            let index = match open_sections.last() {
+                Some((section, _)) => {
+                    let last_child = self.nodes[*section].last_child;
+                    self.parse_node(child, Some(*section), last_child)
+                }
+                None => {
+                    prev = self.parse_node(child, parent, prev);
+                    prev
+                }
+            };
+            if let Some(index) = index.filter(|_| level > 0) {
+                open_sections.push((index, level));
+            }
+        }
+        prev
+    }
+
     fn push_node(&mut self, data: Data, parent: Option<usize>, prev: Option<usize>) -> usize {
-        let index = self.nodes.len();
-
-        self.nodes.push(Raw {
-            index,
-            parent,
-            prev,
-            next: None,
-            first_child: None,
-            last_child: None,
-            data,
-        });
-
-        if let Some(parent) = parent {
-            let parent = &mut self.nodes[parent];
-            if parent.first_child.is_none() {
-                parent.first_child = Some(index);
+        push_node(&mut self.nodes, data, parent, prev)
+    }
+
+    /// Pushes a node with the data and parses the children of `node` into it
+    fn push_with_children(
+        &mut self,
+        data: Data,
+        node: &Handle,
+        parent: Option<usize>,
+        prev: Option<usize>,
+    ) -> usize {
+        let index = self.push_node(data, parent, prev);
+        let mut prev = None;
+        for child in node.children.borrow().iter() {
+            prev = self.parse_node(child, Some(index), prev)
+        }
+        index
+    }
+
+    /// Pushes a node with the data and the text as its only child
+    fn push_text(
+        &mut self,
+        data: Data,
+        text: String,
+        parent: Option<usize>,
+        prev: Option<usize>,
+    ) -> usize {
+        let index = self.push_node(data, parent, prev);
+        self.push_node(Data::Text { contents: text }, Some(index), None);
+        index
+    }
+
+    /// Parses the navboxes found in the page into a footer, which is the last child of the root.
+    /// Navboxes without any links are left out, like the footer when none is left
+    fn parse_related_pages(&mut self) {
+        let navboxes: Vec<NavboxRows> = std::mem::take(&mut self.navboxes)
+            .iter()
+            .filter_map(navbox_contents)
+            .collect();
+        if navboxes.is_empty() || self.nodes.is_empty() {
+            return;
+        }
+
+        let footer = self.push_node(Data::RelatedPages, Some(0), self.nodes[0].last_child);
+        let mut prev = None;
+        for (title, rows) in navboxes {
+            prev = Some(self.parse_navbox(title, rows, footer, prev));
+        }
+    }
+
+    /// Parses a navbox into its title and a group for each of its rows. The links of a row are
+    /// separated like on the wiki, so every renderer shows them apart
+    fn parse_navbox(
+        &mut self,
+        title: Option<String>,
+        rows: Vec<(Option<String>, Vec<Handle>)>,
+        parent: usize,
+        prev: Option<usize>,
+    ) -> usize {
+        let index = self.push_node(Data::Navbox, Some(parent), prev);
+
+        let mut prev =
+            title.map(|title| self.push_text(Data::NavboxTitle, title, Some(index), None));
+        for (label, links) in rows {
+            let group = self.push_node(Data::NavboxGroup, Some(index), prev);
+            let mut prev_link = label.map(|label| {
+                let label = self.push_text(Data::NavboxLabel, label, Some(group), None);
+                let separator = Data::Text {
+                    contents: NAVBOX_LABEL_SEPARATOR.to_string(),
+                };
+                self.push_node(separator, Some(group), Some(label))
+            });
+            for (n, link) in links.iter().enumerate() {
+                if n > 0 {
+                    let separator = Data::Text {
+                        contents: NAVBOX_LINK_SEPARATOR.to_string(),
+                    };
+                    prev_link = Some(self.push_node(separator, Some(group), prev_link));
+                }
+                prev_link = self.parse_node(link, Some(group), prev_link);
             }
-            parent.last_child = Some(index);
+            prev = Some(group);
         }
+        index
+    }
 
-        if let Some(prev) = prev {
-            self.nodes[prev].next = Some(index);
+    /// Parses a taxobox table into its headings and rows
+    ///
+    /// A row with a single header cell is a heading, a row with two cells a label and its value.
+    /// Rows with a single data cell span the whole box and have no label. Rows without any text,
+    /// like the ones only containing an image, are left out
+    fn parse_taxobox(
+        &mut self,
+        node: &Handle,
+        parent: Option<usize>,
+        prev: Option<usize>,
+    ) -> usize {
+        let index = self.push_node(Data::Taxobox, parent, prev);
+
+        let mut prev = None;
+        for row in table_rows(node) {
+            let cells: Vec<Handle> = row
+                .children
+                .borrow()
+                .iter()
+                .filter(|child| matches!(element_name(child).as_deref(), Some("th" | "td")))
+                .cloned()
+                .collect();
+            if !cells.iter().any(has_text) {
+                continue;
+            }
+
+            prev = match cells.as_slice() {
+                [heading] if element_name(heading).as_deref() == Some("th") => {
+                    Some(self.push_with_children(Data::TaxoboxHeading, heading, Some(index), prev))
+                }
+                [value] => {
+                    let row = self.push_node(Data::TaxoboxRow, Some(index), prev);
+                    self.push_with_children(Data::TaxoboxValue, value, Some(row), None);
+                    Some(row)
+                }
+                [label, value] => {
+                    let row = self.push_node(Data::TaxoboxRow, Some(index), prev);
+                    let label = self.push_with_children(Data::TaxoboxLabel, label, Some(row), None);
+                    self.push_with_children(Data::TaxoboxValue, value, Some(row), Some(label));
+                    Some(row)
+                }
+                _ => {
+                    trace!("ignoring taxobox row with '{}' cells", cells.len());
+                    prev
+                }
+            };
+        }
+
+        index
+    }
+
+    /// Parses a maintenance template into a notice holding its compact text. A "multiple issues"
+    /// template results in a notice for each of the issues it lists. Returns the last parsed
+    /// sibling, templates without any text are left out
+    fn parse_maintenance_notices(
+        &mut self,
+        node: &Handle,
+        parent: Option<usize>,
+        mut prev: Option<usize>,
+    ) -> Option<usize> {
+        let nested = nested_maintenance_templates(node);
+        if !nested.is_empty() {
+            for template in nested {
+                prev = self.parse_maintenance_notices(&template, parent, prev);
+            }
+            return prev;
+        }
+
+        let text =
+            compact_text(&find_with_class(node, "mbox-text").unwrap_or_else(|| node.clone()));
+        if text.is_empty() {
+            trace!("ignoring maintenance template without text");
+            return prev;
+        }
+
+        let index = self.push_node(Data::MaintenanceNotice, parent, prev);
+        self.push_node(Data::Text { contents: text }, Some(index), None);
+        Some(index)
+    }
+
+    /// Parses an image or a figure into a single image, captioned with the caption of the
+    /// figure or the alternative text of the image. Returns the last parsed sibling, images
+    /// without a source are left out
+    fn parse_image(
+        &mut self,
+        node: &Handle,
+        parent: Option<usize>,
+        prev: Option<usize>,
+    ) -> Option<usize> {
+        let (src, alt) = match find_image(node) {
+            Some(image) => image,
+            None => {
+                trace!("ignoring image without a source");
+                return prev;
+            }
+        };
+        let caption = find_element(node, "figcaption")
+            .map(|caption| compact_text(&caption))
+            .filter(|caption| !caption.is_empty())
+            .or(alt);
+        Some(self.push_node(Data::Image { src, caption }, parent, prev))
+    }
+
+    /// Parses a table into its caption and rows of cells. Cells spanning several columns or
+    /// rows take up a single cell, rows without any cell are left out
+    /// Parses a collapsible box into a node with its summary, the box itself is parsed into it
+    fn parse_collapsible(
+        &mut self,
+        node: &Handle,
+        parent: Option<usize>,
+        prev: Option<usize>,
+    ) -> usize {
+        let data = Data::Collapsible {
+            summary: collapsible_summary(node),
+            collapsed: COLLAPSED_CLASSES.iter().any(|class| has_class(node, class)),
+        };
+        let index = self.push_node(data, parent, prev);
+
+        match element_name(node).as_deref() {
+            Some("table") => {
+                self.parse_table(node, Some(index), None);
+            }
+            _ => {
+                self.parse_children(node, Some(index), None);
+            }
+        }
+        index
+    }
+
+    fn parse_table(&mut self, node: &Handle, parent: Option<usize>, prev: Option<usize>) -> usize {
+        let index = self.push_node(Data::Table, parent, prev);
+
+        let mut prev = node
+            .children
+            .borrow()
+            .iter()
+            .find(|child| element_name(child).as_deref() == Some("caption"))
+            .map(|caption| self.push_with_children(Data::TableCaption, caption, Some(index), None));
+        for row in table_rows(node) {
+            let cells: Vec<Handle> = row
+                .children
+                .borrow()
+                .iter()
+                .filter(|child| matches!(element_name(child).as_deref(), Some("th" | "td")))
+                .cloned()
+                .collect();
+            if cells.is_empty() {
+                continue;
+            }
+
+            let row = self.push_node(Data::TableRow, Some(index), prev);
+            let mut prev_cell = None;
+            for cell in cells {
+                let header = element_name(&cell).as_deref() == Some("th");
+                prev_cell = Some(self.push_with_children(
+                    Data::TableCell { header },
+                    &cell,
+                    Some(row),
+                    prev_cell,
+                ));
+            }
+            prev = Some(row);
         }
 
         index
@@ -275,6 +1066,33 @@ impl WikipediaParser {
         })
     }
 
+    fn parse_citation<'a>(
+        &self,
+        mut attrs: impl Iterator<Item = &'a (String, String)>,
+    ) -> Option<Data> {
+        let note = attrs
+            .find(|(name, _)| name.as_str() == "href")
+            .and_then(|(_, value)| value.split_once('#'))
+            .map(|(_, note)| note.to_owned())?;
+
+        Some(Data::Citation { note, number: 0 })
+    }
+
+    fn parse_reference<'a>(
+        &self,
+        mut attrs: impl Iterator<Item = &'a (String, String)>,
+    ) -> Option<Data> {
+        let note = attrs
+            .find(|(name, _)| name.as_str() == "id")
+            .map(|(_, value)| value)
+            .filter(|value| value.starts_with("cite_note-"))?;
+
+        Some(Data::Reference {
+            note: note.to_owned(),
+            number: 0,
+        })
+    }
+
     fn parse_wiki_link<'a>(
         &mut self,
         mut attrs: impl Iterator<Item = &'a (String, String)>,
@@ -338,12 +1156,7 @@ impl WikipediaParser {
 
 impl Parser for WikipediaParser {
     fn parse_document(document: &str) -> Self {
-        let mut parser = WikipediaParser { nodes: Vec::new() };
-
-        let rc_dom = parse_document(RcDom::default(), Default::default()).one(document);
-        parser.parse_node(&rc_dom.document, None, None);
-
-        parser
+        WikipediaParser::with_mode(document, ParserMode::Parsoid)
     }
 
     fn nodes(self) -> Vec<Raw> {
@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+
+use crate::document::{Data, Document};
+
+/// Namespaces of pages that are not articles, links to them are not listed
+const NON_ARTICLE_NAMESPACES: [&str; 12] = [
+    "Category",
+    "Draft",
+    "File",
+    "Help",
+    "Image",
+    "Module",
+    "Portal",
+    "Special",
+    "Template",
+    "User",
+    "Wikipedia",
+    "WP",
+];
+
+/// Articles listed under one heading of a portal page, like its selected article or its
+/// featured biography
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortalSection {
+    /// Text of the heading, empty for the articles before the first heading
+    pub heading: String,
+    /// Titles of the linked articles, in the order they appear
+    pub articles: Vec<String>,
+}
+
+/// Returns whether the title belongs to an article and not to a page in another namespace
+fn is_article(title: &str) -> bool {
+    match title.split_once(':') {
+        Some((namespace, _)) => {
+            !NON_ARTICLE_NAMESPACES.contains(&namespace) && !namespace.ends_with("talk")
+        }
+        None => true,
+    }
+}
+
+/// Collects the articles a portal page links to, grouped by the heading they appear under
+///
+/// Headings without any links to articles are left out
+pub fn portal_sections(document: &Document) -> Vec<PortalSection> {
+    let mut sections = vec![PortalSection {
+        heading: String::new(),
+        articles: Vec::new(),
+    }];
+
+    for node in document
+        .nth(0)
+        .into_iter()
+        .flat_map(|root| root.descendants())
+    {
+        match node.data() {
+            Data::Header { .. } => {
+                let heading: String = node
+                    .descendants()
+                    .filter_map(|node| match node.data() {
+                        Data::Text { contents } => Some(contents.as_str()),
+                        _ => None,
+                    })
+                    .collect();
+                sections.push(PortalSection {
+                    heading: heading.split_whitespace().collect::<Vec<&str>>().join(" "),
+                    articles: Vec::new(),
+                });
+            }
+            Data::WikiLink { href, title } => {
+                let title = title
+                    .clone()
+                    .unwrap_or_else(|| href.trim_start_matches("./").replace('_', " "));
+                let section = sections.last_mut().unwrap();
+                if is_article(&title) && !section.articles.contains(&title) {
+                    section.articles.push(title);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    sections.retain(|section| !section.articles.is_empty());
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{portal_sections, PortalSection};
+    use crate::{
+        document::Document,
+        parser::{Parser, WikipediaParser},
+    };
+
+    #[test]
+    fn sections() {
+        let document = Document {
+            nodes: WikipediaParser::parse_document(
+                r#"<section data-mw-section-id="0"><p>The <a rel="mw:WikiLink" href="./Portal:Science" title="Portal:Science">science portal</a> is about <a rel="mw:WikiLink" href="./Science" title="Science">science</a>.</p></section><section data-mw-section-id="1"><h2 id="Selected_article">Selected article</h2><p><a rel="mw:WikiLink" href="./Photosynthesis" title="Photosynthesis">Photosynthesis</a> is used by <a rel="mw:WikiLink" href="./Plant" title="Plant">plants</a>. <a rel="mw:WikiLink" href="./Photosynthesis" title="Photosynthesis">More</a></p></section><section data-mw-section-id="2"><h2 id="Categories">Categories</h2><p><a rel="mw:WikiLink" href="./Category:Science" title="Category:Science">Science</a></p></section>"#,
+            )
+            .nodes(),
+        };
+
+        assert_eq!(
+            portal_sections(&document),
+            vec![
+                PortalSection {
+                    heading: "".to_string(),
+                    articles: vec!["Science".to_string()],
+                },
+                PortalSection {
+                    heading: "Selected article".to_string(),
+                    articles: vec!["Photosynthesis".to_string(), "Plant".to_string()],
+                },
+            ]
+        );
+    }
+}
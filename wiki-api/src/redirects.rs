@@ -0,0 +1,130 @@
+use anyhow::{anyhow, Result};
+
+/// At most this many redirects are followed from the requested title
+pub const MAX_REDIRECTS: usize = 8;
+
+/// Normalizes a title the way the wiki does, so `united_states` and `United states` are the same
+/// page
+pub fn normalize_title(title: &str) -> String {
+    let title = title.replace('_', " ");
+    let mut chars = title.trim().chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Returns the `from` and `to` titles of the redirects listed in a response
+pub(crate) fn parse_redirects(redirects: Option<&serde_json::Value>) -> Vec<(String, String)> {
+    redirects
+        .and_then(|x| x.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|redirect| {
+            let from = redirect.get("from").and_then(|x| x.as_str())?;
+            let to = redirect.get("to").and_then(|x| x.as_str())?;
+            Some((from.to_string(), to.to_string()))
+        })
+        .collect()
+}
+
+/// Follows the redirects starting at the title and returns the titles the chain went through,
+/// the title first and without the page it ended at. The chain is empty when the title isn't a
+/// redirect
+///
+/// # Error
+///
+/// This function returns an error when the redirects lead back to a title of the chain, or when
+/// the chain is longer than [`MAX_REDIRECTS`]
+pub fn redirect_chain(title: &str, redirects: &[(String, String)]) -> Result<Vec<String>> {
+    let mut chain: Vec<String> = Vec::new();
+    let mut current = normalize_title(title);
+
+    while let Some((from, to)) = redirects
+        .iter()
+        .find(|(from, _)| normalize_title(from) == current)
+    {
+        if chain.len() == MAX_REDIRECTS {
+            return Err(anyhow!(
+                "'{}' redirects more than {} times",
+                title,
+                MAX_REDIRECTS
+            ));
+        }
+        chain.push(from.clone());
+
+        current = normalize_title(to);
+        if chain.iter().any(|title| normalize_title(title) == current) {
+            return Err(anyhow!("the redirects of '{}' form a loop", title));
+        }
+    }
+    Ok(chain)
+}
+
+/// Returns the title the redirects listed by the action API started at, `None` when the page
+/// wasn't redirected to
+///
+/// # Error
+///
+/// This function returns an error when the redirects form a loop or a too long chain (See
+/// [`redirect_chain`] for more)
+pub(crate) fn redirect_source(redirects: &[(String, String)]) -> Result<Option<String>> {
+    if redirects.is_empty() {
+        return Ok(None);
+    }
+
+    // the chain starts at the only title that isn't redirected to
+    let (source, _) = redirects
+        .iter()
+        .find(|(from, _)| {
+            !redirects
+                .iter()
+                .any(|(_, to)| normalize_title(to) == normalize_title(from))
+        })
+        .ok_or_else(|| anyhow!("the redirects form a loop"))?;
+    redirect_chain(source, redirects)?;
+    Ok(Some(source.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_title, redirect_chain, redirect_source, MAX_REDIRECTS};
+
+    fn redirects(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(from, to)| (from.to_string(), to.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn normalize() {
+        assert_eq!(normalize_title("rust_language"), "Rust language");
+        assert_eq!(normalize_title(" Rust language "), "Rust language");
+        assert_eq!(normalize_title("éclair"), "Éclair");
+        assert_eq!(normalize_title(""), "");
+    }
+
+    #[test]
+    fn chains() {
+        let single = redirects(&[("USA", "United States")]);
+        assert_eq!(redirect_chain("USA", &single).unwrap(), ["USA"]);
+        assert!(redirect_chain("United States", &single).unwrap().is_empty());
+
+        let chain = redirects(&[("U.S.A.", "USA"), ("USA", "United States")]);
+        assert_eq!(redirect_chain("U.S.A.", &chain).unwrap(), ["U.S.A.", "USA"]);
+
+        let endless = redirects(&[("A", "B"), ("B", "A")]);
+        assert!(redirect_chain("a", &endless).is_err());
+
+        assert_eq!(redirect_source(&chain).unwrap().as_deref(), Some("U.S.A."));
+        assert!(redirect_source(&[]).unwrap().is_none());
+        assert!(redirect_source(&endless).is_err());
+
+        let long: Vec<(String, String)> = (0..=MAX_REDIRECTS)
+            .map(|n| (n.to_string(), (n + 1).to_string()))
+            .collect();
+        assert!(redirect_chain("0", &long).is_err());
+        assert_eq!(redirect_chain("1", &long).unwrap().len(), MAX_REDIRECTS);
+    }
+}
@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::document::{Data, Document};
+
+/// Numbers of the references of a document, keyed by the anchor of their note
+///
+/// References are numbered in the order they are first cited in the document, starting at 1.
+/// Citing the same note again reuses its number. Notes that are never cited are numbered after
+/// all the cited ones, in the order they appear in the reference list
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReferenceRegistry {
+    numbers: HashMap<String, usize>,
+    notes: Vec<String>,
+}
+
+impl ReferenceRegistry {
+    /// Collects the citations and references of the document
+    pub fn collect(document: &Document) -> Self {
+        let mut registry = ReferenceRegistry::default();
+
+        for raw in document.nodes.iter() {
+            if let Data::Citation { ref note, .. } = raw.data {
+                registry.register(note);
+            }
+        }
+        for raw in document.nodes.iter() {
+            if let Data::Reference { ref note, .. } = raw.data {
+                registry.register(note);
+            }
+        }
+
+        registry
+    }
+
+    fn register(&mut self, note: &str) {
+        if self.numbers.contains_key(note) {
+            return;
+        }
+        self.notes.push(note.to_string());
+        self.numbers.insert(note.to_string(), self.notes.len());
+    }
+
+    /// Returns the number of the note with the anchor
+    pub fn number(&self, note: &str) -> Option<usize> {
+        self.numbers.get(note).copied()
+    }
+
+    /// Returns the anchor of the note with the number
+    pub fn note(&self, number: usize) -> Option<&str> {
+        number
+            .checked_sub(1)
+            .and_then(|index| self.notes.get(index))
+            .map(|note| note.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.notes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty()
+    }
+}
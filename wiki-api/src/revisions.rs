@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use reqwest::Client;
+use url::Url;
+
+use crate::{http::send, Endpoint, TITLES_PER_REQUEST};
+
+/// Returns the latest revision of every page listed in a query response, by the title of the
+/// page. Missing pages are left out
+fn parse_latest_revisions(response: &serde_json::Value) -> HashMap<String, usize> {
+    response
+        .get("query")
+        .and_then(|x| x.get("pages"))
+        .and_then(|x| x.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|page| {
+            let title = page.get("title")?.as_str()?;
+            let revision = page
+                .get("revisions")?
+                .as_array()?
+                .first()?
+                .get("revid")?
+                .as_u64()?;
+            Some((title.to_string(), revision as usize))
+        })
+        .collect()
+}
+
+/// Fetches the id of the latest revision of the given pages
+///
+/// Pages that don't exist are missing in the returned map
+///
+/// # Error
+///
+/// This function returns an error when one of the following things happens:
+/// - The request to the server could not be made
+/// - The server returned an error
+/// - The returned result could not be interpreted as json
+pub async fn fetch_latest_revisions(
+    endpoint: Endpoint,
+    titles: &[String],
+) -> Result<HashMap<String, usize>> {
+    let mut revisions = HashMap::new();
+
+    for titles in titles.chunks(TITLES_PER_REQUEST) {
+        let response = send(
+            Client::new()
+                .get(endpoint.clone())
+                .query(&[
+                    ("action", "query"),
+                    ("format", "json"),
+                    ("formatversion", "2"),
+                    ("prop", "revisions"),
+                    ("rvprop", "ids"),
+                ])
+                .query(&[("titles", titles.join("|"))]),
+        )
+        .await
+        .context("failed sending the request")?
+        .error_for_status()
+        .context("the server returned an error")?;
+
+        let res_json = response.api_json()?;
+
+        revisions.extend(parse_latest_revisions(&res_json));
+    }
+
+    Ok(revisions)
+}
+
+/// Returns the url of the page showing what changed on the page since the revision, up to the
+/// latest revision
+pub fn diff_url(endpoint: &Endpoint, title: &str, revision: usize) -> Result<Url> {
+    let mut url = endpoint
+        .join("index.php")
+        .context("the endpoint can't be turned into the index url")?;
+    url.query_pairs_mut()
+        .append_pair("title", title)
+        .append_pair("diff", "cur")
+        .append_pair("oldid", &revision.to_string());
+    Ok(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{diff_url, parse_latest_revisions};
+    use crate::Endpoint;
+
+    #[test]
+    fn latest_revisions() {
+        let response = serde_json::json!({
+            "query": {
+                "pages": [
+                    { "pageid": 1, "title": "Rust", "revisions": [{ "revid": 42, "parentid": 41 }] },
+                    { "title": "Missing page", "missing": true }
+                ]
+            }
+        });
+        assert_eq!(
+            parse_latest_revisions(&response),
+            HashMap::from([("Rust".to_string(), 42)])
+        );
+    }
+
+    #[test]
+    fn diff() {
+        let endpoint = Endpoint::parse("https://en.wikipedia.org/w/api.php").unwrap();
+        assert_eq!(
+            diff_url(&endpoint, "Rust (language)", 42).unwrap().as_str(),
+            "https://en.wikipedia.org/w/index.php?title=Rust+%28language%29&diff=cur&oldid=42"
+        );
+    }
+}
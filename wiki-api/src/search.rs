@@ -1,18 +1,22 @@
 use anyhow::{anyhow, Context, Result};
 
-use reqwest::{Client, Response};
-use serde_repr::Deserialize_repr;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
 use std::fmt::Debug;
 use std::fmt::Display;
 use std::str::FromStr;
 
-use crate::Endpoint;
+use crate::{
+    http::{send, Response},
+    Endpoint,
+};
 
 use super::languages::Language;
 
 /// A finished search containing the found results and additional optional information regarding
 /// the search
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Search {
     /// The found results in this batch
     pub results: Vec<SearchResult>,
@@ -65,7 +69,7 @@ impl Debug for Search {
 }
 
 /// Contains general informations about the search
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SearchInfo {
     /// Whether the search is complete and no more results are available
     pub complete: bool,
@@ -89,19 +93,25 @@ pub struct SearchInfo {
 ///
 /// # Example
 ///
-/// ```
+/// ```no_run
+/// # use wiki_api::search::Search;
+/// # async fn run(search: Search) -> anyhow::Result<()> {
 /// // This will continue the already completed search
-/// let continue_data = search.continue_data()?;
-/// let continued_search = Search::builder()
-///     .query(continue_data.query)
-///     .endpoint(continue_data.endpoint)
-///     .langauge(continue_data.language)
-///     .offset(continue_data.offset)
-///     .search()?;
+/// if let Some(continue_data) = search.continue_data() {
+///     let continued_search = Search::builder()
+///         .query(continue_data.query)
+///         .endpoint(continue_data.endpoint)
+///         .language(continue_data.language)
+///         .offset(continue_data.offset)
+///         .search()
+///         .await?;
+/// }
+/// # Ok(())
+/// # }
 /// ```
 ///
 /// [`Search::continue_data`]: Search::continue_data
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchContinue {
     // TODO: SearchContinue::continue() creates a Search::builder() and continues the search
     // WARN: Do the properties and settings still exist?
@@ -117,7 +127,7 @@ pub struct SearchContinue {
 
 /// A single search result containing additional optional properties if they were added in the
 /// search
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SearchResult {
     /// Namespace where the page belongs to
     pub namespace: Namespace,
@@ -152,7 +162,7 @@ pub struct SearchResult {
 /// They each have a unique number (0 to 15) and are grouped in subject/talk pairs
 ///
 /// Read more in the [MediaWiki API docs](https://www.mediawiki.org/wiki/Manual:Namespace)
-#[derive(Deserialize_repr, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize_repr, Deserialize_repr, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[repr(usize)]
 pub enum Namespace {
     Main = 0,
@@ -224,6 +234,7 @@ impl FromStr for Namespace {
 #[cfg(test)]
 mod tests {
     use super::Namespace;
+    use std::str::FromStr;
     #[test]
     fn test_namespace_display_and_str() {
         macro_rules! test_namespace {
@@ -231,13 +242,13 @@ mod tests {
                 let namespace_str = format!("{}", Namespace::$namespace);
                 assert_eq!(
                     Namespace::from_str(&namespace_str),
-                    Some(Namespace::$namespace)
+                    Ok(Namespace::$namespace)
                 );
 
                 let namespace_str = format!("{}", Namespace::$namespace_talk);
                 assert_eq!(
                     Namespace::from_str(&namespace_str),
-                    Some(Namespace::$namespace_talk)
+                    Ok(Namespace::$namespace_talk)
                 );
             };
         }
@@ -449,7 +460,7 @@ pub struct SearchBuilder<Q, E, L> {
     query: Q,
     endpoint: E,
     language: L,
-    namespace: Option<Namespace>,
+    namespaces: Vec<Namespace>,
     limit: Option<usize>,
     offset: Option<usize>,
     qiprofile: Option<QiProfile>,
@@ -468,7 +479,7 @@ impl<E, L> SearchBuilder<NoQuery, E, L> {
             query: WithQuery(query.into()),
             endpoint: self.endpoint,
             language: self.language,
-            namespace: self.namespace,
+            namespaces: self.namespaces,
             limit: self.limit,
             offset: self.offset,
             qiprofile: self.qiprofile,
@@ -489,7 +500,7 @@ impl<Q, L> SearchBuilder<Q, NoEndpoint, L> {
             query: self.query,
             endpoint: WithEndpoint(endpoint),
             language: self.language,
-            namespace: self.namespace,
+            namespaces: self.namespaces,
             limit: self.limit,
             offset: self.offset,
             qiprofile: self.qiprofile,
@@ -510,7 +521,7 @@ impl<Q, E> SearchBuilder<Q, E, NoLanguage> {
             query: self.query,
             endpoint: self.endpoint,
             language: WithLanguage(language),
-            namespace: self.namespace,
+            namespaces: self.namespaces,
             limit: self.limit,
             offset: self.offset,
             qiprofile: self.qiprofile,
@@ -525,9 +536,16 @@ impl<Q, E> SearchBuilder<Q, E, NoLanguage> {
 }
 
 impl<Q, E, L> SearchBuilder<Q, E, L> {
-    /// Search only in this specific namespace
+    /// Search only in this specific namespace. Can be called multiple times to search in several
+    /// namespaces at once
     pub fn namespace(mut self, namespace: Namespace) -> Self {
-        self.namespace = Some(namespace);
+        self.namespaces.push(namespace);
+        self
+    }
+
+    /// Search in all of these namespaces, every result tells in which one it was found
+    pub fn namespaces(mut self, namespaces: impl IntoIterator<Item = Namespace>) -> Self {
+        self.namespaces.extend(namespaces);
         self
     }
 
@@ -626,13 +644,18 @@ impl SearchBuilder<WithQuery, WithEndpoint, WithLanguage> {
     ///
     /// # Example
     ///
-    /// ```
+    /// ```no_run
+    /// # use wiki_api::{languages::Language, search::Search, Endpoint};
+    /// # async fn run() -> anyhow::Result<()> {
     /// // This searches for the pages containing 'meaning' in the english wikipedia
     /// let search = Search::builder()
     ///     .query("meaning")
-    ///     .endpoint(Url::from("https://en.wikipedia.org/w/api.php")?)
+    ///     .endpoint(Endpoint::parse("https://en.wikipedia.org/w/api.php")?)
     ///     .language(Language::English)
-    ///     .search()?;
+    ///     .search()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
     /// ```
     ///
     /// # Error
@@ -643,17 +666,18 @@ impl SearchBuilder<WithQuery, WithEndpoint, WithLanguage> {
     /// - The returned result could not interpreted as a `Search`
     pub async fn search(self) -> Result<Search> {
         async fn action_query(params: Vec<(&str, String)>, endpoint: Endpoint) -> Result<Response> {
-            Client::new()
-                .get(endpoint)
-                .query(&[
-                    ("action", "query"),
-                    ("format", "json"),
-                    ("formatversion", "2"),
-                ])
-                .query(&params)
-                .send()
-                .await
-                .context("failed sending the request")
+            send(
+                Client::new()
+                    .get(endpoint)
+                    .query(&[
+                        ("action", "query"),
+                        ("format", "json"),
+                        ("formatversion", "2"),
+                    ])
+                    .query(&params),
+            )
+            .await
+            .context("failed sending the request")
         }
 
         let mut params = vec![
@@ -661,8 +685,13 @@ impl SearchBuilder<WithQuery, WithEndpoint, WithLanguage> {
             ("srsearch", self.query.0.clone()),
         ];
 
-        if let Some(namespace) = self.namespace {
-            params.push(("srnamespace", namespace.to_string()));
+        if !self.namespaces.is_empty() {
+            let namespaces: Vec<String> = self
+                .namespaces
+                .iter()
+                .map(|namespace| (*namespace as usize).to_string())
+                .collect();
+            params.push(("srnamespace", namespaces.join("|")));
         }
 
         if let Some(limit) = self.limit {
@@ -716,13 +745,7 @@ impl SearchBuilder<WithQuery, WithEndpoint, WithLanguage> {
             .error_for_status()
             .context("the server returned an error")?;
 
-        let res_json: serde_json::Value = serde_json::from_str(
-            &response
-                .text()
-                .await
-                .context("failed reading the response")?,
-        )
-        .context("failed interpreting the response as json")?;
+        let res_json = response.api_json()?;
 
         let continue_offset = res_json
             .get("continue")
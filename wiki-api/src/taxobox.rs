@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+
+use crate::document::{Data, Document, Node};
+
+/// Rows of a taxobox listed under one of its headings, like the scientific classification of a
+/// species or the properties of a chemical compound
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaxoboxGroup {
+    /// Text of the heading, empty for the rows before the first heading
+    pub heading: String,
+    /// Labels and their values, in the order they appear. Rows spanning the whole box have an
+    /// empty label
+    pub rows: Vec<(String, String)>,
+}
+
+/// Returns the text of the node, with every run of whitespace collapsed into a single space
+pub fn text(node: Node<'_>) -> String {
+    let text: String = node
+        .descendants()
+        .filter_map(|node| match node.data() {
+            Data::Text { contents } => Some(contents.as_str()),
+            _ => None,
+        })
+        .collect();
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// Returns the first taxobox of the document
+pub fn find_taxobox(document: &Document) -> Option<Node<'_>> {
+    document
+        .nth(0)?
+        .descendants()
+        .find(|node| matches!(node.data(), Data::Taxobox))
+}
+
+/// Collects the rows of the first taxobox of the document, grouped by the heading they appear
+/// under. Returns `None` when the document has no taxobox
+///
+/// Headings without any rows, like the name of the species, are kept
+pub fn taxobox_groups(document: &Document) -> Option<Vec<TaxoboxGroup>> {
+    let taxobox = find_taxobox(document)?;
+
+    let mut groups = vec![TaxoboxGroup {
+        heading: String::new(),
+        rows: Vec::new(),
+    }];
+    for child in taxobox.children() {
+        match child.data() {
+            Data::TaxoboxHeading => groups.push(TaxoboxGroup {
+                heading: text(child),
+                rows: Vec::new(),
+            }),
+            Data::TaxoboxRow => {
+                let cell = |data: Data| {
+                    child
+                        .children()
+                        .find(|cell| cell.data() == &data)
+                        .map(text)
+                        .unwrap_or_default()
+                };
+                groups
+                    .last_mut()
+                    .unwrap()
+                    .rows
+                    .push((cell(Data::TaxoboxLabel), cell(Data::TaxoboxValue)));
+            }
+            _ => {}
+        }
+    }
+
+    if groups[0].rows.is_empty() {
+        groups.remove(0);
+    }
+    Some(groups)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{taxobox_groups, TaxoboxGroup};
+    use crate::{
+        document::Document,
+        parser::{Parser, WikipediaParser},
+    };
+
+    fn parse(html: &str) -> Document {
+        Document {
+            nodes: WikipediaParser::parse_document(html).nodes(),
+        }
+    }
+
+    #[test]
+    fn groups() {
+        let document = parse(
+            r#"<section data-mw-section-id="0"><table class="infobox biota"><tbody><tr><th colspan="2">Red fox</th></tr><tr><td colspan="2"><img src="fox.jpg"></td></tr><tr><th colspan="2">Scientific classification</th></tr><tr><td>Kingdom:</td><td><a rel="mw:WikiLink" href="./Animal" title="Animal">Animalia</a></td></tr><tr><td>Genus:</td><td><i>Vulpes</i></td></tr><tr><th colspan="2">Binomial name</th></tr><tr><td colspan="2"><b><i>Vulpes vulpes</i></b></td></tr></tbody></table><p>The red fox is the largest of the true foxes.</p></section>"#,
+        );
+
+        assert_eq!(
+            taxobox_groups(&document),
+            Some(vec![
+                TaxoboxGroup {
+                    heading: "Red fox".to_string(),
+                    rows: Vec::new(),
+                },
+                TaxoboxGroup {
+                    heading: "Scientific classification".to_string(),
+                    rows: vec![
+                        ("Kingdom:".to_string(), "Animalia".to_string()),
+                        ("Genus:".to_string(), "Vulpes".to_string()),
+                    ],
+                },
+                TaxoboxGroup {
+                    heading: "Binomial name".to_string(),
+                    rows: vec![("".to_string(), "Vulpes vulpes".to_string())],
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn other_tables() {
+        let document = parse(
+            r#"<section data-mw-section-id="0"><table class="infobox vcard"><tbody><tr><th>Born</th><td>1900</td></tr></tbody></table></section>"#,
+        );
+
+        assert_eq!(taxobox_groups(&document), None);
+    }
+}
@@ -0,0 +1,693 @@
+use serde::Deserialize;
+use tracing::debug;
+
+use crate::{
+    document::{push_node, Data, Document, Node, Raw},
+    references::ReferenceRegistry,
+    taxobox::text,
+    units::{convert_units, UnitSystem},
+};
+
+/// A post-processing step that is run over a parsed document
+///
+/// Transforms are applied in order after the document has been parsed and before it is handed
+/// to a renderer. Each transform receives the output of the previous one.
+pub trait DocumentTransform {
+    /// Name of the transform, used for logging
+    fn name(&self) -> &'static str;
+
+    /// Transforms the document and returns the result
+    fn transform(&self, document: Document) -> Document;
+}
+
+/// The transforms that are shipped with wiki-api
+///
+/// These can be selected by name (in snake_case) from a configuration file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BuiltinTransform {
+    /// Collapses whitespace inside of text and removes whitespace-only text between block
+    /// elements. See [`MergeWhitespace`]
+    MergeWhitespace,
+    /// Removes inline and block elements without any visible text. See [`StripEmptyElements`]
+    StripEmptyElements,
+    /// Removes sections that consist only of a header. See [`StripEmptySections`]
+    StripEmptySections,
+    /// Puts the infoboxes into collapsed boxes. See [`CollapseInfoboxes`]
+    CollapseInfoboxes,
+}
+
+impl BuiltinTransform {
+    /// The transforms applied when nothing else is configured
+    pub fn defaults() -> Vec<BuiltinTransform> {
+        vec![
+            BuiltinTransform::MergeWhitespace,
+            BuiltinTransform::StripEmptyElements,
+            BuiltinTransform::StripEmptySections,
+        ]
+    }
+}
+
+impl DocumentTransform for BuiltinTransform {
+    fn name(&self) -> &'static str {
+        match self {
+            BuiltinTransform::MergeWhitespace => MergeWhitespace.name(),
+            BuiltinTransform::StripEmptyElements => StripEmptyElements.name(),
+            BuiltinTransform::StripEmptySections => StripEmptySections.name(),
+            BuiltinTransform::CollapseInfoboxes => CollapseInfoboxes.name(),
+        }
+    }
+
+    fn transform(&self, document: Document) -> Document {
+        match self {
+            BuiltinTransform::MergeWhitespace => MergeWhitespace.transform(document),
+            BuiltinTransform::StripEmptyElements => StripEmptyElements.transform(document),
+            BuiltinTransform::StripEmptySections => StripEmptySections.transform(document),
+            BuiltinTransform::CollapseInfoboxes => CollapseInfoboxes.transform(document),
+        }
+    }
+}
+
+/// Applies the transforms in order and returns the resulting document
+pub fn apply_transforms<'a, T>(
+    document: Document,
+    transforms: impl IntoIterator<Item = &'a T>,
+) -> Document
+where
+    T: DocumentTransform + 'a,
+{
+    transforms
+        .into_iter()
+        .fold(document, |document, transform| {
+            let node_count = document.nodes.len();
+            let document = transform.transform(document);
+            debug!(
+                "transform '{}': {} -> {} nodes",
+                transform.name(),
+                node_count,
+                document.nodes.len()
+            );
+            document
+        })
+}
+
+/// Returns whether the node is, or contains, any visible text
+fn has_text(node: Node<'_>) -> bool {
+    std::iter::once(node)
+        .chain(node.descendants())
+        .any(|descendant| match descendant.data() {
+            Data::Text { contents } => !contents.trim().is_empty(),
+            _ => false,
+        })
+}
+
+/// Returns whether the node is rendered as a block
+fn is_block(data: &Data) -> bool {
+    matches!(
+        data,
+        Data::Section { .. }
+            | Data::Header { .. }
+            | Data::Division
+            | Data::Paragraph
+            | Data::HorizontalRule
+            | Data::Preformatted
+            | Data::Poem
+            | Data::Hatnote
+            | Data::RedirectMessage
+            | Data::Disambiguation
+            | Data::MaintenanceNotice
+            | Data::Collapsible { .. }
+            | Data::RelatedPages
+            | Data::Navbox
+            | Data::NavboxTitle
+            | Data::NavboxGroup
+            | Data::Taxobox
+            | Data::TaxoboxHeading
+            | Data::TaxoboxRow
+            | Data::TaxoboxLabel
+            | Data::TaxoboxValue
+            | Data::Table
+            | Data::TableCaption
+            | Data::TableRow
+            | Data::TableCell { .. }
+            | Data::OrderedList
+            | Data::UnorderedList
+            | Data::ListItem
+            | Data::Reference { .. }
+            | Data::DescriptionList
+            | Data::DescriptionListTerm
+            | Data::DerscriptionListDescription
+    )
+}
+
+/// Returns whether any of the ancestors of the node matches
+fn has_ancestor(node: Node<'_>, matches: impl Fn(&Data) -> bool) -> bool {
+    let mut current = node.parent();
+    while let Some(parent) = current {
+        if matches(parent.data()) {
+            return true;
+        }
+        current = parent.parent();
+    }
+    false
+}
+
+/// Returns whether the node is inside of a preformatted block, where whitespace is significant
+fn is_preformatted(node: Node<'_>) -> bool {
+    has_ancestor(node, |data| matches!(data, Data::Preformatted))
+}
+
+/// Returns whether the node is inside of a poem, where non-breaking spaces indent the lines
+fn is_in_poem(node: Node<'_>) -> bool {
+    has_ancestor(node, |data| matches!(data, Data::Poem))
+}
+
+const NON_BREAKING_SPACE: char = '\u{a0}';
+
+/// Returns whether the non-breaking spaces of the text node are kept, they indent the lines of
+/// poems
+fn keeps_non_breaking_spaces(node: Node<'_>) -> bool {
+    match node.data() {
+        Data::Text { contents } => contents.contains(NON_BREAKING_SPACE) && is_in_poem(node),
+        _ => false,
+    }
+}
+
+/// Collapses every run of whitespace in text nodes into a single space and removes
+/// whitespace-only text nodes that sit inside of, or next to, block elements
+///
+/// The HTML returned by the API contains newlines between most block elements. These would
+/// otherwise show up as stray text nodes in the document. Text inside of preformatted blocks is
+/// left untouched and the non-breaking spaces inside of poems are kept.
+pub struct MergeWhitespace;
+
+impl DocumentTransform for MergeWhitespace {
+    fn name(&self) -> &'static str {
+        "merge_whitespace"
+    }
+
+    fn transform(&self, document: Document) -> Document {
+        document.filter_map(|node| match node.data() {
+            Data::Text { .. } if is_preformatted(node) => Some(node.data().clone()),
+            Data::Text { contents }
+                if contents.trim().is_empty() && !keeps_non_breaking_spaces(node) =>
+            {
+                let in_block = node
+                    .parent()
+                    .map(|parent| is_block(parent.data()))
+                    .unwrap_or(true)
+                    || node
+                        .prev()
+                        .map(|prev| is_block(prev.data()))
+                        .unwrap_or(false)
+                    || node
+                        .next()
+                        .map(|next| is_block(next.data()))
+                        .unwrap_or(false);
+                if in_block {
+                    return None;
+                }
+                Some(Data::Text {
+                    contents: " ".to_string(),
+                })
+            }
+            Data::Text { contents } => {
+                let keep_non_breaking = keeps_non_breaking_spaces(node);
+                let mut merged = String::with_capacity(contents.len());
+                let mut last_whitespace = false;
+                for char in contents.chars() {
+                    if char.is_whitespace() && !(keep_non_breaking && char == NON_BREAKING_SPACE) {
+                        if !last_whitespace {
+                            merged.push(' ');
+                        }
+                        last_whitespace = true;
+                        continue;
+                    }
+                    merged.push(char);
+                    last_whitespace = false;
+                }
+                Some(Data::Text { contents: merged })
+            }
+            data => Some(data.clone()),
+        })
+    }
+}
+
+/// Removes spans, paragraphs, divisions and text formatting without any visible text
+pub struct StripEmptyElements;
+
+impl DocumentTransform for StripEmptyElements {
+    fn name(&self) -> &'static str {
+        "strip_empty_elements"
+    }
+
+    fn transform(&self, document: Document) -> Document {
+        document.filter_map(|node| match node.data() {
+            Data::Span
+            | Data::Paragraph
+            | Data::Division
+            | Data::Bold
+            | Data::Italic
+            | Data::Strikethrough
+            | Data::Underline
+            | Data::Small
+            | Data::Mark
+            | Data::Abbreviation { .. }
+                if !has_text(node) && !is_preformatted(node) =>
+            {
+                None
+            }
+            data => Some(data.clone()),
+        })
+    }
+}
+
+/// Removes sections that have no visible text besides their header
+pub struct StripEmptySections;
+
+impl DocumentTransform for StripEmptySections {
+    fn name(&self) -> &'static str {
+        "strip_empty_sections"
+    }
+
+    fn transform(&self, document: Document) -> Document {
+        document.filter_map(|node| match node.data() {
+            Data::Section { .. }
+                if !node
+                    .children()
+                    .filter(|child| !matches!(child.data(), Data::Header { .. }))
+                    .any(has_text) =>
+            {
+                None
+            }
+            data => Some(data.clone()),
+        })
+    }
+}
+
+/// Puts every infobox into a collapsible box that is collapsed at first, with the first heading of
+/// the infobox as its summary. Only the infoboxes parsed as taxoboxes are collapsed, like the
+/// biological taxoboxes and the chemistry infoboxes
+///
+/// Not applied by default, the infoboxes often hold the key facts of an article
+pub struct CollapseInfoboxes;
+
+impl CollapseInfoboxes {
+    /// Summary of the collapsed infobox, the text of its first heading or else of its first row
+    fn summary(infobox: Node<'_>) -> String {
+        infobox
+            .descendants()
+            .find(|node| matches!(node.data(), Data::TaxoboxHeading))
+            .or_else(|| {
+                infobox
+                    .descendants()
+                    .find(|node| matches!(node.data(), Data::TaxoboxRow))
+            })
+            .map(text)
+            .unwrap_or_default()
+    }
+}
+
+impl DocumentTransform for CollapseInfoboxes {
+    fn name(&self) -> &'static str {
+        "collapse_infoboxes"
+    }
+
+    fn transform(&self, document: Document) -> Document {
+        /// Copies the node and its descendants, returns the index of the copy or of the box it
+        /// was put into
+        fn copy_node(
+            nodes: &mut Vec<Raw>,
+            node: Node<'_>,
+            parent: Option<usize>,
+            prev: Option<usize>,
+        ) -> usize {
+            let is_infobox = matches!(node.data(), Data::Taxobox)
+                && !has_ancestor(node, |data| matches!(data, Data::Collapsible { .. }));
+            let (parent, prev, collapsible) = if is_infobox {
+                let summary = CollapseInfoboxes::summary(node);
+                let data = Data::Collapsible {
+                    summary,
+                    collapsed: true,
+                };
+                let collapsible = push_node(nodes, data, parent, prev);
+                (Some(collapsible), None, Some(collapsible))
+            } else {
+                (parent, prev, None)
+            };
+
+            let index = push_node(nodes, node.data().clone(), parent, prev);
+            let mut prev = None;
+            for child in node.children() {
+                prev = Some(copy_node(nodes, child, Some(index), prev));
+            }
+            collapsible.unwrap_or(index)
+        }
+
+        let mut nodes = Vec::new();
+        let mut prev = None;
+        let mut root = document.nth(0);
+        while let Some(node) = root {
+            prev = Some(copy_node(&mut nodes, node, None, prev));
+            root = node.next();
+        }
+
+        Document { nodes }
+    }
+}
+
+/// Removes the notices of maintenance templates, for readers who don't want to be reminded of
+/// the issues of an article
+///
+/// Like [`NumberReferences`], this isn't a [`BuiltinTransform`], it's applied depending on a
+/// setting instead
+pub struct StripMaintenanceNotices;
+
+impl DocumentTransform for StripMaintenanceNotices {
+    fn name(&self) -> &'static str {
+        "strip_maintenance_notices"
+    }
+
+    fn transform(&self, document: Document) -> Document {
+        document.filter_map(|node| match node.data() {
+            Data::MaintenanceNotice => None,
+            data => Some(data.clone()),
+        })
+    }
+}
+
+/// Appends the measurements in the text of the document converted into the system, see
+/// [`convert_units`]. Preformatted text, like code, is left as it is
+///
+/// Like [`NumberReferences`], this isn't a [`BuiltinTransform`], it's applied depending on a
+/// setting instead
+pub struct ConvertUnits(pub UnitSystem);
+
+impl DocumentTransform for ConvertUnits {
+    fn name(&self) -> &'static str {
+        "convert_units"
+    }
+
+    fn transform(&self, document: Document) -> Document {
+        document.filter_map(|node| match node.data() {
+            Data::Text { contents } if !is_preformatted(node) => Some(Data::Text {
+                contents: convert_units(contents, self.0),
+            }),
+            data => Some(data.clone()),
+        })
+    }
+}
+
+/// Numbers the citations and references of the document, see [`ReferenceRegistry`]
+///
+/// Unlike the [`BuiltinTransform`]s, this one can't be disabled and has to run after all other
+/// transforms, so the numbers stay the same no matter which parts of the document were removed
+pub struct NumberReferences;
+
+impl DocumentTransform for NumberReferences {
+    fn name(&self) -> &'static str {
+        "number_references"
+    }
+
+    fn transform(&self, document: Document) -> Document {
+        let registry = ReferenceRegistry::collect(&document);
+        document.filter_map(|node| match node.data() {
+            Data::Citation { note, .. } => Some(Data::Citation {
+                note: note.clone(),
+                number: registry.number(note).unwrap_or_default(),
+            }),
+            Data::Reference { note, .. } => Some(Data::Reference {
+                note: note.clone(),
+                number: registry.number(note).unwrap_or_default(),
+            }),
+            data => Some(data.clone()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CollapseInfoboxes, ConvertUnits, DocumentTransform, MergeWhitespace, NumberReferences,
+        StripEmptyElements, StripEmptySections, StripMaintenanceNotices,
+    };
+    use crate::{
+        document::{Data, Document},
+        parser::{Parser, WikipediaParser},
+        taxobox::text,
+        units::UnitSystem,
+    };
+
+    fn parse(html: &str) -> Document {
+        Document {
+            nodes: WikipediaParser::parse_document(html).nodes(),
+        }
+    }
+
+    #[test]
+    fn merge_whitespace() {
+        let document = MergeWhitespace.transform(parse(
+            "<p>foo\n\n  bar</p>\n<p>baz</p>\n<pre>  x = 1\n    <b>y</b></pre>",
+        ));
+        let texts: Vec<&Data> = document
+            .nodes
+            .iter()
+            .map(|raw| &raw.data)
+            .filter(|data| matches!(data, Data::Text { .. }))
+            .collect();
+
+        assert_eq!(
+            texts,
+            vec![
+                &Data::Text {
+                    contents: "foo bar".to_string()
+                },
+                &Data::Text {
+                    contents: "baz".to_string()
+                },
+                &Data::Text {
+                    contents: "  x = 1\n    ".to_string()
+                },
+                &Data::Text {
+                    contents: "y".to_string()
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn strip_empty_elements() {
+        let document = StripEmptyElements.transform(parse(
+            r#"<p><s>old</s> <del>removed</del> <u>new</u> <small>note</small><small> </small> <mark>this</mark> <span style="text-decoration: line-through;">gone</span></p>"#,
+        ));
+        let formatting: Vec<&Data> = document
+            .nodes
+            .iter()
+            .map(|raw| &raw.data)
+            .filter(|data| {
+                matches!(
+                    data,
+                    Data::Strikethrough | Data::Underline | Data::Small | Data::Mark
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            formatting,
+            vec![
+                &Data::Strikethrough,
+                &Data::Strikethrough,
+                &Data::Underline,
+                &Data::Small,
+                &Data::Mark,
+                &Data::Strikethrough
+            ]
+        );
+    }
+
+    #[test]
+    fn poems() {
+        let document = MergeWhitespace.transform(parse(concat!(
+            r#"<div class="poem"><p>Roses are red,<br/>"#,
+            "\n&nbsp;&nbsp;violets are blue<br/>\n",
+            r#"<span class="mw-poem-indented" style="display: inline-block; "#,
+            r#"margin-inline-start: 2em;">sugar is   sweet.</span></p></div>"#,
+            "<p>a&nbsp;b</p>"
+        )));
+        let data: Vec<&Data> = document
+            .nodes
+            .iter()
+            .map(|raw| &raw.data)
+            .filter(|data| !matches!(data, Data::Unknown | Data::Section { .. } | Data::Paragraph))
+            .collect();
+
+        assert_eq!(
+            data,
+            vec![
+                &Data::Poem,
+                &Data::Text {
+                    contents: "Roses are red,".to_string()
+                },
+                &Data::LineBreak,
+                &Data::Text {
+                    contents: " \u{a0}\u{a0}violets are blue".to_string()
+                },
+                &Data::LineBreak,
+                &Data::PoemIndentation { level: 2 },
+                &Data::Text {
+                    contents: "sugar is sweet.".to_string()
+                },
+                &Data::Text {
+                    contents: "a b".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn abbreviations() {
+        let document = StripEmptyElements.transform(parse(
+            r#"<p><abbr title="North Atlantic Treaty Organization">NATO</abbr> <abbr>etc.</abbr><abbr title="empty"></abbr></p>"#,
+        ));
+        let abbreviations: Vec<&Data> = document
+            .nodes
+            .iter()
+            .map(|raw| &raw.data)
+            .filter(|data| matches!(data, Data::Abbreviation { .. }))
+            .collect();
+
+        assert_eq!(
+            abbreviations,
+            vec![
+                &Data::Abbreviation {
+                    title: Some("North Atlantic Treaty Organization".to_string())
+                },
+                &Data::Abbreviation { title: None }
+            ]
+        );
+    }
+
+    #[test]
+    fn strip_empty_sections() {
+        let document = StripEmptySections.transform(parse(
+            r#"<section data-mw-section-id="1"><h2 id="a">A</h2></section><section data-mw-section-id="2"><h2 id="b">B</h2><p>text</p></section>"#,
+        ));
+        let sections: Vec<&Data> = document
+            .nodes
+            .iter()
+            .map(|raw| &raw.data)
+            .filter(|data| matches!(data, Data::Section { .. }))
+            .collect();
+
+        assert_eq!(sections, vec![&Data::Section { id: 2 }]);
+    }
+
+    #[test]
+    fn number_references() {
+        let document = NumberReferences.transform(parse(
+            r##"<p><a href="./Foo#cite_note-b-2">[2]</a><a href="./Foo#cite_note-a-1">[1]</a><a href="./Foo#cite_note-b-2">[2]</a></p><ol><li id="cite_note-a-1">A</li><li id="cite_note-b-2">B</li><li id="cite_note-c-3">C</li></ol>"##,
+        ));
+        let numbers: Vec<(&str, usize)> = document
+            .nodes
+            .iter()
+            .filter_map(|raw| match raw.data {
+                Data::Citation { ref note, number } | Data::Reference { ref note, number } => {
+                    Some((note.as_str(), number))
+                }
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(
+            numbers,
+            vec![
+                ("cite_note-b-2", 1),
+                ("cite_note-a-1", 2),
+                ("cite_note-b-2", 1),
+                ("cite_note-a-1", 2),
+                ("cite_note-b-2", 1),
+                ("cite_note-c-3", 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn maintenance_notices() {
+        let html = r#"<section data-mw-section-id="0"><table class="box-More_citations_needed plainlinks metadata ambox ambox-content" role="presentation"><tbody><tr><td class="mbox-image"><span>?</span></td><td class="mbox-text"><div class="mbox-text-span">This article <b>needs additional citations for <a rel="mw:WikiLink" href="./Verifiability">verification</a></b>.<span class="hide-when-compact"> Please help improve this article by adding citations.</span> <span class="date-container"><i>(<span class="date">March 2021</span>)</i></span></div></td></tr></tbody></table><table class="box-Multiple_issues ambox ambox-content ambox-multiple_issues"><tbody><tr><td class="mbox-text"><div class="mbox-text-span"><b>This article has multiple issues.</b><div class="mw-collapsible-content"><table class="box-Orphan ambox ambox-style"><tbody><tr><td class="mbox-text"><div class="mbox-text-span">This article is an <b>orphan</b>.</div></td></tr></tbody></table><div class="box-Cleanup ambox ambox-style"><div class="mbox-text"><span class="mbox-text-span">This article <b>may require cleanup</b>.</span></div></div></div></div></td></tr></tbody></table><p>Text</p></section>"#;
+
+        let notices = |document: &Document| -> Vec<String> {
+            document
+                .nodes
+                .iter()
+                .filter(|raw| raw.data == Data::MaintenanceNotice)
+                .filter_map(|raw| document.nth(raw.index))
+                .map(text)
+                .collect()
+        };
+
+        let document = parse(html);
+        assert_eq!(
+            notices(&document),
+            vec![
+                "This article needs additional citations for verification.",
+                "This article is an orphan.",
+                "This article may require cleanup.",
+            ]
+        );
+        assert!(!document.nodes.iter().any(|raw| raw.data == Data::Table));
+
+        let document = StripMaintenanceNotices.transform(document);
+        assert!(notices(&document).is_empty());
+        assert!(document.nodes.iter().any(|raw| raw.data == Data::Paragraph));
+    }
+
+    #[test]
+    fn units() {
+        let html = r#"<section data-mw-section-id="0"><p>The trail is 5 miles long.</p><pre>trail = 5 miles</pre></section>"#;
+        let document = ConvertUnits(UnitSystem::Metric).transform(parse(html));
+        let texts: Vec<&str> = document
+            .nodes
+            .iter()
+            .filter_map(|raw| match raw.data {
+                Data::Text { ref contents } => Some(contents.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert!(texts.contains(&"The trail is 5 miles (8 km) long."));
+        // preformatted text stays as it is
+        assert!(texts.contains(&"trail = 5 miles"));
+    }
+
+    #[test]
+    fn collapse_infoboxes() {
+        let html = r#"<section data-mw-section-id="0"><p>Before</p><table class="infobox biota"><tbody><tr><th colspan="2">Red fox</th></tr><tr><td>Kingdom:</td><td>Animalia</td></tr></tbody></table><p>After</p></section>"#;
+        let document = CollapseInfoboxes.transform(parse(html));
+
+        let collapsible = document
+            .nodes
+            .iter()
+            .find(|raw| matches!(raw.data, Data::Collapsible { .. }))
+            .unwrap();
+        assert_eq!(
+            collapsible.data,
+            Data::Collapsible {
+                summary: "Red fox".to_string(),
+                collapsed: true,
+            }
+        );
+        let infobox = document.nth(collapsible.first_child.unwrap()).unwrap();
+        assert_eq!(infobox.data(), &Data::Taxobox);
+        let rows: Vec<&Data> = infobox.children().map(|row| row.data()).collect();
+        assert_eq!(rows, vec![&Data::TaxoboxHeading, &Data::TaxoboxRow]);
+        assert!(infobox.next().is_none());
+
+        // the box takes the place of the infobox between the paragraphs
+        let collapsible = document.nth(collapsible.index).unwrap();
+        assert_eq!(collapsible.prev().map(text).as_deref(), Some("Before"));
+        assert_eq!(collapsible.next().map(text).as_deref(), Some("After"));
+
+        // collapsing twice doesn't put the box into another one
+        let twice = CollapseInfoboxes.transform(document.clone());
+        assert_eq!(twice, document);
+    }
+}
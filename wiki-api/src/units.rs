@@ -0,0 +1,287 @@
+use serde::{Deserialize, Serialize};
+
+/// The system of units measurements are shown in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnitSystem {
+    Metric,
+    Imperial,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unit {
+    Mile,
+    Kilometre,
+    Foot,
+    Metre,
+    Fahrenheit,
+    Celsius,
+    Pound,
+    Kilogram,
+}
+
+/// Spellings of the units. A spelling comes before the shorter ones it starts with, so the
+/// longest one is found first
+const SPELLINGS: &[(&str, Unit)] = &[
+    ("kilometres", Unit::Kilometre),
+    ("kilometers", Unit::Kilometre),
+    ("kilometre", Unit::Kilometre),
+    ("kilometer", Unit::Kilometre),
+    ("kilograms", Unit::Kilogram),
+    ("kilogram", Unit::Kilogram),
+    ("metres", Unit::Metre),
+    ("meters", Unit::Metre),
+    ("metre", Unit::Metre),
+    ("meter", Unit::Metre),
+    ("miles", Unit::Mile),
+    ("mile", Unit::Mile),
+    ("feet", Unit::Foot),
+    ("foot", Unit::Foot),
+    ("°F", Unit::Fahrenheit),
+    ("°C", Unit::Celsius),
+    ("km", Unit::Kilometre),
+    ("kg", Unit::Kilogram),
+    ("mi", Unit::Mile),
+    ("ft", Unit::Foot),
+    ("lbs", Unit::Pound),
+    ("lb", Unit::Pound),
+    ("m", Unit::Metre),
+];
+
+/// Separators between the numbers of a range, like `5–10 km`
+const RANGE_SEPARATORS: [&str; 3] = ["–", "-", " to "];
+
+/// Spaces allowed between a number and its unit
+const UNIT_SEPARATORS: [char; 2] = [' ', '\u{a0}'];
+
+/// Signs of a negative number, the hyphen and the minus sign
+const MINUS_SIGNS: [char; 2] = ['-', '−'];
+
+/// Symbols of currencies, `$5 m` is money and not a length
+const CURRENCIES: [char; 4] = ['$', '£', '€', '¥'];
+
+impl Unit {
+    fn system(self) -> UnitSystem {
+        match self {
+            Unit::Kilometre | Unit::Metre | Unit::Celsius | Unit::Kilogram => UnitSystem::Metric,
+            Unit::Mile | Unit::Foot | Unit::Fahrenheit | Unit::Pound => UnitSystem::Imperial,
+        }
+    }
+
+    /// Converts the value into the matching unit of the other system, returns the converted
+    /// value and the symbol of that unit
+    fn convert(self, value: f64) -> (f64, &'static str) {
+        match self {
+            Unit::Mile => (value * 1.609344, "km"),
+            Unit::Kilometre => (value / 1.609344, "mi"),
+            Unit::Foot => (value * 0.3048, "m"),
+            Unit::Metre => (value / 0.3048, "ft"),
+            Unit::Fahrenheit => ((value - 32.0) * 5.0 / 9.0, "°C"),
+            Unit::Celsius => (value * 9.0 / 5.0 + 32.0, "°F"),
+            Unit::Pound => (value * 0.45359237, "kg"),
+            Unit::Kilogram => (value / 0.45359237, "lb"),
+        }
+    }
+}
+
+/// A number (or range of numbers) followed by a unit
+#[derive(Debug)]
+struct Measurement {
+    /// Length of the measurement in bytes, from the start of the number to the end of the unit
+    len: usize,
+    unit: Unit,
+    from: f64,
+    to: Option<f64>,
+}
+
+/// Returns the length in bytes of the number at the start of the text, with its digits grouped
+/// by commas and an optional fraction
+fn number_len(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    let mut len = 0;
+    let mut has_point = false;
+    while len < bytes.len() {
+        let digit_follows = bytes.get(len + 1).is_some_and(u8::is_ascii_digit);
+        match bytes[len] {
+            b'0'..=b'9' => len += 1,
+            b',' if !has_point && digit_follows => len += 1,
+            b'.' if !has_point && digit_follows => {
+                has_point = true;
+                len += 1;
+            }
+            _ => break,
+        }
+    }
+    len
+}
+
+/// Parses the number at the start of the text, returns it and its length in bytes
+fn parse_number(text: &str) -> Option<(f64, usize)> {
+    let len = number_len(text);
+    if len == 0 {
+        return None;
+    }
+    let value = text[..len].replace(',', "").parse().ok()?;
+    Some((value, len))
+}
+
+/// Parses the measurement at the start of the text
+fn measurement(text: &str) -> Option<Measurement> {
+    let (from, mut len) = parse_number(text)?;
+
+    let mut to = None;
+    for separator in RANGE_SEPARATORS {
+        if let Some(rest) = text[len..].strip_prefix(separator) {
+            if let Some((value, number_len)) = parse_number(rest) {
+                to = Some(value);
+                len += separator.len() + number_len;
+            }
+            break;
+        }
+    }
+
+    let rest = &text[len..];
+    let unit_start = rest.strip_prefix(UNIT_SEPARATORS).unwrap_or(rest);
+    let (spelling, unit) = SPELLINGS
+        .iter()
+        .find(|(spelling, _)| unit_start.starts_with(spelling))?;
+    // the unit has to be a whole word, `5 mm` isn't in metres
+    if unit_start[spelling.len()..].starts_with(char::is_alphanumeric) {
+        return None;
+    }
+
+    Some(Measurement {
+        len: len + rest.len() - unit_start.len() + spelling.len(),
+        unit: *unit,
+        from,
+        to,
+    })
+}
+
+/// Rounds the converted value to a precision that fits measurements written in text
+fn format_value(value: f64) -> String {
+    let decimals = if value.abs() < 10.0 { 1 } else { 0 };
+    let formatted = format!("{:.*}", decimals, value);
+    match formatted.strip_suffix(".0").unwrap_or(&formatted) {
+        // a value just below zero is rounded to `-0`
+        "-0" => "0".to_string(),
+        rounded => rounded.to_string(),
+    }
+}
+
+/// Appends the value converted into the system to every measurement in the text that is in the
+/// other system, like `5 miles (8 km)`
+///
+/// Measurements next to a parenthesis or followed by another number are left as they are, they
+/// are already converted by the wiki or part of a compound measurement like `5 ft 6 in`
+pub fn convert_units(text: &str, system: UnitSystem) -> String {
+    let mut converted = String::with_capacity(text.len());
+    let mut copied = 0;
+    let mut position = 0;
+
+    while position < text.len() {
+        let rest = &text[position..];
+        let before = &text[..position];
+        let starts_number = rest.starts_with(|c: char| c.is_ascii_digit())
+            && !before.ends_with(|c: char| {
+                c.is_alphanumeric() || c == '.' || c == ',' || CURRENCIES.contains(&c)
+            });
+        let found = if starts_number {
+            measurement(rest)
+        } else {
+            None
+        };
+        let found = match found {
+            Some(found) if found.unit.system() != system => found,
+            Some(found) => {
+                position += found.len;
+                continue;
+            }
+            None => {
+                position += rest.chars().next().map_or(1, char::len_utf8);
+                continue;
+            }
+        };
+
+        let end = position + found.len;
+        let opened = before.trim_end_matches(UNIT_SEPARATORS).ends_with('(');
+        let after = text[end..].trim_start_matches(UNIT_SEPARATORS);
+        if opened || after.starts_with(|c: char| c == '(' || c.is_ascii_digit()) {
+            position = end;
+            continue;
+        }
+
+        // a minus sign only belongs to the number when it isn't a range or hyphen, like `5-6`
+        let negative = before
+            .strip_suffix(MINUS_SIGNS)
+            .is_some_and(|before| !before.ends_with(char::is_alphanumeric));
+        let sign = if negative { -1.0 } else { 1.0 };
+
+        let (from, symbol) = found.unit.convert(sign * found.from);
+        let value = match found.to {
+            Some(to) => format!(
+                "{}–{}",
+                format_value(from),
+                format_value(found.unit.convert(to).0)
+            ),
+            None => format_value(from),
+        };
+
+        converted.push_str(&text[copied..end]);
+        converted.push_str(&format!(" ({} {})", value, symbol));
+        copied = end;
+        position = end;
+    }
+
+    converted.push_str(&text[copied..]);
+    converted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{convert_units, UnitSystem};
+
+    #[test]
+    fn convert_to_metric() {
+        let metric = |text| convert_units(text, UnitSystem::Metric);
+        assert_eq!(
+            metric("The trail is 5 miles long"),
+            "The trail is 5 miles (8 km) long"
+        );
+        assert_eq!(metric("a 1,000 ft cliff"), "a 1,000 ft (305 m) cliff");
+        assert_eq!(metric("It weighs 2.5 lb."), "It weighs 2.5 lb (1.1 kg).");
+        assert_eq!(metric("as cold as -40 °F"), "as cold as -40 °F (-40 °C)");
+        assert_eq!(metric("between 50–68°F"), "between 50–68°F (10–20 °C)");
+        // already metric
+        assert_eq!(metric("5 km away"), "5 km away");
+    }
+
+    #[test]
+    fn convert_to_imperial() {
+        let imperial = |text| convert_units(text, UnitSystem::Imperial);
+        assert_eq!(imperial("it is 100 km away"), "it is 100 km (62 mi) away");
+        assert_eq!(imperial("up to 30 °C"), "up to 30 °C (86 °F)");
+        assert_eq!(imperial("a 3\u{a0}m wall"), "a 3\u{a0}m (9.8 ft) wall");
+        assert_eq!(imperial("10 kilograms"), "10 kilograms (22 lb)");
+    }
+
+    #[test]
+    fn leave_others_alone() {
+        let metric = |text| convert_units(text, UnitSystem::Metric);
+        let imperial = |text| convert_units(text, UnitSystem::Imperial);
+        // converted by the wiki already
+        assert_eq!(metric("5 miles (8.0 km)"), "5 miles (8.0 km)");
+        assert_eq!(imperial("8 km (5 mi)"), "8 km (5 mi)");
+        assert_eq!(imperial("5 miles (8.0 km)"), "5 miles (8.0 km)");
+        // part of a compound measurement
+        assert_eq!(metric("5 ft 6 in tall"), "5 ft 6 in tall");
+        // not units
+        assert_eq!(imperial("the A320 m"), "the A320 m");
+        assert_eq!(imperial("5 mm thick"), "5 mm thick");
+        assert_eq!(imperial("a budget of $5 m"), "a budget of $5 m");
+        assert_eq!(metric("5 milestones"), "5 milestones");
+        // converting twice doesn't add another conversion
+        let once = metric("5 miles");
+        assert_eq!(metric(&once), once);
+    }
+}
@@ -0,0 +1,101 @@
+use anyhow::{Context, Result};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::http::send;
+
+/// Availability API of the Wayback Machine, returns the closest snapshot of an url
+const AVAILABILITY_API: &str = "https://archive.org/wayback/available";
+
+/// An archived copy of a web page in the Wayback Machine
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Url of the snapshot on web.archive.org
+    pub url: String,
+    /// When the snapshot was taken, in the form of `YYYYMMDDhhmmss`
+    pub timestamp: String,
+}
+
+/// Returns the snapshot listed in a response of the availability API, if it's available
+fn parse_availability(response: &serde_json::Value) -> Option<Snapshot> {
+    let closest = response.get("archived_snapshots")?.get("closest")?;
+    if closest.get("available").and_then(|x| x.as_bool()) != Some(true) {
+        return None;
+    }
+
+    Some(Snapshot {
+        // the API returns http urls, even though the archive is served over https
+        url: closest
+            .get("url")?
+            .as_str()?
+            .replacen("http://", "https://", 1),
+        timestamp: closest.get("timestamp")?.as_str()?.to_string(),
+    })
+}
+
+/// Looks up the most recent snapshot of the url in the Wayback Machine. Returns `None` when the
+/// url was never archived
+///
+/// # Error
+///
+/// This function returns an error when one of the following things happens:
+/// - The request to the server could not be made
+/// - The server returned an error
+/// - The returned result could not be interpreted as json
+pub async fn closest_snapshot(url: &str) -> Result<Option<Snapshot>> {
+    let response = send(Client::new().get(AVAILABILITY_API).query(&[("url", url)]))
+        .await
+        .context("failed sending the request")?
+        .error_for_status()
+        .context("the server returned an error")?;
+
+    let res_json: serde_json::Value = serde_json::from_str(&response.text())
+        .context("failed interpreting the response as json")?;
+    debug!("wayback availability of '{}': {}", url, res_json);
+
+    Ok(parse_availability(&res_json))
+}
+
+/// Checks whether the url can still be reached, returning an error when the request fails or the
+/// server responds with an error status
+pub async fn check_link(url: &str) -> Result<()> {
+    Client::new()
+        .get(url)
+        .send()
+        .await
+        .context("failed sending the request")?
+        .error_for_status()
+        .context("the server returned an error")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_availability, Snapshot};
+
+    #[test]
+    fn availability() {
+        let response = serde_json::json!({
+            "url": "example.com",
+            "archived_snapshots": {
+                "closest": {
+                    "status": "200",
+                    "available": true,
+                    "url": "http://web.archive.org/web/20130919044612/http://example.com/",
+                    "timestamp": "20130919044612"
+                }
+            }
+        });
+        assert_eq!(
+            parse_availability(&response),
+            Some(Snapshot {
+                url: "https://web.archive.org/web/20130919044612/http://example.com/".to_string(),
+                timestamp: "20130919044612".to_string(),
+            })
+        );
+
+        let response = serde_json::json!({ "url": "example.invalid", "archived_snapshots": {} });
+        assert_eq!(parse_availability(&response), None);
+    }
+}
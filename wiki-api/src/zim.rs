@@ -0,0 +1,840 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::Instant,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use url::Url;
+
+use crate::{
+    backend::{sections_from_document, BoxFuture, PageBackend, Summary},
+    document::Data,
+    error::ApiError,
+    languages::Language,
+    page::Page,
+    parser::{parse_document, parse_document_with, ParserMode},
+    search::{Namespace, Search, SearchInfo, SearchResult},
+    taxobox::text,
+    Endpoint,
+};
+
+/// First four bytes of every ZIM archive
+const MAGIC_NUMBER: u32 = 72_173_914;
+/// Size of the header at the start of the archive
+const HEADER_SIZE: usize = 80;
+/// Mime type of directory entries that redirect to another entry
+const REDIRECT: u16 = 0xffff;
+/// Index of the main page when the archive has none
+const NO_MAIN_PAGE: u32 = 0xffff_ffff;
+/// How many redirects pointing to redirects are followed
+const MAX_REDIRECTS: usize = 8;
+/// Summaries are cut after this many characters of the introduction
+const MAX_EXTRACT_CHARS: usize = 500;
+
+/// The header of a ZIM archive, with the positions of its lists
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Header {
+    minor_version: u16,
+    entry_count: u32,
+    cluster_count: u32,
+    url_ptr_pos: u64,
+    title_ptr_pos: u64,
+    cluster_ptr_pos: u64,
+    mime_list_pos: u64,
+    main_page: u32,
+    checksum_pos: u64,
+}
+
+fn le_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn le_u32(bytes: &[u8], offset: usize) -> u32 {
+    let mut buf = [0; 4];
+    buf.copy_from_slice(&bytes[offset..offset + 4]);
+    u32::from_le_bytes(buf)
+}
+
+fn le_u64(bytes: &[u8], offset: usize) -> u64 {
+    let mut buf = [0; 8];
+    buf.copy_from_slice(&bytes[offset..offset + 8]);
+    u64::from_le_bytes(buf)
+}
+
+impl Header {
+    fn parse(bytes: &[u8; HEADER_SIZE]) -> Result<Self> {
+        if le_u32(bytes, 0) != MAGIC_NUMBER {
+            bail!("the file isn't a ZIM archive");
+        }
+        let major_version = le_u16(bytes, 4);
+        if !(5..=6).contains(&major_version) {
+            bail!(
+                "version {} of the ZIM format isn't supported",
+                major_version
+            );
+        }
+
+        Ok(Self {
+            minor_version: le_u16(bytes, 6),
+            entry_count: le_u32(bytes, 24),
+            cluster_count: le_u32(bytes, 28),
+            url_ptr_pos: le_u64(bytes, 32),
+            title_ptr_pos: le_u64(bytes, 40),
+            cluster_ptr_pos: le_u64(bytes, 48),
+            mime_list_pos: le_u64(bytes, 56),
+            main_page: le_u32(bytes, 64),
+            checksum_pos: le_u64(bytes, 72),
+        })
+    }
+}
+
+/// What a directory entry stands for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    /// Content stored in a blob of a cluster
+    Content {
+        mime_type: u16,
+        cluster: u32,
+        blob: u32,
+    },
+    /// A redirect to the entry with the index
+    Redirect { target: u32 },
+}
+
+/// An entry of the directory of a ZIM archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectoryEntry {
+    pub namespace: char,
+    pub url: String,
+    /// The title as stored in the archive, empty when it's the same as the url
+    pub title: String,
+    pub kind: EntryKind,
+}
+
+impl DirectoryEntry {
+    /// Reads the entry at the position of the reader
+    fn read(reader: &mut impl BufRead) -> Result<Self> {
+        let mut fixed = [0; 8];
+        reader.read_exact(&mut fixed)?;
+        let mime_type = le_u16(&fixed, 0);
+        let namespace = fixed[3] as char;
+
+        let mut field = [0; 4];
+        reader.read_exact(&mut field)?;
+        let kind = if mime_type == REDIRECT {
+            EntryKind::Redirect {
+                target: u32::from_le_bytes(field),
+            }
+        } else {
+            let cluster = u32::from_le_bytes(field);
+            reader.read_exact(&mut field)?;
+            EntryKind::Content {
+                mime_type,
+                cluster,
+                blob: u32::from_le_bytes(field),
+            }
+        };
+
+        let mut read_string = || -> Result<String> {
+            let mut bytes = Vec::new();
+            reader.read_until(0, &mut bytes)?;
+            if bytes.pop() != Some(0) {
+                bail!("the entry ends unexpectedly");
+            }
+            Ok(String::from_utf8_lossy(&bytes).into_owned())
+        };
+        let url = read_string()?;
+        let title = read_string()?;
+
+        Ok(Self {
+            namespace,
+            url,
+            title,
+            kind,
+        })
+    }
+
+    /// The title the entry is listed with in the title index
+    fn title_key(&self) -> &str {
+        if self.title.is_empty() {
+            &self.url
+        } else {
+            &self.title
+        }
+    }
+
+    /// The title of the article, with spaces instead of underscores
+    pub fn display_title(&self) -> String {
+        self.title_key().replace('_', " ")
+    }
+}
+
+/// Returns the blob with the index from the uncompressed data of a cluster, which starts with
+/// the offsets of its blobs
+fn cluster_blob(data: &[u8], blob: u32, extended: bool) -> Result<Vec<u8>> {
+    let size = if extended { 8 } else { 4 };
+    let offset = |index: usize| -> Result<usize> {
+        let start = index * size;
+        if data.len() < start + size {
+            bail!("the cluster ends unexpectedly");
+        }
+        Ok(if extended {
+            le_u64(data, start) as usize
+        } else {
+            le_u32(data, start) as usize
+        })
+    };
+
+    let blob_count = (offset(0)? / size).saturating_sub(1);
+    let blob = blob as usize;
+    if blob >= blob_count {
+        bail!("the cluster has no blob {}", blob);
+    }
+    let (start, end) = (offset(blob)?, offset(blob + 1)?);
+    data.get(start..end)
+        .map(|bytes| bytes.to_vec())
+        .ok_or_else(|| anyhow!("the blob {} is out of the cluster", blob))
+}
+
+/// A ZIM archive, the format Kiwix stores offline copies of wikis in
+///
+/// Entries are looked up with the url and title indexes of the archive, the full-text index some
+/// archives contain isn't read
+pub struct ZimArchive {
+    path: PathBuf,
+    file: Mutex<BufReader<File>>,
+    header: Header,
+    mime_types: Vec<String>,
+}
+
+impl ZimArchive {
+    /// Opens the archive at the path and reads its header
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let path = path
+            .canonicalize()
+            .with_context(|| format!("unable to find '{}'", path.display()))?;
+        let mut file = BufReader::new(
+            File::open(&path).with_context(|| format!("unable to open '{}'", path.display()))?,
+        );
+
+        let mut bytes = [0; HEADER_SIZE];
+        file.read_exact(&mut bytes)
+            .context("failed reading the header")?;
+        let header = Header::parse(&bytes)?;
+
+        file.seek(SeekFrom::Start(header.mime_list_pos))?;
+        let mut mime_types = Vec::new();
+        loop {
+            let mut bytes = Vec::new();
+            file.read_until(0, &mut bytes)?;
+            bytes.pop();
+            if bytes.is_empty() {
+                break;
+            }
+            mime_types.push(String::from_utf8_lossy(&bytes).into_owned());
+        }
+
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+            header,
+            mime_types,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Number of entries in the archive
+    pub fn entry_count(&self) -> u32 {
+        self.header.entry_count
+    }
+
+    /// The namespace of the articles: `C` since version 6.1 of the format, `A` before
+    pub fn article_namespace(&self) -> char {
+        if self.header.minor_version >= 1 {
+            'C'
+        } else {
+            'A'
+        }
+    }
+
+    /// Index of the entry of the main page, if the archive has one
+    pub fn main_page(&self) -> Option<u32> {
+        Some(self.header.main_page).filter(|index| *index != NO_MAIN_PAGE)
+    }
+
+    pub fn mime_type(&self, entry: &DirectoryEntry) -> Option<&str> {
+        match entry.kind {
+            EntryKind::Content { mime_type, .. } => {
+                self.mime_types.get(mime_type as usize).map(String::as_str)
+            }
+            EntryKind::Redirect { .. } => None,
+        }
+    }
+
+    /// Seeks to the offset and reads from there
+    fn read_at<T>(
+        &self,
+        offset: u64,
+        read: impl FnOnce(&mut BufReader<File>) -> Result<T>,
+    ) -> Result<T> {
+        let mut file = self
+            .file
+            .lock()
+            .map_err(|_| anyhow!("the archive is poisoned"))?;
+        file.seek(SeekFrom::Start(offset))?;
+        read(&mut file)
+    }
+
+    fn read_u64_at(&self, offset: u64) -> Result<u64> {
+        self.read_at(offset, |reader| {
+            let mut bytes = [0; 8];
+            reader.read_exact(&mut bytes)?;
+            Ok(u64::from_le_bytes(bytes))
+        })
+    }
+
+    /// Returns the entry with the index, entries are ordered by their namespace and url
+    pub fn entry(&self, index: u32) -> Result<DirectoryEntry> {
+        if index >= self.header.entry_count {
+            bail!("the archive has no entry {}", index);
+        }
+        let position = self.read_u64_at(self.header.url_ptr_pos + 8 * index as u64)?;
+        self.read_at(position, DirectoryEntry::read)
+            .with_context(|| format!("failed reading the entry {}", index))
+    }
+
+    /// Returns the index of the entry at the position in the title index
+    fn title_entry(&self, position: u32) -> Result<u32> {
+        self.read_at(self.header.title_ptr_pos + 4 * position as u64, |reader| {
+            let mut bytes = [0; 4];
+            reader.read_exact(&mut bytes)?;
+            Ok(u32::from_le_bytes(bytes))
+        })
+    }
+
+    /// Returns the index of the entry with the url, `None` when there is none
+    pub fn find_by_url(&self, namespace: char, url: &str) -> Result<Option<u32>> {
+        let (mut low, mut high) = (0, self.header.entry_count);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            let entry = self.entry(middle)?;
+            match (entry.namespace, entry.url.as_str()).cmp(&(namespace, url)) {
+                std::cmp::Ordering::Less => low = middle + 1,
+                std::cmp::Ordering::Greater => high = middle,
+                std::cmp::Ordering::Equal => return Ok(Some(middle)),
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the indexes of the entries whose title starts with the prefix, in the order of the
+    /// title index. At most `limit` entries are returned
+    pub fn find_by_title_prefix(
+        &self,
+        namespace: char,
+        prefix: &str,
+        limit: usize,
+    ) -> Result<Vec<u32>> {
+        // the title index can't be searched by position when it's missing
+        if self.header.title_ptr_pos == 0 {
+            return Ok(Vec::new());
+        }
+
+        let (mut low, mut high) = (0, self.header.entry_count);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            let entry = self.entry(self.title_entry(middle)?)?;
+            if (entry.namespace, entry.title_key()) < (namespace, prefix) {
+                low = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+
+        let mut indexes = Vec::new();
+        for position in low..self.header.entry_count {
+            if indexes.len() >= limit {
+                break;
+            }
+            let index = self.title_entry(position)?;
+            let entry = self.entry(index)?;
+            if entry.namespace != namespace || !entry.title_key().starts_with(prefix) {
+                break;
+            }
+            indexes.push(index);
+        }
+        Ok(indexes)
+    }
+
+    /// Follows the redirects starting at the entry, returns the entry they end at with its index
+    pub fn resolve(&self, mut index: u32) -> Result<(u32, DirectoryEntry)> {
+        for _ in 0..=MAX_REDIRECTS {
+            let entry = self.entry(index)?;
+            match entry.kind {
+                EntryKind::Redirect { target } => index = target,
+                EntryKind::Content { .. } => return Ok((index, entry)),
+            }
+        }
+        bail!("the entry {} redirects too often", index)
+    }
+
+    /// Reads the content of the entry from its cluster, decompressing it
+    pub fn content(&self, entry: &DirectoryEntry) -> Result<Vec<u8>> {
+        let (cluster, blob) = match entry.kind {
+            EntryKind::Content { cluster, blob, .. } => (cluster, blob),
+            EntryKind::Redirect { .. } => bail!("the entry '{}' is a redirect", entry.url),
+        };
+        if cluster >= self.header.cluster_count {
+            bail!("the archive has no cluster {}", cluster);
+        }
+
+        let pointer = self.header.cluster_ptr_pos + 8 * cluster as u64;
+        let start = self.read_u64_at(pointer)?;
+        let end = if cluster + 1 < self.header.cluster_count {
+            self.read_u64_at(pointer + 8)?
+        } else {
+            self.header.checksum_pos
+        };
+        let raw = self.read_at(start, |reader| {
+            let mut raw = vec![0; end.saturating_sub(start) as usize];
+            reader.read_exact(&mut raw)?;
+            Ok(raw)
+        })?;
+
+        let (info, compressed) = raw
+            .split_first()
+            .ok_or_else(|| anyhow!("the cluster {} is empty", cluster))?;
+        let extended = info & 0x10 != 0;
+        let data = match info & 0x0f {
+            0 | 1 => compressed.to_vec(),
+            4 => {
+                let mut data = Vec::new();
+                xz2::read::XzDecoder::new(compressed)
+                    .read_to_end(&mut data)
+                    .context("failed decompressing the cluster")?;
+                data
+            }
+            5 => {
+                zstd::stream::decode_all(compressed).context("failed decompressing the cluster")?
+            }
+            compression => bail!("the compression {} isn't supported", compression),
+        };
+        cluster_blob(&data, blob, extended)
+    }
+}
+
+/// Reads pages from a ZIM archive instead of a wiki, without any network
+///
+/// The HTML of the articles is parsed with [`ParserMode::Zim`]. Archives don't store the
+/// language links or the revision of a page, so they are always missing
+pub struct ZimBackend {
+    archive: ZimArchive,
+}
+
+impl ZimBackend {
+    pub fn new(archive: ZimArchive) -> Self {
+        Self { archive }
+    }
+
+    /// Opens the archive at the path
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::new(ZimArchive::open(path)?))
+    }
+
+    pub fn archive(&self) -> &ZimArchive {
+        &self.archive
+    }
+
+    /// The `file://` url of the archive, used as the endpoint of its pages and search results
+    pub fn endpoint(&self) -> Endpoint {
+        Url::from_file_path(self.archive.path())
+            .unwrap_or_else(|_| Url::parse("file:///").expect("the url is valid"))
+    }
+
+    /// Returns the index of the article with the title. Links in the archive are relative and
+    /// can be escaped, so the title is tried as it is and decoded
+    fn find_article(&self, title: &str) -> Result<u32> {
+        let namespace = self.archive.article_namespace();
+        let name = title.trim().trim_start_matches("./").replace(' ', "_");
+        if let Some(index) = self.archive.find_by_url(namespace, &name)? {
+            return Ok(index);
+        }
+        // only the percent-encoding is decoded, a plus is part of the title
+        let query = format!("title={}", name.replace('+', "%2B").replace('&', "%26"));
+        let decoded = url::form_urlencoded::parse(query.as_bytes())
+            .next()
+            .map(|(_, title)| title.into_owned())
+            .unwrap_or_default();
+        if decoded != name {
+            if let Some(index) = self.archive.find_by_url(namespace, &decoded)? {
+                return Ok(index);
+            }
+        }
+        Err(anyhow!(ApiError::MissingTitle))
+            .with_context(|| format!("the page '{}' isn't in the archive", title))
+    }
+
+    fn page(&self, title: &str, language: &Language, follow_redirects: bool) -> Result<Page> {
+        let index = self.find_article(title)?;
+        let requested = self.archive.entry(index)?;
+        let (pageid, entry) = match requested.kind {
+            EntryKind::Redirect { target } if !follow_redirects => {
+                let target = self.archive.entry(target)?;
+                return Ok(self.redirect_page(index, &requested, &target, language));
+            }
+            EntryKind::Redirect { .. } => self.archive.resolve(index)?,
+            EntryKind::Content { .. } => (index, requested.clone()),
+        };
+
+        match self.archive.mime_type(&entry) {
+            Some(mime_type) if mime_type.starts_with("text/html") => {}
+            mime_type => bail!(
+                "the entry '{}' isn't an article but '{}'",
+                entry.url,
+                mime_type.unwrap_or_default()
+            ),
+        }
+        let html = String::from_utf8_lossy(&self.archive.content(&entry)?).into_owned();
+
+        let page_title = entry.display_title();
+        let redirected_from = Some(requested.display_title()).filter(|_| pageid != index);
+
+        let is_disambiguation = html.contains("mw:PageProp/disambiguation");
+        let parse_start = Instant::now();
+        let content = parse_document_with(&html, ParserMode::Zim);
+        let parse_time = parse_start.elapsed();
+        let sections = sections_from_document(&content);
+
+        Ok(Page {
+            title: page_title,
+            pageid: pageid as usize,
+            content,
+            language: language.clone(),
+            language_links: None,
+            sections: Some(sections),
+            revision_id: None,
+            is_disambiguation,
+            quality: None,
+            parse_time: Some(parse_time),
+            redirected_from,
+        })
+    }
+
+    /// Builds the page of a redirect, which only links to its target like on the wiki
+    fn redirect_page(
+        &self,
+        index: u32,
+        redirect: &DirectoryEntry,
+        target: &DirectoryEntry,
+        language: &Language,
+    ) -> Page {
+        let target_title = target
+            .display_title()
+            .replace('&', "&amp;")
+            .replace('"', "&quot;")
+            .replace('<', "&lt;");
+        let html = format!(
+            r#"<section data-mw-section-id="0"><div class="redirectMsg"><p>Redirect to:</p><ul class="redirectText"><li><a rel="mw:WikiLink" href="./{}" title="{}">{}</a></li></ul></div></section>"#,
+            target.url, target_title, target_title
+        );
+        let content = parse_document(&html);
+        let sections = sections_from_document(&content);
+
+        Page {
+            title: redirect.display_title(),
+            pageid: index as usize,
+            content,
+            language: language.clone(),
+            language_links: None,
+            sections: Some(sections),
+            revision_id: None,
+            is_disambiguation: false,
+            quality: None,
+            parse_time: None,
+            redirected_from: None,
+        }
+    }
+
+    /// The summary is the first paragraph of the article, archives don't store descriptions
+    fn summary(&self, title: &str) -> Result<Summary> {
+        let page = self.page(title, &Language::default(), true)?;
+        let extract = page
+            .content
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, raw)| raw.data == Data::Paragraph)
+            .filter_map(|(index, _)| page.content.nth(index))
+            .map(text)
+            .find(|text| !text.is_empty())
+            .unwrap_or_default()
+            .chars()
+            .take(MAX_EXTRACT_CHARS)
+            .collect();
+
+        Ok(Summary {
+            title: page.title,
+            description: None,
+            extract,
+        })
+    }
+
+    /// Searches the titles of the articles starting with the query, as it is and with its first
+    /// letter capitalized like the titles of the wiki. Redirects are listed as the article they
+    /// redirect to
+    pub fn search(&self, query: &str, language: &Language, limit: usize) -> Result<Search> {
+        let namespace = self.archive.article_namespace();
+        let query = query.trim();
+        let mut capitalized = query.chars();
+        let capitalized = match capitalized.next() {
+            Some(first) => first.to_uppercase().chain(capitalized).collect(),
+            None => String::new(),
+        };
+
+        // titles that are the same as the url are stored with underscores
+        let mut prefixes = Vec::new();
+        for prefix in [query.to_string(), capitalized] {
+            let underscored = prefix.replace(' ', "_");
+            for prefix in [prefix, underscored] {
+                if !prefix.is_empty() && !prefixes.contains(&prefix) {
+                    prefixes.push(prefix);
+                }
+            }
+        }
+
+        let mut indexes = Vec::new();
+        for prefix in prefixes {
+            indexes.extend(
+                self.archive
+                    .find_by_title_prefix(namespace, &prefix, limit)?,
+            );
+        }
+
+        let endpoint = self.endpoint();
+        let mut results: Vec<SearchResult> = Vec::new();
+        for index in indexes {
+            let (pageid, entry) = self.archive.resolve(index)?;
+            if results.len() >= limit || results.iter().any(|x| x.pageid == pageid as usize) {
+                continue;
+            }
+            if !matches!(self.archive.mime_type(&entry), Some(mime) if mime.starts_with("text/html"))
+            {
+                continue;
+            }
+            results.push(SearchResult {
+                namespace: Namespace::Main,
+                title: entry.display_title(),
+                pageid: pageid as usize,
+                language: language.clone(),
+                endpoint: endpoint.clone(),
+                size: None,
+                wordcount: None,
+                snippet: None,
+                timestamp: None,
+            });
+        }
+
+        Ok(Search {
+            info: SearchInfo {
+                complete: true,
+                total_hits: Some(results.len()),
+                suggestion: None,
+                rewritten_query: None,
+                query: query.to_string(),
+                language: language.clone(),
+            },
+            results,
+            endpoint,
+            continue_offset: None,
+        })
+    }
+}
+
+impl PageBackend for ZimBackend {
+    fn name(&self) -> &'static str {
+        "zim"
+    }
+
+    fn fetch_page<'a>(
+        &'a self,
+        title: &'a str,
+        language: &'a Language,
+    ) -> BoxFuture<'a, Result<Page>> {
+        Box::pin(async move { self.page(title, language, true) })
+    }
+
+    fn fetch_page_without_redirects<'a>(
+        &'a self,
+        title: &'a str,
+        language: &'a Language,
+    ) -> BoxFuture<'a, Result<Page>> {
+        Box::pin(async move { self.page(title, language, false) })
+    }
+
+    fn fetch_summary<'a>(&'a self, title: &'a str) -> BoxFuture<'a, Result<Summary>> {
+        Box::pin(async move { self.summary(title) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EntryKind, ZimBackend};
+    use crate::{document::Data, languages::Language};
+
+    const FOX: &str = r#"<section data-mw-section-id="0"><p>Foxes are mammals.</p></section>"#;
+    const RED_FOX: &str = r##"<section data-mw-section-id="0"><p>The red fox is the largest <a href="Fox" title="Fox">fox</a>, see <a href="https://example.org">this</a> and <a href="#Diet">its diet</a>.</p></section>"##;
+
+    fn entry(kind: EntryKind, url: &str, title: &str) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        match kind {
+            EntryKind::Content {
+                mime_type,
+                cluster,
+                blob,
+            } => {
+                bytes.extend(mime_type.to_le_bytes());
+                bytes.extend([0, b'C', 0, 0, 0, 0]);
+                bytes.extend(cluster.to_le_bytes());
+                bytes.extend(blob.to_le_bytes());
+            }
+            EntryKind::Redirect { target } => {
+                bytes.extend(0xffffu16.to_le_bytes());
+                bytes.extend([0, b'C', 0, 0, 0, 0]);
+                bytes.extend(target.to_le_bytes());
+            }
+        }
+        for text in [url, title] {
+            bytes.extend(text.as_bytes());
+            bytes.push(0);
+        }
+        bytes
+    }
+
+    /// Builds an archive of version 6.1 with two articles and a redirect, in one uncompressed
+    /// cluster
+    fn archive() -> Vec<u8> {
+        let blobs = [FOX, RED_FOX];
+        let mut cluster = vec![1u8];
+        let mut offset = 4 * (blobs.len() + 1);
+        cluster.extend((offset as u32).to_le_bytes());
+        for blob in blobs {
+            offset += blob.len();
+            cluster.extend((offset as u32).to_le_bytes());
+        }
+        for blob in blobs {
+            cluster.extend(blob.as_bytes());
+        }
+
+        let content = |blob| EntryKind::Content {
+            mime_type: 0,
+            cluster: 0,
+            blob,
+        };
+        let entries = [
+            entry(content(0), "Fox", ""),
+            entry(content(1), "Red_fox", "Red fox"),
+            entry(EntryKind::Redirect { target: 1 }, "Vulpes_vulpes", ""),
+        ];
+        let mime_list = b"text/html\0\0";
+
+        let url_ptr_pos = 80 + mime_list.len();
+        let title_ptr_pos = url_ptr_pos + 8 * entries.len();
+        let entries_pos = title_ptr_pos + 4 * entries.len();
+        let cluster_ptr_pos = entries_pos + entries.iter().map(Vec::len).sum::<usize>();
+        let cluster_pos = cluster_ptr_pos + 8;
+        let checksum_pos = cluster_pos + cluster.len();
+
+        let mut bytes = Vec::new();
+        bytes.extend(72_173_914u32.to_le_bytes());
+        bytes.extend(6u16.to_le_bytes());
+        bytes.extend(1u16.to_le_bytes());
+        bytes.extend([0; 16]);
+        bytes.extend((entries.len() as u32).to_le_bytes());
+        bytes.extend(1u32.to_le_bytes());
+        for position in [url_ptr_pos, title_ptr_pos, cluster_ptr_pos, 80] {
+            bytes.extend((position as u64).to_le_bytes());
+        }
+        bytes.extend(1u32.to_le_bytes());
+        bytes.extend(0xffff_ffffu32.to_le_bytes());
+        bytes.extend((checksum_pos as u64).to_le_bytes());
+
+        bytes.extend(mime_list);
+        let mut position = entries_pos;
+        for entry in entries.iter() {
+            bytes.extend((position as u64).to_le_bytes());
+            position += entry.len();
+        }
+        for index in 0..entries.len() as u32 {
+            bytes.extend(index.to_le_bytes());
+        }
+        for entry in entries.iter() {
+            bytes.extend(entry);
+        }
+        bytes.extend((cluster_pos as u64).to_le_bytes());
+        bytes.extend(cluster);
+        bytes.extend([0; 16]);
+        bytes
+    }
+
+    #[test]
+    fn read_archive() {
+        let path = std::env::temp_dir().join(format!("wiki-api-{}.zim", std::process::id()));
+        std::fs::write(&path, archive()).unwrap();
+        let backend = ZimBackend::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let archive = backend.archive();
+        assert_eq!(archive.entry_count(), 3);
+        assert_eq!(archive.main_page(), Some(1));
+        assert_eq!(archive.find_by_url('C', "Red_fox").unwrap(), Some(1));
+        assert_eq!(archive.find_by_url('C', "Wolf").unwrap(), None);
+
+        let language = Language::default();
+        let page = backend.page("Vulpes vulpes", &language, true).unwrap();
+        assert_eq!(page.title, "Red fox");
+        assert_eq!(page.redirected_from.as_deref(), Some("Vulpes vulpes"));
+        let links: Vec<&Data> = page
+            .content
+            .nodes
+            .iter()
+            .map(|raw| &raw.data)
+            .filter(|data| matches!(data, Data::WikiLink { .. }))
+            .collect();
+        assert_eq!(
+            links,
+            vec![&Data::WikiLink {
+                href: "Fox".to_string(),
+                title: Some("Fox".to_string())
+            }]
+        );
+
+        let redirect = backend.page("Vulpes_vulpes", &language, false).unwrap();
+        assert!(redirect
+            .content
+            .nodes
+            .iter()
+            .any(|raw| raw.data == Data::RedirectMessage));
+        assert!(backend.page("Wolf", &language, true).is_err());
+
+        let summary = backend.summary("Fox").unwrap();
+        assert_eq!(summary.extract, "Foxes are mammals.");
+
+        let search = backend.search("vulpes v", &language, 10).unwrap();
+        let titles: Vec<&str> = search.results.iter().map(|x| x.title.as_str()).collect();
+        assert_eq!(titles, vec!["Red fox"]);
+        let search = backend.search("red", &language, 10).unwrap();
+        assert_eq!(search.results.len(), 1);
+        assert!(backend
+            .search("wolf", &language, 10)
+            .unwrap()
+            .results
+            .is_empty());
+    }
+}